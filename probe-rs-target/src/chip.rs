@@ -1,5 +1,5 @@
 use super::memory::MemoryRegion;
-use crate::{serialize::hex_option, CoreType};
+use crate::{serialize::hex_option, CoreType, PackSequences};
 use serde::{Deserialize, Serialize};
 
 /// Represents a DAP scan chain element.
@@ -58,6 +58,16 @@ pub struct Chip {
     /// ref: `<https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/sdf_pg.html#sdf_element_scanchain>`
     #[serde(default)]
     pub scan_chain: Option<Vec<ScanChainElement>>,
+    /// The debug sequences of the chip, taken from its CMSIS-Pack.
+    ///
+    /// They are only used if probe-rs has no built-in sequences for the chip.
+    #[serde(default)]
+    pub debug_sequences: Option<PackSequences>,
+    /// Values which identify a RISC-V chip during auto-detection.
+    ///
+    /// This is the RISC-V counterpart of the `part` field.
+    #[serde(default)]
+    pub riscv_identification: Option<RiscvChipIdentification>,
 }
 
 impl Chip {
@@ -77,10 +87,34 @@ impl Chip {
             flash_algorithms: vec![],
             rtt_scan_ranges: None,
             scan_chain: Some(vec![]),
+            debug_sequences: None,
+            riscv_identification: None,
         }
     }
 }
 
+/// Values read from a RISC-V chip, which identify it during auto-detection.
+///
+/// Fields which are not specified match any value read from the chip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiscvChipIdentification {
+    /// The JTAG IDCODE of the debug transport module.
+    ///
+    /// The version field in bits 31 to 28 is ignored, so that
+    /// all revisions of a chip are matched.
+    #[serde(serialize_with = "hex_option")]
+    pub jtag_idcode: Option<u32>,
+    /// The value of the `mvendorid` CSR, the JEDEC manufacturer ID of the core.
+    #[serde(serialize_with = "hex_option")]
+    pub mvendorid: Option<u32>,
+    /// The value of the `marchid` CSR, the microarchitecture ID of the core.
+    #[serde(serialize_with = "hex_option")]
+    pub marchid: Option<u32>,
+    /// The value of the `mimpid` CSR, the implementation version of the core.
+    #[serde(serialize_with = "hex_option")]
+    pub mimpid: Option<u32>,
+}
+
 /// An individual core inside a chip
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Core {
@@ -103,6 +137,8 @@ pub enum CoreAccessOptions {
     Arm(ArmCoreAccessOptions),
     /// Riscv specific options
     Riscv(RiscvCoreAccessOptions),
+    /// Mips specific options
+    Mips(MipsCoreAccessOptions),
 }
 
 /// The data required to access an ARM core
@@ -123,8 +159,16 @@ pub struct ArmCoreAccessOptions {
 }
 
 /// The data required to access a Risc-V core
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RiscvCoreAccessOptions {
+    /// The index of the hart in the debug module, which is used to select the core.
+    #[serde(default)]
+    pub hart_id: u32,
+}
+
+/// The data required to access a MIPS core
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RiscvCoreAccessOptions {}
+pub struct MipsCoreAccessOptions {}
 
 /// Helper function that interates the scan chain and returns a vector of all of
 /// the ir_lengths of the scan chain elements.
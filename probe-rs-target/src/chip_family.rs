@@ -42,6 +42,8 @@ pub enum CoreType {
     Armv8m,
     /// RISC-V
     Riscv,
+    /// MIPS32: M4K, M14K, microAptiv (PIC32)
+    Mips32,
 }
 
 impl CoreType {
@@ -61,6 +63,8 @@ pub enum Architecture {
     Arm,
     /// A RISC-V core.
     Riscv,
+    /// A MIPS core.
+    Mips,
 }
 
 impl CoreType {
@@ -68,13 +72,14 @@ impl CoreType {
     pub fn architecture(&self) -> Architecture {
         match self {
             CoreType::Riscv => Architecture::Riscv,
+            CoreType::Mips32 => Architecture::Mips,
             _ => Architecture::Arm,
         }
     }
 }
 
 /// Instruction set used by a core
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InstructionSet {
     /// ARM Thumb 2 instruction set
     Thumb2,
@@ -86,6 +91,14 @@ pub enum InstructionSet {
     RV32,
     /// RISC-V 32-bit compressed instruction sets (RV32C) - covers all ISA variants that allow compressed 16-bit instructions.
     RV32C,
+    /// RISC-V 64-bit uncompressed instruction sets (RV64) - covers all ISA variants that use 32-bit instructions.
+    RV64,
+    /// RISC-V 64-bit compressed instruction sets (RV64C) - covers all ISA variants that allow compressed 16-bit instructions.
+    RV64C,
+    /// MIPS32 instruction set, all instructions are 32-bit wide.
+    Mips32,
+    /// microMIPS instruction set, which mixes 16-bit and 32-bit instructions.
+    MicroMips,
 }
 
 impl InstructionSet {
@@ -100,6 +113,10 @@ impl InstructionSet {
             InstructionSet::A64 => 4,
             InstructionSet::RV32 => 4,
             InstructionSet::RV32C => 2,
+            InstructionSet::RV64 => 4,
+            InstructionSet::RV64C => 2,
+            InstructionSet::Mips32 => 4,
+            InstructionSet::MicroMips => 2,
         }
     }
     /// Get the maximum instruction size in bytes. All supported architectures have a maximum instruction size of 4 bytes.
@@ -215,13 +232,32 @@ impl ChipFamily {
                             return Err(format!("Core {} requires setting cti_base", core.name));
                         }
                     }
-                    CoreAccessOptions::Riscv(_) => {
+                    CoreAccessOptions::Riscv(options) => {
                         if core.core_type != CoreType::Riscv {
                             return Err(format!(
                                 "Riscv options don't match core type {:?} on core {}",
                                 core.core_type, core.name
                             ));
                         }
+
+                        // Each core is a separate hart of the debug module.
+                        if variant.cores.iter().any(|other| {
+                            other.name != core.name
+                                && matches!(&other.core_access_options, CoreAccessOptions::Riscv(other_options) if other_options.hart_id == options.hart_id)
+                        }) {
+                            return Err(format!(
+                                "Core {} uses hart {}, which is already used by another core",
+                                core.name, options.hart_id
+                            ));
+                        }
+                    }
+                    CoreAccessOptions::Mips(_) => {
+                        if core.core_type != CoreType::Mips32 {
+                            return Err(format!(
+                                "Mips options don't match core type {:?} on core {}",
+                                core.core_type, core.name
+                            ));
+                        }
                     }
                 }
             }
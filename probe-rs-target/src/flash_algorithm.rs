@@ -40,6 +40,38 @@ pub struct RawFlashAlgorithm {
     /// Address of the `EraseAll()` entry point. Optional.
     #[serde(serialize_with = "hex_option")]
     pub pc_erase_all: Option<u64>,
+    /// Address of the `Verify()` entry point. Optional.
+    ///
+    /// If this is set, the flash contents are verified by the algorithm
+    /// instead of being read back.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_verify: Option<u64>,
+    /// Address of the `BlankCheck(adr, sz, pat)` entry point. Optional.
+    ///
+    /// Returns 0 if all `sz` bytes starting at `adr` contain the pattern `pat`.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_blank_check: Option<u64>,
+    /// Address of the `ReadOptionBytes(buf, sz)` entry point. Optional.
+    ///
+    /// Copies `sz` bytes of option bytes (or fuses) into the buffer `buf`, and returns 0 on success.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_read_option_bytes: Option<u64>,
+    /// Address of the `WriteOptionBytes(buf, sz)` entry point. Optional.
+    ///
+    /// Programs the `sz` bytes in the buffer `buf` as option bytes (or fuses), and returns 0 on success.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_write_option_bytes: Option<u64>,
+    /// Address of the `Protect(level)` entry point. Optional.
+    ///
+    /// Enables readout protection with the device specific `level`, and returns 0 on success.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_protect: Option<u64>,
+    /// Address of the `Unprotect()` entry point. Optional.
+    ///
+    /// Disables readout protection, and returns 0 on success. On most devices,
+    /// this erases the entire flash.
+    #[serde(serialize_with = "hex_option")]
+    pub pc_unprotect: Option<u64>,
     /// The offset from the start of RAM to the data section.
     #[serde(serialize_with = "hex_u_int")]
     pub data_section_offset: u64,
@@ -60,6 +92,10 @@ pub struct RawFlashAlgorithm {
     /// Increase this value if you're concerned about stack
     /// overruns during flashing.
     pub stack_size: Option<u32>,
+    /// The size of the option bytes, in bytes.
+    ///
+    /// This is required to read or write the option bytes.
+    pub option_bytes_size: Option<u32>,
 }
 
 pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
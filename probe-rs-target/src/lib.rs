@@ -16,11 +16,12 @@ mod chip_family;
 mod flash_algorithm;
 mod flash_properties;
 mod memory;
+mod pack_sequence;
 pub(crate) mod serialize;
 
 pub use chip::{
-    get_ir_lengths, ArmCoreAccessOptions, Chip, Core, CoreAccessOptions, RiscvCoreAccessOptions,
-    ScanChainElement,
+    get_ir_lengths, ArmCoreAccessOptions, Chip, Core, CoreAccessOptions, MipsCoreAccessOptions,
+    RiscvChipIdentification, RiscvCoreAccessOptions, ScanChainElement,
 };
 pub use chip_family::{
     Architecture, ChipFamily, CoreType, InstructionSet, TargetDescriptionSource,
@@ -31,3 +32,6 @@ pub use memory::{
     GenericRegion, MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion, SectorDescription,
     SectorInfo,
 };
+pub use pack_sequence::{
+    PackSequence, PackSequenceBlock, PackSequenceControl, PackSequenceElement, PackSequences,
+};
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// The debug sequences of a chip, as described in its CMSIS-Pack.
+///
+/// Debug sequences customize how a debugger connects to, resets and disconnects from
+/// a chip. They are written in a small C-like language, which is interpreted at runtime.
+///
+/// ref: `<https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/pdsc_family_pg.html#element_sequences>`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSequences {
+    /// The content of the `<debugvars>` element, which declares and
+    /// initializes the debug access variables used by the sequences.
+    pub debug_vars: Option<String>,
+    /// The sequences which replace the predefined sequences of the
+    /// debugger, or which are called from other sequences.
+    #[serde(default)]
+    pub sequences: Vec<PackSequence>,
+}
+
+impl PackSequences {
+    /// Get the sequence with the given name, if the chip defines it.
+    ///
+    /// Sequences for a specific processor are ignored, as they only
+    /// apply to one core of a multi-core chip.
+    pub fn get(&self, name: &str) -> Option<&PackSequence> {
+        self.sequences
+            .iter()
+            .find(|sequence| sequence.name == name && sequence.pname.is_none())
+    }
+}
+
+/// A single named debug sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSequence {
+    /// The name of the sequence, e.g. `ResetSystem`.
+    pub name: String,
+    /// The processor this sequence applies to, for multi-core chips.
+    pub pname: Option<String>,
+    /// A disabled sequence does nothing, instead of falling back to the default implementation.
+    #[serde(default)]
+    pub disable: bool,
+    /// The blocks and control elements of the sequence, in execution order.
+    #[serde(default)]
+    pub body: Vec<PackSequenceElement>,
+}
+
+/// An element of a [`PackSequence`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackSequenceElement {
+    /// A block of statements.
+    Block(PackSequenceBlock),
+    /// A conditional or loop around further elements.
+    Control(PackSequenceControl),
+}
+
+/// A block of statements, executed one after the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSequenceBlock {
+    /// The statements of this block should be executed without interruption.
+    #[serde(default)]
+    pub atomic: bool,
+    /// The statements, separated by semicolons.
+    pub code: String,
+}
+
+/// Executes its body conditionally, in a loop, or both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSequenceControl {
+    /// The body is only executed if this expression is not zero.
+    pub if_condition: Option<String>,
+    /// The body is executed repeatedly, as long as this expression is not zero.
+    pub while_condition: Option<String>,
+    /// The maximum time in microseconds the loop may run before the sequence fails.
+    /// If not specified or zero, probe-rs uses a limit of 10 seconds.
+    pub timeout: Option<u64>,
+    /// The nested blocks and control elements.
+    #[serde(default)]
+    pub body: Vec<PackSequenceElement>,
+}
@@ -0,0 +1,259 @@
+//! Module for using the ETM.
+//!
+//! ETM = Embedded Trace Macrocell
+//!
+//! The ETM traces the instructions executed by a core. This module supports the ETMv3 found in
+//! the Cortex-M3 and Cortex-M4, the very similar PTM of the Cortex-A9, and the ETMv4 found in
+//! the Cortex-M7 and the ARMv8-M cores.
+
+use super::super::memory::romtable::CoresightComponent;
+use crate::architecture::arm::{ArmError, ArmProbeInterface};
+
+/// The trace ID (ATID) used for the instruction trace of the ETM.
+pub(crate) const ETM_TRACE_ID: u8 = 0x10;
+
+/// Number of polls of a status register before giving up.
+const STATUS_POLLS: usize = 100;
+
+const REGISTER_OFFSET_ACCESS: u32 = 0xFB0;
+const REGISTER_OFFSET_DEVARCH: u32 = 0xFBC;
+
+// ETMv3 and PTM registers
+const REGISTER_OFFSET_ETMCR: u32 = 0x000;
+const REGISTER_OFFSET_ETMTRIGGER: u32 = 0x008;
+const REGISTER_OFFSET_ETMSR: u32 = 0x010;
+const REGISTER_OFFSET_ETMTSSCR: u32 = 0x018;
+const REGISTER_OFFSET_ETMTEEVR: u32 = 0x020;
+const REGISTER_OFFSET_ETMTECR1: u32 = 0x024;
+const REGISTER_OFFSET_ETMIDR: u32 = 0x1E4;
+const REGISTER_OFFSET_ETMTRACEIDR: u32 = 0x200;
+const REGISTER_OFFSET_ETMOSLAR: u32 = 0x300;
+
+const ETMCR_POWER_DOWN: u32 = 1 << 0;
+const ETMCR_BRANCH_OUTPUT: u32 = 1 << 8;
+const ETMCR_PROGRAMMING: u32 = 1 << 10;
+const ETMCR_PORT_SELECTION: u32 = 1 << 11;
+const ETMSR_PROGRAMMING: u32 = 1 << 1;
+
+/// The resource which is always active, used with function `A` in an event.
+const ETM_EVENT_ALWAYS: u32 = 0x6F;
+/// An event which is never active (`NOT(A)` with the always active resource).
+const ETM_EVENT_NEVER: u32 = 0x406F;
+
+// ETMv4 registers
+const REGISTER_OFFSET_TRCPRGCTLR: u32 = 0x004;
+const REGISTER_OFFSET_TRCSTATR: u32 = 0x00C;
+const REGISTER_OFFSET_TRCCONFIGR: u32 = 0x010;
+const REGISTER_OFFSET_TRCEVENTCTL0R: u32 = 0x020;
+const REGISTER_OFFSET_TRCEVENTCTL1R: u32 = 0x024;
+const REGISTER_OFFSET_TRCSTALLCTLR: u32 = 0x02C;
+const REGISTER_OFFSET_TRCTSCTLR: u32 = 0x030;
+const REGISTER_OFFSET_TRCSYNCPR: u32 = 0x034;
+const REGISTER_OFFSET_TRCBBCTLR: u32 = 0x03C;
+const REGISTER_OFFSET_TRCTRACEIDR: u32 = 0x040;
+const REGISTER_OFFSET_TRCVICTLR: u32 = 0x080;
+const REGISTER_OFFSET_TRCVIIECTLR: u32 = 0x084;
+const REGISTER_OFFSET_TRCVISSCTLR: u32 = 0x088;
+const REGISTER_OFFSET_TRCOSLAR: u32 = 0x300;
+
+const TRCCONFIGR_BB: u32 = 1 << 3;
+const TRCSTATR_IDLE: u32 = 1 << 0;
+/// TRCVICTLR: the always active resource selects the ViewInst event, and the start/stop logic
+/// starts in the started state.
+const TRCVICTLR_ALWAYS: u32 = (1 << 9) | 0x01;
+
+/// The architecture implemented by an ETM.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EtmArchitecture {
+    /// ETMv3, used by the Cortex-M3 and Cortex-M4.
+    EtmV3,
+    /// Program Flow Trace, implemented by a PTM.
+    Ptm,
+    /// ETMv4, used by the Cortex-M7 and ARMv8-M cores.
+    EtmV4,
+}
+
+/// An interface to control the ETM (Embedded Trace Macrocell) of a core.
+pub struct Etm<'a> {
+    component: &'a CoresightComponent,
+    interface: &'a mut dyn ArmProbeInterface,
+}
+
+impl<'a> Etm<'a> {
+    /// Create a new ETM interface from a probe and a ROM table component.
+    pub fn new(
+        interface: &'a mut dyn ArmProbeInterface,
+        component: &'a CoresightComponent,
+    ) -> Self {
+        Etm {
+            interface,
+            component,
+        }
+    }
+
+    /// Determine the architecture of the ETM.
+    ///
+    /// ETMv4 is identified by the DEVARCH register, ETMv3 and PTM by the major architecture
+    /// version in ETMIDR.
+    pub fn architecture(&mut self) -> Result<EtmArchitecture, ArmError> {
+        let devarch = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_DEVARCH)?;
+
+        let architect = devarch >> 21;
+        let present = devarch & (1 << 20) != 0;
+        let archid = devarch & 0xFFFF;
+
+        if present && architect == 0x23B && archid == 0x4A13 {
+            return Ok(EtmArchitecture::EtmV4);
+        }
+
+        let etmidr = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_ETMIDR)?;
+
+        match (etmidr >> 8) & 0xF {
+            2 => Ok(EtmArchitecture::EtmV3),
+            3 => Ok(EtmArchitecture::Ptm),
+            major => Err(ArmError::Other(anyhow::anyhow!(
+                "Unsupported ETM architecture version {}",
+                major + 1
+            ))),
+        }
+    }
+
+    /// Unlock the ETM registers for writing.
+    pub fn unlock(&mut self) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ACCESS, 0xC5AC_CE55)
+    }
+
+    /// Enable tracing of all executed instructions, using `trace_id` as the trace source ID.
+    ///
+    /// Branch broadcasting is enabled, so the target address of every taken branch is
+    /// traced. This allows decoding the trace with only the size of the executed instructions.
+    pub fn enable_instruction_trace(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        self.unlock()?;
+
+        match self.architecture()? {
+            EtmArchitecture::EtmV3 | EtmArchitecture::Ptm => self.enable_v3(trace_id),
+            EtmArchitecture::EtmV4 => self.enable_v4(trace_id),
+        }
+    }
+
+    /// Disable the instruction trace.
+    pub fn disable(&mut self) -> Result<(), ArmError> {
+        self.unlock()?;
+
+        match self.architecture()? {
+            EtmArchitecture::EtmV3 | EtmArchitecture::Ptm => {
+                self.enter_programming_v3()?;
+                self.component.write_reg(
+                    self.interface,
+                    REGISTER_OFFSET_ETMCR,
+                    ETMCR_PROGRAMMING | ETMCR_POWER_DOWN,
+                )
+            }
+            EtmArchitecture::EtmV4 => {
+                self.component
+                    .write_reg(self.interface, REGISTER_OFFSET_TRCPRGCTLR, 0)?;
+                self.wait_for_v4(TRCSTATR_IDLE, TRCSTATR_IDLE)
+            }
+        }
+    }
+
+    fn enter_programming_v3(&mut self) -> Result<(), ArmError> {
+        // Clearing the OS lock is only required for the PTM, the register is reserved on ETMv3.
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMOSLAR, 0)?;
+
+        let control = self
+            .component
+            .read_reg(self.interface, REGISTER_OFFSET_ETMCR)?;
+        self.component.write_reg(
+            self.interface,
+            REGISTER_OFFSET_ETMCR,
+            (control & !ETMCR_POWER_DOWN) | ETMCR_PROGRAMMING,
+        )?;
+
+        self.wait_for_v3(ETMSR_PROGRAMMING, ETMSR_PROGRAMMING)
+    }
+
+    fn enable_v3(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        self.enter_programming_v3()?;
+
+        let control = ETMCR_BRANCH_OUTPUT | ETMCR_PORT_SELECTION;
+
+        self.component.write_reg(
+            self.interface,
+            REGISTER_OFFSET_ETMCR,
+            control | ETMCR_PROGRAMMING,
+        )?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMTRACEIDR, trace_id as u32)?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMTRIGGER, ETM_EVENT_NEVER)?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMTEEVR, ETM_EVENT_ALWAYS)?;
+        // Exclude no address ranges, which traces everything.
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMTECR1, 1 << 24)?;
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMTSSCR, 0)?;
+
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_ETMCR, control)?;
+        self.wait_for_v3(ETMSR_PROGRAMMING, 0)
+    }
+
+    fn enable_v4(&mut self, trace_id: u8) -> Result<(), ArmError> {
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_TRCOSLAR, 0)?;
+
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_TRCPRGCTLR, 0)?;
+        self.wait_for_v4(TRCSTATR_IDLE, TRCSTATR_IDLE)?;
+
+        let registers = [
+            (REGISTER_OFFSET_TRCCONFIGR, TRCCONFIGR_BB),
+            (REGISTER_OFFSET_TRCEVENTCTL0R, 0),
+            (REGISTER_OFFSET_TRCEVENTCTL1R, 0),
+            (REGISTER_OFFSET_TRCSTALLCTLR, 0),
+            (REGISTER_OFFSET_TRCTSCTLR, 0),
+            // Trace synchronization every 2^12 bytes.
+            (REGISTER_OFFSET_TRCSYNCPR, 0xC),
+            // Broadcast branches in the whole address space.
+            (REGISTER_OFFSET_TRCBBCTLR, 0),
+            (REGISTER_OFFSET_TRCTRACEIDR, trace_id as u32),
+            (REGISTER_OFFSET_TRCVICTLR, TRCVICTLR_ALWAYS),
+            (REGISTER_OFFSET_TRCVIIECTLR, 0),
+            (REGISTER_OFFSET_TRCVISSCTLR, 0),
+        ];
+
+        for (offset, value) in registers {
+            self.component.write_reg(self.interface, offset, value)?;
+        }
+
+        self.component
+            .write_reg(self.interface, REGISTER_OFFSET_TRCPRGCTLR, 1)?;
+        self.wait_for_v4(TRCSTATR_IDLE, 0)
+    }
+
+    fn wait_for_v3(&mut self, mask: u32, value: u32) -> Result<(), ArmError> {
+        self.wait_for(REGISTER_OFFSET_ETMSR, mask, value)
+    }
+
+    fn wait_for_v4(&mut self, mask: u32, value: u32) -> Result<(), ArmError> {
+        self.wait_for(REGISTER_OFFSET_TRCSTATR, mask, value)
+    }
+
+    fn wait_for(&mut self, offset: u32, mask: u32, value: u32) -> Result<(), ArmError> {
+        for _ in 0..STATUS_POLLS {
+            if self.component.read_reg(self.interface, offset)? & mask == value {
+                return Ok(());
+            }
+        }
+
+        Err(ArmError::Timeout)
+    }
+}
@@ -1,6 +1,7 @@
 //! Types and functions for interacting with CoreSight Components
 
 mod dwt;
+mod etm;
 mod itm;
 mod scs;
 mod swo;
@@ -8,7 +9,7 @@ mod tmc;
 mod tpiu;
 mod trace_funnel;
 
-use super::ap::{GenericAp, MemoryAp};
+use super::ap::{AccessPort, GenericAp, MemoryAp};
 use super::memory::romtable::{CoresightComponent, PeripheralType, RomTableError};
 use super::memory::Component;
 use super::ArmError;
@@ -16,12 +17,14 @@ use super::{ApAddress, ApInformation, DpAddress, MemoryApInformation};
 use crate::architecture::arm::core::armv6m::Demcr;
 use crate::architecture::arm::{ArmProbeInterface, SwoConfig, SwoMode};
 use crate::{Core, Error, MemoryInterface, MemoryMappedRegister};
+use std::time::Duration;
 
 pub use self::itm::Itm;
 pub use dwt::Dwt;
+pub use etm::{Etm, EtmArchitecture};
 pub use scs::Scs;
 pub use swo::Swo;
-pub use tmc::TraceMemoryController;
+pub use tmc::{Deformatter, TraceMemoryController};
 pub use tpiu::Tpiu;
 pub use trace_funnel::TraceFunnel;
 
@@ -41,6 +44,30 @@ pub enum TraceSink {
     TraceMemory,
 }
 
+/// Specifies the trace source of the data returned by [`Session::read_trace_data`].
+///
+/// [`Session::read_trace_data`]: crate::Session::read_trace_data
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum TraceSource {
+    /// Software and hardware trace packets of the ITM.
+    #[default]
+    Itm,
+
+    /// Instruction trace packets of the ETM.
+    Etm,
+}
+
+impl TraceSource {
+    /// The trace ID (ATID) of the trace source.
+    pub fn trace_id(&self) -> u8 {
+        match self {
+            // See Itm::tx_enable()
+            TraceSource::Itm => 13,
+            TraceSource::Etm => etm::ETM_TRACE_ID,
+        }
+    }
+}
+
 /// An error when operating a core ROM table component occurred.
 #[derive(thiserror::Error, Debug)]
 pub enum ComponentError {
@@ -259,7 +286,9 @@ pub(crate) fn setup_tracing(
 
             // Clear out the TMC FIFO before initiating the capture.
             tmc.disable_capture()?;
-            while !tmc.ready()? {}
+            while !tmc.ready()? {
+                std::thread::sleep(Duration::from_millis(1));
+            }
 
             // Configure the TMC for software-polled mode, as we will read out data using the debug
             // interface.
@@ -272,11 +301,86 @@ pub(crate) fn setup_tracing(
     Ok(())
 }
 
+/// Enables the instruction trace of the ETM of a core, and returns the architecture of the ETM.
+///
+/// Expects to be given a list of all ROM table `components` as the second argument.
+/// The ETM of the core is found on the access port `ap` of the core. If `ap` is shared by
+/// several cores, `core_position` is the position of the core among them, which selects the
+/// ETM in the order of the ROM table.
+pub(crate) fn setup_instruction_trace(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+    ap: MemoryAp,
+    core_position: usize,
+    cores_on_ap: usize,
+) -> Result<EtmArchitecture, ArmError> {
+    let component = find_etm(components, ap, core_position, cores_on_ap)?;
+
+    let mut etm = Etm::new(interface, component);
+    etm.enable_instruction_trace(etm::ETM_TRACE_ID)?;
+    etm.architecture()
+}
+
+/// Disables the instruction trace set up by [`setup_instruction_trace`], and stops the
+/// capture of trace data if the trace memory is used as the `sink`.
+pub(crate) fn disable_instruction_trace(
+    interface: &mut dyn ArmProbeInterface,
+    components: &[CoresightComponent],
+    ap: MemoryAp,
+    core_position: usize,
+    cores_on_ap: usize,
+    sink: Option<&TraceSink>,
+) -> Result<(), Error> {
+    let component = find_etm(components, ap, core_position, cores_on_ap)?;
+    Etm::new(interface, component).disable()?;
+
+    if let Some(TraceSink::TraceMemory) = sink {
+        TraceMemoryController::new(interface, find_component(components, PeripheralType::Tmc)?)
+            .disable_capture()?;
+    }
+
+    Ok(())
+}
+
+/// Find the ETM of a core, see [`setup_instruction_trace`].
+fn find_etm(
+    components: &[CoresightComponent],
+    ap: MemoryAp,
+    core_position: usize,
+    cores_on_ap: usize,
+) -> Result<&CoresightComponent, ArmError> {
+    let etms: Vec<&CoresightComponent> = components
+        .iter()
+        .flat_map(CoresightComponent::iter)
+        .filter(|component| {
+            component.ap.ap_address() == ap.ap_address()
+                && component
+                    .component
+                    .id()
+                    .peripheral_id()
+                    .is_of_type(PeripheralType::Etm)
+        })
+        .collect();
+
+    match etms.as_slice() {
+        [] => Err(RomTableError::ComponentNotFound(PeripheralType::Etm).into()),
+        [etm] => Ok(etm),
+        etms if etms.len() == cores_on_ap => Ok(etms[core_position]),
+        etms => Err(ArmError::Other(anyhow::anyhow!(
+            "Found {} ETMs for {} cores on {:?}, the ETM of the core is unknown",
+            etms.len(),
+            cores_on_ap,
+            ap.ap_address()
+        ))),
+    }
+}
+
 /// Read trace data from internal trace memory
 ///
 /// # Args
 /// * `interface` - The interface with the debug probe.
 /// * `components` - The CoreSight debug components identified in the system.
+/// * `source` - The trace source of which the data is returned.
 ///
 /// # Note
 /// This function will read any available trace data in trace memory without blocking. At most,
@@ -289,6 +393,7 @@ pub(crate) fn setup_tracing(
 pub(crate) fn read_trace_memory(
     interface: &mut dyn ArmProbeInterface,
     components: &[CoresightComponent],
+    source: TraceSource,
 ) -> Result<Vec<u8>, ArmError> {
     let mut tmc =
         TraceMemoryController::new(interface, find_component(components, PeripheralType::Tmc)?);
@@ -323,27 +428,26 @@ pub(crate) fn read_trace_memory(
     }
 
     // The TMC formats data into frames, as it contains trace data from multiple data sources. We
-    // need to deserialize the frames and pull out only the data source of interest. For now, all
-    // we care about is the ITM data.
+    // need to deserialize the frames and pull out only the data source of interest.
 
     let mut id = 0.into();
-    let mut itm_trace = Vec::new();
+    let mut trace = Vec::new();
 
     // Process each formatted frame and extract the multiplexed trace data.
     for frame_buffer in etf_trace.chunks_exact(16) {
         let mut frame = tmc::Frame::new(frame_buffer, id);
         for (id, data) in &mut frame {
             match id.into() {
-                // ITM ATID, see Itm::tx_enable()
-                13 => itm_trace.push(data),
+                id if id == source.trace_id() => trace.push(data),
                 0 => (),
+                id if id == TraceSource::Itm.trace_id() || id == TraceSource::Etm.trace_id() => (),
                 id => tracing::warn!("Unexpected trace source ATID {id}: {data}, ignoring"),
             }
         }
         id = frame.id();
     }
 
-    Ok(itm_trace)
+    Ok(trace)
 }
 
 /// Configures DWT trace unit `unit` to begin tracing `address`.
@@ -275,3 +275,105 @@ impl<'a> Iterator for &mut Frame<'a> {
         ret
     }
 }
+
+/// The full frame synchronization packet, which marks the start of a frame.
+const FULL_SYNC: [u8; 4] = [0xFF, 0xFF, 0xFF, 0x7F];
+/// The halfword synchronization packet, inserted by a TPIU between frames.
+const HALFWORD_SYNC: [u8; 2] = [0xFF, 0x7F];
+
+/// Formatted frame stream demultiplexer.
+///
+/// Extracts the data of a single trace source from a stream of formatted frames, as
+/// received from a TPIU with the formatter enabled. The stream can be passed in
+/// arbitrary chunks, incomplete frames are kept until the rest of the frame is received.
+#[derive(Debug, Default)]
+pub struct Deformatter {
+    buffer: Vec<u8>,
+    synchronized: bool,
+    id: u8,
+}
+
+impl Deformatter {
+    /// Create a new deformatter, which waits for a frame synchronization packet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process the next chunk of the stream, and return the data of trace source `trace_id`.
+    pub fn push(&mut self, data: &[u8], trace_id: u8) -> Vec<u8> {
+        self.buffer.extend_from_slice(data);
+
+        let mut output = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let rest = &self.buffer[offset..];
+
+            if rest.starts_with(&FULL_SYNC) {
+                offset += FULL_SYNC.len();
+                self.synchronized = true;
+            } else if !self.synchronized {
+                // Discard everything up to the next synchronization packet, keeping a possibly
+                // incomplete one at the end.
+                match rest
+                    .windows(FULL_SYNC.len())
+                    .position(|window| window == FULL_SYNC)
+                {
+                    Some(position) => offset += position,
+                    None => {
+                        offset += rest.len().saturating_sub(FULL_SYNC.len() - 1);
+                        break;
+                    }
+                }
+            } else if rest.len() < FULL_SYNC.len() {
+                break;
+            } else if rest.starts_with(&HALFWORD_SYNC) {
+                offset += HALFWORD_SYNC.len();
+            } else if rest.len() < 16 {
+                break;
+            } else {
+                let mut frame = Frame::new(&rest[..16], self.id.into());
+                for (id, byte) in &mut frame {
+                    if u8::from(id) == trace_id {
+                        output.push(byte);
+                    }
+                }
+                self.id = frame.id().into();
+                offset += 16;
+            }
+        }
+
+        self.buffer.drain(..offset);
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deformat_stream_in_chunks() {
+        // Frame with ID 0x10 followed by 7 bytes, then ID 0x0D followed by 6 bytes.
+        let frame = [
+            0x21, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x1B, 0x0A, 0x0C, 0x0B, 0x0E, 0x0D,
+            0x10, 0x00,
+        ];
+
+        let mut stream = vec![0x12, 0x34];
+        stream.extend_from_slice(&FULL_SYNC);
+        stream.extend_from_slice(&frame);
+        stream.extend_from_slice(&HALFWORD_SYNC);
+        stream.extend_from_slice(&frame);
+
+        let mut deformatter = Deformatter::new();
+        let mut etm = Vec::new();
+        for chunk in stream.chunks(5) {
+            etm.extend(deformatter.push(chunk, 0x10));
+        }
+
+        let expected = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        assert_eq!(etm, [expected, expected].concat());
+    }
+}
@@ -1,6 +1,6 @@
 //! Register types and the core interface for armv6-M
 
-use super::{registers::cortex_m::*, CortexMState, Dfsr};
+use super::{cortex_m::DwtVersion, registers::cortex_m::*, CortexMState, Dfsr};
 use crate::{
     architecture::arm::{
         memory::adi_v5_memory_interface::ArmProbe, sequences::ArmDebugSequence, ArmError,
@@ -9,7 +9,7 @@ use crate::{
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType,
-    DebugProbeError, HaltReason, InstructionSet, MemoryInterface, MemoryMappedRegister,
+    DebugProbeError, HaltReason, InstructionSet, MemoryInterface, MemoryMappedRegister, Watchpoint,
 };
 use anyhow::Result;
 use bitfield::bitfield;
@@ -539,6 +539,11 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
                 );
             }
 
+            if let HaltReason::Watchpoint(_) = reason {
+                let cause = super::cortex_m::watchpoint_cause(&mut *self.memory, DwtVersion::V1)?;
+                reason = HaltReason::Watchpoint(cause);
+            }
+
             // Set the status so any semihosting operations will know we're halted
             self.set_core_status(CoreStatus::Halted(reason));
 
@@ -785,6 +790,26 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        super::cortex_m::watchpoints(&mut *self.memory, DwtVersion::V1)
+    }
+
+    fn set_watchpoint(&mut self, unit_index: usize, watchpoint: Watchpoint) -> Result<(), Error> {
+        super::cortex_m::set_watchpoint(&mut *self.memory, DwtVersion::V1, unit_index, watchpoint)
+    }
+
+    fn clear_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_watchpoint(&mut *self.memory, unit_index)
+    }
+
+    fn unit_reserved(&mut self, unit_index: usize) -> Result<bool, Error> {
+        super::cortex_m::watchpoint_unit_reserved(&mut *self.memory, DwtVersion::V1, unit_index)
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         &CORTEX_M_CORE_REGSISTERS
     }
@@ -872,6 +897,9 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
                 demcr.set_vc_harderr(true);
                 demcr.set_vc_corereset(true);
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -891,6 +919,9 @@ impl<'probe> CoreInterface for Armv6m<'probe> {
                 demcr.set_vc_harderr(false);
                 demcr.set_vc_corereset(false);
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -911,6 +942,12 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         let value = self.memory.read_word_32(address)?;
         Ok(value)
     }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.memory
+            .read_word_16(address)
+            .map_err(From::<ArmError>::from)
+    }
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         let value = self.memory.read_word_8(address)?;
         Ok(value)
@@ -926,6 +963,12 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.memory
+            .read_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.memory.read_8(address, data)?;
         Ok(())
@@ -941,6 +984,12 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.memory
+            .write_word_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.memory.write_word_8(address, data)?;
         Ok(())
@@ -956,6 +1005,12 @@ impl<'probe> MemoryInterface for Armv6m<'probe> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.memory
+            .write_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.memory.write_8(address, data)?;
         Ok(())
@@ -2,8 +2,8 @@
 
 use super::{
     instructions::aarch32::{
-        build_bx, build_ldc, build_mcr, build_mov, build_mrc, build_mrs, build_stc, build_vmov,
-        build_vmrs,
+        build_bx, build_ldc, build_ldrh, build_mcr, build_mov, build_mrc, build_mrs, build_stc,
+        build_strh, build_vmov, build_vmrs,
     },
     registers::{
         aarch32::{
@@ -12,7 +12,7 @@ use super::{
         },
         cortex_m::{FP, PC, RA, SP},
     },
-    CortexAState,
+    CortexAState, WatchpointMatch,
 };
 use crate::{
     architecture::arm::{
@@ -22,10 +22,10 @@ use crate::{
     core::{CoreRegisters, MemoryMappedRegister, RegisterId, RegisterValue},
     error::Error,
     memory::valid_32bit_address,
-    Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType,
-    InstructionSet, MemoryInterface,
+    Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType, HaltReason,
+    InstructionSet, MemoryInterface, Watchpoint, WatchpointAccess, WatchpointCause,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::{
     mem::size_of,
     sync::Arc,
@@ -60,6 +60,8 @@ pub struct Armv7a<'probe> {
 
     num_breakpoints: Option<u32>,
 
+    num_watchpoints: Option<u32>,
+
     itr_enabled: bool,
 
     id: usize,
@@ -99,6 +101,7 @@ impl<'probe> Armv7a<'probe> {
             base_address,
             sequence,
             num_breakpoints: None,
+            num_watchpoints: None,
             itr_enabled: false,
             id,
         };
@@ -276,6 +279,23 @@ impl<'probe> Armv7a<'probe> {
         Ok(())
     }
 
+    /// Determine which watchpoint caused the core to halt.
+    ///
+    /// The debug registers do not record the matching watchpoint, so the cause is only known
+    /// if a single watchpoint is enabled.
+    fn watchpoint_cause(&mut self) -> Result<WatchpointCause, Error> {
+        let mut enabled = self
+            .watchpoints()?
+            .into_iter()
+            .enumerate()
+            .filter(|(_, watchpoint)| watchpoint.is_some());
+
+        match (enabled.next(), enabled.next()) {
+            (Some((unit_index, _)), None) => Ok(WatchpointCause::Unit(unit_index)),
+            _ => Ok(WatchpointCause::Unknown),
+        }
+    }
+
     /// Save r0 if needed before it gets clobbered by instruction execution
     fn prepare_r0_for_clobber(&mut self) -> Result<(), Error> {
         self.prepare_for_clobber(0)
@@ -335,7 +355,11 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
         let dbgdscr = Dbgdscr(self.memory.read_word_32(address)?);
 
         if dbgdscr.halted() {
-            let reason = dbgdscr.halt_reason();
+            let mut reason = dbgdscr.halt_reason();
+
+            if let HaltReason::Watchpoint(_) = reason {
+                reason = HaltReason::Watchpoint(self.watchpoint_cause()?);
+            }
 
             self.set_core_status(CoreStatus::Halted(reason));
 
@@ -728,6 +752,131 @@ impl<'probe> CoreInterface for Armv7a<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        if self.num_watchpoints.is_none() {
+            let address = Dbgdidr::get_mmio_address_from_base(self.base_address)?;
+            let dbgdidr = Dbgdidr(self.memory.read_word_32(address)?);
+
+            self.num_watchpoints = Some(dbgdidr.wrps() + 1);
+        }
+        Ok(self.num_watchpoints.unwrap())
+    }
+
+    /// See docs on the [`CoreInterface::watchpoints`] trait
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        let mut watchpoints = vec![];
+        let num_watchpoints = self.available_watchpoint_units()? as usize;
+
+        for wp_unit_index in 0..num_watchpoints {
+            let wp_value_addr = Dbgwvr::get_mmio_address_from_base(self.base_address)?
+                + (wp_unit_index * size_of::<u32>()) as u64;
+            let wp_value = self.memory.read_word_32(wp_value_addr)?;
+
+            let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+                + (wp_unit_index * size_of::<u32>()) as u64;
+            let wp_control = Dbgwcr(self.memory.read_word_32(wp_control_addr)?);
+
+            let access = match wp_control.lsc() {
+                0b01 => WatchpointAccess::Read,
+                0b10 => WatchpointAccess::Write,
+                0b11 => WatchpointAccess::ReadWrite,
+                _ => {
+                    watchpoints.push(None);
+                    continue;
+                }
+            };
+
+            if wp_control.e() {
+                let (address, length) = WatchpointMatch {
+                    address: wp_value as u64,
+                    bas: wp_control.bas(),
+                    mask: wp_control.mask(),
+                }
+                .range();
+
+                watchpoints.push(Some(Watchpoint {
+                    address,
+                    length,
+                    access,
+                    value: None,
+                }));
+            } else {
+                watchpoints.push(None);
+            }
+        }
+        Ok(watchpoints)
+    }
+
+    fn set_watchpoint(
+        &mut self,
+        wp_unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), Error> {
+        valid_32bit_address(watchpoint.address)?;
+
+        if watchpoint.value.is_some() {
+            return Err(Error::NotImplemented("data value watchpoints"));
+        }
+
+        let watchpoint_match = WatchpointMatch::new(
+            watchpoint.address,
+            watchpoint.length,
+            size_of::<u32>() as u64,
+        )?;
+
+        let wp_value_addr = Dbgwvr::get_mmio_address_from_base(self.base_address)?
+            + (wp_unit_index * size_of::<u32>()) as u64;
+        let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+            + (wp_unit_index * size_of::<u32>()) as u64;
+        let mut wp_control = Dbgwcr(0);
+
+        // Watched range
+        wp_control.set_mask(watchpoint_match.mask);
+        wp_control.set_bas(watchpoint_match.bas);
+        // Match on all modes
+        wp_control.set_hmc(true);
+        wp_control.set_pac(0b11);
+        // Access type
+        wp_control.set_lsc(match watchpoint.access {
+            WatchpointAccess::Read => 0b01,
+            WatchpointAccess::Write => 0b10,
+            WatchpointAccess::ReadWrite => 0b11,
+        });
+        // Enable
+        wp_control.set_e(true);
+
+        // Disable the watchpoint while it is reconfigured
+        self.memory.write_word_32(wp_control_addr, 0)?;
+        self.memory
+            .write_word_32(wp_value_addr, watchpoint_match.address as u32)?;
+        self.memory
+            .write_word_32(wp_control_addr, wp_control.into())?;
+
+        // Address masking is optional
+        if Dbgwcr(self.memory.read_word_32(wp_control_addr)?).mask() != watchpoint_match.mask {
+            self.memory.write_word_32(wp_control_addr, 0)?;
+
+            return Err(Error::Other(anyhow!(
+                "The core does not support watching {} bytes",
+                watchpoint.length
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn clear_watchpoint(&mut self, wp_unit_index: usize) -> Result<(), Error> {
+        let wp_value_addr = Dbgwvr::get_mmio_address_from_base(self.base_address)?
+            + (wp_unit_index * size_of::<u32>()) as u64;
+        let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+            + (wp_unit_index * size_of::<u32>()) as u64;
+
+        self.memory.write_word_32(wp_control_addr, 0)?;
+        self.memory.write_word_32(wp_value_addr, 0)?;
+
+        Ok(())
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         match self.state.fp_reg_count {
             Some(16) => &AARCH32_WITH_FP_16_CORE_REGSISTERS,
@@ -850,6 +999,32 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         self.execute_instruction_with_result(instr)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        if address % 2 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        let address = valid_32bit_address(address)?;
+
+        // Save r0, r1
+        self.prepare_r0_for_clobber()?;
+        self.prepare_for_clobber(1)?;
+
+        // Load r0 with the address to read from
+        self.set_r0(address)?;
+
+        // Read data to r1 - LDRH r1, [r0], #2
+        self.execute_instruction(build_ldrh(1, 0, 2))?;
+
+        // Move from r1 to transfer buffer - MCR p14, 0, r1, c0, c5, 0
+        let data = self.execute_instruction_with_result(build_mcr(14, 0, 1, 0, 5, 0))?;
+
+        Ok(data as u16)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         // Find the word this is in and its byte offset
         let byte_offset = address % 4;
@@ -878,6 +1053,14 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_16(address + ((i as u64) * 2))?;
+        }
+
+        Ok(())
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         for (i, byte) in data.iter_mut().enumerate() {
             *byte = self.read_word_8(address + (i as u64))?;
@@ -910,6 +1093,30 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         self.execute_instruction_with_input(instr, data)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        if address % 2 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        let address = valid_32bit_address(address)?;
+
+        // Save r0, r1
+        self.prepare_r0_for_clobber()?;
+        self.prepare_for_clobber(1)?;
+
+        // Load r0 with the address to write to, and r1 with the data
+        self.set_r0(address)?;
+        self.execute_instruction_with_input(build_mrc(14, 0, 1, 0, 5, 0), data.into())?;
+
+        // Write data to memory - STRH r1, [r0], #2
+        self.execute_instruction(build_strh(1, 0, 2))?;
+
+        Ok(())
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         // Find the word this is in and its byte offset
         let byte_offset = address % 4;
@@ -939,6 +1146,14 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        for (i, word) in data.iter().enumerate() {
+            self.write_word_16(address + ((i as u64) * 2), *word)?;
+        }
+
+        Ok(())
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         for (i, byte) in data.iter().enumerate() {
             self.write_word_8(address + ((i as u64) * 4), *byte)?;
@@ -1016,6 +1231,10 @@ mod test {
             todo!()
         }
 
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1055,6 +1274,10 @@ mod test {
             todo!()
         }
 
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1292,6 +1515,58 @@ mod test {
         );
     }
 
+    fn add_read_memory_16_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_r0_expectation(probe, address as u32);
+
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_instrcoml_l(true);
+
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_ldrh(1, 0, 2),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+
+        add_read_reg_expectations(probe, 1, value.into());
+    }
+
+    fn add_write_memory_16_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_r0_expectation(probe, address as u32);
+
+        let mut dbgdscr = Dbgdscr(0);
+        dbgdscr.set_instrcoml_l(true);
+        dbgdscr.set_rxfull_l(true);
+
+        probe.expected_write(
+            Dbgdtrrx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            value.into(),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_mrc(14, 0, 1, 0, 5, 0),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+
+        probe.expected_write(
+            Dbgitr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            build_strh(1, 0, 2),
+        );
+        probe.expected_read(
+            Dbgdscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgdscr.into(),
+        );
+    }
+
     #[test]
     fn armv7a_new() {
         let mut probe = MockProbe::new();
@@ -1918,4 +2193,68 @@ mod test {
 
         assert_eq!(0xBA, armv7a.read_word_8(MEMORY_ADDRESS).unwrap());
     }
+
+    #[test]
+    fn armv7a_read_word_16() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new();
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        // Read memory
+        add_read_reg_expectations(&mut probe, 1, 0);
+        add_read_memory_16_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv7a = Armv7a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(MEMORY_VALUE, armv7a.read_word_16(MEMORY_ADDRESS).unwrap());
+    }
+
+    #[test]
+    fn armv7a_write_word_16() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new();
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+        add_enable_itr_expectations(&mut probe);
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_fp_count_expectations(&mut probe);
+
+        // Write memory
+        add_read_reg_expectations(&mut probe, 1, 0);
+        add_write_memory_16_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv7a = Armv7a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        armv7a.write_word_16(MEMORY_ADDRESS, MEMORY_VALUE).unwrap();
+    }
 }
@@ -1,6 +1,6 @@
 //! Debug register definitions
 
-use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason};
+use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason, WatchpointCause};
 
 memory_mapped_bitfield_register! {
     /// DBGDSCR - Debug Status and Control Registers
@@ -221,7 +221,7 @@ impl Dbgdscr {
                 // Breakpoint debug event
                 0b0001 => HaltReason::Breakpoint(BreakpointCause::Hardware),
                 // Async watchpoint debug event
-                0b0010 => HaltReason::Watchpoint,
+                0b0010 => HaltReason::Watchpoint(WatchpointCause::Unknown),
                 // BKPT instruction
                 0b0011 => HaltReason::Breakpoint(BreakpointCause::Software),
                 // External halt request
@@ -231,7 +231,7 @@ impl Dbgdscr {
                 // OS Unlock vector catch
                 0b1000 => HaltReason::Exception,
                 // Sync watchpoint debug event
-                0b1010 => HaltReason::Watchpoint,
+                0b1010 => HaltReason::Watchpoint(WatchpointCause::Unknown),
                 // All other values are reserved
                 _ => HaltReason::Unknown,
             }
@@ -359,6 +359,50 @@ memory_mapped_bitfield_register! {
     pub e, set_e: 0;
 }
 
+memory_mapped_bitfield_register! {
+    /// DBGWVR - Watchpoint Value Register
+    pub struct Dbgwvr(u32);
+    0x180, "DBGWVR",
+    impl From;
+
+    /// Watchpoint address, bits [1:0] are ignored
+    pub value, set_value : 31, 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// DBGWCR - Watchpoint Control Register
+    pub struct Dbgwcr(u32);
+    0x1C0, "DBGWCR",
+    impl From;
+
+    /// Address range mask. Whether masking is supported is implementation defined.
+    pub mask, set_mask : 28, 24;
+
+    /// Watchpoint type, 0 for unlinked data address match
+    pub wt, set_wt : 20;
+
+    /// Linked breakpoint number
+    pub lbn, set_lbn : 19, 16;
+
+    /// Security state control
+    pub ssc, set_ssc : 15, 14;
+
+    /// Hyp mode control bit
+    pub hmc, set_hmc: 13;
+
+    /// Byte address select
+    pub bas, set_bas: 12, 5;
+
+    /// Load/store access control. 0b01 matches loads, 0b10 stores and 0b11 both.
+    pub lsc, set_lsc: 4, 3;
+
+    /// Privileged access control
+    pub pac, set_pac: 2, 1;
+
+    /// Watchpoint enable
+    pub e, set_e: 0;
+}
+
 memory_mapped_bitfield_register! {
     /// DBGLAR - Lock Access Register
     pub struct Dbglar(u32);
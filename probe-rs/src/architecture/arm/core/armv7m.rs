@@ -1,7 +1,7 @@
 //! Register types and the core interface for armv7-M
 
 use super::{
-    cortex_m::Mvfr0,
+    cortex_m::{DwtVersion, Mvfr0},
     registers::cortex_m::{
         CORTEX_M_CORE_REGSISTERS, CORTEX_M_WITH_FP_CORE_REGSISTERS, FP, PC, RA, SP,
     },
@@ -18,7 +18,7 @@ use crate::{
     },
     error::Error,
     memory::valid_32bit_address,
-    CoreRegister, CoreType, DebugProbeError, InstructionSet, MemoryInterface,
+    CoreRegister, CoreType, DebugProbeError, InstructionSet, MemoryInterface, Watchpoint,
 };
 use anyhow::{anyhow, Result};
 use bitfield::bitfield;
@@ -718,6 +718,11 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
                 );
             }
 
+            if let HaltReason::Watchpoint(_) = reason {
+                let cause = super::cortex_m::watchpoint_cause(&mut *self.memory, DwtVersion::V1)?;
+                reason = HaltReason::Watchpoint(cause);
+            }
+
             // Set the status so any semihosting operations will know we're halted
             self.set_core_status(CoreStatus::Halted(reason));
 
@@ -1010,6 +1015,26 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        super::cortex_m::watchpoints(&mut *self.memory, DwtVersion::V1)
+    }
+
+    fn set_watchpoint(&mut self, unit_index: usize, watchpoint: Watchpoint) -> Result<(), Error> {
+        super::cortex_m::set_watchpoint(&mut *self.memory, DwtVersion::V1, unit_index, watchpoint)
+    }
+
+    fn clear_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_watchpoint(&mut *self.memory, unit_index)
+    }
+
+    fn unit_reserved(&mut self, unit_index: usize) -> Result<bool, Error> {
+        super::cortex_m::watchpoint_unit_reserved(&mut *self.memory, DwtVersion::V1, unit_index)
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         if self.state.fp_present {
             &CORTEX_M_WITH_FP_CORE_REGSISTERS
@@ -1099,6 +1124,9 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
                 demcr.set_vc_harderr(true);
                 demcr.set_vc_corereset(true);
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -1118,6 +1146,9 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
                 demcr.set_vc_harderr(false);
                 demcr.set_vc_corereset(false);
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -1143,6 +1174,12 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.memory
+            .read_word_16(address)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.memory
             .read_word_8(address)
@@ -1161,6 +1198,12 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.memory
+            .read_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.memory
             .read_8(address, data)
@@ -1179,6 +1222,12 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.memory
+            .write_word_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.memory
             .write_word_8(address, data)
@@ -1197,6 +1246,12 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.memory
+            .write_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.memory
             .write_8(address, data)
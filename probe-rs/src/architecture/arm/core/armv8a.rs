@@ -3,10 +3,13 @@
 use super::{
     instructions::{
         aarch64,
-        thumb2::{build_ldr, build_mcr, build_mrc, build_str, build_vmov, build_vmrs},
+        thumb2::{
+            build_ldr, build_ldrh, build_mcr, build_mrc, build_str, build_strh, build_vmov,
+            build_vmrs,
+        },
     },
     registers::{aarch32::AARCH32_WITH_FP_32_CORE_REGSISTERS, aarch64::AARCH64_CORE_REGSISTERS},
-    CortexAState,
+    CortexAState, WatchpointMatch,
 };
 use crate::{
     architecture::arm::{
@@ -18,8 +21,8 @@ use crate::{
     },
     error::Error,
     memory::valid_32bit_address,
-    Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType,
-    InstructionSet, MemoryInterface,
+    Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType, HaltReason,
+    InstructionSet, MemoryInterface, Watchpoint, WatchpointAccess, WatchpointCause,
 };
 use anyhow::Result;
 use std::{
@@ -62,6 +65,8 @@ pub struct Armv8a<'probe> {
 
     num_breakpoints: Option<u32>,
 
+    num_watchpoints: Option<u32>,
+
     id: usize,
 }
 
@@ -104,6 +109,7 @@ impl<'probe> Armv8a<'probe> {
             cti_address,
             sequence,
             num_breakpoints: None,
+            num_watchpoints: None,
             id,
         };
 
@@ -648,6 +654,44 @@ impl<'probe> Armv8a<'probe> {
         self.execute_instruction_with_result_32(instruction)
     }
 
+    fn read_cpu_memory_aarch32_16(&mut self, address: u64) -> Result<u16, Error> {
+        let address = valid_32bit_address(address)?;
+
+        // Save r0, r1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load r0 with the address to read from
+        self.set_reg_value(0, address.into())?;
+
+        // Read data to r1 - LDRH r1, [r0], #2
+        let instruction = build_ldrh(1, 0, 2);
+
+        self.execute_instruction(instruction)?;
+
+        // Move from r1 to transfer buffer - MCR p14, 0, r1, c0, c5, 0
+        let instruction = build_mcr(14, 0, 1, 0, 5, 0);
+        Ok(self.execute_instruction_with_result_32(instruction)? as u16)
+    }
+
+    fn read_cpu_memory_aarch64_16(&mut self, address: u64) -> Result<u16, Error> {
+        // Save x0, x1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load x0 with the address to read from
+        self.set_reg_value(0, address)?;
+
+        // Read data to w1 - LDRH w1, [x0], #2
+        let instruction = aarch64::build_ldrh(1, 0, 2);
+
+        self.execute_instruction(instruction)?;
+
+        // MSR DBGDTRTX_EL0, X1
+        let instruction = aarch64::build_msr(2, 3, 0, 5, 0, 1);
+        Ok(self.execute_instruction_with_result_32(instruction)? as u16)
+    }
+
     fn read_cpu_memory_aarch64_64(&mut self, address: u64) -> Result<u64, Error> {
         // Save x0, x1
         self.prepare_for_clobber(0)?;
@@ -702,6 +746,42 @@ impl<'probe> Armv8a<'probe> {
         Ok(())
     }
 
+    fn write_cpu_memory_aarch32_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        let address = valid_32bit_address(address)?;
+
+        // Save r0, r1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load r0 with the address to write to, and r1 with the data
+        self.set_reg_value(0, address.into())?;
+        self.set_reg_value(1, data.into())?;
+
+        // Write data to memory - STRH r1, [r0], #2
+        let instruction = build_strh(1, 0, 2);
+
+        self.execute_instruction(instruction)?;
+
+        Ok(())
+    }
+
+    fn write_cpu_memory_aarch64_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        // Save x0, x1
+        self.prepare_for_clobber(0)?;
+        self.prepare_for_clobber(1)?;
+
+        // Load x0 with the address to write to, and w1 with the data
+        self.set_reg_value(0, address)?;
+        self.set_reg_value(1, data.into())?;
+
+        // Write data to memory - STRH w1, [x0], #2
+        let instruction = aarch64::build_strh(1, 0, 2);
+
+        self.execute_instruction(instruction)?;
+
+        Ok(())
+    }
+
     fn write_cpu_memory_aarch64_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
         // Save x0, x1
         self.prepare_for_clobber(0)?;
@@ -722,6 +802,98 @@ impl<'probe> Armv8a<'probe> {
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Restart the core, without any special handling of the halt reason.
+    fn resume(&mut self) -> Result<(), Error> {
+        // set writeback values
+        self.writeback_registers()?;
+
+        self.ack_cti_halt()?;
+
+        // Ungate restart CTI channel
+        let mut cti_gate = CtiGate(0);
+        cti_gate.set_en(1, 1);
+
+        let address = CtiGate::get_mmio_address_from_base(self.cti_address)?;
+        self.memory.write_word_32(address, cti_gate.into())?;
+
+        // Pulse it
+        let mut pulse = CtiApppulse(0);
+        pulse.set_apppulse(1, 1);
+
+        let address = CtiApppulse::get_mmio_address_from_base(self.cti_address)?;
+        self.memory.write_word_32(address, pulse.into())?;
+
+        // Wait for ack
+        let address = Edprsr::get_mmio_address_from_base(self.base_address)?;
+
+        loop {
+            let edprsr = Edprsr(self.memory.read_word_32(address)?);
+            if edprsr.sdr() {
+                break;
+            }
+        }
+
+        // Recompute / verify current state
+        self.set_core_status(CoreStatus::Running);
+        let _ = self.status()?;
+
+        // Gate restart channel
+        let cti_gate = CtiGate(0);
+
+        let address = CtiGate::get_mmio_address_from_base(self.cti_address)?;
+        self.memory.write_word_32(address, cti_gate.into())?;
+
+        Ok(())
+    }
+
+    /// Determine which watchpoint caused the core to halt, based on the data address in EDWAR.
+    fn watchpoint_cause(&mut self) -> Result<WatchpointCause, Error> {
+        let address = Edwar::get_mmio_address_from_base(self.base_address)?;
+        let mut data_address = self.memory.read_word_32(address)? as u64;
+        data_address |= (self.memory.read_word_32(address + 4)? as u64) << 32;
+
+        let cause = self
+            .watchpoints()?
+            .into_iter()
+            .position(|watchpoint| {
+                matches!(watchpoint, Some(watchpoint)
+                    if (watchpoint.address..watchpoint.address + watchpoint.length).contains(&data_address))
+            })
+            .map_or(WatchpointCause::Unknown, WatchpointCause::Unit);
+
+        Ok(cause)
+    }
+
+    /// Disable all watchpoints, returning the previous values of their control registers.
+    fn disable_watchpoints(&mut self) -> Result<Vec<u32>, Error> {
+        let num_watchpoints = self.available_watchpoint_units()? as usize;
+        let mut saved_controls = Vec::with_capacity(num_watchpoints);
+
+        for wp_unit_index in 0..num_watchpoints {
+            let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+                + (wp_unit_index * 16) as u64;
+            let wp_control = Dbgwcr(self.memory.read_word_32(wp_control_addr)?);
+            saved_controls.push(wp_control.into());
+
+            if wp_control.e() {
+                self.memory.write_word_32(wp_control_addr, 0)?;
+            }
+        }
+
+        Ok(saved_controls)
+    }
+
+    /// Restore the watchpoint control registers saved by [`Self::disable_watchpoints`].
+    fn restore_watchpoints(&mut self, saved_controls: &[u32]) -> Result<(), Error> {
+        for (wp_unit_index, wp_control) in saved_controls.iter().enumerate() {
+            let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+                + (wp_unit_index * 16) as u64;
+            self.memory.write_word_32(wp_control_addr, *wp_control)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'probe> CoreInterface for Armv8a<'probe> {
@@ -754,7 +926,11 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
         let edscr = Edscr(self.memory.read_word_32(address)?);
 
         if edscr.halted() {
-            let reason = edscr.halt_reason();
+            let mut reason = edscr.halt_reason();
+
+            if let HaltReason::Watchpoint(_) = reason {
+                reason = HaltReason::Watchpoint(self.watchpoint_cause()?);
+            }
 
             self.set_core_status(CoreStatus::Halted(reason));
             self.state.is_64_bit = edscr.currently_64_bit();
@@ -816,46 +992,14 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
             return Ok(());
         }
 
-        // set writeback values
-        self.writeback_registers()?;
-
-        self.ack_cti_halt()?;
-
-        // Ungate restart CTI channel
-        let mut cti_gate = CtiGate(0);
-        cti_gate.set_en(1, 1);
-
-        let address = CtiGate::get_mmio_address_from_base(self.cti_address)?;
-        self.memory.write_word_32(address, cti_gate.into())?;
-
-        // Pulse it
-        let mut pulse = CtiApppulse(0);
-        pulse.set_apppulse(1, 1);
-
-        let address = CtiApppulse::get_mmio_address_from_base(self.cti_address)?;
-        self.memory.write_word_32(address, pulse.into())?;
-
-        // Wait for ack
-        let address = Edprsr::get_mmio_address_from_base(self.base_address)?;
-
-        loop {
-            let edprsr = Edprsr(self.memory.read_word_32(address)?);
-            if edprsr.sdr() {
-                break;
-            }
+        // Watchpoints halt the core before the access completes, so the
+        // access has to be stepped over to avoid hitting the watchpoint again.
+        if let CoreStatus::Halted(HaltReason::Watchpoint(_)) = self.state.current_state {
+            self.step()?;
+            let _ = self.status()?;
         }
 
-        // Recompute / verify current state
-        self.set_core_status(CoreStatus::Running);
-        let _ = self.status()?;
-
-        // Gate restart channel
-        let cti_gate = CtiGate(0);
-
-        let address = CtiGate::get_mmio_address_from_base(self.cti_address)?;
-        self.memory.write_word_32(address, cti_gate.into())?;
-
-        Ok(())
+        self.resume()
     }
 
     fn reset(&mut self) -> Result<(), Error> {
@@ -915,12 +1059,25 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
         edecr.set_ss(true);
         self.memory.write_word_32(edecr_address, edecr.into())?;
 
+        // The access which triggered a watchpoint has not been executed yet,
+        // so watchpoints are disabled while stepping over it.
+        let saved_watchpoints =
+            if let CoreStatus::Halted(HaltReason::Watchpoint(_)) = self.state.current_state {
+                Some(self.disable_watchpoints()?)
+            } else {
+                None
+            };
+
         // Resume
-        self.run()?;
+        self.resume()?;
 
         // Wait for halt
         self.wait_for_core_halted(Duration::from_millis(100))?;
 
+        if let Some(saved_watchpoints) = saved_watchpoints {
+            self.restore_watchpoints(&saved_watchpoints)?;
+        }
+
         // Reset EDECR
         edecr.set_ss(false);
         self.memory.write_word_32(edecr_address, edecr.into())?;
@@ -1053,6 +1210,120 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        if self.num_watchpoints.is_none() {
+            let address = Eddfr::get_mmio_address_from_base(self.base_address)?;
+            let eddfr = Eddfr(self.memory.read_word_32(address)?);
+
+            self.num_watchpoints = Some(eddfr.wrps() + 1);
+        }
+        Ok(self.num_watchpoints.unwrap())
+    }
+
+    /// See docs on the [`CoreInterface::watchpoints`] trait
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        let mut watchpoints = vec![];
+        let num_watchpoints = self.available_watchpoint_units()? as usize;
+
+        for wp_unit_index in 0..num_watchpoints {
+            let wp_control_addr = Dbgwcr::get_mmio_address_from_base(self.base_address)?
+                + (wp_unit_index * 16) as u64;
+            let wp_control = Dbgwcr(self.memory.read_word_32(wp_control_addr)?);
+
+            let access = match wp_control.lsc() {
+                0b01 => WatchpointAccess::Read,
+                0b10 => WatchpointAccess::Write,
+                0b11 => WatchpointAccess::ReadWrite,
+                _ => {
+                    watchpoints.push(None);
+                    continue;
+                }
+            };
+
+            if wp_control.e() {
+                let wp_value_addr = Dbgwvr::get_mmio_address_from_base(self.base_address)?
+                    + (wp_unit_index * 16) as u64;
+                let mut wp_value = self.memory.read_word_32(wp_value_addr)? as u64;
+                wp_value |= (self.memory.read_word_32(wp_value_addr + 4)? as u64) << 32;
+
+                let (address, length) = WatchpointMatch {
+                    address: wp_value,
+                    bas: wp_control.bas(),
+                    mask: wp_control.mask(),
+                }
+                .range();
+
+                watchpoints.push(Some(Watchpoint {
+                    address,
+                    length,
+                    access,
+                    value: None,
+                }));
+            } else {
+                watchpoints.push(None);
+            }
+        }
+        Ok(watchpoints)
+    }
+
+    fn set_watchpoint(
+        &mut self,
+        wp_unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), Error> {
+        if watchpoint.value.is_some() {
+            return Err(Error::NotImplemented("data value watchpoints"));
+        }
+
+        let watchpoint_match = WatchpointMatch::new(watchpoint.address, watchpoint.length, 8)?;
+
+        let wp_value_addr =
+            Dbgwvr::get_mmio_address_from_base(self.base_address)? + (wp_unit_index * 16) as u64;
+        let wp_control_addr =
+            Dbgwcr::get_mmio_address_from_base(self.base_address)? + (wp_unit_index * 16) as u64;
+        let mut wp_control = Dbgwcr(0);
+
+        // Watched range
+        wp_control.set_mask(watchpoint_match.mask);
+        wp_control.set_bas(watchpoint_match.bas);
+        // Match on all modes
+        wp_control.set_hmc(true);
+        wp_control.set_pac(0b11);
+        // Access type
+        wp_control.set_lsc(match watchpoint.access {
+            WatchpointAccess::Read => 0b01,
+            WatchpointAccess::Write => 0b10,
+            WatchpointAccess::ReadWrite => 0b11,
+        });
+        // Enable
+        wp_control.set_e(true);
+
+        let addr_low = watchpoint_match.address as u32;
+        let addr_high = (watchpoint_match.address >> 32) as u32;
+
+        // Disable the watchpoint while it is reconfigured
+        self.memory.write_word_32(wp_control_addr, 0)?;
+        self.memory.write_word_32(wp_value_addr, addr_low)?;
+        self.memory.write_word_32(wp_value_addr + 4, addr_high)?;
+        self.memory
+            .write_word_32(wp_control_addr, wp_control.into())?;
+
+        Ok(())
+    }
+
+    fn clear_watchpoint(&mut self, wp_unit_index: usize) -> Result<(), Error> {
+        let wp_value_addr =
+            Dbgwvr::get_mmio_address_from_base(self.base_address)? + (wp_unit_index * 16) as u64;
+        let wp_control_addr =
+            Dbgwcr::get_mmio_address_from_base(self.base_address)? + (wp_unit_index * 16) as u64;
+
+        self.memory.write_word_32(wp_control_addr, 0)?;
+        self.memory.write_word_32(wp_value_addr, 0)?;
+        self.memory.write_word_32(wp_value_addr + 4, 0)?;
+
+        Ok(())
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         if self.state.is_64_bit {
             &AARCH64_CORE_REGSISTERS
@@ -1189,6 +1460,21 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         }
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        if address % 2 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        if self.state.is_64_bit {
+            self.read_cpu_memory_aarch64_16(address)
+        } else {
+            self.read_cpu_memory_aarch32_16(address)
+        }
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         // Find the word this is in and its byte offset
         let byte_offset = address % 4;
@@ -1217,6 +1503,14 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_16(address + ((i as u64) * 2))?;
+        }
+
+        Ok(())
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         for (i, byte) in data.iter_mut().enumerate() {
             *byte = self.read_word_8(address + (i as u64))?;
@@ -1245,6 +1539,21 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         }
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        if address % 2 != 0 {
+            return Err(Error::MemoryNotAligned {
+                address,
+                alignment: 2,
+            });
+        }
+
+        if self.state.is_64_bit {
+            self.write_cpu_memory_aarch64_16(address, data)
+        } else {
+            self.write_cpu_memory_aarch32_16(address, data)
+        }
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         // Find the word this is in and its byte offset
         let byte_offset = address % 4;
@@ -1274,6 +1583,14 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        for (i, word) in data.iter().enumerate() {
+            self.write_word_16(address + ((i as u64) * 2), *word)?;
+        }
+
+        Ok(())
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         for (i, byte) in data.iter().enumerate() {
             self.write_word_8(address + ((i as u64) * 4), *byte)?;
@@ -1354,6 +1671,10 @@ mod test {
             todo!()
         }
 
+        fn read_16(&mut self, _address: u64, _data: &mut [u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1389,6 +1710,10 @@ mod test {
             todo!()
         }
 
+        fn write_16(&mut self, _address: u64, _data: &[u16]) -> Result<(), ArmError> {
+            todo!()
+        }
+
         fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
             if self.expected_ops.is_empty() {
                 panic!(
@@ -1783,16 +2108,137 @@ mod test {
         );
     }
 
-    #[test]
-    fn armv8a_new() {
-        let mut probe = MockProbe::new(false);
+    fn add_read_memory_16_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_r0_expectation(probe, address as u32);
 
-        // Add expectations
-        add_status_expectations(&mut probe, true);
+        let mut edscr = Edscr(0);
+        edscr.set_ite(true);
+        edscr.set_txfull(true);
 
-        let mock_mem = Box::new(probe) as _;
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            prep_instr_for_itr_32(build_ldrh(1, 0, 2)),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
 
-        let mut state = CortexAState::new();
+        add_read_reg_expectations(probe, 1, value.into());
+    }
+
+    fn add_read_memory_16_aarch64_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_x0_expectation(probe, address);
+
+        let mut edscr = Edscr(0);
+        edscr.set_ite(true);
+        edscr.set_txfull(true);
+
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            aarch64::build_ldrh(1, 0, 2),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            aarch64::build_msr(2, 3, 0, 5, 0, 1),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+        probe.expected_read(
+            Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            value.into(),
+        );
+    }
+
+    fn add_write_memory_16_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_r0_expectation(probe, address as u32);
+
+        let mut edscr = Edscr(0);
+        edscr.set_ite(true);
+        edscr.set_rxfull(true);
+
+        probe.expected_write(
+            Dbgdtrrx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            value.into(),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            prep_instr_for_itr_32(build_mrc(14, 0, 1, 0, 5, 0)),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            prep_instr_for_itr_32(build_strh(1, 0, 2)),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+    }
+
+    fn add_write_memory_16_aarch64_expectations(probe: &mut MockProbe, address: u64, value: u16) {
+        add_set_x0_expectation(probe, address);
+
+        let mut edscr = Edscr(0);
+        edscr.set_ite(true);
+        edscr.set_rxfull(true);
+
+        probe.expected_write(
+            Dbgdtrtx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            0,
+        );
+        probe.expected_write(
+            Dbgdtrrx::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            value.into(),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            aarch64::build_mrs(2, 3, 0, 4, 0, 1),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+
+        probe.expected_write(
+            Editr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            aarch64::build_strh(1, 0, 2),
+        );
+        probe.expected_read(
+            Edscr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            edscr.into(),
+        );
+    }
+
+    #[test]
+    fn armv8a_new() {
+        let mut probe = MockProbe::new(false);
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut state = CortexAState::new();
 
         let core = Armv8a::new(
             mock_mem,
@@ -2450,6 +2896,168 @@ mod test {
         armv8a.clear_hw_breakpoint(0).unwrap();
     }
 
+    #[test]
+    fn armv8a_watchpoints() {
+        const WP_COUNT: u32 = 2;
+        let mut probe = MockProbe::new(false);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Read watchpoint count
+        let mut eddfr = Eddfr(0);
+        eddfr.set_wrps(WP_COUNT - 1);
+        probe.expected_read(
+            Eddfr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            eddfr.into(),
+        );
+
+        // Read WP controls and values
+        let mut dbgwcr = Dbgwcr(0);
+        dbgwcr.set_bas(0b0011_0000);
+        dbgwcr.set_lsc(0b10);
+        dbgwcr.set_e(true);
+        probe.expected_read(
+            Dbgwcr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            dbgwcr.into(),
+        );
+        probe.expected_read(
+            Dbgwvr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap(),
+            0x2000,
+        );
+        probe.expected_read(
+            Dbgwvr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 4,
+            0,
+        );
+
+        probe.expected_read(
+            Dbgwcr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 16,
+            0,
+        );
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        let results = armv8a.watchpoints().unwrap();
+        assert_eq!(
+            Some(Watchpoint {
+                address: 0x2004,
+                length: 2,
+                access: WatchpointAccess::Write,
+                value: None,
+            }),
+            results[0]
+        );
+        assert_eq!(None, results[1]);
+    }
+
+    #[test]
+    fn armv8a_set_watchpoint() {
+        let mut probe = MockProbe::new(false);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Update WP value and control
+        let mut dbgwcr = Dbgwcr(0);
+        // Match on all modes
+        dbgwcr.set_hmc(true);
+        dbgwcr.set_pac(0b11);
+        // Watch 16 bytes
+        dbgwcr.set_mask(4);
+        dbgwcr.set_bas(0xff);
+        // Loads and stores
+        dbgwcr.set_lsc(0b11);
+        // Enable
+        dbgwcr.set_e(true);
+
+        probe.expected_write(
+            Dbgwcr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 16,
+            0,
+        );
+        probe.expected_write(
+            Dbgwvr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 16,
+            0x2010,
+        );
+        probe.expected_write(
+            Dbgwvr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 16 + 4,
+            0,
+        );
+        probe.expected_write(
+            Dbgwcr::get_mmio_address_from_base(TEST_BASE_ADDRESS).unwrap() + 16,
+            dbgwcr.into(),
+        );
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        armv8a
+            .set_watchpoint(
+                1,
+                Watchpoint {
+                    address: 0x2010,
+                    length: 16,
+                    access: WatchpointAccess::ReadWrite,
+                    value: None,
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn armv8a_set_watchpoint_unaligned() {
+        let mut probe = MockProbe::new(false);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        // Crosses a doubleword boundary, and can't be masked
+        assert!(armv8a
+            .set_watchpoint(
+                0,
+                Watchpoint {
+                    address: 0x2006,
+                    length: 4,
+                    access: WatchpointAccess::Read,
+                    value: None,
+                },
+            )
+            .is_err());
+    }
+
     #[test]
     fn armv8a_read_word_32() {
         const MEMORY_VALUE: u32 = 0xBA5EBA11;
@@ -2577,4 +3185,128 @@ mod test {
 
         assert_eq!(0xBA, armv8a.read_word_8(MEMORY_ADDRESS).unwrap());
     }
+
+    #[test]
+    fn armv8a_read_word_16() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new(false);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Read memory
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_reg_expectations(&mut probe, 1, 0);
+        add_read_memory_16_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(MEMORY_VALUE, armv8a.read_word_16(MEMORY_ADDRESS).unwrap());
+    }
+
+    #[test]
+    fn armv8a_read_word_16_aarch64() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new(true);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Read memory
+        add_read_reg_64_expectations(&mut probe, 0, 0);
+        add_read_reg_64_expectations(&mut probe, 1, 0);
+        add_read_memory_16_aarch64_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(MEMORY_VALUE, armv8a.read_word_16(MEMORY_ADDRESS).unwrap());
+    }
+
+    #[test]
+    fn armv8a_write_word_16() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new(false);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Write memory
+        add_read_reg_expectations(&mut probe, 0, 0);
+        add_read_reg_expectations(&mut probe, 1, 0);
+        add_write_memory_16_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        armv8a.write_word_16(MEMORY_ADDRESS, MEMORY_VALUE).unwrap();
+    }
+
+    #[test]
+    fn armv8a_write_word_16_aarch64() {
+        const MEMORY_VALUE: u16 = 0xBA5E;
+        const MEMORY_ADDRESS: u64 = 0x1234567A;
+
+        let mut probe = MockProbe::new(true);
+        let mut state = CortexAState::new();
+
+        // Add expectations
+        add_status_expectations(&mut probe, true);
+
+        // Write memory
+        add_read_reg_64_expectations(&mut probe, 0, 0);
+        add_read_reg_64_expectations(&mut probe, 1, 0);
+        add_write_memory_16_aarch64_expectations(&mut probe, MEMORY_ADDRESS, MEMORY_VALUE);
+
+        let mock_mem = Box::new(probe) as _;
+
+        let mut armv8a = Armv8a::new(
+            mock_mem,
+            &mut state,
+            TEST_BASE_ADDRESS,
+            TEST_CTI_ADDRESS,
+            DefaultArmSequence::create(),
+            0,
+        )
+        .unwrap();
+
+        armv8a.write_word_16(MEMORY_ADDRESS, MEMORY_VALUE).unwrap();
+    }
 }
@@ -1,6 +1,6 @@
 //! Debug register definitions for ARMv8-A
 
-use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason};
+use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason, WatchpointCause};
 
 memory_mapped_bitfield_register! {
     /// EDSCR - Debug Status and Control Register
@@ -132,7 +132,7 @@ impl Edscr {
             // Reset catch.
             0b100111 => HaltReason::Exception,
             // Watchpoint
-            0b101011 => HaltReason::Watchpoint,
+            0b101011 => HaltReason::Watchpoint(WatchpointCause::Unknown),
             // HLT instruction - causes entry into Debug state.
             0b101111 => HaltReason::Breakpoint(BreakpointCause::Software),
             // Software access to debug register.
@@ -194,6 +194,60 @@ memory_mapped_bitfield_register! {
     pub e, set_e: 0;
 }
 
+memory_mapped_bitfield_register! {
+    /// DBGWVR - Watchpoint Value Register
+    pub struct Dbgwvr(u32);
+    0x800, "DBGWVR",
+    impl From;
+
+    /// Watchpoint address, bits [2:0] are ignored
+    pub value, set_value : 31, 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// DBGWCR - Watchpoint Control Register
+    pub struct Dbgwcr(u32);
+    0x808, "DBGWCR",
+    impl From;
+
+    /// Address range mask
+    pub mask, set_mask : 28, 24;
+
+    /// Watchpoint type, 0 for unlinked data address match
+    pub wt, set_wt : 20;
+
+    /// Linked breakpoint number
+    pub lbn, set_lbn : 19, 16;
+
+    /// Security state control
+    pub ssc, set_ssc : 15, 14;
+
+    /// Hyp mode control bit
+    pub hmc, set_hmc: 13;
+
+    /// Byte address select
+    pub bas, set_bas: 12, 5;
+
+    /// Load/store control. 0b01 matches loads, 0b10 stores and 0b11 both.
+    pub lsc, set_lsc: 4, 3;
+
+    /// Privileged access control
+    pub pac, set_pac: 2, 1;
+
+    /// Watchpoint enable
+    pub e, set_e: 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// EDWAR - External Debug Watchpoint Address Register
+    pub struct Edwar(u32);
+    0x030, "EDWAR",
+    impl From;
+
+    /// Data address of the access which triggered the watchpoint, lower half
+    pub address, _: 31, 0;
+}
+
 memory_mapped_bitfield_register! {
     /// EDDFR - External Debug Feature Register
     pub struct Eddfr(u32);
@@ -204,7 +258,7 @@ memory_mapped_bitfield_register! {
     pub ctx_cmps, _: 31, 28;
 
     /// Number of watchpoints, minus 1.
-    pub wrps, _: 23, 20;
+    pub wrps, set_wrps: 23, 20;
 
     /// Number of breakpoints, minus 1
     pub brps, set_brps: 15, 12;
@@ -1,7 +1,7 @@
 //! Register types and the core interface for armv8-M
 
 use super::{
-    cortex_m::{IdPfr1, Mvfr0},
+    cortex_m::{DwtVersion, IdPfr1, Mvfr0},
     registers::cortex_m::{
         CORTEX_M_CORE_REGSISTERS, CORTEX_M_WITH_FP_CORE_REGSISTERS, FP, PC, RA, SP,
     },
@@ -16,7 +16,7 @@ use crate::{
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType, HaltReason,
-    InstructionSet, MemoryInterface, MemoryMappedRegister,
+    InstructionSet, MemoryInterface, MemoryMappedRegister, Watchpoint,
 };
 use anyhow::Result;
 use bitfield::bitfield;
@@ -162,6 +162,11 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
                 );
             }
 
+            if let HaltReason::Watchpoint(_) = reason {
+                let cause = super::cortex_m::watchpoint_cause(&mut *self.memory, DwtVersion::V2)?;
+                reason = HaltReason::Watchpoint(cause);
+            }
+
             // Set the status so any semihosting operations will know we're halted
             self.set_core_status(CoreStatus::Halted(reason));
 
@@ -400,6 +405,26 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
         Ok(())
     }
 
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        super::cortex_m::watchpoints(&mut *self.memory, DwtVersion::V2)
+    }
+
+    fn set_watchpoint(&mut self, unit_index: usize, watchpoint: Watchpoint) -> Result<(), Error> {
+        super::cortex_m::set_watchpoint(&mut *self.memory, DwtVersion::V2, unit_index, watchpoint)
+    }
+
+    fn clear_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_watchpoint(&mut *self.memory, unit_index)
+    }
+
+    fn unit_reserved(&mut self, unit_index: usize) -> Result<bool, Error> {
+        super::cortex_m::watchpoint_unit_reserved(&mut *self.memory, DwtVersion::V2, unit_index)
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         if self.state.fp_present {
             &CORTEX_M_WITH_FP_CORE_REGSISTERS
@@ -497,6 +522,9 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
                     demcr.set_vc_sferr(true);
                 }
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -523,6 +551,9 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
                     demcr.set_vc_sferr(false);
                 }
             }
+            VectorCatchCondition::Interrupt => {
+                return Err(Error::NotImplemented("interrupt vector catch"));
+            }
         };
 
         self.memory
@@ -540,6 +571,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .read_word_32(address)
             .map_err(From::<ArmError>::from)
     }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.memory
+            .read_word_16(address)
+            .map_err(From::<ArmError>::from)
+    }
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.memory
             .read_word_8(address)
@@ -558,6 +595,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.memory
+            .read_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.memory
             .read_8(address, data)
@@ -582,6 +625,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.memory
+            .write_word_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.memory
             .write_word_8(address, data)
@@ -600,6 +649,12 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
             .map_err(From::<ArmError>::from)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.memory
+            .write_16(address, data)
+            .map_err(From::<ArmError>::from)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.memory
             .write_8(address, data)
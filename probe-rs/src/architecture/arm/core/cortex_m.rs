@@ -1,11 +1,13 @@
 //! Common functions and data types for Cortex-M core variants
 
 use crate::{
-    architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmError},
+    architecture::arm::{core::armv7m::Demcr, memory::adi_v5_memory_interface::ArmProbe, ArmError},
     core::RegisterId,
-    memory_mapped_bitfield_register, BreakpointCause, CoreInterface, Error, HaltReason,
-    MemoryMappedRegister, SemihostingCommand,
+    memory::valid_32bit_address,
+    memory_mapped_bitfield_register, semihosting, BreakpointCause, CoreInterface, Error,
+    HaltReason, MemoryMappedRegister, Watchpoint, WatchpointAccess, WatchpointCause,
 };
+use anyhow::anyhow;
 use std::time::{Duration, Instant};
 
 memory_mapped_bitfield_register! {
@@ -142,6 +144,281 @@ impl IdPfr1 {
     }
 }
 
+memory_mapped_bitfield_register! {
+    /// DWT Control Register
+    pub struct DwtCtrl(u32);
+    0xE000_1000, "DWT_CTRL",
+    impl From;
+    /// Number of comparators implemented.
+    pub numcomp, _: 31, 28;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Comparator Register of the first comparator.
+    ///
+    /// The registers of the other comparators follow with a stride of [`DWT_COMPARATOR_STRIDE`].
+    pub struct DwtComp(u32);
+    0xE000_1020, "DWT_COMP0",
+    impl From;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Mask Register of the first comparator. Not implemented on ARMv8-M.
+    pub struct DwtMask(u32);
+    0xE000_1024, "DWT_MASK0",
+    impl From;
+    /// Number of least significant address bits which are ignored by the comparator.
+    pub mask, set_mask: 4, 0;
+}
+
+memory_mapped_bitfield_register! {
+    /// DWT Function Register of the first comparator.
+    pub struct DwtFunction(u32);
+    0xE000_1028, "DWT_FUNCTION0",
+    impl From;
+    /// The comparator has matched since the register was last read. Cleared on read.
+    pub matched, _: 24;
+    /// Size of the watched data as a power of two, only used on ARMv8-M.
+    pub datavsize, set_datavsize: 11, 10;
+    /// Action on a match, only used on ARMv8-M.
+    pub action, set_action: 5, 4;
+    /// Comparator function on ARMv6-M and ARMv7-M, match type on ARMv8-M.
+    pub function, set_function: 3, 0;
+}
+
+/// Offset between the registers of two consecutive DWT comparators.
+const DWT_COMPARATOR_STRIDE: u64 = 0x10;
+
+/// The DWT comparators are programmed differently on ARMv8-M.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DwtVersion {
+    /// ARMv6-M and ARMv7-M, where the watched range is set using the mask register.
+    V1,
+    /// ARMv8-M, where the watched range is set using the data size in the function register.
+    V2,
+}
+
+impl DwtVersion {
+    /// Decode the access type of a comparator configured as data watchpoint.
+    ///
+    /// Returns `None` if the comparator is disabled, or used for something else, e.g. tracing.
+    fn watchpoint_access(self, function: DwtFunction) -> Option<WatchpointAccess> {
+        match self {
+            DwtVersion::V1 => match function.function() {
+                0b0101 => Some(WatchpointAccess::Read),
+                0b0110 => Some(WatchpointAccess::Write),
+                0b0111 => Some(WatchpointAccess::ReadWrite),
+                _ => None,
+            },
+            // Only comparators which generate a debug event are watchpoints.
+            DwtVersion::V2 if function.action() != 0b01 => None,
+            DwtVersion::V2 => match function.function() {
+                0b0110 => Some(WatchpointAccess::Read),
+                0b0101 => Some(WatchpointAccess::Write),
+                0b0100 => Some(WatchpointAccess::ReadWrite),
+                _ => None,
+            },
+        }
+    }
+
+    /// Get the function register value for a data watchpoint.
+    fn watchpoint_function(self, access: WatchpointAccess, size: u32) -> DwtFunction {
+        let mut function = DwtFunction(0);
+
+        match self {
+            DwtVersion::V1 => function.set_function(match access {
+                WatchpointAccess::Read => 0b0101,
+                WatchpointAccess::Write => 0b0110,
+                WatchpointAccess::ReadWrite => 0b0111,
+            }),
+            DwtVersion::V2 => {
+                // Generate a debug event on a match
+                function.set_action(0b01);
+                function.set_datavsize(size);
+                function.set_function(match access {
+                    WatchpointAccess::Read => 0b0110,
+                    WatchpointAccess::Write => 0b0101,
+                    WatchpointAccess::ReadWrite => 0b0100,
+                });
+            }
+        }
+
+        function
+    }
+}
+
+/// Returns the number of DWT comparators, which can be used as data watchpoints.
+pub(crate) fn available_watchpoint_units(memory: &mut dyn ArmProbe) -> Result<u32, Error> {
+    let ctrl = DwtCtrl(memory.read_word_32(DwtCtrl::get_mmio_address())?);
+
+    Ok(ctrl.numcomp())
+}
+
+/// Read the data watchpoints configured in the DWT comparators.
+///
+/// See docs on the [`CoreInterface::watchpoints`] trait.
+pub(crate) fn watchpoints(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+) -> Result<Vec<Option<Watchpoint>>, Error> {
+    let num_watchpoints = available_watchpoint_units(memory)? as usize;
+    let mut watchpoints = Vec::with_capacity(num_watchpoints);
+
+    for unit_index in 0..num_watchpoints {
+        let offset = unit_index as u64 * DWT_COMPARATOR_STRIDE;
+
+        let function = DwtFunction(memory.read_word_32(DwtFunction::get_mmio_address() + offset)?);
+
+        let Some(access) = version.watchpoint_access(function) else {
+            watchpoints.push(None);
+            continue;
+        };
+
+        let address = memory.read_word_32(DwtComp::get_mmio_address() + offset)?;
+
+        let size = match version {
+            DwtVersion::V1 => {
+                DwtMask(memory.read_word_32(DwtMask::get_mmio_address() + offset)?).mask()
+            }
+            DwtVersion::V2 => function.datavsize(),
+        };
+
+        watchpoints.push(Some(Watchpoint {
+            address: address as u64,
+            length: 1 << size,
+            access,
+            value: None,
+        }));
+    }
+
+    Ok(watchpoints)
+}
+
+/// Configure DWT comparator `unit_index` as data watchpoint.
+///
+/// The length of the watched range has to be a power of two, and the address has to be aligned to it.
+/// On ARMv8-M, the maximum length is 4 bytes.
+pub(crate) fn set_watchpoint(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+    unit_index: usize,
+    watchpoint: Watchpoint,
+) -> Result<(), Error> {
+    let address = valid_32bit_address(watchpoint.address)?;
+
+    if watchpoint.value.is_some() {
+        return Err(Error::NotImplemented("data value watchpoints"));
+    }
+
+    if !watchpoint.length.is_power_of_two() || watchpoint.address % watchpoint.length != 0 {
+        return Err(Error::Other(anyhow!(
+            "The watched range of {} bytes at 0x{:08x} is not a naturally aligned power of two",
+            watchpoint.length,
+            address
+        )));
+    }
+
+    let size = watchpoint.length.trailing_zeros();
+
+    if version == DwtVersion::V2 && size > 2 {
+        return Err(Error::Other(anyhow!(
+            "Watchpoints are limited to 4 bytes on ARMv8-M, {} bytes were requested",
+            watchpoint.length
+        )));
+    }
+
+    if watchpoint_unit_reserved(memory, version, unit_index)? {
+        return Err(Error::Other(anyhow!(
+            "DWT comparator {} is already in use, e.g. for data tracing",
+            unit_index
+        )));
+    }
+
+    // The DWT unit is only active if TRCENA is set. On ARMv6-M, the same bit is called DWTENA.
+    let mut demcr = Demcr(memory.read_word_32(Demcr::get_mmio_address())?);
+    demcr.set_trcena(true);
+    memory.write_word_32(Demcr::get_mmio_address(), demcr.into())?;
+
+    let offset = unit_index as u64 * DWT_COMPARATOR_STRIDE;
+
+    // Disable the comparator while it is reconfigured.
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, 0)?;
+    memory.write_word_32(DwtComp::get_mmio_address() + offset, address)?;
+
+    if version == DwtVersion::V1 {
+        let mut mask = DwtMask(0);
+        mask.set_mask(size);
+
+        let mask_address = DwtMask::get_mmio_address() + offset;
+        memory.write_word_32(mask_address, mask.into())?;
+
+        // The maximum mask size is implementation defined.
+        if DwtMask(memory.read_word_32(mask_address)?).mask() != size {
+            return Err(Error::Other(anyhow!(
+                "The DWT does not support watching {} bytes",
+                watchpoint.length
+            )));
+        }
+    }
+
+    let function = version.watchpoint_function(watchpoint.access, size);
+
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, function.into())?;
+
+    Ok(())
+}
+
+/// Check if DWT comparator `unit_index` is used for something other than a data watchpoint,
+/// e.g. for data tracing with SWV, so that it must not be used for a watchpoint.
+pub(crate) fn watchpoint_unit_reserved(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+    unit_index: usize,
+) -> Result<bool, Error> {
+    let offset = unit_index as u64 * DWT_COMPARATOR_STRIDE;
+
+    let function = DwtFunction(memory.read_word_32(DwtFunction::get_mmio_address() + offset)?);
+
+    Ok(function.function() != 0 && version.watchpoint_access(function).is_none())
+}
+
+/// Disable DWT comparator `unit_index`.
+pub(crate) fn clear_watchpoint(memory: &mut dyn ArmProbe, unit_index: usize) -> Result<(), Error> {
+    let offset = unit_index as u64 * DWT_COMPARATOR_STRIDE;
+
+    memory.write_word_32(DwtFunction::get_mmio_address() + offset, 0)?;
+
+    Ok(())
+}
+
+/// Determine which DWT comparator caused a watchpoint halt.
+///
+/// Call this if the core halted because of a watchpoint. As the `MATCHED` bits are
+/// cleared when they are read, this only works once per halt.
+pub(crate) fn watchpoint_cause(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+) -> Result<WatchpointCause, Error> {
+    let num_watchpoints = available_watchpoint_units(memory)? as usize;
+    let mut cause = WatchpointCause::Unknown;
+
+    // Read all function registers, to clear all MATCHED bits.
+    for unit_index in 0..num_watchpoints {
+        let offset = unit_index as u64 * DWT_COMPARATOR_STRIDE;
+
+        let function = DwtFunction(memory.read_word_32(DwtFunction::get_mmio_address() + offset)?);
+
+        if function.matched()
+            && version.watchpoint_access(function).is_some()
+            && cause == WatchpointCause::Unknown
+        {
+            cause = WatchpointCause::Unit(unit_index);
+        }
+    }
+
+    Ok(cause)
+}
+
 pub(crate) fn read_core_reg(memory: &mut dyn ArmProbe, addr: RegisterId) -> Result<u32, Error> {
     // Write the DCRSR value to select the register we want to read.
     let mut dcrsr_val = Dcrsr(0);
@@ -195,26 +472,11 @@ pub(crate) fn check_for_semihosting(
         // BKPT 0xAB -> we are semihosting
         let r0: u32 = core.read_core_reg(RegisterId(0))?.try_into()?;
         let r1: u32 = core.read_core_reg(RegisterId(1))?.try_into()?;
-        tracing::info!("Semihosting found pc={pc:#x} r0={r0:#x} r1={r1:#x}");
+        tracing::debug!("Semihosting found pc={pc:#x} r0={r0:#x} r1={r1:#x}");
         // This is defined by the ARM Semihosting Specification:
         // <https://github.com/ARM-software/abi-aa/blob/main/semihosting/semihosting.rst#the-semihosting-interface>
-        const SYS_EXIT: u32 = 0x18;
-        const SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT: u32 = 0x20026;
-        match (r0, r1) {
-            (SYS_EXIT, SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT) => {
-                reason = HaltReason::Breakpoint(BreakpointCause::Semihosting(
-                    SemihostingCommand::ExitSuccess,
-                ));
-            }
-            (SYS_EXIT, code) => {
-                reason = HaltReason::Breakpoint(BreakpointCause::Semihosting(
-                    SemihostingCommand::ExitError { code: code as u64 },
-                ));
-            }
-            _ => {
-                tracing::warn!("Unknown semihosting operation r0={r0:08x} r1={r1:08x}");
-            }
-        }
+        let command = semihosting::decode_command(core, r0, r1 as u64)?;
+        reason = HaltReason::Breakpoint(BreakpointCause::Semihosting(command));
     }
     Ok(reason)
 }
@@ -295,18 +295,7 @@ impl<'probe> ExceptionInterface for crate::architecture::arm::core::armv7m::Armv
         &mut self,
         stackframe_registers: &crate::debug::DebugRegisters,
     ) -> Result<String, crate::Error> {
-        // Load the provided xPSR register as a bitfield.
-        let exception_number = Xpsr(
-            stackframe_registers
-                .get_register_value_by_role(&crate::core::RegisterRole::ProcessorStatus)?
-                as u32,
-        )
-        .exception_number();
-
-        Ok(format!(
-            "{:?}",
-            ExceptionReason::from(exception_number).expanded_description(self)?
-        ))
+        exception_description(self, stackframe_registers)
     }
 
     fn exception_details(
@@ -316,3 +305,23 @@ impl<'probe> ExceptionInterface for crate::architecture::arm::core::armv7m::Armv
         exception_details(self, stackframe_registers)
     }
 }
+
+/// Decode the exception number of the `stackframe_registers` into a human readable description,
+/// using the fault status registers of the `core` where possible.
+pub(crate) fn exception_description<T: CoreInterface>(
+    core: &mut T,
+    stackframe_registers: &crate::debug::DebugRegisters,
+) -> Result<String, crate::Error> {
+    // Load the provided xPSR register as a bitfield.
+    let exception_number = Xpsr(
+        stackframe_registers
+            .get_register_value_by_role(&crate::core::RegisterRole::ProcessorStatus)?
+            as u32,
+    )
+    .exception_number();
+
+    Ok(format!(
+        "{:?}",
+        ExceptionReason::from(exception_number).expanded_description(core)?
+    ))
+}
@@ -1,7 +1,6 @@
 use crate::{
     core::{ExceptionInfo, ExceptionInterface},
     debug::DebugRegisters,
-    memory::MemoryInterface,
     memory_mapped_bitfield_register, CoreInterface, Error, MemoryMappedRegister, RegisterValue,
 };
 use bitfield::bitfield;
@@ -389,71 +388,95 @@ impl<'probe> ExceptionInterface for crate::architecture::arm::core::armv8m::Armv
         &mut self,
         stackframe_registers: &crate::debug::DebugRegisters,
     ) -> Result<crate::debug::DebugRegisters, crate::Error> {
-        let mut calling_stack_registers = vec![0u32; EXCEPTION_STACK_REGISTERS.len()];
-        let stack_frame_return_address: u32 = get_stack_frame_return_address(stackframe_registers)?;
-        let exc_return = ExcReturn(stack_frame_return_address);
-        let sp_value = if exc_return.is_exception_flag() == 0xFF {
-            let stack_info = (
-                exc_return.use_secure_stack(),
-                exc_return.stack_pointer_selection(),
-            );
-
-            let sp_reg_id = match stack_info {
-                (false, false) => 0b00011000, // non-secure, main stack pointer
-                (false, true) => 0b00011001,  // non-secure, process stack pointer
-                (true, false) => 0b00011010,  // secure, main stack pointer
-                (true, true) => 0b00011011,   // secure, process stack pointer
-            };
-            self.read_core_reg(sp_reg_id.into())?.try_into()?
-        } else {
-            stackframe_registers
-                .get_register_value_by_role(&crate::core::RegisterRole::StackPointer)?
-        };
-
-        self.read_32(sp_value, &mut calling_stack_registers)?;
-        let mut calling_frame_registers = stackframe_registers.clone();
-        for (i, register_role) in EXCEPTION_STACK_REGISTERS.iter().enumerate() {
-            calling_frame_registers
-                .get_register_mut_by_role(register_role)?
-                .value = Some(RegisterValue::U32(calling_stack_registers[i]));
-        }
-        Ok(calling_frame_registers)
+        calling_frame_registers(self, stackframe_registers)
     }
 
     fn exception_description(
         &mut self,
         stackframe_registers: &crate::debug::DebugRegisters,
     ) -> Result<String, crate::Error> {
-        // Load the provided xPSR register as a bitfield.
-        let exception_number = Xpsr(
-            stackframe_registers
-                .get_register_value_by_role(&crate::core::RegisterRole::ProcessorStatus)?
-                as u32,
-        )
-        .exception_number();
-
-        Ok(format!(
-            "{:?}",
-            ExceptionReason::from(exception_number).expanded_description(self)?
-        ))
+        exception_description(self, stackframe_registers)
     }
 
     fn exception_details(
         &mut self,
         stackframe_registers: &DebugRegisters,
     ) -> Result<Option<ExceptionInfo>, Error> {
-        let stack_frame_return_address: u32 = get_stack_frame_return_address(stackframe_registers)?;
-        if ExcReturn(stack_frame_return_address).is_exception_flag() == 0xFF {
-            // This is an exception frame.
+        exception_details(self, stackframe_registers)
+    }
+}
 
-            Ok(Some(ExceptionInfo {
-                description: self.exception_description(stackframe_registers)?,
-                calling_frame_registers: self.calling_frame_registers(stackframe_registers)?,
-            }))
-        } else {
-            // This is a normal function return.
-            Ok(None)
-        }
+/// Read the registers of the calling frame from the exception stack frame.
+pub(crate) fn calling_frame_registers<T: CoreInterface>(
+    core: &mut T,
+    stackframe_registers: &crate::debug::DebugRegisters,
+) -> Result<crate::debug::DebugRegisters, crate::Error> {
+    let mut calling_stack_registers = vec![0u32; EXCEPTION_STACK_REGISTERS.len()];
+    let stack_frame_return_address: u32 = get_stack_frame_return_address(stackframe_registers)?;
+    let exc_return = ExcReturn(stack_frame_return_address);
+    let sp_value = if exc_return.is_exception_flag() == 0xFF {
+        let stack_info = (
+            exc_return.use_secure_stack(),
+            exc_return.stack_pointer_selection(),
+        );
+
+        let sp_reg_id = match stack_info {
+            (false, false) => 0b00011000, // non-secure, main stack pointer
+            (false, true) => 0b00011001,  // non-secure, process stack pointer
+            (true, false) => 0b00011010,  // secure, main stack pointer
+            (true, true) => 0b00011011,   // secure, process stack pointer
+        };
+        core.read_core_reg(sp_reg_id.into())?.try_into()?
+    } else {
+        stackframe_registers.get_register_value_by_role(&crate::core::RegisterRole::StackPointer)?
+    };
+
+    core.read_32(sp_value, &mut calling_stack_registers)?;
+    let mut calling_frame_registers = stackframe_registers.clone();
+    for (i, register_role) in EXCEPTION_STACK_REGISTERS.iter().enumerate() {
+        calling_frame_registers
+            .get_register_mut_by_role(register_role)?
+            .value = Some(RegisterValue::U32(calling_stack_registers[i]));
+    }
+    Ok(calling_frame_registers)
+}
+
+/// Decode the exception number of the `stackframe_registers` into a human readable description,
+/// using the fault status registers of the `core` where possible.
+pub(crate) fn exception_description<T: CoreInterface>(
+    core: &mut T,
+    stackframe_registers: &crate::debug::DebugRegisters,
+) -> Result<String, crate::Error> {
+    // Load the provided xPSR register as a bitfield.
+    let exception_number = Xpsr(
+        stackframe_registers
+            .get_register_value_by_role(&crate::core::RegisterRole::ProcessorStatus)?
+            as u32,
+    )
+    .exception_number();
+
+    Ok(format!(
+        "{:?}",
+        ExceptionReason::from(exception_number).expanded_description(core)?
+    ))
+}
+
+/// Decode the exception information.
+pub(crate) fn exception_details<T: CoreInterface>(
+    core: &mut T,
+    stackframe_registers: &DebugRegisters,
+) -> Result<Option<ExceptionInfo>, Error> {
+    let stack_frame_return_address: u32 = get_stack_frame_return_address(stackframe_registers)?;
+    if ExcReturn(stack_frame_return_address).is_exception_flag() == 0xFF {
+        // This is an exception frame.
+
+        Ok(Some(ExceptionInfo {
+            description: core.exception_description(stackframe_registers)?,
+            calling_frame_registers: core.calling_frame_registers(stackframe_registers)?,
+        }))
+    } else {
+        // This is a normal function return.
+        Ok(None)
     }
 }
 
@@ -81,6 +81,28 @@ pub(crate) mod aarch32 {
         ret
     }
 
+    pub(crate) fn build_ldrh(reg_target: u16, reg_source: u16, imm: u8) -> u32 {
+        let mut ret = 0b1110_0000_1101_0000_0000_0000_1011_0000;
+
+        ret |= (reg_source as u32) << 16;
+        ret |= (reg_target as u32) << 12;
+        ret |= (imm as u32 & 0xF0) << 4;
+        ret |= imm as u32 & 0x0F;
+
+        ret
+    }
+
+    pub(crate) fn build_strh(reg_target: u16, reg_source: u16, imm: u8) -> u32 {
+        let mut ret = 0b1110_0000_1100_0000_0000_0000_1011_0000;
+
+        ret |= (reg_source as u32) << 16;
+        ret |= (reg_target as u32) << 12;
+        ret |= (imm as u32 & 0xF0) << 4;
+        ret |= imm as u32 & 0x0F;
+
+        ret
+    }
+
     pub(crate) fn build_mrs(reg: u16) -> u32 {
         let mut ret = 0b1110_0001_0000_1111_0000_0000_0000_0000;
 
@@ -184,6 +206,22 @@ pub(crate) mod aarch32 {
             // VMOV r1, r2, d3
             assert_eq!(0xEC521B13, instr);
         }
+
+        #[test]
+        fn gen_ldrh_instruction() {
+            let instr = build_ldrh(2, 3, 0x12);
+
+            // LDRH r2, [r3], #0x12
+            assert_eq!(0xE0D321B2, instr);
+        }
+
+        #[test]
+        fn gen_strh_instruction() {
+            let instr = build_strh(2, 3, 0x12);
+
+            // STRH r2, [r3], #0x12
+            assert_eq!(0xE0C321B2, instr);
+        }
     }
 }
 
@@ -211,6 +249,26 @@ pub(crate) mod thumb2 {
         ret
     }
 
+    pub(crate) fn build_ldrh(reg_target: u16, reg_source: u16, imm: u8) -> u32 {
+        let mut ret = 0b1111_1000_0011_0000_0000_1011_0000_0000;
+
+        ret |= (reg_source as u32) << 16;
+        ret |= (reg_target as u32) << 12;
+        ret |= imm as u32;
+
+        ret
+    }
+
+    pub(crate) fn build_strh(reg_target: u16, reg_source: u16, imm: u8) -> u32 {
+        let mut ret = 0b1111_1000_0010_0000_0000_1011_0000_0000;
+
+        ret |= (reg_source as u32) << 16;
+        ret |= (reg_target as u32) << 12;
+        ret |= imm as u32;
+
+        ret
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -230,6 +288,22 @@ pub(crate) mod thumb2 {
             // STR r2, [r3], #4
             assert_eq!(0xF8432B04, instr);
         }
+
+        #[test]
+        fn gen_ldrh_instruction() {
+            let instr = build_ldrh(2, 3, 4);
+
+            // LDRH r2, [r3], #4
+            assert_eq!(0xF8332B04, instr);
+        }
+
+        #[test]
+        fn gen_strh_instruction() {
+            let instr = build_strh(2, 3, 4);
+
+            // STRH r2, [r3], #4
+            assert_eq!(0xF8232B04, instr);
+        }
     }
 }
 
@@ -254,6 +328,16 @@ pub(crate) mod aarch64 {
         ret
     }
 
+    pub(crate) fn build_ldrh(reg_target: u16, reg_source: u16, imm: u16) -> u32 {
+        let mut ret = 0b0111_1000_0100_0000_0000_0100_0000_0000;
+
+        ret |= (imm as u32) << 12;
+        ret |= (reg_source as u32) << 5;
+        ret |= reg_target as u32;
+
+        ret
+    }
+
     pub(crate) fn build_mrs(op0: u8, op1: u8, crn: u8, crm: u8, op2: u8, reg: u16) -> u32 {
         let mut ret = 0b1101_0101_0011_0000_0000_0000_0000_0000;
 
@@ -300,6 +384,16 @@ pub(crate) mod aarch64 {
         ret
     }
 
+    pub(crate) fn build_strh(reg_target: u16, reg_source: u16, imm: u16) -> u32 {
+        let mut ret = 0b0111_1000_0000_0000_0000_0100_0000_0000;
+
+        ret |= (imm as u32) << 12;
+        ret |= (reg_source as u32) << 5;
+        ret |= reg_target as u32;
+
+        ret
+    }
+
     pub(crate) fn build_ins_fp_to_gp(reg_target: u16, reg_source: u16, index: u16) -> u32 {
         let mut ret = 0b0100_1110_0000_1000_0011_1100_0000_0000;
 
@@ -339,6 +433,14 @@ pub(crate) mod aarch64 {
             assert_eq!(0xB8404462, instr);
         }
 
+        #[test]
+        fn gen_ldrh_instruction() {
+            let instr = build_ldrh(2, 3, 4);
+
+            // LDRH w2, [x3], #4
+            assert_eq!(0x78404462, instr);
+        }
+
         #[test]
         fn gen_msr_instruction() {
             let instr = build_msr(2, 3, 4, 1, 2, 3);
@@ -371,6 +473,14 @@ pub(crate) mod aarch64 {
             assert_eq!(0xB8004462, instr);
         }
 
+        #[test]
+        fn gen_strh_instruction() {
+            let instr = build_strh(2, 3, 4);
+
+            // STRH w2, [x3], #4
+            assert_eq!(0x78004462, instr);
+        }
+
         #[test]
         fn gen_ins_gp_to_fp_instruction() {
             let instr = build_ins_gp_to_fp(3, 2, 1);
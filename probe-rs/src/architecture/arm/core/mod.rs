@@ -1,6 +1,6 @@
 use crate::{
     core::{BreakpointCause, RegisterValue},
-    memory_mapped_bitfield_register, CoreStatus, HaltReason,
+    memory_mapped_bitfield_register, CoreStatus, HaltReason, WatchpointCause,
 };
 
 pub mod armv6m;
@@ -77,7 +77,7 @@ impl Dfsr {
         } else if self.external() {
             HaltReason::External
         } else if self.dwttrap() {
-            HaltReason::Watchpoint
+            HaltReason::Watchpoint(WatchpointCause::Unknown)
         } else if self.halted() {
             HaltReason::Request
         } else if self.vcatch() {
@@ -169,6 +169,64 @@ impl CortexAState {
     }
 }
 
+/// Address match configuration of a watchpoint on ARMv7-A and ARMv8-A cores.
+///
+/// Ranges within one `granule` are selected with the byte address select field,
+/// larger ranges with the address mask. The granule is 4 bytes on ARMv7-A and 8 bytes on ARMv8-A.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WatchpointMatch {
+    /// Value for the watchpoint value register
+    pub address: u64,
+    /// Byte address select
+    pub bas: u32,
+    /// Address mask, as a power of two
+    pub mask: u32,
+}
+
+impl WatchpointMatch {
+    pub(crate) fn new(address: u64, length: u64, granule: u64) -> Result<Self, crate::Error> {
+        let offset = address % granule;
+
+        if length > 0 && offset + length <= granule {
+            return Ok(Self {
+                address: address - offset,
+                bas: ((1 << length) - 1) << offset,
+                mask: 0,
+            });
+        }
+
+        // The smallest range which can be masked is 8 bytes.
+        if length >= 8 && length.is_power_of_two() && address % length == 0 {
+            return Ok(Self {
+                address,
+                bas: (1 << granule) - 1,
+                mask: length.trailing_zeros(),
+            });
+        }
+
+        Err(crate::Error::Other(anyhow::anyhow!(
+            "The watched range of {} bytes at {:#010x} is neither contained in {} bytes nor a naturally aligned power of two",
+            length,
+            address,
+            granule
+        )))
+    }
+
+    /// Start address and length of the watched range.
+    pub(crate) fn range(&self) -> (u64, u64) {
+        if self.mask != 0 {
+            (self.address, 1 << self.mask)
+        } else if self.bas == 0 {
+            (self.address, 0)
+        } else {
+            let offset = self.bas.trailing_zeros();
+            let length = (self.bas >> offset).trailing_ones();
+
+            (self.address + offset as u64, length as u64)
+        }
+    }
+}
+
 /// Core implementations should call this function when they
 /// wish to update the [`CoreStatus`] of their core.
 ///
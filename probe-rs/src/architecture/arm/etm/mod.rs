@@ -0,0 +1,179 @@
+//! Decoding of the instruction trace generated by the ETM.
+//!
+//! The ETM is configured by [`Session::setup_instruction_trace`] to broadcast the target
+//! address of every taken branch. Between branches, the executed instructions are
+//! reconstructed by following the program code, which only requires the size of each
+//! instruction.
+//!
+//! Decoding is supported for the ETMv3 protocol, used by the Cortex-M3 and Cortex-M4.
+//!
+//! [`Session::setup_instruction_trace`]: crate::Session::setup_instruction_trace
+
+mod packet;
+
+pub use packet::{Atom, EtmPacket, ExceptionInfo, ISyncReason, PacketDecoder};
+
+use object::{Object, ObjectSection, SectionKind};
+
+/// An error occurred while decoding the instruction trace.
+#[derive(Debug, thiserror::Error)]
+pub enum EtmError {
+    /// The program could not be read from the ELF file.
+    #[error("Failed to read the program from the ELF file.")]
+    Elf(#[from] object::read::Error),
+}
+
+/// The code of a program, used to follow the executed instructions.
+#[derive(Debug, Default)]
+pub struct ProgramImage {
+    sections: Vec<(u64, Vec<u8>)>,
+}
+
+impl ProgramImage {
+    /// Create an empty program image.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a program image from the code sections of an ELF file.
+    pub fn from_elf(elf: &[u8]) -> Result<Self, EtmError> {
+        let file = object::File::parse(elf)?;
+
+        let mut image = Self::new();
+        for section in file.sections() {
+            if section.kind() == SectionKind::Text && section.address() != 0 {
+                image.add_section(section.address(), section.data()?.to_vec());
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Add the code located at `address` to the image.
+    pub fn add_section(&mut self, address: u64, code: Vec<u8>) {
+        self.sections.push((address, code));
+    }
+
+    /// The size of the Thumb instruction at `address`, or `None` if the address is not part of
+    /// the image.
+    fn instruction_size(&self, address: u64) -> Option<u64> {
+        let (start, code) = self
+            .sections
+            .iter()
+            .find(|(start, code)| (*start..*start + code.len() as u64).contains(&address))?;
+
+        let offset = (address - start) as usize;
+        let halfword = u16::from_le_bytes([code[offset], *code.get(offset + 1)?]);
+
+        // 32-bit Thumb instructions start with 0b11101, 0b11110 or 0b11111.
+        if matches!(halfword >> 11, 0b11101..=0b11111) {
+            Some(4)
+        } else {
+            Some(2)
+        }
+    }
+}
+
+/// Reconstructs the addresses of the executed instructions from an ETMv3 trace stream.
+#[derive(Debug)]
+pub struct InstructionTracer<'a> {
+    image: &'a ProgramImage,
+    decoder: PacketDecoder,
+    /// The address of the next instruction, if known.
+    address: Option<u64>,
+}
+
+impl<'a> InstructionTracer<'a> {
+    /// Create a tracer which follows the code in `image`.
+    pub fn new(image: &'a ProgramImage) -> Self {
+        Self {
+            image,
+            decoder: PacketDecoder::new(),
+            address: None,
+        }
+    }
+
+    /// Process the next chunk of the trace stream, and return the addresses of the
+    /// instructions executed in it.
+    pub fn push(&mut self, data: &[u8]) -> Vec<u64> {
+        let mut executed = Vec::new();
+
+        for packet in self.decoder.push(data) {
+            match packet {
+                EtmPacket::ISync { address, .. } => self.address = Some(address as u64),
+                EtmPacket::BranchAddress { address, .. } => self.address = Some(address as u64),
+                EtmPacket::Atoms(atoms) => {
+                    for atom in atoms {
+                        let Some(address) = self.address else {
+                            break;
+                        };
+
+                        let Some(size) = self.image.instruction_size(address) else {
+                            tracing::debug!(
+                                "Instruction at {:#010x} is not part of the program, waiting for the next address",
+                                address
+                            );
+                            self.address = None;
+                            break;
+                        };
+
+                        if atom == Atom::E {
+                            executed.push(address);
+                        }
+                        self.address = Some(address + size);
+                    }
+                }
+                EtmPacket::ASync
+                | EtmPacket::ExceptionReturn
+                | EtmPacket::Trigger
+                | EtmPacket::CycleCount(_)
+                | EtmPacket::Timestamp(_)
+                | EtmPacket::Ignore => (),
+            }
+        }
+
+        executed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn follow_atoms_and_branches() {
+        let mut image = ProgramImage::new();
+        // 0x100: movs r0, #0
+        // 0x102: bl 0x10A
+        // 0x106: b 0x100
+        // 0x108: nop
+        // 0x10A: adds r0, #1
+        // 0x10C: bx lr
+        image.add_section(
+            0x100,
+            vec![
+                0x00, 0x20, 0x00, 0xF0, 0x02, 0xF8, 0xFB, 0xE7, 0x00, 0xBF, 0x01, 0x30, 0x70, 0x47,
+            ],
+        );
+
+        let mut tracer = InstructionTracer::new(&image);
+
+        let mut data = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x80];
+        // Atoms before the first I-sync are ignored.
+        data.push(0b1000_0100);
+        // I-sync at 0x100
+        data.extend_from_slice(&[0x08, 0x20, 0x01, 0x01, 0x00, 0x00]);
+        // movs, bl
+        data.push(0b1000_1000);
+        // Branch to 0x10A
+        data.extend_from_slice(&[0x80 | (0x05 << 1) | 1, 0x80 | 0x02, 0x80, 0x80, 0x10]);
+        // adds, bx
+        data.push(0b1000_1000);
+        // Branch to 0x106
+        data.push((0x03 << 1) | 1);
+        // b
+        data.push(0b1000_0100);
+
+        assert_eq!(tracer.push(&data), [0x100, 0x102, 0x10A, 0x10C, 0x106]);
+    }
+}
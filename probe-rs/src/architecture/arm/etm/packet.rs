@@ -0,0 +1,487 @@
+//! Parser for the ETMv3 packet protocol.
+//!
+//! Only instruction trace without cycle accurate tracing and without context IDs is
+//! supported, which is how probe-rs configures the ETM. The alternative branch address
+//! encoding, used by the ETM of the Cortex-M3 and Cortex-M4, is expected.
+
+/// The header of an A-sync packet is a sequence of at least five zero bytes.
+const A_SYNC_ZEROS: usize = 5;
+/// The last byte of an A-sync packet.
+const A_SYNC_END: u8 = 0x80;
+
+const HEADER_CYCLE_COUNT: u8 = 0x04;
+const HEADER_I_SYNC: u8 = 0x08;
+const HEADER_TRIGGER: u8 = 0x0C;
+const HEADER_VMID: u8 = 0x3C;
+const HEADER_TIMESTAMP: u8 = 0x42;
+const HEADER_TIMESTAMP_ALT: u8 = 0x46;
+const HEADER_IGNORE: u8 = 0x66;
+const HEADER_CONTEXT_ID: u8 = 0x6E;
+const HEADER_I_SYNC_CYCLE_COUNT: u8 = 0x70;
+const HEADER_EXCEPTION_RETURN: u8 = 0x76;
+
+/// Whether an instruction passed its condition code check.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Atom {
+    /// The instruction was executed.
+    E,
+    /// The instruction failed its condition code check.
+    N,
+}
+
+/// The reason for an instruction synchronization packet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ISyncReason {
+    /// Periodic synchronization.
+    Periodic,
+    /// Tracing was enabled.
+    TracingEnabled,
+    /// Tracing restarted after an overflow of the ETM FIFO.
+    Overflow,
+    /// The core exited debug state.
+    DebugExit,
+}
+
+/// An exception traced together with a branch address.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExceptionInfo {
+    /// The exception number.
+    pub number: u16,
+    /// The instruction of the last E atom was cancelled by the exception and not executed.
+    pub cancelled: bool,
+}
+
+/// A packet of the ETMv3 protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EtmPacket {
+    /// Alignment synchronization.
+    ASync,
+    /// Instruction synchronization, giving the address of the next instruction.
+    ISync {
+        /// The address of the next instruction to be executed.
+        address: u32,
+        /// Why the synchronization packet was emitted.
+        reason: ISyncReason,
+    },
+    /// A sequence of executed and not executed instructions.
+    Atoms(Vec<Atom>),
+    /// The last E atom was a branch to `address`.
+    BranchAddress {
+        /// The target address of the branch.
+        address: u32,
+        /// The exception which caused the branch, if any.
+        exception: Option<ExceptionInfo>,
+    },
+    /// The core returned from an exception.
+    ExceptionReturn,
+    /// The trigger condition occurred.
+    Trigger,
+    /// A cycle count.
+    CycleCount(u32),
+    /// A timestamp.
+    Timestamp(u64),
+    /// A packet which carries no trace information.
+    Ignore,
+}
+
+/// Result of parsing a packet from the start of a buffer.
+enum Parsed {
+    /// A complete packet of the given length.
+    Packet(EtmPacket, usize),
+    /// More data is required to parse the packet.
+    Incomplete,
+    /// The header is not valid.
+    Invalid,
+}
+
+/// A streaming parser for ETMv3 packets.
+///
+/// Data before the first A-sync packet, and after an invalid packet until the next A-sync
+/// packet, is discarded.
+#[derive(Debug, Default)]
+pub struct PacketDecoder {
+    buffer: Vec<u8>,
+    synchronized: bool,
+    /// The address of the last branch address packet, used to decompress branch addresses.
+    last_address: u32,
+}
+
+impl PacketDecoder {
+    /// Create a new decoder, which waits for an A-sync packet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process the next chunk of the trace stream, and return the completed packets.
+    pub fn push(&mut self, data: &[u8]) -> Vec<EtmPacket> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.extend_from_slice(data);
+
+        let mut packets = Vec::new();
+        let mut offset = 0;
+
+        while offset < buffer.len() {
+            let rest = &buffer[offset..];
+
+            if !self.synchronized {
+                match find_a_sync(rest) {
+                    Some((position, length)) => {
+                        offset += position + length;
+                        self.synchronized = true;
+                        packets.push(EtmPacket::ASync);
+                    }
+                    None => {
+                        // Keep trailing zeros, which could be the start of an A-sync packet.
+                        let zeros = rest.iter().rev().take_while(|byte| **byte == 0).count();
+                        offset += rest.len() - zeros;
+                        break;
+                    }
+                }
+                continue;
+            }
+
+            match self.parse(rest) {
+                Parsed::Packet(packet, length) => {
+                    offset += length;
+                    packets.push(packet);
+                }
+                Parsed::Incomplete => break,
+                Parsed::Invalid => {
+                    tracing::warn!(
+                        "Invalid ETM packet header {:#04x}, waiting for synchronization",
+                        rest[0]
+                    );
+                    self.synchronized = false;
+                    offset += 1;
+                }
+            }
+        }
+
+        buffer.drain(..offset);
+        self.buffer = buffer;
+
+        packets
+    }
+
+    fn parse(&mut self, data: &[u8]) -> Parsed {
+        let header = data[0];
+
+        if header & 0b1 == 1 {
+            return self.parse_branch_address(data);
+        }
+
+        if header & 0x80 != 0 {
+            return match parse_p_header(header) {
+                Some(atoms) => Parsed::Packet(EtmPacket::Atoms(atoms), 1),
+                None => Parsed::Invalid,
+            };
+        }
+
+        match header {
+            0x00 => match find_a_sync(data) {
+                Some((0, length)) => Parsed::Packet(EtmPacket::ASync, length),
+                Some(_) => Parsed::Invalid,
+                None if data.iter().all(|byte| *byte == 0) => Parsed::Incomplete,
+                None => Parsed::Invalid,
+            },
+            HEADER_I_SYNC => {
+                let Some(packet) = data.get(..6) else {
+                    return Parsed::Incomplete;
+                };
+                Parsed::Packet(parse_i_sync(packet[1], &packet[2..]), 6)
+            }
+            HEADER_I_SYNC_CYCLE_COUNT => {
+                let Some(count) = continuation_length(&data[1..], 5) else {
+                    return Parsed::Incomplete;
+                };
+                let Some(packet) = data.get(1 + count..1 + count + 5) else {
+                    return Parsed::Incomplete;
+                };
+                Parsed::Packet(parse_i_sync(packet[0], &packet[1..]), 1 + count + 5)
+            }
+            HEADER_CYCLE_COUNT => match continuation_length(&data[1..], 5) {
+                Some(length) => Parsed::Packet(
+                    EtmPacket::CycleCount(continuation_value(&data[1..1 + length]) as u32),
+                    1 + length,
+                ),
+                None => Parsed::Incomplete,
+            },
+            HEADER_TIMESTAMP | HEADER_TIMESTAMP_ALT => match continuation_length(&data[1..], 9) {
+                Some(length) => Parsed::Packet(
+                    EtmPacket::Timestamp(continuation_value(&data[1..1 + length])),
+                    1 + length,
+                ),
+                None => Parsed::Incomplete,
+            },
+            HEADER_VMID => match data.len() {
+                1 => Parsed::Incomplete,
+                _ => Parsed::Packet(EtmPacket::Ignore, 2),
+            },
+            HEADER_TRIGGER => Parsed::Packet(EtmPacket::Trigger, 1),
+            HEADER_IGNORE | HEADER_CONTEXT_ID => Parsed::Packet(EtmPacket::Ignore, 1),
+            HEADER_EXCEPTION_RETURN => Parsed::Packet(EtmPacket::ExceptionReturn, 1),
+            _ => Parsed::Invalid,
+        }
+    }
+
+    /// Parse a branch address packet in Thumb state, using the alternative encoding.
+    fn parse_branch_address(&mut self, data: &[u8]) -> Parsed {
+        let mut address = self.last_address;
+        let mut exception_follows = false;
+        let mut length = 0;
+
+        for (index, byte) in data.iter().enumerate().take(5) {
+            let byte = *byte as u32;
+            length = index + 1;
+
+            let continues = byte & 0x80 != 0;
+
+            let (shift, bits) = match index {
+                0 => (1, 6),
+                4 => {
+                    exception_follows = byte & 0x40 != 0;
+                    (28, 4)
+                }
+                _ if continues => (7 * index, 7),
+                _ => {
+                    exception_follows = byte & 0x40 != 0;
+                    (7 * index, 6)
+                }
+            };
+
+            let field = if index == 0 { byte >> 1 } else { byte };
+            let mask = ((1 << bits) - 1) << shift;
+            address = (address & !mask) | ((field << shift) & mask);
+
+            if index == 4 || !continues {
+                break;
+            }
+        }
+
+        let address_complete = match data.get(length - 1) {
+            Some(byte) => length == 5 || byte & 0x80 == 0,
+            None => false,
+        };
+        if !address_complete {
+            return Parsed::Incomplete;
+        }
+
+        let mut exception = None;
+        if exception_follows {
+            let Some(count) = continuation_length(&data[length..], 3) else {
+                return Parsed::Incomplete;
+            };
+            let bytes = &data[length..length + count];
+
+            let mut number = ((bytes[0] >> 1) & 0xF) as u16;
+            if let Some(byte) = bytes.get(1) {
+                number |= ((byte & 0x1F) as u16) << 4;
+            }
+
+            exception = Some(ExceptionInfo {
+                number,
+                cancelled: bytes[0] & 0x20 != 0,
+            });
+            length += count;
+        }
+
+        self.last_address = address;
+
+        Parsed::Packet(EtmPacket::BranchAddress { address, exception }, length)
+    }
+}
+
+/// Find an A-sync packet, returning its position and length.
+fn find_a_sync(data: &[u8]) -> Option<(usize, usize)> {
+    let mut zeros = 0;
+
+    for (index, byte) in data.iter().enumerate() {
+        match *byte {
+            0 => zeros += 1,
+            A_SYNC_END if zeros >= A_SYNC_ZEROS => {
+                return Some((index - zeros, zeros + 1));
+            }
+            _ => zeros = 0,
+        }
+    }
+
+    None
+}
+
+/// Decode a P-header in the non cycle accurate format.
+fn parse_p_header(header: u8) -> Option<Vec<Atom>> {
+    match header & 0b11 {
+        // Format 1: 1NEEEE00
+        0b00 => {
+            let executed = (header >> 2) & 0xF;
+            let mut atoms = vec![Atom::E; executed as usize];
+            if header & 0x40 != 0 {
+                atoms.push(Atom::N);
+            }
+            Some(atoms)
+        }
+        // Format 2: 1000FF10
+        0b10 if header & 0x70 == 0 => {
+            let atom = |bit: u8| {
+                if header & (1 << bit) != 0 {
+                    Atom::N
+                } else {
+                    Atom::E
+                }
+            };
+            Some(vec![atom(3), atom(2)])
+        }
+        _ => None,
+    }
+}
+
+fn parse_i_sync(info: u8, address: &[u8]) -> EtmPacket {
+    let reason = match (info >> 5) & 0b11 {
+        0b00 => ISyncReason::Periodic,
+        0b01 => ISyncReason::TracingEnabled,
+        0b10 => ISyncReason::Overflow,
+        _ => ISyncReason::DebugExit,
+    };
+
+    let address = u32::from_le_bytes([address[0], address[1], address[2], address[3]]);
+
+    EtmPacket::ISync {
+        // Bit 0 indicates the Thumb state.
+        address: address & !1,
+        reason,
+    }
+}
+
+/// The number of bytes of a field in which the top bit of each byte indicates that another
+/// byte follows, or `None` if the field is incomplete.
+fn continuation_length(data: &[u8], max: usize) -> Option<usize> {
+    for (index, byte) in data.iter().enumerate().take(max) {
+        if byte & 0x80 == 0 || index + 1 == max {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+fn continuation_value(data: &[u8]) -> u64 {
+    data.iter().enumerate().fold(0, |value, (index, byte)| {
+        value | ((byte & 0x7F) as u64) << (7 * index)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const A_SYNC: [u8; 6] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x80];
+
+    #[test]
+    fn wait_for_a_sync() {
+        let mut decoder = PacketDecoder::new();
+
+        // Garbage, followed by the start of an A-sync packet.
+        assert!(decoder.push(&[0x12, 0x85, 0x00, 0x00]).is_empty());
+
+        let packets = decoder.push(&[0x00, 0x00, 0x00, 0x80, 0x88]);
+        assert_eq!(
+            packets,
+            [EtmPacket::ASync, EtmPacket::Atoms(vec![Atom::E, Atom::E])]
+        );
+    }
+
+    #[test]
+    fn decode_i_sync_and_atoms() {
+        let mut decoder = PacketDecoder::new();
+
+        let mut data = A_SYNC.to_vec();
+        // I-sync, tracing enabled at 0x0800_0101
+        data.extend_from_slice(&[0x08, 0x20, 0x01, 0x01, 0x00, 0x08]);
+        // Format 1: 3 E atoms, 1 N atom
+        data.push(0b1100_1100);
+        // Format 2: N, E
+        data.push(0b1000_1010);
+
+        let packets = decoder.push(&data);
+
+        assert_eq!(
+            packets,
+            [
+                EtmPacket::ASync,
+                EtmPacket::ISync {
+                    address: 0x0800_0100,
+                    reason: ISyncReason::TracingEnabled
+                },
+                EtmPacket::Atoms(vec![Atom::E, Atom::E, Atom::E, Atom::N]),
+                EtmPacket::Atoms(vec![Atom::N, Atom::E]),
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_compressed_branch_addresses() {
+        let mut decoder = PacketDecoder::new();
+        decoder.push(&A_SYNC);
+
+        // Full address 0x0800_1234, Thumb state
+        let full = [0x80 | (0x1A << 1) | 1, 0x80 | 0x24, 0x80, 0x80 | 0x40, 0x10];
+        assert_eq!(
+            decoder.push(&full),
+            [EtmPacket::BranchAddress {
+                address: 0x0800_1234,
+                exception: None
+            }]
+        );
+
+        // Only the lowest 6 bits of the halfword address change.
+        assert_eq!(
+            decoder.push(&[(0x10 << 1) | 1]),
+            [EtmPacket::BranchAddress {
+                address: 0x0800_1220,
+                exception: None
+            }]
+        );
+
+        // Two bytes, with exception 3 (HardFault) following.
+        assert_eq!(
+            decoder.push(&[0x80 | (0x01 << 1) | 1, 0x40 | 0x02, 0x03 << 1]),
+            [EtmPacket::BranchAddress {
+                address: 0x0800_0102,
+                exception: Some(ExceptionInfo {
+                    number: 3,
+                    cancelled: false
+                })
+            }]
+        );
+    }
+
+    #[test]
+    fn incomplete_packets_are_buffered() {
+        let mut decoder = PacketDecoder::new();
+        decoder.push(&A_SYNC);
+
+        assert!(decoder.push(&[0x08, 0x20, 0x01]).is_empty());
+        assert_eq!(
+            decoder.push(&[0x01, 0x00, 0x08]),
+            [EtmPacket::ISync {
+                address: 0x0800_0100,
+                reason: ISyncReason::TracingEnabled
+            }]
+        );
+    }
+
+    #[test]
+    fn resynchronize_after_invalid_header() {
+        let mut decoder = PacketDecoder::new();
+        decoder.push(&A_SYNC);
+
+        let mut data = vec![0x50, 0x88];
+        data.extend_from_slice(&A_SYNC);
+        data.push(0x88);
+
+        assert_eq!(
+            decoder.push(&data),
+            [EtmPacket::ASync, EtmPacket::Atoms(vec![Atom::E, Atom::E])]
+        );
+    }
+}
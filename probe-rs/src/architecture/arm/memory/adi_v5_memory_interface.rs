@@ -14,6 +14,8 @@ use std::ops::Range;
 pub trait ArmProbe: SwdSequence {
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError>;
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError>;
+
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError>;
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), ArmError>;
@@ -34,6 +36,14 @@ pub trait ArmProbe: SwdSequence {
         Ok(buff[0])
     }
 
+    /// Reads a 16 bit word from `address`.
+    fn read_word_16(&mut self, address: u64) -> Result<u16, ArmError> {
+        let mut buff = [0];
+        self.read_16(address, &mut buff)?;
+
+        Ok(buff[0])
+    }
+
     /// Reads an 8 bit word from `address`.
     fn read_word_8(&mut self, address: u64) -> Result<u8, ArmError> {
         let mut buff = [0];
@@ -70,6 +80,8 @@ pub trait ArmProbe: SwdSequence {
 
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError>;
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError>;
+
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError>;
 
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), ArmError>;
@@ -84,6 +96,11 @@ pub trait ArmProbe: SwdSequence {
         self.write_32(address, &[data])
     }
 
+    /// Writes a 16 bit word to `address`.
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), ArmError> {
+        self.write_16(address, &[data])
+    }
+
     /// Writes a 8 bit word to `address`.
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), ArmError> {
         self.write_8(address, &[data])
@@ -365,6 +382,32 @@ where
         Ok(result.data)
     }
 
+    /// Read a 16 bit word at `address`.
+    ///
+    /// The address where the read should be performed at has to be halfword aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn read_word_16(&mut self, access_port: MemoryAp, address: u64) -> Result<u16, ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if (address % 2) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        // Offset of halfword in word (little endian)
+        let bit_offset = (address % 4) * 8;
+
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
+        self.write_tar_register(access_port, address)?;
+        let result: DRW = self.read_ap_register(access_port)?;
+
+        // Extract the correct halfword
+        // See "Arm Debug Interface Architecture Specification ADIv5.0 to ADIv5.2", C2.2.6
+        Ok(((result.data >> bit_offset) & 0xFFFF) as u16)
+    }
+
     /// Read an 8 bit word at `address`.
     pub fn read_word_8(&mut self, access_port: MemoryAp, address: u64) -> Result<u8, ArmError> {
         if self.ap_information.supports_only_32bit_data_size {
@@ -476,6 +519,82 @@ where
         Ok(())
     }
 
+    /// Read a block of 16 bit words at `address`.
+    ///
+    /// The number of words read is `data.len()`.
+    /// The address where the read should be performed at has to be halfword aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn read_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &mut [u16],
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if (address % 2) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        let start_address = address;
+        let mut data_u32 = vec![0u32; data.len()];
+
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
+
+        // The maximum chunk size we can read before data overflows.
+        // This is the size of the internal counter that is used for the address increment in the ARM spec.
+        let max_chunk_size_bytes = 0x400;
+
+        let mut address = address;
+        let mut data_offset = 0;
+
+        while data_offset < data.len() {
+            // The autoincrement is limited to the 10 lowest bits so we need to write the address
+            // every time it overflows.
+            self.write_tar_register(access_port, address)?;
+
+            let chunk_size_bytes = std::cmp::min(
+                max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
+                (data.len() - data_offset) * 2,
+            );
+
+            tracing::debug!(
+                "Reading chunk with len {} at address {:#08x}",
+                chunk_size_bytes,
+                address
+            );
+
+            let chunk_size_transfer_unit = chunk_size_bytes / 2;
+
+            self.read_ap_register_repeated(
+                access_port,
+                DRW { data: 0 },
+                &mut data_u32[data_offset..(data_offset + chunk_size_transfer_unit)],
+            )?;
+
+            address = address
+                .checked_add((2 * chunk_size_transfer_unit) as u64)
+                .ok_or(ArmError::OutOfBounds)?;
+            data_offset += chunk_size_transfer_unit;
+        }
+
+        // Halfwords are transfered in their byte lanes, see C2.2.6 of the ADI v5.2 specification.
+        for (target, (i, source)) in data.iter_mut().zip(data_u32.iter().enumerate()) {
+            *target = ((*source >> (((start_address + 2 * i as u64) % 4) * 8)) & 0xFFFF) as u16;
+        }
+
+        tracing::debug!("Finished reading block");
+
+        Ok(())
+    }
+
     /// Read a block of 8 bit words at `address`.
     ///
     /// The number of words read is `data.len()`.
@@ -636,6 +755,38 @@ where
         Ok(())
     }
 
+    /// Write a 16 bit word at `address`.
+    ///
+    /// The address where the write should be performed at has to be halfword aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn write_word_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: u16,
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if (address % 2) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        // Offset of halfword in word (little endian)
+        let bit_offset = (address % 4) * 8;
+
+        let csw = self.build_csw_register(DataSize::U16);
+        let drw = DRW {
+            data: u32::from(data) << bit_offset,
+        };
+        self.write_csw_register(access_port, csw)?;
+        self.write_tar_register(access_port, address)?;
+        self.write_ap_register(access_port, drw)?;
+
+        Ok(())
+    }
+
     /// Write an 8 bit word at `address`.
     pub fn write_word_8(
         &mut self,
@@ -760,6 +911,86 @@ where
         Ok(())
     }
 
+    /// Write a block of 16 bit words at `address`.
+    ///
+    /// The number of words written is `data.len()`.
+    /// The address where the write should be performed at has to be halfword aligned.
+    /// Returns `ArmError::MemoryNotAligned` if this does not hold true.
+    pub fn write_16(
+        &mut self,
+        access_port: MemoryAp,
+        address: u64,
+        data: &[u16],
+    ) -> Result<(), ArmError> {
+        if self.ap_information.supports_only_32bit_data_size {
+            return Err(ArmError::UnsupportedTransferWidth(16));
+        }
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if (address % 2) != 0 {
+            return Err(ArmError::alignment_error(address, 2));
+        }
+
+        // Halfwords are transfered in their byte lanes, see C2.2.6 of the ADI v5.2 specification.
+        let data = data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v as u32) << (((address as usize + 2 * i) % 4) * 8))
+            .collect::<Vec<_>>();
+
+        tracing::debug!(
+            "Write block with total size {} bytes to address {:#08x}",
+            data.len() * 2,
+            address
+        );
+
+        let csw = self.build_csw_register(DataSize::U16);
+        self.write_csw_register(access_port, csw)?;
+
+        // maximum chunk size
+        let max_chunk_size_bytes = 0x400_usize;
+
+        let mut address = address;
+        let mut data_offset = 0;
+
+        while data_offset < data.len() {
+            // the autoincrement is limited to the 10 lowest bits so we need to write the address
+            // every time it overflows
+            self.write_tar_register(access_port, address)?;
+
+            let chunk_size_bytes = std::cmp::min(
+                max_chunk_size_bytes - (address as usize % max_chunk_size_bytes),
+                (data.len() - data_offset) * 2,
+            );
+
+            tracing::debug!(
+                "Writing chunk with len {} at address {:#08x}",
+                chunk_size_bytes,
+                address
+            );
+
+            let chunk_size_transfer_unit = chunk_size_bytes / 2;
+
+            self.write_ap_register_repeated(
+                access_port,
+                DRW { data: 0 },
+                &data[data_offset..(data_offset + chunk_size_transfer_unit)],
+            )?;
+
+            address = address
+                .checked_add((2 * chunk_size_transfer_unit) as u64)
+                .ok_or(ArmError::OutOfBounds)?;
+            data_offset += chunk_size_transfer_unit;
+        }
+
+        tracing::debug!("Finished writing block");
+
+        Ok(())
+    }
+
     /// Write a block of 8 bit words at `address`.
     ///
     /// The number of words written is `data.len()`.
@@ -905,6 +1136,16 @@ where
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        if data.len() == 1 {
+            data[0] = self.read_word_16(self.memory_ap, address)?;
+        } else {
+            self.read_16(self.memory_ap, address, data)?;
+        }
+
+        Ok(())
+    }
+
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), ArmError> {
         if data.len() == 1 {
             data[0] = self.read_word_32(self.memory_ap, address)?;
@@ -933,6 +1174,16 @@ where
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        if data.len() == 1 {
+            self.write_word_16(self.memory_ap, address, data[0])?;
+        } else {
+            self.write_16(self.memory_ap, address, data)?;
+        }
+
+        Ok(())
+    }
+
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), ArmError> {
         if data.len() == 1 {
             self.write_word_32(self.memory_ap, address, data[0])?;
@@ -1035,6 +1286,11 @@ mod tests {
         128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143,
     ];
 
+    // DATA8 interpreted as little endian 16-bit words
+    const DATA16: &[u16] = &[
+        0x8180, 0x8382, 0x8584, 0x8786, 0x8988, 0x8b8a, 0x8d8c, 0x8f8e,
+    ];
+
     // DATA8 interpreted as little endian 32-bit words
     const DATA32: &[u32] = &[0x83828180, 0x87868584, 0x8b8a8988, 0x8f8e8d8c];
 
@@ -1066,6 +1322,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_word_16() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..8].copy_from_slice(&DATA8[..8]);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        for address in (0..8).step_by(2) {
+            let value = mi
+                .read_word_16(DUMMY_AP, address)
+                .unwrap_or_else(|_| panic!("read_word_16 failed, address = {address}"));
+            assert_eq!(value, DATA16[address as usize / 2], "address = {address}");
+        }
+    }
+
     #[test]
     fn write_word_32() {
         for &address in &[0, 4] {
@@ -1096,6 +1366,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn write_word_16() {
+        for address in (0..8).step_by(2) {
+            let mut mock = MockMemoryAp::with_pattern();
+            let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+            let mut expected = Vec::from(mi.mock_memory());
+            expected[address..address + 2].copy_from_slice(&DATA8[..2]);
+
+            mi.write_word_16(DUMMY_AP, address as u64, DATA16[0])
+                .unwrap_or_else(|_| panic!("write_word_16 failed, address = {address}"));
+            assert_eq!(mi.mock_memory(), expected.as_slice(), "address = {address}");
+        }
+    }
+
     #[test]
     fn read_32() {
         let mut mock = MockMemoryAp::with_pattern();
@@ -1172,6 +1457,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_16() {
+        let mut mock = MockMemoryAp::with_pattern();
+        mock.memory[..DATA8.len()].copy_from_slice(DATA8);
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        for address in (0..4).step_by(2) {
+            for len in 0..6 {
+                let mut data = vec![0u16; len];
+                mi.read_16(DUMMY_AP, address, &mut data)
+                    .unwrap_or_else(|_| panic!("read_16 failed, address = {address}, len = {len}"));
+
+                assert_eq!(
+                    data.as_slice(),
+                    &DATA16[address as usize / 2..address as usize / 2 + len],
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_16_unaligned_should_error() {
+        let mut mock = MockMemoryAp::with_pattern();
+        let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+        for &address in &[1, 3, 127] {
+            assert!(mi.read_16(DUMMY_AP, address, &mut [0u16; 4]).is_err());
+        }
+    }
+
     #[test]
     fn write_32() {
         for &address in &[0, 4] {
@@ -1232,6 +1548,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn write_16() {
+        for address in (0..4).step_by(2) {
+            for len in 0..6 {
+                let mut mock = MockMemoryAp::with_pattern();
+                let mut mi = ADIMemoryInterface::new_mock(&mut mock);
+
+                let mut expected = Vec::from(mi.mock_memory());
+                expected[address as usize..(address as usize) + len * 2]
+                    .copy_from_slice(&DATA8[..len * 2]);
+
+                let data = &DATA16[..len];
+                mi.write_16(DUMMY_AP, address, data).unwrap_or_else(|_| {
+                    panic!("write_16 failed, address = {address}, len = {len}")
+                });
+
+                assert_eq!(
+                    mi.mock_memory(),
+                    expected.as_slice(),
+                    "address = {address}, len = {len}"
+                );
+            }
+        }
+    }
+
     use super::aligned_range;
 
     #[test]
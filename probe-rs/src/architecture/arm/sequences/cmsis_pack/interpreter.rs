@@ -0,0 +1,655 @@
+//! Executes parsed CMSIS-Pack debug sequences.
+
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use probe_rs_target::{PackSequenceElement, PackSequences};
+
+use crate::architecture::arm::{sequences::ArmDebugSequenceError, ArmError};
+
+use super::{
+    parser::{
+        parse_expression, parse_statements, BinaryOperator, Expression, Statement, UnaryOperator,
+    },
+    AccessSize, SequenceInterface, SequenceParseError,
+};
+
+/// Sequences calling each other can't nest deeper than this, which protects
+/// against sequences which (indirectly) call themselves.
+const MAX_CALL_DEPTH: usize = 16;
+
+/// Loops without a timeout are aborted after this time, so a condition which
+/// never becomes false can't hang the debugger.
+const DEFAULT_LOOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bit 0 of `__errorcontrol` disables the error handling for debug accesses.
+const IGNORE_ACCESS_ERRORS: u64 = 1;
+
+/// The parsed `<debugvars>` and `<sequences>` of a chip.
+#[derive(Debug)]
+pub(crate) struct Program {
+    debug_vars: Vec<Statement>,
+    sequences: HashMap<String, Sequence>,
+}
+
+#[derive(Debug)]
+struct Sequence {
+    disabled: bool,
+    body: Vec<Element>,
+}
+
+#[derive(Debug)]
+enum Element {
+    Block(Vec<Statement>),
+    Control {
+        if_condition: Option<Expression>,
+        while_condition: Option<Expression>,
+        timeout: Duration,
+        body: Vec<Element>,
+    },
+}
+
+impl Program {
+    pub(crate) fn parse(description: &PackSequences) -> Result<Self, SequenceParseError> {
+        let debug_vars = match &description.debug_vars {
+            Some(code) => parse_statements(code).map_err(|message| SequenceParseError {
+                location: "debug variables".to_string(),
+                message,
+            })?,
+            None => vec![],
+        };
+
+        let mut sequences = HashMap::new();
+        for sequence in &description.sequences {
+            // Processor specific sequences are not supported yet, see `PackSequences::get`.
+            if sequence.pname.is_some() {
+                continue;
+            }
+
+            let body = parse_elements(&sequence.body).map_err(|message| SequenceParseError {
+                location: format!("sequence '{}'", sequence.name),
+                message,
+            })?;
+
+            sequences.insert(
+                sequence.name.clone(),
+                Sequence {
+                    disabled: sequence.disable,
+                    body,
+                },
+            );
+        }
+
+        Ok(Self {
+            debug_vars,
+            sequences,
+        })
+    }
+
+    /// Returns true if the sequence with the given name replaces the built-in implementation.
+    pub(crate) fn defines(&self, name: &str) -> bool {
+        self.sequences.contains_key(name)
+    }
+}
+
+fn parse_elements(elements: &[PackSequenceElement]) -> Result<Vec<Element>, String> {
+    elements
+        .iter()
+        .map(|element| match element {
+            PackSequenceElement::Block(block) => Ok(Element::Block(parse_statements(&block.code)?)),
+            PackSequenceElement::Control(control) => Ok(Element::Control {
+                if_condition: control
+                    .if_condition
+                    .as_deref()
+                    .map(parse_expression)
+                    .transpose()?,
+                while_condition: control
+                    .while_condition
+                    .as_deref()
+                    .map(parse_expression)
+                    .transpose()?,
+                timeout: control
+                    .timeout
+                    .filter(|timeout| *timeout != 0)
+                    .map_or(DEFAULT_LOOP_TIMEOUT, Duration::from_micros),
+                body: parse_elements(&control.body)?,
+            }),
+        })
+        .collect()
+}
+
+/// The values of the predefined debug access variables, before the `<debugvars>` are executed.
+pub(crate) fn predefined_variables() -> HashMap<String, u64> {
+    [
+        ("__protocol", 0),
+        // Debug connection, as opposed to flash programming.
+        ("__connection", 1),
+        ("__dp", 0),
+        ("__ap", 0),
+        ("__apid", 0),
+        ("__traceout", 0),
+        ("__errorcontrol", 0),
+        ("__FlashOp", 0),
+        ("__FlashAddr", 0),
+        ("__FlashLen", 0),
+        ("__FlashArg", 0),
+        ("__Result", 0),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+fn error(message: impl Into<String>) -> ArmError {
+    ArmDebugSequenceError::custom(message.into()).into()
+}
+
+pub(crate) struct Interpreter<'a> {
+    program: &'a Program,
+    globals: &'a mut HashMap<String, u64>,
+    /// The local variables of the sequences currently being executed.
+    frames: Vec<HashMap<String, u64>>,
+    interface: &'a mut dyn SequenceInterface,
+}
+
+impl<'a> Interpreter<'a> {
+    pub(crate) fn new(
+        program: &'a Program,
+        globals: &'a mut HashMap<String, u64>,
+        interface: &'a mut dyn SequenceInterface,
+    ) -> Self {
+        Self {
+            program,
+            globals,
+            frames: vec![],
+            interface,
+        }
+    }
+
+    /// Declare and initialize the variables in `<debugvars>`.
+    pub(crate) fn execute_debug_vars(&mut self) -> Result<(), ArmError> {
+        let program = self.program;
+        for statement in &program.debug_vars {
+            self.execute_statement(statement)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn call(&mut self, name: &str) -> Result<(), ArmError> {
+        let program = self.program;
+        let Some(sequence) = program.sequences.get(name) else {
+            // Predefined sequences which are not defined by the pack are replaced by their default implementation.
+            tracing::debug!(
+                "Executing default implementation of debug sequence {}",
+                name
+            );
+            return self
+                .interface
+                .run_default_sequence(name)
+                .unwrap_or_else(|| {
+                    Err(error(format!("The debug sequence '{name}' is not defined")))
+                });
+        };
+
+        if sequence.disabled {
+            tracing::debug!("Skipping disabled debug sequence {}", name);
+            return Ok(());
+        }
+
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(error(format!(
+                "Debug sequence '{name}' exceeds the maximum call depth of {MAX_CALL_DEPTH}"
+            )));
+        }
+
+        tracing::debug!("Executing debug sequence {}", name);
+
+        self.frames.push(HashMap::new());
+        let result = self.execute_elements(&sequence.body);
+        self.frames.pop();
+
+        result
+    }
+
+    fn execute_elements(&mut self, elements: &[Element]) -> Result<(), ArmError> {
+        for element in elements {
+            match element {
+                Element::Block(statements) => {
+                    // Atomic blocks can't be executed atomically without support from the probe,
+                    // so they are simply executed as fast as possible.
+                    for statement in statements {
+                        self.execute_statement(statement)?;
+                    }
+                }
+                Element::Control {
+                    if_condition,
+                    while_condition,
+                    timeout,
+                    body,
+                } => {
+                    if let Some(condition) = if_condition {
+                        if self.evaluate(condition)? == 0 {
+                            continue;
+                        }
+                    }
+
+                    let Some(condition) = while_condition else {
+                        self.execute_elements(body)?;
+                        continue;
+                    };
+
+                    let start = Instant::now();
+                    while self.evaluate(condition)? != 0 {
+                        self.execute_elements(body)?;
+
+                        if start.elapsed() > *timeout {
+                            return Err(error(format!(
+                                "Loop in debug sequence timed out after {timeout:?}"
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute_statement(&mut self, statement: &Statement) -> Result<(), ArmError> {
+        match statement {
+            Statement::Declare { name, value } => {
+                let value = self.evaluate(value)?;
+                match self.frames.last_mut() {
+                    Some(locals) => locals.insert(name.clone(), value),
+                    None => self.globals.insert(name.clone(), value),
+                };
+            }
+            Statement::Assign {
+                name,
+                operator,
+                value,
+            } => {
+                let mut value = self.evaluate(value)?;
+                if let Some(operator) = operator {
+                    value = binary(*operator, self.variable(name)?, value)?;
+                }
+                *self.variable_mut(name)? = value;
+            }
+            Statement::Expression(expression) => {
+                self.evaluate(expression)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn variable(&mut self, name: &str) -> Result<u64, ArmError> {
+        self.variable_mut(name).map(|value| *value)
+    }
+
+    fn variable_mut(&mut self, name: &str) -> Result<&mut u64, ArmError> {
+        if let Some(value) = self
+            .frames
+            .last_mut()
+            .and_then(|locals| locals.get_mut(name))
+        {
+            return Ok(value);
+        }
+
+        self.globals
+            .get_mut(name)
+            .ok_or_else(|| error(format!("Use of undeclared variable '{name}'")))
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Result<u64, ArmError> {
+        match expression {
+            Expression::Number(value) => Ok(*value),
+            Expression::String(string) => Err(error(format!(
+                "The string \"{string}\" can only be used as a function argument"
+            ))),
+            Expression::Variable(name) => self.variable(name),
+            Expression::Call {
+                function,
+                arguments,
+            } => self.call_function(function, arguments),
+            Expression::Unary(operator, operand) => {
+                let operand = self.evaluate(operand)?;
+                Ok(match operator {
+                    UnaryOperator::Not => (operand == 0) as u64,
+                    UnaryOperator::Complement => !operand,
+                    UnaryOperator::Negate => operand.wrapping_neg(),
+                })
+            }
+            // The logical operators only evaluate their right side if necessary.
+            Expression::Binary(BinaryOperator::And, left, right) => {
+                Ok((self.evaluate(left)? != 0 && self.evaluate(right)? != 0) as u64)
+            }
+            Expression::Binary(BinaryOperator::Or, left, right) => {
+                Ok((self.evaluate(left)? != 0 || self.evaluate(right)? != 0) as u64)
+            }
+            Expression::Binary(operator, left, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                binary(*operator, left, right)
+            }
+            Expression::Conditional(condition, if_true, if_false) => {
+                if self.evaluate(condition)? != 0 {
+                    self.evaluate(if_true)
+                } else {
+                    self.evaluate(if_false)
+                }
+            }
+        }
+    }
+
+    fn call_function(&mut self, function: &str, arguments: &[Expression]) -> Result<u64, ArmError> {
+        match function {
+            "Sequence" => {
+                let [name] = string_arguments(function, arguments)?;
+                self.call(name)?;
+                Ok(0)
+            }
+            "Query" => {
+                let [kind, message, default] = arguments else {
+                    return Err(argument_count(function, 3));
+                };
+                let (kind, message, default) = (
+                    self.evaluate(kind)?,
+                    string_argument(function, message)?,
+                    self.evaluate(default)?,
+                );
+                // Sequences are executed without user interaction, so always use the default answer.
+                tracing::info!(
+                    "Debug sequence query (type {}): {}, answering with {}",
+                    kind,
+                    message,
+                    default
+                );
+                Ok(default)
+            }
+            "QueryValue" => {
+                let [message, default] = arguments else {
+                    return Err(argument_count(function, 2));
+                };
+                let (message, default) =
+                    (string_argument(function, message)?, self.evaluate(default)?);
+                tracing::info!(
+                    "Debug sequence query: {}, answering with {:#x}",
+                    message,
+                    default
+                );
+                Ok(default)
+            }
+            "LoadDebugInfo" => {
+                let [file] = string_arguments(function, arguments)?;
+                tracing::debug!("Debug sequence requested to load {}, ignoring it", file);
+                Ok(0)
+            }
+            "Message" => {
+                let [kind, format, values @ ..] = arguments else {
+                    return Err(argument_count(function, 2));
+                };
+                let kind = self.evaluate(kind)?;
+                let format = string_argument(function, format)?;
+                let values = values
+                    .iter()
+                    .map(|value| self.evaluate(value))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let message = format_message(format, &values);
+                match kind {
+                    0 => tracing::info!("{}", message),
+                    1 => tracing::warn!("{}", message),
+                    // Errors abort the sequence.
+                    _ => return Err(error(message)),
+                }
+                Ok(0)
+            }
+            "Read8" => self.read_memory(function, arguments, AccessSize::U8),
+            "Read16" => self.read_memory(function, arguments, AccessSize::U16),
+            "Read32" => self.read_memory(function, arguments, AccessSize::U32),
+            "Read64" => self.read_memory(function, arguments, AccessSize::U64),
+            "Write8" => self.write_memory(function, arguments, AccessSize::U8),
+            "Write16" => self.write_memory(function, arguments, AccessSize::U16),
+            "Write32" => self.write_memory(function, arguments, AccessSize::U32),
+            "Write64" => self.write_memory(function, arguments, AccessSize::U64),
+            "ReadDP" => {
+                let [address] = self.number_arguments(function, arguments)?;
+                self.access(|interface, _| interface.read_dp(address as u8).map(u64::from))
+            }
+            "WriteDP" => {
+                let [address, value] = self.number_arguments(function, arguments)?;
+                self.access(|interface, _| interface.write_dp(address as u8, value as u32))?;
+                Ok(0)
+            }
+            "ReadAP" | "ReadAccessAP" => {
+                let [address] = self.number_arguments(function, arguments)?;
+                self.access(|interface, ap| interface.read_ap(ap, address as u8).map(u64::from))
+            }
+            "WriteAP" | "WriteAccessAP" => {
+                let [address, value] = self.number_arguments(function, arguments)?;
+                self.access(|interface, ap| interface.write_ap(ap, address as u8, value as u32))?;
+                Ok(0)
+            }
+            "DAP_Delay" => {
+                let [delay] = self.number_arguments(function, arguments)?;
+                thread::sleep(Duration::from_micros(delay));
+                Ok(0)
+            }
+            "DAP_WriteABORT" => {
+                let [value] = self.number_arguments(function, arguments)?;
+                // ABORT is always at address 0 of the DP.
+                self.access(|interface, _| interface.write_dp(0x0, value as u32))?;
+                Ok(0)
+            }
+            "DAP_SWJ_Pins" => {
+                let [pin_out, pin_select, pin_wait] = self.number_arguments(function, arguments)?;
+                let pins =
+                    self.interface
+                        .swj_pins(pin_out as u32, pin_select as u32, pin_wait as u32)?;
+                Ok(pins.into())
+            }
+            "DAP_SWJ_Clock" => {
+                let [frequency] = self.number_arguments(function, arguments)?;
+                // The speed of the probe is configured by the user, don't let the sequence override it.
+                tracing::debug!(
+                    "Debug sequence requested an SWJ clock of {} Hz, ignoring it",
+                    frequency
+                );
+                Ok(0)
+            }
+            "DAP_SWJ_Sequence" => {
+                let [count, bits] = self.number_arguments(function, arguments)?;
+                if !(1..=64).contains(&count) {
+                    return Err(error(format!("Invalid bit count {count} for {function}")));
+                }
+                self.interface.swj_sequence(count as u8, bits)?;
+                Ok(0)
+            }
+            "DAP_JTAG_Sequence" => {
+                let [count, tms, tdi] = self.number_arguments(function, arguments)?;
+                if !(1..=64).contains(&count) {
+                    return Err(error(format!("Invalid bit count {count} for {function}")));
+                }
+                self.interface.jtag_sequence(count as u8, tms != 0, tdi)?;
+                // The TDO data is not captured by the probes.
+                Ok(0)
+            }
+            _ => Err(error(format!("Unknown function '{function}'"))),
+        }
+    }
+
+    fn number_arguments<const N: usize>(
+        &mut self,
+        function: &str,
+        arguments: &[Expression],
+    ) -> Result<[u64; N], ArmError> {
+        if arguments.len() != N {
+            return Err(argument_count(function, N));
+        }
+
+        let mut values = [0; N];
+        for (value, argument) in values.iter_mut().zip(arguments) {
+            *value = self.evaluate(argument)?;
+        }
+
+        Ok(values)
+    }
+
+    fn read_memory(
+        &mut self,
+        function: &str,
+        arguments: &[Expression],
+        size: AccessSize,
+    ) -> Result<u64, ArmError> {
+        let [address] = self.number_arguments(function, arguments)?;
+        self.access(|interface, ap| interface.read_memory(ap, address, size))
+    }
+
+    fn write_memory(
+        &mut self,
+        function: &str,
+        arguments: &[Expression],
+        size: AccessSize,
+    ) -> Result<u64, ArmError> {
+        let [address, value] = self.number_arguments(function, arguments)?;
+        self.access(|interface, ap| interface.write_memory(ap, address, size, value))?;
+        Ok(0)
+    }
+
+    /// Run a debug access on the AP selected by `__ap`, and handle
+    /// errors as configured by `__errorcontrol`.
+    fn access<T: Default>(
+        &mut self,
+        access: impl FnOnce(&mut dyn SequenceInterface, u8) -> Result<T, ArmError>,
+    ) -> Result<T, ArmError> {
+        let ap = self.variable("__ap")? as u8;
+
+        match access(&mut *self.interface, ap) {
+            Ok(value) => Ok(value),
+            Err(e) if self.variable("__errorcontrol")? & IGNORE_ACCESS_ERRORS != 0 => {
+                tracing::debug!("Ignoring failed debug access in sequence: {}", e);
+                *self.variable_mut("__Result")? = 1;
+                Ok(T::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn binary(operator: BinaryOperator, left: u64, right: u64) -> Result<u64, ArmError> {
+    Ok(match operator {
+        BinaryOperator::Or => (left != 0 || right != 0) as u64,
+        BinaryOperator::And => (left != 0 && right != 0) as u64,
+        BinaryOperator::BitOr => left | right,
+        BinaryOperator::BitXor => left ^ right,
+        BinaryOperator::BitAnd => left & right,
+        BinaryOperator::Equal => (left == right) as u64,
+        BinaryOperator::NotEqual => (left != right) as u64,
+        BinaryOperator::Less => (left < right) as u64,
+        BinaryOperator::LessEqual => (left <= right) as u64,
+        BinaryOperator::Greater => (left > right) as u64,
+        BinaryOperator::GreaterEqual => (left >= right) as u64,
+        BinaryOperator::ShiftLeft => left.checked_shl(right as u32).unwrap_or(0),
+        BinaryOperator::ShiftRight => left.checked_shr(right as u32).unwrap_or(0),
+        BinaryOperator::Add => left.wrapping_add(right),
+        BinaryOperator::Subtract => left.wrapping_sub(right),
+        BinaryOperator::Multiply => left.wrapping_mul(right),
+        BinaryOperator::Divide => left
+            .checked_div(right)
+            .ok_or_else(|| error("Division by zero in debug sequence"))?,
+        BinaryOperator::Remainder => left
+            .checked_rem(right)
+            .ok_or_else(|| error("Division by zero in debug sequence"))?,
+    })
+}
+
+fn argument_count(function: &str, expected: usize) -> ArmError {
+    error(format!("{function} expects {expected} arguments"))
+}
+
+fn string_argument<'e>(function: &str, argument: &'e Expression) -> Result<&'e str, ArmError> {
+    match argument {
+        Expression::String(string) => Ok(string),
+        _ => Err(error(format!("{function} expects a string argument"))),
+    }
+}
+
+fn string_arguments<'e, const N: usize>(
+    function: &str,
+    arguments: &'e [Expression],
+) -> Result<[&'e str; N], ArmError> {
+    if arguments.len() != N {
+        return Err(argument_count(function, N));
+    }
+
+    let mut strings = [""; N];
+    for (string, argument) in strings.iter_mut().zip(arguments) {
+        *string = string_argument(function, argument)?;
+    }
+
+    Ok(strings)
+}
+
+/// Format a `Message`, which uses `printf` style placeholders.
+fn format_message(format: &str, values: &[u64]) -> String {
+    let mut message = String::new();
+    let mut values = values.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            message.push(c);
+            continue;
+        }
+
+        if chars.next_if_eq(&'%').is_some() {
+            message.push('%');
+            continue;
+        }
+
+        let zero_padded = chars.next_if_eq(&'0').is_some();
+        let mut width = 0;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+            width = width * 10 + digit as usize;
+            chars.next();
+        }
+        // Length modifiers don't matter, all values are 64 bit.
+        while chars.next_if(|c| matches!(c, 'l' | 'h')).is_some() {}
+
+        let value = values.next().copied().unwrap_or_default();
+        let formatted = match chars.next() {
+            Some('x') => format!("{value:x}"),
+            Some('X') => format!("{value:X}"),
+            Some('o') => format!("{value:o}"),
+            Some('d' | 'i') => format!("{}", value as i64),
+            _ => format!("{value}"),
+        };
+
+        let padding = if zero_padded { '0' } else { ' ' };
+        for _ in formatted.len()..width {
+            message.push(padding);
+        }
+        message.push_str(&formatted);
+    }
+
+    message
+}
+
+#[cfg(test)]
+mod test {
+    use super::format_message;
+
+    #[test]
+    fn format_messages() {
+        assert_eq!(
+            format_message("DPIDR: 0x%08X, %d%% done", &[0x2ba01477, 50]),
+            "DPIDR: 0x2BA01477, 50% done"
+        );
+        assert_eq!(format_message("%4x|%u", &[0xab, 7]), "  ab|7");
+    }
+}
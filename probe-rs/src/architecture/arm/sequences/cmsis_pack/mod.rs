@@ -0,0 +1,934 @@
+//! Debug sequences described in a CMSIS-Pack.
+//!
+//! Instead of implementing a sequence in Rust, the `<sequences>` of a pack are
+//! carried over into the target description by `target-gen`, and interpreted when
+//! the sequence is executed. Sequences which are not described fall back to the
+//! default implementations of [`ArmDebugSequence`], also when they are called
+//! from another sequence.
+
+mod interpreter;
+mod parser;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use probe_rs_target::{CoreType, PackSequences};
+
+use crate::{
+    architecture::arm::{
+        ap::{AccessPort, MemoryAp},
+        communication_interface::{DapProbe, Initialized},
+        component::TraceSink,
+        memory::{adi_v5_memory_interface::ArmProbe, CoresightComponent},
+        ApAddress, ArmCommunicationInterface, ArmError, ArmProbeInterface, DapAccess, DpAddress,
+        PortType,
+    },
+    WireProtocol,
+};
+
+use self::interpreter::{predefined_variables, Interpreter, Program};
+
+use super::{ArmDebugSequence, ArmDebugSequenceError, DefaultArmSequence};
+
+/// A debug sequence from a CMSIS-Pack could not be parsed.
+#[derive(thiserror::Error, Debug)]
+#[error("Failed to parse the {location}: {message}")]
+pub struct SequenceParseError {
+    location: String,
+    message: String,
+}
+
+/// Runs the debug sequences of a CMSIS-Pack.
+#[derive(Debug)]
+pub struct CmsisPackSequence {
+    program: Program,
+    /// The debug access variables. They are initialized before the first
+    /// sequence is executed, and keep their values between sequences.
+    variables: Mutex<Option<HashMap<String, u64>>>,
+}
+
+impl CmsisPackSequence {
+    /// Parses the given sequences, to execute them instead of the default implementations.
+    pub fn create(
+        sequences: &PackSequences,
+    ) -> Result<Arc<dyn ArmDebugSequence>, SequenceParseError> {
+        Ok(Arc::new(Self {
+            program: Program::parse(sequences)?,
+            variables: Mutex::new(None),
+        }))
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.program.defines(name)
+    }
+
+    /// Executes the sequence `name`, with `__ap` set to `ap`.
+    fn run(
+        &self,
+        name: &str,
+        interface: &mut dyn SequenceInterface,
+        ap: Option<u8>,
+    ) -> Result<(), ArmError> {
+        let mut variables = self.variables.lock().unwrap();
+
+        if variables.is_none() {
+            let mut initialized = predefined_variables();
+            Interpreter::new(&self.program, &mut initialized, interface).execute_debug_vars()?;
+            *variables = Some(initialized);
+        }
+        let variables = variables
+            .as_mut()
+            .expect("The debug access variables were initialized above");
+
+        if let Some(protocol) = interface.protocol() {
+            // The low 16 bits are the protocol, bit 16 indicates a SWJ-DP,
+            // which probe-rs always assumes to be present.
+            let protocol = match protocol {
+                WireProtocol::Jtag => 1,
+                WireProtocol::Swd => 2,
+            };
+            variables.insert("__protocol".to_string(), 1 << 16 | protocol);
+        }
+        if let Some(ap) = ap {
+            variables.insert("__ap".to_string(), ap.into());
+        }
+
+        Interpreter::new(&self.program, variables, interface).call(name)
+    }
+}
+
+impl ArmDebugSequence for CmsisPackSequence {
+    fn reset_hardware_assert(&self, interface: &mut dyn DapProbe) -> Result<(), ArmError> {
+        if !self.defines("ResetHardwareAssert") {
+            return DefaultArmSequence(()).reset_hardware_assert(interface);
+        }
+
+        self.run(
+            "ResetHardwareAssert",
+            &mut DapProbeInterface(interface),
+            None,
+        )
+    }
+
+    fn reset_hardware_deassert(&self, memory: &mut dyn ArmProbe) -> Result<(), ArmError> {
+        if !self.defines("ResetHardwareDeassert") {
+            return DefaultArmSequence(()).reset_hardware_deassert(memory);
+        }
+
+        let ap = memory.ap().ap_address().ap;
+        self.run(
+            "ResetHardwareDeassert",
+            &mut CoreProbeInterface { memory, core: None },
+            Some(ap),
+        )
+    }
+
+    fn debug_port_setup(&self, interface: &mut dyn DapProbe) -> Result<(), ArmError> {
+        if !self.defines("DebugPortSetup") {
+            return DefaultArmSequence(()).debug_port_setup(interface);
+        }
+
+        self.run("DebugPortSetup", &mut DapProbeInterface(interface), None)
+    }
+
+    fn debug_port_start(
+        &self,
+        interface: &mut ArmCommunicationInterface<Initialized>,
+        dp: DpAddress,
+    ) -> Result<(), ArmError> {
+        if !self.defines("DebugPortStart") {
+            return DefaultArmSequence(()).debug_port_start(interface, dp);
+        }
+
+        self.run(
+            "DebugPortStart",
+            &mut ProbeInterface {
+                interface,
+                dp,
+                core: None,
+            },
+            None,
+        )
+    }
+
+    fn debug_core_start(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        core_ap: MemoryAp,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+        cti_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.defines("DebugCoreStart") {
+            return DefaultArmSequence(())
+                .debug_core_start(interface, core_ap, core_type, debug_base, cti_base);
+        }
+
+        let ApAddress { dp, ap } = core_ap.ap_address();
+        let core = CoreInfo {
+            core_type,
+            debug_base,
+            cti_base,
+        };
+        self.run(
+            "DebugCoreStart",
+            &mut ProbeInterface {
+                interface,
+                dp,
+                core: Some((core_ap, core)),
+            },
+            Some(ap),
+        )
+    }
+
+    fn reset_catch_set(
+        &self,
+        core: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.defines("ResetCatchSet") {
+            return DefaultArmSequence(()).reset_catch_set(core, core_type, debug_base);
+        }
+
+        let ap = core.ap().ap_address().ap;
+        self.run(
+            "ResetCatchSet",
+            &mut CoreProbeInterface::new(core, core_type, debug_base),
+            Some(ap),
+        )
+    }
+
+    fn reset_catch_clear(
+        &self,
+        core: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.defines("ResetCatchClear") {
+            return DefaultArmSequence(()).reset_catch_clear(core, core_type, debug_base);
+        }
+
+        let ap = core.ap().ap_address().ap;
+        self.run(
+            "ResetCatchClear",
+            &mut CoreProbeInterface::new(core, core_type, debug_base),
+            Some(ap),
+        )
+    }
+
+    fn trace_start(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        components: &[CoresightComponent],
+        sink: &TraceSink,
+    ) -> Result<(), ArmError> {
+        if !self.defines("TraceStart") {
+            return DefaultArmSequence(()).trace_start(interface, components, sink);
+        }
+
+        let dp = DpAddress::Default;
+        self.run(
+            "TraceStart",
+            &mut ProbeInterface {
+                interface,
+                dp,
+                core: None,
+            },
+            None,
+        )
+    }
+
+    fn reset_system(
+        &self,
+        interface: &mut dyn ArmProbe,
+        core_type: CoreType,
+        debug_base: Option<u64>,
+    ) -> Result<(), ArmError> {
+        if !self.defines("ResetSystem") {
+            return DefaultArmSequence(()).reset_system(interface, core_type, debug_base);
+        }
+
+        let ap = interface.ap().ap_address().ap;
+        self.run(
+            "ResetSystem",
+            &mut CoreProbeInterface::new(interface, core_type, debug_base),
+            Some(ap),
+        )
+    }
+
+    fn debug_device_unlock(
+        &self,
+        interface: &mut dyn ArmProbeInterface,
+        default_ap: MemoryAp,
+        permissions: &crate::Permissions,
+    ) -> Result<(), ArmError> {
+        if !self.defines("DebugDeviceUnlock") {
+            return DefaultArmSequence(()).debug_device_unlock(interface, default_ap, permissions);
+        }
+
+        let ApAddress { dp, ap } = default_ap.ap_address();
+        self.run(
+            "DebugDeviceUnlock",
+            &mut ProbeInterface {
+                interface,
+                dp,
+                core: None,
+            },
+            Some(ap),
+        )
+    }
+
+    fn recover_support_start(&self, interface: &mut dyn ArmProbe) -> Result<(), ArmError> {
+        if !self.defines("RecoverSupportStart") {
+            return DefaultArmSequence(()).recover_support_start(interface);
+        }
+
+        let ap = interface.ap().ap_address().ap;
+        self.run(
+            "RecoverSupportStart",
+            &mut CoreProbeInterface {
+                memory: interface,
+                core: None,
+            },
+            Some(ap),
+        )
+    }
+
+    fn debug_core_stop(
+        &self,
+        interface: &mut dyn ArmProbe,
+        core_type: CoreType,
+    ) -> Result<(), ArmError> {
+        if !self.defines("DebugCoreStop") {
+            return DefaultArmSequence(()).debug_core_stop(interface, core_type);
+        }
+
+        let ap = interface.ap().ap_address().ap;
+        self.run(
+            "DebugCoreStop",
+            &mut CoreProbeInterface::new(interface, core_type, None),
+            Some(ap),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessSize {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+/// The debug accesses a sequence can make.
+///
+/// Depending on the point of the connection at which a sequence is executed,
+/// only some of them are available.
+pub(crate) trait SequenceInterface {
+    fn read_memory(&mut self, ap: u8, address: u64, size: AccessSize) -> Result<u64, ArmError>;
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: AccessSize,
+        value: u64,
+    ) -> Result<(), ArmError>;
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, ArmError>;
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), ArmError>;
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, ArmError>;
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), ArmError>;
+
+    fn swj_pins(&mut self, pin_out: u32, pin_select: u32, pin_wait: u32) -> Result<u32, ArmError>;
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), ArmError>;
+
+    fn jtag_sequence(&mut self, _cycles: u8, _tms: bool, _tdi: u64) -> Result<(), ArmError> {
+        Err(unavailable("DAP_JTAG_Sequence"))
+    }
+
+    /// The protocol used to talk to the target, if it is known at this point.
+    fn protocol(&self) -> Option<WireProtocol> {
+        None
+    }
+
+    /// Run the default implementation of the predefined sequence `name`, for a sequence
+    /// calling it which is not defined by the pack.
+    ///
+    /// Returns `None` if there is no default implementation, or if it can't be run with this interface.
+    fn run_default_sequence(&mut self, _name: &str) -> Option<Result<(), ArmError>> {
+        None
+    }
+}
+
+/// The core a sequence is executed for.
+#[derive(Debug, Clone, Copy)]
+struct CoreInfo {
+    core_type: CoreType,
+    debug_base: Option<u64>,
+    cti_base: Option<u64>,
+}
+
+/// Run the default implementation of a sequence which only needs access to the memory of a core.
+///
+/// Most of them also depend on the type of the core, and can't be run if it is unknown.
+fn run_default_core_sequence(
+    name: &str,
+    memory: &mut dyn ArmProbe,
+    core: Option<CoreInfo>,
+) -> Option<Result<(), ArmError>> {
+    let sequence = DefaultArmSequence(());
+
+    let result = match (name, core) {
+        ("ResetHardwareDeassert", _) => sequence.reset_hardware_deassert(memory),
+        ("RecoverSupportStart", _) => sequence.recover_support_start(memory),
+        ("ResetSystem", Some(core)) => {
+            sequence.reset_system(memory, core.core_type, core.debug_base)
+        }
+        ("ResetCatchSet", Some(core)) => {
+            sequence.reset_catch_set(memory, core.core_type, core.debug_base)
+        }
+        ("ResetCatchClear", Some(core)) => {
+            sequence.reset_catch_clear(memory, core.core_type, core.debug_base)
+        }
+        ("DebugCoreStop", Some(core)) => sequence.debug_core_stop(memory, core.core_type),
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+fn unavailable(function: &str) -> ArmError {
+    ArmDebugSequenceError::custom(format!(
+        "{function} is not available in this debug sequence"
+    ))
+    .into()
+}
+
+fn read_memory(memory: &mut dyn ArmProbe, address: u64, size: AccessSize) -> Result<u64, ArmError> {
+    Ok(match size {
+        AccessSize::U8 => memory.read_word_8(address)?.into(),
+        AccessSize::U16 => memory.read_word_16(address)?.into(),
+        AccessSize::U32 => memory.read_word_32(address)?.into(),
+        AccessSize::U64 => memory.read_word_64(address)?,
+    })
+}
+
+fn write_memory(
+    memory: &mut dyn ArmProbe,
+    address: u64,
+    size: AccessSize,
+    value: u64,
+) -> Result<(), ArmError> {
+    match size {
+        AccessSize::U8 => memory.write_word_8(address, value as u8)?,
+        AccessSize::U16 => memory.write_word_16(address, value as u16)?,
+        AccessSize::U32 => memory.write_word_32(address, value as u32)?,
+        AccessSize::U64 => memory.write_word_64(address, value)?,
+    }
+
+    // Sequences often poll for the effect of a write, so it has to reach the target right away.
+    memory.flush()
+}
+
+/// Sequences executed before the debug port is powered up only have raw access to the DAP.
+struct DapProbeInterface<'a>(&'a mut dyn DapProbe);
+
+impl DapProbeInterface<'_> {
+    /// Select the AP and the register bank for an AP access.
+    fn select_ap(&mut self, ap: u8, address: u8) -> Result<(), ArmError> {
+        let select = (u32::from(ap) << 24) | u32::from(address & 0xF0);
+        self.0.raw_write_register(PortType::DebugPort, 0x8, select)
+    }
+}
+
+impl SequenceInterface for DapProbeInterface<'_> {
+    fn read_memory(&mut self, _ap: u8, _address: u64, _size: AccessSize) -> Result<u64, ArmError> {
+        Err(unavailable("Memory access"))
+    }
+
+    fn write_memory(
+        &mut self,
+        _ap: u8,
+        _address: u64,
+        _size: AccessSize,
+        _value: u64,
+    ) -> Result<(), ArmError> {
+        Err(unavailable("Memory access"))
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, ArmError> {
+        self.0.raw_read_register(PortType::DebugPort, address)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), ArmError> {
+        self.0
+            .raw_write_register(PortType::DebugPort, address, value)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, ArmError> {
+        self.select_ap(ap, address)?;
+        self.0
+            .raw_read_register(PortType::AccessPort, address & 0xF)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), ArmError> {
+        self.select_ap(ap, address)?;
+        self.0
+            .raw_write_register(PortType::AccessPort, address & 0xF, value)
+    }
+
+    fn swj_pins(&mut self, pin_out: u32, pin_select: u32, pin_wait: u32) -> Result<u32, ArmError> {
+        Ok(self.0.swj_pins(pin_out, pin_select, pin_wait)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), ArmError> {
+        Ok(self.0.swj_sequence(bit_len, bits)?)
+    }
+
+    fn jtag_sequence(&mut self, cycles: u8, tms: bool, tdi: u64) -> Result<(), ArmError> {
+        Ok(self.0.jtag_sequence(cycles, tms, tdi)?)
+    }
+
+    fn protocol(&self) -> Option<WireProtocol> {
+        self.0.active_protocol()
+    }
+
+    fn run_default_sequence(&mut self, name: &str) -> Option<Result<(), ArmError>> {
+        let sequence = DefaultArmSequence(());
+
+        match name {
+            "ResetHardwareAssert" => Some(sequence.reset_hardware_assert(self.0)),
+            "DebugPortSetup" => Some(sequence.debug_port_setup(self.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Sequences executed with a fully initialized debug port.
+struct ProbeInterface<'a> {
+    interface: &'a mut dyn ArmProbeInterface,
+    dp: DpAddress,
+    /// The memory AP of the core the sequence is executed for, if any.
+    core: Option<(MemoryAp, CoreInfo)>,
+}
+
+impl SequenceInterface for ProbeInterface<'_> {
+    fn read_memory(&mut self, ap: u8, address: u64, size: AccessSize) -> Result<u64, ArmError> {
+        let ap = MemoryAp::new(ApAddress { dp: self.dp, ap });
+        read_memory(&mut *self.interface.memory_interface(ap)?, address, size)
+    }
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: AccessSize,
+        value: u64,
+    ) -> Result<(), ArmError> {
+        let ap = MemoryAp::new(ApAddress { dp: self.dp, ap });
+        write_memory(
+            &mut *self.interface.memory_interface(ap)?,
+            address,
+            size,
+            value,
+        )
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, ArmError> {
+        self.interface.read_raw_dp_register(self.dp, address)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), ArmError> {
+        self.interface
+            .write_raw_dp_register(self.dp, address, value)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, ArmError> {
+        self.interface
+            .read_raw_ap_register(ApAddress { dp: self.dp, ap }, address)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), ArmError> {
+        self.interface
+            .write_raw_ap_register(ApAddress { dp: self.dp, ap }, address, value)
+    }
+
+    fn swj_pins(&mut self, pin_out: u32, pin_select: u32, pin_wait: u32) -> Result<u32, ArmError> {
+        Ok(self.interface.swj_pins(pin_out, pin_select, pin_wait)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), ArmError> {
+        Ok(self.interface.swj_sequence(bit_len, bits)?)
+    }
+
+    fn run_default_sequence(&mut self, name: &str) -> Option<Result<(), ArmError>> {
+        let (core_ap, core) = self.core?;
+
+        if name == "DebugCoreStart" {
+            return Some(DefaultArmSequence(()).debug_core_start(
+                self.interface,
+                core_ap,
+                core.core_type,
+                core.debug_base,
+                core.cti_base,
+            ));
+        }
+
+        let mut memory = match self.interface.memory_interface(core_ap) {
+            Ok(memory) => memory,
+            Err(error) => return Some(Err(error)),
+        };
+        run_default_core_sequence(name, &mut *memory, Some(core))
+    }
+}
+
+/// Sequences executed on a core, which get the memory interface of the core.
+struct CoreProbeInterface<'a> {
+    memory: &'a mut dyn ArmProbe,
+    /// The core, if it is known when the sequence is executed.
+    core: Option<CoreInfo>,
+}
+
+impl<'a> CoreProbeInterface<'a> {
+    fn new(memory: &'a mut dyn ArmProbe, core_type: CoreType, debug_base: Option<u64>) -> Self {
+        Self {
+            memory,
+            core: Some(CoreInfo {
+                core_type,
+                debug_base,
+                cti_base: None,
+            }),
+        }
+    }
+}
+
+impl SequenceInterface for CoreProbeInterface<'_> {
+    fn read_memory(&mut self, ap: u8, address: u64, size: AccessSize) -> Result<u64, ArmError> {
+        let core_ap = self.memory.ap().ap_address();
+        if core_ap.ap == ap {
+            return read_memory(self.memory, address, size);
+        }
+
+        let ap = MemoryAp::new(ApAddress { dp: core_ap.dp, ap });
+        let interface = self.memory.get_arm_communication_interface()?;
+        read_memory(&mut *interface.memory_interface(ap)?, address, size)
+    }
+
+    fn write_memory(
+        &mut self,
+        ap: u8,
+        address: u64,
+        size: AccessSize,
+        value: u64,
+    ) -> Result<(), ArmError> {
+        let core_ap = self.memory.ap().ap_address();
+        if core_ap.ap == ap {
+            return write_memory(self.memory, address, size, value);
+        }
+
+        let ap = MemoryAp::new(ApAddress { dp: core_ap.dp, ap });
+        let interface = self.memory.get_arm_communication_interface()?;
+        write_memory(&mut *interface.memory_interface(ap)?, address, size, value)
+    }
+
+    fn read_dp(&mut self, address: u8) -> Result<u32, ArmError> {
+        let dp = self.memory.ap().ap_address().dp;
+        self.memory
+            .get_arm_communication_interface()?
+            .read_raw_dp_register(dp, address)
+    }
+
+    fn write_dp(&mut self, address: u8, value: u32) -> Result<(), ArmError> {
+        let dp = self.memory.ap().ap_address().dp;
+        self.memory
+            .get_arm_communication_interface()?
+            .write_raw_dp_register(dp, address, value)
+    }
+
+    fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32, ArmError> {
+        let dp = self.memory.ap().ap_address().dp;
+        self.memory
+            .get_arm_communication_interface()?
+            .read_raw_ap_register(ApAddress { dp, ap }, address)
+    }
+
+    fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<(), ArmError> {
+        let dp = self.memory.ap().ap_address().dp;
+        self.memory
+            .get_arm_communication_interface()?
+            .write_raw_ap_register(ApAddress { dp, ap }, address, value)
+    }
+
+    fn swj_pins(&mut self, pin_out: u32, pin_select: u32, pin_wait: u32) -> Result<u32, ArmError> {
+        Ok(self.memory.swj_pins(pin_out, pin_select, pin_wait)?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), ArmError> {
+        Ok(self.memory.swj_sequence(bit_len, bits)?)
+    }
+
+    fn run_default_sequence(&mut self, name: &str) -> Option<Result<(), ArmError>> {
+        run_default_core_sequence(name, self.memory, self.core)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use probe_rs_target::{
+        PackSequence, PackSequenceBlock, PackSequenceControl, PackSequenceElement, PackSequences,
+    };
+
+    use super::{AccessSize, CmsisPackSequence, SequenceInterface};
+    use crate::architecture::arm::{ArmError, DapError};
+
+    /// Target memory, where every 32 bit word is a register.
+    #[derive(Default)]
+    struct MockInterface {
+        memory: HashMap<u64, u64>,
+        writes: Vec<(u64, u64)>,
+        default_sequences: Vec<String>,
+    }
+
+    impl SequenceInterface for MockInterface {
+        fn read_memory(
+            &mut self,
+            _ap: u8,
+            address: u64,
+            _size: AccessSize,
+        ) -> Result<u64, ArmError> {
+            self.memory
+                .get(&address)
+                .copied()
+                .ok_or(ArmError::Dap(DapError::FaultResponse))
+        }
+
+        fn write_memory(
+            &mut self,
+            _ap: u8,
+            address: u64,
+            _size: AccessSize,
+            value: u64,
+        ) -> Result<(), ArmError> {
+            self.writes.push((address, value));
+            self.memory.insert(address, value);
+            Ok(())
+        }
+
+        fn read_dp(&mut self, _address: u8) -> Result<u32, ArmError> {
+            Ok(0x2ba01477)
+        }
+
+        fn write_dp(&mut self, _address: u8, _value: u32) -> Result<(), ArmError> {
+            Ok(())
+        }
+
+        fn read_ap(&mut self, _ap: u8, _address: u8) -> Result<u32, ArmError> {
+            Ok(0)
+        }
+
+        fn write_ap(&mut self, _ap: u8, _address: u8, _value: u32) -> Result<(), ArmError> {
+            Ok(())
+        }
+
+        fn swj_pins(
+            &mut self,
+            _pin_out: u32,
+            _pin_select: u32,
+            _pin_wait: u32,
+        ) -> Result<u32, ArmError> {
+            Ok(0)
+        }
+
+        fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), ArmError> {
+            Ok(())
+        }
+
+        fn run_default_sequence(&mut self, name: &str) -> Option<Result<(), ArmError>> {
+            if name != "ResetSystem" {
+                return None;
+            }
+
+            self.default_sequences.push(name.to_string());
+            Some(Ok(()))
+        }
+    }
+
+    fn block(code: &str) -> PackSequenceElement {
+        PackSequenceElement::Block(PackSequenceBlock {
+            atomic: false,
+            code: code.to_string(),
+        })
+    }
+
+    fn control(
+        if_condition: Option<&str>,
+        while_condition: Option<&str>,
+        body: Vec<PackSequenceElement>,
+    ) -> PackSequenceElement {
+        PackSequenceElement::Control(PackSequenceControl {
+            if_condition: if_condition.map(String::from),
+            while_condition: while_condition.map(String::from),
+            timeout: Some(1_000_000),
+            body,
+        })
+    }
+
+    fn sequence(name: &str, body: Vec<PackSequenceElement>) -> PackSequence {
+        PackSequence {
+            name: name.to_string(),
+            pname: None,
+            disable: false,
+            body,
+        }
+    }
+
+    fn program(debug_vars: &str, sequences: Vec<PackSequence>) -> CmsisPackSequence {
+        let description = PackSequences {
+            debug_vars: Some(debug_vars.to_string()),
+            sequences,
+        };
+
+        CmsisPackSequence {
+            program: super::Program::parse(&description).unwrap(),
+            variables: Default::default(),
+        }
+    }
+
+    #[test]
+    fn control_flow() {
+        let sequences = program(
+            "__var Unlock = 0x45670123;",
+            vec![
+                sequence(
+                    "ResetSystem",
+                    vec![
+                        block("__var count = 0; Write32(0x4000, Unlock);"),
+                        control(
+                            Some("Read32(0x4000) == 0x45670123"),
+                            Some("count < 3"),
+                            vec![block("count += 1; Write32(0x4004, count << 4);")],
+                        ),
+                        control(Some("count != 3"), None, vec![block("Write32(0x4008, 1);")]),
+                        block("Sequence(\"ResetCatchSet\");"),
+                    ],
+                ),
+                sequence("ResetCatchSet", vec![block("Write8(0x400C, ~0 & 0xFF)")]),
+            ],
+        );
+
+        let mut interface = MockInterface::default();
+        sequences
+            .run("ResetSystem", &mut interface, Some(0))
+            .unwrap();
+
+        assert_eq!(
+            interface.writes,
+            vec![
+                (0x4000, 0x45670123),
+                (0x4004, 0x10),
+                (0x4004, 0x20),
+                (0x4004, 0x30),
+                (0x400C, 0xFF)
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_vars_keep_their_value() {
+        let sequences = program(
+            "__var Calls = 0;",
+            vec![sequence(
+                "DebugCoreStart",
+                vec![block("Calls += 1; Write32(0x2000, Calls);")],
+            )],
+        );
+
+        let mut interface = MockInterface::default();
+        for _ in 0..2 {
+            sequences
+                .run("DebugCoreStart", &mut interface, Some(0))
+                .unwrap();
+        }
+
+        assert_eq!(interface.writes, vec![(0x2000, 1), (0x2000, 2)]);
+    }
+
+    #[test]
+    fn access_errors() {
+        let sequences = program(
+            "",
+            vec![
+                sequence("Strict", vec![block("Read32(0x1000);")]),
+                sequence(
+                    "Lenient",
+                    vec![block(
+                        "__errorcontrol = 1; __var value = Read32(0x1000); __errorcontrol = 0; Write32(0x2000, __Result);",
+                    )],
+                ),
+                sequence("Abort", vec![block("Message(2, \"Device is locked\");")]),
+            ],
+        );
+
+        let mut interface = MockInterface::default();
+        assert!(sequences.run("Strict", &mut interface, None).is_err());
+        assert!(sequences.run("Abort", &mut interface, None).is_err());
+
+        sequences.run("Lenient", &mut interface, None).unwrap();
+        assert_eq!(interface.writes, vec![(0x2000, 1)]);
+    }
+
+    #[test]
+    fn loop_timeout() {
+        let sequences = program(
+            "",
+            vec![sequence(
+                "WaitForever",
+                vec![PackSequenceElement::Control(PackSequenceControl {
+                    if_condition: None,
+                    while_condition: Some("1".to_string()),
+                    timeout: Some(1_000),
+                    body: vec![block("Write32(0x3000, 1);")],
+                })],
+            )],
+        );
+
+        let mut interface = MockInterface::default();
+        assert!(sequences.run("WaitForever", &mut interface, None).is_err());
+        assert!(!interface.writes.is_empty());
+    }
+
+    #[test]
+    fn undefined_sequences_use_the_default_implementation() {
+        let sequences = program(
+            "",
+            vec![
+                sequence(
+                    "ResetHardware",
+                    vec![block("Sequence(\"ResetSystem\"); Write32(0x2000, 1);")],
+                ),
+                sequence("CallUndefined", vec![block("Sequence(\"Undefined\");")]),
+            ],
+        );
+
+        let mut interface = MockInterface::default();
+        sequences
+            .run("ResetHardware", &mut interface, Some(0))
+            .unwrap();
+        assert_eq!(interface.default_sequences, vec!["ResetSystem"]);
+        assert_eq!(interface.writes, vec![(0x2000, 1)]);
+
+        assert!(sequences
+            .run("CallUndefined", &mut interface, Some(0))
+            .is_err());
+    }
+}
@@ -0,0 +1,532 @@
+//! Parser for the C-like language of CMSIS-Pack debug sequences.
+//!
+//! The language only knows 64-bit unsigned integer variables, the usual C operators,
+//! and calls of the predefined debug access functions. See
+//! <https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/debug_description.html#debug_access_expressions>.
+
+use std::{iter::Peekable, str::CharIndices};
+
+/// A single statement of a sequence block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Statement {
+    /// `__var name = value;`
+    Declare { name: String, value: Expression },
+    /// `name = value;`, or a compound assignment like `name |= value;`
+    Assign {
+        name: String,
+        operator: Option<BinaryOperator>,
+        value: Expression,
+    },
+    /// An expression which is only evaluated for its side effects, usually a function call.
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Expression {
+    Number(u64),
+    String(String),
+    Variable(String),
+    Call {
+        function: String,
+        arguments: Vec<Expression>,
+    },
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnaryOperator {
+    Not,
+    Complement,
+    Negate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOperator {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOperator {
+    /// The binding strength of the operator, following the C precedence rules.
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::BitOr => 3,
+            BinaryOperator::BitXor => 4,
+            BinaryOperator::BitAnd => 5,
+            BinaryOperator::Equal | BinaryOperator::NotEqual => 6,
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => 7,
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => 8,
+            BinaryOperator::Add | BinaryOperator::Subtract => 9,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Remainder => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    String(String),
+    Identifier(String),
+    Unary(UnaryOperator),
+    Binary(BinaryOperator),
+    /// `=`, or a compound assignment operator like `+=`.
+    Assign(Option<BinaryOperator>),
+    Question,
+    Colon,
+    OpenParen,
+    CloseParen,
+    Comma,
+    Semicolon,
+}
+
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '/' if chars.next_if(|(_, c)| *c == '/').is_some() => {
+                // Line comment
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if(|(_, c)| *c == '*').is_some() => {
+                // Block comment
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) if previous == '*' => break,
+                        Some((_, c)) => previous = c,
+                        None => return Err("Unterminated comment".to_string()),
+                    }
+                }
+                continue;
+            }
+            '0'..='9' => Token::Number(number(code, start, &mut chars)?),
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let mut end = start + 1;
+                while let Some((index, _)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = index + 1;
+                }
+                Token::Identifier(code[start..end].to_string())
+            }
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => string.push('\n'),
+                            Some((_, 't')) => string.push('\t'),
+                            Some((_, c)) => string.push(c),
+                            None => return Err("Unterminated string".to_string()),
+                        },
+                        Some((_, c)) => string.push(c),
+                        None => return Err("Unterminated string".to_string()),
+                    }
+                }
+                Token::String(string)
+            }
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            ',' => Token::Comma,
+            ';' => Token::Semicolon,
+            '?' => Token::Question,
+            ':' => Token::Colon,
+            '~' => Token::Unary(UnaryOperator::Complement),
+            '!' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                Token::Binary(BinaryOperator::NotEqual)
+            }
+            '!' => Token::Unary(UnaryOperator::Not),
+            '=' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                Token::Binary(BinaryOperator::Equal)
+            }
+            '=' => Token::Assign(None),
+            '&' if chars.next_if(|(_, c)| *c == '&').is_some() => {
+                Token::Binary(BinaryOperator::And)
+            }
+            '|' if chars.next_if(|(_, c)| *c == '|').is_some() => Token::Binary(BinaryOperator::Or),
+            '<' if chars.next_if(|(_, c)| *c == '<').is_some() => {
+                operator_or_assignment(BinaryOperator::ShiftLeft, &mut chars)
+            }
+            '>' if chars.next_if(|(_, c)| *c == '>').is_some() => {
+                operator_or_assignment(BinaryOperator::ShiftRight, &mut chars)
+            }
+            '<' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                Token::Binary(BinaryOperator::LessEqual)
+            }
+            '>' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                Token::Binary(BinaryOperator::GreaterEqual)
+            }
+            '<' => Token::Binary(BinaryOperator::Less),
+            '>' => Token::Binary(BinaryOperator::Greater),
+            '&' => operator_or_assignment(BinaryOperator::BitAnd, &mut chars),
+            '|' => operator_or_assignment(BinaryOperator::BitOr, &mut chars),
+            '^' => operator_or_assignment(BinaryOperator::BitXor, &mut chars),
+            '+' => operator_or_assignment(BinaryOperator::Add, &mut chars),
+            '-' => operator_or_assignment(BinaryOperator::Subtract, &mut chars),
+            '*' => operator_or_assignment(BinaryOperator::Multiply, &mut chars),
+            '/' => operator_or_assignment(BinaryOperator::Divide, &mut chars),
+            '%' => operator_or_assignment(BinaryOperator::Remainder, &mut chars),
+            other => return Err(format!("Unexpected character '{other}'")),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn operator_or_assignment(
+    operator: BinaryOperator,
+    chars: &mut Peekable<CharIndices<'_>>,
+) -> Token {
+    if chars.next_if(|(_, c)| *c == '=').is_some() {
+        Token::Assign(Some(operator))
+    } else {
+        Token::Binary(operator)
+    }
+}
+
+fn number(code: &str, start: usize, chars: &mut Peekable<CharIndices<'_>>) -> Result<u64, String> {
+    let mut end = start + 1;
+    while let Some((index, _)) = chars.next_if(|(_, c)| c.is_ascii_alphanumeric()) {
+        end = index + 1;
+    }
+
+    // Integer suffixes like `UL` don't change anything, as all values are 64 bit wide anyway.
+    let literal = code[start..end].trim_end_matches(['u', 'U', 'l', 'L']);
+
+    let value = if let Some(hex) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+    } else {
+        literal.parse()
+    };
+
+    value.map_err(|_| format!("Invalid number '{}'", &code[start..end]))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(code: &str) -> Result<Self, String> {
+        Ok(Self {
+            tokens: tokenize(code)?,
+            position: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected:?}, found {token:?}")),
+            None => Err(format!("Expected {expected:?}, found the end of the code")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, String> {
+        let statement = match (self.peek(), self.tokens.get(self.position + 1)) {
+            (Some(Token::Identifier(keyword)), _) if keyword == "__var" => {
+                self.position += 1;
+                let Some(Token::Identifier(name)) = self.next() else {
+                    return Err("Expected a variable name after '__var'".to_string());
+                };
+                self.expect(Token::Assign(None))?;
+                Statement::Declare {
+                    name,
+                    value: self.expression()?,
+                }
+            }
+            (Some(Token::Identifier(name)), Some(Token::Assign(operator))) => {
+                let name = name.clone();
+                let operator = *operator;
+                self.position += 2;
+                Statement::Assign {
+                    name,
+                    operator,
+                    value: self.expression()?,
+                }
+            }
+            _ => Statement::Expression(self.expression()?),
+        };
+
+        // The semicolon after the last statement is optional.
+        if !self.at_end() {
+            self.expect(Token::Semicolon)?;
+        }
+
+        Ok(statement)
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let condition = self.binary(1)?;
+
+        if self.peek() != Some(&Token::Question) {
+            return Ok(condition);
+        }
+
+        self.position += 1;
+        let if_true = self.expression()?;
+        self.expect(Token::Colon)?;
+        let if_false = self.expression()?;
+
+        Ok(Expression::Conditional(
+            Box::new(condition),
+            Box::new(if_true),
+            Box::new(if_false),
+        ))
+    }
+
+    /// Parse a chain of binary operators which bind at least as strong as `min_precedence`.
+    fn binary(&mut self, min_precedence: u8) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+
+        while let Some(&Token::Binary(operator)) = self.peek() {
+            if operator.precedence() < min_precedence {
+                break;
+            }
+            self.position += 1;
+
+            // All binary operators are left associative.
+            let right = self.binary(operator.precedence() + 1)?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Unary(operator)) => {
+                Ok(Expression::Unary(operator, Box::new(self.unary()?)))
+            }
+            Some(Token::Binary(BinaryOperator::Subtract)) => Ok(Expression::Unary(
+                UnaryOperator::Negate,
+                Box::new(self.unary()?),
+            )),
+            Some(Token::Binary(BinaryOperator::Add)) => self.unary(),
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::String(value)) => Ok(Expression::String(value)),
+            Some(Token::OpenParen) => {
+                let expression = self.expression()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(name)) => {
+                if self.peek() != Some(&Token::OpenParen) {
+                    return Ok(Expression::Variable(name));
+                }
+                self.position += 1;
+
+                let mut arguments = Vec::new();
+                if self.peek() == Some(&Token::CloseParen) {
+                    self.position += 1;
+                } else {
+                    loop {
+                        arguments.push(self.expression()?);
+                        match self.next() {
+                            Some(Token::Comma) => {}
+                            Some(Token::CloseParen) => break,
+                            _ => return Err(format!("Expected ',' or ')' in call of {name}")),
+                        }
+                    }
+                }
+
+                Ok(Expression::Call {
+                    function: name,
+                    arguments,
+                })
+            }
+            Some(token) => Err(format!("Unexpected {token:?}")),
+            None => Err("Unexpected end of the code".to_string()),
+        }
+    }
+}
+
+/// Parse the statements of a `<block>` or `<debugvars>` element.
+pub(crate) fn parse_statements(code: &str) -> Result<Vec<Statement>, String> {
+    let mut parser = Parser::new(code)?;
+    let mut statements = Vec::new();
+
+    while !parser.at_end() {
+        // Allow empty statements, e.g. a stray semicolon.
+        if parser.peek() == Some(&Token::Semicolon) {
+            parser.position += 1;
+            continue;
+        }
+
+        statements.push(parser.statement()?);
+    }
+
+    Ok(statements)
+}
+
+/// Parse the condition of a `<control>` element.
+pub(crate) fn parse_expression(code: &str) -> Result<Expression, String> {
+    let mut parser = Parser::new(code)?;
+    let expression = parser.expression()?;
+
+    match parser.next() {
+        None => Ok(expression),
+        Some(token) => Err(format!("Unexpected {token:?} after the expression")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn variable(name: &str) -> Box<Expression> {
+        Box::new(Expression::Variable(name.to_string()))
+    }
+
+    fn number(value: u64) -> Box<Expression> {
+        Box::new(Expression::Number(value))
+    }
+
+    #[test]
+    fn operator_precedence() {
+        let expression = parse_expression("a | b & 0x10 << 2 + 1").unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::Binary(
+                BinaryOperator::BitOr,
+                variable("a"),
+                Box::new(Expression::Binary(
+                    BinaryOperator::BitAnd,
+                    variable("b"),
+                    Box::new(Expression::Binary(
+                        BinaryOperator::ShiftLeft,
+                        number(0x10),
+                        Box::new(Expression::Binary(
+                            BinaryOperator::Add,
+                            number(2),
+                            number(1)
+                        ))
+                    ))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn left_associativity() {
+        let expression = parse_expression("10 - 4 - 3").unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::Binary(
+                BinaryOperator::Subtract,
+                Box::new(Expression::Binary(
+                    BinaryOperator::Subtract,
+                    number(10),
+                    number(4)
+                )),
+                number(3)
+            )
+        );
+    }
+
+    #[test]
+    fn statements() {
+        let statements = parse_statements(
+            r#"
+            // Enable the debug clock
+            __var value = Read32(0x40021000) /* RCC */;
+            value |= 0x1UL;
+            Write32(0x40021000, value);
+            Message(0, "Value: %x", value)
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Declare {
+                    name: "value".to_string(),
+                    value: Expression::Call {
+                        function: "Read32".to_string(),
+                        arguments: vec![Expression::Number(0x40021000)]
+                    }
+                },
+                Statement::Assign {
+                    name: "value".to_string(),
+                    operator: Some(BinaryOperator::BitOr),
+                    value: Expression::Number(1)
+                },
+                Statement::Expression(Expression::Call {
+                    function: "Write32".to_string(),
+                    arguments: vec![
+                        Expression::Number(0x40021000),
+                        Expression::Variable("value".to_string())
+                    ]
+                }),
+                Statement::Expression(Expression::Call {
+                    function: "Message".to_string(),
+                    arguments: vec![
+                        Expression::Number(0),
+                        Expression::String("Value: %x".to_string()),
+                        Expression::Variable("value".to_string())
+                    ]
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_code() {
+        assert!(parse_statements("__var = 1;").is_err());
+        assert!(parse_statements("Write32(0x1000, 1").is_err());
+        assert!(parse_statements("a = 1 b = 2;").is_err());
+        assert!(parse_expression("(1 + 2").is_err());
+        assert!(parse_expression("0xZZ").is_err());
+    }
+}
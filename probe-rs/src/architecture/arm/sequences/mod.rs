@@ -1,6 +1,7 @@
 //! Debug sequences to operate special requirements ARM targets.
 
 pub mod atsame5x;
+pub mod cmsis_pack;
 pub mod efm32xg2;
 pub mod infineon;
 mod nrf;
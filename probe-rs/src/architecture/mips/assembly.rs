@@ -0,0 +1,138 @@
+//! MIPS32 instruction encodings used to build the programs executed from the debug segment.
+//!
+//! Only the small subset of instructions required for register and memory
+//! access in debug mode is implemented here.
+
+/// No operation (`sll $0, $0, 0`).
+pub(crate) const NOP: u32 = 0x0000_0000;
+
+/// Return from debug exception.
+pub(crate) const DERET: u32 = 0x4200_001F;
+
+/// Software debug breakpoint.
+pub(crate) const SDBBP: u32 = 0x7000_003F;
+
+/// Software debug breakpoint in the 16-bit microMIPS encoding.
+pub(crate) const SDBBP16: u16 = 0x46C0;
+
+/// Synchronize shared memory.
+pub(crate) const SYNC: u32 = 0x0000_000F;
+
+/// Execution hazard barrier.
+pub(crate) const EHB: u32 = 0x0000_00C0;
+
+const fn i_type(opcode: u32, rs: u8, rt: u8, immediate: u16) -> u32 {
+    opcode << 26 | (rs as u32 & 0x1F) << 21 | (rt as u32 & 0x1F) << 16 | immediate as u32
+}
+
+/// Assemble a `lui rt, immediate` instruction.
+pub(crate) const fn lui(rt: u8, immediate: u16) -> u32 {
+    i_type(0x0F, 0, rt, immediate)
+}
+
+/// Assemble a `ori rt, rs, immediate` instruction.
+pub(crate) const fn ori(rt: u8, rs: u8, immediate: u16) -> u32 {
+    i_type(0x0D, rs, rt, immediate)
+}
+
+/// Assemble a `lw rt, offset(base)` instruction.
+pub(crate) const fn lw(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x23, base, rt, offset as u16)
+}
+
+/// Assemble a `sw rt, offset(base)` instruction.
+pub(crate) const fn sw(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x2B, base, rt, offset as u16)
+}
+
+/// Assemble a `lhu rt, offset(base)` instruction.
+pub(crate) const fn lhu(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x25, base, rt, offset as u16)
+}
+
+/// Assemble a `sh rt, offset(base)` instruction.
+pub(crate) const fn sh(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x29, base, rt, offset as u16)
+}
+
+/// Assemble a `lbu rt, offset(base)` instruction.
+pub(crate) const fn lbu(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x24, base, rt, offset as u16)
+}
+
+/// Assemble a `sb rt, offset(base)` instruction.
+pub(crate) const fn sb(rt: u8, base: u8, offset: i16) -> u32 {
+    i_type(0x28, base, rt, offset as u16)
+}
+
+/// Assemble an unconditional relative branch (`beq $0, $0, offset`).
+///
+/// The offset is given in instructions, relative to the delay slot.
+pub(crate) const fn b(offset: i16) -> u32 {
+    i_type(0x04, 0, 0, offset as u16)
+}
+
+/// Assemble a `mfc0 rt, rd, sel` instruction.
+pub(crate) const fn mfc0(rt: u8, rd: u8, sel: u8) -> u32 {
+    0x10 << 26 | (rt as u32 & 0x1F) << 16 | (rd as u32 & 0x1F) << 11 | (sel as u32 & 0x7)
+}
+
+/// Assemble a `mtc0 rt, rd, sel` instruction.
+pub(crate) const fn mtc0(rt: u8, rd: u8, sel: u8) -> u32 {
+    mfc0(rt, rd, sel) | 0x4 << 21
+}
+
+/// Assemble a `mflo rd` instruction.
+pub(crate) const fn mflo(rd: u8) -> u32 {
+    (rd as u32 & 0x1F) << 11 | 0x12
+}
+
+/// Assemble a `mfhi rd` instruction.
+pub(crate) const fn mfhi(rd: u8) -> u32 {
+    (rd as u32 & 0x1F) << 11 | 0x10
+}
+
+/// Assemble a `mtlo rs` instruction.
+pub(crate) const fn mtlo(rs: u8) -> u32 {
+    (rs as u32 & 0x1F) << 21 | 0x13
+}
+
+/// Assemble a `mthi rs` instruction.
+pub(crate) const fn mthi(rs: u8) -> u32 {
+    (rs as u32 & 0x1F) << 21 | 0x11
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_immediate_instructions() {
+        // lui $8, 0xff20
+        assert_eq!(lui(8, 0xff20), 0x3c08_ff20);
+        // ori $8, $8, 0x1234
+        assert_eq!(ori(8, 8, 0x1234), 0x3508_1234);
+        // lw $9, 0x1000($15)
+        assert_eq!(lw(9, 15, 0x1000), 0x8de9_1000);
+        // sw $9, -4($8)
+        assert_eq!(sw(9, 8, -4), 0xad09_fffc);
+        // lhu $9, 2($15)
+        assert_eq!(lhu(9, 15, 2), 0x95e9_0002);
+        // sh $9, -2($8)
+        assert_eq!(sh(9, 8, -2), 0xa509_fffe);
+    }
+
+    #[test]
+    fn encode_coprocessor_instructions() {
+        // mfc0 $8, $24 (DEPC)
+        assert_eq!(mfc0(8, 24, 0), 0x4008_c000);
+        // mtc0 $15, $31 (DESAVE)
+        assert_eq!(mtc0(15, 31, 0), 0x408f_f800);
+    }
+
+    #[test]
+    fn encode_branch() {
+        // b -3
+        assert_eq!(b(-3), 0x1000_fffd);
+    }
+}
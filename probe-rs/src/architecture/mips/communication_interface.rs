@@ -0,0 +1,741 @@
+//! EJTAG Communication
+//!
+//! This module implements communication with a MIPS32 core
+//! through the EJTAG interface, as described in the MIPS EJTAG
+//! specification, revision 4.14 and later.
+//!
+//! All accesses to the core state are done by executing small programs
+//! on the core while it is in debug mode. The instructions and data of these
+//! programs are served by the probe through processor accesses (PrAcc) to
+//! the debug segment (dmseg).
+
+use super::{
+    assembly,
+    ejtag::{instruction, Ejtag, EjtagControl},
+};
+use crate::{
+    memory::valid_32bit_address, probe::JTAGAccess, DebugProbeError, Error as ProbeRsError,
+    MemoryInterface, Probe,
+};
+use std::time::{Duration, Instant};
+
+/// Some error occurred when working with the MIPS core.
+#[derive(thiserror::Error, Debug)]
+pub enum MipsError {
+    /// An error with operating the debug probe occurred.
+    #[error("Debug Probe Error")]
+    DebugProbe(#[from] DebugProbeError),
+    /// A timeout occurred while waiting for the core.
+    #[error("Timeout while waiting for the core.")]
+    Timeout,
+    /// The connected target is not a MIPS device.
+    #[error("Connected target is not a MIPS device.")]
+    NoMipsTarget,
+    /// The core is not in debug mode, which is required for the requested operation.
+    #[error("The core is not in debug mode.")]
+    NotInDebugMode,
+    /// The core was reset while an operation was in progress.
+    #[error("The core was reset during an EJTAG operation.")]
+    ResetOccurred,
+    /// The core accessed an address of the debug segment which is not used by the executed program.
+    #[error("Unexpected processor access to address {address:#010x} of the debug segment.")]
+    UnexpectedAccess {
+        /// The address of the access.
+        address: u32,
+    },
+}
+
+impl From<MipsError> for ProbeRsError {
+    fn from(err: MipsError) -> Self {
+        match err {
+            MipsError::DebugProbe(e) => e.into(),
+            MipsError::Timeout => ProbeRsError::Timeout,
+            other => ProbeRsError::Mips(other),
+        }
+    }
+}
+
+/// Timeout for MIPS operations.
+const MIPS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Start of the debug segment. Register `$15` holds this value while
+/// a program is executed, and is used as base for all dmseg accesses.
+const DMSEG: u32 = 0xFF20_0000;
+
+/// Location of the debug exception vector, when `ProbTrap` is set.
+const PRACC_TEXT: u32 = DMSEG + 0x200;
+
+/// Offset of the program input parameters from the start of the debug segment.
+const PRACC_PARAM_IN: i16 = 0x1000;
+
+/// Offset of the program output parameters from the start of the debug segment.
+const PRACC_PARAM_OUT: i16 = 0x2000;
+
+/// Offset of the scratch area used to save temporary registers.
+const PRACC_SCRATCH: i16 = 0x4000;
+
+/// Size of each parameter area, in words.
+const PRACC_PARAM_WORDS: usize = 0x400;
+
+/// Number of words which are read or written by a single program.
+const PRACC_CHUNK_SIZE: usize = 256;
+
+/// The `DESAVE` register, used to save `$15` during program execution.
+const CP0_DESAVE: u8 = 31;
+
+/// Temporary register used by the programs.
+const TMP0: u8 = 8;
+/// Temporary register used by the programs.
+const TMP1: u8 = 9;
+/// Base register holding the start of the debug segment.
+const BASE: u8 = 15;
+
+/// A interface that implements controls for MIPS cores.
+#[derive(Debug)]
+pub struct MipsCommunicationInterface {
+    ejtag: Ejtag,
+}
+
+impl MipsCommunicationInterface {
+    /// Creates a new MIPS communication interface with a given probe driver.
+    pub fn new(probe: Box<dyn JTAGAccess>) -> Result<Self, (Box<dyn JTAGAccess>, MipsError)> {
+        let ejtag = Ejtag::new(probe)?;
+
+        Ok(Self { ejtag })
+    }
+
+    /// Read the targets IDCODE.
+    pub fn read_idcode(&mut self) -> Result<u32, DebugProbeError> {
+        self.ejtag.read_idcode()
+    }
+
+    /// Read the EJTAG implementation register of the target.
+    pub fn read_impcode(&mut self) -> Result<u32, DebugProbeError> {
+        self.ejtag.read_impcode().map(|impcode| impcode.0)
+    }
+
+    /// Enable the probe to serve processor accesses to the debug segment.
+    ///
+    /// This has to be done before the core can be halted.
+    pub fn enter_debug_mode(&mut self) -> Result<(), MipsError> {
+        tracing::debug!("Building MIPS interface");
+
+        let impcode = self.ejtag.read_impcode()?;
+        tracing::debug!("EJTAG implementation: {:?}", impcode);
+
+        if impcode.mips64() {
+            tracing::warn!("64-bit MIPS cores are not supported, only 32-bit access will be used.");
+        }
+
+        let control = self.ejtag.control(EjtagControl::base())?;
+
+        if control.rocc() {
+            self.clear_reset_occurred()?;
+        }
+
+        Ok(())
+    }
+
+    /// Load an instruction into the instruction register of the TAP.
+    ///
+    /// This can be used by debug sequences for vendor specific instructions,
+    /// which only select the bypass register.
+    pub fn write_instruction(&mut self, instruction: u32) -> Result<(), MipsError> {
+        self.ejtag.execute_instruction(instruction)?;
+
+        Ok(())
+    }
+
+    /// Destruct the interface and return the stored probe driver.
+    pub fn close(self) -> Probe {
+        Probe::from_attached_probe(self.ejtag.probe.into_probe())
+    }
+
+    pub(super) fn read_control(&mut self) -> Result<EjtagControl, MipsError> {
+        let control = self.ejtag.control(EjtagControl::base())?;
+
+        if control.rocc() {
+            self.clear_reset_occurred()?;
+        }
+
+        Ok(control)
+    }
+
+    fn clear_reset_occurred(&mut self) -> Result<(), MipsError> {
+        tracing::debug!("Clearing reset occurred (Rocc) bit");
+
+        let mut control = EjtagControl::base();
+        control.set_rocc(false);
+        self.ejtag.control(control)?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the core is in debug mode.
+    pub(super) fn in_debug_mode(&mut self) -> Result<bool, MipsError> {
+        Ok(self.read_control()?.dm())
+    }
+
+    /// Request a debug interrupt, which halts the core.
+    pub(super) fn request_halt(&mut self) -> Result<(), MipsError> {
+        let mut control = EjtagControl::base();
+        control.set_ejtagbrk(true);
+        self.ejtag.control(control)?;
+
+        Ok(())
+    }
+
+    /// Reset the processor and the peripherals.
+    pub(super) fn reset(&mut self) -> Result<(), MipsError> {
+        let mut control = EjtagControl::base();
+        control.set_prrst(true);
+        control.set_perrst(true);
+        self.ejtag.control(control)?;
+
+        self.ejtag.control(EjtagControl::base())?;
+
+        // Not all cores implement PrRst, so use the reset pin as well.
+        if let Err(e) = self.ejtag.target_reset() {
+            tracing::debug!("Probe reset failed, relying on PrRst only: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Select whether the core should enter debug mode directly after the next reset.
+    pub(super) fn set_boot_into_debug_mode(&mut self, enabled: bool) -> Result<(), MipsError> {
+        let instruction = if enabled {
+            instruction::EJTAGBOOT
+        } else {
+            instruction::NORMALBOOT
+        };
+
+        self.ejtag.execute_instruction(instruction)?;
+
+        Ok(())
+    }
+
+    /// Wait until the core performs an access to the debug segment, and return the address.
+    fn wait_for_pracc(&mut self) -> Result<(u32, bool), MipsError> {
+        let start = Instant::now();
+
+        loop {
+            let control = self.ejtag.control(EjtagControl::base())?;
+
+            if control.rocc() {
+                self.clear_reset_occurred()?;
+                return Err(MipsError::ResetOccurred);
+            }
+
+            if control.pracc() {
+                let address = self.ejtag.read_address()?;
+                return Ok((address, control.prnw()));
+            }
+
+            if start.elapsed() > MIPS_TIMEOUT {
+                return Err(MipsError::Timeout);
+            }
+        }
+    }
+
+    /// Signal the core that the pending access has been handled.
+    fn finish_pracc(&mut self) -> Result<(), MipsError> {
+        let mut control = EjtagControl::base();
+        control.set_pracc(false);
+        self.ejtag.control(control)?;
+
+        Ok(())
+    }
+
+    /// Execute a program on the core, which has to be halted.
+    ///
+    /// The program is wrapped so that `$15` contains the start of the debug segment,
+    /// and the original value is restored at the end. Input parameters can be
+    /// loaded relative to `$15` from [`PRACC_PARAM_IN`], results are stored to
+    /// [`PRACC_PARAM_OUT`].
+    pub(super) fn execute(
+        &mut self,
+        body: &[u32],
+        param_in: &[u32],
+        param_out: &mut [u32],
+    ) -> Result<(), MipsError> {
+        if !self.in_debug_mode()? {
+            return Err(MipsError::NotInDebugMode);
+        }
+
+        let mut code = Vec::with_capacity(body.len() + 4);
+        code.push(assembly::mtc0(BASE, CP0_DESAVE, 0));
+        code.push(assembly::lui(BASE, (DMSEG >> 16) as u16));
+        code.extend_from_slice(body);
+        // Jump back to the start of the debug vector, and restore $15 in the delay slot.
+        code.push(assembly::b(-(code.len() as i16 + 1)));
+        code.push(assembly::mfc0(BASE, CP0_DESAVE, 0));
+
+        let mut scratch = [0u32; 4];
+        let mut passes = 0;
+
+        loop {
+            let (address, is_write) = self.wait_for_pracc()?;
+
+            if address == PRACC_TEXT {
+                if passes > 0 {
+                    // The program finished, and the core waits for the next instruction.
+                    break;
+                }
+                passes += 1;
+            }
+
+            let offset = address.wrapping_sub(DMSEG);
+
+            if is_write {
+                let data = self.ejtag.read_data()?;
+                let slot = area_index(offset, PRACC_PARAM_OUT)
+                    .and_then(|index| param_out.get_mut(index))
+                    .or_else(|| {
+                        area_index(offset, PRACC_SCRATCH).and_then(|index| scratch.get_mut(index))
+                    });
+
+                match slot {
+                    Some(slot) => *slot = data,
+                    None => return Err(MipsError::UnexpectedAccess { address }),
+                }
+            } else {
+                let data = if (PRACC_TEXT - DMSEG..PRACC_PARAM_IN as u32).contains(&offset) {
+                    let index = (offset - (PRACC_TEXT - DMSEG)) as usize / 4;
+                    code.get(index).copied().unwrap_or(assembly::NOP)
+                } else if let Some(value) = area_index(offset, PRACC_PARAM_IN)
+                    .and_then(|index| param_in.get(index))
+                    .or_else(|| {
+                        area_index(offset, PRACC_PARAM_OUT).and_then(|index| param_out.get(index))
+                    })
+                    .or_else(|| area_index(offset, PRACC_SCRATCH).and_then(|i| scratch.get(i)))
+                {
+                    *value
+                } else {
+                    return Err(MipsError::UnexpectedAccess { address });
+                };
+
+                self.ejtag.write_data(data)?;
+            }
+
+            self.finish_pracc()?;
+        }
+
+        Ok(())
+    }
+
+    /// Leave debug mode, by executing a `DERET` instruction.
+    pub(super) fn resume(&mut self) -> Result<(), MipsError> {
+        if !self.in_debug_mode()? {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let mut deret_issued = false;
+
+        loop {
+            let control = self.read_control()?;
+
+            if !control.dm() && deret_issued {
+                return Ok(());
+            }
+
+            if control.pracc() {
+                let address = self.ejtag.read_address()?;
+
+                if address == PRACC_TEXT {
+                    if deret_issued {
+                        // The core entered debug mode again, e.g. after a single step.
+                        return Ok(());
+                    }
+
+                    self.ejtag.write_data(assembly::DERET)?;
+                    deret_issued = true;
+                } else if control.prnw() {
+                    // Should not happen, just discard the data.
+                    self.ejtag.read_data()?;
+                } else {
+                    self.ejtag.write_data(assembly::NOP)?;
+                }
+
+                self.finish_pracc()?;
+            }
+
+            if start.elapsed() > MIPS_TIMEOUT {
+                return Err(MipsError::Timeout);
+            }
+        }
+    }
+
+    /// Read a general purpose register.
+    pub(super) fn read_gpr(&mut self, register: u8) -> Result<u32, MipsError> {
+        let mut out = [0u32];
+
+        if register == BASE {
+            self.execute(
+                &[
+                    assembly::sw(TMP0, BASE, PRACC_SCRATCH),
+                    assembly::mfc0(TMP0, CP0_DESAVE, 0),
+                    assembly::sw(TMP0, BASE, PRACC_PARAM_OUT),
+                    assembly::lw(TMP0, BASE, PRACC_SCRATCH),
+                ],
+                &[],
+                &mut out,
+            )?;
+        } else {
+            self.execute(
+                &[assembly::sw(register, BASE, PRACC_PARAM_OUT)],
+                &[],
+                &mut out,
+            )?;
+        }
+
+        Ok(out[0])
+    }
+
+    /// Write a general purpose register.
+    pub(super) fn write_gpr(&mut self, register: u8, value: u32) -> Result<(), MipsError> {
+        if register == 0 {
+            return Ok(());
+        }
+
+        if register == BASE {
+            // $15 is restored from DESAVE at the end of the program.
+            self.write_via_tmp(value, assembly::mtc0(TMP0, CP0_DESAVE, 0))
+        } else {
+            self.execute(
+                &[assembly::lw(register, BASE, PRACC_PARAM_IN), assembly::SYNC],
+                &[value],
+                &mut [],
+            )
+        }
+    }
+
+    /// Read a coprocessor 0 register.
+    pub(super) fn read_cp0(&mut self, register: u8, select: u8) -> Result<u32, MipsError> {
+        self.read_via_tmp(assembly::mfc0(TMP0, register, select))
+    }
+
+    /// Write a coprocessor 0 register.
+    pub(super) fn write_cp0(
+        &mut self,
+        register: u8,
+        select: u8,
+        value: u32,
+    ) -> Result<(), MipsError> {
+        self.write_via_tmp(value, assembly::mtc0(TMP0, register, select))
+    }
+
+    /// Read the `lo` register.
+    pub(super) fn read_lo(&mut self) -> Result<u32, MipsError> {
+        self.read_via_tmp(assembly::mflo(TMP0))
+    }
+
+    /// Read the `hi` register.
+    pub(super) fn read_hi(&mut self) -> Result<u32, MipsError> {
+        self.read_via_tmp(assembly::mfhi(TMP0))
+    }
+
+    /// Write the `lo` register.
+    pub(super) fn write_lo(&mut self, value: u32) -> Result<(), MipsError> {
+        self.write_via_tmp(value, assembly::mtlo(TMP0))
+    }
+
+    /// Write the `hi` register.
+    pub(super) fn write_hi(&mut self, value: u32) -> Result<(), MipsError> {
+        self.write_via_tmp(value, assembly::mthi(TMP0))
+    }
+
+    /// Execute `instruction`, which places a value into the temporary register, and return the value.
+    fn read_via_tmp(&mut self, instruction: u32) -> Result<u32, MipsError> {
+        let mut out = [0u32];
+
+        self.execute(
+            &[
+                assembly::sw(TMP0, BASE, PRACC_SCRATCH),
+                instruction,
+                assembly::sw(TMP0, BASE, PRACC_PARAM_OUT),
+                assembly::lw(TMP0, BASE, PRACC_SCRATCH),
+            ],
+            &[],
+            &mut out,
+        )?;
+
+        Ok(out[0])
+    }
+
+    /// Load `value` into the temporary register, and execute `instruction`, which consumes it.
+    fn write_via_tmp(&mut self, value: u32, instruction: u32) -> Result<(), MipsError> {
+        self.execute(
+            &[
+                assembly::sw(TMP0, BASE, PRACC_SCRATCH),
+                assembly::lw(TMP0, BASE, PRACC_PARAM_IN),
+                instruction,
+                assembly::EHB,
+                assembly::lw(TMP0, BASE, PRACC_SCRATCH),
+            ],
+            &[value],
+            &mut [],
+        )
+    }
+
+    /// Build the prologue of a memory access program, which saves the temporary
+    /// registers and loads `address` into `$8`.
+    fn memory_access_prologue(address: u32) -> Vec<u32> {
+        vec![
+            assembly::sw(TMP0, BASE, PRACC_SCRATCH),
+            assembly::sw(TMP1, BASE, PRACC_SCRATCH + 4),
+            assembly::lui(TMP0, (address >> 16) as u16),
+            assembly::ori(TMP0, TMP0, address as u16),
+        ]
+    }
+
+    /// Build the epilogue of a memory access program, which restores the temporary registers.
+    fn memory_access_epilogue(program: &mut Vec<u32>) {
+        program.push(assembly::SYNC);
+        program.push(assembly::lw(TMP1, BASE, PRACC_SCRATCH + 4));
+        program.push(assembly::lw(TMP0, BASE, PRACC_SCRATCH));
+    }
+
+    fn read_memory(
+        &mut self,
+        address: u32,
+        data: &mut [u32],
+        load: fn(u8, u8, i16) -> u32,
+        width: u32,
+    ) -> Result<(), MipsError> {
+        for (chunk_index, chunk) in data.chunks_mut(PRACC_CHUNK_SIZE).enumerate() {
+            let chunk_address = address + (chunk_index * PRACC_CHUNK_SIZE) as u32 * width;
+
+            let mut program = Self::memory_access_prologue(chunk_address);
+
+            for index in 0..chunk.len() {
+                program.push(load(TMP1, TMP0, (index as u32 * width) as i16));
+                program.push(assembly::sw(
+                    TMP1,
+                    BASE,
+                    PRACC_PARAM_OUT + (index * 4) as i16,
+                ));
+            }
+
+            Self::memory_access_epilogue(&mut program);
+
+            self.execute(&program, &[], chunk)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_memory(
+        &mut self,
+        address: u32,
+        data: &[u32],
+        store: fn(u8, u8, i16) -> u32,
+        width: u32,
+    ) -> Result<(), MipsError> {
+        for (chunk_index, chunk) in data.chunks(PRACC_CHUNK_SIZE).enumerate() {
+            let chunk_address = address + (chunk_index * PRACC_CHUNK_SIZE) as u32 * width;
+
+            let mut program = Self::memory_access_prologue(chunk_address);
+
+            for index in 0..chunk.len() {
+                program.push(assembly::lw(
+                    TMP1,
+                    BASE,
+                    PRACC_PARAM_IN + (index * 4) as i16,
+                ));
+                program.push(store(TMP1, TMP0, (index as u32 * width) as i16));
+            }
+
+            Self::memory_access_epilogue(&mut program);
+
+            self.execute(&program, chunk, &mut [])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the word index into the parameter area starting at `area`, if `offset` is located in it.
+fn area_index(offset: u32, area: i16) -> Option<usize> {
+    let area = area as u32;
+    let end = area + (PRACC_PARAM_WORDS * 4) as u32;
+
+    (area..end)
+        .contains(&offset)
+        .then(|| (offset - area) as usize / 4)
+}
+
+impl MemoryInterface for MipsCommunicationInterface {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        false
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, crate::Error> {
+        let mut data = [0u32; 2];
+        self.read_32(address, &mut data)?;
+
+        Ok(data[0] as u64 | (data[1] as u64) << 32)
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, crate::Error> {
+        let mut data = [0u32];
+        self.read_32(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, crate::Error> {
+        let mut data = [0u16];
+        self.read_16(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, crate::Error> {
+        let mut data = [0u8];
+        self.read_8(address, &mut data)?;
+
+        Ok(data[0])
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), crate::Error> {
+        for (i, word) in data.iter_mut().enumerate() {
+            *word = self.read_word_64(address + (i * 8) as u64)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        if address % 4 != 0 {
+            return Err(crate::Error::MemoryNotAligned {
+                address: address as u64,
+                alignment: 4,
+            });
+        }
+
+        self.read_memory(address, data, assembly::lw, 4)?;
+
+        Ok(())
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        if address % 2 != 0 {
+            return Err(crate::Error::MemoryNotAligned {
+                address: address as u64,
+                alignment: 2,
+            });
+        }
+
+        let mut words = vec![0u32; data.len()];
+        self.read_memory(address, &mut words, assembly::lhu, 2)?;
+
+        for (halfword, word) in data.iter_mut().zip(words) {
+            *halfword = word as u16;
+        }
+
+        Ok(())
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        let mut words = vec![0u32; data.len()];
+        self.read_memory(address, &mut words, assembly::lbu, 1)?;
+
+        for (byte, word) in data.iter_mut().zip(words) {
+            *byte = word as u8;
+        }
+
+        Ok(())
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), crate::Error> {
+        self.write_32(address, &[data as u32, (data >> 32) as u32])
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), crate::Error> {
+        self.write_32(address, &[data])
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), crate::Error> {
+        self.write_16(address, &[data])
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), crate::Error> {
+        self.write_8(address, &[data])
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), crate::Error> {
+        for (i, word) in data.iter().enumerate() {
+            self.write_word_64(address + (i * 8) as u64, *word)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        if address % 4 != 0 {
+            return Err(crate::Error::MemoryNotAligned {
+                address: address as u64,
+                alignment: 4,
+            });
+        }
+
+        self.write_memory(address, data, assembly::sw, 4)?;
+
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        if address % 2 != 0 {
+            return Err(crate::Error::MemoryNotAligned {
+                address: address as u64,
+                alignment: 2,
+            });
+        }
+
+        let words: Vec<u32> = data.iter().map(|halfword| *halfword as u32).collect();
+        self.write_memory(address, &words, assembly::sh, 2)?;
+
+        Ok(())
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
+        let address = valid_32bit_address(address)?;
+
+        let words: Vec<u32> = data.iter().map(|byte| *byte as u32).collect();
+        self.write_memory(address, &words, assembly::sb, 1)?;
+
+        Ok(())
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
+        if address % 4 != 0 || data.len() % 4 != 0 {
+            return self.write_8(address, data);
+        }
+
+        let words: Vec<u32> = data
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        self.write_32(address, &words)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, crate::Error> {
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+//! EJTAG TAP controller handling
+//!
+//! The EJTAG TAP gives access to the control, address and data registers
+//! which are used to serve processor accesses (PrAcc) to the debug segment.
+use std::convert::TryInto;
+
+use bitfield::bitfield;
+
+use super::communication_interface::MipsError;
+use crate::{probe::JTAGAccess, DebugProbeError};
+
+/// Length of the EJTAG instruction register.
+const EJTAG_IR_LENGTH: u32 = 5;
+
+/// EJTAG TAP instructions.
+pub(crate) mod instruction {
+    /// Select the IDCODE register.
+    pub const IDCODE: u32 = 0x01;
+    /// Select the implementation register.
+    pub const IMPCODE: u32 = 0x03;
+    /// Select the address register.
+    pub const ADDRESS: u32 = 0x08;
+    /// Select the data register.
+    pub const DATA: u32 = 0x09;
+    /// Select the EJTAG control register.
+    pub const CONTROL: u32 = 0x0A;
+    /// Let the processor take a debug exception directly after reset.
+    pub const EJTAGBOOT: u32 = 0x0C;
+    /// Let the processor execute the normal reset handler after reset.
+    pub const NORMALBOOT: u32 = 0x0D;
+}
+
+bitfield! {
+    /// The EJTAG control register (ECR).
+    #[derive(Copy, Clone)]
+    pub struct EjtagControl(u32);
+    impl Debug;
+    /// Reset occurred. Has to be cleared by writing 0.
+    pub rocc, set_rocc: 31;
+    /// Peripheral reset.
+    pub perrst, set_perrst: 20;
+    /// Pending processor access is a write.
+    pub prnw, _: 19;
+    /// Pending processor access. Cleared by writing 0 to finish the access.
+    pub pracc, set_pracc: 18;
+    /// Processor reset.
+    pub prrst, set_prrst: 16;
+    /// Processor accesses to the debug segment are served by the probe.
+    pub proben, set_proben: 15;
+    /// Debug exception vector is located in the debug segment.
+    pub probtrap, set_probtrap: 14;
+    /// Request a debug interrupt.
+    pub ejtagbrk, set_ejtagbrk: 12;
+    /// The processor is in debug mode.
+    pub dm, _: 3;
+}
+
+impl EjtagControl {
+    /// The control value used for all regular accesses.
+    ///
+    /// `Rocc` and `PrAcc` are written as one, so that their state is not changed.
+    pub fn base() -> Self {
+        let mut control = EjtagControl(0);
+        control.set_rocc(true);
+        control.set_pracc(true);
+        control.set_proben(true);
+        control.set_probtrap(true);
+        control
+    }
+}
+
+bitfield! {
+    /// The EJTAG implementation register.
+    #[derive(Copy, Clone)]
+    pub struct Impcode(u32);
+    impl Debug;
+    /// EJTAG version.
+    pub u8, ejtag_version, _: 31, 29;
+    /// DINT signal is supported.
+    pub dint_sup, _: 24;
+    /// Width of the ASID field.
+    pub u8, asid_size, _: 22, 21;
+    /// The processor does not support DMA access.
+    pub no_dma, _: 14;
+    /// The processor is a 64-bit implementation.
+    pub mips64, _: 0;
+}
+
+/// Access to the EJTAG TAP controller.
+#[derive(Debug)]
+pub struct Ejtag {
+    pub probe: Box<dyn JTAGAccess>,
+}
+
+impl Ejtag {
+    pub fn new(mut probe: Box<dyn JTAGAccess>) -> Result<Self, (Box<dyn JTAGAccess>, MipsError)> {
+        probe.set_ir_len(EJTAG_IR_LENGTH);
+
+        let mut ejtag = Self { probe };
+
+        let idcode = match ejtag.read_idcode() {
+            Ok(value) => value,
+            Err(e) => return Err((ejtag.probe, e.into())),
+        };
+
+        if idcode == 0 || idcode == u32::MAX {
+            return Err((ejtag.probe, MipsError::NoMipsTarget));
+        }
+
+        tracing::debug!("EJTAG IDCODE: {:#010x}", idcode);
+
+        Ok(ejtag)
+    }
+
+    fn read_u32(&mut self, instruction: u32) -> Result<u32, DebugProbeError> {
+        let value = self.probe.read_register(instruction, 32)?;
+
+        Ok(u32::from_le_bytes((&value[..]).try_into().unwrap()))
+    }
+
+    fn write_u32(&mut self, instruction: u32, value: u32) -> Result<u32, DebugProbeError> {
+        let value = self
+            .probe
+            .write_register(instruction, &value.to_le_bytes(), 32)?;
+
+        Ok(u32::from_le_bytes((&value[..]).try_into().unwrap()))
+    }
+
+    pub fn read_idcode(&mut self) -> Result<u32, DebugProbeError> {
+        self.read_u32(instruction::IDCODE)
+    }
+
+    pub fn read_impcode(&mut self) -> Result<Impcode, DebugProbeError> {
+        self.read_u32(instruction::IMPCODE).map(Impcode)
+    }
+
+    /// Write the EJTAG control register, and return the previous value.
+    pub fn control(&mut self, control: EjtagControl) -> Result<EjtagControl, DebugProbeError> {
+        self.write_u32(instruction::CONTROL, control.0)
+            .map(EjtagControl)
+    }
+
+    /// Read the address of the pending processor access.
+    pub fn read_address(&mut self) -> Result<u32, DebugProbeError> {
+        self.read_u32(instruction::ADDRESS)
+    }
+
+    /// Read the data of a pending processor write.
+    pub fn read_data(&mut self) -> Result<u32, DebugProbeError> {
+        self.read_u32(instruction::DATA)
+    }
+
+    /// Provide the data for a pending processor read.
+    pub fn write_data(&mut self, value: u32) -> Result<(), DebugProbeError> {
+        self.write_u32(instruction::DATA, value).map(|_| ())
+    }
+
+    /// Load one of the instructions which only select the bypass register,
+    /// e.g. [`instruction::EJTAGBOOT`].
+    pub fn execute_instruction(&mut self, instruction: u32) -> Result<(), DebugProbeError> {
+        self.probe.read_register(instruction, 1).map(|_| ())
+    }
+
+    pub fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        self.probe.target_reset()
+    }
+}
@@ -0,0 +1,5 @@
+pub(crate) mod mips32 {
+    use crate::core::ExceptionInterface;
+
+    impl<'probe> ExceptionInterface for crate::architecture::mips::Mips32<'probe> {}
+}
@@ -0,0 +1,468 @@
+//! A simulated MIPS32 core with an EJTAG TAP, used to test the MIPS debug implementation.
+//!
+//! The mock contains a small interpreter for the instructions used by the debug
+//! programs. Instruction fetches and data accesses to the debug segment are turned
+//! into processor accesses (PrAcc), which have to be served through the EJTAG
+//! address, data and control registers, just like on real hardware.
+
+use std::collections::HashMap;
+
+use super::{assembly, ejtag::instruction};
+use crate::{probe::JTAGAccess, DebugProbe, DebugProbeError, DebugProbeSelector, WireProtocol};
+use probe_rs_target::ScanChainElement;
+
+const DMSEG_START: u32 = 0xFF20_0000;
+const DMSEG_END: u32 = 0xFF30_0000;
+const DEBUG_VECTOR: u32 = 0xFF20_0200;
+const IBS: u32 = 0xFF30_1000;
+
+pub(super) const MOCK_IDCODE: u32 = 0x0430_7053;
+pub(super) const MOCK_IMPCODE: u32 = 0x2000_4000;
+
+/// Number of instructions which are executed when the core is resumed,
+/// until it is considered to run in an endless loop.
+const RUN_LIMIT: usize = 64;
+
+mod debug_bits {
+    pub const DSS: u32 = 1 << 0;
+    pub const DBP: u32 = 1 << 1;
+    pub const DIB: u32 = 1 << 4;
+    pub const DINT: u32 = 1 << 5;
+    pub const SST: u32 = 1 << 8;
+    pub const DM: u32 = 1 << 30;
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AccessKind {
+    Fetch,
+    Load { rt: usize, width: u32 },
+    Store,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Access {
+    address: u32,
+    kind: AccessKind,
+    data: u32,
+}
+
+enum Execution {
+    Done,
+    Pending,
+    Breakpoint,
+    Deret,
+}
+
+#[derive(Debug)]
+pub(super) struct MockEjtag {
+    pub regs: [u32; 32],
+    pub lo: u32,
+    pub hi: u32,
+    pub pc: u32,
+    pub cp0: HashMap<(u8, u8), u32>,
+    memory: HashMap<u32, u8>,
+
+    in_debug: bool,
+    delay_slot_target: Option<u32>,
+    pending: Option<Access>,
+    rocc: bool,
+}
+
+impl MockEjtag {
+    pub fn new(pc: u32) -> Self {
+        let mut mock = Self {
+            regs: [0; 32],
+            lo: 0,
+            hi: 0,
+            pc,
+            cp0: HashMap::new(),
+            memory: HashMap::new(),
+            in_debug: false,
+            delay_slot_target: None,
+            pending: None,
+            rocc: false,
+        };
+
+        // Four instruction breakpoint units.
+        mock.write_memory(IBS, 4, 4 << 24);
+
+        mock
+    }
+
+    pub fn read_memory(&self, address: u32, width: u32) -> u32 {
+        (0..width).fold(0, |value, i| {
+            value | (*self.memory.get(&(address + i)).unwrap_or(&0) as u32) << (8 * i)
+        })
+    }
+
+    pub fn write_memory(&mut self, address: u32, width: u32, value: u32) {
+        for i in 0..width {
+            self.memory.insert(address + i, (value >> (8 * i)) as u8);
+        }
+    }
+
+    fn debug_register(&self) -> u32 {
+        *self.cp0.get(&(23, 0)).unwrap_or(&0)
+    }
+
+    fn control(&self) -> u32 {
+        let mut control = 1 << 15 | 1 << 14;
+
+        if self.rocc {
+            control |= 1 << 31;
+        }
+
+        if let Some(access) = self.pending {
+            control |= 1 << 18;
+
+            if matches!(access.kind, AccessKind::Store) {
+                control |= 1 << 19;
+            }
+        }
+
+        if self.in_debug {
+            control |= 1 << 3;
+        }
+
+        control
+    }
+
+    fn write_control(&mut self, value: u32) {
+        if value & 1 << 31 == 0 {
+            self.rocc = false;
+        }
+
+        if value & 1 << 12 != 0 && !self.in_debug {
+            self.enter_debug_mode(debug_bits::DINT);
+        }
+
+        if value & 1 << 18 == 0 {
+            if let Some(access) = self.pending.take() {
+                self.complete_access(access);
+            }
+        }
+    }
+
+    fn enter_debug_mode(&mut self, cause: u32) {
+        let sst = self.debug_register() & debug_bits::SST;
+
+        self.cp0.insert((24, 0), self.pc);
+        self.cp0.insert((23, 0), debug_bits::DM | sst | cause);
+
+        self.in_debug = true;
+        self.delay_slot_target = None;
+        self.pc = DEBUG_VECTOR;
+
+        self.pending = Some(Access {
+            address: DEBUG_VECTOR,
+            kind: AccessKind::Fetch,
+            data: 0,
+        });
+    }
+
+    fn complete_access(&mut self, access: Access) {
+        let result = match access.kind {
+            AccessKind::Fetch => self.execute(access.data),
+            AccessKind::Load { rt, width } => {
+                let value = match width {
+                    1 => access.data & 0xFF,
+                    2 => access.data & 0xFFFF,
+                    _ => access.data,
+                };
+                self.set_reg(rt, value);
+                Execution::Done
+            }
+            AccessKind::Store => Execution::Done,
+        };
+
+        self.after_execution(result);
+    }
+
+    fn after_execution(&mut self, result: Execution) {
+        match result {
+            Execution::Pending => {}
+            Execution::Done => {
+                self.advance_pc(None);
+                self.fetch();
+            }
+            Execution::Breakpoint => self.enter_debug_mode(debug_bits::DBP),
+            Execution::Deret => {
+                self.in_debug = false;
+                self.pc = *self.cp0.get(&(24, 0)).unwrap_or(&0);
+                self.delay_slot_target = None;
+                self.cp0
+                    .insert((23, 0), self.debug_register() & !debug_bits::DM);
+                self.run_user_code();
+            }
+        }
+    }
+
+    fn advance_pc(&mut self, branch_target: Option<u32>) {
+        let next = self.delay_slot_target.take().unwrap_or(self.pc + 4);
+        self.delay_slot_target = branch_target;
+        self.pc = next;
+    }
+
+    fn fetch(&mut self) {
+        if self.in_debug && in_dmseg(self.pc) {
+            self.pending = Some(Access {
+                address: self.pc,
+                kind: AccessKind::Fetch,
+                data: 0,
+            });
+        }
+    }
+
+    fn run_user_code(&mut self) {
+        let single_step = self.debug_register() & debug_bits::SST != 0;
+
+        for executed in 0..RUN_LIMIT {
+            if single_step && executed == 1 {
+                self.enter_debug_mode(debug_bits::DSS);
+                return;
+            }
+
+            if self.breakpoint_hit(self.pc) {
+                self.enter_debug_mode(debug_bits::DIB);
+                return;
+            }
+
+            let instruction = self.read_memory(self.pc, 4);
+
+            match self.execute(instruction) {
+                Execution::Breakpoint => {
+                    self.enter_debug_mode(debug_bits::DBP);
+                    return;
+                }
+                // Taken branches already advanced the program counter.
+                Execution::Pending => {}
+                _ => self.advance_pc(None),
+            }
+        }
+    }
+
+    fn breakpoint_hit(&self, pc: u32) -> bool {
+        (0..4).any(|unit| {
+            let iba = 0xFF30_1100 + 0x100 * unit;
+            self.read_memory(iba + 0x18, 4) & 1 != 0 && self.read_memory(iba, 4) == pc
+        })
+    }
+
+    fn set_reg(&mut self, index: usize, value: u32) {
+        if index != 0 {
+            self.regs[index] = value;
+        }
+    }
+
+    fn execute(&mut self, instruction: u32) -> Execution {
+        let opcode = instruction >> 26;
+        let rs = (instruction >> 21 & 0x1F) as usize;
+        let rt = (instruction >> 16 & 0x1F) as usize;
+        let rd = (instruction >> 11 & 0x1F) as usize;
+        let immediate = instruction & 0xFFFF;
+        let offset = immediate as u16 as i16 as i32;
+
+        match opcode {
+            0x00 => match instruction & 0x3F {
+                // sll, including nop and ehb
+                0x00 => self.set_reg(rd, self.regs[rt] << (instruction >> 6 & 0x1F)),
+                // sync
+                0x0F => {}
+                0x10 => self.set_reg(rd, self.hi),
+                0x11 => self.hi = self.regs[rs],
+                0x12 => self.set_reg(rd, self.lo),
+                0x13 => self.lo = self.regs[rs],
+                other => panic!("Unsupported SPECIAL function {other:#x}"),
+            },
+            0x04 => {
+                if self.regs[rs] == self.regs[rt] {
+                    let target = (self.pc as i32 + 4 + (offset << 2)) as u32;
+                    self.advance_pc(Some(target));
+                    self.fetch();
+                    return Execution::Pending;
+                }
+            }
+            0x0D => self.set_reg(rt, self.regs[rs] | immediate),
+            0x0F => self.set_reg(rt, immediate << 16),
+            0x10 if instruction == assembly::DERET => return Execution::Deret,
+            0x10 => {
+                let register = (rd as u8, (instruction & 0x7) as u8);
+                match rs {
+                    0x00 => self.set_reg(rt, *self.cp0.get(&register).unwrap_or(&0)),
+                    0x04 => {
+                        let mut value = self.regs[rt];
+                        if register == (23, 0) {
+                            value =
+                                (value & !debug_bits::DM) | self.debug_register() & debug_bits::DM;
+                        }
+                        self.cp0.insert(register, value);
+                    }
+                    other => panic!("Unsupported COP0 operation {other:#x}"),
+                }
+            }
+            0x1C if instruction == assembly::SDBBP => return Execution::Breakpoint,
+            0x23..=0x25 => {
+                let address = (self.regs[rs] as i32 + offset) as u32;
+                let width = match opcode {
+                    0x24 => 1,
+                    0x25 => 2,
+                    _ => 4,
+                };
+
+                if in_dmseg(address) {
+                    self.pending = Some(Access {
+                        address,
+                        kind: AccessKind::Load { rt, width },
+                        data: 0,
+                    });
+                    return Execution::Pending;
+                }
+
+                let value = self.read_memory(address, width);
+                self.set_reg(rt, value);
+            }
+            0x28 | 0x29 | 0x2B => {
+                let address = (self.regs[rs] as i32 + offset) as u32;
+                let width = match opcode {
+                    0x28 => 1,
+                    0x29 => 2,
+                    _ => 4,
+                };
+
+                if in_dmseg(address) {
+                    self.pending = Some(Access {
+                        address,
+                        kind: AccessKind::Store,
+                        data: self.regs[rt],
+                    });
+                    return Execution::Pending;
+                }
+
+                self.write_memory(address, width, self.regs[rt]);
+            }
+            other => panic!("Unsupported opcode {other:#x} in instruction {instruction:#010x}"),
+        }
+
+        Execution::Done
+    }
+}
+
+fn in_dmseg(address: u32) -> bool {
+    (DMSEG_START..DMSEG_END).contains(&address)
+}
+
+impl JTAGAccess for MockEjtag {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        let value = match address {
+            instruction::IDCODE => MOCK_IDCODE,
+            instruction::IMPCODE => MOCK_IMPCODE,
+            instruction::ADDRESS => self.pending.map(|a| a.address).unwrap_or(0),
+            instruction::DATA => self.pending.map(|a| a.data).unwrap_or(0),
+            instruction::EJTAGBOOT | instruction::NORMALBOOT => 0,
+            other => panic!("Unexpected read of instruction {other:#x}"),
+        };
+
+        Ok(value.to_le_bytes()[..(len as usize + 7) / 8].to_vec())
+    }
+
+    fn set_idle_cycles(&mut self, _idle_cycles: u8) {}
+
+    fn get_idle_cycles(&self) -> u8 {
+        0
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        assert_eq!(len, 5);
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        assert_eq!(len, 32);
+        let value = u32::from_le_bytes(data.try_into().unwrap());
+
+        let captured = match address {
+            instruction::CONTROL => {
+                let captured = self.control();
+                self.write_control(value);
+                captured
+            }
+            instruction::DATA => {
+                let pending = self.pending.as_mut().expect("No pending processor access");
+                let captured = pending.data;
+                pending.data = value;
+                captured
+            }
+            other => panic!("Unexpected write of instruction {other:#x}"),
+        };
+
+        Ok(captured.to_le_bytes().to_vec())
+    }
+}
+
+/// This is just a blanket impl that will crash if used (only relevant in tests,
+/// so no problem as we do not use it) to fulfill the marker requirement.
+impl DebugProbe for MockEjtag {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
+
+    fn get_name(&self) -> &str {
+        "Mock EJTAG"
+    }
+
+    fn speed_khz(&self) -> u32 {
+        todo!()
+    }
+
+    fn set_speed(&mut self, _speed_khz: u32) -> Result<u32, DebugProbeError> {
+        todo!()
+    }
+
+    fn set_scan_chain(
+        &mut self,
+        _scan_chain: Vec<ScanChainElement>,
+    ) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn select_protocol(&mut self, _protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        Some(WireProtocol::Jtag)
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+}
@@ -0,0 +1,738 @@
+//! All the interface bits for MIPS.
+
+use self::registers::*;
+use crate::{
+    core::{
+        Architecture, BreakpointCause, CoreInformation, CoreRegisters, RegisterId, RegisterValue,
+    },
+    memory::valid_32bit_address,
+    CoreInterface, CoreRegister, CoreStatus, CoreType, Error, HaltReason, InstructionSet,
+    MemoryInterface, WatchpointCause,
+};
+use bitfield::bitfield;
+use communication_interface::{MipsCommunicationInterface, MipsError};
+use registers::MIPS32_CORE_REGISTERS;
+use std::time::{Duration, Instant};
+
+pub(crate) mod assembly;
+pub mod communication_interface;
+mod ejtag;
+pub(crate) mod exception_handling;
+pub(crate) mod registers;
+pub use registers::PC;
+pub mod sequences;
+
+#[cfg(test)]
+mod mock;
+
+/// Coprocessor 0 register numbers.
+mod cp0 {
+    pub const BADVADDR: u8 = 8;
+    pub const STATUS: u8 = 12;
+    pub const CAUSE: u8 = 13;
+    pub const DEBUG: u8 = 23;
+    pub const DEPC: u8 = 24;
+}
+
+/// Instruction breakpoint registers, located in the debug register segment (drseg).
+mod ibreak {
+    /// Instruction breakpoint status register.
+    pub const IBS: u64 = 0xFF30_1000;
+    /// Address of the instruction breakpoint address register of a unit.
+    pub const fn iba(unit: usize) -> u64 {
+        0xFF30_1100 + 0x100 * unit as u64
+    }
+    /// Address of the instruction breakpoint address mask register of a unit.
+    pub const fn ibm(unit: usize) -> u64 {
+        iba(unit) + 0x08
+    }
+    /// Address of the instruction breakpoint control register of a unit.
+    pub const fn ibc(unit: usize) -> u64 {
+        iba(unit) + 0x18
+    }
+    /// Breakpoint enable bit in the control register.
+    pub const IBC_BE: u32 = 1 << 0;
+}
+
+bitfield! {
+    /// The `Debug` register of coprocessor 0.
+    #[derive(Copy, Clone)]
+    pub struct DebugRegister(u32);
+    impl Debug;
+    /// Exception occurred in a branch delay slot.
+    pub dbd, _: 31;
+    /// The processor is in debug mode.
+    pub dm, _: 30;
+    /// Single step is enabled.
+    pub sst, set_sst: 8;
+    /// Debug interrupt exception, caused by a halt request.
+    pub dint, _: 5;
+    /// Debug instruction break exception.
+    pub dib, _: 4;
+    /// Debug data break exception on a store.
+    pub ddbs, _: 3;
+    /// Debug data break exception on a load.
+    pub ddbl, _: 2;
+    /// Debug breakpoint exception, caused by a `SDBBP` instruction.
+    pub dbp, _: 1;
+    /// Debug single step exception.
+    pub dss, _: 0;
+}
+
+/// A interface to operate MIPS32 cores.
+pub struct Mips32<'probe> {
+    interface: &'probe mut MipsCommunicationInterface,
+    state: &'probe mut MipsState,
+    id: usize,
+}
+
+impl<'probe> Mips32<'probe> {
+    /// Create a new MIPS32 interface.
+    pub fn new(
+        interface: &'probe mut MipsCommunicationInterface,
+        state: &'probe mut MipsState,
+        id: usize,
+    ) -> Self {
+        Self {
+            interface,
+            state,
+            id,
+        }
+    }
+
+    fn read_debug_register(&mut self) -> Result<DebugRegister, MipsError> {
+        self.interface.read_cp0(cp0::DEBUG, 0).map(DebugRegister)
+    }
+
+    fn set_single_step(&mut self, enabled: bool) -> Result<(), MipsError> {
+        let mut debug = self.read_debug_register()?;
+        debug.set_sst(enabled);
+        self.interface.write_cp0(cp0::DEBUG, 0, debug.0)
+    }
+
+    /// Returns the size of the `sdbbp` instruction at the current `depc`,
+    /// or `None` if there is no `sdbbp` instruction.
+    fn sdbbp_size_at_depc(&mut self) -> Result<Option<usize>, crate::Error> {
+        let depc = self.interface.read_cp0(cp0::DEPC, 0)?;
+        let address = (depc & !1) as u64;
+
+        if depc & 1 != 0 {
+            let mut instruction = [0u8; 2];
+            self.read_8(address, &mut instruction)?;
+
+            // The lowest 4 bits hold the breakpoint code.
+            if u16::from_le_bytes(instruction) & 0xFFF0 == assembly::SDBBP16 {
+                return Ok(Some(2));
+            }
+        } else if self.read_word_32(address)? & 0xFC00_003F == assembly::SDBBP {
+            // Bits 25:6 hold the breakpoint code.
+            return Ok(Some(4));
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'probe> CoreInterface for Mips32<'probe> {
+    fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), crate::Error> {
+        let start = Instant::now();
+
+        while start.elapsed() < timeout {
+            if self.interface.in_debug_mode()? {
+                return Ok(());
+            }
+        }
+
+        Err(MipsError::Timeout.into())
+    }
+
+    fn core_halted(&mut self) -> Result<bool, crate::Error> {
+        Ok(self.interface.in_debug_mode()?)
+    }
+
+    fn status(&mut self) -> Result<crate::core::CoreStatus, crate::Error> {
+        if !self.interface.in_debug_mode()? {
+            return Ok(CoreStatus::Running);
+        }
+
+        let debug = self.read_debug_register()?;
+
+        let reason = if debug.dbp() {
+            // A sdbbp instruction was hit
+            HaltReason::Breakpoint(BreakpointCause::Software)
+        } else if debug.dib() {
+            HaltReason::Breakpoint(BreakpointCause::Hardware)
+        } else if debug.ddbl() || debug.ddbs() {
+            HaltReason::Watchpoint(WatchpointCause::Unknown)
+        } else if debug.dss() {
+            HaltReason::Step
+        } else if debug.dint() {
+            HaltReason::Request
+        } else {
+            HaltReason::Unknown
+        };
+
+        Ok(CoreStatus::Halted(reason))
+    }
+
+    fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, crate::Error> {
+        if !self.interface.in_debug_mode()? {
+            self.interface.request_halt()?;
+            self.wait_for_core_halted(timeout)?;
+        }
+
+        let pc = self.read_core_reg(self.program_counter().into())?;
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
+
+    fn run(&mut self) -> Result<(), crate::Error> {
+        // Before we run, we always perform a single instruction step, to account for possible breakpoints that might get us stuck on the current instruction.
+        self.step()?;
+
+        self.interface.resume()?;
+
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), crate::Error> {
+        self.interface.set_boot_into_debug_mode(false)?;
+        self.interface.reset()?;
+
+        Ok(())
+    }
+
+    fn reset_and_halt(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<crate::core::CoreInformation, crate::Error> {
+        self.reset_catch_set()?;
+        self.interface.reset()?;
+        self.wait_for_core_halted(timeout)?;
+        self.reset_catch_clear()?;
+
+        let pc = self.read_core_reg(self.program_counter().into())?;
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
+
+    fn step(&mut self) -> Result<crate::core::CoreInformation, crate::Error> {
+        let halt_reason = self.status()?;
+        if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
+        ) && self.state.hw_breakpoints_enabled
+        {
+            // If we are halted on a software breakpoint, we skip the sdbbp instruction by advancing the depc.
+            // The sdbbp may have been removed in the meantime, e.g. by GDB, in which case we step normally.
+            if let Some(sdbbp_size) = self.sdbbp_size_at_depc()? {
+                let mut debug_pc = self.read_core_reg(PC.id)?;
+                debug_pc.increment_address(sdbbp_size)?;
+
+                self.write_core_reg(PC.id, debug_pc)?;
+                return Ok(CoreInformation {
+                    pc: debug_pc.try_into()?,
+                });
+            }
+        } else if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        ) {
+            // If we are halted on a hardware breakpoint.
+            self.enable_breakpoints(false)?;
+        }
+
+        self.set_single_step(true)?;
+
+        self.interface.resume()?;
+        self.wait_for_core_halted(Duration::from_millis(100))?;
+
+        self.set_single_step(false)?;
+
+        // Re-enable breakpoints before we continue.
+        if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        ) {
+            self.enable_breakpoints(true)?;
+        }
+
+        let pc = self.read_core_reg(PC.id)?;
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
+
+    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
+        let value = match address.0 {
+            register @ 0..=31 => self.interface.read_gpr(register as u8)?,
+            32 => self.interface.read_cp0(cp0::STATUS, 0)?,
+            33 => self.interface.read_lo()?,
+            34 => self.interface.read_hi()?,
+            35 => self.interface.read_cp0(cp0::BADVADDR, 0)?,
+            36 => self.interface.read_cp0(cp0::CAUSE, 0)?,
+            37 => self.interface.read_cp0(cp0::DEPC, 0)?,
+            _ => {
+                return Err(Error::GenericCoreError(format!(
+                    "Register {address:?} is not available on MIPS32 cores"
+                )))
+            }
+        };
+
+        Ok(value.into())
+    }
+
+    fn write_core_reg(
+        &mut self,
+        address: RegisterId,
+        value: RegisterValue,
+    ) -> Result<(), crate::Error> {
+        let value: u32 = value.try_into()?;
+
+        match address.0 {
+            register @ 0..=31 => self.interface.write_gpr(register as u8, value)?,
+            32 => self.interface.write_cp0(cp0::STATUS, 0, value)?,
+            33 => self.interface.write_lo(value)?,
+            34 => self.interface.write_hi(value)?,
+            35 => self.interface.write_cp0(cp0::BADVADDR, 0, value)?,
+            36 => self.interface.write_cp0(cp0::CAUSE, 0, value)?,
+            37 => self.interface.write_cp0(cp0::DEPC, 0, value)?,
+            _ => {
+                return Err(Error::GenericCoreError(format!(
+                    "Register {address:?} is not available on MIPS32 cores"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn available_breakpoint_units(&mut self) -> Result<u32, crate::Error> {
+        if let Some(count) = self.state.breakpoint_units {
+            return Ok(count);
+        }
+
+        // The number of instruction breakpoints is stored in the BCN field of IBS.
+        let ibs = self.interface.read_word_32(ibreak::IBS)?;
+        let count = (ibs >> 24) & 0xF;
+
+        tracing::debug!("Target supports {} breakpoints.", count);
+
+        self.state.breakpoint_units = Some(count);
+
+        Ok(count)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        let mut breakpoints = vec![];
+        let num_hw_breakpoints = self.available_breakpoint_units()? as usize;
+
+        for unit in 0..num_hw_breakpoints {
+            let control = self.interface.read_word_32(ibreak::ibc(unit))?;
+
+            if control & ibreak::IBC_BE != 0 {
+                let address = self.interface.read_word_32(ibreak::iba(unit))?;
+                breakpoints.push(Some(address as u64));
+            } else {
+                breakpoints.push(None);
+            }
+        }
+
+        Ok(breakpoints)
+    }
+
+    fn enable_breakpoints(&mut self, state: bool) -> Result<(), crate::Error> {
+        for unit in 0..self.available_breakpoint_units()? as usize {
+            // Only modify units which have an address assigned, i.e. which were set by probe-rs.
+            let address = self.interface.read_word_32(ibreak::iba(unit))?;
+
+            if address != 0 {
+                let control = if state { ibreak::IBC_BE } else { 0 };
+                self.interface.write_word_32(ibreak::ibc(unit), control)?;
+            }
+        }
+
+        self.state.hw_breakpoints_enabled = state;
+        Ok(())
+    }
+
+    fn set_hw_breakpoint(&mut self, unit_index: usize, addr: u64) -> Result<(), crate::Error> {
+        let addr = valid_32bit_address(addr)?;
+
+        if !self.hw_breakpoints_enabled() {
+            self.enable_breakpoints(true)?;
+        }
+
+        self.interface
+            .write_word_32(ibreak::iba(unit_index), addr)?;
+        // Compare all address bits.
+        self.interface.write_word_32(ibreak::ibm(unit_index), 0)?;
+        self.interface
+            .write_word_32(ibreak::ibc(unit_index), ibreak::IBC_BE)?;
+
+        Ok(())
+    }
+
+    fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), crate::Error> {
+        self.interface.write_word_32(ibreak::ibc(unit_index), 0)?;
+        self.interface.write_word_32(ibreak::iba(unit_index), 0)?;
+
+        Ok(())
+    }
+
+    fn registers(&self) -> &'static CoreRegisters {
+        &MIPS32_CORE_REGISTERS
+    }
+
+    fn program_counter(&self) -> &'static CoreRegister {
+        &PC
+    }
+
+    fn frame_pointer(&self) -> &'static CoreRegister {
+        &FP
+    }
+
+    fn stack_pointer(&self) -> &'static CoreRegister {
+        &SP
+    }
+
+    fn return_address(&self) -> &'static CoreRegister {
+        &RA
+    }
+
+    fn hw_breakpoints_enabled(&self) -> bool {
+        self.state.hw_breakpoints_enabled
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Mips
+    }
+
+    fn core_type(&self) -> CoreType {
+        CoreType::Mips32
+    }
+
+    fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
+        // The ISA mode is stored in the lowest bit of DEPC.
+        let depc = self.interface.read_cp0(cp0::DEPC, 0)?;
+
+        if depc & 1 != 0 {
+            Ok(InstructionSet::MicroMips)
+        } else {
+            Ok(InstructionSet::Mips32)
+        }
+    }
+
+    fn fpu_support(&mut self) -> Result<bool, crate::error::Error> {
+        Err(crate::error::Error::Other(anyhow::anyhow!(
+            "Fpu detection not yet implemented"
+        )))
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn reset_catch_set(&mut self) -> Result<(), Error> {
+        self.interface.set_boot_into_debug_mode(true)?;
+
+        Ok(())
+    }
+
+    fn reset_catch_clear(&mut self) -> Result<(), Error> {
+        self.interface.set_boot_into_debug_mode(false)?;
+
+        Ok(())
+    }
+
+    fn debug_core_stop(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'probe> MemoryInterface for Mips32<'probe> {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        self.interface.supports_native_64bit_access()
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, crate::error::Error> {
+        self.interface.read_word_64(address)
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        self.interface.read_word_32(address)
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.interface.read_word_16(address)
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        self.interface.read_word_8(address)
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
+        self.interface.read_64(address, data)
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        self.interface.read_32(address, data)
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.interface.read_16(address, data)
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.interface.read_8(address, data)
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
+        self.interface.write_word_64(address, data)
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        self.interface.write_word_32(address, data)
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.interface.write_word_16(address, data)
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        self.interface.write_word_8(address, data)
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), Error> {
+        self.interface.write_64(address, data)
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        self.interface.write_32(address, data)
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.interface.write_16(address, data)
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.interface.write_8(address, data)
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.interface.write(address, data)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+        self.interface.supports_8bit_transfers()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.interface.flush()
+    }
+}
+
+#[derive(Debug)]
+/// Flags used to control the [`SpecificCoreState`](crate::core::SpecificCoreState) for MIPS architecture
+pub struct MipsState {
+    /// A flag to remember whether we want to use hw_breakpoints during stepping of the core.
+    hw_breakpoints_enabled: bool,
+
+    /// The number of instruction breakpoint units, read from the `IBS` register.
+    breakpoint_units: Option<u32>,
+}
+
+impl MipsState {
+    pub(crate) fn new() -> Self {
+        Self {
+            hw_breakpoints_enabled: false,
+            breakpoint_units: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        assembly,
+        communication_interface::MipsCommunicationInterface,
+        mock::{MockEjtag, MOCK_IDCODE, MOCK_IMPCODE},
+        registers::{HI, LO},
+        Mips32, MipsState, PC,
+    };
+    use crate::{
+        core::{BreakpointCause, RegisterId, RegisterValue},
+        CoreInterface, CoreStatus, HaltReason, InstructionSet, MemoryInterface,
+    };
+    use std::time::Duration;
+
+    const RESET_PC: u32 = 0x9D00_0000;
+
+    fn attached_interface() -> MipsCommunicationInterface {
+        let mut mock = MockEjtag::new(RESET_PC);
+        // Fill the program memory with nops, except for a software breakpoint.
+        for i in 0..32 {
+            mock.write_memory(RESET_PC + 4 * i, 4, assembly::NOP);
+        }
+        mock.write_memory(RESET_PC + 0x40, 4, assembly::SDBBP);
+
+        let mut interface = MipsCommunicationInterface::new(Box::new(mock)).unwrap();
+        interface.enter_debug_mode().unwrap();
+        interface
+    }
+
+    #[test]
+    fn read_identification() {
+        let mut interface = attached_interface();
+
+        assert_eq!(interface.read_idcode().unwrap(), MOCK_IDCODE);
+        assert_eq!(interface.read_impcode().unwrap(), MOCK_IMPCODE);
+    }
+
+    #[test]
+    fn halt_and_status() {
+        let mut interface = attached_interface();
+        let mut state = MipsState::new();
+        let mut core = Mips32::new(&mut interface, &mut state, 0);
+
+        assert_eq!(core.status().unwrap(), CoreStatus::Running);
+
+        let info = core.halt(Duration::from_millis(100)).unwrap();
+
+        assert_eq!(info.pc, RESET_PC as u64);
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Request)
+        );
+        assert_eq!(core.instruction_set().unwrap(), InstructionSet::Mips32);
+    }
+
+    #[test]
+    fn read_and_write_registers() {
+        let mut interface = attached_interface();
+        let mut state = MipsState::new();
+        let mut core = Mips32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        for (register, value) in [
+            (RegisterId(4), 0x1234_5678u32),
+            (RegisterId(8), 0xdead_beef),
+            (RegisterId(15), 0x0bad_cafe),
+            (RegisterId(29), 0xa000_8000),
+            (LO.id, 0x1111_2222),
+            (HI.id, 0x3333_4444),
+            (PC.id, RESET_PC + 8),
+        ] {
+            core.write_core_reg(register, RegisterValue::U32(value))
+                .unwrap();
+            assert_eq!(
+                core.read_core_reg(register).unwrap(),
+                RegisterValue::U32(value),
+                "Register {register:?}"
+            );
+        }
+
+        // Writes to the zero register are ignored.
+        core.write_core_reg(RegisterId(0), RegisterValue::U32(1))
+            .unwrap();
+        assert_eq!(
+            core.read_core_reg(RegisterId(0)).unwrap(),
+            RegisterValue::U32(0)
+        );
+
+        // The temporary registers of the debug programs are not modified.
+        assert_eq!(
+            core.read_core_reg(RegisterId(8)).unwrap(),
+            RegisterValue::U32(0xdead_beef)
+        );
+        assert_eq!(
+            core.read_core_reg(RegisterId(15)).unwrap(),
+            RegisterValue::U32(0x0bad_cafe)
+        );
+    }
+
+    #[test]
+    fn read_and_write_memory() {
+        let mut interface = attached_interface();
+        let mut state = MipsState::new();
+        let mut core = Mips32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        let words: Vec<u32> = (0..300u32).map(|i| i.wrapping_mul(0x0101_0101)).collect();
+        core.write_32(0xA000_0000, &words).unwrap();
+
+        let mut readback = vec![0u32; words.len()];
+        core.read_32(0xA000_0000, &mut readback).unwrap();
+        assert_eq!(readback, words);
+
+        core.write_8(0xA000_0101, &[0xAA, 0xBB, 0xCC]).unwrap();
+        let mut bytes = [0u8; 5];
+        core.read_8(0xA000_0100, &mut bytes).unwrap();
+        assert_eq!(bytes, [0x40, 0xAA, 0xBB, 0xCC, 0x41]);
+
+        core.write_word_16(0xA000_0102, 0x1234).unwrap();
+        let mut halfwords = [0u16; 3];
+        core.read_16(0xA000_0100, &mut halfwords).unwrap();
+        assert_eq!(halfwords, [0xAA40, 0x1234, 0x4141]);
+
+        assert!(core.read_word_32(0xA000_0002).is_err());
+        assert!(core.read_word_16(0xA000_0001).is_err());
+    }
+
+    #[test]
+    fn step_and_run_to_breakpoint() {
+        let mut interface = attached_interface();
+        let mut state = MipsState::new();
+        let mut core = Mips32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        let info = core.step().unwrap();
+        assert_eq!(info.pc, RESET_PC as u64 + 4);
+        assert_eq!(core.status().unwrap(), CoreStatus::Halted(HaltReason::Step));
+
+        assert_eq!(core.available_breakpoint_units().unwrap(), 4);
+
+        core.set_hw_breakpoint(1, RESET_PC as u64 + 0x20).unwrap();
+        assert_eq!(
+            core.hw_breakpoints().unwrap(),
+            vec![None, Some(RESET_PC as u64 + 0x20), None, None]
+        );
+
+        core.run().unwrap();
+        core.wait_for_core_halted(Duration::from_millis(100))
+            .unwrap();
+
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        );
+        assert_eq!(
+            core.read_core_reg(PC.id).unwrap(),
+            RegisterValue::U32(RESET_PC + 0x20)
+        );
+
+        // Continuing steps over the hardware breakpoint, and halts on the software breakpoint.
+        core.run().unwrap();
+        core.wait_for_core_halted(Duration::from_millis(100))
+            .unwrap();
+
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
+        );
+        assert_eq!(
+            core.read_core_reg(PC.id).unwrap(),
+            RegisterValue::U32(RESET_PC + 0x40)
+        );
+
+        core.clear_hw_breakpoint(1).unwrap();
+        assert_eq!(core.hw_breakpoints().unwrap(), vec![None; 4]);
+
+        // Once the software breakpoint is removed, the original instruction is stepped.
+        core.write_word_32(RESET_PC as u64 + 0x40, assembly::NOP)
+            .unwrap();
+
+        let info = core.step().unwrap();
+        assert_eq!(info.pc, RESET_PC as u64 + 0x44);
+        assert_eq!(core.status().unwrap(), CoreStatus::Halted(HaltReason::Step));
+    }
+}
@@ -0,0 +1,29 @@
+//! Debug sequences to operate special requirements MIPS targets.
+
+use super::communication_interface::MipsCommunicationInterface;
+use std::sync::Arc;
+
+pub mod pic32;
+
+/// A interface to operate debug sequences for MIPS targets.
+///
+/// Should be implemented on a custom handle for chips that require special sequence code.
+pub trait MipsDebugSequence: Send + Sync {
+    /// Executed when the probe establishes a connection to the target,
+    /// before the EJTAG interface is used to halt the core.
+    fn on_connect(&self, _interface: &mut MipsCommunicationInterface) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
+
+/// The default sequences that is used for MIPS chips that do not specify a specific sequence.
+pub struct DefaultMipsSequence(pub(crate) ());
+
+impl DefaultMipsSequence {
+    /// Creates a new default MIPS debug sequence.
+    pub fn create() -> Arc<dyn MipsDebugSequence> {
+        Arc::new(Self(()))
+    }
+}
+
+impl MipsDebugSequence for DefaultMipsSequence {}
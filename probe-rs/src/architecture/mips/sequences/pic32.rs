@@ -0,0 +1,32 @@
+//! Sequences for the Microchip PIC32 family.
+
+use std::sync::Arc;
+
+use super::MipsDebugSequence;
+use crate::architecture::mips::communication_interface::MipsCommunicationInterface;
+
+/// Instruction of the Microchip TAP (MTAP), which switches the TAP to the EJTAG TAP (ETAP).
+const MTAP_SW_ETAP: u32 = 0x05;
+
+/// The debug sequence implementation for the PIC32 family.
+///
+/// The PIC32 devices start with the Microchip TAP selected,
+/// which has to be switched to the EJTAG TAP of the core.
+pub struct Pic32(());
+
+impl Pic32 {
+    /// Creates a new debug sequence handle for the PIC32 family.
+    pub fn create() -> Arc<dyn MipsDebugSequence> {
+        Arc::new(Self(()))
+    }
+}
+
+impl MipsDebugSequence for Pic32 {
+    fn on_connect(&self, interface: &mut MipsCommunicationInterface) -> Result<(), crate::Error> {
+        tracing::info!("Switching PIC32 MTAP to the EJTAG TAP");
+
+        interface.write_instruction(MTAP_SW_ETAP)?;
+
+        Ok(())
+    }
+}
@@ -6,12 +6,11 @@
 
 use super::{
     dtm::{DmiOperation, DmiOperationStatus, Dtm},
-    registers, Dmcontrol, Dmstatus,
+    registers, Dmcontrol, Dmcs2, Dmstatus,
 };
 use crate::{
     architecture::riscv::*,
     core::RegisterId,
-    memory::valid_32bit_address,
     memory_mapped_bitfield_register,
     probe::{CommandResult, DeferredResultIndex, JTAGAccess},
     DebugProbeError, Error as ProbeRsError, MemoryInterface, MemoryMappedRegister, Probe,
@@ -54,9 +53,12 @@ pub enum RiscvError {
     /// The program buffer is too small for the supplied program.
     #[error("Program buffer is too small for supplied program.")]
     ProgramBufferTooSmall,
-    /// Memory width larger than 32 bits is not supported yet.
-    #[error("Memory width larger than 32 bits is not supported yet.")]
+    /// The memory access width is not supported by the memory access method.
+    #[error("Memory accesses with a width of {} bits are not supported.", .0.byte_width() * 8)]
     UnsupportedBusAccessWidth(RiscvBusAccess),
+    /// The address can't be accessed with the available memory access method.
+    #[error("Address {0:#x} is out of range for the memory access method.")]
+    AddressOutOfRange(u64),
     /// An error during system bus access occurred.
     #[error("Error using system bus")]
     SystemBusAccess,
@@ -69,6 +71,15 @@ pub enum RiscvError {
     /// The target does not support halt after reset.
     #[error("The target does not support halt after reset.")]
     ResetHaltRequestNotSupported,
+    /// The hart does not exist in the debug module.
+    #[error("Hart {0} does not exist, the debug module has {1} harts.")]
+    HartNotFound(u32, u32),
+    /// The trigger is already used by probe-rs for another purpose.
+    #[error("Trigger {0} is already used for another purpose.")]
+    TriggerInUse(usize),
+    /// None of the triggers which support the required trigger type are unused.
+    #[error("No unused trigger of type {0} is available.")]
+    NoTriggerAvailable(u32),
 }
 
 impl From<RiscvError> for ProbeRsError {
@@ -157,6 +168,52 @@ impl From<u8> for DebugModuleVersion {
     }
 }
 
+/// Address of the `mvendorid` CSR.
+const MVENDORID: u16 = 0xf11;
+/// Address of the `marchid` CSR.
+const MARCHID: u16 = 0xf12;
+/// Address of the `mimpid` CSR.
+const MIMPID: u16 = 0xf13;
+
+/// Information about a RISC-V chip, which is used for
+/// automatic detection of the connected chip.
+///
+/// It is read with [`RiscvCommunicationInterface::read_chip_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiscvChipInfo {
+    /// The JTAG IDCODE of the debug transport module.
+    pub jtag_idcode: u32,
+    /// The version of the debug module, from the `dmstatus` register.
+    pub debug_version: DebugModuleVersion,
+    /// The number of harts connected to the debug module.
+    pub num_harts: u32,
+    /// The number of `dscratch` registers, from the `hartinfo` register.
+    pub nscratch: u8,
+    /// The JEDEC manufacturer ID of the core, from the `mvendorid` CSR.
+    pub mvendorid: Option<u32>,
+    /// The microarchitecture ID of the core, from the `marchid` CSR.
+    pub marchid: Option<u32>,
+    /// The implementation version of the core, from the `mimpid` CSR.
+    pub mimpid: Option<u32>,
+}
+
+impl std::fmt::Display for RiscvChipInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IDCODE {:#010x}", self.jtag_idcode)?;
+
+        if let (Some(mvendorid), Some(marchid), Some(mimpid)) =
+            (self.mvendorid, self.marchid, self.mimpid)
+        {
+            write!(
+                f,
+                ", mvendorid {mvendorid:#x}, marchid {marchid:#x}, mimpid {mimpid:#x}"
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 struct CoreRegisterAbstractCmdSupport(u8);
 
@@ -206,6 +263,15 @@ pub struct RiscvCommunicationInterfaceState {
     /// Number of harts
     num_harts: u32,
 
+    /// The hart which is currently selected in `dmcontrol`
+    selected_hart: u32,
+
+    /// Whether the harts have 64-bit wide registers (RV64).
+    is_64_bit: bool,
+
+    /// Width of the system bus addresses, in bits
+    sbasize: u32,
+
     memory_access_info: HashMap<RiscvBusAccess, MemoryAccessMethod>,
 
     /// describes, if the given register can be read / written with an
@@ -248,6 +314,13 @@ impl RiscvCommunicationInterfaceState {
             // We assume only a singe hart exisits initially
             num_harts: 1,
 
+            selected_hart: 0,
+
+            // Determined when entering debug mode
+            is_64_bit: false,
+
+            sbasize: 0,
+
             memory_access_info: HashMap::new(),
 
             abstract_cmd_register_info: HashMap::new(),
@@ -255,12 +328,9 @@ impl RiscvCommunicationInterfaceState {
     }
 
     /// Get the memory access method which should be used for an
-    /// access with the specified width.
-    fn memory_access_method(&mut self, access_width: RiscvBusAccess) -> MemoryAccessMethod {
-        *self
-            .memory_access_info
-            .entry(access_width)
-            .or_insert(MemoryAccessMethod::ProgramBuffer)
+    /// access with the specified width, if it is already known.
+    fn memory_access_method(&self, access_width: RiscvBusAccess) -> Option<MemoryAccessMethod> {
+        self.memory_access_info.get(&access_width).copied()
     }
 }
 
@@ -304,6 +374,222 @@ impl RiscvCommunicationInterface {
         self.dtm.read_idcode()
     }
 
+    /// Read the information used to identify the connected chip.
+    ///
+    /// To read the identification CSRs, hart 0 is halted for a short time.
+    /// If it was running before, it is resumed afterwards. If the CSRs cannot
+    /// be read, the chip can still be identified by its JTAG IDCODE.
+    pub fn read_chip_info(&mut self) -> Result<RiscvChipInfo, RiscvError> {
+        let jtag_idcode = self.read_idcode()?;
+
+        let hartinfo: Hartinfo = self.read_dm_register()?;
+
+        let (mvendorid, marchid, mimpid) = match self.read_identification_csrs() {
+            Ok((mvendorid, marchid, mimpid)) => (Some(mvendorid), Some(marchid), Some(mimpid)),
+            Err(e) => {
+                tracing::debug!("Unable to read the identification CSRs: {}", e);
+                (None, None, None)
+            }
+        };
+
+        Ok(RiscvChipInfo {
+            jtag_idcode,
+            debug_version: self.state.debug_version,
+            num_harts: self.state.num_harts,
+            nscratch: hartinfo.nscratch() as u8,
+            mvendorid,
+            marchid,
+            mimpid,
+        })
+    }
+
+    /// Read the `mvendorid`, `marchid` and `mimpid` CSRs of hart 0.
+    fn read_identification_csrs(&mut self) -> Result<(u32, u32, u32), RiscvError> {
+        self.with_halted_hart(|interface| {
+            let mvendorid = interface.read_identification_csr(MVENDORID)?;
+            let marchid = interface.read_identification_csr(MARCHID)?;
+            let mimpid = interface.read_identification_csr(MIMPID)?;
+
+            Ok((mvendorid, marchid, mimpid))
+        })
+    }
+
+    /// Run `access` with the selected hart halted.
+    ///
+    /// If the hart is running, it is halted for a short time and resumed afterwards.
+    fn with_halted_hart<T>(
+        &mut self,
+        access: impl FnOnce(&mut Self) -> Result<T, RiscvError>,
+    ) -> Result<T, RiscvError> {
+        let status: Dmstatus = self.read_dm_register()?;
+
+        let was_running = !status.allhalted();
+
+        if was_running {
+            self.halt_selected_hart(Duration::from_millis(100))?;
+        }
+
+        let result = access(self);
+
+        if was_running {
+            self.resume_selected_hart(Duration::from_millis(100))?;
+        }
+
+        result
+    }
+
+    fn read_identification_csr(&mut self, address: u16) -> Result<u32, RiscvError> {
+        // Most debug modules support reading CSRs with an abstract command,
+        // which is faster and doesn't need the program buffer.
+        match self.abstract_cmd_register_read(RegisterId(address)) {
+            Ok(value) => Ok(value),
+            Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::NotSupported)) => {
+                self.read_csr_progbuf(address)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The number of harts connected to the debug module.
+    pub fn num_harts(&self) -> u32 {
+        self.state.num_harts
+    }
+
+    /// Select the hart which is accessed by all further operations.
+    pub fn select_hart(&mut self, hart: u32) -> Result<(), RiscvError> {
+        if hart >= self.state.num_harts {
+            return Err(RiscvError::HartNotFound(hart, self.state.num_harts));
+        }
+
+        if self.state.selected_hart == hart {
+            return Ok(());
+        }
+
+        let mut control = Dmcontrol(0);
+        control.set_hartsel(hart);
+        control.set_dmactive(true);
+
+        self.write_dm_register(control)?;
+        self.state.selected_hart = hart;
+
+        Ok(())
+    }
+
+    /// A `dmcontrol` value which keeps the currently selected hart selected.
+    ///
+    /// All writes to `dmcontrol` also write `hartsel`, so this has to be used as the
+    /// starting point for requests to the selected hart.
+    pub(crate) fn selected_hart_dmcontrol(&self) -> Dmcontrol {
+        let mut control = Dmcontrol(0);
+        control.set_hartsel(self.state.selected_hart);
+        control
+    }
+
+    /// Add a hart to a halt group and to the resume group with the same number,
+    /// using the `dmcs2` register.
+    ///
+    /// When one hart of a halt group halts, the debug module halts all other harts
+    /// of the group as well, and resuming one hart of a resume group resumes all of them.
+    /// Groups are numbered from 1 to 31, group 0 removes the hart from its groups.
+    ///
+    /// Returns `false` if the debug module doesn't support halt groups. Resume groups are optional.
+    pub fn set_halt_group(&mut self, hart: u32, group: u8) -> Result<bool, RiscvError> {
+        let previous_hart = self.state.selected_hart;
+        self.select_hart(hart)?;
+
+        let result = self.write_hart_group(false, group).and_then(|halt_group| {
+            if halt_group && !self.write_hart_group(true, group)? {
+                tracing::debug!("Hart {} does not support resume groups", hart);
+            }
+
+            Ok(halt_group)
+        });
+
+        self.select_hart(previous_hart)?;
+
+        result
+    }
+
+    /// Write the halt or resume group of the selected hart, and check if it was accepted.
+    fn write_hart_group(&mut self, resume_group: bool, group: u8) -> Result<bool, RiscvError> {
+        let mut dmcs2 = Dmcs2(0);
+        dmcs2.set_hgselect(false);
+        dmcs2.set_grouptype(resume_group);
+        dmcs2.set_group(group as u32 & 0x1f);
+        dmcs2.set_hgwrite(true);
+        self.write_dm_register(dmcs2)?;
+
+        // If the group type is not supported, the group field is always 0.
+        let readback: Dmcs2 = self.read_dm_register()?;
+
+        Ok(readback.grouptype() == resume_group && readback.group() == group as u32)
+    }
+
+    fn halt_selected_hart(&mut self, timeout: Duration) -> Result<(), RiscvError> {
+        let mut dmcontrol = self.selected_hart_dmcontrol();
+        dmcontrol.set_haltreq(true);
+        dmcontrol.set_dmactive(true);
+        self.write_dm_register(dmcontrol)?;
+
+        let start = Instant::now();
+
+        let halted = loop {
+            let status: Dmstatus = self.read_dm_register()?;
+
+            if status.allhalted() {
+                break true;
+            }
+
+            if start.elapsed() > timeout {
+                break false;
+            }
+        };
+
+        // clear the halt request
+        let mut dmcontrol = self.selected_hart_dmcontrol();
+        dmcontrol.set_dmactive(true);
+        self.write_dm_register(dmcontrol)?;
+
+        if halted {
+            Ok(())
+        } else {
+            Err(RiscvError::Timeout)
+        }
+    }
+
+    /// Resume the selected hart, and wait until the debug module acknowledges the resume request.
+    pub(crate) fn resume_selected_hart(&mut self, timeout: Duration) -> Result<(), RiscvError> {
+        let mut dmcontrol = self.selected_hart_dmcontrol();
+        dmcontrol.set_resumereq(true);
+        dmcontrol.set_dmactive(true);
+        self.write_dm_register(dmcontrol)?;
+
+        let start = Instant::now();
+
+        let acknowledged = loop {
+            let status: Dmstatus = self.read_dm_register()?;
+
+            if status.allresumeack() {
+                break true;
+            }
+
+            if start.elapsed() > timeout {
+                break false;
+            }
+        };
+
+        // clear the resume request
+        let mut dmcontrol = self.selected_hart_dmcontrol();
+        dmcontrol.set_dmactive(true);
+        self.write_dm_register(dmcontrol)?;
+
+        if acknowledged {
+            Ok(())
+        } else {
+            Err(RiscvError::RequestNotAcknowledged)
+        }
+    }
+
     fn enter_debug_mode(&mut self) -> Result<(), RiscvError> {
         // We need a jtag interface
 
@@ -394,6 +680,7 @@ impl RiscvCommunicationInterface {
         control.set_dmactive(true);
 
         self.write_dm_register(control)?;
+        self.state.selected_hart = 0;
 
         // determine size of the program buffer, and number of data
         // registers for abstract commands
@@ -437,6 +724,9 @@ impl RiscvCommunicationInterface {
         // the system bus access conforms to the debug
         // specification 13.2.
         if sbcs.sbversion() == 1 {
+            self.state.sbasize = sbcs.sbasize();
+            tracing::debug!("System bus address width: {} bits", self.state.sbasize);
+
             // When possible, we use system bus access for memory access
 
             if sbcs.sbaccess8() {
@@ -475,6 +765,13 @@ impl RiscvCommunicationInterface {
             );
         }
 
+        // All harts of a debug module are assumed to have the same register width.
+        self.state.is_64_bit = self.with_halted_hart(Self::detect_64_bit)?;
+        tracing::debug!(
+            "Register width: {} bits",
+            if self.state.is_64_bit { 64 } else { 32 }
+        );
+
         Ok(())
     }
 
@@ -599,8 +896,45 @@ impl RiscvCommunicationInterface {
         Ok(())
     }
 
+    /// Write the address for a system bus access.
+    ///
+    /// Writing `sbaddress0` can trigger a read, so the upper bits are written first.
+    fn write_sysbus_address(&mut self, address: u64, schedule: bool) -> Result<(), RiscvError> {
+        if self.state.sbasize <= 32 && address > u32::MAX as u64 {
+            return Err(RiscvError::AddressOutOfRange(address));
+        }
+
+        if self.state.sbasize > 32 {
+            let upper_bits = Sbaddress1((address >> 32) as u32);
+
+            if schedule {
+                self.schedule_write_dm_register(upper_bits)?;
+            } else {
+                self.write_dm_register(upper_bits)?;
+            }
+        }
+
+        let lower_bits = Sbaddress0(address as u32);
+
+        if schedule {
+            self.schedule_write_dm_register(lower_bits)
+        } else {
+            self.write_dm_register(lower_bits)
+        }
+    }
+
+    /// Check that the address fits into the registers of the hart,
+    /// for memory accesses using the program buffer.
+    fn check_progbuf_address(&mut self, address: u64) -> Result<(), RiscvError> {
+        if address > u32::MAX as u64 && !self.is_64_bit() {
+            Err(RiscvError::AddressOutOfRange(address))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Perform a single read from a memory location, using system bus access.
-    fn perform_memory_read_sysbus<V: RiscvValue>(&mut self, address: u32) -> Result<V, RiscvError> {
+    fn perform_memory_read_sysbus<V: RiscvValue>(&mut self, address: u64) -> Result<V, RiscvError> {
         let mut sbcs = Sbcs(0);
 
         sbcs.set_sbaccess(V::WIDTH as u32);
@@ -608,7 +942,7 @@ impl RiscvCommunicationInterface {
 
         self.write_dm_register(sbcs)?;
 
-        self.write_dm_register(Sbaddress0(address))?;
+        self.write_sysbus_address(address, false)?;
         let data = self.read_large_dtm_register::<V, Sbdata>()?;
 
         // Check that the read was succesful
@@ -626,7 +960,7 @@ impl RiscvCommunicationInterface {
     /// Only reads up to a width of 32 bits are currently supported.
     fn perform_memory_read_multiple_sysbus<V: RiscvValue32>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &mut [V],
     ) -> Result<(), RiscvError> {
         let mut sbcs = Sbcs(0);
@@ -640,7 +974,7 @@ impl RiscvCommunicationInterface {
 
         self.schedule_write_dm_register(sbcs)?;
 
-        self.schedule_write_dm_register(Sbaddress0(address))?;
+        self.write_sysbus_address(address, true)?;
 
         let data_len = data.len();
 
@@ -684,21 +1018,23 @@ impl RiscvCommunicationInterface {
     }
 
     /// Perform memory read from a single location using the program buffer.
-    /// Only reads up to a width of 32 bits are currently supported.
-    fn perform_memory_read_progbuf<V: RiscvValue32>(
+    /// Reads with a width of 64 bits are only supported by RV64 harts.
+    fn perform_memory_read_progbuf<V: RiscvValue64>(
         &mut self,
-        address: u32,
+        address: u64,
     ) -> Result<V, RiscvError> {
         // assemble
         //  lb s1, 0(s0)
 
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
+        self.check_progbuf_address(address)?;
+
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
 
         let lw_command: u32 = assembly::lw(0, 8, V::WIDTH as u8, 8);
 
         self.setup_program_buffer(&[lw_command])?;
 
-        self.write_dm_register(Data0(address))?;
+        self.write_data_xlen(address)?;
 
         // Write s0, then execute program buffer
         let mut command = AccessRegisterCommand(0);
@@ -706,8 +1042,8 @@ impl RiscvCommunicationInterface {
         command.set_transfer(true);
         command.set_write(true);
 
-        // registers are 32 bit, so we have size 2 here
-        command.set_aarsize(RiscvBusAccess::A32);
+        // registers have the width of the hart
+        command.set_aarsize(self.register_access_width());
         command.set_postexec(true);
 
         // register s0, ie. 0x1008
@@ -724,22 +1060,26 @@ impl RiscvCommunicationInterface {
         }
 
         // Read back s0
-        let value = self.abstract_cmd_register_read(&registers::S0)?;
+        let value = self.abstract_cmd_register_read_xlen(&registers::S0)?;
 
         // Restore s0 register
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
 
         Ok(V::from_register_value(value))
     }
 
     fn perform_memory_read_multiple_progbuf<V: RiscvValue32>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &mut [V],
     ) -> Result<(), RiscvError> {
+        self.check_progbuf_address(address)?;
+
+        let register_width = self.register_access_width();
+
         // Backup registers s0 and s1
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
-        let s1 = self.abstract_cmd_register_read(&registers::S1)?;
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
+        let s1 = self.abstract_cmd_register_read_xlen(&registers::S1)?;
 
         // Load a word from address in register 8 (S0), with offset 0, into register 9 (S9)
         let lw_command: u32 = assembly::lw(0, 8, V::WIDTH as u8, 9);
@@ -749,7 +1089,7 @@ impl RiscvCommunicationInterface {
             assembly::addi(8, 8, V::WIDTH.byte_width() as u16),
         ])?;
 
-        self.write_dm_register(Data0(address))?;
+        self.write_data_xlen(address)?;
 
         // Write s0, then execute program buffer
         let mut command = AccessRegisterCommand(0);
@@ -757,8 +1097,8 @@ impl RiscvCommunicationInterface {
         command.set_transfer(true);
         command.set_write(true);
 
-        // registers are 32 bit, so we have size 2 here
-        command.set_aarsize(RiscvBusAccess::A32);
+        // registers have the width of the hart
+        command.set_aarsize(register_width);
         command.set_postexec(true);
 
         // register s0, ie. 0x1008
@@ -774,7 +1114,7 @@ impl RiscvCommunicationInterface {
             command.set_transfer(true);
             command.set_write(false);
 
-            // registers are 32 bit, so we have size 2 here
+            // the loaded value fits into the lower 32 bits of the register
             command.set_aarsize(RiscvBusAccess::A32);
             command.set_postexec(true);
 
@@ -788,7 +1128,7 @@ impl RiscvCommunicationInterface {
             *word = V::from_register_value(value.0);
         }
 
-        let last_value = self.abstract_cmd_register_read(&registers::S1)?;
+        let last_value = self.abstract_cmd_register_read::<u32>(&registers::S1)?;
 
         data[data.len() - 1] = V::from_register_value(last_value);
 
@@ -800,8 +1140,8 @@ impl RiscvCommunicationInterface {
             ));
         }
 
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
-        self.abstract_cmd_register_write(&registers::S1, s1)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S1, s1)?;
 
         Ok(())
     }
@@ -809,7 +1149,7 @@ impl RiscvCommunicationInterface {
     /// Memory write using system bus
     fn perform_memory_write_sysbus<V: RiscvValue>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &[V],
     ) -> Result<(), RiscvError> {
         let mut sbcs = Sbcs(0);
@@ -820,7 +1160,7 @@ impl RiscvCommunicationInterface {
 
         self.schedule_write_dm_register(sbcs)?;
 
-        self.schedule_write_dm_register(Sbaddress0(address))?;
+        self.write_sysbus_address(address, true)?;
 
         for value in data {
             self.schedule_write_large_dtm_register::<V, Sbdata>(*value)?;
@@ -847,10 +1187,10 @@ impl RiscvCommunicationInterface {
     }
 
     /// Perform memory write to a single location using the program buffer.
-    /// Only writes up to a width of 32 bits are currently supported.
-    fn perform_memory_write_progbuf<V: RiscvValue32>(
+    /// Writes with a width of 64 bits are only supported by RV64 harts.
+    fn perform_memory_write_progbuf<V: RiscvValue64>(
         &mut self,
-        address: u32,
+        address: u64,
         data: V,
     ) -> Result<(), RiscvError> {
         tracing::debug!(
@@ -859,19 +1199,21 @@ impl RiscvCommunicationInterface {
             data
         );
 
+        self.check_progbuf_address(address)?;
+
         // Backup registers s0 and s1
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
-        let s1 = self.abstract_cmd_register_read(&registers::S1)?;
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
+        let s1 = self.abstract_cmd_register_read_xlen(&registers::S1)?;
 
         let sw_command = assembly::sw(0, 8, V::WIDTH as u32, 9);
 
         self.setup_program_buffer(&[sw_command])?;
 
         // write address into s0
-        self.abstract_cmd_register_write(&registers::S0, address)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, address)?;
 
         // write data into data 0
-        self.write_dm_register(Data0(data.into()))?;
+        self.write_data_xlen(data.into())?;
 
         // Write s1, then execute program buffer
         let mut command = AccessRegisterCommand(0);
@@ -879,8 +1221,8 @@ impl RiscvCommunicationInterface {
         command.set_transfer(true);
         command.set_write(true);
 
-        // registers are 32 bit, so we have size 2 here
-        command.set_aarsize(RiscvBusAccess::A32);
+        // registers have the width of the hart
+        command.set_aarsize(self.register_access_width());
         command.set_postexec(true);
 
         // register s1, ie. 0x1009
@@ -904,8 +1246,8 @@ impl RiscvCommunicationInterface {
 
         // Restore register s0 and s1
 
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
-        self.abstract_cmd_register_write(&registers::S1, s1)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S1, s1)?;
 
         Ok(())
     }
@@ -914,11 +1256,15 @@ impl RiscvCommunicationInterface {
     /// Only writes up to a width of 32 bits are currently supported.
     fn perform_memory_write_multiple_progbuf<V: RiscvValue32>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &[V],
     ) -> Result<(), RiscvError> {
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
-        let s1 = self.abstract_cmd_register_read(&registers::S1)?;
+        self.check_progbuf_address(address)?;
+
+        let register_width = self.register_access_width();
+
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
+        let s1 = self.abstract_cmd_register_read_xlen(&registers::S1)?;
 
         // Setup program buffer for multiple writes
         // Store value from register s9 into memory,
@@ -931,11 +1277,11 @@ impl RiscvCommunicationInterface {
         ])?;
 
         // write address into s0
-        self.abstract_cmd_register_write(&registers::S0, address)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, address)?;
 
         for value in data {
             // write address into data 0
-            self.write_dm_register(Data0((*value).into()))?;
+            self.write_data_xlen(Into::<u32>::into(*value).into())?;
 
             // Write s0, then execute program buffer
             let mut command = AccessRegisterCommand(0);
@@ -943,8 +1289,8 @@ impl RiscvCommunicationInterface {
             command.set_transfer(true);
             command.set_write(true);
 
-            // registers are 32 bit, so we have size 2 here
-            command.set_aarsize(RiscvBusAccess::A32);
+            // registers have the width of the hart
+            command.set_aarsize(register_width);
             command.set_postexec(true);
 
             // register s1
@@ -970,8 +1316,91 @@ impl RiscvCommunicationInterface {
 
         // Restore register s0 and s1
 
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
-        self.abstract_cmd_register_write(&registers::S1, s1)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S1, s1)?;
+
+        Ok(())
+    }
+
+    /// Write the address for the Access Memory abstract command into `arg1`.
+    ///
+    /// The address has the register width of the hart, independent of the width of the access.
+    /// On RV32 harts, `arg1` overlaps with the upper half of 64-bit data in `arg0`, so
+    /// 64-bit accesses are not possible.
+    fn write_abstract_memory_address(
+        &mut self,
+        address: u64,
+        access_width: RiscvBusAccess,
+    ) -> Result<(), RiscvError> {
+        if self.is_64_bit() {
+            self.write_large_dtm_register::<u64, Arg1>(address)
+        } else if access_width > RiscvBusAccess::A32 {
+            Err(RiscvError::UnsupportedBusAccessWidth(access_width))
+        } else if address > u32::MAX as u64 {
+            Err(RiscvError::AddressOutOfRange(address))
+        } else {
+            self.write_dm_register(Data1(address as u32))
+        }
+    }
+
+    /// Perform a single read from a memory location, using the Access Memory abstract command.
+    fn perform_memory_read_abstract<V: RiscvValue>(
+        &mut self,
+        address: u64,
+    ) -> Result<V, RiscvError> {
+        self.write_abstract_memory_address(address, V::WIDTH)?;
+
+        let mut command = AccessMemoryCommand(0);
+        command.set_aamsize(V::WIDTH as u32);
+        command.set_write(false);
+
+        self.execute_abstract_command(command.into())?;
+
+        self.read_large_dtm_register::<V, Arg0>()
+    }
+
+    /// Perform multiple reads from consecutive memory locations,
+    /// using the Access Memory abstract command.
+    fn perform_memory_read_multiple_abstract<V: RiscvValue>(
+        &mut self,
+        address: u64,
+        data: &mut [V],
+    ) -> Result<(), RiscvError> {
+        for (index, value) in data.iter_mut().enumerate() {
+            let offset = (index * V::WIDTH.byte_width()) as u64;
+            *value = self.perform_memory_read_abstract(address + offset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Perform a single write to a memory location, using the Access Memory abstract command.
+    fn perform_memory_write_abstract<V: RiscvValue>(
+        &mut self,
+        address: u64,
+        data: V,
+    ) -> Result<(), RiscvError> {
+        self.write_abstract_memory_address(address, V::WIDTH)?;
+        self.write_large_dtm_register::<V, Arg0>(data)?;
+
+        let mut command = AccessMemoryCommand(0);
+        command.set_aamsize(V::WIDTH as u32);
+        command.set_write(true);
+
+        self.execute_abstract_command(command.into())
+    }
+
+    /// Perform multiple writes to consecutive memory locations,
+    /// using the Access Memory abstract command.
+    fn perform_memory_write_multiple_abstract<V: RiscvValue>(
+        &mut self,
+        address: u64,
+        data: &[V],
+    ) -> Result<(), RiscvError> {
+        for (index, value) in data.iter().enumerate() {
+            let offset = (index * V::WIDTH.byte_width()) as u64;
+            self.perform_memory_write_abstract(address + offset, *value)?;
+        }
 
         Ok(())
     }
@@ -982,7 +1411,7 @@ impl RiscvCommunicationInterface {
         // resumereq    = 0
         // ackhavereset = 0
 
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.selected_hart_dmcontrol();
         dmcontrol.set_haltreq(false);
         dmcontrol.set_resumereq(false);
         dmcontrol.set_ackhavereset(false);
@@ -1063,11 +1492,78 @@ impl RiscvCommunicationInterface {
         entry.unset(rw);
     }
 
+    /// Determine if the selected hart has 64-bit wide registers (RV64). The hart has to be halted.
+    fn detect_64_bit(&mut self) -> Result<bool, RiscvError> {
+        // Reads which are wider than the register have to fail,
+        // so try to read s0 with 64 bits.
+        let mut command = AccessRegisterCommand(0);
+        command.set_cmd_type(0);
+        command.set_transfer(true);
+        command.set_aarsize(RiscvBusAccess::A64);
+        command.set_regno((registers::S0).id.0 as u32);
+
+        match self.execute_abstract_command(command.0) {
+            Ok(_) => Ok(true),
+            Err(RiscvError::AbstractCommand(
+                AbstractCommandErrorKind::NotSupported | AbstractCommandErrorKind::Exception,
+            )) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check if the harts have 64-bit wide registers (RV64).
+    pub(crate) fn is_64_bit(&self) -> bool {
+        self.state.is_64_bit
+    }
+
+    /// Access width for the general purpose registers of the harts.
+    fn register_access_width(&self) -> RiscvBusAccess {
+        if self.is_64_bit() {
+            RiscvBusAccess::A64
+        } else {
+            RiscvBusAccess::A32
+        }
+    }
+
+    /// Read a core register with its full width, using an abstract command.
+    pub(crate) fn abstract_cmd_register_read_xlen(
+        &mut self,
+        regno: impl Into<RegisterId>,
+    ) -> Result<u64, RiscvError> {
+        if self.is_64_bit() {
+            self.abstract_cmd_register_read::<u64>(regno)
+        } else {
+            self.abstract_cmd_register_read::<u32>(regno).map(u64::from)
+        }
+    }
+
+    /// Write a core register with its full width, using an abstract command.
+    pub(crate) fn abstract_cmd_register_write_xlen(
+        &mut self,
+        regno: impl Into<RegisterId>,
+        value: u64,
+    ) -> Result<(), RiscvError> {
+        if self.is_64_bit() {
+            self.abstract_cmd_register_write(regno, value)
+        } else {
+            self.abstract_cmd_register_write(regno, value as u32)
+        }
+    }
+
+    /// Write a value with the full register width into the `data` registers.
+    fn write_data_xlen(&mut self, value: u64) -> Result<(), RiscvError> {
+        if self.is_64_bit() {
+            self.write_large_dtm_register::<u64, Arg0>(value)
+        } else {
+            self.write_dm_register(Data0(value as u32))
+        }
+    }
+
     // Read a core register using an abstract command
-    pub(crate) fn abstract_cmd_register_read(
+    pub(crate) fn abstract_cmd_register_read<V: RiscvValue>(
         &mut self,
         regno: impl Into<RegisterId>,
-    ) -> Result<u32, RiscvError> {
+    ) -> Result<V, RiscvError> {
         let regno = regno.into();
 
         // Check if the register was already tried via abstract cmd
@@ -1081,7 +1577,7 @@ impl RiscvCommunicationInterface {
         let mut command = AccessRegisterCommand(0);
         command.set_cmd_type(0);
         command.set_transfer(true);
-        command.set_aarsize(RiscvBusAccess::A32);
+        command.set_aarsize(V::WIDTH);
 
         command.set_regno(regno.0 as u32);
 
@@ -1098,9 +1594,7 @@ impl RiscvCommunicationInterface {
             Err(e) => return Err(e),
         }
 
-        let register_value: Data0 = self.read_dm_register()?;
-
-        Ok(register_value.into())
+        self.read_large_dtm_register::<V, Arg0>()
     }
 
     pub(crate) fn abstract_cmd_register_write<V: RiscvValue>(
@@ -1144,6 +1638,12 @@ impl RiscvCommunicationInterface {
 
     /// Read the CSR progbuf register.
     pub fn read_csr_progbuf(&mut self, address: u16) -> Result<u32, RiscvError> {
+        self.read_csr_progbuf_xlen(address)
+            .map(|value| value as u32)
+    }
+
+    /// Read a CSR with its full width, using the program buffer.
+    pub(crate) fn read_csr_progbuf_xlen(&mut self, address: u16) -> Result<u64, RiscvError> {
         tracing::debug!("Reading CSR {:#04x}", address);
 
         // Validate that the CSR address is valid
@@ -1151,7 +1651,7 @@ impl RiscvCommunicationInterface {
             return Err(RiscvError::UnsupportedCsrAddress(address));
         }
 
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
 
         // Read csr value into register 8 (s0)
         let csrr_cmd = assembly::csrr(8, address);
@@ -1165,16 +1665,25 @@ impl RiscvCommunicationInterface {
         self.execute_abstract_command(postexec_cmd.0)?;
 
         // read the s0 value
-        let reg_value = self.abstract_cmd_register_read(&registers::S0)?;
+        let reg_value = self.abstract_cmd_register_read_xlen(&registers::S0)?;
 
         // restore original value in s0
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
 
         Ok(reg_value)
     }
 
     /// Write the CSR progbuf register.
     pub fn write_csr_progbuf(&mut self, address: u16, value: u32) -> Result<(), RiscvError> {
+        self.write_csr_progbuf_xlen(address, value.into())
+    }
+
+    /// Write a CSR with its full width, using the program buffer.
+    pub(crate) fn write_csr_progbuf_xlen(
+        &mut self,
+        address: u16,
+        value: u64,
+    ) -> Result<(), RiscvError> {
         tracing::debug!("Writing CSR {:#04x}={}", address, value);
 
         // Validate that the CSR address is valid
@@ -1183,10 +1692,10 @@ impl RiscvCommunicationInterface {
         }
 
         // Backup register s0
-        let s0 = self.abstract_cmd_register_read(&registers::S0)?;
+        let s0 = self.abstract_cmd_register_read_xlen(&registers::S0)?;
 
         // Write value into s0
-        self.abstract_cmd_register_write(&registers::S0, value)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, value)?;
 
         // Built the CSRW command to write into the program buffer
         let csrw_cmd = assembly::csrw(address, 8);
@@ -1200,7 +1709,7 @@ impl RiscvCommunicationInterface {
 
         // command: transfer, regno = 0x1008
         // restore original value in s0
-        self.abstract_cmd_register_write(&registers::S0, s0)?;
+        self.abstract_cmd_register_write_xlen(&registers::S0, s0)?;
 
         Ok(())
     }
@@ -1221,72 +1730,120 @@ impl RiscvCommunicationInterface {
         V::write_to_register::<R>(self, value)
     }
 
-    fn read_word<V: RiscvValue32>(&mut self, address: u32) -> Result<V, crate::Error> {
-        let result = match self.state.memory_access_method(V::WIDTH) {
-            MemoryAccessMethod::ProgramBuffer => self.perform_memory_read_progbuf(address)?,
-            MemoryAccessMethod::SystemBus => self.perform_memory_read_sysbus(address)?,
-            MemoryAccessMethod::AbstractCommand => {
-                unimplemented!("Memory access using abstract commands is not implemted")
+    /// Perform a memory access with the memory access method for the access width.
+    ///
+    /// If system bus access is not available for the width, the method is determined
+    /// with the first access: The program buffer is used if possible, and the Access Memory
+    /// abstract command otherwise.
+    fn perform_memory_access<T>(
+        &mut self,
+        access_width: RiscvBusAccess,
+        mut access: impl FnMut(&mut Self, MemoryAccessMethod) -> Result<T, RiscvError>,
+    ) -> Result<T, RiscvError> {
+        if let Some(method) = self.state.memory_access_method(access_width) {
+            return access(self, method);
+        }
+
+        let (result, method) = match access(self, MemoryAccessMethod::ProgramBuffer) {
+            Err(
+                RiscvError::ProgramBufferTooSmall
+                | RiscvError::AbstractCommand(AbstractCommandErrorKind::NotSupported),
+            ) => {
+                tracing::debug!(
+                    "The program buffer can't be used for {:?} memory access, using abstract commands",
+                    access_width
+                );
+
+                (
+                    access(self, MemoryAccessMethod::AbstractCommand)?,
+                    MemoryAccessMethod::AbstractCommand,
+                )
             }
+            result => (result?, MemoryAccessMethod::ProgramBuffer),
         };
 
+        self.state.memory_access_info.insert(access_width, method);
+
+        Ok(result)
+    }
+
+    fn read_word<V: RiscvValue64>(&mut self, address: u64) -> Result<V, crate::Error> {
+        let result = self.perform_memory_access(V::WIDTH, |interface, method| match method {
+            MemoryAccessMethod::ProgramBuffer => interface.perform_memory_read_progbuf(address),
+            MemoryAccessMethod::SystemBus => interface.perform_memory_read_sysbus(address),
+            MemoryAccessMethod::AbstractCommand => interface.perform_memory_read_abstract(address),
+        })?;
+
         Ok(result)
     }
 
     fn read_multiple<V: RiscvValue32>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &mut [V],
     ) -> Result<(), crate::Error> {
         tracing::debug!("read_32 from {:#08x}", address);
 
-        match self.state.memory_access_method(RiscvBusAccess::A32) {
+        self.perform_memory_access(V::WIDTH, |interface, method| match method {
             MemoryAccessMethod::ProgramBuffer => {
-                self.perform_memory_read_multiple_progbuf(address, data)?;
+                interface.perform_memory_read_multiple_progbuf(address, data)
             }
             MemoryAccessMethod::SystemBus => {
-                self.perform_memory_read_multiple_sysbus(address, data)?;
+                interface.perform_memory_read_multiple_sysbus(address, data)
             }
             MemoryAccessMethod::AbstractCommand => {
-                unimplemented!("Memory access using abstract commands is not implemted")
+                interface.perform_memory_read_multiple_abstract(address, data)
             }
-        };
+        })?;
 
         Ok(())
     }
 
-    fn write_word<V: RiscvValue32>(&mut self, address: u32, data: V) -> Result<(), crate::Error> {
-        match self.state.memory_access_method(V::WIDTH) {
+    fn write_word<V: RiscvValue64>(&mut self, address: u64, data: V) -> Result<(), crate::Error> {
+        self.perform_memory_access(V::WIDTH, |interface, method| match method {
             MemoryAccessMethod::ProgramBuffer => {
-                self.perform_memory_write_progbuf(address, data)?
+                interface.perform_memory_write_progbuf(address, data)
+            }
+            MemoryAccessMethod::SystemBus => {
+                interface.perform_memory_write_sysbus(address, &[data])
             }
-            MemoryAccessMethod::SystemBus => self.perform_memory_write_sysbus(address, &[data])?,
             MemoryAccessMethod::AbstractCommand => {
-                unimplemented!("Memory access using abstract commands is not implemted")
+                interface.perform_memory_write_abstract(address, data)
             }
-        };
+        })?;
 
         Ok(())
     }
 
     fn write_multiple<V: RiscvValue32>(
         &mut self,
-        address: u32,
+        address: u64,
         data: &[V],
     ) -> Result<(), crate::Error> {
-        match self.state.memory_access_method(V::WIDTH) {
-            MemoryAccessMethod::SystemBus => self.perform_memory_write_sysbus(address, data)?,
+        self.perform_memory_access(V::WIDTH, |interface, method| match method {
+            MemoryAccessMethod::SystemBus => interface.perform_memory_write_sysbus(address, data),
             MemoryAccessMethod::ProgramBuffer => {
-                self.perform_memory_write_multiple_progbuf(address, data)?
+                interface.perform_memory_write_multiple_progbuf(address, data)
             }
             MemoryAccessMethod::AbstractCommand => {
-                unimplemented!("Memory access using abstract commands is not implemted")
+                interface.perform_memory_write_multiple_abstract(address, data)
             }
-        }
+        })?;
 
         Ok(())
     }
 
+    /// Check if 64-bit wide memory accesses are possible.
+    ///
+    /// The program buffer and the Access Memory abstract command can only be used
+    /// for them on RV64 harts.
+    fn supports_64bit_access(&mut self) -> Result<bool, RiscvError> {
+        match self.state.memory_access_method(RiscvBusAccess::A64) {
+            Some(MemoryAccessMethod::SystemBus | MemoryAccessMethod::AbstractCommand) => Ok(true),
+            Some(MemoryAccessMethod::ProgramBuffer) | None => Ok(self.is_64_bit()),
+        }
+    }
+
     /// Destruct the interface and return the stored probe driver.
     pub fn close(self) -> Probe {
         Probe::from_attached_probe(self.dtm.probe.into_probe())
@@ -1397,6 +1954,16 @@ impl LargeRegister for Arg0 {
     const R3_ADDRESS: u8 = Data3::ADDRESS_OFFSET as u8;
 }
 
+/// The `arg1` argument of abstract commands, for arguments of 64 bits.
+struct Arg1 {}
+
+impl LargeRegister for Arg1 {
+    const R0_ADDRESS: u8 = Data2::ADDRESS_OFFSET as u8;
+    const R1_ADDRESS: u8 = Data3::ADDRESS_OFFSET as u8;
+    const R2_ADDRESS: u8 = Data4::ADDRESS_OFFSET as u8;
+    const R3_ADDRESS: u8 = Data5::ADDRESS_OFFSET as u8;
+}
+
 /// Helper trait, limited to RiscvValue no larger than 32 bits
 pub(crate) trait RiscvValue32: RiscvValue + Into<u32> {
     fn from_register_value(value: u32) -> Self;
@@ -1418,6 +1985,33 @@ impl RiscvValue32 for u32 {
     }
 }
 
+/// Helper trait, limited to RiscvValue which fit into
+/// the general purpose registers of a RV64 hart.
+pub(crate) trait RiscvValue64: RiscvValue + Into<u64> {
+    fn from_register_value(value: u64) -> Self;
+}
+
+impl RiscvValue64 for u8 {
+    fn from_register_value(value: u64) -> Self {
+        value as u8
+    }
+}
+impl RiscvValue64 for u16 {
+    fn from_register_value(value: u64) -> Self {
+        value as u16
+    }
+}
+impl RiscvValue64 for u32 {
+    fn from_register_value(value: u64) -> Self {
+        value as u32
+    }
+}
+impl RiscvValue64 for u64 {
+    fn from_register_value(value: u64) -> Self {
+        value
+    }
+}
+
 /// Marker trait for different values which
 /// can be read / written using the debug module.
 pub(crate) trait RiscvValue: std::fmt::Debug + Copy + Sized {
@@ -1718,11 +2312,14 @@ impl RiscvValue for u128 {
 
 impl MemoryInterface for RiscvCommunicationInterface {
     fn supports_native_64bit_access(&mut self) -> bool {
-        false
+        self.supports_64bit_access().unwrap_or(false)
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, crate::error::Error> {
-        let address = valid_32bit_address(address)?;
+        if self.supports_64bit_access()? {
+            return self.read_word(address);
+        }
+
         let mut ret = self.read_word::<u32>(address)? as u64;
         ret |= (self.read_word::<u32>(address + 4)? as u64) << 32;
 
@@ -1730,47 +2327,53 @@ impl MemoryInterface for RiscvCommunicationInterface {
     }
 
     fn read_word_32(&mut self, address: u64) -> Result<u32, crate::Error> {
-        let address = valid_32bit_address(address)?;
+        self.read_word(address)
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, crate::Error> {
         self.read_word(address)
     }
 
     fn read_word_8(&mut self, address: u64) -> Result<u8, crate::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("read_word_8 from {:#08x}", address);
         self.read_word(address)
     }
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), crate::error::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("read_64 from {:#08x}", address);
 
         for (i, d) in data.iter_mut().enumerate() {
-            *d = self.read_word_64((address + (i as u32 * 8)).into())?;
+            *d = self.read_word_64(address + (i as u64 * 8))?;
         }
 
         Ok(())
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("read_32 from {:#08x}", address);
         self.read_multiple(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), crate::Error> {
+        tracing::debug!("read_16 from {:#08x}", address);
+        self.read_multiple(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("read_8 from {:#08x}", address);
 
         self.read_multiple(address, data)
     }
 
     fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         self.read_multiple(address, data)
     }
 
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), crate::error::Error> {
-        let address = valid_32bit_address(address)?;
+        if self.supports_64bit_access()? {
+            return self.write_word(address, data);
+        }
+
         let low_word = data as u32;
         let high_word = (data >> 32) as u32;
 
@@ -1779,42 +2382,46 @@ impl MemoryInterface for RiscvCommunicationInterface {
     }
 
     fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
+        self.write_word(address, data)
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), crate::Error> {
         self.write_word(address, data)
     }
 
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         self.write_word(address, data)
     }
 
     fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), crate::error::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("write_64 to {:#08x}", address);
 
         for (i, d) in data.iter().enumerate() {
-            self.write_word_64((address + (i as u32 * 8)).into(), *d)?;
+            self.write_word_64(address + (i as u64 * 8), *d)?;
         }
 
         Ok(())
     }
 
     fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("write_32 to {:#08x}", address);
 
         self.write_multiple(address, data)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), crate::Error> {
+        tracing::debug!("write_16 to {:#08x}", address);
+
+        self.write_multiple(address, data)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         tracing::debug!("write_8 to {:#08x}", address);
 
         self.write_multiple(address, data)
     }
 
     fn write(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
-        let address = valid_32bit_address(address)?;
         self.write_multiple(address, data)
     }
 
@@ -1865,10 +2472,7 @@ impl From<RiscvBusAccess> for u8 {
 
 /// Different methods of memory access,
 /// which can be supported by a debug module.
-///
-/// The `AbstractCommand` method for memory access is not implemented.
 #[derive(Debug, Copy, Clone)]
-#[allow(dead_code)]
 enum MemoryAccessMethod {
     /// Memory access using the program buffer is supported
     ProgramBuffer,
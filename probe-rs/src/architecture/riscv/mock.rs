@@ -0,0 +1,1349 @@
+//! A simulated RISC-V debug module with its harts, accessed through a JTAG DTM,
+//! used to test the RISC-V debug implementation.
+//!
+//! The harts contain a small interpreter for the instructions used in the program
+//! buffer and in the test programs. Which ways to access registers and memory the
+//! debug module supports can be configured, to test the fallbacks between them.
+
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{
+    assembly,
+    communication_interface::{RiscvCommunicationInterface, RiscvError},
+};
+use crate::{probe::JTAGAccess, DebugProbe, DebugProbeError, DebugProbeSelector, WireProtocol};
+use probe_rs_target::ScanChainElement;
+
+pub(super) const MOCK_IDCODE: u32 = 0x2000_0913;
+
+/// The address of the first instruction after a reset.
+pub(super) const RESET_VECTOR: u64 = 0x8000_0000;
+
+/// The RAM of the simulated chip.
+pub(super) const RAM: Range<u64> = 0x8000_0000..0x8001_0000;
+
+/// The largest NAPOT range of the address match triggers, as reported in `maskmax`.
+pub(super) const MASKMAX: u64 = 6;
+
+/// Number of instructions which are executed when a hart is resumed,
+/// until it is considered to run in an endless loop.
+const RUN_LIMIT: usize = 64;
+
+/// Number of address bits of the `dmi` register.
+const ABITS: u32 = 7;
+
+mod jtag {
+    pub const IDCODE: u32 = 0x01;
+    pub const DTMCS: u32 = 0x10;
+    pub const DMI: u32 = 0x11;
+}
+
+mod csr {
+    pub const MISA: u16 = 0x301;
+    pub const MTVEC: u16 = 0x305;
+    pub const MEPC: u16 = 0x341;
+    pub const MCAUSE: u16 = 0x342;
+    pub const TSELECT: u16 = 0x7a0;
+    pub const TDATA1: u16 = 0x7a1;
+    pub const TDATA2: u16 = 0x7a2;
+    pub const TINFO: u16 = 0x7a4;
+    pub const DCSR: u16 = 0x7b0;
+    pub const DPC: u16 = 0x7b1;
+    pub const DSCRATCH0: u16 = 0x7b2;
+    pub const MVENDORID: u16 = 0xf11;
+    pub const MARCHID: u16 = 0xf12;
+    pub const MIMPID: u16 = 0xf13;
+    pub const MHARTID: u16 = 0xf14;
+}
+
+/// Causes of entering debug mode, as reported in `dcsr.cause`.
+pub(super) mod cause {
+    pub const EBREAK: u64 = 1;
+    pub const TRIGGER: u64 = 2;
+    pub const REQUEST: u64 = 3;
+    pub const STEP: u64 = 4;
+    pub const RESET: u64 = 5;
+    pub const GROUP: u64 = 6;
+}
+
+/// Exception codes, as reported in `mcause`.
+pub(super) mod exception {
+    pub const FETCH_ACCESS_FAULT: u64 = 1;
+    pub const ILLEGAL_INSTRUCTION: u64 = 2;
+    pub const BREAKPOINT: u64 = 3;
+    pub const LOAD_ACCESS_FAULT: u64 = 5;
+    pub const STORE_ACCESS_FAULT: u64 = 7;
+}
+
+mod cmderr {
+    pub const NOT_SUPPORTED: u32 = 2;
+    pub const EXCEPTION: u32 = 3;
+    pub const HALT_RESUME: u32 = 4;
+    pub const BUS: u32 = 5;
+}
+
+pub(super) const TRIGGER_TYPE_MCONTROL: u64 = 2;
+pub(super) const TRIGGER_TYPE_ICOUNT: u64 = 3;
+pub(super) const TRIGGER_TYPE_ETRIGGER: u64 = 5;
+
+/// The memory of the simulated chip, which is shared by all harts.
+#[derive(Debug, Default)]
+pub(super) struct MockMemory {
+    regions: Vec<Range<u64>>,
+    bytes: HashMap<u64, u8>,
+}
+
+impl MockMemory {
+    fn mapped(&self, address: u64, width: usize) -> bool {
+        (0..width as u64).all(|offset| {
+            matches!(address.checked_add(offset), Some(byte) if self.regions.iter().any(|r| r.contains(&byte)))
+        })
+    }
+
+    /// Read `width` bytes, or `None` if they are not mapped.
+    pub fn read(&self, address: u64, width: usize) -> Option<u64> {
+        if !self.mapped(address, width) {
+            return None;
+        }
+
+        Some((0..width as u64).fold(0, |value, i| {
+            value | (*self.bytes.get(&(address + i)).unwrap_or(&0) as u64) << (8 * i)
+        }))
+    }
+
+    /// Write `width` bytes, returning `false` if they are not mapped.
+    pub fn write(&mut self, address: u64, width: usize, value: u64) -> bool {
+        if !self.mapped(address, width) {
+            return false;
+        }
+
+        for i in 0..width as u64 {
+            self.bytes.insert(address + i, (value >> (8 * i)) as u8);
+        }
+
+        true
+    }
+
+    /// Write a program into memory.
+    pub fn write_program(&mut self, address: u64, instructions: &[u32]) {
+        for (i, instruction) in instructions.iter().enumerate() {
+            assert!(self.write(address + 4 * i as u64, 4, *instruction as u64));
+        }
+    }
+}
+
+/// A trigger of the trigger module of a hart.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MockTrigger {
+    /// The supported trigger types, as reported in `tinfo`.
+    pub types: u64,
+    /// The raw value of `tdata1`, in the layout for the register width of the hart.
+    pub tdata1: u64,
+    pub tdata2: u64,
+}
+
+/// A memory access or instruction fetch, checked against the triggers.
+#[derive(Debug, Clone, Copy)]
+enum TriggerAccess {
+    Execute(u64),
+    Load { address: u64, value: u64 },
+    Store { address: u64, value: u64 },
+}
+
+/// Why a hart stopped executing instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    Ebreak,
+    Trigger,
+    Exception(u64),
+}
+
+#[derive(Debug)]
+pub(super) struct MockHart {
+    pub xlen64: bool,
+    pub regs: [u64; 32],
+    pub pc: u64,
+    pub csrs: HashMap<u16, u64>,
+    pub triggers: Vec<MockTrigger>,
+    pub tselect: usize,
+    pub halted: bool,
+    pub resumeack: bool,
+    pub havereset: bool,
+    pub halt_group: u32,
+    pub resume_group: u32,
+    /// Whether this hart can be added to halt and resume groups.
+    pub supports_groups: bool,
+    resethaltreq: bool,
+    in_reset: bool,
+}
+
+impl MockHart {
+    fn new(hartid: u64, xlen64: bool) -> Self {
+        let mxl = if xlen64 { 2u64 << 62 } else { 1 << 30 };
+        // The I, M and C extensions.
+        let misa = mxl | 1 << 8 | 1 << 12 | 1 << 2;
+
+        let csrs = HashMap::from([
+            (csr::MISA, misa),
+            (csr::MTVEC, 0),
+            (csr::MEPC, 0),
+            (csr::MCAUSE, 0),
+            // xdebugver = 4, prv = M
+            (csr::DCSR, 4 << 28 | 3),
+            (csr::DPC, 0),
+            (csr::DSCRATCH0, 0),
+            (csr::MVENDORID, 0x489),
+            (csr::MARCHID, 0x8000_0007),
+            (csr::MIMPID, 0x2019_0531),
+            (csr::MHARTID, hartid),
+        ]);
+
+        // mcontrol, icount, itrigger and etrigger
+        let all_types = 1 << 2 | 1 << 3 | 1 << 4 | 1 << 5;
+
+        let mut hart = Self {
+            xlen64,
+            regs: [0; 32],
+            pc: RESET_VECTOR,
+            csrs,
+            triggers: vec![],
+            tselect: 0,
+            halted: false,
+            resumeack: false,
+            havereset: false,
+            halt_group: 0,
+            resume_group: 0,
+            supports_groups: true,
+            resethaltreq: false,
+            in_reset: false,
+        };
+
+        hart.triggers = (0..4)
+            .map(|_| {
+                let mut trigger = MockTrigger {
+                    types: all_types,
+                    tdata1: 0,
+                    tdata2: 0,
+                };
+                trigger.write_tdata1(0, hart.xlen());
+                trigger
+            })
+            .collect();
+
+        hart
+    }
+
+    pub fn xlen(&self) -> u32 {
+        if self.xlen64 {
+            64
+        } else {
+            32
+        }
+    }
+
+    fn xlen_mask(&self) -> u64 {
+        if self.xlen64 {
+            u64::MAX
+        } else {
+            u32::MAX as u64
+        }
+    }
+
+    pub fn dcsr(&self) -> u64 {
+        self.csrs[&csr::DCSR]
+    }
+
+    pub fn dpc(&self) -> u64 {
+        self.csrs[&csr::DPC]
+    }
+
+    fn set_reg(&mut self, index: usize, value: u64) {
+        if index != 0 {
+            self.regs[index] = value & self.xlen_mask();
+        }
+    }
+
+    /// Sign extend a value from `bits` bits to the register width.
+    fn sign_extend(&self, value: u64, bits: u32) -> u64 {
+        let shift = 64 - bits;
+        (((value << shift) as i64) >> shift) as u64 & self.xlen_mask()
+    }
+
+    pub fn read_csr(&self, address: u16) -> Option<u64> {
+        match address {
+            csr::TSELECT => Some(self.tselect as u64),
+            csr::TDATA1 => self.triggers.get(self.tselect).map(|t| t.tdata1),
+            csr::TDATA2 => self.triggers.get(self.tselect).map(|t| t.tdata2),
+            csr::TINFO => Some(self.triggers.get(self.tselect).map_or(1, |t| t.types)),
+            _ => self.csrs.get(&address).copied(),
+        }
+    }
+
+    /// Write a CSR, returning `false` if it doesn't exist or is read-only.
+    pub fn write_csr(&mut self, address: u16, value: u64) -> bool {
+        let value = value & self.xlen_mask();
+        let xlen = self.xlen();
+
+        match address {
+            csr::TSELECT => {
+                // Unsupported values are not written.
+                if (value as usize) < self.triggers.len() {
+                    self.tselect = value as usize;
+                }
+            }
+            csr::TDATA1 => match self.triggers.get_mut(self.tselect) {
+                Some(trigger) => trigger.write_tdata1(value, xlen),
+                None => return false,
+            },
+            csr::TDATA2 => match self.triggers.get_mut(self.tselect) {
+                Some(trigger) => trigger.tdata2 = value,
+                None => return false,
+            },
+            csr::DCSR => {
+                // xdebugver and cause are read-only.
+                let read_only = 0xf << 28 | 0x7 << 6;
+                let dcsr = self.dcsr() & read_only | value & !read_only;
+                self.csrs.insert(csr::DCSR, dcsr);
+            }
+            csr::MISA | csr::MVENDORID | csr::MARCHID | csr::MIMPID | csr::MHARTID | csr::TINFO => {
+                return false
+            }
+            _ => match self.csrs.get_mut(&address) {
+                Some(register) => *register = value,
+                None => return false,
+            },
+        }
+
+        true
+    }
+
+    /// Enter debug mode, with the program counter saved in `dpc`.
+    fn enter_debug_mode(&mut self, cause: u64) {
+        self.halted = true;
+        self.csrs.insert(csr::DPC, self.pc);
+
+        let dcsr = self.dcsr() & !(0x7 << 6) | cause << 6;
+        self.csrs.insert(csr::DCSR, dcsr);
+    }
+
+    fn leave_debug_mode(&mut self) {
+        self.pc = self.dpc();
+        self.halted = false;
+        self.resumeack = true;
+    }
+
+    fn reset(&mut self) {
+        self.regs = [0; 32];
+        self.pc = RESET_VECTOR;
+        self.havereset = true;
+        self.halted = false;
+
+        if self.resethaltreq {
+            self.enter_debug_mode(cause::RESET);
+        }
+    }
+
+    /// Check the triggers for an access, and set the `hit` bits of the triggers which fire.
+    fn check_triggers(&mut self, access: TriggerAccess) -> bool {
+        let xlen = self.xlen();
+        let mut index = 0;
+
+        while index < self.triggers.len() {
+            // The triggers which are chained together have to match all.
+            let mut end = index;
+            while end + 1 < self.triggers.len() && self.triggers[end].mcontrol_field(11, 1) == 1 {
+                end += 1;
+            }
+
+            let chain = &mut self.triggers[index..=end];
+
+            if chain.iter().all(|trigger| trigger.matches(access, xlen)) {
+                for trigger in chain {
+                    trigger.tdata1 |= 1 << 20;
+                }
+                return true;
+            }
+
+            index = end + 1;
+        }
+
+        false
+    }
+
+    /// Count down the instruction count triggers after an instruction, and check if one fires.
+    fn count_instruction(&mut self) -> bool {
+        let xlen = self.xlen();
+        let mut fired = false;
+
+        for trigger in &mut self.triggers {
+            let count = trigger.tdata1 >> 10 & 0x3fff;
+
+            if trigger.trigger_type(xlen) != TRIGGER_TYPE_ICOUNT
+                || trigger.tdata1 & 1 << 9 == 0
+                || trigger.tdata1 & 0x3f != 1
+                || count == 0
+            {
+                continue;
+            }
+
+            trigger.tdata1 = trigger.tdata1 & !(0x3fff << 10) | (count - 1) << 10;
+
+            if count == 1 {
+                trigger.tdata1 |= 1 << 24;
+                fired = true;
+            }
+        }
+
+        fired
+    }
+
+    /// Take a trap, which enters debug mode if an exception trigger catches it.
+    fn trap(&mut self, cause: u64) {
+        self.csrs.insert(csr::MEPC, self.pc);
+        self.csrs.insert(csr::MCAUSE, cause);
+        self.pc = self.csrs[&csr::MTVEC] & !0b11;
+
+        let xlen = self.xlen();
+        let mut caught = false;
+
+        for trigger in &mut self.triggers {
+            if trigger.trigger_type(xlen) == TRIGGER_TYPE_ETRIGGER
+                && trigger.tdata1 & 1 << 9 != 0
+                && trigger.tdata1 & 0x3f == 1
+                && trigger.tdata2 & 1 << cause != 0
+            {
+                trigger.tdata1 |= 1 << (xlen - 6);
+                caught = true;
+            }
+        }
+
+        if caught {
+            self.enter_debug_mode(cause::TRIGGER);
+        }
+    }
+
+    /// Execute a single instruction.
+    ///
+    /// In debug mode, instructions are executed from the program buffer,
+    /// so the program counter is not changed and triggers don't fire.
+    fn execute(
+        &mut self,
+        instruction: u32,
+        memory: &mut MockMemory,
+        debug_mode: bool,
+    ) -> Result<(), Stop> {
+        let illegal = Stop::Exception(exception::ILLEGAL_INSTRUCTION);
+
+        let opcode = instruction & 0x7f;
+        let rd = (instruction >> 7 & 0x1f) as usize;
+        let funct3 = instruction >> 12 & 0x7;
+        let rs1 = (instruction >> 15 & 0x1f) as usize;
+        let rs2 = (instruction >> 20 & 0x1f) as usize;
+        let imm_i = ((instruction as i32) >> 20) as i64 as u64;
+        let imm_s = ((instruction as i32) >> 25 << 5 | (instruction >> 7 & 0x1f) as i32) as i64;
+
+        let mut next_pc = self.pc.wrapping_add(4) & self.xlen_mask();
+
+        match opcode {
+            // Loads
+            0x03 => {
+                let (width, signed) = match funct3 {
+                    0 => (1, true),
+                    1 => (2, true),
+                    2 => (4, true),
+                    3 if self.xlen64 => (8, false),
+                    4 => (1, false),
+                    5 => (2, false),
+                    6 if self.xlen64 => (4, false),
+                    _ => return Err(illegal),
+                };
+                let address = self.regs[rs1].wrapping_add(imm_i) & self.xlen_mask();
+                let value = memory
+                    .read(address, width)
+                    .ok_or(Stop::Exception(exception::LOAD_ACCESS_FAULT))?;
+
+                if !debug_mode && self.check_triggers(TriggerAccess::Load { address, value }) {
+                    return Err(Stop::Trigger);
+                }
+
+                let value = if signed {
+                    self.sign_extend(value, 8 * width as u32)
+                } else {
+                    value
+                };
+                self.set_reg(rd, value);
+            }
+            // Stores
+            0x23 => {
+                let width = match funct3 {
+                    0..=2 => 1 << funct3,
+                    3 if self.xlen64 => 8,
+                    _ => return Err(illegal),
+                };
+                let address = self.regs[rs1].wrapping_add(imm_s as u64) & self.xlen_mask();
+                let value = self.regs[rs2] & (u64::MAX >> (64 - 8 * width));
+
+                if !debug_mode && self.check_triggers(TriggerAccess::Store { address, value }) {
+                    return Err(Stop::Trigger);
+                }
+
+                if !memory.write(address, width, value) {
+                    return Err(Stop::Exception(exception::STORE_ACCESS_FAULT));
+                }
+            }
+            0x13 => {
+                let shift_mask = if self.xlen64 { 0x3f } else { 0x1f };
+                let shift = (instruction >> 20) as u64 & shift_mask;
+                let value = self.regs[rs1];
+
+                let result = match funct3 {
+                    // addi
+                    0 => value.wrapping_add(imm_i),
+                    // slli
+                    1 => value << shift,
+                    // srai
+                    5 if instruction & 1 << 30 != 0 => {
+                        (self.sign_extend(value, self.xlen()) as i64 >> shift) as u64
+                    }
+                    // srli
+                    5 => value >> shift,
+                    _ => return Err(illegal),
+                };
+                self.set_reg(rd, result);
+            }
+            // lui
+            0x37 => self.set_reg(rd, (instruction & 0xffff_f000) as i32 as i64 as u64),
+            // jal
+            0x6f if !debug_mode => {
+                let offset = (instruction & 0x8000_0000) as i32 >> 11
+                    | (instruction & 0x000f_f000) as i32
+                    | ((instruction >> 9) & 0x800) as i32
+                    | ((instruction >> 20) & 0x7fe) as i32;
+                self.set_reg(rd, next_pc);
+                next_pc = self.pc.wrapping_add(offset as i64 as u64) & self.xlen_mask();
+            }
+            0x73 if instruction == assembly::EBREAK => return Err(Stop::Ebreak),
+            // csrrw and csrrs
+            0x73 if funct3 == 1 || funct3 == 2 => {
+                let address = (instruction >> 20) as u16;
+                let old = self.read_csr(address).ok_or(illegal)?;
+
+                let new = if funct3 == 1 {
+                    Some(self.regs[rs1])
+                } else if rs1 != 0 {
+                    Some(old | self.regs[rs1])
+                } else {
+                    None
+                };
+
+                if let Some(new) = new {
+                    if !self.write_csr(address, new) {
+                        return Err(illegal);
+                    }
+                }
+                self.set_reg(rd, old);
+            }
+            _ => return Err(illegal),
+        }
+
+        if !debug_mode {
+            self.pc = next_pc;
+        }
+
+        Ok(())
+    }
+
+    /// Run the hart, until it enters debug mode or the run limit is reached.
+    fn run(&mut self, memory: &mut MockMemory) {
+        let single_step = self.dcsr() & 1 << 2 != 0;
+
+        for executed in 0..RUN_LIMIT {
+            if self.halted {
+                return;
+            }
+
+            if single_step && executed == 1 {
+                self.enter_debug_mode(cause::STEP);
+                return;
+            }
+
+            if self.check_triggers(TriggerAccess::Execute(self.pc)) {
+                self.enter_debug_mode(cause::TRIGGER);
+                return;
+            }
+
+            let result = match memory.read(self.pc, 4) {
+                Some(instruction) => self.execute(instruction as u32, memory, false),
+                None => Err(Stop::Exception(exception::FETCH_ACCESS_FAULT)),
+            };
+
+            match result {
+                Ok(()) => {}
+                // ebreakm
+                Err(Stop::Ebreak) if self.dcsr() & 1 << 15 != 0 => {
+                    self.enter_debug_mode(cause::EBREAK);
+                    return;
+                }
+                Err(Stop::Ebreak) => self.trap(exception::BREAKPOINT),
+                Err(Stop::Trigger) => {
+                    self.enter_debug_mode(cause::TRIGGER);
+                    return;
+                }
+                Err(Stop::Exception(cause)) => self.trap(cause),
+            }
+
+            if self.count_instruction() {
+                self.enter_debug_mode(cause::TRIGGER);
+                return;
+            }
+        }
+    }
+}
+
+impl MockTrigger {
+    fn trigger_type(&self, xlen: u32) -> u64 {
+        self.tdata1 >> (xlen - 4)
+    }
+
+    fn mcontrol_field(&self, offset: u32, width: u32) -> u64 {
+        self.tdata1 >> offset & ((1 << width) - 1)
+    }
+
+    /// Write `tdata1`. Unsupported trigger types are replaced by the first supported one,
+    /// and `maskmax` is read-only.
+    fn write_tdata1(&mut self, value: u64, xlen: u32) {
+        let type_offset = xlen - 4;
+        let xlen_mask = u64::MAX >> (64 - xlen);
+
+        let requested_type = value >> type_offset & 0xf;
+        let trigger_type = if self.types & 1 << requested_type != 0 {
+            requested_type
+        } else {
+            self.types.trailing_zeros() as u64
+        };
+
+        let mut value = value & !(0xf << type_offset) | trigger_type << type_offset;
+
+        if trigger_type == TRIGGER_TYPE_MCONTROL {
+            let maskmax_offset = xlen - 11;
+            value = value & !(0x3f << maskmax_offset) | MASKMAX << maskmax_offset;
+        }
+
+        self.tdata1 = value & xlen_mask;
+    }
+
+    /// Check if an address or data value match trigger matches an access.
+    fn matches(&self, access: TriggerAccess, xlen: u32) -> bool {
+        // Enabled in M-mode, and entering debug mode.
+        if self.trigger_type(xlen) != TRIGGER_TYPE_MCONTROL
+            || self.mcontrol_field(6, 1) == 0
+            || self.mcontrol_field(12, 4) != 1
+        {
+            return false;
+        }
+
+        let select_data = self.mcontrol_field(19, 1) == 1;
+
+        let (enabled, address, value) = match access {
+            TriggerAccess::Execute(address) => (self.mcontrol_field(2, 1), address, 0),
+            TriggerAccess::Load { address, value } => (self.mcontrol_field(0, 1), address, value),
+            TriggerAccess::Store { address, value } => (self.mcontrol_field(1, 1), address, value),
+        };
+
+        if enabled == 0 {
+            return false;
+        }
+
+        let compared = if select_data { value } else { address };
+
+        match self.mcontrol_field(7, 4) {
+            0 => compared == self.tdata2,
+            1 => {
+                let mask = !((2u64 << self.tdata2.trailing_ones()) - 1);
+                compared & mask == self.tdata2 & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The debug module, and the harts and memory of the simulated chip.
+#[derive(Debug)]
+pub(super) struct MockDebugModule {
+    pub harts: Vec<MockHart>,
+    pub memory: MockMemory,
+    pub progbuf_size: usize,
+    /// Whether CSRs can be accessed using the Access Register abstract command.
+    pub abstract_csr_access: bool,
+    /// Whether the Access Memory abstract command is supported.
+    pub abstract_memory_access: bool,
+    /// Whether system bus access is supported.
+    pub system_bus: bool,
+    /// Whether halt and resume groups are supported.
+    pub halt_groups: bool,
+    /// Number of `dmstatus` reads after a resume request before it is acknowledged.
+    pub resumeack_delay: usize,
+    /// All values written to `dmcontrol`.
+    pub dmcontrol_writes: Vec<u32>,
+    /// Number of abstract commands which were executed.
+    pub abstract_commands: usize,
+
+    hartsellen: u32,
+    hartsel: u32,
+    dmactive: bool,
+    hartreset: bool,
+    data: [u32; 12],
+    progbuf: [u32; 16],
+    cmderr: u32,
+    dmcs2_grouptype: bool,
+    resumeack_pending: usize,
+    sbcs: u32,
+    sberror: u32,
+    sbaddress: u64,
+    sbdata: [u32; 2],
+    dmi_result: u32,
+}
+
+impl MockDebugModule {
+    pub fn new(num_harts: usize, xlen64: bool) -> Self {
+        let mut memory = MockMemory::default();
+        memory.regions.push(RAM);
+
+        Self {
+            harts: (0..num_harts)
+                .map(|hartid| MockHart::new(hartid as u64, xlen64))
+                .collect(),
+            memory,
+            progbuf_size: 4,
+            abstract_csr_access: true,
+            abstract_memory_access: false,
+            system_bus: false,
+            halt_groups: true,
+            resumeack_delay: 0,
+            dmcontrol_writes: vec![],
+            abstract_commands: 0,
+            hartsellen: 4,
+            hartsel: 0,
+            dmactive: false,
+            hartreset: false,
+            data: [0; 12],
+            progbuf: [0; 16],
+            cmderr: 0,
+            dmcs2_grouptype: false,
+            resumeack_pending: 0,
+            sbcs: 0,
+            sberror: 0,
+            sbaddress: 0,
+            sbdata: [0; 2],
+            dmi_result: 0,
+        }
+    }
+
+    /// Create the JTAG probe which is connected to the debug module.
+    pub fn into_probe(self) -> (MockDtm, Arc<Mutex<Self>>) {
+        let dm = Arc::new(Mutex::new(self));
+
+        (MockDtm { dm: dm.clone() }, dm)
+    }
+
+    fn selected_hart(&mut self) -> Option<usize> {
+        let index = self.hartsel as usize;
+
+        (index < self.harts.len()).then_some(index)
+    }
+
+    fn read(&mut self, address: u32) -> u32 {
+        match address {
+            0x04..=0x0f => self.data[address as usize - 0x04],
+            0x10 => self.dmcontrol(),
+            0x11 => self.dmstatus(),
+            // nscratch = 1
+            0x12 => 1 << 20,
+            // abstractcs, with 12 data registers
+            0x16 => (self.progbuf_size as u32) << 24 | self.cmderr << 8 | 12,
+            0x20..=0x2f => self.progbuf[address as usize - 0x20],
+            0x32 => self.dmcs2(),
+            0x38 => self.read_sbcs(),
+            0x39 => self.sbaddress as u32,
+            0x3a => (self.sbaddress >> 32) as u32,
+            0x3c => {
+                let value = self.sbdata[0];
+                if self.sbcs & 1 << 15 != 0 {
+                    self.system_bus_read();
+                }
+                value
+            }
+            0x3d => self.sbdata[1],
+            // haltsum0
+            0x40 => self
+                .harts
+                .iter()
+                .enumerate()
+                .filter(|(_, hart)| hart.halted)
+                .fold(0, |sum, (index, _)| sum | 1 << index),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, address: u32, value: u32) {
+        match address {
+            0x04..=0x0f => self.data[address as usize - 0x04] = value,
+            0x10 => self.write_dmcontrol(value),
+            0x16 => self.cmderr &= !(value >> 8 & 0x7),
+            0x17 => self.execute_command(value),
+            0x20..=0x2f => self.progbuf[address as usize - 0x20] = value,
+            0x32 => self.write_dmcs2(value),
+            0x38 => self.write_sbcs(value),
+            0x39 => {
+                self.sbaddress = self.sbaddress & !0xffff_ffff | value as u64;
+                if self.sbcs & 1 << 20 != 0 {
+                    self.system_bus_read();
+                }
+            }
+            0x3a => self.sbaddress = self.sbaddress & 0xffff_ffff | (value as u64) << 32,
+            0x3c => {
+                self.sbdata[0] = value;
+                self.system_bus_write();
+            }
+            0x3d => self.sbdata[1] = value,
+            _ => {}
+        }
+    }
+
+    fn dmcontrol(&self) -> u32 {
+        (self.hartsel & 0x3ff) << 16
+            | (self.hartsel >> 10 & 0x3ff) << 6
+            | (self.hartreset as u32) << 29
+            | self.dmactive as u32
+    }
+
+    fn write_dmcontrol(&mut self, value: u32) {
+        self.dmcontrol_writes.push(value);
+
+        let haltreq = value & 1 << 31 != 0;
+        let resumereq = value & 1 << 30 != 0;
+        let hartreset = value & 1 << 29 != 0;
+        let ackhavereset = value & 1 << 28 != 0;
+        let resethaltreq = value & 1 << 3 != 0;
+        let clrresethaltreq = value & 1 << 2 != 0;
+
+        let hartsel = (value >> 16 & 0x3ff) | (value >> 6 & 0x3ff) << 10;
+        self.hartsel = hartsel & ((1 << self.hartsellen) - 1);
+        self.dmactive = value & 1 != 0;
+        self.hartreset = hartreset;
+
+        let Some(index) = self.selected_hart() else {
+            return;
+        };
+
+        let hart = &mut self.harts[index];
+
+        if ackhavereset {
+            hart.havereset = false;
+        }
+        if resethaltreq {
+            hart.resethaltreq = true;
+        } else if clrresethaltreq {
+            hart.resethaltreq = false;
+        }
+
+        if hartreset {
+            hart.in_reset = true;
+            return;
+        } else if hart.in_reset {
+            hart.in_reset = false;
+            hart.reset();
+        }
+
+        if haltreq {
+            if !hart.halted {
+                hart.enter_debug_mode(cause::REQUEST);
+                self.halt_group_members(index);
+            }
+        } else if resumereq && hart.halted {
+            hart.resumeack = false;
+            self.resumeack_pending = self.resumeack_delay;
+            self.resume(index);
+        }
+    }
+
+    fn dmstatus(&mut self) -> u32 {
+        // version 0.13, authenticated, hasresethaltreq
+        let mut status = 2 | 1 << 7 | 1 << 5;
+
+        match self.selected_hart() {
+            None => status |= 1 << 15 | 1 << 14,
+            Some(index) => {
+                let hart = &self.harts[index];
+                if hart.halted {
+                    status |= 1 << 9 | 1 << 8;
+                } else {
+                    status |= 1 << 11 | 1 << 10;
+                }
+                if self.resumeack_pending > 0 {
+                    self.resumeack_pending -= 1;
+                } else if hart.resumeack {
+                    status |= 1 << 17 | 1 << 16;
+                }
+                if hart.havereset {
+                    status |= 1 << 19 | 1 << 18;
+                }
+            }
+        }
+
+        status
+    }
+
+    fn dmcs2(&mut self) -> u32 {
+        let grouptype = self.dmcs2_grouptype;
+
+        match self.selected_hart() {
+            Some(index) if self.halt_groups && self.harts[index].supports_groups => {
+                let hart = &self.harts[index];
+                let group = if grouptype {
+                    hart.resume_group
+                } else {
+                    hart.halt_group
+                };
+                (grouptype as u32) << 11 | group << 2
+            }
+            _ => 0,
+        }
+    }
+
+    fn write_dmcs2(&mut self, value: u32) {
+        let grouptype = value & 1 << 11 != 0;
+        let group = value >> 2 & 0x1f;
+        let hgwrite = value & 1 << 1 != 0;
+        let hgselect = value & 1 != 0;
+
+        if !self.halt_groups {
+            return;
+        }
+
+        self.dmcs2_grouptype = grouptype;
+
+        if let (Some(index), true, false) = (self.selected_hart(), hgwrite, hgselect) {
+            let hart = &mut self.harts[index];
+            if !hart.supports_groups {
+                return;
+            }
+
+            if grouptype {
+                hart.resume_group = group;
+            } else {
+                hart.halt_group = group;
+            }
+        }
+    }
+
+    /// Halt the other harts of the halt group of a hart which halted.
+    fn halt_group_members(&mut self, index: usize) {
+        let group = self.harts[index].halt_group;
+
+        if group == 0 {
+            return;
+        }
+
+        for hart in &mut self.harts {
+            if hart.halt_group == group && !hart.halted {
+                hart.enter_debug_mode(cause::GROUP);
+            }
+        }
+    }
+
+    /// Resume a hart and the other harts of its resume group.
+    fn resume(&mut self, index: usize) {
+        let group = self.harts[index].resume_group;
+
+        let resumed: Vec<usize> = (0..self.harts.len())
+            .filter(|&other| {
+                other == index || (group != 0 && self.harts[other].resume_group == group)
+            })
+            .filter(|&other| self.harts[other].halted)
+            .collect();
+
+        for &hart in &resumed {
+            self.harts[hart].leave_debug_mode();
+        }
+
+        for hart in resumed {
+            if !self.harts[hart].halted {
+                self.harts[hart].run(&mut self.memory);
+
+                if self.harts[hart].halted {
+                    self.halt_group_members(hart);
+                }
+            }
+        }
+    }
+
+    fn arg(&self, index: usize, width: u32) -> u64 {
+        if width == 64 {
+            self.data[2 * index] as u64 | (self.data[2 * index + 1] as u64) << 32
+        } else {
+            self.data[index] as u64
+        }
+    }
+
+    fn set_arg(&mut self, index: usize, width: u32, value: u64) {
+        if width == 64 {
+            self.data[2 * index] = value as u32;
+            self.data[2 * index + 1] = (value >> 32) as u32;
+        } else {
+            self.data[index] = value as u32;
+        }
+    }
+
+    fn execute_command(&mut self, command: u32) {
+        // Commands are ignored until the error is cleared.
+        if self.cmderr != 0 {
+            return;
+        }
+
+        self.abstract_commands += 1;
+
+        let result = match command >> 24 {
+            0 => self.access_register(command),
+            2 => self.access_memory(command),
+            _ => Err(cmderr::NOT_SUPPORTED),
+        };
+
+        if let Err(error) = result {
+            self.cmderr = error;
+        }
+    }
+
+    fn halted_hart(&mut self) -> Result<usize, u32> {
+        match self.selected_hart() {
+            Some(index) if self.harts[index].halted => Ok(index),
+            _ => Err(cmderr::HALT_RESUME),
+        }
+    }
+
+    fn access_register(&mut self, command: u32) -> Result<(), u32> {
+        let aarsize = command >> 20 & 0x7;
+        let postexec = command & 1 << 18 != 0;
+        let transfer = command & 1 << 17 != 0;
+        let write = command & 1 << 16 != 0;
+        let regno = command & 0xffff;
+
+        let index = self.halted_hart()?;
+
+        if transfer {
+            let width = match aarsize {
+                2 => 32,
+                3 if self.harts[index].xlen64 => 64,
+                _ => return Err(cmderr::NOT_SUPPORTED),
+            };
+
+            match regno {
+                0..=0xfff if !self.abstract_csr_access => return Err(cmderr::NOT_SUPPORTED),
+                0..=0xfff => {
+                    let csr = regno as u16;
+                    if write {
+                        let value = self.arg(0, width);
+                        if !self.harts[index].write_csr(csr, value) {
+                            return Err(cmderr::EXCEPTION);
+                        }
+                    } else {
+                        let value = self.harts[index].read_csr(csr).ok_or(cmderr::EXCEPTION)?;
+                        self.set_arg(0, width, value);
+                    }
+                }
+                0x1000..=0x101f => {
+                    let register = (regno - 0x1000) as usize;
+                    if write {
+                        let value = self.arg(0, width);
+                        self.harts[index].set_reg(register, value);
+                    } else {
+                        let value = self.harts[index].regs[register];
+                        self.set_arg(0, width, value);
+                    }
+                }
+                _ => return Err(cmderr::NOT_SUPPORTED),
+            }
+        }
+
+        if postexec {
+            self.execute_progbuf(index)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute_progbuf(&mut self, index: usize) -> Result<(), u32> {
+        for word in 0..self.progbuf_size {
+            let instruction = self.progbuf[word];
+
+            match self.harts[index].execute(instruction, &mut self.memory, true) {
+                Ok(()) => {}
+                Err(Stop::Ebreak) => return Ok(()),
+                Err(_) => return Err(cmderr::EXCEPTION),
+            }
+        }
+
+        // There is no implicit ebreak.
+        Err(cmderr::EXCEPTION)
+    }
+
+    fn access_memory(&mut self, command: u32) -> Result<(), u32> {
+        if !self.abstract_memory_access {
+            return Err(cmderr::NOT_SUPPORTED);
+        }
+
+        let aamsize = command >> 20 & 0x7;
+        let postincrement = command & 1 << 19 != 0;
+        let write = command & 1 << 16 != 0;
+
+        let index = self.halted_hart()?;
+        let xlen = self.harts[index].xlen();
+
+        let width = match aamsize {
+            0..=2 => 1 << aamsize,
+            3 if xlen == 64 => 8,
+            _ => return Err(cmderr::NOT_SUPPORTED),
+        };
+        let data_width = if width == 8 { 64 } else { 32 };
+
+        // The address has the register width of the hart.
+        let address = self.arg(1, xlen);
+
+        if write {
+            let value = self.arg(0, data_width);
+            if !self.memory.write(address, width, value) {
+                return Err(cmderr::BUS);
+            }
+        } else {
+            let value = self.memory.read(address, width).ok_or(cmderr::BUS)?;
+            self.set_arg(0, data_width, value);
+        }
+
+        if postincrement {
+            self.set_arg(1, xlen, address + width as u64);
+        }
+
+        Ok(())
+    }
+
+    fn read_sbcs(&self) -> u32 {
+        if !self.system_bus {
+            return 0;
+        }
+
+        let xlen64 = matches!(self.harts.first(), Some(hart) if hart.xlen64);
+        let (sbasize, widths) = if xlen64 { (64, 0b1111) } else { (32, 0b111) };
+
+        // sbversion 1
+        1 << 29 | self.sbcs | self.sberror << 12 | sbasize << 5 | widths
+    }
+
+    fn write_sbcs(&mut self, value: u32) {
+        if !self.system_bus {
+            return;
+        }
+
+        self.sberror &= !(value >> 12 & 0x7);
+        // sbreadonaddr, sbaccess, sbautoincrement and sbreadondata
+        self.sbcs = value & 0x1f_8000;
+    }
+
+    fn system_bus_width(&mut self) -> Option<usize> {
+        if self.sberror != 0 {
+            return None;
+        }
+
+        let width = 1 << (self.sbcs >> 17 & 0x7);
+
+        if width > 8 || self.read_sbcs() & width as u32 == 0 {
+            self.sberror = 4;
+            return None;
+        }
+
+        Some(width)
+    }
+
+    fn system_bus_read(&mut self) {
+        let Some(width) = self.system_bus_width() else {
+            return;
+        };
+
+        match self.memory.read(self.sbaddress, width) {
+            Some(value) => {
+                self.sbdata = [value as u32, (value >> 32) as u32];
+                self.system_bus_increment(width);
+            }
+            None => self.sberror = 2,
+        }
+    }
+
+    fn system_bus_write(&mut self) {
+        let Some(width) = self.system_bus_width() else {
+            return;
+        };
+
+        let value = self.sbdata[0] as u64 | (self.sbdata[1] as u64) << 32;
+
+        if self.memory.write(self.sbaddress, width, value) {
+            self.system_bus_increment(width);
+        } else {
+            self.sberror = 2;
+        }
+    }
+
+    fn system_bus_increment(&mut self, width: usize) {
+        if self.sbcs & 1 << 16 != 0 {
+            self.sbaddress += width as u64;
+        }
+    }
+}
+
+/// The JTAG Debug Transport Module, connected to a [`MockDebugModule`].
+#[derive(Debug)]
+pub(super) struct MockDtm {
+    dm: Arc<Mutex<MockDebugModule>>,
+}
+
+impl MockDtm {
+    fn dm(&self) -> MutexGuard<'_, MockDebugModule> {
+        self.dm.lock().unwrap()
+    }
+}
+
+impl JTAGAccess for MockDtm {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        let value = match address {
+            jtag::IDCODE => MOCK_IDCODE as u128,
+            // version 1
+            jtag::DTMCS => (ABITS << 4 | 1) as u128,
+            jtag::DMI => (self.dm().dmi_result as u128) << 2,
+            other => panic!("Unexpected read of instruction {other:#x}"),
+        };
+
+        Ok(value.to_le_bytes()[..(len as usize + 7) / 8].to_vec())
+    }
+
+    fn set_idle_cycles(&mut self, _idle_cycles: u8) {}
+
+    fn get_idle_cycles(&self) -> u8 {
+        0
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        assert_eq!(len, 5);
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let mut bytes = [0u8; 16];
+        bytes[..data.len()].copy_from_slice(data);
+        let value = u128::from_le_bytes(bytes);
+
+        let captured = match address {
+            // Errors are never sticky, so a dmireset has no effect.
+            jtag::DTMCS => (ABITS << 4 | 1) as u128,
+            jtag::DMI => {
+                assert_eq!(len, ABITS + 34);
+
+                let mut dm = self.dm();
+                let captured = (dm.dmi_result as u128) << 2;
+
+                let dm_address = (value >> 34) as u32 & ((1 << ABITS) - 1);
+                let dm_value = (value >> 2) as u32;
+
+                match value & 0x3 {
+                    1 => dm.dmi_result = dm.read(dm_address),
+                    2 => dm.write(dm_address, dm_value),
+                    _ => {}
+                }
+
+                captured
+            }
+            other => panic!("Unexpected write of instruction {other:#x}"),
+        };
+
+        Ok(captured.to_le_bytes()[..(len as usize + 7) / 8].to_vec())
+    }
+}
+
+/// Only the parts needed to attach a session to the mock are implemented,
+/// the others crash if used.
+impl DebugProbe for MockDtm {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
+
+    fn get_name(&self) -> &str {
+        "Mock RISC-V DTM"
+    }
+
+    fn speed_khz(&self) -> u32 {
+        todo!()
+    }
+
+    fn set_speed(&mut self, _speed_khz: u32) -> Result<u32, DebugProbeError> {
+        todo!()
+    }
+
+    fn set_scan_chain(
+        &mut self,
+        _scan_chain: Vec<ScanChainElement>,
+    ) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn select_protocol(&mut self, _protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        todo!()
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        Some(WireProtocol::Jtag)
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        RiscvCommunicationInterface::new(self).map_err(|(probe, err)| (probe.into_probe(), err))
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        true
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+}
@@ -8,25 +8,51 @@ use crate::{
         Architecture, BreakpointCause, CoreInformation, CoreRegisters, RegisterId, RegisterValue,
     },
     memory::valid_32bit_address,
-    memory_mapped_bitfield_register, CoreInterface, CoreRegister, CoreStatus, CoreType, Error,
-    HaltReason, InstructionSet, MemoryInterface,
+    memory_mapped_bitfield_register, semihosting, CoreInterface, CoreRegister, CoreStatus,
+    CoreType, Error, HaltReason, InstructionSet, MemoryInterface, SemihostingCommand,
+    VectorCatchCondition, Watchpoint, WatchpointAccess, WatchpointCause,
 };
 use anyhow::{anyhow, Result};
 use bitfield::bitfield;
 use communication_interface::{AbstractCommandErrorKind, RiscvCommunicationInterface, RiscvError};
-use registers::RISCV_CORE_REGSISTERS;
+use registers::{RISCV64_CORE_REGSISTERS, RISCV_CORE_REGSISTERS};
 use std::time::{Duration, Instant};
+use triggers::{
+    TriggerAllocator, TriggerUse, TRIGGER_TYPE_ETRIGGER, TRIGGER_TYPE_ICOUNT,
+    TRIGGER_TYPE_ITRIGGER, TRIGGER_TYPE_MCONTROL,
+};
 
 #[macro_use]
 pub(crate) mod registers;
-pub use registers::PC;
+pub use registers::{PC, RV64_PC};
 pub(crate) mod assembly;
 pub mod communication_interface;
 mod dtm;
 pub(crate) mod exception_handling;
+#[cfg(test)]
+mod mock;
 pub mod sequences;
+mod triggers;
+
+/// The `type`, `dmode` and `maskmax` fields of `tdata1`, which are
+/// in bits 31 to 21 on RV32 harts, and in bits 63 to 53 on RV64 harts.
+///
+/// For `etrigger` and `itrigger`, the `hit` field takes the place of `maskmax`.
+const TDATA1_UPPER_FIELDS: u32 = 0xffe0_0000;
+
+/// The `type` and `dmode` fields of an `icount` trigger, the only fields which
+/// depend on the register width. The other fields are in the same bits for RV32 and RV64.
+const TDATA1_ICOUNT_UPPER_FIELDS: u32 = 0xf800_0000;
+
+/// The exception causes which indicate a fault, caught for [`VectorCatchCondition::HardFault`].
+///
+/// These are the misaligned address, access fault, illegal instruction and page fault exceptions.
+const FAULT_EXCEPTIONS: u64 = 0b1011_0000_1111_0111;
 
 /// A interface to operate RISC-V cores.
+///
+/// Both RV32 and RV64 harts are supported, the register width is
+/// determined when the [`RiscvCommunicationInterface`] is created.
 pub struct Riscv32<'probe> {
     interface: &'probe mut RiscvCommunicationInterface,
     state: &'probe mut RiscVState,
@@ -68,35 +94,103 @@ impl<'probe> Riscv32<'probe> {
     }
 
     fn write_csr(&mut self, address: u16, value: u32) -> Result<(), RiscvError> {
+        self.write_csr_xlen(address, value.into())
+    }
+
+    /// Read a register with the full register width of the hart.
+    fn read_csr_xlen(&mut self, address: u16) -> Result<u64, RiscvError> {
+        tracing::debug!("Reading CSR {:#x}", address);
+
+        match self.interface.abstract_cmd_register_read_xlen(address) {
+            Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::NotSupported)) => {
+                tracing::debug!("Could not read core register {:#x} with abstract command, falling back to program buffer", address);
+                self.interface.read_csr_progbuf_xlen(address)
+            }
+            other => other,
+        }
+    }
+
+    /// Write a register with the full register width of the hart.
+    fn write_csr_xlen(&mut self, address: u16, value: u64) -> Result<(), RiscvError> {
         tracing::debug!("Writing CSR {:#x}", address);
 
-        match self.interface.abstract_cmd_register_write(address, value) {
+        match self
+            .interface
+            .abstract_cmd_register_write_xlen(address, value)
+        {
             Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::NotSupported)) => {
                 tracing::debug!("Could not write core register {:#x} with abstract command, falling back to program buffer", address);
-                self.interface.write_csr_progbuf(address, value)
+                self.interface.write_csr_progbuf_xlen(address, value)
             }
             other => other,
         }
     }
 
-    // Resume the core.
-    fn resume_core(&mut self) -> Result<(), crate::Error> {
-        // set resume request.
-        let mut dmcontrol = Dmcontrol(0);
-        dmcontrol.set_resumereq(true);
-        dmcontrol.set_dmactive(true);
-        self.interface.write_dm_register(dmcontrol)?;
+    /// Read the `tdata1` register of the selected trigger, using the layout of RV32 harts.
+    ///
+    /// On RV64 harts, the `type`, `dmode` and `maskmax` fields are in the upper bits of the register.
+    fn read_tdata1(&mut self) -> Result<Tdata1, RiscvError> {
+        let value = self.read_csr_xlen(0x7a1)?;
 
-        // check if request has been acknowleged.
-        let status: Dmstatus = self.interface.read_dm_register()?;
-        if !status.allresumeack() {
-            return Err(RiscvError::RequestNotAcknowledged.into());
+        if self.interface.is_64_bit() {
+            let upper_fields = Self::tdata1_upper_fields((value >> 60) as u32);
+
+            Ok(Tdata1 {
+                value: (value >> 32) as u32 & upper_fields | value as u32 & !upper_fields,
+                rv64_bits: value as u32 & upper_fields,
+            })
+        } else {
+            Ok(Tdata1::new(value as u32))
+        }
+    }
+
+    /// Write the `tdata1` register of the selected trigger, using the layout of RV32 harts.
+    fn write_tdata1(&mut self, value: u32) -> Result<(), RiscvError> {
+        self.update_tdata1(Tdata1::new(0), value)
+    }
+
+    /// Write a modified value of `tdata1`, which was read with [`Self::read_tdata1`].
+    ///
+    /// The fields which only exist on RV64 harts are preserved.
+    fn update_tdata1(&mut self, previous: Tdata1, value: u32) -> Result<(), RiscvError> {
+        let value = if self.interface.is_64_bit() {
+            let upper_fields = Self::tdata1_upper_fields(value >> 28);
+
+            ((value & upper_fields) as u64) << 32
+                | (value & !upper_fields) as u64
+                | (previous.rv64_bits & upper_fields) as u64
+        } else {
+            value as u64
         };
 
-        // clear resume request.
-        let mut dmcontrol = Dmcontrol(0);
-        dmcontrol.set_dmactive(true);
-        self.interface.write_dm_register(dmcontrol)?;
+        self.write_csr_xlen(0x7a1, value)
+    }
+
+    /// The fields of `tdata1` which are in the upper bits of the register on RV64 harts,
+    /// depending on the trigger type.
+    fn tdata1_upper_fields(trigger_type: u32) -> u32 {
+        if trigger_type == TRIGGER_TYPE_ICOUNT {
+            TDATA1_ICOUNT_UPPER_FIELDS
+        } else {
+            TDATA1_UPPER_FIELDS
+        }
+    }
+
+    /// Check that an address can be used by the hart.
+    fn valid_address(&mut self, address: u64) -> Result<u64, crate::Error> {
+        if self.interface.is_64_bit() {
+            Ok(address)
+        } else {
+            Ok(valid_32bit_address(address)?.into())
+        }
+    }
+
+    // Resume the core.
+    fn resume_core(&mut self) -> Result<(), crate::Error> {
+        self.interface
+            .resume_selected_hart(Duration::from_millis(100))?;
+
+        self.state.halted_by_step_trigger = false;
 
         Ok(())
     }
@@ -116,32 +210,79 @@ impl<'probe> Riscv32<'probe> {
             Ok(dmstatus.hasresethaltreq())
         }
     }
-}
 
-impl<'probe> CoreInterface for Riscv32<'probe> {
-    fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), crate::Error> {
-        let start = Instant::now();
+    /// Returns the size of the EBREAK (ebreak or c.ebreak) instruction at the current `dpc`,
+    /// or `None` if there is no EBREAK instruction.
+    fn ebreak_size_at_dpc(&mut self) -> Result<Option<usize>, crate::Error> {
+        let debug_pc = self.read_csr_xlen(0x7b1)?;
 
-        while start.elapsed() < timeout {
-            let dmstatus: Dmstatus = self.interface.read_dm_register()?;
+        let mut instruction = [0u8; 4];
+        self.read_8(debug_pc, &mut instruction[..2])?;
 
-            tracing::trace!("{:?}", dmstatus);
+        if u16::from_le_bytes([instruction[0], instruction[1]]) == 0x9002 {
+            return Ok(Some(2));
+        }
 
-            if dmstatus.allhalted() {
-                return Ok(());
-            }
+        self.read_8(debug_pc + 2, &mut instruction[2..])?;
+
+        if u32::from_le_bytes(instruction) == 0x0010_0073 {
+            Ok(Some(4))
+        } else {
+            Ok(None)
         }
+    }
 
-        Err(Error::Riscv(RiscvError::Timeout))
+    /// Check if the core is halted on the `ebreak` of a semihosting call.
+    ///
+    /// A semihosting call is an uncompressed `ebreak`, placed between `slli x0, x0, 0x1f` and
+    /// `srai x0, x0, 7`.
+    fn is_semihosting_call(&mut self) -> Result<bool, crate::Error> {
+        const SLLI_X0_X0_0X1F: u32 = 0x01F0_1013;
+        const EBREAK: u32 = 0x0010_0073;
+        const SRAI_X0_X0_7: u32 = 0x4070_5013;
+
+        let debug_pc = self.read_csr_xlen(0x7b1)?;
+
+        let mut instructions = [0u8; 12];
+        if self
+            .read_8(debug_pc.wrapping_sub(4), &mut instructions)
+            .is_err()
+        {
+            // The ebreak is at the start or the end of the readable memory, so it can't be
+            // surrounded by the semihosting sequence.
+            return Ok(false);
+        }
+
+        let words = instructions
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()));
+        Ok(words.eq([SLLI_X0_X0_0X1F, EBREAK, SRAI_X0_X0_7]))
     }
 
-    fn core_halted(&mut self) -> Result<bool, crate::Error> {
-        let dmstatus: Dmstatus = self.interface.read_dm_register()?;
+    /// Check if the core halted on a semihosting call, and decode it.
+    ///
+    /// The operation is passed in `a0`, and its parameter in `a1`.
+    fn check_for_semihosting(&mut self) -> Result<Option<SemihostingCommand>, crate::Error> {
+        if !self.is_semihosting_call()? {
+            return Ok(None);
+        }
 
-        Ok(dmstatus.allhalted())
+        let debug_pc = self.read_csr_xlen(0x7b1)?;
+        let operation: u64 = self.read_core_reg(RegisterId(0x100A))?.try_into()?;
+        let parameter: u64 = self.read_core_reg(RegisterId(0x100B))?.try_into()?;
+        tracing::debug!("Semihosting found pc={debug_pc:#x} a0={operation:#x} a1={parameter:#x}");
+
+        // This is defined by the RISC-V Semihosting Specification, which uses the operations of
+        // the ARM Semihosting Specification:
+        // <https://github.com/riscv-non-isa/riscv-semihosting/blob/main/riscv-semihosting.adoc>
+        semihosting::decode_command(self, operation as u32, parameter).map(Some)
     }
 
-    fn status(&mut self) -> Result<crate::core::CoreStatus, crate::Error> {
+    /// Determine the status of the core.
+    ///
+    /// Halts on an `ebreak` are reported as software breakpoints if `decode_semihosting` is
+    /// not set, because the registers of a semihosting call may already contain its result.
+    fn core_status(&mut self, decode_semihosting: bool) -> Result<CoreStatus, crate::Error> {
         // TODO: We should use hartsum to determine if any hart is halted
         //       quickly
 
@@ -149,19 +290,26 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
         if status.allhalted() {
             // determine reason for halt
-            let dcsr = Dcsr(self.read_core_reg(RegisterId::from(0x7b0))?.try_into()?);
+            let dcsr = Dcsr(self.read_csr(0x7b0)?);
 
             let reason = match dcsr.cause() {
-                // An ebreak instruction was hit
-                1 => HaltReason::Breakpoint(BreakpointCause::Software),
-                // Trigger module caused halt
-                2 => HaltReason::Breakpoint(BreakpointCause::Hardware),
+                // An ebreak instruction was hit, which may be a semihosting call
+                1 if !decode_semihosting => HaltReason::Breakpoint(BreakpointCause::Software),
+                1 => match self.check_for_semihosting()? {
+                    Some(command) => HaltReason::Breakpoint(BreakpointCause::Semihosting(command)),
+                    None => HaltReason::Breakpoint(BreakpointCause::Software),
+                },
+                // Trigger module caused halt, which includes single steps using an instruction count trigger
+                2 if self.state.halted_by_step_trigger => HaltReason::Step,
+                2 => self.trigger_halt_reason()?,
                 // Debugger requested a halt
                 3 => HaltReason::Request,
                 // Core halted after single step
                 4 => HaltReason::Step,
                 // Core halted directly after reset
                 5 => HaltReason::Exception,
+                // Another hart of the halt group halted
+                6 => HaltReason::External,
                 // Reserved for future use in specification
                 _ => HaltReason::Unknown,
             };
@@ -177,6 +325,337 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         }
     }
 
+    /// Returns the trigger allocator, which is created by enumerating the triggers
+    /// when it is used for the first time.
+    fn triggers(&mut self) -> Result<&mut TriggerAllocator, crate::Error> {
+        let triggers = match self.state.triggers.take() {
+            Some(triggers) => triggers,
+            None => self.enumerate_triggers()?,
+        };
+
+        Ok(self.state.triggers.insert(triggers))
+    }
+
+    /// Determine the number of triggers, and which trigger types they support.
+    fn enumerate_triggers(&mut self) -> Result<TriggerAllocator, crate::Error> {
+        tracing::debug!("Determining number of HW breakpoints supported");
+
+        let tselect = 0x7a0;
+        let tinfo = 0x7a4;
+
+        let mut trigger_types = vec![];
+
+        // These steps follow the debug specification 0.13, section 5.1 Enumeration
+        loop {
+            let tselect_index = trigger_types.len() as u32;
+
+            tracing::debug!("Trying tselect={}", tselect_index);
+            if let Err(e) = self.write_csr(tselect, tselect_index) {
+                match e {
+                    RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception) => break,
+                    other_error => return Err(other_error.into()),
+                }
+            }
+
+            let readback = self.read_csr(tselect)?;
+
+            if readback != tselect_index {
+                break;
+            }
+
+            let types = match self.read_csr(tinfo) {
+                Ok(tinfo_val) => {
+                    if tinfo_val & 0xffff == 1 {
+                        // Trigger doesn't exist, break the loop
+                        break;
+                    }
+
+                    tinfo_val & 0xffff
+                }
+                Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception)) => {
+                    // An exception means we have to read tdata1 to discover the type
+                    let trigger_type = Mcontrol(self.read_tdata1()?.value).type_();
+
+                    if trigger_type == 0 {
+                        break;
+                    }
+
+                    1 << trigger_type
+                }
+                Err(other) => return Err(other.into()),
+            };
+
+            tracing::info!(
+                "Discovered trigger with index {} and types {:#06x}",
+                tselect_index,
+                types
+            );
+
+            trigger_types.push(types);
+        }
+
+        tracing::debug!("Target supports {} breakpoints.", trigger_types.len());
+
+        Ok(TriggerAllocator::new(trigger_types))
+    }
+
+    /// Disable trigger `unit_index`, and mark it as unused.
+    fn clear_trigger(&mut self, unit_index: usize) -> Result<(), crate::Error> {
+        let tselect = 0x7a0;
+        let tdata2 = 0x7a2;
+
+        self.write_csr(tselect, unit_index as u32)?;
+        self.write_tdata1(0)?;
+        self.write_csr(tdata2, 0)?;
+
+        self.triggers()?.release(unit_index);
+
+        Ok(())
+    }
+
+    /// Step a single instruction using an instruction count trigger, instead of `dcsr.step`.
+    ///
+    /// Returns `false` if no instruction count trigger is available. Unlike with
+    /// `dcsr.step`, interrupts can not be masked, so the step may end at the start
+    /// of an interrupt handler.
+    fn step_with_trigger(&mut self) -> Result<bool, crate::Error> {
+        let tselect = 0x7a0;
+
+        let triggers = self.triggers()?;
+        let unit_index = match triggers.find(TriggerUse::Step) {
+            Some(unit_index) => unit_index,
+            None => match triggers.allocate(TriggerUse::Step, TRIGGER_TYPE_ICOUNT) {
+                Some(unit_index) => unit_index,
+                None => return Ok(false),
+            },
+        };
+
+        let mut icount = Icount(0);
+        icount.set_type(TRIGGER_TYPE_ICOUNT);
+        icount.set_dmode(true);
+        // Halt after a single instruction, in all privilege modes.
+        icount.set_count(1);
+        icount.set_m(true);
+        icount.set_s(true);
+        icount.set_u(true);
+        // Enter debug mode
+        icount.set_action(1);
+
+        self.write_csr(tselect, unit_index as u32)?;
+        self.write_tdata1(icount.0)?;
+
+        self.resume_core()?;
+        self.wait_for_core_halted(Duration::from_millis(100))?;
+
+        self.clear_trigger(unit_index)?;
+
+        // dcsr.cause will report a trigger, not a step.
+        self.state.halted_by_step_trigger = true;
+
+        Ok(true)
+    }
+
+    /// Add the trap causes in `causes` to the exception or interrupt trigger used for `usage`,
+    /// or remove them if `enable` is false.
+    ///
+    /// The trigger is allocated when the first cause is added, and released when the last one is removed.
+    fn update_trap_catch(
+        &mut self,
+        usage: TriggerUse,
+        causes: u64,
+        enable: bool,
+    ) -> Result<(), crate::Error> {
+        let tselect = 0x7a0;
+        let tdata2 = 0x7a2;
+
+        let trigger_type = if usage == TriggerUse::InterruptCatch {
+            TRIGGER_TYPE_ITRIGGER
+        } else {
+            TRIGGER_TYPE_ETRIGGER
+        };
+
+        let (unit_index, current_causes) = match self.triggers()?.find(usage) {
+            Some(unit_index) => {
+                self.write_csr(tselect, unit_index as u32)?;
+                (unit_index, self.read_csr_xlen(tdata2)?)
+            }
+            None if enable => match self.triggers()?.allocate(usage, trigger_type) {
+                Some(unit_index) => (unit_index, 0),
+                None => return Err(RiscvError::NoTriggerAvailable(trigger_type).into()),
+            },
+            None => return Ok(()),
+        };
+
+        let causes = if enable {
+            current_causes | causes
+        } else {
+            current_causes & !causes
+        };
+
+        if causes == 0 {
+            return self.clear_trigger(unit_index);
+        }
+
+        let mut trap_trigger = TrapTrigger(0);
+        trap_trigger.set_type(trigger_type);
+        trap_trigger.set_dmode(true);
+        trap_trigger.set_m(true);
+        trap_trigger.set_s(true);
+        trap_trigger.set_u(true);
+        // Enter debug mode
+        trap_trigger.set_action(1);
+
+        self.write_csr(tselect, unit_index as u32)?;
+        self.write_tdata1(trap_trigger.0)?;
+        self.write_csr_xlen(tdata2, causes)?;
+
+        Ok(())
+    }
+
+    /// A mask of all trap causes which can be selected in `tdata2` of an
+    /// exception or interrupt trigger, one bit per cause.
+    fn all_trap_causes(&mut self) -> Result<u64, crate::Error> {
+        if self.interface.is_64_bit() {
+            Ok(u64::MAX)
+        } else {
+            Ok(u32::MAX.into())
+        }
+    }
+
+    /// Check if a trigger is configured as data watchpoint by probe-rs.
+    fn is_watchpoint_trigger(tdata_value: &Mcontrol) -> bool {
+        tdata_value.type_() == 0b10
+            && tdata_value.action() == 1
+            && !tdata_value.execute()
+            && (tdata_value.load() || tdata_value.store())
+            && (tdata_value.m() || tdata_value.u())
+    }
+
+    /// Determine whether an instruction breakpoint, a data watchpoint or an
+    /// exception or interrupt trigger caused the trigger module to halt the core.
+    ///
+    /// The `hit` bit of the triggers is optional, so if no watchpoint reports a hit,
+    /// the halt is attributed to an instruction breakpoint at the current `dpc`.
+    fn trigger_halt_reason(&mut self) -> Result<HaltReason, crate::Error> {
+        let tselect = 0x7a0;
+        let tdata2 = 0x7a2;
+
+        let dpc = self.read_csr_xlen(0x7b1)?;
+
+        let mut watchpoint_configured = false;
+        let mut trap_catch_configured = false;
+        let mut breakpoint_hit = false;
+
+        for unit_index in 0..self.available_breakpoint_units()? as usize {
+            self.write_csr(tselect, unit_index as u32)?;
+
+            let tdata1 = self.read_tdata1()?;
+            let mut tdata_value = Mcontrol(tdata1.value);
+
+            if Self::is_watchpoint_trigger(&tdata_value) {
+                if tdata_value.hit() {
+                    // The hit bit has to be cleared by the debugger.
+                    tdata_value.set_hit(false);
+                    self.update_tdata1(tdata1, tdata_value.0)?;
+
+                    return Ok(HaltReason::Watchpoint(WatchpointCause::Unit(unit_index)));
+                }
+                watchpoint_configured = true;
+            } else if tdata_value.type_() == TRIGGER_TYPE_MCONTROL
+                && tdata_value.execute()
+                && self.read_csr_xlen(tdata2)? == dpc
+            {
+                breakpoint_hit = true;
+            } else if matches!(
+                tdata_value.type_(),
+                TRIGGER_TYPE_ITRIGGER | TRIGGER_TYPE_ETRIGGER
+            ) {
+                let mut trap_trigger = TrapTrigger(tdata1.value);
+
+                if trap_trigger.hit() {
+                    trap_trigger.set_hit(false);
+                    self.update_tdata1(tdata1, trap_trigger.0)?;
+
+                    return Ok(HaltReason::Exception);
+                }
+                trap_catch_configured = true;
+            }
+        }
+
+        if trap_catch_configured && !breakpoint_hit && self.at_trap_vector(dpc)? {
+            Ok(HaltReason::Exception)
+        } else if watchpoint_configured && !breakpoint_hit {
+            Ok(HaltReason::Watchpoint(WatchpointCause::Unknown))
+        } else {
+            Ok(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        }
+    }
+
+    /// Check if `address` is the base address of the machine mode trap vector,
+    /// where exception and interrupt triggers halt the hart.
+    ///
+    /// With vectored interrupts, an interrupt halts the hart at an offset from the base address,
+    /// so those are only detected if the trigger reports a hit.
+    fn at_trap_vector(&mut self, address: u64) -> Result<bool, crate::Error> {
+        let mtvec = self.read_csr_xlen(0x305)?;
+
+        Ok(mtvec & !0b11 == address)
+    }
+
+    /// Enable or disable all data watchpoints.
+    ///
+    /// Watchpoints are disabled by clearing the privilege mode bits, like instruction breakpoints.
+    fn enable_watchpoints(&mut self, state: bool) -> Result<(), crate::Error> {
+        let tselect = 0x7a0;
+
+        for unit_index in 0..self.available_breakpoint_units()? as usize {
+            self.write_csr(tselect, unit_index as u32)?;
+
+            let tdata1 = self.read_tdata1()?;
+            let mut tdata_value = Mcontrol(tdata1.value);
+
+            if tdata_value.type_() == 0b10
+                && tdata_value.action() == 1
+                && !tdata_value.execute()
+                && (tdata_value.load() || tdata_value.store())
+            {
+                tdata_value.set_m(state);
+                tdata_value.set_u(state);
+                self.update_tdata1(tdata1, tdata_value.0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'probe> CoreInterface for Riscv32<'probe> {
+    fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), crate::Error> {
+        let start = Instant::now();
+
+        while start.elapsed() < timeout {
+            let dmstatus: Dmstatus = self.interface.read_dm_register()?;
+
+            tracing::trace!("{:?}", dmstatus);
+
+            if dmstatus.allhalted() {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Riscv(RiscvError::Timeout))
+    }
+
+    fn core_halted(&mut self) -> Result<bool, crate::Error> {
+        let dmstatus: Dmstatus = self.interface.read_dm_register()?;
+
+        Ok(dmstatus.allhalted())
+    }
+
+    fn status(&mut self) -> Result<crate::core::CoreStatus, crate::Error> {
+        self.core_status(true)
+    }
+
     fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, crate::Error> {
         // write 1 to the haltreq register, which is part
         // of the dmcontrol register
@@ -186,7 +665,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.interface.read_dm_register::<Dmcontrol>()?
         );
 
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.selected_hart_dmcontrol();
 
         dmcontrol.set_haltreq(true);
         dmcontrol.set_dmactive(true);
@@ -196,7 +675,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         self.wait_for_core_halted(timeout)?;
 
         // clear the halt request
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.selected_hart_dmcontrol();
 
         dmcontrol.set_dmactive(true);
 
@@ -237,7 +716,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
         self.reset_catch_set()?;
 
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.selected_hart_dmcontrol();
         dmcontrol.set_dmactive(true);
         dmcontrol.set_hartreset(true);
         dmcontrol.set_haltreq(true);
@@ -260,7 +739,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             //
             // TODO: Cache this
             tracing::debug!("Hartreset bit not supported, using ndmreset");
-            let mut dmcontrol = Dmcontrol(0);
+            let mut dmcontrol = self.interface.selected_hart_dmcontrol();
             dmcontrol.set_dmactive(true);
             dmcontrol.set_ndmreset(true);
             dmcontrol.set_haltreq(true);
@@ -268,7 +747,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.interface.write_dm_register(dmcontrol)?;
 
             tracing::debug!("Clearing ndmreset bit");
-            let mut dmcontrol = Dmcontrol(0);
+            let mut dmcontrol = self.interface.selected_hart_dmcontrol();
             dmcontrol.set_dmactive(true);
             dmcontrol.set_ndmreset(false);
             dmcontrol.set_haltreq(true);
@@ -284,7 +763,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         }
 
         // acknowledge the reset, clear the halt request
-        let mut dmcontrol = Dmcontrol(0);
+        let mut dmcontrol = self.interface.selected_hart_dmcontrol();
         dmcontrol.set_dmactive(true);
         dmcontrol.set_ackhavereset(true);
 
@@ -298,158 +777,108 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn step(&mut self) -> Result<crate::core::CoreInformation, crate::Error> {
-        let halt_reason = self.status()?;
+        // A semihosting call is not decoded again, it may already have been serviced.
+        let halt_reason = self.core_status(false)?;
         if matches!(
             halt_reason,
             CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
-        ) && self.state.hw_breakpoints_enabled
+        ) && (self.state.hw_breakpoints_enabled || self.is_semihosting_call()?)
         {
             // If we are halted on a software breakpoint AND we have passed the flashing operation, we can skip the single step and manually advance the dpc.
-            let mut debug_pc = self.read_core_reg(RegisterId(0x7b1))?;
-            // Advance the dpc by the size of the EBREAK (ebreak or c.ebreak) instruction.
-            if matches!(self.instruction_set()?, InstructionSet::RV32C) {
-                debug_pc.increment_address(2)?;
-            } else {
-                debug_pc.increment_address(4)?;
+            // The ebreak of a semihosting call is always skipped, so the core continues after the call.
+            // The EBREAK may have been removed in the meantime, e.g. by GDB, in which case we step normally.
+            if let Some(ebreak_size) = self.ebreak_size_at_dpc()? {
+                let mut debug_pc = self.read_core_reg(RegisterId(0x7b1))?;
+                // Advance the dpc by the size of the EBREAK (ebreak or c.ebreak) instruction.
+                debug_pc.increment_address(ebreak_size)?;
+
+                self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
+                return Ok(CoreInformation {
+                    pc: debug_pc.try_into()?,
+                });
             }
-
-            self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
-            return Ok(CoreInformation {
-                pc: debug_pc.try_into()?,
-            });
         } else if matches!(
             halt_reason,
             CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
         ) {
             // If we are halted on a hardware breakpoint.
             self.enable_breakpoints(false)?;
+        } else if matches!(halt_reason, CoreStatus::Halted(HaltReason::Watchpoint(_))) {
+            // The access which triggered the watchpoint has not been executed yet.
+            self.enable_watchpoints(false)?;
         }
 
-        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
-        // Set it up, so that the next `self.run()` will only do a single step
-        dcsr.set_step(true);
-        // Disable any interrupts during single step.
-        dcsr.set_stepie(false);
-        dcsr.set_stopcount(true);
-        self.write_csr(0x7b0, dcsr.0)?;
-
-        // Now we can resume the core for the single step.
-        self.resume_core()?;
-        self.wait_for_core_halted(Duration::from_millis(100))?;
-
-        let pc = self.read_core_reg(RegisterId(0x7b1))?;
-
-        // clear step request
-        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
-        dcsr.set_step(false);
-        //Re-enable interrupts for single step.
-        dcsr.set_stepie(true);
-        dcsr.set_stopcount(false);
-        self.write_csr(0x7b0, dcsr.0)?;
-
-        // Re-enable breakpoints before we continue.
-        if matches!(
-            halt_reason,
-            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
-        ) {
-            // If we are halted on a hardware breakpoint.
-            self.enable_breakpoints(true)?;
-        }
-
-        Ok(CoreInformation { pc: pc.try_into()? })
-    }
-
-    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
-        self.read_csr(address.0)
-            .map(|v| v.into())
-            .map_err(|e| e.into())
-    }
-
-    fn write_core_reg(
-        &mut self,
-        address: RegisterId,
-        value: RegisterValue,
-    ) -> Result<(), crate::Error> {
-        let value: u32 = value.try_into()?;
-        self.write_csr(address.0, value).map_err(|e| e.into())
-    }
-
-    fn available_breakpoint_units(&mut self) -> Result<u32, crate::Error> {
-        // TODO: This should probably only be done once, when initialising
-
-        tracing::debug!("Determining number of HW breakpoints supported");
-
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tinfo = 0x7a4;
-
-        let mut tselect_index = 0;
-
-        // These steps follow the debug specification 0.13, section 5.1 Enumeration
-        loop {
-            tracing::debug!("Trying tselect={}", tselect_index);
-            if let Err(e) = self.write_csr(tselect, tselect_index) {
-                match e {
-                    RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception) => break,
-                    other_error => return Err(other_error.into()),
-                }
-            }
-
-            let readback = self.read_csr(tselect)?;
-
-            if readback != tselect_index {
-                break;
-            }
-
-            match self.read_csr(tinfo) {
-                Ok(tinfo_val) => {
-                    if tinfo_val & 0xffff == 1 {
-                        // Trigger doesn't exist, break the loop
-                        break;
-                    } else {
-                        tracing::info!(
-                            "Discovered trigger with index {} and type {}",
-                            tselect_index,
-                            tinfo_val & 0xffff
-                        );
-                    }
-                }
-                Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception)) => {
-                    // An exception means we have to read tdata1 to discover the type
-                    let tdata_val = self.read_csr(tdata1)?;
+        // Prefer an instruction count trigger, and fall back to `dcsr.step` if there is none.
+        if !self.step_with_trigger()? {
+            let mut dcsr = Dcsr(self.read_csr(0x7b0)?);
+            // Set it up, so that the next `self.run()` will only do a single step
+            dcsr.set_step(true);
+            // Disable any interrupts during single step.
+            dcsr.set_stepie(false);
+            dcsr.set_stopcount(true);
+            self.write_csr(0x7b0, dcsr.0)?;
+
+            // Now we can resume the core for the single step.
+            self.resume_core()?;
+            self.wait_for_core_halted(Duration::from_millis(100))?;
+
+            // clear step request
+            let mut dcsr = Dcsr(self.read_csr(0x7b0)?);
+            dcsr.set_step(false);
+            //Re-enable interrupts for single step.
+            dcsr.set_stepie(true);
+            dcsr.set_stopcount(false);
+            self.write_csr(0x7b0, dcsr.0)?;
+        }
 
-                    // Read the mxl field from the misa register (see RISC-V Privileged Spec, 3.1.1)
-                    let misa_value = Misa(self.read_csr(0x301)?);
-                    let xlen = u32::pow(2, misa_value.mxl() + 4);
+        let pc = self.read_core_reg(RegisterId(0x7b1))?;
 
-                    let trigger_type = tdata_val >> (xlen - 4);
+        // Re-enable breakpoints before we continue.
+        if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        ) {
+            // If we are halted on a hardware breakpoint.
+            self.enable_breakpoints(true)?;
+        } else if matches!(halt_reason, CoreStatus::Halted(HaltReason::Watchpoint(_))) {
+            self.enable_watchpoints(true)?;
+        }
 
-                    if trigger_type == 0 {
-                        break;
-                    }
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
 
-                    tracing::info!(
-                        "Discovered trigger with index {} and type {}",
-                        tselect_index,
-                        trigger_type,
-                    );
-                }
-                Err(other) => return Err(other.into()),
-            }
+    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
+        if self.interface.is_64_bit() {
+            Ok(self.read_csr_xlen(address.0)?.into())
+        } else {
+            Ok(self.read_csr(address.0)?.into())
+        }
+    }
 
-            tselect_index += 1;
+    fn write_core_reg(
+        &mut self,
+        address: RegisterId,
+        value: RegisterValue,
+    ) -> Result<(), crate::Error> {
+        if self.interface.is_64_bit() {
+            let value: u64 = value.try_into()?;
+            self.write_csr_xlen(address.0, value)?;
+        } else {
+            let value: u32 = value.try_into()?;
+            self.write_csr(address.0, value)?;
         }
 
-        tracing::debug!("Target supports {} breakpoints.", tselect_index);
+        Ok(())
+    }
 
-        Ok(tselect_index)
+    fn available_breakpoint_units(&mut self) -> Result<u32, crate::Error> {
+        Ok(self.triggers()?.len() as u32)
     }
 
     /// See docs on the [`CoreInterface::hw_breakpoints`] trait
     /// NOTE: For riscv, this assumes that only execution breakpoints are used.
     fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
         let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
         let tdata2 = 0x7a2;
 
         let mut breakpoints = vec![];
@@ -459,25 +888,23 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             self.write_csr(tselect, bp_unit_index as u32)?;
 
             // Read the trigger "configuration" data.
-            let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+            let tdata_value = Mcontrol(self.read_tdata1()?.value);
 
             tracing::warn!("Breakpoint {}: {:?}", bp_unit_index, tdata_value);
 
             // The trigger must be active in at least a single mode
             let trigger_any_mode_active = tdata_value.m() || tdata_value.s() || tdata_value.u();
 
-            let trigger_any_action_enabled =
-                tdata_value.execute() || tdata_value.store() || tdata_value.load();
-
             // Only return if the trigger if it is for an execution debug action in all modes.
+            // Triggers for loads and stores are data watchpoints.
             if tdata_value.type_() == 0b10
                 && tdata_value.action() == 1
                 && tdata_value.match_() == 0
                 && trigger_any_mode_active
-                && trigger_any_action_enabled
+                && tdata_value.execute()
             {
-                let breakpoint = self.read_csr(tdata2)?;
-                breakpoints.push(Some(breakpoint as u64));
+                let breakpoint = self.read_csr_xlen(tdata2)?;
+                breakpoints.push(Some(breakpoint));
             } else {
                 breakpoints.push(None);
             }
@@ -489,14 +916,14 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     fn enable_breakpoints(&mut self, state: bool) -> Result<(), crate::Error> {
         // Loop through all triggers, and enable/disable them.
         let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
 
         for bp_unit_index in 0..self.available_breakpoint_units()? as usize {
             // Select the trigger.
             self.write_csr(tselect, bp_unit_index as u32)?;
 
             // Read the trigger "configuration" data.
-            let mut tdata_value = Mcontrol(self.read_csr(tdata1)?);
+            let tdata1 = self.read_tdata1()?;
+            let mut tdata_value = Mcontrol(tdata1.value);
 
             // Only modify the trigger if it is for an execution debug action in all modes(probe-rs enabled it) or no modes (we previously disabled it).
             if tdata_value.type_() == 0b10
@@ -513,7 +940,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
                 );
                 tdata_value.set_m(state);
                 tdata_value.set_u(state);
-                self.write_csr(tdata1, tdata_value.0)?;
+                self.update_tdata1(tdata1, tdata_value.0)?;
             }
         }
 
@@ -522,7 +949,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn set_hw_breakpoint(&mut self, bp_unit_index: usize, addr: u64) -> Result<(), crate::Error> {
-        let addr = valid_32bit_address(addr)?;
+        let addr = self.valid_address(addr)?;
 
         if !self.hw_breakpoints_enabled() {
             self.enable_breakpoints(true)?;
@@ -530,27 +957,22 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
 
         // select requested trigger
         let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
         let tdata2 = 0x7a2;
 
         tracing::warn!("Setting breakpoint {}", bp_unit_index);
 
-        self.write_csr(tselect, bp_unit_index as u32)?;
-
-        // verify the trigger has the correct type
-
-        let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+        // verify the trigger has the correct type, and is not used for something else
+        self.triggers()?
+            .claim(bp_unit_index, TriggerUse::Breakpoint, TRIGGER_TYPE_MCONTROL)?;
 
-        // This should not happen
-        let trigger_type = tdata_value.type_();
-        if trigger_type != 0b10 {
-            return Err(RiscvError::UnexpectedTriggerType(trigger_type).into());
-        }
+        self.write_csr(tselect, bp_unit_index as u32)?;
 
         // Setup the trigger
 
         let mut instruction_breakpoint = Mcontrol(0);
 
+        instruction_breakpoint.set_type(TRIGGER_TYPE_MCONTROL);
+
         // Enter debug mode
         instruction_breakpoint.set_action(1);
 
@@ -569,42 +991,253 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         // Match address
         instruction_breakpoint.set_select(false);
 
-        self.write_csr(tdata1, instruction_breakpoint.0)?;
-        self.write_csr(tdata2, addr)?;
+        self.write_tdata1(instruction_breakpoint.0)?;
+        self.write_csr_xlen(tdata2, addr)?;
 
         Ok(())
     }
 
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), crate::Error> {
+        self.clear_trigger(unit_index)
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, crate::Error> {
+        self.available_breakpoint_units()
+    }
+
+    /// See docs on the [`CoreInterface::watchpoints`] trait
+    ///
+    /// A watchpoint with a data value uses two chained triggers, and is reported for both of them.
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        let tselect = 0x7a0;
+        let tdata2 = 0x7a2;
+
+        let mut watchpoints: Vec<Option<Watchpoint>> = vec![];
+        let num_triggers = self.available_breakpoint_units()? as usize;
+        for unit_index in 0..num_triggers {
+            // Select the trigger.
+            self.write_csr(tselect, unit_index as u32)?;
+
+            // Read the trigger "configuration" data.
+            let tdata_value = Mcontrol(self.read_tdata1()?.value);
+
+            if !Self::is_watchpoint_trigger(&tdata_value) {
+                watchpoints.push(None);
+                continue;
+            }
+
+            if tdata_value.select() {
+                // The data value of a watchpoint, which was already read with the address.
+                let watchpoint = watchpoints
+                    .last()
+                    .copied()
+                    .flatten()
+                    .filter(|watchpoint| watchpoint.value.is_some());
+                watchpoints.push(watchpoint);
+                continue;
+            }
+
+            let access = match (tdata_value.load(), tdata_value.store()) {
+                (true, false) => WatchpointAccess::Read,
+                (false, true) => WatchpointAccess::Write,
+                _ => WatchpointAccess::ReadWrite,
+            };
+
+            let value = self.read_csr_xlen(tdata2)?;
+
+            let (address, length) = match tdata_value.match_() {
+                // Exact match of a single byte
+                0 => (value, 1),
+                // NAPOT range, the number of trailing ones encodes the size
+                1 => {
+                    let length = 2 << value.trailing_ones();
+                    (value & !(length - 1), length)
+                }
+                _ => {
+                    watchpoints.push(None);
+                    continue;
+                }
+            };
+
+            // The data value is matched by the next trigger in the chain.
+            let value = if tdata_value.chain() && unit_index + 1 < num_triggers {
+                self.write_csr(tselect, unit_index as u32 + 1)?;
+                Some(self.read_csr_xlen(tdata2)?)
+            } else {
+                None
+            };
+
+            watchpoints.push(Some(Watchpoint {
+                address,
+                length,
+                access,
+                value,
+            }));
+        }
+
+        Ok(watchpoints)
+    }
+
+    fn set_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), crate::Error> {
+        let addr = self.valid_address(watchpoint.address)?;
+
+        if !watchpoint.length.is_power_of_two() || watchpoint.address % watchpoint.length != 0 {
+            return Err(Error::Other(anyhow!(
+                "The watched range of {} bytes at {:#010x} is not a naturally aligned power of two",
+                watchpoint.length,
+                addr
+            )));
+        }
+
         let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
         let tdata2 = 0x7a2;
 
         self.write_csr(tselect, unit_index as u32)?;
-        self.write_csr(tdata1, 0)?;
-        self.write_csr(tdata2, 0)?;
+
+        let tdata_value = Mcontrol(self.read_tdata1()?.value);
+
+        let mut data_watchpoint = Mcontrol(0);
+
+        data_watchpoint.set_type(TRIGGER_TYPE_MCONTROL);
+
+        // Enter debug mode
+        data_watchpoint.set_action(1);
+        data_watchpoint.set_dmode(true);
+
+        data_watchpoint.set_m(true);
+        data_watchpoint.set_u(true);
+
+        // Match the address of loads and / or stores
+        data_watchpoint.set_select(false);
+        data_watchpoint.set_load(matches!(
+            watchpoint.access,
+            WatchpointAccess::Read | WatchpointAccess::ReadWrite
+        ));
+        data_watchpoint.set_store(matches!(
+            watchpoint.access,
+            WatchpointAccess::Write | WatchpointAccess::ReadWrite
+        ));
+
+        // Only halt if the next trigger matches the data value as well
+        data_watchpoint.set_chain(watchpoint.value.is_some());
+
+        let match_value = if watchpoint.length == 1 {
+            // Match exactly the value in tdata2
+            data_watchpoint.set_match(0);
+            addr
+        } else {
+            // The largest supported NAPOT range is reported in maskmax.
+            if watchpoint.length.trailing_zeros() > tdata_value.maskmax() {
+                return Err(Error::Other(anyhow!(
+                    "The trigger does not support watching {} bytes",
+                    watchpoint.length
+                )));
+            }
+
+            // Match the top bits of the value in tdata2, up to the first zero bit
+            data_watchpoint.set_match(1);
+            addr | (watchpoint.length / 2 - 1)
+        };
+
+        // verify the trigger has the correct type, and is not used for something else
+        self.triggers()?
+            .claim(unit_index, TriggerUse::Watchpoint, TRIGGER_TYPE_MCONTROL)?;
+
+        if watchpoint.value.is_some() {
+            // The data value is matched by the next trigger, which is chained to this one.
+            if let Err(error) = self.triggers()?.claim(
+                unit_index + 1,
+                TriggerUse::WatchpointValue,
+                TRIGGER_TYPE_MCONTROL,
+            ) {
+                self.triggers()?.release(unit_index);
+                return Err(error.into());
+            }
+        }
+
+        self.write_csr(tselect, unit_index as u32)?;
+        self.write_tdata1(data_watchpoint.0)?;
+        self.write_csr_xlen(tdata2, match_value)?;
+
+        if let Some(value) = watchpoint.value {
+            let mut value_match = data_watchpoint;
+
+            // Match exactly the data value in tdata2
+            value_match.set_chain(false);
+            value_match.set_select(true);
+            value_match.set_match(0);
+
+            self.write_csr(tselect, unit_index as u32 + 1)?;
+            self.write_tdata1(value_match.0)?;
+            self.write_csr_xlen(tdata2, value)?;
+        }
 
         Ok(())
     }
 
+    fn clear_watchpoint(&mut self, unit_index: usize) -> Result<(), crate::Error> {
+        let tselect = 0x7a0;
+
+        self.write_csr(tselect, unit_index as u32)?;
+
+        // The data value trigger must not remain active on its own.
+        if Mcontrol(self.read_tdata1()?.value).chain() {
+            self.clear_trigger(unit_index + 1)?;
+        }
+
+        self.clear_trigger(unit_index)
+    }
+
+    fn unit_reserved(&mut self, unit_index: usize) -> Result<bool, crate::Error> {
+        Ok(self.triggers()?.reserved(unit_index))
+    }
+
+    fn watchpoints_share_breakpoint_units(&self) -> bool {
+        true
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
-        &RISCV_CORE_REGSISTERS
+        if self.interface.is_64_bit() {
+            &RISCV64_CORE_REGSISTERS
+        } else {
+            &RISCV_CORE_REGSISTERS
+        }
     }
 
     fn program_counter(&self) -> &'static CoreRegister {
-        &PC
+        if self.interface.is_64_bit() {
+            &RV64_PC
+        } else {
+            &PC
+        }
     }
 
     fn frame_pointer(&self) -> &'static CoreRegister {
-        &FP
+        if self.interface.is_64_bit() {
+            &RV64_FP
+        } else {
+            &FP
+        }
     }
 
     fn stack_pointer(&self) -> &'static CoreRegister {
-        &SP
+        if self.interface.is_64_bit() {
+            &RV64_SP
+        } else {
+            &SP
+        }
     }
 
     fn return_address(&self) -> &'static CoreRegister {
-        &RA
+        if self.interface.is_64_bit() {
+            &RV64_RA
+        } else {
+            &RA
+        }
     }
 
     fn hw_breakpoints_enabled(&self) -> bool {
@@ -612,7 +1245,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn debug_on_sw_breakpoint(&mut self, enabled: bool) -> Result<(), crate::error::Error> {
-        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
+        let mut dcsr = Dcsr(self.read_csr(0x7b0)?);
 
         dcsr.set_ebreakm(enabled);
         dcsr.set_ebreaks(enabled);
@@ -633,10 +1266,13 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         let misa_value = Misa(self.read_csr(0x301)?);
 
         // Check if the Bit at position 2 (signifies letter C, for compressed) is set.
-        if misa_value.extensions() & (1 << 2) != 0 {
-            Ok(InstructionSet::RV32C)
-        } else {
-            Ok(InstructionSet::RV32)
+        let compressed = misa_value.extensions() & (1 << 2) != 0;
+
+        match (self.interface.is_64_bit(), compressed) {
+            (false, false) => Ok(InstructionSet::RV32),
+            (false, true) => Ok(InstructionSet::RV32C),
+            (true, false) => Ok(InstructionSet::RV64),
+            (true, true) => Ok(InstructionSet::RV64C),
         }
     }
 
@@ -682,6 +1318,46 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         self.debug_on_sw_breakpoint(false)?;
         Ok(())
     }
+
+    fn enable_vector_catch(&mut self, condition: VectorCatchCondition) -> Result<(), Error> {
+        match condition {
+            VectorCatchCondition::HardFault => {
+                self.update_trap_catch(TriggerUse::ExceptionCatch, FAULT_EXCEPTIONS, true)
+            }
+            VectorCatchCondition::CoreReset => self.reset_catch_set(),
+            VectorCatchCondition::SecureFault => {
+                Err(Error::NotImplemented("secure fault vector catch"))
+            }
+            VectorCatchCondition::All => {
+                self.update_trap_catch(TriggerUse::ExceptionCatch, FAULT_EXCEPTIONS, true)?;
+                self.reset_catch_set()
+            }
+            VectorCatchCondition::Interrupt => {
+                let interrupts = self.all_trap_causes()?;
+                self.update_trap_catch(TriggerUse::InterruptCatch, interrupts, true)
+            }
+        }
+    }
+
+    fn disable_vector_catch(&mut self, condition: VectorCatchCondition) -> Result<(), Error> {
+        match condition {
+            VectorCatchCondition::HardFault => {
+                self.update_trap_catch(TriggerUse::ExceptionCatch, FAULT_EXCEPTIONS, false)
+            }
+            VectorCatchCondition::CoreReset => self.reset_catch_clear(),
+            VectorCatchCondition::SecureFault => {
+                Err(Error::NotImplemented("secure fault vector catch"))
+            }
+            VectorCatchCondition::All => {
+                self.update_trap_catch(TriggerUse::ExceptionCatch, FAULT_EXCEPTIONS, false)?;
+                self.reset_catch_clear()
+            }
+            VectorCatchCondition::Interrupt => {
+                let interrupts = self.all_trap_causes()?;
+                self.update_trap_catch(TriggerUse::InterruptCatch, interrupts, false)
+            }
+        }
+    }
 }
 
 impl<'probe> MemoryInterface for Riscv32<'probe> {
@@ -697,6 +1373,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.read_word_32(address)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.interface.read_word_16(address)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.interface.read_word_8(address)
     }
@@ -709,6 +1389,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.read_32(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.interface.read_16(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.interface.read_8(address, data)
     }
@@ -721,6 +1405,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.write_word_32(address, data)
     }
 
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.interface.write_word_16(address, data)
+    }
+
     fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
         self.interface.write_word_8(address, data)
     }
@@ -733,6 +1421,10 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
         self.interface.write_32(address, data)
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        self.interface.write_16(address, data)
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
         self.interface.write_8(address, data)
     }
@@ -758,6 +1450,12 @@ pub struct RiscVState {
 
     /// Store the value of the `hasresethaltreq` bit of the `dmcstatus` register.
     hasresethaltreq: Option<bool>,
+
+    /// The triggers of the trigger module, and what they are used for.
+    triggers: Option<TriggerAllocator>,
+
+    /// Remember that the last step used an instruction count trigger, which is reported as trigger halt.
+    halted_by_step_trigger: bool,
 }
 
 impl RiscVState {
@@ -765,6 +1463,8 @@ impl RiscVState {
         Self {
             hw_breakpoints_enabled: false,
             hasresethaltreq: None,
+            triggers: None,
+            halted_by_step_trigger: false,
         }
     }
 }
@@ -806,6 +1506,20 @@ impl Dmcontrol {
     }
 }
 
+memory_mapped_bitfield_register! {
+    /// `dmcs2` register, located at address 0x32
+    ///
+    /// Used to configure halt and resume groups.
+    pub struct Dmcs2(u32);
+    0x32, "dmcs2",
+    impl From;
+    grouptype, set_grouptype: 11;
+    dmexttrigger, set_dmexttrigger: 10, 7;
+    group, set_group: 6, 2;
+    hgwrite, set_hgwrite: 1;
+    hgselect, set_hgselect: 0;
+}
+
 memory_mapped_bitfield_register! {
     /// Readonly `dmstatus` register.
     ///
@@ -907,6 +1621,25 @@ memory_mapped_bitfield_register! { pub struct Progbuf13(u32); 0x2D, "progbuf13",
 memory_mapped_bitfield_register! { pub struct Progbuf14(u32); 0x2E, "progbuf14", impl From; }
 memory_mapped_bitfield_register! { pub struct Progbuf15(u32); 0x2F, "progbuf15", impl From; }
 
+/// The value of the `tdata1` register of a trigger, in the layout of RV32 harts.
+#[derive(Debug, Clone, Copy)]
+struct Tdata1 {
+    /// The register value.
+    value: u32,
+    /// On RV64 harts, the bits of the lower half of the register which are taken by the
+    /// upper fields in the RV32 layout. These are `sizehi` of an address match trigger.
+    rv64_bits: u32,
+}
+
+impl Tdata1 {
+    fn new(value: u32) -> Self {
+        Self {
+            value,
+            rv64_bits: 0,
+        }
+    }
+}
+
 bitfield! {
     struct Mcontrol(u32);
     impl Debug;
@@ -929,6 +1662,36 @@ bitfield! {
     load, set_load: 0;
 }
 
+bitfield! {
+    /// Instruction count trigger (`icount`), the layout of `tdata1` for trigger type 3.
+    struct Icount(u32);
+    impl Debug;
+
+    type_, set_type: 31, 28;
+    dmode, set_dmode: 27;
+    hit, set_hit: 24;
+    count, set_count: 23, 10;
+    m, set_m: 9;
+    s, set_s: 7;
+    u, set_u: 6;
+    action, set_action: 5, 0;
+}
+
+bitfield! {
+    /// Interrupt and exception triggers (`itrigger` and `etrigger`), the layout of `tdata1`
+    /// for trigger types 4 and 5. The causes to trigger on are selected in `tdata2`.
+    struct TrapTrigger(u32);
+    impl Debug;
+
+    type_, set_type: 31, 28;
+    dmode, set_dmode: 27;
+    hit, set_hit: 26;
+    m, set_m: 9;
+    s, set_s: 7;
+    u, set_u: 6;
+    action, set_action: 5, 0;
+}
+
 bitfield! {
     /// Isa and Extensions (see RISC-V Privileged Spec, 3.1.1)
     pub struct Misa(u32);
@@ -939,3 +1702,637 @@ bitfield! {
     /// Standard RISC-V extensions
     extensions, _: 25, 0;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        communication_interface::{RiscvCommunicationInterface, RiscvError},
+        mock::{MockDebugModule, RESET_VECTOR, TRIGGER_TYPE_MCONTROL},
+        registers::{RISCV64_CORE_REGSISTERS, RISCV_CORE_REGSISTERS},
+        Dmcontrol, RiscVState, Riscv32, FAULT_EXCEPTIONS, PC, RV64_PC, TRIGGER_TYPE_ETRIGGER,
+        TRIGGER_TYPE_ICOUNT, TRIGGER_TYPE_ITRIGGER,
+    };
+    use crate::{
+        core::{BreakpointCause, RegisterId, RegisterValue},
+        CoreInterface, CoreStatus, HaltReason, MemoryInterface, SemihostingCommand,
+        VectorCatchCondition, Watchpoint, WatchpointAccess, WatchpointCause,
+    };
+    use std::time::Duration;
+
+    const NOP: u32 = 0x0000_0013;
+    const EBREAK: u32 = 0x0010_0073;
+    const SLLI_X0_X0_0X1F: u32 = 0x01F0_1013;
+    const SRAI_X0_X0_7: u32 = 0x4070_5013;
+    const SW_X6_0_X5: u32 = 0x0062_A023;
+    const SW_X7_0_X5: u32 = 0x0072_A023;
+    const ILLEGAL: u32 = 0xFFFF_FFFF;
+
+    #[test]
+    fn register_width_is_detected_on_attach() {
+        let (dtm, dm) = MockDebugModule::new(1, true).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+
+        // The hart was running, and has to be resumed after the detection.
+        assert!(!dm.lock().unwrap().harts[0].halted);
+
+        let mut state = RiscVState::new();
+        let core = Riscv32::new(&mut interface, &mut state, 0);
+
+        // No register has been accessed yet, the width is still known.
+        assert!(std::ptr::eq(core.registers(), &*RISCV64_CORE_REGSISTERS));
+        assert_eq!(core.program_counter(), &RV64_PC);
+    }
+
+    #[test]
+    fn rv32_registers() {
+        let (dtm, _dm) = MockDebugModule::new(1, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+
+        assert!(std::ptr::eq(core.registers(), &*RISCV_CORE_REGSISTERS));
+        assert_eq!(core.program_counter(), &PC);
+
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_core_reg(RegisterId(0x1008), RegisterValue::U32(0xdead_beef))
+            .unwrap();
+        assert_eq!(
+            core.read_core_reg(RegisterId(0x1008)).unwrap(),
+            RegisterValue::U32(0xdead_beef)
+        );
+    }
+
+    #[test]
+    fn rv64_registers_and_program_counter() {
+        let (dtm, dm) = MockDebugModule::new(1, true).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        dm.lock().unwrap().harts[0].regs[9] = 0x1234_5678_9abc_def0;
+        assert_eq!(
+            core.read_core_reg(RegisterId(0x1009)).unwrap(),
+            RegisterValue::U64(0x1234_5678_9abc_def0)
+        );
+
+        core.write_core_reg(
+            RegisterId(0x100a),
+            RegisterValue::U64(0xfedc_ba98_7654_3210),
+        )
+        .unwrap();
+        assert_eq!(dm.lock().unwrap().harts[0].regs[10], 0xfedc_ba98_7654_3210);
+
+        core.write_core_reg(RV64_PC.id, RegisterValue::U64(0x1_0000_0040))
+            .unwrap();
+        assert_eq!(dm.lock().unwrap().harts[0].dpc(), 0x1_0000_0040);
+        assert_eq!(
+            core.read_core_reg(RV64_PC.id).unwrap(),
+            RegisterValue::U64(0x1_0000_0040)
+        );
+    }
+
+    #[test]
+    fn rv64_tdata1_layout() {
+        let (dtm, dm) = MockDebugModule::new(1, true).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        core.set_hw_breakpoint(0, 0x8000_0100).unwrap();
+        let tdata1 = dm.lock().unwrap().harts[0].triggers[0].tdata1;
+        assert_eq!(tdata1 >> 60, TRIGGER_TYPE_MCONTROL);
+        // dmode
+        assert_ne!(tdata1 & 1 << 59, 0);
+        // execute
+        assert_ne!(tdata1 & 1 << 2, 0);
+
+        // sizehi is kept when the trigger is modified.
+        dm.lock().unwrap().harts[0].triggers[0].tdata1 |= 0b11 << 21;
+        core.enable_breakpoints(false).unwrap();
+        let tdata1 = dm.lock().unwrap().harts[0].triggers[0].tdata1;
+        assert_eq!(tdata1 >> 60, TRIGGER_TYPE_MCONTROL);
+        assert_eq!(tdata1 >> 21 & 0b11, 0b11);
+    }
+
+    #[test]
+    fn semihosting_call_is_decoded_once() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.memory.write_program(
+            RESET_VECTOR,
+            &[NOP, SLLI_X0_X0_0X1F, EBREAK, SRAI_X0_X0_7, NOP, EBREAK],
+        );
+        // SYS_WRITE0, with a pointer to the string.
+        dm.harts[0].regs[10] = 0x04;
+        dm.harts[0].regs[11] = RESET_VECTOR + 0x100;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_core_reg(PC.id, RegisterValue::U32(RESET_VECTOR as u32))
+            .unwrap();
+        core.debug_on_sw_breakpoint(true).unwrap();
+
+        core.run().unwrap();
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+                SemihostingCommand::Request {
+                    operation: 0x04,
+                    parameter: RESET_VECTOR + 0x100,
+                }
+            )))
+        );
+
+        // The result of the call is written to a0. It is the operation number of
+        // SYS_EXIT_EXTENDED, whose parameter block in a1 must not be read again.
+        core.write_core_reg(RegisterId(0x100A), RegisterValue::U32(0x20))
+            .unwrap();
+        core.write_core_reg(RegisterId(0x100B), RegisterValue::U32(0x10))
+            .unwrap();
+
+        core.run().unwrap();
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
+        );
+        assert_eq!(dm.lock().unwrap().harts[0].dpc(), RESET_VECTOR + 0x14);
+    }
+
+    #[test]
+    fn abstract_command_memory_access_rv64() {
+        let mut dm = MockDebugModule::new(1, true);
+        dm.progbuf_size = 0;
+        dm.abstract_memory_access = true;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        core.write_word_64(RESET_VECTOR, 0x0123_4567_89ab_cdef)
+            .unwrap();
+        core.write_word_8(RESET_VECTOR + 8, 0x5a).unwrap();
+        core.write_32(RESET_VECTOR + 12, &[0xdead_beef, 0xcafe_f00d])
+            .unwrap();
+
+        {
+            let memory = &dm.lock().unwrap().memory;
+            assert_eq!(memory.read(RESET_VECTOR, 8), Some(0x0123_4567_89ab_cdef));
+            assert_eq!(memory.read(RESET_VECTOR + 8, 1), Some(0x5a));
+            assert_eq!(memory.read(RESET_VECTOR + 12, 4), Some(0xdead_beef));
+            assert_eq!(memory.read(RESET_VECTOR + 16, 4), Some(0xcafe_f00d));
+        }
+
+        assert!(core.supports_native_64bit_access());
+        assert_eq!(
+            core.read_word_64(RESET_VECTOR).unwrap(),
+            0x0123_4567_89ab_cdef
+        );
+        assert_eq!(core.read_word_16(RESET_VECTOR + 2).unwrap(), 0x89ab);
+        assert_eq!(core.read_word_8(RESET_VECTOR + 8).unwrap(), 0x5a);
+    }
+
+    #[test]
+    fn abstract_command_memory_access_rv32() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.progbuf_size = 0;
+        dm.abstract_memory_access = true;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        // The address is passed in the 32-bit wide `arg1`, 64-bit accesses are split.
+        assert!(!core.supports_native_64bit_access());
+        core.write_word_64(RESET_VECTOR + 0x20, 0x0123_4567_89ab_cdef)
+            .unwrap();
+        core.write_word_16(RESET_VECTOR + 0x28, 0xbeef).unwrap();
+
+        assert_eq!(
+            dm.lock().unwrap().memory.read(RESET_VECTOR + 0x20, 8),
+            Some(0x0123_4567_89ab_cdef)
+        );
+        assert_eq!(
+            core.read_word_64(RESET_VECTOR + 0x20).unwrap(),
+            0x0123_4567_89ab_cdef
+        );
+        assert_eq!(core.read_word_32(RESET_VECTOR + 0x28).unwrap(), 0xbeef);
+    }
+
+    #[test]
+    fn memory_access_method_fallback() {
+        // The system bus is used without any abstract commands.
+        let mut dm = MockDebugModule::new(1, false);
+        dm.system_bus = true;
+        dm.progbuf_size = 0;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let commands = dm.lock().unwrap().abstract_commands;
+        interface.write_word_32(RESET_VECTOR, 0x1234_5678).unwrap();
+        assert_eq!(interface.read_word_32(RESET_VECTOR).unwrap(), 0x1234_5678);
+        assert_eq!(dm.lock().unwrap().abstract_commands, commands);
+
+        // Without system bus access, the program buffer is preferred.
+        let (dtm, dm) = MockDebugModule::new(1, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_word_32(RESET_VECTOR, 0x1234_5678).unwrap();
+        assert_eq!(
+            dm.lock().unwrap().memory.read(RESET_VECTOR, 4),
+            Some(0x1234_5678)
+        );
+
+        // Neither the program buffer nor the Access Memory command can be used.
+        let mut dm = MockDebugModule::new(1, false);
+        dm.progbuf_size = 0;
+
+        let (dtm, _dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        assert!(core.read_word_32(RESET_VECTOR).is_err());
+    }
+
+    #[test]
+    fn unsupported_program_buffer_is_not_retried() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.progbuf_size = 0;
+        dm.abstract_memory_access = true;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        core.write_word_32(RESET_VECTOR, 0x1234_5678).unwrap();
+
+        // The program buffer was found to be unusable with the first access, so
+        // the following accesses use a single Access Memory command each.
+        let commands = dm.lock().unwrap().abstract_commands;
+        assert_eq!(core.read_word_32(RESET_VECTOR).unwrap(), 0x1234_5678);
+        assert_eq!(dm.lock().unwrap().abstract_commands, commands + 1);
+
+        core.write_word_32(RESET_VECTOR, 0x9abc_def0).unwrap();
+        assert_eq!(dm.lock().unwrap().abstract_commands, commands + 2);
+    }
+
+    #[test]
+    fn dmcontrol_hartsel_encoding() {
+        let mut control = Dmcontrol(0);
+        control.set_hartsel(0x4_0123);
+        assert_eq!(control.hartsello(), 0x123);
+        assert_eq!(control.hartselhi(), 0x100);
+        assert_eq!(control.0, 0x123 << 16 | 0x100 << 6);
+        assert_eq!(control.hartsel(), 0x4_0123);
+
+        // hartsel is 20 bits wide
+        control.set_hartsel(1 << 20 | 5);
+        assert_eq!(control.hartsel(), 5);
+    }
+
+    #[test]
+    fn select_hart() {
+        let (dtm, dm) = MockDebugModule::new(2, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        assert_eq!(interface.num_harts(), 2);
+
+        assert!(matches!(
+            interface.select_hart(2),
+            Err(RiscvError::HartNotFound(2, 2))
+        ));
+
+        let writes = dm.lock().unwrap().dmcontrol_writes.len();
+        interface.select_hart(1).unwrap();
+
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 1);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_core_reg(RegisterId(0x1008), RegisterValue::U32(0x1234_5678))
+            .unwrap();
+
+        let dm = dm.lock().unwrap();
+        assert!(dm.harts[1].halted);
+        assert!(!dm.harts[0].halted);
+        assert_eq!(dm.harts[1].regs[8], 0x1234_5678);
+
+        // All requests after the selection keep the hart selected.
+        assert!(dm.dmcontrol_writes.len() > writes);
+        for &write in &dm.dmcontrol_writes[writes..] {
+            assert_eq!(Dmcontrol(write).hartsel(), 1);
+        }
+    }
+
+    #[test]
+    fn halt_group() {
+        let (dtm, dm) = MockDebugModule::new(2, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        assert!(interface.set_halt_group(0, 1).unwrap());
+        assert!(interface.set_halt_group(1, 1).unwrap());
+
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        assert!(dm.lock().unwrap().harts[1].halted);
+
+        // Resuming one hart of the group resumes the others as well.
+        core.run().unwrap();
+        assert!(!dm.lock().unwrap().harts[1].halted);
+
+        // Without the group, the harts are halted independently.
+        assert!(interface.set_halt_group(0, 0).unwrap());
+        assert!(interface.set_halt_group(1, 0).unwrap());
+
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        assert!(!dm.lock().unwrap().harts[1].halted);
+    }
+
+    #[test]
+    fn halt_group_is_not_enabled_partially() {
+        let file = std::fs::File::open("tests/riscv_multi_hart_test.yaml").unwrap();
+        crate::config::add_target_from_yaml(file).unwrap();
+
+        let mut debug_module = MockDebugModule::new(2, false);
+        debug_module.harts[1].supports_groups = false;
+        let (dtm, dm) = debug_module.into_probe();
+
+        let mut session = crate::Probe::from_specific_probe(Box::new(dtm))
+            .attach("RISCV_TWO_HARTS", crate::Permissions::default())
+            .unwrap();
+        assert!(!session.enable_halt_group(&[0, 1]).unwrap());
+        drop(session);
+
+        // The first hart must not be left in a halt group on its own.
+        let dm = dm.lock().unwrap();
+        assert_eq!(dm.harts[0].halt_group, 0);
+        assert_eq!(dm.harts[0].resume_group, 0);
+    }
+
+    const HALTREQ: u32 = 1 << 31;
+    const RESUMEREQ: u32 = 1 << 30;
+
+    #[test]
+    fn read_chip_info_of_halted_hart() {
+        let (dtm, dm) = MockDebugModule::new(1, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        Riscv32::new(&mut interface, &mut state, 0)
+            .halt(Duration::from_millis(100))
+            .unwrap();
+        dm.lock().unwrap().dmcontrol_writes.clear();
+
+        let info = interface.read_chip_info().unwrap();
+        assert_eq!(info.mvendorid, Some(0x489));
+        assert_eq!(info.marchid, Some(0x8000_0007));
+        assert_eq!(info.mimpid, Some(0x2019_0531));
+
+        // The hart is neither halted nor resumed.
+        let dm = dm.lock().unwrap();
+        assert!(dm.harts[0].halted);
+        assert!(dm
+            .dmcontrol_writes
+            .iter()
+            .all(|value| value & (HALTREQ | RESUMEREQ) == 0));
+    }
+
+    #[test]
+    fn read_chip_info_of_running_hart() {
+        let (dtm, dm) = MockDebugModule::new(1, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        dm.lock().unwrap().dmcontrol_writes.clear();
+
+        let info = interface.read_chip_info().unwrap();
+        assert_eq!(info.mvendorid, Some(0x489));
+
+        // The hart is halted to read the CSRs, and resumed afterwards.
+        let dm = dm.lock().unwrap();
+        assert!(!dm.harts[0].halted);
+        let requests: Vec<u32> = dm
+            .dmcontrol_writes
+            .iter()
+            .map(|value| value & (HALTREQ | RESUMEREQ))
+            .filter(|request| *request != 0)
+            .collect();
+        assert_eq!(requests, [HALTREQ, RESUMEREQ]);
+    }
+
+    #[test]
+    fn resume_waits_for_acknowledgement() {
+        let (dtm, dm) = MockDebugModule::new(1, false).into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+
+        core.halt(Duration::from_millis(100)).unwrap();
+        dm.lock().unwrap().resumeack_delay = 3;
+        core.run().unwrap();
+
+        // A resume request which is never acknowledged times out.
+        core.halt(Duration::from_millis(100)).unwrap();
+        dm.lock().unwrap().resumeack_delay = usize::MAX;
+        assert!(matches!(
+            core.run(),
+            Err(crate::Error::Riscv(RiscvError::RequestNotAcknowledged))
+        ));
+
+        // The resume request is cleared even if it wasn't acknowledged.
+        let dm = dm.lock().unwrap();
+        assert_eq!(dm.dmcontrol_writes.last().unwrap() & RESUMEREQ, 0);
+    }
+
+    /// The types of the triggers of the first hart, from `tdata1` of an RV32 hart.
+    fn trigger_types(dm: &std::sync::Mutex<MockDebugModule>) -> Vec<u32> {
+        let dm = dm.lock().unwrap();
+        dm.harts[0]
+            .triggers
+            .iter()
+            .map(|trigger| (trigger.tdata1 >> 28) as u32)
+            .collect()
+    }
+
+    #[test]
+    fn step_with_instruction_count_trigger() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.memory
+            .write_program(RESET_VECTOR, &[NOP, NOP, NOP, EBREAK]);
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_core_reg(PC.id, RegisterValue::U32(RESET_VECTOR as u32))
+            .unwrap();
+
+        assert_eq!(core.step().unwrap().pc, RESET_VECTOR + 4);
+        assert_eq!(core.status().unwrap(), CoreStatus::Halted(HaltReason::Step));
+
+        // The trigger is released after the step, and dcsr.step is not used.
+        assert!(!trigger_types(&dm).contains(&TRIGGER_TYPE_ICOUNT));
+        assert_eq!(dm.lock().unwrap().harts[0].dcsr() & 1 << 2, 0);
+    }
+
+    #[test]
+    fn step_without_instruction_count_trigger() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.memory
+            .write_program(RESET_VECTOR, &[NOP, NOP, NOP, EBREAK]);
+        for trigger in &mut dm.harts[0].triggers {
+            // Only mcontrol triggers
+            trigger.types = 1 << 2;
+        }
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.write_core_reg(PC.id, RegisterValue::U32(RESET_VECTOR as u32))
+            .unwrap();
+
+        assert_eq!(core.step().unwrap().pc, RESET_VECTOR + 4);
+        assert_eq!(core.status().unwrap(), CoreStatus::Halted(HaltReason::Step));
+        assert_eq!(dm.lock().unwrap().harts[0].dcsr() & 1 << 2, 0);
+    }
+
+    #[test]
+    fn trap_catch_triggers() {
+        let mut dm = MockDebugModule::new(1, false);
+        dm.memory.write_program(RESET_VECTOR, &[NOP, ILLEGAL]);
+        // mtvec
+        assert!(dm.harts[0].write_csr(0x305, RESET_VECTOR + 0x40));
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+
+        core.enable_vector_catch(VectorCatchCondition::HardFault)
+            .unwrap();
+        core.enable_vector_catch(VectorCatchCondition::Interrupt)
+            .unwrap();
+
+        let types = trigger_types(&dm);
+        let etrigger = types
+            .iter()
+            .position(|&t| t == TRIGGER_TYPE_ETRIGGER)
+            .unwrap();
+        let itrigger = types
+            .iter()
+            .position(|&t| t == TRIGGER_TYPE_ITRIGGER)
+            .unwrap();
+        assert_eq!(
+            dm.lock().unwrap().harts[0].triggers[etrigger].tdata2,
+            FAULT_EXCEPTIONS
+        );
+        assert_eq!(
+            dm.lock().unwrap().harts[0].triggers[itrigger].tdata2,
+            u32::MAX as u64
+        );
+
+        // Catching all exceptions reuses the exception trigger.
+        core.enable_vector_catch(VectorCatchCondition::All).unwrap();
+        assert_eq!(trigger_types(&dm), types);
+
+        core.write_core_reg(PC.id, RegisterValue::U32(RESET_VECTOR as u32))
+            .unwrap();
+        core.run().unwrap();
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Exception)
+        );
+        assert_eq!(dm.lock().unwrap().harts[0].dpc(), RESET_VECTOR + 0x40);
+
+        // The triggers are released when the last cause is removed.
+        core.disable_vector_catch(VectorCatchCondition::All)
+            .unwrap();
+        assert!(!trigger_types(&dm).contains(&TRIGGER_TYPE_ETRIGGER));
+        assert!(trigger_types(&dm).contains(&TRIGGER_TYPE_ITRIGGER));
+
+        core.disable_vector_catch(VectorCatchCondition::Interrupt)
+            .unwrap();
+        assert!(!trigger_types(&dm).contains(&TRIGGER_TYPE_ITRIGGER));
+    }
+
+    #[test]
+    fn data_value_watchpoint() {
+        let address = RESET_VECTOR + 0x100;
+
+        let mut dm = MockDebugModule::new(1, false);
+        dm.memory
+            .write_program(RESET_VECTOR, &[SW_X6_0_X5, SW_X7_0_X5, NOP, EBREAK]);
+        dm.harts[0].regs[5] = address;
+        dm.harts[0].regs[6] = 0x1;
+        dm.harts[0].regs[7] = 0x42;
+
+        let (dtm, dm) = dm.into_probe();
+        let mut interface = RiscvCommunicationInterface::new(Box::new(dtm)).unwrap();
+        let mut state = RiscVState::new();
+        let mut core = Riscv32::new(&mut interface, &mut state, 0);
+        core.halt(Duration::from_millis(100)).unwrap();
+        core.debug_on_sw_breakpoint(true).unwrap();
+
+        let watchpoint = Watchpoint {
+            address,
+            length: 4,
+            access: WatchpointAccess::Write,
+            value: Some(0x42),
+        };
+
+        // The value is matched by the next trigger, which doesn't exist for the last one.
+        assert!(core.set_watchpoint(3, watchpoint).is_err());
+        assert!(!core.unit_reserved(3).unwrap());
+
+        core.set_watchpoint(1, watchpoint).unwrap();
+        {
+            let dm = dm.lock().unwrap();
+            let triggers = &dm.harts[0].triggers;
+            // chain
+            assert_ne!(triggers[1].tdata1 & 1 << 11, 0);
+            // select
+            assert_ne!(triggers[2].tdata1 & 1 << 19, 0);
+            assert_eq!(triggers[2].tdata2, 0x42);
+        }
+        assert_eq!(
+            core.watchpoints().unwrap(),
+            vec![None, Some(watchpoint), Some(watchpoint), None]
+        );
+
+        // The first store doesn't write the value.
+        core.write_core_reg(PC.id, RegisterValue::U32(RESET_VECTOR as u32))
+            .unwrap();
+        core.run().unwrap();
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Watchpoint(WatchpointCause::Unit(1)))
+        );
+        assert_eq!(dm.lock().unwrap().harts[0].dpc(), RESET_VECTOR + 4);
+        assert_eq!(dm.lock().unwrap().memory.read(address, 4), Some(0x1));
+
+        // Both triggers are released.
+        core.clear_watchpoint(1).unwrap();
+        assert_eq!(core.watchpoints().unwrap(), vec![None; 4]);
+        assert!(!core.unit_reserved(2).unwrap());
+        assert_eq!(dm.lock().unwrap().harts[0].triggers[2].tdata2, 0);
+
+        core.run().unwrap();
+        assert_eq!(
+            core.status().unwrap(),
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Software))
+        );
+        assert_eq!(dm.lock().unwrap().memory.read(address, 4), Some(0x42));
+    }
+}
@@ -47,9 +47,44 @@ pub const S1: CoreRegister = CoreRegister {
     unwind_rule: UnwindRule::Clear,
 };
 
+/// The program counter register of a RV64 core.
+pub const RV64_PC: CoreRegister = CoreRegister {
+    data_type: RegisterDataType::UnsignedInteger(64),
+    ..PC
+};
+
+pub(crate) const RV64_FP: CoreRegister = CoreRegister {
+    data_type: RegisterDataType::UnsignedInteger(64),
+    ..FP
+};
+
+pub(crate) const RV64_SP: CoreRegister = CoreRegister {
+    data_type: RegisterDataType::UnsignedInteger(64),
+    ..SP
+};
+
+pub(crate) const RV64_RA: CoreRegister = CoreRegister {
+    data_type: RegisterDataType::UnsignedInteger(64),
+    ..RA
+};
+
 pub(crate) static RISCV_CORE_REGSISTERS: Lazy<CoreRegisters> =
     Lazy::new(|| CoreRegisters::new(RISCV_REGISTERS_SET.iter().collect()));
 
+pub(crate) static RISCV64_CORE_REGSISTERS: Lazy<CoreRegisters> =
+    Lazy::new(|| CoreRegisters::new(RISCV64_REGISTERS_SET.iter().collect()));
+
+/// The registers of a RV64 core are the same as the ones of a RV32 core, with a width of 64 bits.
+static RISCV64_REGISTERS_SET: Lazy<Vec<CoreRegister>> = Lazy::new(|| {
+    RISCV_REGISTERS_SET
+        .iter()
+        .map(|register| CoreRegister {
+            data_type: RegisterDataType::UnsignedInteger(64),
+            ..register.clone()
+        })
+        .collect()
+});
+
 static RISCV_REGISTERS_SET: &[CoreRegister] = &[
     CoreRegister {
         roles: &[RegisterRole::Core("x0"), RegisterRole::Other("zero")],
@@ -0,0 +1,225 @@
+//! Allocation of the triggers of the RISC-V trigger module.
+//!
+//! Breakpoints, watchpoints, single stepping and exception catching all use the same
+//! triggers, so the allocator keeps track of which trigger is used for which purpose.
+
+use super::communication_interface::RiscvError;
+
+/// Address / data match trigger (`mcontrol`)
+pub(crate) const TRIGGER_TYPE_MCONTROL: u32 = 2;
+/// Instruction count trigger (`icount`)
+pub(crate) const TRIGGER_TYPE_ICOUNT: u32 = 3;
+/// Interrupt trigger (`itrigger`)
+pub(crate) const TRIGGER_TYPE_ITRIGGER: u32 = 4;
+/// Exception trigger (`etrigger`)
+pub(crate) const TRIGGER_TYPE_ETRIGGER: u32 = 5;
+
+/// The purpose probe-rs uses a trigger for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TriggerUse {
+    /// Instruction breakpoint
+    Breakpoint,
+    /// Address match of a data watchpoint
+    Watchpoint,
+    /// Data value match, chained to the address match in the preceding trigger.
+    WatchpointValue,
+    /// Instruction count trigger used to single step the hart.
+    Step,
+    /// Halt when the hart takes an exception.
+    ExceptionCatch,
+    /// Halt when the hart takes an interrupt.
+    InterruptCatch,
+}
+
+#[derive(Debug)]
+struct Trigger {
+    /// Bit mask of the supported trigger types, as reported by `tinfo`.
+    types: u32,
+    usage: Option<TriggerUse>,
+}
+
+/// Keeps track of the triggers of a hart, and what they are used for.
+#[derive(Debug)]
+pub(crate) struct TriggerAllocator {
+    triggers: Vec<Trigger>,
+}
+
+impl TriggerAllocator {
+    /// Create an allocator for triggers supporting the given types.
+    ///
+    /// Each entry of `types` is a bit mask of the types supported by the trigger,
+    /// in the format of the `tinfo` CSR.
+    pub(crate) fn new(types: Vec<u32>) -> Self {
+        Self {
+            triggers: types
+                .into_iter()
+                .map(|types| Trigger { types, usage: None })
+                .collect(),
+        }
+    }
+
+    /// The number of triggers implemented by the hart.
+    pub(crate) fn len(&self) -> usize {
+        self.triggers.len()
+    }
+
+    /// Check if trigger `index` can be configured as a trigger of type `trigger_type`.
+    pub(crate) fn supports(&self, index: usize, trigger_type: u32) -> bool {
+        self.triggers
+            .get(index)
+            .map(|trigger| trigger.types & (1 << trigger_type) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Check if trigger `index` can not be used for a new breakpoint or watchpoint.
+    pub(crate) fn reserved(&self, index: usize) -> bool {
+        !self.supports(index, TRIGGER_TYPE_MCONTROL)
+            || matches!(
+                self.triggers[index].usage,
+                Some(
+                    TriggerUse::WatchpointValue
+                        | TriggerUse::Step
+                        | TriggerUse::ExceptionCatch
+                        | TriggerUse::InterruptCatch
+                )
+            )
+    }
+
+    /// Returns the first trigger which is used for `usage`.
+    pub(crate) fn find(&self, usage: TriggerUse) -> Option<usize> {
+        self.triggers
+            .iter()
+            .position(|trigger| trigger.usage == Some(usage))
+    }
+
+    /// Use trigger `index` for `usage`.
+    ///
+    /// Breakpoints and watchpoints are placed into a specific trigger, so a trigger
+    /// used by a breakpoint or watchpoint can be taken over by another one.
+    pub(crate) fn claim(
+        &mut self,
+        index: usize,
+        usage: TriggerUse,
+        trigger_type: u32,
+    ) -> Result<(), RiscvError> {
+        if !self.supports(index, trigger_type) {
+            return Err(RiscvError::UnexpectedTriggerType(trigger_type));
+        }
+
+        let trigger = &mut self.triggers[index];
+
+        let available = match (trigger.usage, usage) {
+            (None, _) => true,
+            (Some(current), usage) if current == usage => true,
+            (
+                Some(TriggerUse::Breakpoint | TriggerUse::Watchpoint),
+                TriggerUse::Breakpoint | TriggerUse::Watchpoint,
+            ) => true,
+            _ => false,
+        };
+
+        if !available {
+            return Err(RiscvError::TriggerInUse(index));
+        }
+
+        trigger.usage = Some(usage);
+
+        Ok(())
+    }
+
+    /// Find an unused trigger of type `trigger_type`, and use it for `usage`.
+    ///
+    /// Triggers are allocated starting with the last one, because breakpoints
+    /// and watchpoints are placed starting with the first trigger.
+    pub(crate) fn allocate(&mut self, usage: TriggerUse, trigger_type: u32) -> Option<usize> {
+        let index = (0..self.triggers.len()).rev().find(|&index| {
+            self.triggers[index].usage.is_none() && self.supports(index, trigger_type)
+        })?;
+
+        self.triggers[index].usage = Some(usage);
+
+        Some(index)
+    }
+
+    /// Mark trigger `index` as unused.
+    pub(crate) fn release(&mut self, index: usize) {
+        if let Some(trigger) = self.triggers.get_mut(index) {
+            trigger.usage = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MCONTROL: u32 = 1 << TRIGGER_TYPE_MCONTROL;
+    const ICOUNT: u32 = 1 << TRIGGER_TYPE_ICOUNT;
+    const ETRIGGER: u32 = 1 << TRIGGER_TYPE_ETRIGGER;
+
+    #[test]
+    fn allocate_from_the_last_trigger() {
+        let mut triggers = TriggerAllocator::new(vec![MCONTROL, MCONTROL | ICOUNT, MCONTROL]);
+
+        assert_eq!(
+            triggers.allocate(TriggerUse::Step, TRIGGER_TYPE_ICOUNT),
+            Some(1)
+        );
+        assert_eq!(
+            triggers.allocate(TriggerUse::Step, TRIGGER_TYPE_ICOUNT),
+            None
+        );
+        assert_eq!(
+            triggers.allocate(TriggerUse::ExceptionCatch, TRIGGER_TYPE_ETRIGGER),
+            None
+        );
+        assert_eq!(triggers.find(TriggerUse::Step), Some(1));
+
+        triggers.release(1);
+        assert_eq!(triggers.find(TriggerUse::Step), None);
+    }
+
+    #[test]
+    fn reserved_triggers_are_not_used_for_breakpoints() {
+        let mut triggers = TriggerAllocator::new(vec![MCONTROL, ETRIGGER, MCONTROL | ETRIGGER]);
+
+        assert_eq!(
+            triggers.allocate(TriggerUse::ExceptionCatch, TRIGGER_TYPE_ETRIGGER),
+            Some(2)
+        );
+
+        assert!(!triggers.reserved(0));
+        assert!(triggers.reserved(1));
+        assert!(triggers.reserved(2));
+
+        assert!(matches!(
+            triggers.claim(2, TriggerUse::Breakpoint, TRIGGER_TYPE_MCONTROL),
+            Err(RiscvError::TriggerInUse(2))
+        ));
+        assert!(matches!(
+            triggers.claim(1, TriggerUse::Breakpoint, TRIGGER_TYPE_MCONTROL),
+            Err(RiscvError::UnexpectedTriggerType(TRIGGER_TYPE_MCONTROL))
+        ));
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints_share_triggers() {
+        let mut triggers = TriggerAllocator::new(vec![MCONTROL, MCONTROL]);
+
+        triggers
+            .claim(0, TriggerUse::Breakpoint, TRIGGER_TYPE_MCONTROL)
+            .unwrap();
+        triggers
+            .claim(0, TriggerUse::Watchpoint, TRIGGER_TYPE_MCONTROL)
+            .unwrap();
+        triggers
+            .claim(1, TriggerUse::WatchpointValue, TRIGGER_TYPE_MCONTROL)
+            .unwrap();
+
+        assert!(triggers.reserved(1));
+        assert!(matches!(
+            triggers.claim(0, TriggerUse::WatchpointValue, TRIGGER_TYPE_MCONTROL),
+            Err(RiscvError::TriggerInUse(0))
+        ));
+    }
+}
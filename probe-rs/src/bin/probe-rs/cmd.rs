@@ -6,14 +6,19 @@ pub mod chip;
 pub mod dap_server;
 pub mod debug;
 pub mod download;
+pub mod dump;
 pub mod erase;
 pub mod gdb;
 pub mod info;
 pub mod itm;
 pub mod list;
 pub mod profile;
+pub mod protect;
 pub mod read;
 pub mod reset;
 pub mod run;
+pub mod serve;
+pub mod test;
 pub mod trace;
+pub mod unprotect;
 pub mod write;
@@ -52,6 +52,7 @@ pub struct Flashing {
     pub restore_unwritten_bytes: bool,
     pub flash_layout_output_path: Option<String>,
     pub do_chip_erase: bool,
+    pub skip_unchanged_sectors: bool,
 }
 
 /// The reset config struct holding all the possible reset options.
@@ -21,7 +21,10 @@ use std::{
     panic,
     path::{Path, PathBuf},
     process,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 use time::{OffsetDateTime, UtcOffset};
@@ -181,6 +184,7 @@ fn main_try(mut args: Vec<OsString>, offset: UtcOffset) -> Result<()> {
                     vendor_id: u16::from_str_radix(vid, 16)?,
                     product_id: u16::from_str_radix(pid, 16)?,
                     serial_number: config.probe.serial.clone(),
+                    remote_address: None,
                 };
                 // if two probes with the same VID:PID pair exist we just choose one
                 Probe::open(selector)?
@@ -571,6 +575,9 @@ fn flash(
         program_progress.set_style(style);
         program_progress.set_message(" Programming pages  ");
 
+        let skipped_sectors = Arc::new(AtomicUsize::new(0));
+        let skipped_sectors_clone = skipped_sectors.clone();
+
         let flash_layout_output_path = config.flashing.flash_layout_output_path.clone();
         // Register callback to update the progress.
         let progress = FlashProgress::new(move |event| {
@@ -613,6 +620,13 @@ fn flash(
                 SectorErased { size, .. } => {
                     erase_progress.inc(size);
                 }
+                SectorSkipped { size } => {
+                    erase_progress.inc(size);
+                    skipped_sectors_clone.fetch_add(1, Ordering::Relaxed);
+                }
+                PageSkipped { size } => {
+                    program_progress.inc(size as u64);
+                }
                 PageFilled { size, .. } => {
                     if let Some(fp) = fill_progress.as_ref() {
                         fp.inc(size)
@@ -650,6 +664,7 @@ fn flash(
         options.progress = Some(progress);
         options.keep_unwritten_bytes = config.flashing.restore_unwritten_bytes;
         options.do_chip_erase = config.flashing.do_chip_erase;
+        options.skip_unchanged_sectors = config.flashing.skip_unchanged_sectors;
 
         download_file_with_options(session, path, Format::Elf, options)
             .with_context(|| format!("failed to flash {}", path.display()))?;
@@ -657,10 +672,20 @@ fn flash(
         // If we don't do this, the inactive progress bars will swallow log
         // messages, so they'll never be printed anywhere.
         logging::clear_progress_bar();
+
+        let skipped_sectors = skipped_sectors.load(Ordering::Relaxed);
+        if skipped_sectors > 0 {
+            logging::println(format!(
+                "     {} {} unchanged sectors",
+                "Skipped".green().bold(),
+                skipped_sectors
+            ));
+        }
     } else {
         let mut options = DownloadOptions::new();
         options.keep_unwritten_bytes = config.flashing.restore_unwritten_bytes;
         options.do_chip_erase = config.flashing.do_chip_erase;
+        options.skip_unchanged_sectors = config.flashing.skip_unchanged_sectors;
 
         download_file_with_options(session, path, Format::Elf, options)
             .with_context(|| format!("failed to flash {}", path.display()))?;
@@ -3,7 +3,8 @@ use super::{
     dap_types,
     repl_commands_helpers::{build_expanded_commands, command_completions},
     request_helpers::{
-        disassemble_target_memory, get_dap_source, get_variable_reference,
+        clear_exception_breakpoints, disassemble_target_memory, get_dap_source,
+        get_variable_reference, set_exception_breakpoint, set_function_breakpoint,
         set_instruction_breakpoint,
     },
 };
@@ -12,7 +13,7 @@ use crate::cmd::dap_server::{
     server::{
         configuration::ConsoleLog,
         core_data::CoreHandle,
-        session_data::{BreakpointType, SourceLocationScope},
+        session_data::{BreakpointConditions, BreakpointType, SourceLocationScope},
     },
     DebuggerError,
 };
@@ -28,7 +29,7 @@ use probe_rs::{
         ColumnType, DebugRegisters, SourceLocation, SteppingMode, VariableName, VariableNodeType,
         VerifiedBreakpoint,
     },
-    Architecture::Riscv,
+    Architecture::{Mips, Riscv},
     CoreStatus, Error, HaltReason, MemoryInterface, RegisterValue,
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -660,14 +661,16 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
             // Ensure ebreak enters debug mode, this is necessary for soft breakpoints to work on architectures like RISC-V.
             target_core.core.debug_on_sw_breakpoint(true)?;
 
-            // For RISC-V, we need to re-enable any breakpoints that were previously set, because the core reset 'forgets' them.
-            if target_core.core.architecture() == Riscv {
+            // For RISC-V and MIPS, we need to re-enable any breakpoints that were previously set, because the core reset 'forgets' them.
+            if matches!(target_core.core.architecture(), Riscv | Mips) {
                 let saved_breakpoints = std::mem::take(&mut target_core.core_data.breakpoints);
 
                 for breakpoint in saved_breakpoints {
-                    match target_core
-                        .set_breakpoint(breakpoint.address, breakpoint.breakpoint_type.clone())
-                    {
+                    match target_core.set_breakpoint(
+                        breakpoint.address,
+                        breakpoint.breakpoint_type.clone(),
+                        breakpoint.conditions.clone(),
+                    ) {
                         Ok(_) => {}
                         Err(error) => {
                             //This will cause the debugger to show the user an error, but not stop the debugger.
@@ -809,6 +812,11 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
                         requested_breakpoint_line,
                         requested_breakpoint_column,
                         &args.source,
+                        BreakpointConditions::new(
+                            bp.condition.as_ref(),
+                            bp.hit_condition.as_ref(),
+                            bp.log_message.as_ref(),
+                        ),
                     ) {
                         Ok(VerifiedBreakpoint {
                             address,
@@ -896,6 +904,86 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
         self.send_response(request, Ok(Some(instruction_breakpoint_body)))
     }
 
+    pub(crate) fn set_function_breakpoints(
+        &mut self,
+        target_core: &mut CoreHandle,
+        request: &Request,
+    ) -> Result<()> {
+        let arguments: SetFunctionBreakpointsArguments = get_arguments(self, request)?;
+
+        // Always clear existing breakpoints before setting new ones.
+        match target_core.clear_breakpoints(BreakpointType::FunctionBreakpoint) {
+            Ok(_) => {}
+            Err(error) => tracing::warn!("Failed to clear function breakpoints. {}", error),
+        }
+
+        let function_breakpoint_body = SetFunctionBreakpointsResponseBody {
+            breakpoints: arguments
+                .breakpoints
+                .iter()
+                .map(|requested_breakpoint| {
+                    set_function_breakpoint(
+                        &requested_breakpoint.name,
+                        BreakpointType::FunctionBreakpoint,
+                        BreakpointConditions::new(
+                            requested_breakpoint.condition.as_ref(),
+                            requested_breakpoint.hit_condition.as_ref(),
+                            None,
+                        ),
+                        target_core,
+                    )
+                })
+                .collect(),
+        };
+
+        // In addition to the response values, also show a message to users for any breakpoints that could not be verified.
+        for breakpoint_response in &function_breakpoint_body.breakpoints {
+            if !breakpoint_response.verified {
+                if let Some(message) = &breakpoint_response.message {
+                    self.log_to_console(format!("Warning: {message}"));
+                    self.show_message(MessageSeverity::Warning, message.clone());
+                }
+            }
+        }
+
+        self.send_response(request, Ok(Some(function_breakpoint_body)))
+    }
+
+    pub(crate) fn set_exception_breakpoints(
+        &mut self,
+        target_core: &mut CoreHandle,
+        request: &Request,
+    ) -> Result<()> {
+        let arguments: SetExceptionBreakpointsArguments = get_arguments(self, request)?;
+
+        // Always clear existing exception breakpoints before enabling the requested filters.
+        if let Err(error) = clear_exception_breakpoints(target_core) {
+            tracing::warn!("Failed to clear exception breakpoints. {}", error);
+        }
+
+        let breakpoints: Vec<Breakpoint> = arguments
+            .filters
+            .iter()
+            .map(|filter| set_exception_breakpoint(filter, target_core))
+            .collect();
+
+        for breakpoint_response in &breakpoints {
+            if !breakpoint_response.verified {
+                if let Some(message) = &breakpoint_response.message {
+                    self.log_to_console(format!("Warning: {message}"));
+                    self.show_message(MessageSeverity::Warning, message.clone());
+                }
+            }
+        }
+
+        self.send_response(
+            request,
+            Ok(Some(SetExceptionBreakpointsResponseBody {
+                breakpoints: Some(breakpoints),
+            })),
+        )
+    }
+
     pub(crate) fn threads(
         &mut self,
         target_core: &mut CoreHandle,
@@ -1568,7 +1656,9 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
                     Err(wait_error) => {
                         if matches!(
                             wait_error,
-                            Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout)
+                            Error::Arm(ArmError::Timeout)
+                                | Error::Riscv(RiscvError::Timeout)
+                                | Error::Timeout
                         ) {
                             // The core is still running.
                         } else {
@@ -30,9 +30,17 @@ impl DapStatus for CoreStatus {
                     "exception",
                     "Core halted due to an exception, e.g. interupt handler".to_string(),
                 ),
-                HaltReason::Watchpoint => (
+                HaltReason::Watchpoint(cause) => (
                     "data breakpoint",
-                    "Core halted due to a watchpoint or data breakpoint".to_string(),
+                    format!(
+                        "Core halted due to a watchpoint or data breakpoint ({:?}) @{}.",
+                        cause,
+                        if let Some(program_counter) = program_counter {
+                            format!("{program_counter:#010x}")
+                        } else {
+                            "(unspecified location)".to_string()
+                        }
+                    ),
                 ),
                 HaltReason::Step => (
                     "step",
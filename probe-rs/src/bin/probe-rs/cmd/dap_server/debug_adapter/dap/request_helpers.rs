@@ -1,21 +1,36 @@
 use crate::cmd::dap_server::{
     debug_adapter::dap::dap_types::{DisassembledInstruction, Source},
-    server::{core_data::CoreHandle, session_data::BreakpointType},
+    server::{
+        core_data::CoreHandle,
+        session_data::{BreakpointConditions, BreakpointType},
+    },
     DebuggerError,
 };
 use anyhow::{anyhow, Result};
 use capstone::{
     arch::arm::ArchMode as armArchMode, arch::arm64::ArchMode as aarch64ArchMode,
-    arch::riscv::ArchMode as riscvArchMode, prelude::*, Endian,
+    arch::mips::ArchMode as mipsArchMode, arch::riscv::ArchMode as riscvArchMode, prelude::*,
+    Endian,
 };
 use num_traits::Zero;
 use probe_rs::{
     debug::{ColumnType, SourceLocation},
-    CoreType, InstructionSet, MemoryInterface,
+    CoreType, Error, InstructionSet, MemoryInterface, VectorCatchCondition,
 };
 use std::time::Duration;
 
-use super::dap_types::{Breakpoint, InstructionBreakpoint, MemoryAddress};
+use super::dap_types::{
+    Breakpoint, ExceptionBreakpointsFilter, InstructionBreakpoint, MemoryAddress,
+};
+
+/// The filter for [`exception_breakpoint_filters`] which halts on a HardFault.
+const HARD_FAULT_FILTER: &str = "hardfault";
+/// The filter for [`exception_breakpoint_filters`] which halts on a core reset.
+const RESET_FILTER: &str = "reset";
+/// The filter for [`exception_breakpoint_filters`] which halts when a Rust panic occurs.
+const PANIC_FILTER: &str = "panic";
+/// The function which is called by the Rust `core` library for every panic.
+const PANIC_HANDLER_FUNCTION: &str = "rust_begin_unwind";
 
 pub(crate) fn disassemble_target_memory(
     target_core: &mut CoreHandle,
@@ -27,7 +42,10 @@ pub(crate) fn disassemble_target_memory(
     let cs = get_capstone(target_core)?;
     let target_instruction_set = target_core.core.instruction_set()?;
     let instruction_offset_as_bytes = match target_instruction_set {
-        InstructionSet::Thumb2 | InstructionSet::RV32C => {
+        InstructionSet::Thumb2
+        | InstructionSet::RV32C
+        | InstructionSet::RV64C
+        | InstructionSet::MicroMips => {
             // Since we cannot guarantee the size of individual instructions, let's assume we will read the 120% of the requested number of 16-bit instructions.
             (instruction_offset
                 * target_core
@@ -37,7 +55,11 @@ pub(crate) fn disassemble_target_memory(
                 / 4
                 * 5
         }
-        InstructionSet::A32 | InstructionSet::A64 | InstructionSet::RV32 => {
+        InstructionSet::A32
+        | InstructionSet::A64
+        | InstructionSet::RV32
+        | InstructionSet::RV64
+        | InstructionSet::Mips32 => {
             instruction_offset
                 * target_core
                     .core
@@ -258,6 +280,30 @@ pub(crate) fn get_capstone(target_core: &mut CoreHandle) -> Result<Capstone, Deb
                 capstone::arch::riscv::ArchExtraMode::RiscVC,
             ))
             .build(),
+        InstructionSet::RV64 => Capstone::new()
+            .riscv()
+            .mode(riscvArchMode::RiscV64)
+            .endian(Endian::Little)
+            .build(),
+        InstructionSet::RV64C => Capstone::new()
+            .riscv()
+            .mode(riscvArchMode::RiscV64)
+            .endian(Endian::Little)
+            .extra_mode(std::iter::once(
+                capstone::arch::riscv::ArchExtraMode::RiscVC,
+            ))
+            .build(),
+        InstructionSet::Mips32 => Capstone::new()
+            .mips()
+            .mode(mipsArchMode::Mips32)
+            .endian(Endian::Little)
+            .build(),
+        InstructionSet::MicroMips => Capstone::new()
+            .mips()
+            .mode(mipsArchMode::Mips32)
+            .endian(Endian::Little)
+            .extra_mode(std::iter::once(capstone::arch::mips::ArchExtraMode::Micro))
+            .build(),
     }
     .map_err(|err| anyhow!("Error creating capstone: {:?}", err))?;
     let _ = cs.set_skipdata(true);
@@ -384,7 +430,15 @@ pub(crate) fn set_instruction_breakpoint(
         .as_str()
         .try_into()
     {
-        match target_core.set_breakpoint(memory_reference, BreakpointType::InstructionBreakpoint) {
+        match target_core.set_breakpoint(
+            memory_reference,
+            BreakpointType::InstructionBreakpoint,
+            BreakpointConditions::new(
+                requested_breakpoint.condition.as_ref(),
+                requested_breakpoint.hit_condition.as_ref(),
+                None,
+            ),
+        ) {
             Ok(_) => {
                 breakpoint_response.verified = true;
                 breakpoint_response.instruction_reference =
@@ -428,3 +482,189 @@ pub(crate) fn set_instruction_breakpoint(
     };
     breakpoint_response
 }
+
+/// A helper function to set the breakpoints for the function(s) called `function_name`, and return a [`Breakpoint`] struct for the response.
+/// If the name resolves to more than one function, e.g. a generic function, a breakpoint is set on each of them, and the first location is reported.
+pub(crate) fn set_function_breakpoint(
+    function_name: &str,
+    breakpoint_type: BreakpointType,
+    conditions: BreakpointConditions,
+    target_core: &mut CoreHandle,
+) -> Breakpoint {
+    let mut breakpoint_response = Breakpoint {
+        column: None,
+        end_column: None,
+        end_line: None,
+        id: None,
+        instruction_reference: None,
+        line: None,
+        message: None,
+        offset: None,
+        source: None,
+        verified: false,
+    };
+
+    let addresses = match target_core
+        .core_data
+        .debug_info
+        .get_function_addresses(function_name)
+    {
+        Ok(addresses) if addresses.is_empty() => {
+            breakpoint_response.message = Some(format!(
+                "Could not find a function called {function_name:?}. Functions that are only ever inlined can not be used as a breakpoint location."
+            ));
+            return breakpoint_response;
+        }
+        Ok(addresses) => addresses,
+        Err(error) => {
+            breakpoint_response.message = Some(format!(
+                "Could not find a function called {function_name:?}: {error}"
+            ));
+            return breakpoint_response;
+        }
+    };
+
+    for address in &addresses {
+        if let Err(error) =
+            target_core.set_breakpoint(*address, breakpoint_type.clone(), conditions.clone())
+        {
+            breakpoint_response.message = Some(format!(
+                "Warning: Could not set breakpoint for {function_name:?} at memory address: {address:#010x}: {error}"
+            ));
+            return breakpoint_response;
+        }
+    }
+
+    let address = addresses[0];
+    breakpoint_response.verified = true;
+    breakpoint_response.instruction_reference = Some(format!("{address:#010x}"));
+    if let Some(source_location) = target_core
+        .core_data
+        .debug_info
+        .get_source_location(address)
+    {
+        breakpoint_response.source = get_dap_source(&source_location);
+        breakpoint_response.line = source_location.line.map(|line| line as i64);
+        breakpoint_response.column = source_location.column.map(|col| match col {
+            ColumnType::LeftEdge => 0_i64,
+            ColumnType::Column(c) => c as i64,
+        });
+    }
+    breakpoint_response.message = Some(match addresses.len() {
+        1 => format!("Function breakpoint for {function_name:?} set @:{address:#010x}."),
+        count => format!(
+            "Function breakpoint for {function_name:?} set at {count} locations, the first @:{address:#010x}."
+        ),
+    });
+    breakpoint_response
+}
+
+/// The exception breakpoint filters which can be selected by the user, and are advertised in the `Capabilities` of the debug adapter.
+pub(crate) fn exception_breakpoint_filters() -> Vec<ExceptionBreakpointsFilter> {
+    vec![
+        ExceptionBreakpointsFilter {
+            filter: HARD_FAULT_FILTER.to_string(),
+            label: "HardFault".to_string(),
+            description: Some("Halt when a HardFault exception occurs.".to_string()),
+            default: Some(false),
+            supports_condition: None,
+            condition_description: None,
+        },
+        ExceptionBreakpointsFilter {
+            filter: PANIC_FILTER.to_string(),
+            label: "Rust panic".to_string(),
+            description: Some(format!(
+                "Halt when the target panics, with a breakpoint on `{PANIC_HANDLER_FUNCTION}`."
+            )),
+            default: Some(false),
+            supports_condition: None,
+            condition_description: None,
+        },
+        ExceptionBreakpointsFilter {
+            filter: RESET_FILTER.to_string(),
+            label: "Reset".to_string(),
+            description: Some("Halt when the core is reset.".to_string()),
+            default: Some(false),
+            supports_condition: None,
+            condition_description: None,
+        },
+    ]
+}
+
+/// Remove all the exception breakpoints, i.e. disable the vector catches, and clear the breakpoints for the [`exception_breakpoint_filters`].
+pub(crate) fn clear_exception_breakpoints(
+    target_core: &mut CoreHandle,
+) -> Result<(), DebuggerError> {
+    for condition in [
+        VectorCatchCondition::HardFault,
+        VectorCatchCondition::CoreReset,
+    ] {
+        match target_core.core.disable_vector_catch(condition) {
+            Ok(()) | Err(Error::NotImplemented(_)) => {}
+            Err(error) => return Err(DebuggerError::ProbeRs(error)),
+        }
+    }
+    target_core.clear_breakpoints(BreakpointType::ExceptionBreakpoint)?;
+    Ok(())
+}
+
+/// A helper function to enable the exception breakpoint for one of the [`exception_breakpoint_filters`], and return a [`Breakpoint`] struct for the response.
+pub(crate) fn set_exception_breakpoint(filter: &str, target_core: &mut CoreHandle) -> Breakpoint {
+    let vector_catch_condition = match filter {
+        HARD_FAULT_FILTER => VectorCatchCondition::HardFault,
+        RESET_FILTER => VectorCatchCondition::CoreReset,
+        PANIC_FILTER => {
+            return set_function_breakpoint(
+                PANIC_HANDLER_FUNCTION,
+                BreakpointType::ExceptionBreakpoint,
+                BreakpointConditions::default(),
+                target_core,
+            );
+        }
+        other => {
+            return Breakpoint {
+                column: None,
+                end_column: None,
+                end_line: None,
+                id: None,
+                instruction_reference: None,
+                line: None,
+                message: Some(format!("Unknown exception breakpoint filter: {other:?}")),
+                offset: None,
+                source: None,
+                verified: false,
+            };
+        }
+    };
+
+    let (verified, message) = match target_core.core.enable_vector_catch(vector_catch_condition) {
+        Ok(()) => (
+            true,
+            format!("Exception breakpoint for {filter:?} enabled."),
+        ),
+        Err(Error::NotImplemented(_)) => (
+            false,
+            format!(
+                "Exception breakpoints for {filter:?} are not supported for {:?} cores.",
+                target_core.core.core_type()
+            ),
+        ),
+        Err(error) => (
+            false,
+            format!("Warning: Could not enable the exception breakpoint for {filter:?}: {error}"),
+        ),
+    };
+
+    Breakpoint {
+        column: None,
+        end_column: None,
+        end_line: None,
+        id: None,
+        instruction_reference: None,
+        line: None,
+        message: Some(message),
+        offset: None,
+        source: None,
+        verified,
+    }
+}
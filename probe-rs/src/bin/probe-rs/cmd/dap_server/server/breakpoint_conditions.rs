@@ -0,0 +1,352 @@
+use crate::cmd::dap_server::DebuggerError;
+use anyhow::anyhow;
+use std::cmp::Ordering;
+
+/// The comparison operators supported in a breakpoint condition. Two character operators are listed first, so that `<=` is not mistaken for `<`.
+const COMPARISON_OPERATORS: [&str; 6] = ["==", "!=", "<=", ">=", "<", ">"];
+
+/// Evaluate the `condition` of a breakpoint, e.g. `index == 10 && state != 0`.
+///
+/// Every operand is either an integer literal, a quoted string, `true`/`false`, or the name of a register or variable,
+/// which will be looked up with `resolve`. Comparisons can be combined with `&&` and `||`, where `&&` takes precedence.
+/// An operand without a comparison is true if it is not zero and not `false`.
+pub(crate) fn evaluate_condition(
+    condition: &str,
+    resolve: &mut dyn FnMut(&str) -> Option<String>,
+) -> Result<bool, DebuggerError> {
+    for alternative in split_outside_quotes(condition, "||") {
+        let mut all_terms_are_true = true;
+        for term in split_outside_quotes(alternative, "&&") {
+            if !evaluate_term(term.trim(), resolve)? {
+                all_terms_are_true = false;
+                break;
+            }
+        }
+        if all_terms_are_true {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Check if `hit_count` satisfies the `hitCondition` of a breakpoint.
+///
+/// The hit condition is a number, optionally preceded by one of `==`, `!=`, `<`, `<=`, `>`, `>=` or `%`.
+/// A plain number is treated like `>=`, i.e. the breakpoint will halt on that hit, and every hit after it.
+pub(crate) fn hit_condition_is_met(
+    hit_condition: &str,
+    hit_count: u64,
+) -> Result<bool, DebuggerError> {
+    let hit_condition = hit_condition.trim();
+    let (operator, value) = COMPARISON_OPERATORS
+        .iter()
+        .chain(["%"].iter())
+        .find_map(|operator| {
+            hit_condition
+                .strip_prefix(operator)
+                .map(|value| (*operator, value))
+        })
+        .unwrap_or((">=", hit_condition));
+
+    let value = value.trim().parse::<u64>().map_err(|_| {
+        DebuggerError::Other(anyhow!(
+            "Invalid hit condition `{hit_condition}`. Expected a number, optionally preceded by one of ==, !=, <, <=, >, >= or %."
+        ))
+    })?;
+
+    match operator {
+        "%" if value == 0 => Err(DebuggerError::Other(anyhow!(
+            "Invalid hit condition `{hit_condition}`. The modulus can not be zero."
+        ))),
+        "%" => Ok(hit_count % value == 0),
+        comparison => Ok(compare(hit_count.cmp(&value), comparison)),
+    }
+}
+
+/// Build the message of a logpoint, by replacing every `{expression}` with the value of the expression.
+///
+/// Literal braces can be written as `{{` and `}}`.
+pub(crate) fn format_log_message(
+    log_message: &str,
+    resolve: &mut dyn FnMut(&str) -> Option<String>,
+) -> String {
+    let mut message = String::with_capacity(log_message.len());
+    let mut characters = log_message.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '{' if characters.peek() == Some(&'{') => {
+                characters.next();
+                message.push('{');
+            }
+            '}' if characters.peek() == Some(&'}') => {
+                characters.next();
+                message.push('}');
+            }
+            '{' => {
+                let expression: String = characters.by_ref().take_while(|c| *c != '}').collect();
+                match operand_value(expression.trim(), resolve) {
+                    Ok(value) => message.push_str(&value),
+                    Err(error) => message.push_str(&format!("<{error}>")),
+                }
+            }
+            other => message.push(other),
+        }
+    }
+
+    message
+}
+
+/// Evaluate a single comparison, or a single operand, of a condition.
+fn evaluate_term(
+    term: &str,
+    resolve: &mut dyn FnMut(&str) -> Option<String>,
+) -> Result<bool, DebuggerError> {
+    if term.is_empty() {
+        return Err(DebuggerError::Other(anyhow!(
+            "Missing operand in breakpoint condition."
+        )));
+    }
+
+    let Some((position, operator)) = find_comparison_operator(term) else {
+        let value = operand_value(term, resolve)?;
+        return match value.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => parse_number(other)
+                .map(|number| number != Number::Integer(0) && number != Number::Float(0.0))
+                .ok_or_else(|| {
+                    DebuggerError::Other(anyhow!(
+                        "The value `{other}` of `{term}` can not be used as a condition."
+                    ))
+                }),
+        };
+    };
+
+    let left = operand_value(term[..position].trim(), resolve)?;
+    let right = operand_value(term[position + operator.len()..].trim(), resolve)?;
+
+    let ordering = match (parse_number(&left), parse_number(&right)) {
+        (Some(left), Some(right)) => compare_numbers(&left, &right),
+        _ => Some(left.cmp(&right)),
+    };
+
+    // Values that can not be ordered (NaN) are only ever unequal.
+    Ok(ordering.map_or(operator == "!=", |ordering| compare(ordering, operator)))
+}
+
+/// Split `condition` at every `separator` which is not inside a quoted string.
+fn split_outside_quotes<'a>(condition: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (position, character) in condition.char_indices() {
+        if character == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && position >= start && condition[position..].starts_with(separator) {
+            parts.push(&condition[start..position]);
+            start = position + separator.len();
+        }
+    }
+    parts.push(&condition[start..]);
+    parts
+}
+
+/// Find the first comparison operator in `term` which is not inside a quoted string.
+fn find_comparison_operator(term: &str) -> Option<(usize, &'static str)> {
+    let mut in_quotes = false;
+    for (position, character) in term.char_indices() {
+        if character == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes {
+            if let Some(operator) = COMPARISON_OPERATORS
+                .iter()
+                .find(|operator| term[position..].starts_with(**operator))
+            {
+                return Some((position, operator));
+            }
+        }
+    }
+    None
+}
+
+/// Resolve an operand to its value. Literals are returned as is, and anything else is looked up with `resolve`.
+fn operand_value(
+    operand: &str,
+    resolve: &mut dyn FnMut(&str) -> Option<String>,
+) -> Result<String, DebuggerError> {
+    if operand.is_empty() {
+        return Err(DebuggerError::Other(anyhow!("Missing operand.")));
+    }
+
+    if operand.len() >= 2 && operand.starts_with('"') && operand.ends_with('"') {
+        return Ok(operand[1..operand.len() - 1].to_string());
+    }
+
+    if operand == "true" || operand == "false" || parse_number(operand).is_some() {
+        return Ok(operand.to_string());
+    }
+
+    resolve(operand)
+        .map(|value| value.trim_matches('"').to_string())
+        .ok_or_else(|| {
+            DebuggerError::Other(anyhow!(
+                "`{operand}` is not a register or a variable in scope."
+            ))
+        })
+}
+
+/// A numeric value in a condition.
+#[derive(Debug, PartialEq)]
+enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+/// Parse a decimal, hexadecimal (`0x`) or binary (`0b`) integer, or a floating point number.
+fn parse_number(value: &str) -> Option<Number> {
+    let (negative, digits) = match value.trim().strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.trim()),
+    };
+
+    let integer = if let Some(hex_digits) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i128::from_str_radix(hex_digits, 16).ok()
+    } else if let Some(binary_digits) = digits
+        .strip_prefix("0b")
+        .or_else(|| digits.strip_prefix("0B"))
+    {
+        i128::from_str_radix(binary_digits, 2).ok()
+    } else {
+        digits.parse::<i128>().ok()
+    };
+
+    match integer {
+        Some(integer) if negative => Some(Number::Integer(-integer)),
+        Some(integer) => Some(Number::Integer(integer)),
+        // Only accept floats in numeric notation, so that `inf` or `nan` can be variable names.
+        None if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+            value.trim().parse::<f64>().ok().map(Number::Float)
+        }
+        None => None,
+    }
+}
+
+impl Number {
+    fn as_f64(&self) -> f64 {
+        match self {
+            Number::Integer(integer) => *integer as f64,
+            Number::Float(float) => *float,
+        }
+    }
+}
+
+/// Compare numbers, converting to floating point only if either of them is not an integer.
+fn compare_numbers(left: &Number, right: &Number) -> Option<Ordering> {
+    match (left, right) {
+        (Number::Integer(left), Number::Integer(right)) => Some(left.cmp(right)),
+        (left, right) => left.as_f64().partial_cmp(&right.as_f64()),
+    }
+}
+
+/// Apply a comparison `operator` to the `ordering` of its operands.
+fn compare(ordering: Ordering, operator: &str) -> bool {
+    match operator {
+        "==" => ordering == Ordering::Equal,
+        "!=" => ordering != Ordering::Equal,
+        "<" => ordering == Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        ">=" => ordering != Ordering::Less,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn resolve(expression: &str) -> Option<String> {
+        match expression {
+            "index" => Some("10".to_string()),
+            "flag" => Some("true".to_string()),
+            "name" => Some("\"probe\"".to_string()),
+            "pc" => Some("0x08000400".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn condition_comparisons() {
+        assert!(evaluate_condition("index == 10", &mut resolve).unwrap());
+        assert!(!evaluate_condition("index != 10", &mut resolve).unwrap());
+        assert!(evaluate_condition("index >= 10", &mut resolve).unwrap());
+        assert!(!evaluate_condition("index > 10", &mut resolve).unwrap());
+        assert!(evaluate_condition("index < 0xb", &mut resolve).unwrap());
+        assert!(evaluate_condition("pc == 0x8000400", &mut resolve).unwrap());
+        assert!(evaluate_condition("name == \"probe\"", &mut resolve).unwrap());
+        assert!(evaluate_condition("index > -1.5", &mut resolve).unwrap());
+    }
+
+    #[test]
+    fn condition_combinations() {
+        assert!(evaluate_condition("flag", &mut resolve).unwrap());
+        assert!(evaluate_condition("index", &mut resolve).unwrap());
+        assert!(!evaluate_condition("index == 1 && flag", &mut resolve).unwrap());
+        assert!(evaluate_condition("index == 1 || flag", &mut resolve).unwrap());
+        assert!(evaluate_condition("index == 1 && flag || index == 10", &mut resolve).unwrap());
+    }
+
+    #[test]
+    fn condition_with_quoted_operators() {
+        let mut resolve = |expression: &str| match expression {
+            "text" => Some("\"a || b\"".to_string()),
+            other => resolve(other),
+        };
+
+        assert!(evaluate_condition("text == \"a || b\"", &mut resolve).unwrap());
+        assert!(!evaluate_condition("text == \"a && b\"", &mut resolve).unwrap());
+        assert!(evaluate_condition("text != \"a && b\" && index == 10", &mut resolve).unwrap());
+        assert!(evaluate_condition(
+            "index == 1 || name == \"pro||be\" || text == \"a || b\"",
+            &mut resolve
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn condition_errors() {
+        assert!(evaluate_condition("missing == 1", &mut resolve).is_err());
+        assert!(evaluate_condition("index ==", &mut resolve).is_err());
+        assert!(evaluate_condition("name", &mut resolve).is_err());
+    }
+
+    #[test]
+    fn hit_conditions() {
+        assert!(!hit_condition_is_met("3", 2).unwrap());
+        assert!(hit_condition_is_met("3", 3).unwrap());
+        assert!(hit_condition_is_met("3", 4).unwrap());
+        assert!(!hit_condition_is_met("== 3", 4).unwrap());
+        assert!(hit_condition_is_met("<3", 2).unwrap());
+        assert!(hit_condition_is_met("%2", 4).unwrap());
+        assert!(!hit_condition_is_met("%2", 5).unwrap());
+        assert!(hit_condition_is_met("%0", 1).is_err());
+        assert!(hit_condition_is_met("often", 1).is_err());
+    }
+
+    #[test]
+    fn log_messages() {
+        assert_eq!(
+            format_log_message("index={index}, name={ name }", &mut resolve),
+            "index=10, name=probe"
+        );
+        assert_eq!(
+            format_log_message("{{index}} {missing}", &mut resolve),
+            "{index} <`missing` is not a register or a variable in scope.>"
+        );
+    }
+}
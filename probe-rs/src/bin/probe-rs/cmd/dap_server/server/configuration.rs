@@ -23,6 +23,10 @@ pub struct SessionConfig {
     /// The target to be selected.
     pub(crate) chip: Option<String>,
 
+    /// Core dump created with `probe-rs dump`. Relative to `cwd`, or fully qualified.
+    /// If specified, the core dump is debugged instead of a target attached to a probe.
+    pub(crate) core_dump: Option<PathBuf>,
+
     /// Assert target's reset during connect
     #[serde(default)]
     pub(crate) connect_under_reset: bool,
@@ -41,6 +45,12 @@ pub struct SessionConfig {
     #[serde(default)]
     pub(crate) flashing_config: FlashingConfig,
 
+    /// Halt and resume all cores of the target together with the debugged core, for SMP targets.
+    ///
+    /// This requires support for halt groups, e.g. by the debug module of RISC-V targets.
+    #[serde(default)]
+    pub(crate) halt_all_cores: bool,
+
     /// Every core on the target has certain configuration.
     ///
     /// NOTE: Although we allow specifying multiple core configurations, this is a work in progress, and probe-rs-debugger currently only supports debugging a single core.
@@ -53,6 +63,24 @@ impl SessionConfig {
         // Update the `cwd`.
         self.cwd = self.resolve_cwd()?;
 
+        // Update the `core_dump` and validate that the file exists.
+        if self.core_dump.is_some() {
+            if self.flashing_config.flashing_enabled {
+                return Err(DebuggerError::Other(anyhow!(
+                    "Please do not use the `flashing_enabled` option when debugging a `core_dump`."
+                )));
+            }
+
+            let core_dump = get_absolute_path(self.cwd.clone(), self.core_dump.as_ref())?;
+            if !core_dump.is_file() {
+                return Err(DebuggerError::Other(anyhow!(
+                    "Core dump file {:?} not found.",
+                    core_dump
+                )));
+            }
+            self.core_dump = Some(core_dump);
+        }
+
         for target_core_config in &mut self.core_configs {
             // Update the `program_binary` and validate that the file exists.
             target_core_config.program_binary = match get_absolute_path(
@@ -93,6 +121,20 @@ impl SessionConfig {
                         None
                     }
                 };
+            // Update the `semihosting_root` and validate that the directory exists.
+            if target_core_config.semihosting_root.is_some() {
+                let semihosting_root = get_absolute_path(
+                    self.cwd.clone(),
+                    target_core_config.semihosting_root.as_ref(),
+                )?;
+                if !semihosting_root.is_dir() {
+                    return Err(DebuggerError::Other(anyhow!(
+                        "Semihosting root directory {:?} not found.",
+                        semihosting_root
+                    )));
+                }
+                target_core_config.semihosting_root = Some(semihosting_root);
+            }
         }
 
         Ok(())
@@ -168,6 +210,10 @@ pub struct FlashingConfig {
     #[serde(default)]
     pub(crate) restore_unwritten_bytes: bool,
 
+    /// Skip erasing and programming the sectors which already contain the data to be flashed
+    #[serde(default)]
+    pub(crate) skip_unchanged_sectors: bool,
+
     /// [`FormatOptions`] to control the flashing operation, depending on the type of binary ( [`probe_rs::flashing::Format`] ) to be flashed.
     #[serde(default)]
     pub(crate) format_options: FormatOptions,
@@ -187,6 +233,10 @@ pub struct CoreConfig {
     /// CMSIS-SVD file for the target. Relative to `cwd`, or fully qualified.
     pub(crate) svd_file: Option<PathBuf>,
 
+    /// Directory with the files the program may access through semihosting. Relative to `cwd`,
+    /// or fully qualified.
+    pub(crate) semihosting_root: Option<PathBuf>,
+
     #[serde(flatten)]
     pub(crate) rtt_config: rtt::RttConfig,
 }
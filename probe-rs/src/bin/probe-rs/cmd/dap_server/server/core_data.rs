@@ -1,6 +1,16 @@
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
 
-use super::session_data::{self, ActiveBreakpoint, BreakpointType, SourceLocationScope};
+use super::{
+    breakpoint_conditions::{evaluate_condition, format_log_message, hit_condition_is_met},
+    session_data::{
+        self, ActiveBreakpoint, BreakpointConditions, BreakpointType, SourceLocationScope,
+    },
+};
 use crate::cmd::dap_server::{
     debug_adapter::{
         dap::{
@@ -17,9 +27,10 @@ use crate::cmd::dap_server::{
 use crate::util::rtt::{self, ChannelMode, DataFormat, RttActiveTarget};
 use anyhow::{anyhow, Result};
 use probe_rs::{
-    debug::{debug_info::DebugInfo, ColumnType, VerifiedBreakpoint},
+    debug::{debug_info::DebugInfo, ColumnType, VariableName, VerifiedBreakpoint},
     rtt::{Rtt, ScanRegion},
-    Core, CoreStatus, Error, HaltReason,
+    semihosting::SemihostingHost,
+    BreakpointCause, Core, CoreStatus, Error, HaltReason, SemihostingCommand,
 };
 use time::UtcOffset;
 
@@ -41,6 +52,34 @@ pub struct CoreData {
     pub(crate) stack_frames: Vec<probe_rs::debug::stack_frame::StackFrame>,
     pub(crate) breakpoints: Vec<session_data::ActiveBreakpoint>,
     pub(crate) rtt_connection: Option<debug_rtt::RttConnection>,
+    pub(crate) semihosting: SemihostingHost,
+    /// The console output of [`CoreData::semihosting`], to be forwarded to the debug console.
+    pub(crate) semihosting_output: SemihostingOutput,
+}
+
+/// Collects the console output of semihosting requests.
+#[derive(Clone, Default)]
+pub(crate) struct SemihostingOutput(Arc<Mutex<Vec<u8>>>);
+
+impl SemihostingOutput {
+    /// Take all the output collected so far.
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl Write for SemihostingOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 /// [CoreHandle] provides handles to various data structures required to debug a single instance of a core. The actual state is stored in [session_data::SessionData].
@@ -88,6 +127,32 @@ impl<'p> CoreHandle<'p> {
                                     status
                                 );
                             }
+                            CoreStatus::Halted(HaltReason::Breakpoint(
+                                BreakpointCause::Semihosting(SemihostingCommand::Request {
+                                    operation,
+                                    parameter,
+                                }),
+                            )) => {
+                                // Semihosting requests are serviced and the core resumed, without notifying the client.
+                                self.core_data.semihosting.service(
+                                    &mut self.core,
+                                    operation,
+                                    parameter,
+                                )?;
+                                let output = self.core_data.semihosting_output.take();
+                                if !output.is_empty() {
+                                    debug_adapter.log_to_console(String::from_utf8_lossy(&output));
+                                }
+                                return Ok(CoreStatus::Running);
+                            }
+                            CoreStatus::Halted(HaltReason::Breakpoint(_))
+                                if !self.breakpoint_should_halt(debug_adapter) =>
+                            {
+                                // The conditions of the breakpoint were not met, or it is a logpoint, so we quietly resume.
+                                // The `last_known_status` is left unchanged, so the client will not be notified.
+                                self.core.run()?;
+                                return Ok(CoreStatus::Running);
+                            }
                             CoreStatus::Halted(_) => {
                                 // HaltReason::Step is a special case, where we have to send a custome event to the client that the core halted.
                                 // In this case, we don't re-send the "stopped" event, but further down, we will
@@ -235,11 +300,135 @@ impl<'p> CoreHandle<'p> {
             .find(|(_, breakpoint)| breakpoint.address == address)
     }
 
+    /// Decide if the core should remain halted at the breakpoint it just hit.
+    /// This evaluates the [`BreakpointConditions`] of the breakpoint, updates its hit count, and writes the message of a logpoint to the debug console.
+    /// If the conditions can not be evaluated, the core remains halted, and the reason is reported to the debug console.
+    fn breakpoint_should_halt<P: ProtocolAdapter>(
+        &mut self,
+        debug_adapter: &mut DebugAdapter<P>,
+    ) -> bool {
+        let Ok(program_counter) = self.core.read_core_reg::<u64>(self.core.program_counter())
+        else {
+            return true;
+        };
+        let Some((breakpoint_index, breakpoint)) = self.find_breakpoint_in_cache(program_counter)
+        else {
+            return true;
+        };
+        let conditions = breakpoint.conditions.clone();
+        if conditions == BreakpointConditions::default() {
+            return true;
+        }
+
+        if conditions.condition.is_some() || conditions.log_message.is_some() {
+            // The expressions are evaluated in the context of the top most stack frame.
+            match self
+                .core_data
+                .debug_info
+                .unwind(&mut self.core, program_counter)
+            {
+                Ok(stack_frames) => self.core_data.stack_frames = stack_frames,
+                Err(error) => {
+                    debug_adapter.log_to_console(format!(
+                        "Failed to unwind the stack to evaluate the breakpoint at {program_counter:#010x}: {error}"
+                    ));
+                    return true;
+                }
+            }
+        }
+
+        if let Some(condition) = &conditions.condition {
+            match evaluate_condition(condition, &mut |expression| {
+                self.breakpoint_expression_value(expression)
+            }) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(error) => {
+                    debug_adapter.log_to_console(format!(
+                        "Failed to evaluate the breakpoint condition `{condition}`: {error}"
+                    ));
+                    return true;
+                }
+            }
+        }
+
+        let breakpoint = &mut self.core_data.breakpoints[breakpoint_index];
+        breakpoint.hit_count += 1;
+        let hit_count = breakpoint.hit_count;
+
+        if let Some(hit_condition) = &conditions.hit_condition {
+            match hit_condition_is_met(hit_condition, hit_count) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(error) => {
+                    debug_adapter.log_to_console(format!("{error}"));
+                    return true;
+                }
+            }
+        }
+
+        if let Some(log_message) = &conditions.log_message {
+            let message = format_log_message(log_message, &mut |expression| {
+                self.breakpoint_expression_value(expression)
+            });
+            debug_adapter.log_to_console(message);
+            return false;
+        }
+
+        true
+    }
+
+    /// Get the value of a register, or of a local or static variable, in the top most stack frame.
+    fn breakpoint_expression_value(&mut self, expression: &str) -> Option<String> {
+        let stack_frame = self.core_data.stack_frames.first_mut()?;
+
+        // Always search the registers first, because we don't have a VariableCache for them.
+        if let Some(register_value) = stack_frame
+            .registers
+            .get_register_by_name(expression)
+            .and_then(|register| register.value)
+        {
+            return Some(format!("{register_value}"));
+        }
+
+        for search_cache in [
+            stack_frame.local_variables.as_mut(),
+            stack_frame.static_variables.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if search_cache.len() == 1 {
+                // The root of a scope doesn't have cached children by default, so we need to resolve them before we search.
+                let mut scope_root = search_cache.get_children(None).ok()?.into_iter().next()?;
+                self.core_data
+                    .debug_info
+                    .cache_deferred_variables(
+                        search_cache,
+                        &mut self.core,
+                        &mut scope_root,
+                        &stack_frame.registers,
+                        stack_frame.frame_base,
+                    )
+                    .ok()?;
+            }
+
+            if let Some(variable) =
+                search_cache.get_variable_by_name(&VariableName::Named(expression.to_string()))
+            {
+                return Some(variable.get_value(search_cache));
+            }
+        }
+
+        None
+    }
+
     /// Set a single breakpoint in target configuration as well as [`super::core_data::CoreHandle`]
     pub(crate) fn set_breakpoint(
         &mut self,
         address: u64,
         breakpoint_type: session_data::BreakpointType,
+        conditions: BreakpointConditions,
     ) -> Result<(), DebuggerError> {
         // NOTE: After receiving a DAP [`crate::debug_adapter::dap::dap_types::BreakpointEvent`], VSCode will mistakenly
         // identify a `InstructionBreakpoint` as a `SourceBreakpoint`. This results in breakpoints not being cleared correctly from [`CoreHandle::clear_breakpoints()`].
@@ -257,6 +446,8 @@ impl<'p> CoreHandle<'p> {
             .push(session_data::ActiveBreakpoint {
                 breakpoint_type,
                 address,
+                conditions,
+                hit_count: 0,
             });
         Ok(())
     }
@@ -310,6 +501,7 @@ impl<'p> CoreHandle<'p> {
         requested_breakpoint_line: u64,
         requested_breakpoint_column: Option<u64>,
         requested_source: &Source,
+        conditions: BreakpointConditions,
     ) -> Result<VerifiedBreakpoint, DebuggerError> {
         let VerifiedBreakpoint {
                  address,
@@ -329,6 +521,7 @@ impl<'p> CoreHandle<'p> {
                 source: requested_source.clone(),
                 location: SourceLocationScope::Specific(source_location.clone()),
             },
+            conditions,
         )?;
         Ok(VerifiedBreakpoint {
             address,
@@ -371,6 +564,7 @@ impl<'p> CoreHandle<'p> {
                                     ColumnType::Column(c) => c,
                                 }),
                                 &source,
+                                breakpoint.conditions.clone(),
                             )
                         })
                 {
@@ -11,7 +11,7 @@ use crate::cmd::dap_server::{
                 Capabilities, Event, ExitedEventBody, InitializeRequestArguments, MessageSeverity,
                 Request, RttWindowOpenedArguments, TerminatedEventBody,
             },
-            request_helpers::halt_core,
+            request_helpers::{exception_breakpoint_filters, halt_core},
         },
         protocol::ProtocolAdapter,
     },
@@ -155,6 +155,8 @@ impl Debugger {
                     | "setBreakpoint"
                     | "setBreakpoints"
                     | "setInstructionBreakpoints"
+                    | "setFunctionBreakpoints"
+                    | "setExceptionBreakpoints"
                     | "clearBreakpoint"
                     | "stackTrace"
                     | "threads"
@@ -254,6 +256,12 @@ impl Debugger {
                     "setInstructionBreakpoints" => {
                         debug_adapter.set_instruction_breakpoints(&mut target_core, &request)
                     }
+                    "setFunctionBreakpoints" => {
+                        debug_adapter.set_function_breakpoints(&mut target_core, &request)
+                    }
+                    "setExceptionBreakpoints" => {
+                        debug_adapter.set_exception_breakpoints(&mut target_core, &request)
+                    }
                     "stackTrace" => debug_adapter.stack_trace(&mut target_core, &request),
                     "scopes" => debug_adapter.scopes(&mut target_core, &request),
                     "disassemble" => debug_adapter.disassemble(&mut target_core, &request),
@@ -494,7 +502,9 @@ impl Debugger {
             };
         }
 
-        if requested_target_session_type == TargetSessionType::LaunchRequest {
+        if requested_target_session_type == TargetSessionType::LaunchRequest
+            && self.config.core_dump.is_none()
+        {
             // This will effectively do a `reset` and `halt` of the core, which is what we want until after the `configuration_done` request.
             debug_adapter
                 .restart(&mut target_core, None)
@@ -502,6 +512,7 @@ impl Debugger {
         } else {
             // Ensure ebreak enters debug mode, this is necessary for soft breakpoints to work on architectures like RISC-V.
             // For LaunchRequest, this is done in the `restart` above.
+            // A core dump can not be reset, so it is treated like an AttachRequest.
             target_core.core.debug_on_sw_breakpoint(true)?;
         }
 
@@ -594,6 +605,8 @@ impl Debugger {
         let mut download_options = DownloadOptions::default();
         download_options.keep_unwritten_bytes = self.config.flashing_config.restore_unwritten_bytes;
         download_options.do_chip_erase = self.config.flashing_config.full_chip_erase;
+        download_options.skip_unchanged_sectors =
+            self.config.flashing_config.skip_unchanged_sectors;
 
         let rc_debug_adapter = Rc::new(RefCell::new(debug_adapter));
         let rc_debug_adapter_clone = rc_debug_adapter.clone();
@@ -679,6 +692,12 @@ impl Debugger {
                             )
                             .ok();
                     }
+                    probe_rs::flashing::ProgressEvent::SectorSkipped { size } => {
+                        flash_progress.sector_size_done += size as usize;
+                    }
+                    probe_rs::flashing::ProgressEvent::PageSkipped { size } => {
+                        flash_progress.page_size_done += size as usize;
+                    }
                     probe_rs::flashing::ProgressEvent::FailedErasing => {
                         debug_adapter
                             .update_progress(Some(1.0), Some("Erasing Sectors Failed!"), id)
@@ -813,11 +832,14 @@ impl Debugger {
             supports_clipboard_context: Some(true),
             supports_disassemble_request: Some(true),
             supports_instruction_breakpoints: Some(true),
+            supports_conditional_breakpoints: Some(true),
+            supports_hit_conditional_breakpoints: Some(true),
+            supports_log_points: Some(true),
+            supports_function_breakpoints: Some(true),
+            exception_breakpoint_filters: Some(exception_breakpoint_filters()),
             supports_stepping_granularity: Some(true),
             supports_completions_request: Some(true),
             // supports_value_formatting_options: Some(true),
-            // supports_function_breakpoints: Some(true),
-            // TODO: Use DEMCR register to implement exception breakpoints
             // supports_exception_options: Some(true),
             // supports_exception_filter_options: Some (true),
             ..Default::default()
@@ -1,6 +1,6 @@
 use super::{
     configuration::{self, CoreConfig, SessionConfig},
-    core_data::{CoreData, CoreHandle},
+    core_data::{CoreData, CoreHandle, SemihostingOutput},
 };
 use crate::cmd::dap_server::{
     debug_adapter::{
@@ -12,7 +12,8 @@ use anyhow::{anyhow, Result};
 use probe_rs::{
     config::TargetSelector,
     debug::{debug_info::DebugInfo, SourceLocation},
-    CoreStatus, DebugProbeError, Permissions, Probe, ProbeCreationError, Session,
+    semihosting::SemihostingHost,
+    CoreDump, CoreStatus, DebugProbeError, Permissions, Probe, ProbeCreationError, Session,
 };
 use std::env::set_current_dir;
 use time::UtcOffset;
@@ -28,6 +29,10 @@ pub(crate) enum BreakpointType {
         source: Source,
         location: SourceLocationScope,
     },
+    /// A breakpoint was requested using a function name.
+    FunctionBreakpoint,
+    /// A breakpoint on a well-known function, e.g. the panic handler, as a result of a user selecting an exception breakpoint filter.
+    ExceptionBreakpoint,
 }
 
 /// Breakpoint requests will either be refer to a specific SourceLcoation, or unspecified, in which case it will refer to
@@ -38,11 +43,47 @@ pub(crate) enum SourceLocationScope {
     Specific(SourceLocation),
 }
 
+/// The optional conditions of a breakpoint, as requested by the DAP client.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct BreakpointConditions {
+    /// The core will only remain halted if this expression evaluates to true.
+    pub(crate) condition: Option<String>,
+    /// The core will only remain halted if the number of hits satisfies this expression, e.g. `>= 5` or `% 10`.
+    pub(crate) hit_condition: Option<String>,
+    /// If set, this is a logpoint. The message is written to the debug console, and the core resumes.
+    pub(crate) log_message: Option<String>,
+}
+
+impl BreakpointConditions {
+    /// Create the conditions from the fields of a DAP breakpoint request. Empty strings are ignored.
+    pub(crate) fn new(
+        condition: Option<&String>,
+        hit_condition: Option<&String>,
+        log_message: Option<&String>,
+    ) -> Self {
+        let non_empty = |value: Option<&String>| {
+            value
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| value.trim().to_string())
+        };
+        Self {
+            condition: non_empty(condition),
+            hit_condition: non_empty(hit_condition),
+            log_message: log_message
+                .filter(|value| !value.is_empty())
+                .map(String::to_string),
+        }
+    }
+}
+
 /// Provide the storage and methods to handle various [`BreakpointType`]
 #[derive(Clone, Debug)]
 pub(crate) struct ActiveBreakpoint {
     pub(crate) breakpoint_type: BreakpointType,
     pub(crate) address: u64,
+    pub(crate) conditions: BreakpointConditions,
+    /// The number of times the breakpoint was hit while its `condition` was true.
+    pub(crate) hit_count: u64,
 }
 
 /// SessionData is designed to be similar to [probe_rs::Session], in as much that it provides handles to the [CoreHandle] instances for each of the available [probe_rs::Core] involved in the debug session.
@@ -64,6 +105,107 @@ impl SessionData {
         config: &mut configuration::SessionConfig,
         timestamp_offset: UtcOffset,
     ) -> Result<Self, DebuggerError> {
+        let mut target_session = match &config.core_dump {
+            Some(core_dump) => {
+                let core_dump = CoreDump::load(core_dump).map_err(|error| {
+                    anyhow!("Failed to load the core dump {:?}: {:?}", core_dump, error)
+                })?;
+                Session::from_core_dump(core_dump)?
+            }
+            None => Self::attach_to_target(config)?,
+        };
+
+        // Change the current working directory if `config.cwd` is `Some(T)`.
+        if let Some(new_cwd) = config.cwd.clone() {
+            set_current_dir(new_cwd.as_path()).map_err(|err| {
+                anyhow!(
+                    "Failed to set current working directory to: {:?}, {:?}",
+                    new_cwd,
+                    err
+                )
+            })?;
+        };
+
+        // `FlashingConfig` probe level initialization.
+
+        // `CoreConfig` probe level initialization.
+        if config.core_configs.len() != 1 {
+            // TODO: For multi-core, allow > 1.
+            return Err(DebuggerError::Other(anyhow!("probe-rs-debugger requires that one, and only one, core  be configured for debugging.")));
+        }
+
+        // Filter `CoreConfig` entries based on those that match an actual core on the target probe.
+        let valid_core_configs = config
+            .core_configs
+            .iter()
+            .filter(|&core_config| {
+                target_session
+                    .list_cores()
+                    .iter()
+                    .any(|(target_core_index, _)| *target_core_index == core_config.core_index)
+            })
+            .cloned()
+            .collect::<Vec<CoreConfig>>();
+
+        if config.halt_all_cores {
+            let cores = target_session
+                .list_cores()
+                .into_iter()
+                .map(|(core_index, _)| core_index)
+                .collect::<Vec<_>>();
+
+            if !target_session.enable_halt_group(&cores)? {
+                tracing::warn!("The cores of the target can not be halted together.");
+            }
+        }
+
+        let mut core_data_vec = vec![];
+
+        for core_configuration in &valid_core_configs {
+            let semihosting_output = SemihostingOutput::default();
+            // The standard input of the debugger may be the connection to the client, so the
+            // program doesn't get any console input.
+            let mut semihosting = SemihostingHost::new()
+                .with_stdout(semihosting_output.clone())
+                .with_stdin(std::io::empty());
+            if let Some(program_binary) = core_configuration
+                .program_binary
+                .as_ref()
+                .and_then(|path| path.file_name())
+            {
+                semihosting = semihosting.with_command_line(program_binary.to_string_lossy());
+            }
+            if let Some(root) = &core_configuration.semihosting_root {
+                semihosting = semihosting.with_root(root);
+            }
+
+            core_data_vec.push(CoreData {
+                core_index: core_configuration.core_index,
+                last_known_status: CoreStatus::Unknown,
+                target_name: format!(
+                    "{}-{}",
+                    core_configuration.core_index,
+                    target_session.target().name
+                ),
+                debug_info: debug_info_from_binary(core_configuration)?,
+                core_peripherals: None,
+                stack_frames: Vec::<probe_rs::debug::stack_frame::StackFrame>::new(),
+                breakpoints: Vec::<ActiveBreakpoint>::new(),
+                rtt_connection: None,
+                semihosting,
+                semihosting_output,
+            })
+        }
+
+        Ok(SessionData {
+            session: target_session,
+            core_data: core_data_vec,
+            timestamp_offset,
+        })
+    }
+
+    /// Open the configured probe, and attach to the target.
+    fn attach_to_target(config: &SessionConfig) -> Result<Session, DebuggerError> {
         // `SessionConfig` Probe/Session level configurations initialization.
         let mut target_probe = match config.probe_selector.clone() {
             Some(selector) => Probe::open(selector.clone()).map_err(|e| match e {
@@ -133,62 +275,7 @@ impl SessionData {
                 .map_err(|err| anyhow!("Error attaching to the probe: {:?}.", err))?
         };
 
-        // Change the current working directory if `config.cwd` is `Some(T)`.
-        if let Some(new_cwd) = config.cwd.clone() {
-            set_current_dir(new_cwd.as_path()).map_err(|err| {
-                anyhow!(
-                    "Failed to set current working directory to: {:?}, {:?}",
-                    new_cwd,
-                    err
-                )
-            })?;
-        };
-
-        // `FlashingConfig` probe level initialization.
-
-        // `CoreConfig` probe level initialization.
-        if config.core_configs.len() != 1 {
-            // TODO: For multi-core, allow > 1.
-            return Err(DebuggerError::Other(anyhow!("probe-rs-debugger requires that one, and only one, core  be configured for debugging.")));
-        }
-
-        // Filter `CoreConfig` entries based on those that match an actual core on the target probe.
-        let valid_core_configs = config
-            .core_configs
-            .iter()
-            .filter(|&core_config| {
-                target_session
-                    .list_cores()
-                    .iter()
-                    .any(|(target_core_index, _)| *target_core_index == core_config.core_index)
-            })
-            .cloned()
-            .collect::<Vec<CoreConfig>>();
-
-        let mut core_data_vec = vec![];
-
-        for core_configuration in &valid_core_configs {
-            core_data_vec.push(CoreData {
-                core_index: core_configuration.core_index,
-                last_known_status: CoreStatus::Unknown,
-                target_name: format!(
-                    "{}-{}",
-                    core_configuration.core_index,
-                    target_session.target().name
-                ),
-                debug_info: debug_info_from_binary(core_configuration)?,
-                core_peripherals: None,
-                stack_frames: Vec::<probe_rs::debug::stack_frame::StackFrame>::new(),
-                breakpoints: Vec::<ActiveBreakpoint>::new(),
-                rtt_connection: None,
-            })
-        }
-
-        Ok(SessionData {
-            session: target_session,
-            core_data: core_data_vec,
-            timestamp_offset,
-        })
+        Ok(target_session)
     }
 
     /// Reload the a specific core's debug info from the binary file.
@@ -4,7 +4,8 @@ use std::{io::prelude::*, time::Duration};
 use anyhow::anyhow;
 use capstone::{
     arch::arm::ArchMode as armArchMode, arch::arm64::ArchMode as aarch64ArchMode,
-    arch::riscv::ArchMode as riscvArchMode, prelude::*, Capstone, Endian,
+    arch::mips::ArchMode as mipsArchMode, arch::riscv::ArchMode as riscvArchMode, prelude::*,
+    Capstone, Endian,
 };
 use num_traits::Num;
 use parse_int::parse;
@@ -16,7 +17,8 @@ use probe_rs::{
     debug::{
         debug_info::DebugInfo, registers::DebugRegisters, stack_frame::StackFrame, VariableName,
     },
-    Core, CoreRegister, CoreType, InstructionSet, MemoryInterface, RegisterId, RegisterValue,
+    Core, CoreDump, CoreRegister, CoreType, InstructionSet, MemoryInterface, RegisterId,
+    RegisterValue, Session,
 };
 use rustyline::DefaultEditor;
 
@@ -33,11 +35,18 @@ pub struct Cmd {
     #[clap(long, value_parser)]
     /// Binary to debug
     exe: Option<PathBuf>,
+
+    #[clap(long, value_parser)]
+    /// Core dump created with `probe-rs dump`, to debug instead of an attached target
+    core_dump: Option<PathBuf>,
 }
 
 impl Cmd {
     pub fn run(self) -> anyhow::Result<()> {
-        let (mut session, _probe_options) = self.common.simple_attach()?;
+        let mut session = match &self.core_dump {
+            Some(path) => Session::from_core_dump(CoreDump::load(path)?)?,
+            None => self.common.simple_attach()?.0,
+        };
 
         let di = self
             .exe
@@ -194,6 +203,30 @@ impl DebugCli {
                             capstone::arch::riscv::ArchExtraMode::RiscVC,
                         ))
                         .build(),
+                    InstructionSet::RV64 => Capstone::new()
+                        .riscv()
+                        .mode(riscvArchMode::RiscV64)
+                        .endian(Endian::Little)
+                        .build(),
+                    InstructionSet::RV64C => Capstone::new()
+                        .riscv()
+                        .mode(riscvArchMode::RiscV64)
+                        .endian(Endian::Little)
+                        .extra_mode(std::iter::once(
+                            capstone::arch::riscv::ArchExtraMode::RiscVC,
+                        ))
+                        .build(),
+                    InstructionSet::Mips32 => Capstone::new()
+                        .mips()
+                        .mode(mipsArchMode::Mips32)
+                        .endian(Endian::Little)
+                        .build(),
+                    InstructionSet::MicroMips => Capstone::new()
+                        .mips()
+                        .mode(mipsArchMode::Mips32)
+                        .endian(Endian::Little)
+                        .extra_mode(std::iter::once(capstone::arch::mips::ArchExtraMode::Micro))
+                        .build(),
                 }
                 .map_err(|err| anyhow!("Error creating capstone: {:?}", err))?;
 
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use probe_rs::CoreDump;
+
+use crate::util::common_options::ProbeOptions;
+
+#[derive(clap::Parser)]
+pub struct Cmd {
+    #[clap(flatten)]
+    common: ProbeOptions,
+
+    /// The file to store the core dump in
+    #[clap(value_parser)]
+    path: PathBuf,
+}
+
+impl Cmd {
+    pub fn run(self) -> anyhow::Result<()> {
+        let (mut session, _probe_options) = self.common.simple_attach()?;
+
+        let core_dump = CoreDump::capture(&mut session)?;
+        core_dump.store(&self.path)?;
+
+        println!("Core dump stored in {}", self.path.display());
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::Duration;
 
@@ -18,6 +19,10 @@ pub struct Cmd {
     )]
     reset_halt: bool,
 
+    /// Directory with the files the program may access through semihosting
+    #[clap(long)]
+    semihosting_root: Option<PathBuf>,
+
     #[clap(flatten)]
     common: ProbeOptions,
 }
@@ -36,10 +41,13 @@ impl Cmd {
             .gdb_connection_string
             .unwrap_or_else(|| "localhost:1337".to_string());
 
-        let instances = probe_rs::gdb_server::GdbInstanceConfiguration::from_session(
+        let mut instances = probe_rs::gdb_server::GdbInstanceConfiguration::from_session(
             &session,
             Some(gdb_connection_string),
         );
+        for instance in instances.iter_mut() {
+            instance.semihosting_root = self.semihosting_root.clone();
+        }
 
         for instance in instances.iter() {
             println!(
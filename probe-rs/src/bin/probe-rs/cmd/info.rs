@@ -13,6 +13,7 @@ use probe_rs::{
             sequences::DefaultArmSequence,
             ApAddress, ApInformation, ArmProbeInterface, DpAddress, MemoryApInformation, Register,
         },
+        mips::communication_interface::MipsCommunicationInterface,
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     MemoryMappedRegister, Probe, WireProtocol,
@@ -137,6 +138,32 @@ fn try_show_info(
             "Unable to debug RISC-V targets using the current probe. RISC-V specific information cannot be printed."
         );
         }
+
+        if probe.has_mips_interface() {
+            match probe.try_into_mips_interface() {
+                Ok(mut interface) => {
+                    if let Err(e) = show_mips_info(&mut interface) {
+                        log::warn!("Error showing MIPS chip information: {}", e);
+                    }
+
+                    probe = interface.close();
+                }
+                Err((interface_probe, e)) => {
+                    let mut source = Some(&e as &dyn Error);
+
+                    while let Some(parent) = source {
+                        log::error!("Error: {}", parent);
+                        source = parent.source();
+                    }
+
+                    probe = interface_probe;
+                }
+            }
+        } else {
+            println!(
+                "Unable to debug MIPS targets using the current probe. MIPS specific information cannot be printed."
+            );
+        }
     } else {
         tracing::info!("Debugging RISCV-Targets over SWD is not supported.");
     }
@@ -364,7 +391,9 @@ fn cpu_info_tree(scs: &mut Scs) -> Result<Tree<String>> {
 }
 
 fn show_riscv_info(interface: &mut RiscvCommunicationInterface) -> Result<()> {
-    let idcode = interface.read_idcode()?;
+    let chip_info = interface.read_chip_info()?;
+
+    let idcode = chip_info.jtag_idcode;
 
     let version = (idcode >> 28) & 0xf;
     let part_number = (idcode >> 12) & 0xffff;
@@ -380,6 +409,44 @@ fn show_riscv_info(interface: &mut RiscvCommunicationInterface) -> Result<()> {
     println!("\t Version:      {version}");
     println!("\t Part:         {part_number}");
     println!("\t Manufacturer: {manufacturer_id} ({jep_id})");
+    println!("\tDebug Module: {:?}", chip_info.debug_version);
+    println!("\t Harts:        {}", chip_info.num_harts);
+    println!("\t dscratch:     {}", chip_info.nscratch);
+
+    if let (Some(mvendorid), Some(marchid), Some(mimpid)) =
+        (chip_info.mvendorid, chip_info.marchid, chip_info.mimpid)
+    {
+        println!("\tHart 0:");
+        println!("\t mvendorid:    {mvendorid:#x}");
+        println!("\t marchid:      {marchid:#x}");
+        println!("\t mimpid:       {mimpid:#x}");
+    }
+
+    Ok(())
+}
+
+fn show_mips_info(interface: &mut MipsCommunicationInterface) -> Result<()> {
+    let idcode = interface.read_idcode()?;
+    let impcode = interface.read_impcode()?;
+
+    let version = (idcode >> 28) & 0xf;
+    let part_number = (idcode >> 12) & 0xffff;
+    let manufacturer_id = (idcode >> 1) & 0x7ff;
+
+    let jep_cc = (manufacturer_id >> 7) & 0xf;
+    let jep_id = manufacturer_id & 0x3f;
+
+    let jep_id = jep106::JEP106Code::new(jep_cc as u8, jep_id as u8);
+
+    let ejtag_version = (impcode >> 29) & 0x7;
+
+    println!("MIPS Chip:");
+    println!("\tIDCODE: {idcode:010x}");
+    println!("\t Version:      {version}");
+    println!("\t Part:         {part_number}");
+    println!("\t Manufacturer: {manufacturer_id} ({jep_id})");
+    println!("\tIMPCODE: {impcode:010x}");
+    println!("\t EJTAG version: {ejtag_version}");
 
     Ok(())
 }
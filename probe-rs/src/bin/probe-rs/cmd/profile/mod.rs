@@ -1,6 +1,9 @@
+mod output;
+
 use std::collections::HashMap;
 use std::fs::File;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::Context;
@@ -11,7 +14,9 @@ use probe_rs::{
         memory::PeripheralType,
         DpAddress, SwoConfig,
     },
+    debug::DebugInfo,
     flashing::{FileDownloadError, Format},
+    Core,
 };
 use time::Instant;
 
@@ -22,6 +27,7 @@ use addr2line::{
 };
 
 use crate::util::flash::run_flash_download;
+use output::{Frame, Stacks};
 use tracing::info;
 
 #[derive(clap::Parser)]
@@ -43,6 +49,15 @@ pub struct Cmd {
     /// Limit the number of entries to output
     #[clap(long, default_value_t = 25)]
     limit: usize,
+    /// The format of the profile.
+    ///
+    /// The call stack of each sample is only collected by the naive method. The ITM method
+    /// only samples the program counter, so its stacks only contain the sampled function.
+    #[clap(long, value_enum, default_value_t = OutputFormat::Top)]
+    output_format: OutputFormat,
+    /// Write the profile to the given file instead of stdout.
+    #[clap(long)]
+    output: Option<PathBuf>,
     /// Profile Method
     #[clap(subcommand)]
     method: ProfileMethod,
@@ -64,6 +79,19 @@ pub enum ProfileMethod {
     },
 }
 
+/// The format of the profile output.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The functions with the most samples.
+    Top,
+    /// Call stacks in the folded format, as used by `inferno` and `flamegraph.pl`.
+    Folded,
+    /// An SVG flamegraph of the call stacks.
+    Flamegraph,
+    /// A gzip compressed `pprof` protobuf profile, for use with `go tool pprof`.
+    Pprof,
+}
+
 impl core::fmt::Display for ProfileMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let s = format!("{:?}", self);
@@ -93,6 +121,10 @@ impl Cmd {
 
         let bytes = std::fs::read(&self.run.path)?;
         let symbols = Symbols::try_from(&bytes)?;
+        let debug_info = match self.output_format {
+            OutputFormat::Top => None,
+            _ => Some(DebugInfo::from_raw(&bytes)?),
+        };
 
         if self.flash {
             run_flash_download(
@@ -108,6 +140,7 @@ impl Cmd {
         let start = Instant::now();
         let mut reads = 0;
         let mut samples: HashMap<u32, u64> = HashMap::with_capacity(256 * (self.duration as usize));
+        let mut stacks = Stacks::new();
         let duration = Duration::from_secs(self.duration);
         info!("Profiling...");
 
@@ -122,6 +155,10 @@ impl Cmd {
                     core.halt(std::time::Duration::from_millis(10))?;
                     let pc: u32 = core.read_core_reg(pc_reg)?;
                     *samples.entry(pc).or_insert(1) += 1;
+                    if let Some(debug_info) = &debug_info {
+                        let stack = unwind(&mut core, debug_info, &symbols, pc)?;
+                        *stacks.entry(stack).or_insert(0) += 1;
+                    }
                     reads += 1;
                     core.run()?;
                     if Instant::now() - start > duration {
@@ -149,6 +186,9 @@ impl Cmd {
                 for packet in iter {
                     if let TracePacket::PCSample { pc: Some(pc) } = packet? {
                         *samples.entry(pc).or_insert(1) += 1;
+                        if debug_info.is_some() {
+                            *stacks.entry(vec![leaf_frame(&symbols, pc)]).or_insert(0) += 1;
+                        }
                         reads += 1;
                     }
                     if Instant::now() - start > duration {
@@ -158,11 +198,35 @@ impl Cmd {
             }
         }
 
+        let mut writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(
+                File::create(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?,
+            ),
+            None => Box::new(std::io::stdout()),
+        };
+
+        match self.output_format {
+            OutputFormat::Top => {}
+            OutputFormat::Folded => return Ok(output::write_folded(&stacks, writer)?),
+            OutputFormat::Flamegraph => {
+                let title = format!("{} ({reads} samples)", self.run.path);
+                return Ok(output::write_flamegraph(&stacks, &title, writer)?);
+            }
+            OutputFormat::Pprof => {
+                return Ok(output::write_pprof(
+                    &stacks,
+                    start.elapsed().unsigned_abs(),
+                    writer,
+                )?)
+            }
+        }
+
         let mut v = Vec::from_iter(samples);
         // sort by frequency
         v.sort_by(|&(_, a), &(_, b)| b.cmp(&a));
 
-        println!("Samples {}", reads);
+        writeln!(writer, "Samples {}", reads)?;
 
         for (address, count) in v.into_iter().take(self.limit) {
             let name = symbols
@@ -172,19 +236,68 @@ impl Cmd {
                 .get_location(address as u64)
                 .unwrap_or(("UNKNOWN".to_owned(), 0));
             if self.line_info {
-                println!("{}:{}", file, num);
+                writeln!(writer, "{}:{}", file, num)?;
             }
-            println!(
+            writeln!(
+                writer,
                 "{:>50} - {:.01}%",
                 name,
                 (count as f64 / reads as f64) * 100.0
-            );
+            )?;
         }
 
         Ok(())
     }
 }
 
+/// Collects the call stack of the halted core, starting with the innermost frame.
+fn unwind(
+    core: &mut Core,
+    debug_info: &DebugInfo,
+    symbols: &Symbols,
+    pc: u32,
+) -> anyhow::Result<Vec<Frame>> {
+    let frames = debug_info.unwind(core, pc as u64)?;
+
+    if frames.is_empty() {
+        return Ok(vec![leaf_frame(symbols, pc)]);
+    }
+
+    frames
+        .into_iter()
+        .map(|frame| {
+            let (file, line) = match frame.source_location {
+                Some(location) => (location.file, location.line),
+                None => (None, None),
+            };
+
+            Ok(Frame {
+                function: frame.function_name,
+                file,
+                line,
+                address: frame.pc.try_into()?,
+            })
+        })
+        .collect()
+}
+
+/// A frame for a sampled program counter, when the call stack is not known.
+fn leaf_frame(symbols: &Symbols, pc: u32) -> Frame {
+    let (file, line) = match symbols.get_location(pc as u64) {
+        Some((file, line)) => (Some(file), Some(line as u64)),
+        None => (None, None),
+    };
+
+    Frame {
+        function: symbols
+            .get_name(pc as u64)
+            .unwrap_or_else(|| format!("UNKNOWN - {:08X}", pc)),
+        file,
+        line,
+        address: pc as u64,
+    }
+}
+
 // Wrapper around addr2line that allows to look up function names
 pub(crate) struct Symbols<'sym> {
     file: ObjectFile<'sym, &'sym [u8]>,
@@ -0,0 +1,418 @@
+//! Writers for the collected call stacks, in formats understood by common profiling tools.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::time::Duration;
+
+use svg::{
+    node::element::{Group, Rectangle, Text, Title},
+    node::Text as Content,
+    Document, Node,
+};
+
+/// A frame of a sampled call stack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    /// The name of the function.
+    pub function: String,
+    /// The source file of the function, if known.
+    pub file: Option<String>,
+    /// The source line executed in the frame, if known.
+    pub line: Option<u64>,
+    /// The address executed in the frame.
+    pub address: u64,
+}
+
+/// The number of samples of each call stack. Stacks are ordered from the innermost frame.
+pub type Stacks = HashMap<Vec<Frame>, u64>;
+
+/// Write the stacks in the folded format, as used by `inferno` and `flamegraph.pl`.
+///
+/// Each line contains the function names from the outermost to the innermost frame,
+/// separated by `;`, followed by the number of samples.
+pub fn write_folded(stacks: &Stacks, mut writer: impl Write) -> std::io::Result<()> {
+    let mut lines = BTreeMap::new();
+
+    for (stack, count) in stacks {
+        let names: Vec<&str> = stack.iter().rev().map(|f| f.function.as_str()).collect();
+        *lines.entry(names.join(";")).or_insert(0) += count;
+    }
+
+    for (line, count) in lines {
+        writeln!(writer, "{line} {count}")?;
+    }
+
+    Ok(())
+}
+
+/// A node in the call tree of the flamegraph.
+#[derive(Default)]
+struct CallTree {
+    count: u64,
+    children: BTreeMap<String, CallTree>,
+}
+
+impl CallTree {
+    fn depth(&self) -> usize {
+        self.children
+            .values()
+            .map(|child| child.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+const FLAMEGRAPH_WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const FONT_SIZE: f64 = 12.0;
+/// Approximate width of a character, used to truncate names which do not fit into a frame.
+const CHARACTER_WIDTH: f64 = FONT_SIZE * 0.6;
+
+/// Write the stacks as an SVG flamegraph.
+pub fn write_flamegraph(stacks: &Stacks, title: &str, writer: impl Write) -> std::io::Result<()> {
+    let mut root = CallTree::default();
+
+    for (stack, count) in stacks {
+        root.count += count;
+
+        let mut node = &mut root;
+        for frame in stack.iter().rev() {
+            node = node.children.entry(frame.function.clone()).or_default();
+            node.count += count;
+        }
+    }
+
+    let depth = root.depth() + 1;
+    let height = FRAME_HEIGHT * (depth + 2) as f64;
+
+    let mut document = Document::new()
+        .set("viewBox", (0, 0, FLAMEGRAPH_WIDTH, height))
+        .set("width", FLAMEGRAPH_WIDTH)
+        .set("height", height)
+        .set("font-family", "Verdana, sans-serif")
+        .set("font-size", FONT_SIZE);
+
+    document.append(
+        Text::new()
+            .set("x", FLAMEGRAPH_WIDTH / 2.0)
+            .set("y", FRAME_HEIGHT)
+            .set("text-anchor", "middle")
+            .add(Content::new(title)),
+    );
+
+    // The root frame is drawn at the bottom, with the callees stacked on top.
+    let mut frames = Group::new();
+    draw_frame(
+        &mut frames,
+        "all",
+        &root,
+        root.count,
+        0.0,
+        height - FRAME_HEIGHT,
+    );
+    document.append(frames);
+
+    svg::write(writer, &document)
+}
+
+fn draw_frame(group: &mut Group, name: &str, node: &CallTree, total: u64, x: f64, y: f64) {
+    let width = FLAMEGRAPH_WIDTH * node.count as f64 / total as f64;
+
+    let percent = 100.0 * node.count as f64 / total as f64;
+
+    let mut frame = Group::new().add(Title::new().add(Content::new(format!(
+        "{name} ({} samples, {percent:.2}%)",
+        node.count
+    ))));
+
+    frame.append(
+        Rectangle::new()
+            .set("x", x)
+            .set("y", y)
+            .set("width", width)
+            .set("height", FRAME_HEIGHT - 1.0)
+            .set("fill", frame_color(name))
+            .set("rx", 2),
+    );
+
+    let characters = ((width - 6.0) / CHARACTER_WIDTH) as usize;
+    if characters >= 3 {
+        let label = if name.chars().count() > characters {
+            let truncated: String = name.chars().take(characters - 2).collect();
+            format!("{truncated}..")
+        } else {
+            name.to_string()
+        };
+
+        frame.append(
+            Text::new()
+                .set("x", x + 3.0)
+                .set("y", y + FRAME_HEIGHT - 4.0)
+                .add(Content::new(label)),
+        );
+    }
+
+    group.append(frame);
+
+    let mut child_x = x;
+    for (child_name, child) in &node.children {
+        draw_frame(group, child_name, child, total, child_x, y - FRAME_HEIGHT);
+        child_x += FLAMEGRAPH_WIDTH * child.count as f64 / total as f64;
+    }
+}
+
+/// A warm color derived from the function name, so a function has the same color everywhere.
+fn frame_color(name: &str) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    name.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let red = 205 + (hash % 50);
+    let green = (hash >> 8) % 230;
+    let blue = (hash >> 16) % 55;
+
+    format!("rgb({red},{green},{blue})")
+}
+
+/// Write the stacks as a gzip compressed `pprof` protobuf profile.
+pub fn write_pprof(stacks: &Stacks, duration: Duration, writer: impl Write) -> std::io::Result<()> {
+    let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    encoder.write_all(&encode_pprof(stacks, duration))?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Encode the stacks as a `perftools.profiles.Profile` message.
+fn encode_pprof(stacks: &Stacks, duration: Duration) -> Vec<u8> {
+    let mut strings = StringTable::default();
+    let mut functions: HashMap<(&str, Option<&str>), u64> = HashMap::new();
+    let mut locations: HashMap<&Frame, u64> = HashMap::new();
+
+    let mut profile = Vec::new();
+
+    // sample_type
+    let mut value_type = Vec::new();
+    encode_varint_field(&mut value_type, 1, strings.index("samples"));
+    encode_varint_field(&mut value_type, 2, strings.index("count"));
+    encode_bytes_field(&mut profile, 1, &value_type);
+
+    // Sort the stacks to get a reproducible output.
+    let mut sorted: Vec<_> = stacks.iter().collect();
+    sorted.sort_by_key(|(stack, _)| {
+        stack
+            .iter()
+            .map(|frame| (frame.address, frame.function.clone()))
+            .collect::<Vec<_>>()
+    });
+
+    let mut location_messages = Vec::new();
+    let mut function_messages = Vec::new();
+
+    for (stack, count) in sorted {
+        let mut location_ids = Vec::new();
+
+        for frame in stack {
+            let next_id = locations.len() as u64 + 1;
+            let id = *locations.entry(frame).or_insert_with(|| {
+                let function_key = (frame.function.as_str(), frame.file.as_deref());
+                let next_function_id = functions.len() as u64 + 1;
+                let function_id = *functions.entry(function_key).or_insert_with(|| {
+                    let mut function = Vec::new();
+                    encode_varint_field(&mut function, 1, next_function_id);
+                    encode_varint_field(&mut function, 2, strings.index(&frame.function));
+                    encode_varint_field(&mut function, 3, strings.index(&frame.function));
+                    if let Some(file) = &frame.file {
+                        encode_varint_field(&mut function, 4, strings.index(file));
+                    }
+                    function_messages.push(function);
+                    next_function_id
+                });
+
+                let mut line = Vec::new();
+                encode_varint_field(&mut line, 1, function_id);
+                if let Some(number) = frame.line {
+                    encode_varint_field(&mut line, 2, number);
+                }
+
+                let mut location = Vec::new();
+                encode_varint_field(&mut location, 1, next_id);
+                encode_varint_field(&mut location, 3, frame.address);
+                encode_bytes_field(&mut location, 4, &line);
+                location_messages.push(location);
+
+                next_id
+            });
+            location_ids.push(id);
+        }
+
+        let mut sample = Vec::new();
+        encode_packed_field(&mut sample, 1, &location_ids);
+        encode_packed_field(&mut sample, 2, &[*count]);
+        encode_bytes_field(&mut profile, 2, &sample);
+    }
+
+    for location in location_messages {
+        encode_bytes_field(&mut profile, 4, &location);
+    }
+    for function in function_messages {
+        encode_bytes_field(&mut profile, 5, &function);
+    }
+    for string in &strings.strings {
+        encode_bytes_field(&mut profile, 6, string.as_bytes());
+    }
+
+    encode_varint_field(&mut profile, 10, duration.as_nanos() as u64);
+
+    profile
+}
+
+/// The string table of a pprof profile, where the first entry is always the empty string.
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl Default for StringTable {
+    fn default() -> Self {
+        Self {
+            strings: vec![String::new()],
+            indices: HashMap::from([(String::new(), 0)]),
+        }
+    }
+}
+
+impl StringTable {
+    fn index(&mut self, string: &str) -> u64 {
+        if let Some(index) = self.indices.get(string) {
+            return *index;
+        }
+
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        index
+    }
+}
+
+const WIRE_TYPE_VARINT: u64 = 0;
+const WIRE_TYPE_LENGTH_DELIMITED: u64 = 2;
+
+fn encode_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn encode_varint_field(buffer: &mut Vec<u8>, field: u64, value: u64) {
+    encode_varint(buffer, field << 3 | WIRE_TYPE_VARINT);
+    encode_varint(buffer, value);
+}
+
+fn encode_bytes_field(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_varint(buffer, field << 3 | WIRE_TYPE_LENGTH_DELIMITED);
+    encode_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+fn encode_packed_field(buffer: &mut Vec<u8>, field: u64, values: &[u64]) {
+    let mut packed = Vec::new();
+    for value in values {
+        encode_varint(&mut packed, *value);
+    }
+    encode_bytes_field(buffer, field, &packed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(function: &str, address: u64) -> Frame {
+        Frame {
+            function: function.to_string(),
+            file: Some("main.rs".to_string()),
+            line: Some(address),
+            address,
+        }
+    }
+
+    fn stacks() -> Stacks {
+        HashMap::from([
+            (vec![frame("leaf", 0x10), frame("main", 0x100)], 3),
+            (vec![frame("other", 0x20), frame("main", 0x104)], 1),
+            (vec![frame("main", 0x108)], 2),
+        ])
+    }
+
+    #[test]
+    fn folded_stacks_start_with_the_outermost_frame() {
+        let mut output = Vec::new();
+        write_folded(&stacks(), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "main 2\nmain;leaf 3\nmain;other 1\n"
+        );
+    }
+
+    #[test]
+    fn flamegraph_contains_all_functions() {
+        let mut output = Vec::new();
+        write_flamegraph(&stacks(), "test", &mut output).unwrap();
+
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.contains("main (6 samples, 100.00%)"));
+        assert!(svg.contains("leaf (3 samples, 50.00%)"));
+        assert!(svg.contains("other (1 samples, 16.67%)"));
+    }
+
+    #[test]
+    fn pprof_deduplicates_locations_and_functions() {
+        let profile = encode_pprof(&stacks(), Duration::from_secs(1));
+
+        // Samples are sorted by the address of the innermost frame.
+        let first_sample = [
+            0x12, 0x07, // sample, 7 bytes
+            0x0A, 0x02, 0x01, 0x02, // location ids 1, 2
+            0x12, 0x01, 0x03, // 3 samples
+        ];
+        let position = profile
+            .windows(first_sample.len())
+            .position(|window| window == first_sample);
+        assert!(position.is_some());
+
+        // 5 distinct locations, 3 distinct functions.
+        let count_fields = |key: u8| {
+            let mut count = 0;
+            let mut offset = 0;
+            while offset < profile.len() {
+                let field = profile[offset];
+                let wire_type = field & 0x7;
+                offset += 1;
+                let mut value = 0u64;
+                let mut shift = 0;
+                loop {
+                    let byte = profile[offset];
+                    offset += 1;
+                    value |= ((byte & 0x7F) as u64) << shift;
+                    shift += 7;
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+                if wire_type == 2 {
+                    offset += value as usize;
+                }
+                if field == key {
+                    count += 1;
+                }
+            }
+            count
+        };
+
+        assert_eq!(count_fields(0x22), 5);
+        assert_eq!(count_fields(0x2A), 3);
+    }
+}
@@ -0,0 +1,23 @@
+use probe_rs::flashing::protect;
+
+use crate::util::common_options::ProbeOptions;
+
+#[derive(clap::Parser)]
+pub struct Cmd {
+    #[clap(flatten)]
+    common: ProbeOptions,
+
+    /// The device specific protection level
+    #[clap(long, default_value = "1")]
+    level: u32,
+}
+
+impl Cmd {
+    pub fn run(self) -> anyhow::Result<()> {
+        let (mut session, _probe_options) = self.common.simple_attach()?;
+
+        protect(&mut session, self.level, None)?;
+
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ use crate::CoreOptions;
 /// Output is a space separated list of hex values padded to the read word width.
 /// e.g. 2 words
 ///     00 00 (8-bit)
+///     0000 0000 (16-bit)
 ///     00000000 00000000 (32-bit)
 ///     0000000000000000 0000000000000000 (64-bit)
 ///
@@ -46,6 +47,14 @@ impl Cmd {
                 }
                 println!();
             }
+            ReadWriteBitWidth::B16 => {
+                let mut values = vec![0; words];
+                core.read_16(self.read_write_options.address, &mut values)?;
+                for val in values {
+                    print!("{:04x} ", val);
+                }
+                println!();
+            }
             ReadWriteBitWidth::B32 => {
                 let mut values = vec![0; words];
                 core.read_32(self.read_write_options.address, &mut values)?;
@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::Write;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -9,6 +9,7 @@ use std::time::Duration;
 use anyhow::{anyhow, Context, Result};
 use probe_rs::debug::DebugInfo;
 use probe_rs::flashing::{FileDownloadError, Format};
+use probe_rs::semihosting::SemihostingHost;
 use probe_rs::{BreakpointCause, Core, HaltReason, SemihostingCommand, VectorCatchCondition};
 use probe_rs_target::MemoryRegion;
 use signal_hook::consts::signal;
@@ -44,6 +45,15 @@ pub struct Cmd {
     #[clap(long)]
     pub(crate) no_location: bool,
 
+    /// Directory with the files the program may access through semihosting
+    #[clap(long)]
+    pub(crate) semihosting_root: Option<PathBuf>,
+
+    /// Command line passed to the program through semihosting. Defaults to the file name of the
+    /// program
+    #[clap(long)]
+    pub(crate) semihosting_cmdline: Option<String>,
+
     #[clap(flatten)]
     pub(crate) format_options: FormatOptions,
 }
@@ -94,6 +104,16 @@ impl Cmd {
             core.run()?;
         }
 
+        let command_line = self.semihosting_cmdline.unwrap_or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let mut semihosting = SemihostingHost::new().with_command_line(command_line);
+        if let Some(root) = self.semihosting_root {
+            semihosting = semihosting.with_root(root);
+        }
+
         run_loop(
             &mut core,
             &memory_map,
@@ -102,6 +122,7 @@ impl Cmd {
             timestamp_offset,
             self.always_print_stacktrace,
             self.no_location,
+            &mut semihosting,
         )?;
 
         Ok(())
@@ -109,9 +130,11 @@ impl Cmd {
 }
 
 /// Print all RTT messsages and a stacktrace when the core stops due to an
-/// exception or when ctrl + c is pressed.
+/// exception or when ctrl + c is pressed. Semihosting requests of the core
+/// are serviced by `semihosting`.
 ///
 /// Returns `Ok(())` if the core gracefully halted, or an error.
+#[allow(clippy::too_many_arguments)]
 fn run_loop(
     core: &mut Core<'_>,
     memory_map: &[MemoryRegion],
@@ -120,6 +143,7 @@ fn run_loop(
     timestamp_offset: UtcOffset,
     always_print_stacktrace: bool,
     no_location: bool,
+    semihosting: &mut SemihostingHost,
 ) -> Result<(), anyhow::Error> {
     let mut rtt_config = rtt::RttConfig::default();
     rtt_config.channels.push(rtt::RttChannelConfig {
@@ -143,16 +167,18 @@ fn run_loop(
     let mut stdout = std::io::stdout();
     while !exit.load(Ordering::Relaxed) {
         let had_rtt_data = poll_rtt(&mut rtta, core, &mut stdout)?;
-        if poll_stacktrace(core, path)? {
-            return Ok(());
-        }
+        let had_semihosting_request = match poll_stacktrace(core, path, semihosting)? {
+            PollResult::Exited => return Ok(()),
+            PollResult::Semihosting => true,
+            PollResult::Running => false,
+        };
 
         // Poll RTT with a frequency of 10 Hz if we do not receive any new data.
         // Once we receive new data, we bump the frequency to 1kHz.
         //
         // If the polling frequency is too high, the USB connection to the probe
         // can become unstable. Hence we only pull as little as necessary.
-        if had_rtt_data {
+        if had_rtt_data || had_semihosting_request {
             std::thread::sleep(Duration::from_millis(1));
         } else {
             std::thread::sleep(Duration::from_millis(100));
@@ -163,7 +189,7 @@ fn run_loop(
     if manually_halted {
         core.halt(Duration::from_secs(1))?;
         if always_print_stacktrace {
-            poll_stacktrace(core, path)?;
+            poll_stacktrace(core, path, semihosting)?;
         }
     }
 
@@ -172,23 +198,45 @@ fn run_loop(
     Ok(())
 }
 
+/// The outcome of polling the core status.
+enum PollResult {
+    /// The program exited, and the debugger should stop polling.
+    Exited,
+    /// A semihosting request was serviced, and the core resumed.
+    Semihosting,
+    /// The core is still running.
+    Running,
+}
+
 /// Try to fetch the necessary data of the core to print its stacktrace.
 ///
-/// Returns `Ok(true)` if the debuger should stop polling, or `Ok(false)` if the
-/// polling should continue, or an error.
-fn poll_stacktrace(core: &mut Core<'_>, path: &Path) -> Result<bool> {
+/// Returns whether the debugger should continue polling, or an error.
+fn poll_stacktrace(
+    core: &mut Core<'_>,
+    path: &Path,
+    semihosting: &mut SemihostingHost,
+) -> Result<PollResult> {
     let status = core.status()?;
     let registers = core.registers();
     let pc_register = registers.pc().expect("a program counter register");
     match status {
         probe_rs::CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
             SemihostingCommand::ExitSuccess,
-        ))) => Ok(true),
+        ))) => Ok(PollResult::Exited),
         probe_rs::CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
             SemihostingCommand::ExitError { code },
         ))) => Err(anyhow!(
             "Semihosting indicates exit with failure code: {code:#08x} ({code})"
         )),
+        probe_rs::CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+            SemihostingCommand::Request {
+                operation,
+                parameter,
+            },
+        ))) => {
+            semihosting.service(core, operation, parameter)?;
+            Ok(PollResult::Semihosting)
+        }
         probe_rs::CoreStatus::Halted(_reason) => {
             // Try and give the user some info as to why it halted.
             print_stacktrace(core, pc_register, path)?;
@@ -200,7 +248,7 @@ fn poll_stacktrace(core: &mut Core<'_>, path: &Path) -> Result<bool> {
         | probe_rs::CoreStatus::Sleeping
         | probe_rs::CoreStatus::Unknown => {
             // Carry on
-            Ok(false)
+            Ok(PollResult::Running)
         }
     }
 }
@@ -259,11 +307,11 @@ fn print_stacktrace(
     Ok(())
 }
 
-/// Poll RTT and print the received buffer.
-fn poll_rtt(
+/// Poll RTT and write the received buffer to `output`.
+pub(crate) fn poll_rtt(
     rtta: &mut Option<rtt::RttActiveTarget>,
     core: &mut Core<'_>,
-    stdout: &mut std::io::Stdout,
+    output: &mut impl Write,
 ) -> Result<bool, anyhow::Error> {
     let mut had_data = false;
     if let Some(rtta) = rtta {
@@ -271,7 +319,7 @@ fn poll_rtt(
             if !data.is_empty() {
                 had_data = true;
             }
-            stdout.write_all(data.as_bytes())?;
+            output.write_all(data.as_bytes())?;
         }
     };
     Ok(had_data)
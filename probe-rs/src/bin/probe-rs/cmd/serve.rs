@@ -0,0 +1,34 @@
+use probe_rs::{Probe, ProbeServer};
+
+#[derive(clap::Parser)]
+pub struct Cmd {
+    /// The address to listen on for clients
+    ///
+    /// There is no authentication, anyone who can reach this address can use the attached probes.
+    /// Use e.g. `0.0.0.0:4242` to make the probes available to other machines.
+    #[clap(long, default_value = "127.0.0.1:4242")]
+    address: String,
+}
+
+impl Cmd {
+    pub fn run(self) -> anyhow::Result<()> {
+        let server = ProbeServer::bind(&self.address)?;
+        let address = server.local_addr()?;
+
+        let probes = Probe::list_all();
+        if probes.is_empty() {
+            println!("No debug probes were found, they can still be attached later.");
+        } else {
+            println!("Serving the following debug probes:");
+            for probe in &probes {
+                let serial = probe.serial_number.as_deref().unwrap_or("");
+                println!("  tcp://{address}/{serial} - {probe:?}");
+            }
+        }
+        println!("Listening on {address}");
+
+        server.run()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,530 @@
+mod report;
+
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use object::{Object, ObjectSection, ObjectSymbol};
+use probe_rs::semihosting::SemihostingHost;
+use probe_rs::{
+    BreakpointCause, Core, CoreStatus, HaltReason, SemihostingCommand, VectorCatchCondition,
+};
+use probe_rs_target::MemoryRegion;
+use signal_hook::consts::signal;
+use time::UtcOffset;
+
+use super::run::poll_rtt;
+use crate::util::common_options::{BinaryDownloadOptions, ProbeOptions};
+use crate::util::flash::run_flash_download;
+use crate::util::rtt::{self, RttActiveTarget, RttConfig};
+use report::{ConsoleFormat, ConsoleReporter, TestOutcome, TestResult};
+
+/// ELF section listing the tests of a program, as NUL-terminated names.
+const TEST_SECTION: &str = ".probe_rs_tests";
+
+/// Prefix of the symbols naming the tests of a program without a test section.
+const TEST_SYMBOL_PREFIX: &str = "__probe_rs_test_";
+
+/// Run the tests of a firmware on the target, one test at a time.
+///
+/// The tests are listed in the `.probe_rs_tests` section of the ELF file, as NUL-terminated
+/// names. Without this section, every symbol named `__probe_rs_test_<name>` is a test.
+///
+/// For each test, the target is reset and the program is started with the test name as its
+/// semihosting command line, after the program name. The test passes when the program exits
+/// successfully through semihosting, and fails when it exits with an error, halts for another
+/// reason or times out. The output of the test is collected from RTT and semihosting.
+#[derive(clap::Parser)]
+pub struct Cmd {
+    #[clap(flatten)]
+    probe_options: ProbeOptions,
+
+    #[clap(flatten)]
+    download_options: BinaryDownloadOptions,
+
+    /// The path to the ELF file with the tests
+    path: String,
+
+    /// Only run the tests whose name contains this string
+    filter: Option<String>,
+
+    /// Only run the test whose name is exactly the filter
+    #[clap(long)]
+    exact: bool,
+
+    /// Skip the tests whose name contains this string. Can be given multiple times
+    #[clap(long)]
+    skip: Vec<String>,
+
+    /// List the tests instead of running them
+    #[clap(long)]
+    list: bool,
+
+    /// The format of the test report printed to stdout
+    #[clap(long, value_enum, default_value_t = OutputFormat::Pretty)]
+    format: OutputFormat,
+
+    /// Print one character per test, same as `--format terse`
+    #[clap(short, long)]
+    quiet: bool,
+
+    /// Print the output of the tests while they run, instead of only for failed tests
+    #[clap(long)]
+    nocapture: bool,
+
+    /// Additionally write a JUnit XML report to the given file
+    #[clap(long)]
+    junit: Option<PathBuf>,
+
+    /// The maximum duration of each test in seconds
+    #[clap(long, default_value_t = 60)]
+    timeout: u64,
+
+    /// Whether to erase the entire chip before downloading
+    #[clap(long)]
+    chip_erase: bool,
+
+    /// Suppress filename and line number information from the rtt log
+    #[clap(long)]
+    no_location: bool,
+
+    /// Directory with the files the tests may access through semihosting
+    #[clap(long)]
+    semihosting_root: Option<PathBuf>,
+
+    /// Accepted for compatibility with libtest. Tests always run one at a time.
+    #[clap(long, hide = true)]
+    test_threads: Option<usize>,
+
+    /// Accepted for compatibility with libtest.
+    #[clap(long, hide = true)]
+    color: Option<String>,
+
+    /// Accepted for compatibility with libtest.
+    #[clap(short = 'Z', hide = true)]
+    unstable_options: Vec<String>,
+}
+
+/// The format of the test report printed to stdout.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// One line per test, like libtest
+    Pretty,
+    /// One character per test, like libtest
+    Terse,
+    /// A JUnit XML report
+    Junit,
+}
+
+/// Everything needed to run a single test.
+struct TestContext<'a> {
+    path: &'a Path,
+    program_name: String,
+    memory_map: Vec<MemoryRegion>,
+    rtt_scan_regions: Vec<Range<u64>>,
+    has_rtt: bool,
+    no_location: bool,
+    timestamp_offset: UtcOffset,
+    semihosting_root: Option<PathBuf>,
+    timeout: Duration,
+    echo: bool,
+}
+
+impl Cmd {
+    pub fn run(self, timestamp_offset: UtcOffset) -> Result<()> {
+        let path = Path::new(&self.path);
+        let elf = std::fs::read(path).context("Failed to open binary file.")?;
+
+        let all_tests = find_tests(&elf)?;
+        let tests: Vec<String> = all_tests
+            .iter()
+            .filter(|test| self.is_selected(test))
+            .cloned()
+            .collect();
+        let filtered_out = all_tests.len() - tests.len();
+
+        let console_format = match (self.format, self.quiet) {
+            (OutputFormat::Junit, _) => None,
+            (_, true) | (OutputFormat::Terse, false) => Some(ConsoleFormat::Terse),
+            (OutputFormat::Pretty, false) => Some(ConsoleFormat::Pretty),
+        };
+
+        if self.list {
+            let format = console_format.unwrap_or(ConsoleFormat::Terse);
+            report::write_list(&tests, format, std::io::stdout())?;
+            return Ok(());
+        }
+
+        let (mut session, probe_options) = self.probe_options.simple_attach()?;
+
+        let mut loader = session.target().flash_loader();
+        loader.load_elf_data(&mut elf.as_slice())?;
+        run_flash_download(
+            &mut session,
+            path,
+            &self.download_options,
+            &probe_options,
+            loader,
+            self.chip_erase,
+        )?;
+
+        let program_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let context = TestContext {
+            path,
+            program_name,
+            memory_map: session.target().memory_map.clone(),
+            rtt_scan_regions: session.target().rtt_scan_regions.clone(),
+            has_rtt: RttActiveTarget::get_rtt_symbol(&mut std::io::Cursor::new(&elf)).is_some(),
+            no_location: self.no_location,
+            timestamp_offset,
+            semihosting_root: self.semihosting_root,
+            timeout: Duration::from_secs(self.timeout),
+            // Echoed output would end up in the JUnit report printed to stdout.
+            echo: self.nocapture && console_format.is_some(),
+        };
+
+        let mut core = session.core(0)?;
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let sig_id = signal_hook::flag::register(signal::SIGINT, interrupted.clone())?;
+
+        let reporter = console_format.map(ConsoleReporter::new);
+        let mut stdout = std::io::stdout();
+        if let Some(reporter) = &reporter {
+            reporter.start(tests.len(), &mut stdout)?;
+        }
+
+        let start = Instant::now();
+        let mut results = Vec::with_capacity(tests.len());
+        for test in &tests {
+            if let Some(reporter) = &reporter {
+                reporter.test_started(test, &mut stdout)?;
+            }
+
+            let test_start = Instant::now();
+            let result =
+                run_test(&mut core, test, &context, &interrupted).unwrap_or_else(|error| {
+                    // The next test resets the target, which may recover from the error.
+                    TestResult {
+                        name: test.to_string(),
+                        outcome: TestOutcome::Error(format!("{error:#}")),
+                        duration: test_start.elapsed(),
+                        output: String::new(),
+                    }
+                });
+
+            if let Some(reporter) = &reporter {
+                reporter.test_finished(&result, &mut stdout)?;
+            }
+            results.push(result);
+
+            if interrupted.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        let duration = start.elapsed();
+
+        signal_hook::low_level::unregister(sig_id);
+        signal_hook::flag::register_conditional_default(signal::SIGINT, interrupted)?;
+
+        // Write the JUnit file even if the report can't be written to stdout.
+        let console_report = match &reporter {
+            Some(reporter) => reporter.finish(&results, filtered_out, duration, &mut stdout),
+            None => report::write_junit(&suite_name(path), &results, duration, &mut stdout),
+        };
+
+        if let Some(junit) = &self.junit {
+            let file = std::fs::File::create(junit)
+                .with_context(|| format!("Failed to create {}", junit.display()))?;
+            report::write_junit(&suite_name(path), &results, duration, file)?;
+        }
+        console_report?;
+
+        let failed = results.iter().filter(|r| !r.outcome.passed()).count();
+        if failed > 0 {
+            anyhow::bail!("{failed} of {} tests failed", results.len());
+        }
+        if results.len() < tests.len() {
+            anyhow::bail!("The test run was interrupted");
+        }
+
+        Ok(())
+    }
+
+    /// Whether the test is selected by the filter and skip options, like in libtest.
+    fn is_selected(&self, test: &str) -> bool {
+        let matches = |pattern: &str| {
+            if self.exact {
+                test == pattern
+            } else {
+                test.contains(pattern)
+            }
+        };
+
+        let selected = match &self.filter {
+            Some(filter) => matches(filter),
+            None => true,
+        };
+
+        selected && !self.skip.iter().any(|pattern| matches(pattern))
+    }
+}
+
+/// Reset the target and run a single test, until it exits, halts or times out.
+fn run_test(
+    core: &mut Core<'_>,
+    test: &str,
+    context: &TestContext<'_>,
+    interrupted: &AtomicBool,
+) -> Result<TestResult> {
+    let mut output = TestOutput::new(context.echo);
+
+    let mut semihosting = SemihostingHost::new()
+        .with_command_line(format!("{} {test}", context.program_name))
+        .with_stdout(output.clone())
+        .with_stdin(std::io::empty());
+    if let Some(root) = &context.semihosting_root {
+        semihosting = semihosting.with_root(root);
+    }
+
+    let mut rtt_config = RttConfig::default();
+    rtt_config.channels.push(rtt::RttChannelConfig {
+        channel_number: Some(0),
+        show_location: !context.no_location,
+        ..Default::default()
+    });
+
+    core.reset_and_halt(Duration::from_millis(100))?;
+    if let Err(e) = core.enable_vector_catch(VectorCatchCondition::All) {
+        tracing::warn!("Failed to enable_vector_catch: {:?}", e);
+    }
+    core.run()?;
+
+    let start = Instant::now();
+    let mut rtta = None;
+    let outcome = loop {
+        // The program sets up the RTT control block after the reset, so keep trying to attach.
+        if rtta.is_none() && context.has_rtt {
+            rtta = rtt::attach_to_rtt(
+                core,
+                &context.memory_map,
+                &context.rtt_scan_regions,
+                context.path,
+                &rtt_config,
+                context.timestamp_offset,
+            )
+            .ok();
+        }
+
+        let mut busy = poll_rtt(&mut rtta, core, &mut output)?;
+
+        match core.status()? {
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(command))) => {
+                match command {
+                    SemihostingCommand::ExitSuccess => break TestOutcome::Passed,
+                    SemihostingCommand::ExitError { code } => {
+                        break TestOutcome::Failed(format!("exited with code {code:#x} ({code})"))
+                    }
+                    SemihostingCommand::Request {
+                        operation,
+                        parameter,
+                    } => {
+                        semihosting.service(core, operation, parameter)?;
+                        busy = true;
+                    }
+                }
+            }
+            CoreStatus::Halted(reason) => {
+                let pc: u64 = core.read_core_reg(core.program_counter())?;
+                break TestOutcome::Failed(format!(
+                    "core halted unexpectedly ({reason:?}) at {pc:#010x}"
+                ));
+            }
+            _ => {}
+        }
+
+        if interrupted.load(Ordering::Relaxed) {
+            core.halt(Duration::from_millis(100))?;
+            break TestOutcome::Failed("interrupted".to_string());
+        }
+
+        if start.elapsed() > context.timeout {
+            core.halt(Duration::from_millis(100))?;
+            break TestOutcome::TimedOut(context.timeout);
+        }
+
+        // Poll quickly while the test produces output or makes semihosting calls, but not so
+        // often that the USB connection to the probe becomes unstable.
+        if busy {
+            std::thread::sleep(Duration::from_millis(1));
+        } else {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+    let duration = start.elapsed();
+
+    // Collect the output written right before the test ended.
+    poll_rtt(&mut rtta, core, &mut output)?;
+
+    Ok(TestResult {
+        name: test.to_string(),
+        outcome,
+        duration,
+        output: output.into_string(),
+    })
+}
+
+/// Collects the output of a test, and optionally prints it as it is received.
+#[derive(Clone)]
+struct TestOutput {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    echo: bool,
+}
+
+impl TestOutput {
+    fn new(echo: bool) -> Self {
+        Self {
+            buffer: Arc::default(),
+            echo,
+        }
+    }
+
+    fn into_string(self) -> String {
+        let buffer = self.buffer.lock().unwrap();
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+impl Write for TestOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.echo {
+            std::io::stdout().write_all(buf)?;
+        }
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.echo {
+            std::io::stdout().flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Enumerate the tests of an ELF file, sorted by name.
+fn find_tests(elf: &[u8]) -> Result<Vec<String>> {
+    let file = object::File::parse(elf).context("Failed to parse the ELF file")?;
+
+    let mut tests = if let Some(section) = file.section_by_name(TEST_SECTION) {
+        parse_test_section(section.data()?)?
+    } else {
+        file.symbols()
+            .filter_map(|symbol| {
+                let name = symbol.name().ok()?;
+                name.strip_prefix(TEST_SYMBOL_PREFIX).map(str::to_string)
+            })
+            .collect()
+    };
+
+    tests.sort();
+    tests.dedup();
+
+    Ok(tests)
+}
+
+/// Split the contents of the test section into the test names.
+fn parse_test_section(data: &[u8]) -> Result<Vec<String>> {
+    data.split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            std::str::from_utf8(name)
+                .map(str::to_string)
+                .context("Test names must be valid UTF-8")
+        })
+        .collect()
+}
+
+/// The name of the test suite in the JUnit report, which is the name of the program.
+fn suite_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "probe-rs".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use probe_rs::config::add_target_from_yaml;
+    use probe_rs::flashing::DownloadOptions;
+    use probe_rs::{FakeProbe, Permissions, Probe, SimulatedTarget};
+
+    #[test]
+    fn test_section_contains_nul_terminated_names() {
+        let tests = parse_test_section(b"tests::adds\0tests::divides\0\0\0").unwrap();
+
+        assert_eq!(tests, vec!["tests::adds", "tests::divides"]);
+    }
+
+    #[test]
+    fn test_section_rejects_invalid_names() {
+        assert!(parse_test_section(b"tests::\xff\0").is_err());
+    }
+
+    /// A test which keeps making semihosting calls still times out.
+    #[test]
+    fn semihosting_loop_times_out() {
+        add_target_from_yaml(File::open("tests/simulated_cortex_m.yaml").unwrap()).unwrap();
+        let target = SimulatedTarget::new()
+            .with_flash(0x0, 0x1_0000, 0x1000)
+            .with_ram(0x2000_0000, 0x4000);
+        let mut session =
+            Probe::from_specific_probe(Box::new(FakeProbe::with_simulated_target(target)))
+                .attach("simulated_cortex_m", Permissions::default())
+                .unwrap();
+
+        // Vector table with the initial stack pointer and the reset vector,
+        // followed by `loop: movs r0, #3 (SYS_WRITEC); bkpt 0xAB; b loop`
+        let mut image = Vec::new();
+        image.extend_from_slice(&0x2000_4000u32.to_le_bytes());
+        image.extend_from_slice(&0x9u32.to_le_bytes());
+        image.extend_from_slice(&[0x03, 0x20, 0xAB, 0xBE, 0xFC, 0xE7]);
+
+        let mut loader = session.target().flash_loader();
+        loader.add_data(0x0, &image).unwrap();
+        loader
+            .commit(&mut session, DownloadOptions::default())
+            .unwrap();
+
+        let context = TestContext {
+            path: Path::new("semihosting_loop"),
+            program_name: "semihosting_loop".to_string(),
+            memory_map: session.target().memory_map.clone(),
+            rtt_scan_regions: Vec::new(),
+            has_rtt: false,
+            no_location: false,
+            timestamp_offset: UtcOffset::UTC,
+            semihosting_root: None,
+            timeout: Duration::from_millis(200),
+            echo: false,
+        };
+        let mut core = session.core(0).unwrap();
+
+        let result = run_test(&mut core, "loop", &context, &AtomicBool::new(false)).unwrap();
+        assert!(matches!(result.outcome, TestOutcome::TimedOut(_)));
+        assert!(!result.output.is_empty());
+
+        let result = run_test(&mut core, "loop", &context, &AtomicBool::new(true)).unwrap();
+        assert!(matches!(result.outcome, TestOutcome::Failed(reason) if reason == "interrupted"));
+    }
+}
@@ -0,0 +1,457 @@
+//! Writers for the test results, in the console formats of libtest and as JUnit XML.
+
+use std::io::Write;
+use std::time::Duration;
+
+/// How a test ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// The test exited successfully.
+    Passed,
+    /// The test failed, for the given reason.
+    Failed(String),
+    /// The test did not finish within the timeout.
+    TimedOut(Duration),
+    /// The test could not be run, because of an error while communicating with the target.
+    Error(String),
+}
+
+impl TestOutcome {
+    /// Whether the test passed.
+    pub fn passed(&self) -> bool {
+        matches!(self, TestOutcome::Passed)
+    }
+
+    /// The reason the test failed, or `None` if it passed.
+    pub fn failure_message(&self) -> Option<String> {
+        match self {
+            TestOutcome::Passed => None,
+            TestOutcome::Failed(reason) | TestOutcome::Error(reason) => Some(reason.clone()),
+            TestOutcome::TimedOut(timeout) => {
+                Some(format!("test timed out after {}s", timeout.as_secs_f64()))
+            }
+        }
+    }
+}
+
+/// The result of running a single test on the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    /// The name of the test.
+    pub name: String,
+    /// How the test ended.
+    pub outcome: TestOutcome,
+    /// The time from starting the test until it ended.
+    pub duration: Duration,
+    /// The RTT and semihosting output of the test.
+    pub output: String,
+}
+
+/// Console output formats, matching the ones of libtest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleFormat {
+    /// One line per test.
+    Pretty,
+    /// One character per test.
+    Terse,
+}
+
+/// Write the list of tests, as printed by libtest for `--list`.
+pub fn write_list(
+    tests: &[String],
+    format: ConsoleFormat,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    for test in tests {
+        writeln!(writer, "{test}: test")?;
+    }
+
+    if format == ConsoleFormat::Pretty {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{}, {}",
+            plural(tests.len(), "test"),
+            plural(0, "benchmark")
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints the progress and the summary of a test run the way libtest does.
+pub struct ConsoleReporter {
+    format: ConsoleFormat,
+}
+
+impl ConsoleReporter {
+    pub fn new(format: ConsoleFormat) -> Self {
+        Self { format }
+    }
+
+    /// Write the header of a run of `count` tests.
+    pub fn start(&self, count: usize, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer)?;
+        writeln!(writer, "running {}", plural(count, "test"))?;
+        writer.flush()
+    }
+
+    /// Write the start of the line of a test, before it is run.
+    pub fn test_started(&self, name: &str, mut writer: impl Write) -> std::io::Result<()> {
+        if self.format == ConsoleFormat::Pretty {
+            write!(writer, "test {name} ... ")?;
+        }
+        writer.flush()
+    }
+
+    /// Write the outcome of a test, after it ended.
+    pub fn test_finished(
+        &self,
+        result: &TestResult,
+        mut writer: impl Write,
+    ) -> std::io::Result<()> {
+        match (self.format, result.outcome.passed()) {
+            (ConsoleFormat::Pretty, true) => writeln!(writer, "ok")?,
+            (ConsoleFormat::Pretty, false) => writeln!(writer, "FAILED")?,
+            (ConsoleFormat::Terse, true) => write!(writer, ".")?,
+            (ConsoleFormat::Terse, false) => write!(writer, "F")?,
+        }
+        writer.flush()
+    }
+
+    /// Write the output of the failed tests and the summary line.
+    pub fn finish(
+        &self,
+        results: &[TestResult],
+        filtered_out: usize,
+        duration: Duration,
+        mut writer: impl Write,
+    ) -> std::io::Result<()> {
+        if self.format == ConsoleFormat::Terse {
+            writeln!(writer)?;
+        }
+
+        let failures: Vec<&TestResult> = results.iter().filter(|r| !r.outcome.passed()).collect();
+
+        if !failures.is_empty() {
+            writeln!(writer)?;
+            writeln!(writer, "failures:")?;
+            writeln!(writer)?;
+            for failure in &failures {
+                writeln!(writer, "---- {} stdout ----", failure.name)?;
+                write!(writer, "{}", failure.output)?;
+                if !failure.output.is_empty() && !failure.output.ends_with('\n') {
+                    writeln!(writer)?;
+                }
+                if let Some(message) = failure.outcome.failure_message() {
+                    writeln!(writer, "{message}")?;
+                }
+                writeln!(writer)?;
+            }
+
+            writeln!(writer)?;
+            writeln!(writer, "failures:")?;
+            for failure in &failures {
+                writeln!(writer, "    {}", failure.name)?;
+            }
+        }
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "test result: {}. {} passed; {} failed; 0 ignored; 0 measured; {} filtered out; finished in {:.2}s",
+            if failures.is_empty() { "ok" } else { "FAILED" },
+            results.len() - failures.len(),
+            failures.len(),
+            filtered_out,
+            duration.as_secs_f64(),
+        )?;
+        writeln!(writer)?;
+
+        writer.flush()
+    }
+}
+
+/// Write the results as a JUnit XML report with a single test suite named `suite`.
+pub fn write_junit(
+    suite: &str,
+    results: &[TestResult],
+    duration: Duration,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    let errors = results
+        .iter()
+        .filter(|r| matches!(r.outcome, TestOutcome::Error(_)))
+        .count();
+    let failures = results.iter().filter(|r| !r.outcome.passed()).count() - errors;
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<testsuites>")?;
+    writeln!(
+        writer,
+        r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" skipped="0" time="{:.3}">"#,
+        xml_escape(suite),
+        results.len(),
+        failures,
+        errors,
+        duration.as_secs_f64(),
+    )?;
+
+    for result in results {
+        write!(
+            writer,
+            r#"    <testcase classname="{}" name="{}" time="{:.3}""#,
+            xml_escape(suite),
+            xml_escape(&result.name),
+            result.duration.as_secs_f64(),
+        )?;
+
+        if result.outcome.passed() && result.output.is_empty() {
+            writeln!(writer, "/>")?;
+            continue;
+        }
+        writeln!(writer, ">")?;
+
+        if let Some(message) = result.outcome.failure_message() {
+            let (element, kind) = match result.outcome {
+                TestOutcome::TimedOut(_) => ("failure", "timeout"),
+                TestOutcome::Error(_) => ("error", "probe"),
+                _ => ("failure", "assert"),
+            };
+            writeln!(
+                writer,
+                r#"      <{element} type="{kind}" message="{}"/>"#,
+                xml_escape(&message)
+            )?;
+        }
+        if !result.output.is_empty() {
+            writeln!(
+                writer,
+                "      <system-out>{}</system-out>",
+                xml_escape(&result.output)
+            )?;
+        }
+
+        writeln!(writer, "    </testcase>")?;
+    }
+
+    writeln!(writer, "  </testsuite>")?;
+    writeln!(writer, "</testsuites>")?;
+
+    writer.flush()
+}
+
+/// Format a count of things, in the plural unless there is exactly one, like libtest.
+fn plural(count: usize, name: &str) -> String {
+    if count == 1 {
+        format!("{count} {name}")
+    } else {
+        format!("{count} {name}s")
+    }
+}
+
+/// Escape `text` for use in XML attributes and text.
+///
+/// Control characters cannot be represented in XML 1.0 and are replaced.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> Vec<TestResult> {
+        vec![
+            TestResult {
+                name: "tests::adds".to_string(),
+                outcome: TestOutcome::Passed,
+                duration: Duration::from_millis(12),
+                output: String::new(),
+            },
+            TestResult {
+                name: "tests::divides".to_string(),
+                outcome: TestOutcome::Failed("exited with code 0x1 (1)".to_string()),
+                duration: Duration::from_millis(250),
+                output: "panicked at 'attempt to divide by zero'".to_string(),
+            },
+            TestResult {
+                name: "tests::waits".to_string(),
+                outcome: TestOutcome::TimedOut(Duration::from_secs(5)),
+                duration: Duration::from_secs(5),
+                output: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn list_matches_libtest() {
+        let tests = vec!["tests::adds".to_string(), "tests::divides".to_string()];
+
+        let mut output = Vec::new();
+        write_list(&tests, ConsoleFormat::Pretty, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "tests::adds: test\ntests::divides: test\n\n2 tests, 0 benchmarks\n"
+        );
+
+        let mut output = Vec::new();
+        write_list(&tests, ConsoleFormat::Terse, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "tests::adds: test\ntests::divides: test\n"
+        );
+    }
+
+    #[test]
+    fn pretty_output_matches_libtest() {
+        let reporter = ConsoleReporter::new(ConsoleFormat::Pretty);
+        let results = results();
+
+        let mut output = Vec::new();
+        reporter.start(results.len(), &mut output).unwrap();
+        for result in &results {
+            reporter.test_started(&result.name, &mut output).unwrap();
+            reporter.test_finished(result, &mut output).unwrap();
+        }
+        reporter
+            .finish(&results, 1, Duration::from_millis(5262), &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\n\
+             running 3 tests\n\
+             test tests::adds ... ok\n\
+             test tests::divides ... FAILED\n\
+             test tests::waits ... FAILED\n\
+             \n\
+             failures:\n\
+             \n\
+             ---- tests::divides stdout ----\n\
+             panicked at 'attempt to divide by zero'\n\
+             exited with code 0x1 (1)\n\
+             \n\
+             ---- tests::waits stdout ----\n\
+             test timed out after 5s\n\
+             \n\
+             \n\
+             failures:\n    \
+                 tests::divides\n    \
+                 tests::waits\n\
+             \n\
+             test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 1 filtered out; finished in 5.26s\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn terse_output_prints_one_character_per_test() {
+        let reporter = ConsoleReporter::new(ConsoleFormat::Terse);
+        let results = &results()[..1];
+
+        let mut output = Vec::new();
+        reporter.start(results.len(), &mut output).unwrap();
+        reporter
+            .test_started(&results[0].name, &mut output)
+            .unwrap();
+        reporter.test_finished(&results[0], &mut output).unwrap();
+        reporter
+            .finish(results, 0, Duration::from_millis(12), &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\nrunning 1 test\n.\n\ntest result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s\n\n"
+        );
+    }
+
+    #[test]
+    fn junit_reports_failures_and_output() {
+        let mut output = Vec::new();
+        write_junit(
+            "firmware",
+            &results(),
+            Duration::from_millis(5262),
+            &mut output,
+        )
+        .unwrap();
+
+        let xml = String::from_utf8(output).unwrap();
+        assert!(xml.contains(
+            r#"<testsuite name="firmware" tests="3" failures="2" errors="0" skipped="0" time="5.262">"#
+        ));
+        assert!(xml.contains(r#"<testcase classname="firmware" name="tests::adds" time="0.012"/>"#));
+        assert!(xml.contains(r#"<failure type="assert" message="exited with code 0x1 (1)"/>"#));
+        assert!(xml.contains(
+            "<system-out>panicked at &apos;attempt to divide by zero&apos;</system-out>"
+        ));
+        assert!(xml.contains(r#"<failure type="timeout" message="test timed out after 5s"/>"#));
+    }
+
+    #[test]
+    fn junit_reports_errors_separately() {
+        let mut results = results();
+        results.push(TestResult {
+            name: "tests::resets".to_string(),
+            outcome: TestOutcome::Error("Timeout occurred during operation.".to_string()),
+            duration: Duration::from_millis(100),
+            output: String::new(),
+        });
+
+        let mut output = Vec::new();
+        write_junit(
+            "firmware",
+            &results,
+            Duration::from_millis(5362),
+            &mut output,
+        )
+        .unwrap();
+
+        let xml = String::from_utf8(output).unwrap();
+        assert!(xml.contains(
+            r#"<testsuite name="firmware" tests="4" failures="2" errors="1" skipped="0" time="5.362">"#
+        ));
+        assert!(
+            xml.contains(r#"<error type="probe" message="Timeout occurred during operation."/>"#)
+        );
+    }
+
+    #[test]
+    fn counts_are_pluralized_like_libtest() {
+        let mut output = Vec::new();
+        write_list(
+            &["tests::adds".to_string()],
+            ConsoleFormat::Pretty,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "tests::adds: test\n\n1 test, 0 benchmarks\n"
+        );
+    }
+
+    #[test]
+    fn xml_escape_replaces_control_characters() {
+        assert_eq!(
+            xml_escape("\x1b[31m<a & b>\x1b[0m\n"),
+            "\u{FFFD}[31m&lt;a &amp; b&gt;\u{FFFD}[0m\n"
+        );
+    }
+}
@@ -1,9 +1,18 @@
+use std::collections::{HashMap, VecDeque};
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
 use std::time::Duration;
 use std::time::Instant;
 
-use probe_rs::MemoryInterface;
+use anyhow::Context;
+use probe_rs::architecture::arm::{
+    component::{EtmArchitecture, TraceSink},
+    etm::{InstructionTracer, ProgramImage},
+    SwoConfig,
+};
+use probe_rs::debug::DebugInfo;
+use probe_rs::{MemoryInterface, Session, VectorCatchCondition};
 use scroll::{Pwrite, LE};
 
 use crate::util::{common_options::ProbeOptions, parse_u64};
@@ -18,12 +27,39 @@ pub struct Cmd {
     common: ProbeOptions,
 
     /// The address of the memory to dump from the target.
-    #[clap(value_parser = parse_u64)]
-    loc: u64,
+    #[clap(value_parser = parse_u64, required_unless_present = "etm")]
+    loc: Option<u64>,
+
+    /// Trace the executed instructions with the ETM until the core halts, and print the
+    /// source lines executed last. The ELF file is used to decode the trace.
+    ///
+    /// Only ETMv3 trace, as implemented by the Cortex-M3 and Cortex-M4, can be decoded.
+    #[clap(long, value_name = "ELF", conflicts_with = "loc")]
+    etm: Option<PathBuf>,
+
+    /// The number of source lines to print when tracing with the ETM.
+    #[clap(long, default_value_t = 20, requires = "etm")]
+    lines: usize,
+
+    /// Trace over SWO instead of the embedded trace memory, with the given TPIU clock in Hz.
+    #[clap(long, value_name = "HZ", requires = "etm")]
+    swo_clock: Option<u32>,
+
+    /// The baud rate of the SWO output.
+    #[clap(long, default_value_t = 1_000_000, requires = "swo_clock")]
+    swo_baud: u32,
 }
 
 impl Cmd {
     pub fn run(self) -> anyhow::Result<()> {
+        match (self.etm.clone(), self.loc) {
+            (Some(elf), _) => self.trace_instructions(&elf),
+            (None, Some(loc)) => self.trace_memory(loc),
+            (None, None) => unreachable!("clap requires either a location or an ELF file"),
+        }
+    }
+
+    fn trace_memory(self, loc: u64) -> anyhow::Result<()> {
         let mut xs = vec![];
         let mut ys = vec![];
 
@@ -39,7 +75,7 @@ impl Cmd {
             let instant = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
 
             // Read data.
-            let value: u32 = core.read_word_32(self.loc)?;
+            let value: u32 = core.read_word_32(loc)?;
 
             xs.push(instant);
             ys.push(value);
@@ -61,4 +97,109 @@ impl Cmd {
             sleep(Duration::from_millis(time_to_wait));
         }
     }
+
+    /// Traces the executed instructions until the core halts, for example because of a fault.
+    fn trace_instructions(self, elf: &Path) -> anyhow::Result<()> {
+        let data = std::fs::read(elf)
+            .with_context(|| format!("Failed to read ELF file {}", elf.display()))?;
+        let image = ProgramImage::from_elf(&data)?;
+        let debug_info = DebugInfo::from_raw(&data)?;
+
+        let (mut session, _probe_options) = self.common.simple_attach()?;
+
+        let sink = match self.swo_clock {
+            Some(clock) => TraceSink::Swo(SwoConfig::new(clock).set_baud(self.swo_baud)),
+            None => TraceSink::TraceMemory,
+        };
+        let architecture = session.setup_instruction_trace(self.shared.core, sink)?;
+        if architecture != EtmArchitecture::EtmV3 {
+            session.disable_instruction_trace(self.shared.core)?;
+            anyhow::bail!(
+                "The core has an {architecture:?} trace unit, only ETMv3 instruction trace can be decoded."
+            );
+        }
+
+        {
+            let mut core = session.core(self.shared.core)?;
+            core.enable_vector_catch(VectorCatchCondition::HardFault)?;
+            if core.core_halted()? {
+                core.run()?;
+            }
+        }
+
+        eprintln!("Tracing until the core halts...");
+
+        let mut tracer = InstructionTracer::new(&image);
+        let mut locations = HashMap::new();
+        let mut history = VecDeque::with_capacity(self.lines);
+
+        loop {
+            let halted = session.core(self.shared.core)?.core_halted()?;
+
+            for address in tracer.push(&read_trace(&mut session, halted)?) {
+                let location = locations
+                    .entry(address)
+                    .or_insert_with(|| source_line(&debug_info, address));
+
+                let Some(location) = location else {
+                    continue;
+                };
+
+                if history.back() == Some(location) {
+                    continue;
+                }
+
+                if history.len() == self.lines {
+                    history.pop_front();
+                }
+                history.push_back(location.clone());
+            }
+
+            if halted {
+                break;
+            }
+        }
+
+        let status = session.core(self.shared.core)?.status()?;
+        println!("Core halted ({status:?}), last executed source lines:");
+        for location in history {
+            println!("    {location}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the available trace data. Once the core is halted, the trace is read until no more
+/// data is available.
+fn read_trace(session: &mut Session, halted: bool) -> anyhow::Result<Vec<u8>> {
+    let mut trace = session.read_trace_data()?;
+
+    if halted {
+        loop {
+            let data = session.read_trace_data()?;
+            if data.is_empty() {
+                break;
+            }
+            trace.extend(data);
+        }
+    }
+
+    Ok(trace)
+}
+
+/// The source file and line of the instruction at `address`.
+fn source_line(debug_info: &DebugInfo, address: u64) -> Option<String> {
+    let location = debug_info.get_source_location(address)?;
+
+    let path = match (location.directory, location.file) {
+        (Some(directory), Some(file)) => directory.join(file).display().to_string(),
+        (None, Some(file)) => file,
+        (_, None) => return None,
+    };
+
+    match location.line {
+        Some(line) => Some(format!("{path}:{line}")),
+        None => Some(path),
+    }
 }
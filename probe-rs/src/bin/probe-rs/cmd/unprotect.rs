@@ -0,0 +1,19 @@
+use probe_rs::flashing::unprotect;
+
+use crate::util::common_options::ProbeOptions;
+
+#[derive(clap::Parser)]
+pub struct Cmd {
+    #[clap(flatten)]
+    common: ProbeOptions,
+}
+
+impl Cmd {
+    pub fn run(self) -> anyhow::Result<()> {
+        let (mut session, _probe_options) = self.common.simple_attach()?;
+
+        unprotect(&mut session, None)?;
+
+        Ok(())
+    }
+}
@@ -48,6 +48,20 @@ impl Cmd {
                 }
                 core.write_8(self.read_write_options.address, &bvalues)?;
             }
+            ReadWriteBitWidth::B16 => {
+                let mut bvalues = Vec::new();
+                for val in &self.values {
+                    if val > &(u16::max_value() as u64) {
+                        return Err(anyhow::anyhow!(
+                            "{} in {:?} is too large for a 16 bit write.",
+                            val,
+                            self.values,
+                        ));
+                    }
+                    bvalues.push(*val as u16);
+                }
+                core.write_16(self.read_write_options.address, &bvalues)?;
+            }
             ReadWriteBitWidth::B32 => {
                 let mut bvalues = Vec::new();
                 for val in &self.values {
@@ -51,19 +51,30 @@ enum Subcommand {
     Reset(cmd::reset::Cmd),
     /// Run a GDB server
     Gdb(cmd::gdb::Cmd),
+    /// Serve the connected debug probes to other machines, which select them with `--probe tcp://HOST:PORT/SERIAL`
+    Serve(cmd::serve::Cmd),
     /// Basic command line debugger
     Debug(cmd::debug::Cmd),
     /// Download memory to attached target
     Download(cmd::download::Cmd),
     /// Erase all nonvolatile memory of attached target
     Erase(cmd::erase::Cmd),
+    /// Store the registers and RAM of attached target in a core dump, for post-mortem debugging
+    Dump(cmd::dump::Cmd),
+    /// Enable readout protection of attached target. Requires `--allow-protect`
+    Protect(cmd::protect::Cmd),
+    /// Disable readout protection of attached target, which usually erases all its memory. Requires `--allow-erase-all`
+    Unprotect(cmd::unprotect::Cmd),
     /// Flash and run an ELF program
     #[clap(name = "run")]
     Run(cmd::run::Cmd),
+    /// Flash an ELF program and run its tests on the target, one at a time
+    #[clap(name = "test")]
+    Test(cmd::test::Cmd),
     /// Attach to rtt logging
     #[clap(name = "attach")]
     Attach(cmd::attach::Cmd),
-    /// Trace a memory location on the target
+    /// Trace a memory location, or the executed instructions, on the target
     #[clap(name = "trace")]
     Trace(cmd::trace::Cmd),
     /// Configure and monitor ITM trace packets from the target.
@@ -250,12 +261,17 @@ fn main() -> Result<()> {
         Subcommand::List(cmd) => cmd.run(),
         Subcommand::Info(cmd) => cmd.run(),
         Subcommand::Gdb(cmd) => cmd.run(),
+        Subcommand::Serve(cmd) => cmd.run(),
         Subcommand::Reset(cmd) => cmd.run(),
         Subcommand::Debug(cmd) => cmd.run(),
         Subcommand::Download(cmd) => cmd.run(),
         Subcommand::Run(cmd) => cmd.run(true, utc_offset),
+        Subcommand::Test(cmd) => cmd.run(utc_offset),
         Subcommand::Attach(cmd) => cmd.run(utc_offset),
         Subcommand::Erase(cmd) => cmd.run(),
+        Subcommand::Dump(cmd) => cmd.run(),
+        Subcommand::Protect(cmd) => cmd.run(),
+        Subcommand::Unprotect(cmd) => cmd.run(),
         Subcommand::Trace(cmd) => cmd.run(),
         Subcommand::Itm(cmd) => cmd.run(),
         Subcommand::Chip(cmd) => cmd.run(),
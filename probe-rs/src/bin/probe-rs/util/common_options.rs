@@ -95,6 +95,9 @@ pub struct BinaryDownloadOptions {
     /// After flashing, read back all the flashed data to verify it has been written correctly.
     #[arg(long)]
     pub verify: bool,
+    /// Read back the flash before erasing it, and skip the sectors which already contain the data to be flashed.
+    #[arg(long)]
+    pub skip_unchanged_sectors: bool,
 }
 
 /// Supported bit-widths for read/write commands (not every device may support each width).
@@ -102,6 +105,8 @@ pub struct BinaryDownloadOptions {
 pub enum ReadWriteBitWidth {
     /// 8-bit width
     B8 = 8,
+    /// 16-bit width
+    B16 = 16,
     /// 32-bit width
     B32 = 32,
     /// 64-bit width
@@ -136,6 +141,7 @@ pub struct ProbeOptions {
     ///
     /// Use '--probe VID:PID' or '--probe VID:PID:Serial' if you have more than one
     /// probe with the same VID:PID.",
+    /// Use '--probe tcp://HOST:PORT/Serial' for a probe served by `probe-rs serve` on another machine.
     #[arg(long = "probe", help_heading = "PROBE CONFIGURATION")]
     pub probe_selector: Option<DebugProbeSelector>,
     /// The protocol speed in kHz.
@@ -151,6 +157,10 @@ pub struct ProbeOptions {
     /// firmware, to be erased even when it has read-only protection.
     #[arg(long)]
     pub allow_erase_all: bool,
+    /// Use this flag to allow the readout protection and the option bytes of the chip
+    /// to be changed. Depending on the chip, this can permanently lock it.
+    #[arg(long)]
+    pub allow_protect: bool,
 }
 
 impl ProbeOptions {
@@ -304,6 +314,9 @@ impl LoadedProbeOptions {
         if self.0.allow_erase_all {
             permissions = permissions.allow_erase_all();
         }
+        if self.0.allow_protect {
+            permissions = permissions.allow_protect();
+        }
 
         let session = if self.0.connect_under_reset {
             probe.attach_under_reset(target, permissions)
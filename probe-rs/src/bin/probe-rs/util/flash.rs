@@ -4,6 +4,7 @@ use super::common_options::{BinaryDownloadOptions, LoadedProbeOptions, Operation
 use super::logging;
 
 use std::fs::File;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -37,6 +38,9 @@ pub fn run_flash_download(
     options.do_chip_erase = do_chip_erase;
     options.disable_double_buffering = download_options.disable_double_buffering;
     options.verify = download_options.verify;
+    options.skip_unchanged_sectors = download_options.skip_unchanged_sectors;
+
+    let skipped_sectors = Arc::new(AtomicUsize::new(0));
 
     if !download_options.disable_progressbars {
         // Create progress bars.
@@ -69,6 +73,8 @@ pub fn run_flash_download(
         program_progress.set_style(style);
         program_progress.set_message(" Programming pages  ");
 
+        let skipped_sectors_clone = skipped_sectors.clone();
+
         // Register callback to update the progress.
         let flash_layout_output_path = download_options.flash_layout_output_path.clone();
         let progress = FlashProgress::new(move |event| {
@@ -114,6 +120,13 @@ pub fn run_flash_download(
                 SectorErased { size, .. } => {
                     erase_progress.inc(size);
                 }
+                SectorSkipped { size } => {
+                    erase_progress.inc(size);
+                    skipped_sectors_clone.fetch_add(1, Ordering::Relaxed);
+                }
+                PageSkipped { size } => {
+                    program_progress.inc(size as u64);
+                }
                 PageFilled { size, .. } => {
                     if let Some(fp) = fill_progress.as_ref() {
                         fp.inc(size)
@@ -157,6 +170,14 @@ pub fn run_flash_download(
                 path: path.to_path_buf(),
             })?;
     } else {
+        // Without progress bars, the skipped sectors still have to be counted.
+        let skipped_sectors_clone = skipped_sectors.clone();
+        options.progress = Some(FlashProgress::new(move |event| {
+            if let ProgressEvent::SectorSkipped { .. } = event {
+                skipped_sectors_clone.fetch_add(1, Ordering::Relaxed);
+            }
+        }));
+
         loader
             .commit(session, options)
             .map_err(|error| OperationError::FlashingFailed {
@@ -167,6 +188,15 @@ pub fn run_flash_download(
             })?;
     }
 
+    let skipped_sectors = skipped_sectors.load(Ordering::Relaxed);
+    if skipped_sectors > 0 {
+        logging::eprintln(format!(
+            "     {} {} unchanged sectors",
+            "Skipped".green().bold(),
+            skipped_sectors
+        ));
+    }
+
     // Stop timer.
     let elapsed = instant.elapsed();
     logging::eprintln(format!(
@@ -1,17 +1,22 @@
-use crate::architecture::arm::ArmChipInfo;
+use crate::architecture::{arm::ArmChipInfo, riscv::communication_interface::RiscvChipInfo};
 
 /// Information about a chip which is used
 /// for automatic detection of the connected chip.
 ///
 /// For ARM-based chips, the function [ArmProbeInterface::read_from_rom_table] is
-/// used to read the information from the target.
+/// used to read the information from the target. For RISC-V based chips,
+/// [RiscvCommunicationInterface::read_chip_info] is used.
 ///
 /// [ArmProbeInterface::read_from_rom_table]: crate::architecture::arm::communication_interface::ArmProbeInterface::read_from_rom_table
+/// [RiscvCommunicationInterface::read_chip_info]: crate::architecture::riscv::communication_interface::RiscvCommunicationInterface::read_chip_info
 #[derive(Debug)]
 pub(crate) enum ChipInfo {
     /// ARM specific information for chip
     /// auto-detection. See [ArmChipInfo].
     Arm(ArmChipInfo),
+    /// RISC-V specific information for chip
+    /// auto-detection. See [RiscvChipInfo].
+    Riscv(RiscvChipInfo),
 }
 
 impl From<ArmChipInfo> for ChipInfo {
@@ -19,3 +24,9 @@ impl From<ArmChipInfo> for ChipInfo {
         ChipInfo::Arm(info)
     }
 }
+
+impl From<RiscvChipInfo> for ChipInfo {
+    fn from(info: RiscvChipInfo) -> Self {
+        ChipInfo::Riscv(info)
+    }
+}
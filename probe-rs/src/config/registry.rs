@@ -1,9 +1,13 @@
 //! Internal target registry
 
 use super::{Chip, ChipFamily, ChipInfo, Core, Target, TargetDescriptionSource};
+use crate::architecture::arm::sequences::cmsis_pack::SequenceParseError;
+use crate::architecture::riscv::communication_interface::RiscvChipInfo;
 use crate::config::CoreType;
 use once_cell::sync::Lazy;
-use probe_rs_target::{CoreAccessOptions, RiscvCoreAccessOptions};
+use probe_rs_target::{
+    CoreAccessOptions, MipsCoreAccessOptions, RiscvChipIdentification, RiscvCoreAccessOptions,
+};
 use std::io::Read;
 use std::sync::{Arc, Mutex};
 
@@ -40,6 +44,9 @@ pub enum RegistryError {
     /// One of the RTT scan ranges is not enclosed in exactly one RAM region.
     #[error("Chip's RTT scan region {:#010x}..{:#010x} is not enclosed by any single RAM region.", .0.start, .0.end)]
     InvalidRttScanRange(std::ops::Range<u64>),
+    /// The CMSIS-Pack debug sequences of a chip could not be parsed.
+    #[error("The debug sequences of chip '{0}' are invalid")]
+    InvalidDebugSequence(String, #[source] SequenceParseError),
 }
 
 fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
@@ -104,12 +111,37 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
                 cores: vec![Core {
                     name: "core".to_owned(),
                     core_type: CoreType::Riscv,
-                    core_access_options: CoreAccessOptions::Riscv(RiscvCoreAccessOptions {}),
+                    core_access_options: CoreAccessOptions::Riscv(RiscvCoreAccessOptions::default()),
                 }],
                 memory_map: vec![],
                 flash_algorithms: vec![],
                 rtt_scan_ranges: None,
                 scan_chain: Some(vec![]),
+                debug_sequences: None,
+                riscv_identification: None,
+            }],
+            flash_algorithms: vec![],
+            source: TargetDescriptionSource::Generic,
+        },
+        ChipFamily {
+            name: "Generic MIPS".to_owned(),
+            manufacturer: None,
+            pack_file_release: None,
+            generated_from_pack: false,
+            variants: vec![Chip {
+                name: "mips32".to_owned(),
+                part: None,
+                cores: vec![Core {
+                    name: "core".to_owned(),
+                    core_type: CoreType::Mips32,
+                    core_access_options: CoreAccessOptions::Mips(MipsCoreAccessOptions {}),
+                }],
+                memory_map: vec![],
+                flash_algorithms: vec![],
+                rtt_scan_ranges: None,
+                scan_chain: Some(vec![]),
+                debug_sequences: None,
+                riscv_identification: None,
             }],
             flash_algorithms: vec![],
             source: TargetDescriptionSource::Generic,
@@ -280,6 +312,41 @@ impl Registry {
                         return Err(RegistryError::ChipAutodetectFailed);
                     }
                 }
+                ChipInfo::Riscv(chip_info) => {
+                    // The JTAG IDCODE already contains the manufacturer,
+                    // so all families have to be searched.
+                    let mut identified_chips = Vec::new();
+
+                    for family in &self.families {
+                        tracing::debug!("Checking family {}", family.name);
+
+                        let chips = family
+                            .variants()
+                            .iter()
+                            .filter(|v| {
+                                v.riscv_identification
+                                    .as_ref()
+                                    .map(|identification| {
+                                        riscv_identification_matches(identification, &chip_info)
+                                    })
+                                    .unwrap_or(false)
+                            })
+                            .map(|c| (family, c));
+
+                        identified_chips.extend(chips)
+                    }
+
+                    if identified_chips.len() == 1 {
+                        identified_chips.pop().unwrap()
+                    } else {
+                        tracing::debug!(
+                            "Found {} matching chips for information {:?}, unable to determine chip",
+                            identified_chips.len(),
+                            chip_info
+                        );
+                        return Err(RegistryError::ChipAutodetectFailed);
+                    }
+                }
             }
         };
         self.get_target(family, chip)
@@ -313,6 +380,38 @@ impl Registry {
     }
 }
 
+/// Check if the values read from a RISC-V chip match the identification of a target.
+///
+/// Fields of the identification which are not specified match any value. The CSRs
+/// can't be read from every chip, in that case only the JTAG IDCODE is compared.
+/// At least one field has to be compared, otherwise the identification doesn't match.
+fn riscv_identification_matches(
+    identification: &RiscvChipIdentification,
+    chip_info: &RiscvChipInfo,
+) -> bool {
+    // The version field of the IDCODE changes between revisions of the same chip.
+    const IDCODE_VERSION_MASK: u32 = 0xf000_0000;
+
+    // Returns `None` if the field can't be compared.
+    fn field_matches(expected: Option<u32>, actual: Option<u32>) -> Option<bool> {
+        Some(expected? == actual?)
+    }
+
+    let fields = [
+        field_matches(
+            identification
+                .jtag_idcode
+                .map(|idcode| idcode & !IDCODE_VERSION_MASK),
+            Some(chip_info.jtag_idcode & !IDCODE_VERSION_MASK),
+        ),
+        field_matches(identification.mvendorid, chip_info.mvendorid),
+        field_matches(identification.marchid, chip_info.marchid),
+        field_matches(identification.mimpid, chip_info.mimpid),
+    ];
+
+    fields.contains(&Some(true)) && !fields.contains(&Some(false))
+}
+
 /// Get a target from the internal registry based on its name.
 pub fn get_target_by_name(name: impl AsRef<str>) -> Result<Target, RegistryError> {
     REGISTRY.lock().unwrap().get_target_by_name(name)
@@ -382,6 +481,7 @@ fn match_name_prefix(pattern: &str, name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::architecture::riscv::communication_interface::DebugModuleVersion;
     use probe_rs_target::get_ir_lengths;
     use std::fs::File;
     type TestResult = Result<(), RegistryError>;
@@ -430,6 +530,100 @@ mod tests {
         assert!(registry.get_target_by_name("nrf51822_Xxaa").is_ok());
     }
 
+    fn riscv_chip_info(jtag_idcode: u32, mvendorid: Option<u32>) -> ChipInfo {
+        ChipInfo::Riscv(RiscvChipInfo {
+            jtag_idcode,
+            debug_version: DebugModuleVersion::Version0_13,
+            num_harts: 1,
+            nscratch: 2,
+            mvendorid,
+            marchid: None,
+            mimpid: None,
+        })
+    }
+
+    #[test]
+    fn autodetect_riscv() {
+        let registry = Registry::from_builtin_families();
+
+        let target = registry
+            .get_target_by_chip_info(riscv_chip_info(0x0000_5c25, None))
+            .unwrap();
+        assert_eq!(target.name, "esp32c3");
+
+        // The version field of the IDCODE is ignored.
+        let target = registry
+            .get_target_by_chip_info(riscv_chip_info(0x1000_dc25, Some(0x612)))
+            .unwrap();
+        assert_eq!(target.name, "esp32c6");
+    }
+
+    #[test]
+    fn autodetect_riscv_unknown() {
+        let registry = Registry::from_builtin_families();
+
+        assert!(matches!(
+            registry.get_target_by_chip_info(riscv_chip_info(0x1234_5677, None)),
+            Err(RegistryError::ChipAutodetectFailed)
+        ));
+    }
+
+    #[test]
+    fn riscv_identification() {
+        let identification = RiscvChipIdentification {
+            jtag_idcode: Some(0x0000_5c25),
+            mvendorid: Some(0x612),
+            ..Default::default()
+        };
+
+        let chip_info = |jtag_idcode, mvendorid| RiscvChipInfo {
+            jtag_idcode,
+            debug_version: DebugModuleVersion::Version0_13,
+            num_harts: 1,
+            nscratch: 2,
+            mvendorid,
+            marchid: Some(0x8000_0001),
+            mimpid: None,
+        };
+
+        assert!(riscv_identification_matches(
+            &identification,
+            &chip_info(0x0000_5c25, Some(0x612))
+        ));
+        assert!(riscv_identification_matches(
+            &identification,
+            &chip_info(0x0000_5c25, None)
+        ));
+        assert!(!riscv_identification_matches(
+            &identification,
+            &chip_info(0x0000_5c25, Some(0x489))
+        ));
+        assert!(!riscv_identification_matches(
+            &identification,
+            &chip_info(0x0000_dc25, Some(0x612))
+        ));
+
+        // An empty identification doesn't match anything.
+        assert!(!riscv_identification_matches(
+            &RiscvChipIdentification::default(),
+            &chip_info(0x0000_5c25, Some(0x612))
+        ));
+
+        // Neither does one whose fields couldn't be read from the chip.
+        let csr_identification = RiscvChipIdentification {
+            mvendorid: Some(0x612),
+            ..Default::default()
+        };
+        assert!(!riscv_identification_matches(
+            &csr_identification,
+            &chip_info(0x0000_5c25, None)
+        ));
+        assert!(riscv_identification_matches(
+            &csr_identification,
+            &chip_info(0x0000_5c25, Some(0x612))
+        ));
+    }
+
     #[test]
     fn validate_generic_targets() {
         let mut families = vec![];
@@ -495,4 +689,55 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn add_riscv_target_with_multiple_harts() -> TestResult {
+        let file = File::open("tests/riscv_multi_hart_test.yaml")?;
+        add_target_from_yaml(file)?;
+
+        let hart_ids = |target: Target| {
+            target
+                .cores
+                .iter()
+                .map(|core| match &core.core_access_options {
+                    CoreAccessOptions::Riscv(options) => options.hart_id,
+                    other => panic!("Unexpected core access options {other:?}"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let target = get_target_by_name("RISCV_TWO_HARTS").unwrap();
+        assert_eq!(hart_ids(target), vec![0, 1]);
+
+        // The hart defaults to 0 if it is not specified
+        let target = get_target_by_name("RISCV_DEFAULT_HART").unwrap();
+        assert_eq!(hart_ids(target), vec![0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_duplicate_riscv_harts() {
+        let family = r#"
+name: DUPLICATE_HARTS
+variants:
+  - name: DUPLICATE_HARTS
+    cores:
+      - name: hart0
+        type: riscv
+        core_access_options: !Riscv
+          hart_id: 1
+      - name: hart1
+        type: riscv
+        core_access_options: !Riscv
+          hart_id: 1
+    memory_map: []
+flash_algorithms: []
+"#;
+
+        assert!(matches!(
+            add_target_from_yaml(family.as_bytes()),
+            Err(RegistryError::InvalidChipFamilyDefinition(_, _))
+        ));
+    }
 }
@@ -5,6 +5,7 @@ use crate::architecture::arm::{
     ap::MemoryAp,
     sequences::{
         atsame5x::AtSAME5x,
+        cmsis_pack::CmsisPackSequence,
         efm32xg2::EFM32xG2,
         infineon::XMC4000,
         nrf52::Nrf52,
@@ -19,6 +20,7 @@ use crate::architecture::arm::{
     },
     ApAddress, DpAddress,
 };
+use crate::architecture::mips::sequences::{pic32::Pic32, DefaultMipsSequence, MipsDebugSequence};
 use crate::architecture::riscv::sequences::{esp32c3::ESP32C3, esp32c6::ESP32C6};
 use crate::architecture::riscv::sequences::{DefaultRiscvSequence, RiscvDebugSequence};
 use crate::flashing::FlashLoader;
@@ -112,6 +114,7 @@ impl Target {
         let mut debug_sequence = match chip.cores[0].core_type.architecture() {
             Architecture::Arm => DebugSequence::Arm(DefaultArmSequence::create()),
             Architecture::Riscv => DebugSequence::Riscv(DefaultRiscvSequence::create()),
+            Architecture::Mips => DebugSequence::Mips(DefaultMipsSequence::create()),
         };
 
         if chip.name.starts_with("MIMXRT10") {
@@ -145,6 +148,9 @@ impl Target {
         } else if chip.name.starts_with("esp32c6") {
             tracing::warn!("Using custom sequence for ESP32C6");
             debug_sequence = DebugSequence::Riscv(ESP32C6::create());
+        } else if chip.name.starts_with("PIC32") {
+            tracing::warn!("Using custom sequence for PIC32");
+            debug_sequence = DebugSequence::Mips(Pic32::create());
         } else if chip.name.starts_with("nRF5340") {
             tracing::warn!("Using custom sequence for nRF5340");
             debug_sequence = DebugSequence::Arm(Nrf5340::create());
@@ -185,6 +191,16 @@ impl Target {
         } else if chip.name.starts_with("XMC4") {
             tracing::warn!("Using custom sequence for XMC4000");
             debug_sequence = DebugSequence::Arm(XMC4000::create());
+        } else if let (DebugSequence::Arm(_), Some(sequences)) =
+            (&debug_sequence, &chip.debug_sequences)
+        {
+            tracing::info!(
+                "Using the debug sequences from the CMSIS-Pack for {}",
+                chip.name
+            );
+            let sequence = CmsisPackSequence::create(sequences)
+                .map_err(|e| RegistryError::InvalidDebugSequence(chip.name.clone(), e))?;
+            debug_sequence = DebugSequence::Arm(sequence);
         }
 
         let rtt_scan_regions = match &chip.rtt_scan_ranges {
@@ -333,15 +349,15 @@ impl From<Target> for TargetSelector {
 }
 
 /// This is the type to denote a general debug sequence.
-/// It can differentiate between ARM and RISC-V for now.
-/// Currently, only the ARM variant does something sensible;
-/// RISC-V will be ignored when encountered.
+/// It can differentiate between ARM, RISC-V and MIPS for now.
 #[derive(Clone)]
 pub enum DebugSequence {
     /// An ARM debug sequence.
     Arm(Arc<dyn ArmDebugSequence>),
     /// A RISC-V debug sequence.
     Riscv(Arc<dyn RiscvDebugSequence>),
+    /// A MIPS debug sequence.
+    Mips(Arc<dyn MipsDebugSequence>),
 }
 
 pub(crate) trait CoreExt {
@@ -361,6 +377,7 @@ impl CoreExt for Core {
                 ap: options.ap,
             })),
             probe_rs_target::CoreAccessOptions::Riscv(_) => None,
+            probe_rs_target::CoreAccessOptions::Mips(_) => None,
         }
     }
 }
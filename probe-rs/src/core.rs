@@ -4,16 +4,18 @@ use crate::{
 };
 use anyhow::{anyhow, Result};
 pub use probe_rs_target::{Architecture, CoreAccessOptions};
-use probe_rs_target::{ArmCoreAccessOptions, RiscvCoreAccessOptions};
+use probe_rs_target::{ArmCoreAccessOptions, MipsCoreAccessOptions, RiscvCoreAccessOptions};
 use std::{sync::Arc, time::Duration};
 
 pub mod core_state;
 pub mod core_status;
+pub mod dump;
 pub mod memory_mapped_registers;
 pub mod registers;
 
 pub use core_state::*;
 pub use core_status::*;
+pub use dump::{CoreDump, CoreDumpError};
 pub use memory_mapped_registers::MemoryMappedRegister;
 pub use registers::*;
 
@@ -92,6 +94,48 @@ pub trait CoreInterface: MemoryInterface + ExceptionInterface {
     /// Clears the breakpoint configured in unit `unit_index`.
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), error::Error>;
 
+    /// Returns the number of data watchpoint units of the core.
+    fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        Ok(0)
+    }
+
+    /// Read the data watchpoints configured on the core, and return them in a Vector.
+    /// A value of None in any position of the Vector indicates that the position is unset/available.
+    fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, error::Error> {
+        Ok(vec![])
+    }
+
+    /// Sets a data watchpoint. It does so by using unit `unit_index`.
+    fn set_watchpoint(
+        &mut self,
+        _unit_index: usize,
+        _watchpoint: Watchpoint,
+    ) -> Result<(), error::Error> {
+        Err(Error::NotImplemented("data watchpoints"))
+    }
+
+    /// Clears the data watchpoint configured in unit `unit_index`.
+    fn clear_watchpoint(&mut self, _unit_index: usize) -> Result<(), error::Error> {
+        Err(Error::NotImplemented("data watchpoints"))
+    }
+
+    /// Returns `true` if data watchpoints use the same units as hardware breakpoints,
+    /// e.g. the triggers of the RISC-V trigger module.
+    ///
+    /// In this case, a unit used by a watchpoint is not available for breakpoints, and vice versa.
+    fn watchpoints_share_breakpoint_units(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the watchpoint unit `unit_index` can not be used for a new watchpoint,
+    /// e.g. because the core uses it to catch exceptions, or it is used for tracing.
+    ///
+    /// If [`CoreInterface::watchpoints_share_breakpoint_units`] is `true`, this applies to
+    /// breakpoints as well.
+    fn unit_reserved(&mut self, _unit_index: usize) -> Result<bool, error::Error> {
+        Ok(false)
+    }
+
     /// Returns a list of all the registers of this core.
     fn registers(&self) -> &'static registers::CoreRegisters;
 
@@ -176,6 +220,10 @@ impl<'probe> MemoryInterface for Core<'probe> {
         self.inner.read_word_32(address)
     }
 
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        self.inner.read_word_16(address)
+    }
+
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
         self.inner.read_word_8(address)
     }
@@ -188,6 +236,10 @@ impl<'probe> MemoryInterface for Core<'probe> {
         self.inner.read_32(address, data)
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        self.inner.read_16(address, data)
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
         self.inner.read_8(address, data)
     }
@@ -204,6 +256,10 @@ impl<'probe> MemoryInterface for Core<'probe> {
         self.inner.write_word_32(addr, data)
     }
 
+    fn write_word_16(&mut self, addr: u64, data: u16) -> Result<(), Error> {
+        self.inner.write_word_16(addr, data)
+    }
+
     fn write_word_8(&mut self, addr: u64, data: u8) -> Result<(), Error> {
         self.inner.write_word_8(addr, data)
     }
@@ -216,6 +272,10 @@ impl<'probe> MemoryInterface for Core<'probe> {
         self.inner.write_32(addr, data)
     }
 
+    fn write_16(&mut self, addr: u64, data: &[u16]) -> Result<(), Error> {
+        self.inner.write_16(addr, data)
+    }
+
     fn write_8(&mut self, addr: u64, data: &[u8]) -> Result<(), Error> {
         self.inner.write_8(addr, data)
     }
@@ -339,7 +399,8 @@ impl<'probe> Core<'probe> {
             CoreAccessOptions::Arm(options) => {
                 let sequence = match &target.debug_sequence {
                     crate::config::DebugSequence::Arm(seq) => seq.clone(),
-                    crate::config::DebugSequence::Riscv(_) => panic!(
+                    crate::config::DebugSequence::Riscv(_)
+                    | crate::config::DebugSequence::Mips(_) => panic!(
                         "Mismatch between sequence and core kind. This is a bug, please report it."
                     ),
                 };
@@ -360,6 +421,14 @@ impl<'probe> Core<'probe> {
                     specific_state,
                 }
             }
+            CoreAccessOptions::Mips(options) => {
+                let core_state = CoreState::new(ResolvedCoreOptions::Mips { options });
+                CombinedCoreState {
+                    id,
+                    core_state,
+                    specific_state,
+                }
+            }
         }
     }
 
@@ -518,9 +587,22 @@ impl<'probe> Core<'probe> {
 
     /// Find the index of the next available HW breakpoint comparator.
     fn find_free_breakpoint_comparator_index(&mut self) -> Result<usize, error::Error> {
+        let shared_units = self.inner.watchpoints_share_breakpoint_units();
+        let watchpoints = if shared_units {
+            self.inner.watchpoints()?
+        } else {
+            vec![]
+        };
+
         let mut next_available_hw_breakpoint = 0;
         for breakpoint in self.inner.hw_breakpoints()? {
-            if breakpoint.is_none() {
+            let used_by_watchpoint =
+                matches!(watchpoints.get(next_available_hw_breakpoint), Some(Some(_)));
+
+            if breakpoint.is_none()
+                && !used_by_watchpoint
+                && !(shared_units && self.inner.unit_reserved(next_available_hw_breakpoint)?)
+            {
                 return Ok(next_available_hw_breakpoint);
             } else {
                 next_available_hw_breakpoint += 1;
@@ -531,6 +613,28 @@ impl<'probe> Core<'probe> {
         )))
     }
 
+    /// Find the index of the next available data watchpoint unit.
+    fn find_free_watchpoint_unit_index(&mut self) -> Result<usize, error::Error> {
+        let breakpoints = if self.inner.watchpoints_share_breakpoint_units() {
+            self.inner.hw_breakpoints()?
+        } else {
+            vec![]
+        };
+
+        for (unit_index, watchpoint) in self.inner.watchpoints()?.into_iter().enumerate() {
+            if watchpoint.is_none()
+                && !matches!(breakpoints.get(unit_index), Some(Some(_)))
+                && !self.inner.unit_reserved(unit_index)?
+            {
+                return Ok(unit_index);
+            }
+        }
+
+        Err(error::Error::Other(anyhow!(
+            "No available data watchpoints"
+        )))
+    }
+
     /// Set a hardware breakpoint
     ///
     /// This function will try to set a hardware breakpoint att `address`.
@@ -608,6 +712,126 @@ impl<'probe> Core<'probe> {
         Ok(())
     }
 
+    /// Returns the number of data watchpoint units of the core.
+    pub fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        self.inner.available_watchpoint_units()
+    }
+
+    /// Read the data watchpoints configured on the core.
+    ///
+    /// The position in the returned Vector is the index of the watchpoint unit,
+    /// as reported by [`WatchpointCause::Unit`]. A value of None indicates that the unit is unused.
+    pub fn watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, error::Error> {
+        self.inner.watchpoints()
+    }
+
+    /// Set a data watchpoint
+    ///
+    /// This function will try to set a data watchpoint, which halts the core when
+    /// the `length` bytes starting at `address` are accessed as specified by `access`.
+    ///
+    /// The amount of watchpoints which are supported, as well as the supported
+    /// alignment and length of the watched range, is chip specific.
+    #[tracing::instrument(skip(self))]
+    pub fn set_watchpoint(
+        &mut self,
+        address: u64,
+        length: u64,
+        access: WatchpointAccess,
+    ) -> Result<(), error::Error> {
+        self.place_watchpoint(Watchpoint {
+            address,
+            length,
+            access,
+            value: None,
+        })
+    }
+
+    /// Set a data value watchpoint
+    ///
+    /// This function works like [`Core::set_watchpoint`], but only halts the core
+    /// when `value` is read from or written to the watched range.
+    ///
+    /// Data value matching is only supported by some architectures, e.g. by the RISC-V trigger module.
+    #[tracing::instrument(skip(self))]
+    pub fn set_data_value_watchpoint(
+        &mut self,
+        address: u64,
+        length: u64,
+        access: WatchpointAccess,
+        value: u64,
+    ) -> Result<(), error::Error> {
+        self.place_watchpoint(Watchpoint {
+            address,
+            length,
+            access,
+            value: Some(value),
+        })
+    }
+
+    fn place_watchpoint(&mut self, watchpoint: Watchpoint) -> Result<(), error::Error> {
+        // If the watchpoint is set already, return its unit index, else find the next free index.
+        let unit_index = match self
+            .inner
+            .watchpoints()?
+            .iter()
+            .position(|&wp| wp == Some(watchpoint))
+        {
+            Some(unit_index) => unit_index,
+            None => self.find_free_watchpoint_unit_index()?,
+        };
+
+        tracing::debug!(
+            "Trying to set data watchpoint #{} for {} bytes at {:#010x}",
+            unit_index,
+            watchpoint.length,
+            watchpoint.address
+        );
+
+        self.inner.set_watchpoint(unit_index, watchpoint)
+    }
+
+    /// Clear a data watchpoint
+    ///
+    /// This function will clear the data watchpoints starting at `address`.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_watchpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        let units: Vec<usize> = self
+            .inner
+            .watchpoints()?
+            .iter()
+            .enumerate()
+            .filter(|(_, wp)| matches!(wp, Some(wp) if wp.address == address))
+            .map(|(unit_index, _)| unit_index)
+            .collect();
+
+        if units.is_empty() {
+            return Err(error::Error::Other(anyhow!(
+                "No watchpoint found at address {:#010x}",
+                address
+            )));
+        }
+
+        for unit_index in units {
+            self.inner.clear_watchpoint(unit_index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Clear all data watchpoints
+    ///
+    /// This function will clear all data watchpoints which are configured on the target.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_all_watchpoints(&mut self) -> Result<(), error::Error> {
+        for (unit_index, watchpoint) in self.inner.watchpoints()?.into_iter().enumerate() {
+            if watchpoint.is_some() {
+                self.inner.clear_watchpoint(unit_index)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the architecture of the core.
     pub fn architecture(&self) -> Architecture {
         self.inner.architecture()
@@ -659,6 +883,9 @@ pub enum ResolvedCoreOptions {
     Riscv {
         options: RiscvCoreAccessOptions,
     },
+    Mips {
+        options: MipsCoreAccessOptions,
+    },
 }
 
 impl std::fmt::Debug for ResolvedCoreOptions {
@@ -670,6 +897,7 @@ impl std::fmt::Debug for ResolvedCoreOptions {
                 .field("options", options)
                 .finish(),
             Self::Riscv { options } => f.debug_struct("Riscv").field("options", options).finish(),
+            Self::Mips { options } => f.debug_struct("Mips").field("options", options).finish(),
         }
     }
 }
@@ -5,6 +5,7 @@ use crate::{
             core::{CortexAState, CortexMState},
             ApAddress, ArmProbeInterface, DpAddress,
         },
+        mips::{communication_interface::MipsCommunicationInterface, MipsState},
         riscv::{communication_interface::RiscvCommunicationInterface, RiscVState},
     },
     Core, CoreType, Error,
@@ -39,7 +40,7 @@ impl CombinedCoreState {
 
         let (options, debug_sequence) = match &self.core_state.core_access_options {
             ResolvedCoreOptions::Arm { options, sequence } => (options, sequence.clone()),
-            ResolvedCoreOptions::Riscv { .. } => {
+            ResolvedCoreOptions::Riscv { .. } | ResolvedCoreOptions::Mips { .. } => {
                 return Err(Error::UnableToOpenProbe(
                     "Core architecture and Probe mismatch.",
                 ))
@@ -90,7 +91,7 @@ impl CombinedCoreState {
         let (sequence_handle, arm_core_access_options) = match &self.core_state.core_access_options
         {
             ResolvedCoreOptions::Arm { sequence, options } => (sequence, options),
-            ResolvedCoreOptions::Riscv { .. } => {
+            ResolvedCoreOptions::Riscv { .. } | ResolvedCoreOptions::Mips { .. } => {
                 panic!("This should never happen. Please file a bug if it does.");
             }
         };
@@ -116,7 +117,7 @@ impl CombinedCoreState {
         let (sequence_handle, arm_core_access_options) = match &self.core_state.core_access_options
         {
             ResolvedCoreOptions::Arm { sequence, options } => (sequence, options),
-            ResolvedCoreOptions::Riscv { .. } => {
+            ResolvedCoreOptions::Riscv { .. } | ResolvedCoreOptions::Mips { .. } => {
                 panic!("This should never happen. Please file a bug if it does.");
             }
         };
@@ -139,6 +140,8 @@ impl CombinedCoreState {
         &'probe mut self,
         interface: &'probe mut RiscvCommunicationInterface,
     ) -> Result<Core<'probe>, Error> {
+        interface.select_hart(self.core_state.riscv_hart_id())?;
+
         Ok(match &mut self.specific_state {
             SpecificCoreState::Riscv(s) => Core::new(crate::architecture::riscv::Riscv32::new(
                 interface, s, self.id,
@@ -151,6 +154,22 @@ impl CombinedCoreState {
         })
     }
 
+    pub(crate) fn attach_mips<'probe>(
+        &'probe mut self,
+        interface: &'probe mut MipsCommunicationInterface,
+    ) -> Result<Core<'probe>, Error> {
+        Ok(match &mut self.specific_state {
+            SpecificCoreState::Mips32(s) => Core::new(crate::architecture::mips::Mips32::new(
+                interface, s, self.id,
+            )),
+            _ => {
+                return Err(Error::UnableToOpenProbe(
+                    "Core architecture and Probe mismatch.",
+                ))
+            }
+        })
+    }
+
     /// Get the memory AP for this core.
     ///
     /// ## Panic
@@ -176,10 +195,24 @@ impl CoreState {
         }
     }
 
+    /// Get the hart of a RISC-V core.
+    ///
+    /// ## Panic
+    ///
+    /// This function will panic if the core is not a RISC-V core.
+    pub(crate) fn riscv_hart_id(&self) -> u32 {
+        match &self.core_access_options {
+            ResolvedCoreOptions::Riscv { options } => options.hart_id,
+            ResolvedCoreOptions::Arm { .. } | ResolvedCoreOptions::Mips { .. } => {
+                panic!("This should never happen. Please file a bug if it does.")
+            }
+        }
+    }
+
     pub(crate) fn memory_ap(&self) -> MemoryAp {
         let arm_core_access_options = match &self.core_access_options {
             ResolvedCoreOptions::Arm { options, .. } => options,
-            ResolvedCoreOptions::Riscv { .. } => {
+            ResolvedCoreOptions::Riscv { .. } | ResolvedCoreOptions::Mips { .. } => {
                 panic!("This should never happen. Please file a bug if it does.")
             }
         };
@@ -215,6 +248,8 @@ pub enum SpecificCoreState {
     Armv8m(CortexMState),
     /// The state of an RISC-V core.
     Riscv(RiscVState),
+    /// The state of a MIPS32 core.
+    Mips32(MipsState),
 }
 
 impl SpecificCoreState {
@@ -227,6 +262,7 @@ impl SpecificCoreState {
             CoreType::Armv8a => SpecificCoreState::Armv8a(CortexAState::new()),
             CoreType::Armv8m => SpecificCoreState::Armv8m(CortexMState::new()),
             CoreType::Riscv => SpecificCoreState::Riscv(RiscVState::new()),
+            CoreType::Mips32 => SpecificCoreState::Mips32(MipsState::new()),
         }
     }
 
@@ -239,6 +275,7 @@ impl SpecificCoreState {
             SpecificCoreState::Armv8a(_) => CoreType::Armv8a,
             SpecificCoreState::Armv8m(_) => CoreType::Armv8m,
             SpecificCoreState::Riscv(_) => CoreType::Riscv,
+            SpecificCoreState::Mips32(_) => CoreType::Mips32,
         }
     }
 }
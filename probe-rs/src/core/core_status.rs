@@ -1,5 +1,5 @@
 /// The status of the core.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CoreStatus {
     /// The core is currently running.
     Running,
@@ -26,7 +26,7 @@ impl CoreStatus {
 }
 
 /// Indicates the operation the target would like the debugger to perform.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum SemihostingCommand {
     /// The target indicates that it completed successfully and no-longer wishes
     /// to run.
@@ -37,10 +37,19 @@ pub enum SemihostingCommand {
         /// Some architecture-specific or application specific exit code
         code: u64,
     },
+    /// The target requests any other operation, which can be performed by a
+    /// [`SemihostingHost`](crate::semihosting::SemihostingHost).
+    Request {
+        /// The number of the operation, passed in the first argument register.
+        operation: u32,
+        /// The parameter of the operation, passed in the second argument register. For most
+        /// operations, this is the address of a parameter block.
+        parameter: u64,
+    },
 }
 
 /// When the core halts due to a breakpoint request, some architectures will allow us to distinguish between a software and hardware breakpoint.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum BreakpointCause {
     /// We encountered a hardware breakpoint.
     Hardware,
@@ -56,8 +65,19 @@ pub enum BreakpointCause {
     Semihosting(SemihostingCommand),
 }
 
+/// When the core halts due to a data watchpoint, some architectures allow us to determine which watchpoint was hit.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub enum WatchpointCause {
+    /// The watchpoint configured in the given watchpoint unit was hit.
+    ///
+    /// The watchpoint itself can be looked up using [`Core::watchpoints`](crate::Core::watchpoints).
+    Unit(usize),
+    /// We were not able to determine which watchpoint was hit.
+    Unknown,
+}
+
 /// The reason why a core was halted.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum HaltReason {
     /// Multiple reasons for a halt.
     ///
@@ -70,8 +90,8 @@ pub enum HaltReason {
     /// Core halted due to an exception, e.g. an
     /// an interrupt.
     Exception,
-    /// Core halted due to a data watchpoint
-    Watchpoint,
+    /// Core halted due to a data watchpoint. The cause is `Unknown` if we cannot determine which watchpoint was hit.
+    Watchpoint(WatchpointCause),
     /// Core halted after single step
     Step,
     /// Core halted because of a debugger request
@@ -97,4 +117,30 @@ pub enum VectorCatchCondition {
     SecureFault,
     /// We encountered any exception.
     All,
+    /// We encountered an interrupt. This is only supported on RISC-V.
+    Interrupt,
+}
+
+/// The kind of data access which triggers a [`Watchpoint`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum WatchpointAccess {
+    /// Trigger on data reads.
+    Read,
+    /// Trigger on data writes.
+    Write,
+    /// Trigger on both data reads and writes.
+    ReadWrite,
+}
+
+/// A data watchpoint, which halts the core when a range of memory is accessed.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Watchpoint {
+    /// Start address of the watched memory range.
+    pub address: u64,
+    /// Length of the watched memory range, in bytes.
+    pub length: u64,
+    /// The kind of access which triggers the watchpoint.
+    pub access: WatchpointAccess,
+    /// Only trigger if this value is read or written, instead of on any access.
+    pub value: Option<u64>,
 }
@@ -0,0 +1,926 @@
+//! Capture the state of a target into a core dump, for post-mortem debugging without hardware attached.
+//!
+//! Core dumps are stored as ELF core files. Every captured memory range is a `PT_LOAD` segment,
+//! and the registers of all cores are stored in a `probe-rs` note.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use object::elf::{FileHeader32, FileHeader64, ET_CORE, PT_LOAD, PT_NOTE};
+use object::read::elf::{FileHeader, ProgramHeader};
+use object::{Endianness, FileKind};
+
+use crate::architecture::arm::core::exception_handling::{
+    armv6m, armv6m_armv7m_shared, armv7m, armv8m,
+};
+use crate::architecture::arm::core::registers::{
+    aarch32::{
+        AARCH32_CORE_REGSISTERS, AARCH32_WITH_FP_16_CORE_REGSISTERS,
+        AARCH32_WITH_FP_32_CORE_REGSISTERS,
+    },
+    aarch64::{self, AARCH64_CORE_REGSISTERS},
+    cortex_m::{self, CORTEX_M_CORE_REGSISTERS, CORTEX_M_WITH_FP_CORE_REGSISTERS},
+};
+use crate::architecture::mips::registers::{self as mips, MIPS32_CORE_REGISTERS};
+use crate::architecture::riscv::registers::{
+    self as riscv, RISCV64_CORE_REGSISTERS, RISCV_CORE_REGSISTERS,
+};
+use crate::config::MemoryRegion;
+use crate::core::{
+    Architecture, CoreInformation, CoreInterface, CoreRegister, CoreRegisters, CoreStatus,
+    ExceptionInfo, ExceptionInterface, HaltReason, RegisterId, RegisterRole, RegisterValue,
+};
+use crate::debug::DebugRegisters;
+use crate::{Core, CoreType, Error, InstructionSet, MemoryInterface, Session};
+
+/// Name of the ELF note which holds the core dump metadata.
+const NOTE_NAME: &[u8] = b"probe-rs";
+
+/// Type of the ELF note which holds the core dump metadata.
+const NOTE_TYPE_METADATA: u32 = 1;
+
+/// The System Control Block of Cortex-M cores, which contains the fault status and address registers.
+const CORTEX_M_FAULT_REGISTERS: Range<u64> = 0xE000_ED00..0xE000_ED40;
+
+/// The Secure Fault Status and Address registers of ARMv8-M cores.
+const ARMV8M_SECURE_FAULT_REGISTERS: Range<u64> = 0xE000_EDE4..0xE000_EDEC;
+
+/// The banked stack pointers of ARMv8-M cores, which are used to unwind exception frames.
+const ARMV8M_STACK_POINTERS: Range<u16> = 0b0001_1000..0b0001_1100;
+
+/// Errors which can occur when storing or loading a [`CoreDump`].
+#[derive(thiserror::Error, Debug)]
+pub enum CoreDumpError {
+    /// The core dump file could not be read or written.
+    #[error("Failed to access the core dump file")]
+    Io(#[from] std::io::Error),
+    /// The core dump file is not a valid ELF file.
+    #[error("Failed to parse the core dump file")]
+    Parse(#[from] object::read::Error),
+    /// The metadata in the core dump could not be encoded or decoded.
+    #[error("Failed to decode the core dump metadata")]
+    Metadata(#[from] bincode::Error),
+    /// The file is an ELF file, but not a core dump created by probe-rs.
+    #[error("Invalid core dump file: {0}")]
+    InvalidFile(&'static str),
+}
+
+/// A snapshot of the registers of all cores, and the RAM of a target.
+///
+/// A core dump is created with [`CoreDump::capture`], and can be opened as a [`Session`]
+/// with [`Session::from_core_dump`], to inspect the state of the target without hardware attached.
+#[derive(Debug, Clone)]
+pub struct CoreDump {
+    target: String,
+    cores: Vec<DumpedCore>,
+    memory: Vec<DumpedMemory>,
+}
+
+/// Metadata which is stored in the ELF note of a core dump.
+#[derive(Serialize, Deserialize)]
+struct CoreDumpMetadata {
+    target: String,
+    cores: Vec<DumpedCore>,
+}
+
+/// The state of a single core in a core dump.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DumpedCore {
+    core_type: CoreType,
+    instruction_set: InstructionSet,
+    fpu_support: Option<bool>,
+    floating_point_register_count: usize,
+    registers: BTreeMap<u16, RegisterValue>,
+}
+
+/// A range of memory in a core dump.
+#[derive(Debug, Clone)]
+struct DumpedMemory {
+    address: u64,
+    data: Vec<u8>,
+}
+
+impl DumpedMemory {
+    fn range(&self) -> Range<u64> {
+        self.address..self.address + self.data.len() as u64
+    }
+}
+
+impl CoreDump {
+    /// Capture the registers of all cores, and the RAM regions of the target.
+    ///
+    /// All cores are halted, and remain halted after the capture. Memory which can not be read
+    /// is skipped with a warning, so that a core dump can still be captured from a partially
+    /// broken target.
+    pub fn capture(session: &mut Session) -> Result<Self, Error> {
+        let mut cores = Vec::new();
+
+        for (index, core_type) in session.list_cores() {
+            let mut core = session.core(index)?;
+
+            if !core.core_halted()? {
+                core.halt(Duration::from_millis(100))?;
+            }
+
+            let mut register_ids = core
+                .registers()
+                .all_registers()
+                .map(|register| register.id())
+                .collect::<Vec<_>>();
+            if core_type == CoreType::Armv8m {
+                register_ids.extend(ARMV8M_STACK_POINTERS.map(RegisterId));
+            }
+
+            let mut registers = BTreeMap::new();
+            for id in register_ids {
+                match core.read_core_reg::<RegisterValue>(id) {
+                    Ok(value) => {
+                        registers.insert(id.0, value);
+                    }
+                    Err(error) => {
+                        tracing::debug!("Skipping register {:?} of core {}: {}", id, index, error)
+                    }
+                }
+            }
+
+            let floating_point_register_count = core
+                .registers()
+                .all_registers()
+                .filter(|register| register.register_has_role(RegisterRole::FloatingPoint))
+                .count();
+
+            cores.push(DumpedCore {
+                core_type,
+                instruction_set: core.instruction_set()?,
+                fpu_support: core.fpu_support().ok(),
+                floating_point_register_count,
+                registers,
+            });
+        }
+
+        let mut ranges = Vec::new();
+        for region in &session.target().memory_map {
+            if let MemoryRegion::Ram(region) = region {
+                let core_index = region
+                    .cores
+                    .first()
+                    .and_then(|name| session.target().core_index_by_name(name))
+                    .unwrap_or(0);
+                ranges.push((core_index, region.range.clone()));
+            }
+        }
+        for (index, core) in cores.iter().enumerate() {
+            if core.core_type.is_cortex_m() {
+                ranges.push((index, CORTEX_M_FAULT_REGISTERS));
+            }
+            if core.core_type == CoreType::Armv8m {
+                ranges.push((index, ARMV8M_SECURE_FAULT_REGISTERS));
+            }
+        }
+
+        let mut memory: Vec<DumpedMemory> = Vec::new();
+        for (core_index, range) in ranges {
+            if memory.iter().any(|dumped| dumped.range() == range) {
+                continue;
+            }
+
+            tracing::debug!(
+                "Capturing memory {:#010x}..{:#010x}",
+                range.start,
+                range.end
+            );
+
+            let mut data = vec![0; (range.end - range.start) as usize];
+            match session.core(core_index)?.read(range.start, &mut data) {
+                Ok(()) => memory.push(DumpedMemory {
+                    address: range.start,
+                    data,
+                }),
+                Err(error) => tracing::warn!(
+                    "Skipping memory {:#010x}..{:#010x} in core dump: {}",
+                    range.start,
+                    range.end,
+                    error
+                ),
+            }
+        }
+
+        Ok(Self {
+            target: session.target().name.clone(),
+            cores,
+            memory,
+        })
+    }
+
+    /// Store the core dump in an ELF core file at `path`.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), CoreDumpError> {
+        std::fs::write(path, self.to_elf()?)?;
+        Ok(())
+    }
+
+    /// Load a core dump from an ELF core file at `path`, which was created with [`CoreDump::store`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CoreDumpError> {
+        Self::from_elf(&std::fs::read(path)?)
+    }
+
+    /// The name of the target the core dump was captured from.
+    pub fn target_name(&self) -> &str {
+        &self.target
+    }
+
+    /// Lists the cores in the core dump with their number and their type.
+    pub fn list_cores(&self) -> Vec<(usize, CoreType)> {
+        self.cores
+            .iter()
+            .enumerate()
+            .map(|(index, core)| (index, core.core_type))
+            .collect()
+    }
+
+    /// The architecture of the target the core dump was captured from.
+    pub fn architecture(&self) -> Architecture {
+        self.cores
+            .first()
+            .map(|core| core.core_type.architecture())
+            .unwrap_or(Architecture::Arm)
+    }
+
+    /// Get a [`Core`] which reads its registers and memory from the core dump.
+    ///
+    /// The core is always halted, and can not be run or stepped. Writes to registers and memory
+    /// only change the core dump in memory.
+    pub fn core(&mut self, core_index: usize) -> Result<Core<'_>, Error> {
+        let core = self
+            .cores
+            .get_mut(core_index)
+            .ok_or(Error::CoreNotFound(core_index))?;
+
+        Ok(Core::new(CoreDumpCore {
+            id: core_index,
+            core,
+            memory: &mut self.memory,
+        }))
+    }
+
+    fn is_64_bit(&self) -> bool {
+        self.cores.iter().any(|core| {
+            matches!(
+                core.instruction_set,
+                InstructionSet::A64 | InstructionSet::RV64 | InstructionSet::RV64C
+            )
+        }) || self
+            .memory
+            .iter()
+            .any(|memory| memory.range().end > u32::MAX as u64 + 1)
+    }
+
+    fn machine(&self) -> u16 {
+        match self.cores.first() {
+            Some(core) if core.instruction_set == InstructionSet::A64 => object::elf::EM_AARCH64,
+            Some(core) => match core.core_type.architecture() {
+                Architecture::Arm => object::elf::EM_ARM,
+                Architecture::Riscv => object::elf::EM_RISCV,
+                Architecture::Mips => object::elf::EM_MIPS,
+            },
+            None => object::elf::EM_NONE,
+        }
+    }
+
+    fn to_elf(&self) -> Result<Vec<u8>, CoreDumpError> {
+        let metadata = bincode::serialize(&CoreDumpMetadata {
+            target: self.target.clone(),
+            cores: self.cores.clone(),
+        })?;
+
+        let mut note = Vec::new();
+        note.extend_from_slice(&(NOTE_NAME.len() as u32 + 1).to_le_bytes());
+        note.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+        note.extend_from_slice(&NOTE_TYPE_METADATA.to_le_bytes());
+        note.extend_from_slice(NOTE_NAME);
+        note.push(0);
+        note.resize((note.len() + 3) / 4 * 4, 0);
+        note.extend_from_slice(&metadata);
+        note.resize((note.len() + 3) / 4 * 4, 0);
+
+        let mut elf = ElfWriter {
+            data: Vec::new(),
+            is_64_bit: self.is_64_bit(),
+        };
+        let (header_size, program_header_size) = if elf.is_64_bit { (64, 56) } else { (52, 32) };
+        let program_header_count = 1 + self.memory.len();
+        let note_offset = header_size + program_header_size * program_header_count as u64;
+
+        // ELF header
+        elf.data.extend_from_slice(&[0x7f, b'E', b'L', b'F']);
+        elf.data.push(if elf.is_64_bit {
+            object::elf::ELFCLASS64
+        } else {
+            object::elf::ELFCLASS32
+        });
+        elf.data.push(object::elf::ELFDATA2LSB);
+        elf.data.push(object::elf::EV_CURRENT);
+        elf.data.resize(16, 0);
+        elf.u16(ET_CORE);
+        elf.u16(self.machine());
+        elf.u32(object::elf::EV_CURRENT as u32);
+        elf.word(0); // e_entry
+        elf.word(header_size); // e_phoff
+        elf.word(0); // e_shoff
+        elf.u32(0); // e_flags
+        elf.u16(header_size as u16);
+        elf.u16(program_header_size as u16);
+        elf.u16(program_header_count as u16);
+        elf.u16(0); // e_shentsize
+        elf.u16(0); // e_shnum
+        elf.u16(0); // e_shstrndx
+
+        elf.program_header(
+            PT_NOTE,
+            object::elf::PF_R,
+            note_offset,
+            0,
+            note.len() as u64,
+            4,
+        );
+
+        let mut offset = note_offset + note.len() as u64;
+        for memory in &self.memory {
+            elf.program_header(
+                PT_LOAD,
+                object::elf::PF_R | object::elf::PF_W,
+                offset,
+                memory.address,
+                memory.data.len() as u64,
+                1,
+            );
+            offset += memory.data.len() as u64;
+        }
+
+        elf.data.extend_from_slice(&note);
+        for memory in &self.memory {
+            elf.data.extend_from_slice(&memory.data);
+        }
+
+        Ok(elf.data)
+    }
+
+    fn from_elf(data: &[u8]) -> Result<Self, CoreDumpError> {
+        match FileKind::parse(data)? {
+            FileKind::Elf32 => Self::parse_elf::<FileHeader32<Endianness>>(data),
+            FileKind::Elf64 => Self::parse_elf::<FileHeader64<Endianness>>(data),
+            _ => Err(CoreDumpError::InvalidFile("The file is not an ELF file.")),
+        }
+    }
+
+    fn parse_elf<Elf: FileHeader<Endian = Endianness>>(data: &[u8]) -> Result<Self, CoreDumpError> {
+        let header = Elf::parse(data)?;
+        let endian = header.endian()?;
+
+        if header.e_type(endian) != ET_CORE {
+            return Err(CoreDumpError::InvalidFile(
+                "The ELF file is not a core file.",
+            ));
+        }
+
+        let mut metadata = None;
+        let mut memory = Vec::new();
+
+        for segment in header.program_headers(endian, data)? {
+            match segment.p_type(endian) {
+                PT_NOTE => {
+                    let Some(mut notes) = segment.notes(endian, data)? else {
+                        continue;
+                    };
+                    while let Some(note) = notes.next()? {
+                        if note.name() == NOTE_NAME && note.n_type(endian) == NOTE_TYPE_METADATA {
+                            metadata = Some(bincode::deserialize::<CoreDumpMetadata>(note.desc())?);
+                        }
+                    }
+                }
+                PT_LOAD => {
+                    let contents = segment.data(endian, data).map_err(|()| {
+                        CoreDumpError::InvalidFile("A memory segment is out of bounds.")
+                    })?;
+                    memory.push(DumpedMemory {
+                        address: segment.p_vaddr(endian).into(),
+                        data: contents.to_vec(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let metadata = metadata.ok_or(CoreDumpError::InvalidFile(
+            "The core file was not created by probe-rs.",
+        ))?;
+
+        Ok(Self {
+            target: metadata.target,
+            cores: metadata.cores,
+            memory,
+        })
+    }
+}
+
+/// Helper to write little endian ELF structures, where addresses and offsets are 32 or 64 bit wide.
+struct ElfWriter {
+    data: Vec<u8>,
+    is_64_bit: bool,
+}
+
+impl ElfWriter {
+    fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Write an address or offset.
+    fn word(&mut self, value: u64) {
+        if self.is_64_bit {
+            self.data.extend_from_slice(&value.to_le_bytes());
+        } else {
+            self.u32(value as u32);
+        }
+    }
+
+    fn program_header(
+        &mut self,
+        segment_type: u32,
+        flags: u32,
+        offset: u64,
+        address: u64,
+        size: u64,
+        align: u64,
+    ) {
+        self.u32(segment_type);
+        if self.is_64_bit {
+            self.u32(flags);
+        }
+        self.word(offset);
+        self.word(address); // p_vaddr
+        self.word(address); // p_paddr
+        self.word(size); // p_filesz
+        self.word(size); // p_memsz
+        if !self.is_64_bit {
+            self.u32(flags);
+        }
+        self.word(align);
+    }
+}
+
+/// A halted core, which reads its registers and memory from a [`CoreDump`].
+struct CoreDumpCore<'dump> {
+    id: usize,
+    core: &'dump mut DumpedCore,
+    memory: &'dump mut Vec<DumpedMemory>,
+}
+
+impl CoreDumpCore<'_> {
+    fn memory_range(&mut self, address: u64, size: usize) -> Result<&mut [u8], Error> {
+        let end = address.checked_add(size as u64).ok_or_else(|| {
+            Error::Other(anyhow!(
+                "Memory access of {} bytes at {:#010x} overflows the address space",
+                size,
+                address
+            ))
+        })?;
+
+        self.memory
+            .iter_mut()
+            .find(|memory| memory.address <= address && end <= memory.range().end)
+            .map(|memory| {
+                let start = (address - memory.address) as usize;
+                &mut memory.data[start..start + size]
+            })
+            .ok_or_else(|| {
+                Error::Other(anyhow!(
+                    "Memory {:#010x}..{:#010x} is not contained in the core dump",
+                    address,
+                    end
+                ))
+            })
+    }
+
+    fn is_rv64(&self) -> bool {
+        matches!(
+            self.core.instruction_set,
+            InstructionSet::RV64 | InstructionSet::RV64C
+        )
+    }
+}
+
+impl MemoryInterface for CoreDumpCore<'_> {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        true
+    }
+
+    fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
+        let mut data = [0u64; 1];
+        self.read_64(address, &mut data)?;
+        Ok(data[0])
+    }
+
+    fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
+        let mut data = [0u32; 1];
+        self.read_32(address, &mut data)?;
+        Ok(data[0])
+    }
+
+    fn read_word_16(&mut self, address: u64) -> Result<u16, Error> {
+        let mut data = [0u16; 1];
+        self.read_16(address, &mut data)?;
+        Ok(data[0])
+    }
+
+    fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
+        let mut data = [0u8; 1];
+        self.read_8(address, &mut data)?;
+        Ok(data[0])
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 8)?;
+        for (word, bytes) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(())
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 4)?;
+        for (word, bytes) in data.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(())
+    }
+
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 2)?;
+        for (word, bytes) in data.iter_mut().zip(bytes.chunks_exact(2)) {
+            *word = u16::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(())
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        data.copy_from_slice(self.memory_range(address, data.len())?);
+        Ok(())
+    }
+
+    fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
+        self.write_64(address, &[data])
+    }
+
+    fn write_word_32(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        self.write_32(address, &[data])
+    }
+
+    fn write_word_16(&mut self, address: u64, data: u16) -> Result<(), Error> {
+        self.write_16(address, &[data])
+    }
+
+    fn write_word_8(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        self.write_8(address, &[data])
+    }
+
+    fn write_64(&mut self, address: u64, data: &[u64]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 8)?;
+        for (word, bytes) in data.iter().zip(bytes.chunks_exact_mut(8)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    fn write_32(&mut self, address: u64, data: &[u32]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 4)?;
+        for (word, bytes) in data.iter().zip(bytes.chunks_exact_mut(4)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), Error> {
+        let bytes = self.memory_range(address, data.len() * 2)?;
+        for (word, bytes) in data.iter().zip(bytes.chunks_exact_mut(2)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        self.memory_range(address, data.len())?
+            .copy_from_slice(data);
+        Ok(())
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl CoreInterface for CoreDumpCore<'_> {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn wait_for_core_halted(&mut self, _timeout: Duration) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn core_halted(&mut self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn status(&mut self) -> Result<CoreStatus, Error> {
+        Ok(CoreStatus::Halted(HaltReason::Unknown))
+    }
+
+    fn halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+        let pc = self.read_core_reg(self.program_counter().id())?;
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
+
+    fn run(&mut self) -> Result<(), Error> {
+        Err(Error::NotImplemented("running a core dump"))
+    }
+
+    fn reset(&mut self) -> Result<(), Error> {
+        Err(Error::NotImplemented("resetting a core dump"))
+    }
+
+    fn reset_and_halt(&mut self, _timeout: Duration) -> Result<CoreInformation, Error> {
+        Err(Error::NotImplemented("resetting a core dump"))
+    }
+
+    fn step(&mut self) -> Result<CoreInformation, Error> {
+        Err(Error::NotImplemented("stepping a core dump"))
+    }
+
+    fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
+        self.core.registers.get(&address.0).copied().ok_or_else(|| {
+            Error::Register(format!(
+                "Register {address:?} is not contained in the core dump"
+            ))
+        })
+    }
+
+    fn write_core_reg(&mut self, address: RegisterId, value: RegisterValue) -> Result<(), Error> {
+        self.core.registers.insert(address.0, value);
+        Ok(())
+    }
+
+    fn available_breakpoint_units(&mut self) -> Result<u32, Error> {
+        Ok(0)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        Ok(vec![])
+    }
+
+    fn enable_breakpoints(&mut self, _state: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_hw_breakpoint(&mut self, _unit_index: usize, _addr: u64) -> Result<(), Error> {
+        Err(Error::NotImplemented("breakpoints in a core dump"))
+    }
+
+    fn clear_hw_breakpoint(&mut self, _unit_index: usize) -> Result<(), Error> {
+        Err(Error::NotImplemented("breakpoints in a core dump"))
+    }
+
+    fn registers(&self) -> &'static CoreRegisters {
+        let floating_point_register_count = self.core.floating_point_register_count;
+
+        match self.core.core_type {
+            CoreType::Armv6m => &CORTEX_M_CORE_REGSISTERS,
+            CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+                if floating_point_register_count > 0 {
+                    &CORTEX_M_WITH_FP_CORE_REGSISTERS
+                } else {
+                    &CORTEX_M_CORE_REGSISTERS
+                }
+            }
+            CoreType::Armv7a => match floating_point_register_count {
+                0 => &AARCH32_CORE_REGSISTERS,
+                1..=31 => &AARCH32_WITH_FP_16_CORE_REGSISTERS,
+                _ => &AARCH32_WITH_FP_32_CORE_REGSISTERS,
+            },
+            CoreType::Armv8a if self.core.instruction_set == InstructionSet::A64 => {
+                &AARCH64_CORE_REGSISTERS
+            }
+            CoreType::Armv8a => &AARCH32_WITH_FP_32_CORE_REGSISTERS,
+            CoreType::Riscv if self.is_rv64() => &RISCV64_CORE_REGSISTERS,
+            CoreType::Riscv => &RISCV_CORE_REGSISTERS,
+            CoreType::Mips32 => &MIPS32_CORE_REGISTERS,
+        }
+    }
+
+    fn program_counter(&self) -> &'static CoreRegister {
+        match self.core.core_type {
+            CoreType::Armv8a if self.core.instruction_set == InstructionSet::A64 => &aarch64::PC,
+            CoreType::Riscv if self.is_rv64() => &riscv::RV64_PC,
+            CoreType::Riscv => &riscv::PC,
+            CoreType::Mips32 => &mips::PC,
+            _ => &cortex_m::PC,
+        }
+    }
+
+    fn frame_pointer(&self) -> &'static CoreRegister {
+        match self.core.core_type {
+            CoreType::Armv8a if self.core.instruction_set == InstructionSet::A64 => &aarch64::FP,
+            CoreType::Riscv if self.is_rv64() => &riscv::RV64_FP,
+            CoreType::Riscv => &riscv::FP,
+            CoreType::Mips32 => &mips::FP,
+            _ => &cortex_m::FP,
+        }
+    }
+
+    fn stack_pointer(&self) -> &'static CoreRegister {
+        match self.core.core_type {
+            CoreType::Armv8a if self.core.instruction_set == InstructionSet::A64 => &aarch64::SP,
+            CoreType::Riscv if self.is_rv64() => &riscv::RV64_SP,
+            CoreType::Riscv => &riscv::SP,
+            CoreType::Mips32 => &mips::SP,
+            _ => &cortex_m::SP,
+        }
+    }
+
+    fn return_address(&self) -> &'static CoreRegister {
+        match self.core.core_type {
+            CoreType::Armv8a if self.core.instruction_set == InstructionSet::A64 => &aarch64::RA,
+            CoreType::Riscv if self.is_rv64() => &riscv::RV64_RA,
+            CoreType::Riscv => &riscv::RA,
+            CoreType::Mips32 => &mips::RA,
+            _ => &cortex_m::RA,
+        }
+    }
+
+    fn hw_breakpoints_enabled(&self) -> bool {
+        false
+    }
+
+    fn debug_on_sw_breakpoint(&mut self, _enabled: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn architecture(&self) -> Architecture {
+        self.core.core_type.architecture()
+    }
+
+    fn core_type(&self) -> CoreType {
+        self.core.core_type
+    }
+
+    fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
+        Ok(self.core.instruction_set)
+    }
+
+    fn fpu_support(&mut self) -> Result<bool, Error> {
+        self.core.fpu_support.ok_or_else(|| {
+            Error::Other(anyhow!(
+                "The FPU support of the core is not contained in the core dump"
+            ))
+        })
+    }
+
+    fn reset_catch_set(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn reset_catch_clear(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn debug_core_stop(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ExceptionInterface for CoreDumpCore<'_> {
+    fn exception_details(
+        &mut self,
+        stackframe_registers: &DebugRegisters,
+    ) -> Result<Option<ExceptionInfo>, Error> {
+        match self.core.core_type {
+            CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em => {
+                armv6m_armv7m_shared::exception_details(self, stackframe_registers)
+            }
+            CoreType::Armv8m => armv8m::exception_details(self, stackframe_registers),
+            _ => Err(Error::NotImplemented(
+                "Unwinding of exception frames has not yet been implemented for this architecture.",
+            )),
+        }
+    }
+
+    fn calling_frame_registers(
+        &mut self,
+        stackframe_registers: &DebugRegisters,
+    ) -> Result<DebugRegisters, Error> {
+        match self.core.core_type {
+            CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em => {
+                armv6m_armv7m_shared::calling_frame_registers(self, stackframe_registers)
+            }
+            CoreType::Armv8m => armv8m::calling_frame_registers(self, stackframe_registers),
+            _ => Err(Error::NotImplemented(
+                "Not implemented for this architecture.",
+            )),
+        }
+    }
+
+    fn exception_description(
+        &mut self,
+        stackframe_registers: &DebugRegisters,
+    ) -> Result<String, Error> {
+        match self.core.core_type {
+            CoreType::Armv6m => armv6m::exception_description(stackframe_registers),
+            CoreType::Armv7m | CoreType::Armv7em => {
+                armv7m::exception_description(self, stackframe_registers)
+            }
+            CoreType::Armv8m => armv8m::exception_description(self, stackframe_registers),
+            _ => Err(Error::NotImplemented(
+                "Not implemented for this architecture.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn core_dump(address: u64) -> CoreDump {
+        CoreDump {
+            target: "nRF52840_xxAA".to_string(),
+            cores: vec![DumpedCore {
+                core_type: CoreType::Armv7em,
+                instruction_set: InstructionSet::Thumb2,
+                fpu_support: Some(true),
+                floating_point_register_count: 32,
+                registers: BTreeMap::from([
+                    (15, RegisterValue::U32(0x0000_1234)),
+                    (13, RegisterValue::U32(address as u32 + 0x10)),
+                ]),
+            }],
+            memory: vec![DumpedMemory {
+                address,
+                data: (0..64).collect(),
+            }],
+        }
+    }
+
+    #[test]
+    fn elf_roundtrip() {
+        let original = core_dump(0x2000_0000);
+        let elf = original.to_elf().unwrap();
+        assert_eq!(&elf[..4], b"\x7fELF");
+        assert_eq!(elf[4], object::elf::ELFCLASS32);
+
+        let mut loaded = CoreDump::from_elf(&elf).unwrap();
+        assert_eq!(loaded.target_name(), "nRF52840_xxAA");
+        assert_eq!(loaded.list_cores(), vec![(0, CoreType::Armv7em)]);
+
+        let mut core = loaded.core(0).unwrap();
+        let pc: u32 = core.read_core_reg(RegisterId(15)).unwrap();
+        assert_eq!(pc, 0x1234);
+        assert_eq!(core.read_word_32(0x2000_0004).unwrap(), 0x0706_0504);
+        assert!(core.read_word_32(0x2000_0040).is_err());
+        // Accesses wrapping around the end of the address space are rejected.
+        assert!(core.read_word_32(u64::MAX - 1).is_err());
+        assert!(core.run().is_err());
+    }
+
+    #[test]
+    fn elf64_for_high_addresses() {
+        let elf = core_dump(0x1_0000_0000).to_elf().unwrap();
+        assert_eq!(elf[4], object::elf::ELFCLASS64);
+
+        let mut loaded = CoreDump::from_elf(&elf).unwrap();
+        let mut core = loaded.core(0).unwrap();
+        let mut data = [0u8; 4];
+        core.read(0x1_0000_003c, &mut data).unwrap();
+        assert_eq!(data, [60, 61, 62, 63]);
+    }
+
+    #[test]
+    fn reject_other_elf_files() {
+        let mut elf = core_dump(0x2000_0000).to_elf().unwrap();
+        // Change the file type to ET_EXEC
+        elf[16] = 2;
+        assert!(matches!(
+            CoreDump::from_elf(&elf),
+            Err(CoreDumpError::InvalidFile(_))
+        ));
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::Error;
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     convert::Infallible,
@@ -214,7 +215,7 @@ impl From<u16> for RegisterId {
 /// Creating a new `RegisterValue` should be done using From or Into.
 /// Converting a value back to a primitive type can be done with either
 /// a match arm or TryInto
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum RegisterValue {
     /// 32-bit unsigned integer
     U32(u32),
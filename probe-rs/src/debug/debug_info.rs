@@ -127,6 +127,101 @@ impl DebugInfo {
         Ok(None)
     }
 
+    /// Find the addresses where a breakpoint should be set, to halt on entry to the function(s) called `function_name`.
+    ///
+    /// The name is compared with the `DW_AT_name` of the function (e.g. `main`), its mangled linkage name,
+    /// and its demangled linkage name without the hash (e.g. `my_crate::module::function`).
+    /// A partial path, such as `module::function`, matches the end of a demangled name.
+    /// If the line program marks the end of the function prologue, that address is used instead of the entry address.
+    ///
+    /// Functions which are only ever inlined have no address of their own, and will not be found.
+    pub fn get_function_addresses(&self, function_name: &str) -> Result<Vec<u64>, DebugError> {
+        let mut addresses = Vec::new();
+        let mut units = self.dwarf.units();
+
+        while let Some(unit_info) = self.get_next_unit_info(&mut units) {
+            let unit = &unit_info.unit;
+            let mut entries = unit.entries();
+
+            while let Some((_, entry)) = entries.next_dfs()? {
+                if entry.tag() != gimli::DW_TAG_subprogram {
+                    continue;
+                }
+
+                let Some(range) = self.dwarf.die_ranges(unit, entry)?.next()? else {
+                    // Declarations and abstract instances of inlined functions have no code.
+                    continue;
+                };
+
+                let name = self.function_attribute_string(unit, entry, gimli::DW_AT_name);
+                let linkage_name = self
+                    .function_attribute_string(unit, entry, gimli::DW_AT_linkage_name)
+                    .or_else(|| {
+                        self.function_attribute_string(unit, entry, gimli::DW_AT_MIPS_linkage_name)
+                    });
+
+                if function_name_matches(function_name, name.as_deref(), linkage_name.as_deref()) {
+                    addresses.push(
+                        self.prologue_end(unit, range.begin, range.end)?
+                            .unwrap_or(range.begin),
+                    );
+                }
+            }
+        }
+
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    /// Read a string attribute of a function DIE, following `DW_AT_specification` and `DW_AT_abstract_origin` if required.
+    fn function_attribute_string(
+        &self,
+        unit: &gimli::Unit<GimliReader>,
+        entry: &gimli::DebuggingInformationEntry<GimliReader>,
+        attribute: gimli::DwAt,
+    ) -> Option<String> {
+        if let Ok(Some(value)) = entry.attr_value(attribute) {
+            return self
+                .dwarf
+                .attr_string(unit, value)
+                .ok()
+                .map(|name| String::from_utf8_lossy(&name).to_string());
+        }
+
+        for origin_attribute in [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin] {
+            if let Ok(Some(gimli::AttributeValue::UnitRef(offset))) =
+                entry.attr_value(origin_attribute)
+            {
+                let origin = unit.entry(offset).ok()?;
+                return self.function_attribute_string(unit, &origin, attribute);
+            }
+        }
+
+        None
+    }
+
+    /// Find the address in `low_pc..high_pc` which the line program marks as the end of the function prologue.
+    fn prologue_end(
+        &self,
+        unit: &gimli::Unit<GimliReader>,
+        low_pc: u64,
+        high_pc: u64,
+    ) -> Result<Option<u64>, DebugError> {
+        let Some(line_program) = unit.line_program.clone() else {
+            return Ok(None);
+        };
+
+        let mut rows = line_program.rows();
+        while let Some((_, row)) = rows.next_row()? {
+            if row.prologue_end() && (low_pc..high_pc).contains(&row.address()) {
+                return Ok(Some(row.address()));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Try get the [`SourceLocation`] for a given address.
     pub fn get_source_location(&self, address: u64) -> Option<SourceLocation> {
         let mut units = self.dwarf.units();
@@ -1209,6 +1304,27 @@ pub(crate) fn canonical_path_eq(primary_path: &Path, secondary_path: &Path) -> b
         })
 }
 
+/// Check if a requested function name matches the `DW_AT_name` or the (demangled) linkage name of a function.
+fn function_name_matches(
+    requested_name: &str,
+    name: Option<&str>,
+    linkage_name: Option<&str>,
+) -> bool {
+    if name == Some(requested_name) || linkage_name == Some(requested_name) {
+        return true;
+    }
+
+    let Some(demangled_name) =
+        linkage_name.and_then(|linkage_name| rustc_demangle::try_demangle(linkage_name).ok())
+    else {
+        return false;
+    };
+
+    // The alternate format omits the hash.
+    let demangled_name = format!("{demangled_name:#}");
+    demangled_name == requested_name || demangled_name.ends_with(&format!("::{requested_name}"))
+}
+
 /// Get a handle to the [`gimli::UnwindTableRow`] for this call frame, so that we can reference it to unwind register values.
 fn get_unwind_info<'a>(
     unwind_context: &'a mut Box<UnwindContext<DwarfReader>>,
@@ -1435,8 +1551,12 @@ fn unwind_program_counter_register(
     } else {
         match return_address {
             RegisterValue::U32(return_address) => {
-                if matches!(core.instruction_set(), Ok(InstructionSet::Thumb2)) {
+                if matches!(
+                    core.instruction_set(),
+                    Ok(InstructionSet::Thumb2 | InstructionSet::MicroMips)
+                ) {
                     // NOTE: [ARMv7-M Architecture Reference Manual](https://developer.arm.com/documentation/ddi0403/ee), Section A5.1.2: We have to clear the last bit to ensure the PC is half-word aligned. (on ARM architecture, when in Thumb state for certain instruction types will set the LSB to 1)
+                    // The same applies to the ISA mode bit of microMIPS return addresses.
                     *register_rule_string = "PC=(unwound LR & !0b1) (dwarf Undefined)".to_string();
                     Some(RegisterValue::U32(return_address & !0b1))
                 } else {
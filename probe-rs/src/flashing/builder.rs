@@ -69,6 +69,11 @@ impl FlashSector {
     pub fn size(&self) -> u64 {
         self.size
     }
+
+    /// Check if the sector overlaps the memory range of `size` bytes at `address`.
+    pub(super) fn overlaps(&self, address: u64, size: u64) -> bool {
+        self.address < address + size && address < self.address + self.size
+    }
 }
 
 /// A struct to hold all the information about one region
@@ -141,6 +146,78 @@ impl FlashLayout {
     pub fn visualize(&self) -> FlashVisualizer {
         FlashVisualizer::new(self)
     }
+
+    /// Remove the sectors which don't have to be erased and programmed, because their contents are unchanged.
+    ///
+    /// `changed_sectors` holds one entry per sector, which is `true` if the flash contents of the sector differ from its pages.
+    /// A page which overlaps a changed sector has to be programmed, so all the sectors it overlaps are kept, as they have to be erased first.
+    ///
+    /// Returns the removed sectors and pages.
+    pub(super) fn remove_unchanged_sectors(
+        &mut self,
+        changed_sectors: &[bool],
+    ) -> (Vec<FlashSector>, Vec<FlashPage>) {
+        let mut keep_sectors = changed_sectors.to_vec();
+
+        // Pages can span multiple sectors, so keep going until no more sectors are added.
+        let mut sectors_added = true;
+        while sectors_added {
+            sectors_added = false;
+            for page in &self.pages {
+                let overlapping_sectors = self
+                    .sectors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, sector)| sector.overlaps(page.address(), page.size() as u64))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+
+                if overlapping_sectors.iter().any(|&index| keep_sectors[index]) {
+                    for index in overlapping_sectors {
+                        sectors_added |= !keep_sectors[index];
+                        keep_sectors[index] = true;
+                    }
+                }
+            }
+        }
+
+        let (kept_sectors, removed_sectors): (Vec<_>, Vec<_>) = std::mem::take(&mut self.sectors)
+            .into_iter()
+            .zip(keep_sectors)
+            .partition(|(_, keep)| *keep);
+        self.sectors = kept_sectors.into_iter().map(|(sector, _)| sector).collect();
+
+        let mut removed_pages = Vec::new();
+        let mut new_page_indices = Vec::with_capacity(self.pages.len());
+        for page in std::mem::take(&mut self.pages) {
+            if self
+                .sectors
+                .iter()
+                .any(|sector| sector.overlaps(page.address(), page.size() as u64))
+            {
+                new_page_indices.push(Some(self.pages.len()));
+                self.pages.push(page);
+            } else {
+                new_page_indices.push(None);
+                removed_pages.push(page);
+            }
+        }
+
+        self.fills = std::mem::take(&mut self.fills)
+            .into_iter()
+            .filter_map(|fill| {
+                new_page_indices[fill.page_index].map(|page_index| FlashFill { page_index, ..fill })
+            })
+            .collect();
+
+        (
+            removed_sectors
+                .into_iter()
+                .map(|(sector, _)| sector)
+                .collect(),
+            removed_pages,
+        )
+    }
 }
 
 /// A block of data that is to be written to flash.
@@ -1145,4 +1222,78 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn remove_unchanged_sectors() {
+        let (region, flash_algorithm) = assemble_demo_flash1();
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 5024]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, true)
+            .unwrap();
+
+        let (skipped_sectors, skipped_pages) =
+            flash_layout.remove_unchanged_sectors(&[false, true]);
+
+        assert_eq!(
+            skipped_sectors,
+            vec![FlashSector {
+                address: 0x000000,
+                size: 0x001000,
+            }]
+        );
+        assert_eq!(
+            skipped_pages
+                .iter()
+                .map(|page| page.address())
+                .collect::<Vec<_>>(),
+            vec![0x000000, 0x000400, 0x000800, 0x000C00]
+        );
+        assert_eq!(
+            flash_layout.sectors(),
+            &[FlashSector {
+                address: 0x001000,
+                size: 0x001000,
+            }]
+        );
+        assert_eq!(flash_layout.pages().len(), 4);
+        assert_eq!(flash_layout.pages()[0].address(), 0x001000);
+        // The fills refer to the remaining pages.
+        assert_eq!(
+            flash_layout
+                .fills()
+                .iter()
+                .map(|fill| fill.page_index())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn remove_unchanged_sectors_page_spans_sectors() {
+        let (region, flash_algorithm) = assemble_demo_flash2();
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 1024]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, false)
+            .unwrap();
+        assert_eq!(flash_layout.sectors().len(), 8);
+
+        // The page spans all sectors, so a single changed sector requires erasing all of them.
+        let mut changed_sectors = [false; 8];
+        changed_sectors[3] = true;
+        let (skipped_sectors, skipped_pages) =
+            flash_layout.remove_unchanged_sectors(&changed_sectors);
+
+        assert!(skipped_sectors.is_empty());
+        assert!(skipped_pages.is_empty());
+        assert_eq!(flash_layout.sectors().len(), 8);
+
+        let (skipped_sectors, skipped_pages) = flash_layout.remove_unchanged_sectors(&[false; 8]);
+
+        assert_eq!(skipped_sectors.len(), 8);
+        assert_eq!(skipped_pages.len(), 1);
+        assert!(flash_layout.sectors().is_empty());
+        assert!(flash_layout.pages().is_empty());
+    }
 }
@@ -0,0 +1,108 @@
+//! CRC32 routines which are run on the target, to verify flash contents
+//! without reading them back through the probe.
+
+use probe_rs_target::InstructionSet;
+
+/// Polynomial of the CRC32 used by zlib and Ethernet, in reversed bit order.
+///
+/// The routines take the polynomial as an argument, which avoids a literal pool in the Thumb routine.
+pub(super) const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// CRC32 routine for the Thumb instruction set, which only uses ARMv6-M instructions.
+///
+/// Calculates the CRC32 of `r1` bytes starting at address `r0`, with the polynomial in `r2`.
+/// The result is returned in `r0`.
+const THUMB_CRC32_ROUTINE: [u16; 18] = [
+    0x2300, //     movs r3, #0
+    0x43db, //     mvns r3, r3
+    0x2900, // 1:  cmp r1, #0
+    0xd00a, //     beq 3f
+    0x7804, //     ldrb r4, [r0]
+    0x3001, //     adds r0, #1
+    0x3901, //     subs r1, #1
+    0x4063, //     eors r3, r4
+    0x2508, //     movs r5, #8
+    0x085b, // 2:  lsrs r3, r3, #1
+    0xd300, //     bcc 4f
+    0x4053, //     eors r3, r2
+    0x3d01, // 4:  subs r5, #1
+    0xd1fa, //     bne 2b
+    0xe7f2, //     b 1b
+    0x43d8, // 3:  mvns r0, r3
+    0x4770, //     bx lr
+    0xbf00, //     nop
+];
+
+/// CRC32 routine for the RV32I instruction set.
+///
+/// Calculates the CRC32 of `a1` bytes starting at address `a0`, with the polynomial in `a2`.
+/// The result is returned in `a0`.
+const RISCV_CRC32_ROUTINE: [u32; 16] = [
+    0xfff0_0293, //     li t0, -1
+    0x0205_8a63, // 1:  beqz a1, 3f
+    0x0005_4303, //     lbu t1, 0(a0)
+    0x0015_0513, //     addi a0, a0, 1
+    0xfff5_8593, //     addi a1, a1, -1
+    0x0062_c2b3, //     xor t0, t0, t1
+    0x0080_0393, //     li t2, 8
+    0x0012_f313, // 2:  andi t1, t0, 1
+    0x0012_d293, //     srli t0, t0, 1
+    0x0003_0463, //     beqz t1, 4f
+    0x00c2_c2b3, //     xor t0, t0, a2
+    0xfff3_8393, // 4:  addi t2, t2, -1
+    0xfe03_96e3, //     bnez t2, 2b
+    0xfd1f_f06f, //     j 1b
+    0xfff2_c513, // 3:  not a0, t0
+    0x0000_8067, //     ret
+];
+
+/// Get the machine code of the CRC32 routine for `instruction_set`,
+/// or `None` if there is no routine for it.
+///
+/// The routine does not use the stack, and returns to the address in the link register.
+///
+/// The RISC-V routine only uses RV32I instructions, so it is not used on RV64 harts.
+pub(super) fn crc32_routine(instruction_set: InstructionSet) -> Option<Vec<u8>> {
+    match instruction_set {
+        InstructionSet::Thumb2 => Some(
+            THUMB_CRC32_ROUTINE
+                .iter()
+                .flat_map(|instruction| instruction.to_le_bytes())
+                .collect(),
+        ),
+        InstructionSet::RV32 | InstructionSet::RV32C => Some(
+            RISCV_CRC32_ROUTINE
+                .iter()
+                .flat_map(|instruction| instruction.to_le_bytes())
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Calculate the CRC32 of `data` on the host, the same way as the target routines do.
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let lsb = crc & 1;
+            crc >>= 1;
+            if lsb != 0 {
+                crc ^= CRC32_POLYNOMIAL;
+            }
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::crc32;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+}
@@ -134,10 +134,20 @@ pub struct DownloadOptions {
     /// If the chip was pre-erased with external erasers, this flag can set to true to skip erasing
     /// It may be useful for mass production.
     pub skip_erase: bool,
-    /// After flashing, read back all the flashed data to verify it has been written correctly.
+    /// After flashing, verify that all the data has been written correctly.
+    ///
+    /// Flash contents are checked on the target where possible, using the `Verify()` entry point of the
+    /// flash algorithm or a CRC32 routine. Everything else is read back.
     pub verify: bool,
     /// Disable double buffering when loading flash.
     pub disable_double_buffering: bool,
+    /// Before erasing, read back the contents of every sector that would be erased, and skip the sectors which
+    /// already contain the data to be flashed. Bytes of a sector which are not written have to be erased already,
+    /// so the result is the same as without this option.
+    ///
+    /// This is faster if only a small part of a large image has changed, as reading is usually much faster than
+    /// erasing and programming. It has no effect if `do_chip_erase` is set.
+    pub skip_unchanged_sectors: bool,
 }
 
 impl DownloadOptions {
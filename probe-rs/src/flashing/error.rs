@@ -50,6 +50,28 @@ pub enum FlashError {
     /// This target does not support full chip flash erases.
     #[error("The chip erase routine is not supported with the given flash algorithm.")]
     ChipEraseNotSupported,
+    /// The flash algorithm does not have the entry point for the given routine.
+    #[error("The '{0}' routine is not supported with the given flash algorithm.")]
+    RoutineNotSupported(&'static str),
+    /// The option bytes to be written don't have the size given in the flash algorithm.
+    #[error("The option bytes have a size of {size} bytes, but the flash algorithm expects {expected} bytes.")]
+    InvalidOptionBytesSize {
+        /// The size of the given option bytes.
+        size: usize,
+        /// The size of the option bytes, as given in the flash algorithm.
+        expected: u32,
+    },
+    /// The option bytes don't fit into the page buffer of the flash algorithm.
+    #[error("The option bytes have a size of {size} bytes, which exceeds the page buffer of {page_size} bytes.")]
+    OptionBytesTooLarge {
+        /// The size of the option bytes.
+        size: usize,
+        /// The size of the page buffer of the flash algorithm.
+        page_size: u32,
+    },
+    /// The session does not have the permission for the operation.
+    #[error("An operation could not be performed because it lacked the permission to do so: {0}")]
+    MissingPermissions(String),
     /// Calling the given routine returned the given error code.
     #[error("The execution of '{name}' failed with code {error_code}. This might indicate a problem with the flash algorithm.")]
     RoutineCallFailed {
@@ -1,7 +1,11 @@
 use super::FlashError;
-use crate::{architecture::riscv, core::Architecture, Target};
+use crate::{
+    architecture::{mips, riscv},
+    core::Architecture,
+    Target,
+};
 use probe_rs_target::{FlashProperties, PageInfo, RamRegion, RawFlashAlgorithm, SectorInfo};
-use std::{cmp::max, convert::TryInto, mem::size_of_val};
+use std::{convert::TryInto, mem::size_of_val};
 
 /// A flash algorithm, which has been assembled for a specific
 /// chip.
@@ -29,6 +33,18 @@ pub struct FlashAlgorithm {
     pub pc_erase_sector: u64,
     /// Address of the `EraseAll()` entry point. Optional.
     pub pc_erase_all: Option<u64>,
+    /// Address of the `Verify()` entry point. Optional.
+    pub pc_verify: Option<u64>,
+    /// Address of the `BlankCheck()` entry point. Optional.
+    pub pc_blank_check: Option<u64>,
+    /// Address of the `ReadOptionBytes()` entry point. Optional.
+    pub pc_read_option_bytes: Option<u64>,
+    /// Address of the `WriteOptionBytes()` entry point. Optional.
+    pub pc_write_option_bytes: Option<u64>,
+    /// Address of the `Protect()` entry point. Optional.
+    pub pc_protect: Option<u64>,
+    /// Address of the `Unprotect()` entry point. Optional.
+    pub pc_unprotect: Option<u64>,
     /// Initial value of the R9 register for calling flash algo entry points, which
     /// determines where the position-independent data resides.
     pub static_base: u64,
@@ -163,6 +179,8 @@ impl FlashAlgorithm {
     // Header for RISCV Flash Algorithms
     const RISCV_FLASH_BLOB_HEADER: [u32; 2] = [riscv::assembly::EBREAK, riscv::assembly::EBREAK];
 
+    const MIPS_FLASH_BLOB_HEADER: [u32; 2] = [mips::assembly::SDBBP, mips::assembly::NOP];
+
     const ARM_FLASH_BLOB_HEADER: [u32; 8] = [
         0xE00A_BE00,
         0x062D_780D,
@@ -177,16 +195,21 @@ impl FlashAlgorithm {
     /// When the target architecture is not known, and we need to allocate space for the header,
     /// this function returns the maximum size of the header of supported architectures.
     pub fn get_max_algorithm_header_size() -> u64 {
-        max(
+        [
             size_of_val(&Self::ARM_FLASH_BLOB_HEADER),
             size_of_val(&Self::RISCV_FLASH_BLOB_HEADER),
-        ) as u64
+            size_of_val(&Self::MIPS_FLASH_BLOB_HEADER),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default() as u64
     }
 
     fn get_algorithm_header(architecture: Architecture) -> &'static [u32] {
         match architecture {
             Architecture::Arm => &Self::ARM_FLASH_BLOB_HEADER,
             Architecture::Riscv => &Self::RISCV_FLASH_BLOB_HEADER,
+            Architecture::Mips => &Self::MIPS_FLASH_BLOB_HEADER,
         }
     }
 
@@ -306,6 +329,12 @@ impl FlashAlgorithm {
             pc_program_page: code_start + raw.pc_program_page,
             pc_erase_sector: code_start + raw.pc_erase_sector,
             pc_erase_all: raw.pc_erase_all.map(|v| code_start + v),
+            pc_verify: raw.pc_verify.map(|v| code_start + v),
+            pc_blank_check: raw.pc_blank_check.map(|v| code_start + v),
+            pc_read_option_bytes: raw.pc_read_option_bytes.map(|v| code_start + v),
+            pc_write_option_bytes: raw.pc_write_option_bytes.map(|v| code_start + v),
+            pc_protect: raw.pc_protect.map(|v| code_start + v),
+            pc_unprotect: raw.pc_unprotect.map(|v| code_start + v),
             static_base: code_start + raw.data_section_offset,
             begin_stack: addr_stack,
             begin_data: page_buffers[0],
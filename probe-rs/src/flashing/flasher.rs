@@ -1,6 +1,7 @@
 use probe_rs_target::{MemoryRegion, RawFlashAlgorithm};
 use tracing::Level;
 
+use super::checksum::{self, CRC32_POLYNOMIAL};
 use super::{
     FlashAlgorithm, FlashBuilder, FlashError, FlashFill, FlashLayout, FlashPage, FlashProgress,
 };
@@ -277,6 +278,9 @@ impl<'session> Flasher<'session> {
     /// If `restore_unwritten_bytes` is `true`, all bytes of a sector,
     /// that are not to be written during flashing will be read from the flash first
     /// and written again once the sector is erased.
+    ///
+    /// If `skip_unchanged_sectors` is `true`, sectors which already contain the data to be written
+    /// are neither erased nor programmed.
     pub(super) fn program(
         &mut self,
         region: &NvmRegion,
@@ -284,6 +288,7 @@ impl<'session> Flasher<'session> {
         restore_unwritten_bytes: bool,
         enable_double_buffering: bool,
         skip_erasing: bool,
+        skip_unchanged_sectors: bool,
     ) -> Result<(), FlashError> {
         tracing::debug!("Starting program procedure.");
         // Convert the list of flash operations into flash sectors and pages.
@@ -323,6 +328,19 @@ impl<'session> Flasher<'session> {
         // We successfully finished filling.
         self.progress.finished_filling();
 
+        if skip_unchanged_sectors {
+            self.remove_unchanged_sectors(&mut flash_layout)?;
+
+            if flash_layout.sectors().is_empty() {
+                tracing::info!("The flash contents are unchanged, skipping erase and program.");
+                self.progress.started_erasing();
+                self.progress.finished_erasing();
+                self.progress.started_programming();
+                self.progress.finished_programming();
+                return Ok(());
+            }
+        }
+
         // Skip erase if necessary
         if !skip_erasing {
             // Erase all necessary sectors
@@ -359,6 +377,89 @@ impl<'session> Flasher<'session> {
         })
     }
 
+    /// Verify that the flash in `region` contains the data of `flash_builder`.
+    pub(super) fn verify(
+        &mut self,
+        region: &NvmRegion,
+        flash_builder: &FlashBuilder,
+    ) -> Result<(), FlashError> {
+        self.run_verify(|active| {
+            for (address, data) in flash_builder.data_in_range(&region.range) {
+                tracing::debug!(
+                    "    verifying: {:08x}-{:08x} ({} bytes)",
+                    address,
+                    address + data.len() as u64,
+                    data.len()
+                );
+
+                if !active.contains(address, data)? {
+                    return Err(FlashError::Verify);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Compare the sectors in `flash_layout` with the current flash contents,
+    /// and remove the sectors and pages which don't have to be erased and programmed.
+    ///
+    /// Bytes of a sector which are not covered by any page would be erased, so they
+    /// have to contain the erased byte value for the sector to be unchanged.
+    fn remove_unchanged_sectors(
+        &mut self,
+        flash_layout: &mut FlashLayout,
+    ) -> Result<(), FlashError> {
+        let erased_byte_value = self.flash_algorithm().flash_properties.erased_byte_value;
+
+        let changed_sectors = self.run_verify(|active| {
+            let mut changed_sectors = Vec::with_capacity(flash_layout.sectors().len());
+            for sector in flash_layout.sectors() {
+                let sector_end = sector.address() + sector.size();
+                let mut expected = vec![erased_byte_value; sector.size() as usize];
+                for page in flash_layout.pages() {
+                    // Only the part of the page inside this sector.
+                    let start = sector.address().max(page.address());
+                    let end = sector_end.min(page.address() + page.size() as u64);
+                    if start >= end {
+                        continue;
+                    }
+
+                    expected
+                        [(start - sector.address()) as usize..(end - sector.address()) as usize]
+                        .copy_from_slice(
+                            &page.data()[(start - page.address()) as usize
+                                ..(end - page.address()) as usize],
+                        );
+                }
+
+                let mut current = vec![0; expected.len()];
+                active
+                    .core
+                    .read(sector.address(), &mut current)
+                    .map_err(FlashError::Core)?;
+
+                changed_sectors.push(current != expected);
+            }
+            Ok(changed_sectors)
+        })?;
+
+        let (skipped_sectors, skipped_pages) =
+            flash_layout.remove_unchanged_sectors(&changed_sectors);
+        tracing::debug!(
+            "Skipping {} unchanged sectors, {} sectors remain to be flashed.",
+            skipped_sectors.len(),
+            flash_layout.sectors().len()
+        );
+        for sector in skipped_sectors {
+            self.progress.sector_skipped(sector.size());
+        }
+        for page in skipped_pages {
+            self.progress.page_skipped(page.size());
+        }
+
+        Ok(())
+    }
+
     /// Programs the pages given in `flash_layout` into the flash.
     fn program_simple(&mut self, flash_layout: &FlashLayout) -> Result<(), FlashError> {
         self.progress.started_programming();
@@ -602,6 +703,17 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         Ok(())
     }
 
+    /// Make sure `size` bytes of option bytes fit into the page buffer of the flash algorithm.
+    fn check_option_bytes_size(&self, size: usize) -> Result<(), FlashError> {
+        let page_size = self.flash_algorithm.flash_properties.page_size;
+
+        if size > page_size as usize {
+            return Err(FlashError::OptionBytesTooLarge { size, page_size });
+        }
+
+        Ok(())
+    }
+
     fn call_function_and_wait(
         &mut self,
         registers: &Registers,
@@ -893,6 +1005,109 @@ impl<'p> ActiveFlasher<'p, Program> {
         }
     }
 
+    /// Program `data` as option bytes, using the `WriteOptionBytes()` entry point.
+    pub(super) fn write_option_bytes(&mut self, data: &[u8]) -> Result<(), FlashError> {
+        let pc_write_option_bytes = self
+            .flash_algorithm
+            .pc_write_option_bytes
+            .ok_or(FlashError::RoutineNotSupported("write_option_bytes"))?;
+        self.check_option_bytes_size(data.len())?;
+        let buffer_address = self.flash_algorithm.begin_data;
+
+        self.core
+            .write_8(buffer_address, data)
+            .map_err(FlashError::Core)?;
+
+        let result = self.call_function_and_wait(
+            &Registers {
+                pc: into_reg(pc_write_option_bytes)?,
+                r0: Some(into_reg(buffer_address)?),
+                r1: Some(data.len() as u32),
+                r2: None,
+                r3: None,
+            },
+            false,
+            Duration::from_millis(
+                self.flash_algorithm.flash_properties.erase_sector_timeout as u64,
+            ),
+        )?;
+
+        if result != 0 {
+            Err(FlashError::RoutineCallFailed {
+                name: "write_option_bytes",
+                error_code: result,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enable readout protection with the device specific `level`, using the `Protect()` entry point.
+    pub(super) fn protect(&mut self, level: u32) -> Result<(), FlashError> {
+        tracing::info!("Enabling readout protection, level {}", level);
+
+        let pc_protect = self
+            .flash_algorithm
+            .pc_protect
+            .ok_or(FlashError::RoutineNotSupported("protect"))?;
+
+        let result = self.call_function_and_wait(
+            &Registers {
+                pc: into_reg(pc_protect)?,
+                r0: Some(level),
+                r1: None,
+                r2: None,
+                r3: None,
+            },
+            false,
+            Duration::from_millis(
+                self.flash_algorithm.flash_properties.erase_sector_timeout as u64,
+            ),
+        )?;
+
+        if result != 0 {
+            Err(FlashError::RoutineCallFailed {
+                name: "protect",
+                error_code: result,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Disable readout protection, using the `Unprotect()` entry point.
+    ///
+    /// This usually erases the entire flash, so the timeout is the same as for a chip erase.
+    pub(super) fn unprotect(&mut self) -> Result<(), FlashError> {
+        tracing::info!("Disabling readout protection");
+
+        let pc_unprotect = self
+            .flash_algorithm
+            .pc_unprotect
+            .ok_or(FlashError::RoutineNotSupported("unprotect"))?;
+
+        let result = self.call_function_and_wait(
+            &Registers {
+                pc: into_reg(pc_unprotect)?,
+                r0: None,
+                r1: None,
+                r2: None,
+                r3: None,
+            },
+            false,
+            Duration::from_secs(30),
+        )?;
+
+        if result != 0 {
+            Err(FlashError::RoutineCallFailed {
+                name: "unprotect",
+                error_code: result,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
     pub(super) fn start_program_page_with_buffer(
         &mut self,
         address: u64,
@@ -961,3 +1176,187 @@ impl<'p> ActiveFlasher<'p, Program> {
         Ok(())
     }
 }
+
+impl<'p> ActiveFlasher<'p, Verify> {
+    /// The number of bytes checked by a single call of the CRC32 routine.
+    const CRC32_CHUNK_SIZE: usize = 0x1_0000;
+
+    /// Check if the flash at `address` contains `data`.
+    ///
+    /// The `Verify()` entry point of the flash algorithm is used if it is available. Otherwise,
+    /// a CRC32 of the flash contents is calculated on the target, and compared to the CRC32 of `data`.
+    /// The flash contents are only read back if there is no CRC32 routine for the core.
+    pub(super) fn contains(&mut self, address: u64, data: &[u8]) -> Result<bool, FlashError> {
+        if let Some(pc_verify) = self.flash_algorithm.pc_verify {
+            return self.verify_with_algorithm(pc_verify, address, data);
+        }
+
+        if let Some(routine) = checksum::crc32_routine(self.core.instruction_set()?) {
+            // The routine is loaded into the page buffer, which has to be large enough.
+            if routine.len() <= self.flash_algorithm.flash_properties.page_size as usize {
+                return self.verify_with_crc32(&routine, address, data);
+            }
+        }
+
+        let mut current = vec![0; data.len()];
+        self.core
+            .read(address, &mut current)
+            .map_err(FlashError::Core)?;
+
+        Ok(current == data)
+    }
+
+    /// Check if `size` bytes starting at `address` are erased.
+    ///
+    /// The `BlankCheck()` entry point of the flash algorithm is used if it is available.
+    /// Otherwise, the flash contents are compared with the erased byte value.
+    pub(super) fn is_blank(&mut self, address: u64, size: u64) -> Result<bool, FlashError> {
+        let erased_byte_value = self.flash_algorithm.flash_properties.erased_byte_value;
+
+        let Some(pc_blank_check) = self.flash_algorithm.pc_blank_check else {
+            return self.contains(address, &vec![erased_byte_value; size as usize]);
+        };
+
+        let result = self.call_function_and_wait(
+            &Registers {
+                pc: into_reg(pc_blank_check)?,
+                r0: Some(into_reg(address)?),
+                r1: Some(into_reg(size)?),
+                r2: Some(erased_byte_value as u32),
+                r3: None,
+            },
+            false,
+            Duration::from_millis(
+                self.flash_algorithm.flash_properties.erase_sector_timeout as u64,
+            ),
+        )?;
+
+        Ok(result == 0)
+    }
+
+    /// Read `size` bytes of option bytes, using the `ReadOptionBytes()` entry point.
+    pub(super) fn read_option_bytes(&mut self, size: u32) -> Result<Vec<u8>, FlashError> {
+        let pc_read_option_bytes = self
+            .flash_algorithm
+            .pc_read_option_bytes
+            .ok_or(FlashError::RoutineNotSupported("read_option_bytes"))?;
+        self.check_option_bytes_size(size as usize)?;
+        let buffer_address = self.flash_algorithm.begin_data;
+
+        let result = self.call_function_and_wait(
+            &Registers {
+                pc: into_reg(pc_read_option_bytes)?,
+                r0: Some(into_reg(buffer_address)?),
+                r1: Some(size),
+                r2: None,
+                r3: None,
+            },
+            false,
+            Duration::from_secs(2),
+        )?;
+
+        if result != 0 {
+            return Err(FlashError::RoutineCallFailed {
+                name: "read_option_bytes",
+                error_code: result,
+            });
+        }
+
+        let mut option_bytes = vec![0; size as usize];
+        self.core
+            .read(buffer_address, &mut option_bytes)
+            .map_err(FlashError::Core)?;
+
+        Ok(option_bytes)
+    }
+
+    /// Compare `data` page by page with the flash contents, by calling the `Verify()` entry point.
+    ///
+    /// The entry point returns the end address of the compared range if the contents match.
+    fn verify_with_algorithm(
+        &mut self,
+        pc_verify: u64,
+        address: u64,
+        data: &[u8],
+    ) -> Result<bool, FlashError> {
+        let page_size = self.flash_algorithm.flash_properties.page_size as usize;
+        let buffer_address = self.flash_algorithm.begin_data;
+
+        for (index, chunk) in data.chunks(page_size).enumerate() {
+            let chunk_address = address + (index * page_size) as u64;
+
+            self.core
+                .write_8(buffer_address, chunk)
+                .map_err(FlashError::Core)?;
+
+            let result = self.call_function_and_wait(
+                &Registers {
+                    pc: into_reg(pc_verify)?,
+                    r0: Some(into_reg(chunk_address)?),
+                    r1: Some(chunk.len() as u32),
+                    r2: Some(into_reg(buffer_address)?),
+                    r3: None,
+                },
+                false,
+                Duration::from_millis(
+                    self.flash_algorithm.flash_properties.program_page_timeout as u64,
+                ),
+            )?;
+
+            let end_address = into_reg(chunk_address + chunk.len() as u64)?;
+            if result != end_address {
+                tracing::debug!(
+                    "Verify() reported a mismatch in the page at {:#010x}, returned {:#010x}",
+                    chunk_address,
+                    result
+                );
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Compare the CRC32 of `data` with the CRC32 of the flash contents, calculated by `routine` on the target.
+    fn verify_with_crc32(
+        &mut self,
+        routine: &[u8],
+        address: u64,
+        data: &[u8],
+    ) -> Result<bool, FlashError> {
+        let routine_address = self.flash_algorithm.begin_data;
+
+        self.core
+            .write_8(routine_address, routine)
+            .map_err(FlashError::Core)?;
+
+        for (index, chunk) in data.chunks(Self::CRC32_CHUNK_SIZE).enumerate() {
+            let chunk_address = address + (index * Self::CRC32_CHUNK_SIZE) as u64;
+
+            let result = self.call_function_and_wait(
+                &Registers {
+                    pc: into_reg(routine_address)?,
+                    r0: Some(into_reg(chunk_address)?),
+                    r1: Some(chunk.len() as u32),
+                    r2: Some(CRC32_POLYNOMIAL),
+                    r3: None,
+                },
+                false,
+                Duration::from_secs(5),
+            )?;
+
+            let expected = checksum::crc32(chunk);
+            if result != expected {
+                tracing::debug!(
+                    "CRC32 mismatch at {:#010x}: expected {:#010x}, flash contains {:#010x}",
+                    chunk_address,
+                    expected,
+                    result
+                );
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
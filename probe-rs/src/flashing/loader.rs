@@ -387,7 +387,7 @@ impl FlashLoader {
                 do_use_double_buffering = false;
             }
 
-            for region in regions {
+            for region in &regions {
                 tracing::debug!(
                     "    programming region: {:08x}-{:08x} ({} bytes)",
                     region.range.start,
@@ -397,13 +397,23 @@ impl FlashLoader {
 
                 // Program the data.
                 flasher.program(
-                    &region,
+                    region,
                     &self.builder,
                     options.keep_unwritten_bytes,
                     do_use_double_buffering,
                     options.skip_erase || do_chip_erase,
+                    options.skip_unchanged_sectors && !do_chip_erase,
                 )?;
             }
+
+            // Verify the flash contents while the flash algorithm is still loaded,
+            // so that it can be checked on the target instead of being read back.
+            if options.verify {
+                tracing::debug!("Verifying ranges for algo: {}", algo_name);
+                for region in &regions {
+                    flasher.verify(region, &self.builder)?;
+                }
+            }
         }
 
         tracing::debug!("committing RAM!");
@@ -463,6 +473,12 @@ impl FlashLoader {
                     .target()
                     .get_memory_region_by_address(address)
                     .unwrap();
+
+                // NVM regions have already been verified after flashing them.
+                if let MemoryRegion::Nvm(_) = associated_region {
+                    continue;
+                }
+
                 let core_name = match associated_region {
                     MemoryRegion::Ram(r) => &r.cores,
                     MemoryRegion::Generic(r) => &r.cores,
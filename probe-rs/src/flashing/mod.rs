@@ -44,6 +44,7 @@
 //!
 
 mod builder;
+mod checksum;
 mod download;
 mod erase;
 mod error;
@@ -51,6 +52,7 @@ mod flash_algorithm;
 mod flasher;
 mod loader;
 mod progress;
+mod protection;
 mod visualizer;
 
 use builder::*;
@@ -63,4 +65,5 @@ pub use error::*;
 pub use flash_algorithm::*;
 pub use loader::*;
 pub use progress::*;
+pub use protection::*;
 pub use visualizer::*;
@@ -67,6 +67,16 @@ impl FlashProgress {
         self.emit(ProgressEvent::PageFilled { size, time });
     }
 
+    /// Signalize that a sector was skipped, because its contents are unchanged.
+    pub(super) fn sector_skipped(&self, size: u64) {
+        self.emit(ProgressEvent::SectorSkipped { size });
+    }
+
+    /// Signalize that a page was skipped, because its contents are unchanged.
+    pub(super) fn page_skipped(&self, size: u32) {
+        self.emit(ProgressEvent::PageSkipped { size });
+    }
+
     /// Signalize that the programming procedure failed.
     pub(super) fn failed_programming(&self) {
         self.emit(ProgressEvent::FailedProgramming);
@@ -112,6 +122,8 @@ impl FlashProgress {
 /// * `StartedFilling`
 /// * `PageFilled` for every page
 /// * `FinishedFilling`
+/// * `SectorSkipped` and `PageSkipped` for every unchanged sector and page, if
+///   [`DownloadOptions::skip_unchanged_sectors`](super::DownloadOptions::skip_unchanged_sectors) is set
 /// * `StartedErasing`
 /// * `SectorErased` for every sector
 /// * `FinishedErasing`
@@ -144,6 +156,16 @@ pub enum ProgressEvent {
     FailedFilling,
     /// Filling of the pages has finished successfully.
     FinishedFilling,
+    /// A sector is not erased, because it already contains the data to be flashed.
+    SectorSkipped {
+        /// The size of the sector in bytes.
+        size: u64,
+    },
+    /// A page is not programmed, because it is located in a skipped sector.
+    PageSkipped {
+        /// The size of the page in bytes.
+        size: u32,
+    },
     /// Erasing of flash has started.
     StartedErasing,
     /// A sector has been erased successfully.
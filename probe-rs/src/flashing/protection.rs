@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use probe_rs_target::{MemoryRange, MemoryRegion, NvmRegion, RawFlashAlgorithm};
+
+use crate::flashing::{flasher::Flasher, FlashError, FlashLoader};
+use crate::session::MissingPermissions;
+use crate::Session;
+
+use super::FlashProgress;
+
+/// Check if all nonvolatile memory is erased.
+///
+/// The `BlankCheck()` entry point of the flash algorithm is used if it is available,
+/// otherwise the flash contents are compared with the erased byte value.
+pub fn blank_check(
+    session: &mut Session,
+    progress: Option<FlashProgress>,
+) -> Result<bool, FlashError> {
+    tracing::debug!("Checking if the flash is blank...");
+
+    let mut algos: HashMap<(String, String), Vec<NvmRegion>> = HashMap::new();
+    for region in &session.target().memory_map {
+        if let MemoryRegion::Nvm(region) = region {
+            let algo = FlashLoader::get_flash_algorithm_for_region(region, session.target())?;
+
+            // Get the first core that can access the region
+            let core_name = region
+                .cores
+                .first()
+                .ok_or_else(|| FlashError::NoNvmCoreAccess(region.clone()))?;
+
+            let entry = algos
+                .entry((algo.name.clone(), core_name.clone()))
+                .or_default();
+            entry.push(region.clone());
+        }
+    }
+
+    for ((algo_name, core_name), regions) in algos {
+        tracing::debug!("Blank check with algorithm: {}", algo_name);
+
+        // This can't fail, algo_name comes from the target.
+        let algo = session.target().flash_algorithm_by_name(&algo_name);
+        let algo = algo.unwrap().clone();
+
+        let core_index = session.target().core_index_by_name(&core_name).unwrap();
+        let mut flasher = Flasher::new(session, core_index, &algo, progress.clone())?;
+
+        let sectors = flasher
+            .flash_algorithm()
+            .iter_sectors()
+            .filter(|info| {
+                let range = info.base_address..info.base_address + info.size;
+                regions.iter().any(|r| r.range.contains_range(&range))
+            })
+            .collect::<Vec<_>>();
+
+        let blank = flasher.run_verify(|active| {
+            for info in sectors {
+                if !active.is_blank(info.base_address, info.size)? {
+                    tracing::debug!(
+                        "    sector: {:08x}-{:08x} ({} bytes) is not blank",
+                        info.base_address,
+                        info.base_address + info.size,
+                        info.size
+                    );
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        })?;
+
+        if !blank {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Read the option bytes (or fuses) of the chip.
+///
+/// This requires a flash algorithm with a `ReadOptionBytes()` entry point, and the size of the option bytes.
+pub fn read_option_bytes(
+    session: &mut Session,
+    progress: Option<FlashProgress>,
+) -> Result<Vec<u8>, FlashError> {
+    let (algo, core_index) = flash_algorithm_with(session, "read_option_bytes", |algo| {
+        algo.option_bytes_size.and(algo.pc_read_option_bytes)
+    })?;
+    let size = algo.option_bytes_size.unwrap_or_default();
+
+    let mut flasher = Flasher::new(session, core_index, &algo, progress)?;
+    flasher.run_verify(|active| active.read_option_bytes(size))
+}
+
+/// Write the option bytes (or fuses) of the chip.
+///
+/// This requires a flash algorithm with a `WriteOptionBytes()` entry point, and `data` has to have
+/// the size of the option bytes given in the flash algorithm. The option bytes are passed to the
+/// flash algorithm in its page buffer, so they can't be larger than a page.
+///
+/// Option bytes can enable readout protection, so the session needs the [`Permissions::allow_protect`] permission.
+///
+/// [`Permissions::allow_protect`]: crate::Permissions::allow_protect
+pub fn write_option_bytes(
+    session: &mut Session,
+    data: &[u8],
+    progress: Option<FlashProgress>,
+) -> Result<(), FlashError> {
+    session
+        .permissions()
+        .protect()
+        .map_err(|MissingPermissions(permission)| FlashError::MissingPermissions(permission))?;
+
+    let (algo, core_index) = flash_algorithm_with(session, "write_option_bytes", |algo| {
+        algo.option_bytes_size.and(algo.pc_write_option_bytes)
+    })?;
+    let expected = algo.option_bytes_size.unwrap_or_default();
+    if data.len() != expected as usize {
+        return Err(FlashError::InvalidOptionBytesSize {
+            size: data.len(),
+            expected,
+        });
+    }
+
+    let mut flasher = Flasher::new(session, core_index, &algo, progress)?;
+    flasher.run_program(|active| active.write_option_bytes(data))
+}
+
+/// Enable readout protection of the chip.
+///
+/// The meaning of `level` is device specific. This requires a flash algorithm with a `Protect()` entry point,
+/// and the [`Permissions::allow_protect`] permission.
+///
+/// # Warning
+/// Depending on the device and the `level`, this can permanently lock the debug access to the chip.
+///
+/// [`Permissions::allow_protect`]: crate::Permissions::allow_protect
+pub fn protect(
+    session: &mut Session,
+    level: u32,
+    progress: Option<FlashProgress>,
+) -> Result<(), FlashError> {
+    session
+        .permissions()
+        .protect()
+        .map_err(|MissingPermissions(permission)| FlashError::MissingPermissions(permission))?;
+
+    let (algo, core_index) = flash_algorithm_with(session, "protect", |algo| algo.pc_protect)?;
+
+    let mut flasher = Flasher::new(session, core_index, &algo, progress)?;
+    flasher.run_program(|active| active.protect(level))
+}
+
+/// Disable readout protection of the chip.
+///
+/// On most devices this erases the entire flash, so it requires the [`Permissions::allow_erase_all`] permission,
+/// as well as a flash algorithm with an `Unprotect()` entry point.
+///
+/// [`Permissions::allow_erase_all`]: crate::Permissions::allow_erase_all
+pub fn unprotect(session: &mut Session, progress: Option<FlashProgress>) -> Result<(), FlashError> {
+    session
+        .permissions()
+        .erase_all()
+        .map_err(|MissingPermissions(permission)| FlashError::MissingPermissions(permission))?;
+
+    let (algo, core_index) = flash_algorithm_with(session, "unprotect", |algo| algo.pc_unprotect)?;
+
+    let mut flasher = Flasher::new(session, core_index, &algo, progress)?;
+    flasher.run_program(|active| active.unprotect())
+}
+
+/// Find the flash algorithm of an NVM region which has the entry point selected by `entry_point`,
+/// and the index of the core which can run it.
+fn flash_algorithm_with(
+    session: &Session,
+    routine: &'static str,
+    entry_point: impl Fn(&RawFlashAlgorithm) -> Option<u64>,
+) -> Result<(RawFlashAlgorithm, usize), FlashError> {
+    let target = session.target();
+
+    for region in &target.memory_map {
+        if let MemoryRegion::Nvm(region) = region {
+            let algo = FlashLoader::get_flash_algorithm_for_region(region, target)?;
+            if entry_point(algo).is_none() {
+                continue;
+            }
+
+            let core_name = region
+                .cores
+                .first()
+                .ok_or_else(|| FlashError::NoNvmCoreAccess(region.clone()))?;
+            let core_index = target.core_index_by_name(core_name).unwrap();
+
+            tracing::debug!("Using algorithm {} for {}", algo.name, routine);
+
+            return Ok((algo.clone(), core_index));
+        }
+    }
+
+    Err(FlashError::RoutineNotSupported(routine))
+}
@@ -2,6 +2,7 @@ use crate::{CoreType, Error, Session};
 use anyhow::Result;
 
 use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::Duration;
 
@@ -19,6 +20,8 @@ pub struct GdbInstanceConfiguration {
     pub cores: Vec<usize>,
     /// The list of [SocketAddr] addresses to bind to
     pub socket_addrs: Vec<SocketAddr>,
+    /// Directory with the files the cores may access through semihosting
+    pub semihosting_root: Option<PathBuf>,
 }
 
 impl GdbInstanceConfiguration {
@@ -65,6 +68,7 @@ impl GdbInstanceConfiguration {
                 core_type: *core_type,
                 cores: cores.to_vec(),
                 socket_addrs: adjust_addrs(&addrs, i),
+                semihosting_root: None,
             })
             .collect();
 
@@ -89,7 +93,12 @@ pub fn run<'a>(
     // Turn our group list into GDB targets
     let mut targets = instances
         .map(|instance| {
-            target::RuntimeTarget::new(session, instance.cores.to_vec(), &instance.socket_addrs[..])
+            target::RuntimeTarget::new(
+                session,
+                instance.cores.to_vec(),
+                &instance.socket_addrs[..],
+                instance.semihosting_root.clone(),
+            )
         })
         .collect::<Result<Vec<target::RuntimeTarget>, Error>>()?;
 
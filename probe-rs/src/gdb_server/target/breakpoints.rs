@@ -0,0 +1,239 @@
+use super::{GdbErrorExt, RuntimeTarget};
+
+use crate::config::MemoryRegion;
+use crate::{CoreType, Error, InstructionSet, MemoryInterface, WatchpointAccess};
+use gdbstub::target::ext::breakpoints::{
+    Breakpoints, HwBreakpoint, HwBreakpointOps, HwWatchpoint, HwWatchpointOps, SwBreakpoint,
+    SwBreakpointOps, WatchKind,
+};
+use gdbstub::target::TargetResult;
+
+/// A software breakpoint requested by GDB
+pub(crate) enum SoftwareBreakpoint {
+    /// A breakpoint instruction was written to RAM. `original` holds the replaced bytes.
+    Patched { original: Vec<u8> },
+    /// The address is not in RAM, so a hardware breakpoint is used instead.
+    Hardware,
+}
+
+impl Breakpoints for RuntimeTarget<'_> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_breakpoint(&mut self) -> Option<HwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_watchpoint(&mut self) -> Option<HwWatchpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SwBreakpoint for RuntimeTarget<'_> {
+    fn add_sw_breakpoint(
+        &mut self,
+        addr: u64,
+        kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        if self.sw_breakpoints.contains_key(&addr) {
+            return Ok(true);
+        }
+
+        let mut session = self.session.lock().unwrap();
+
+        let in_ram = matches!(
+            session.target().get_memory_region_by_address(addr),
+            Some(MemoryRegion::Ram(_))
+        );
+
+        if !in_ram {
+            // Code in flash can't be patched, fall back to a hardware breakpoint.
+            for core_id in &self.cores {
+                let mut core = session.core(*core_id).into_target_result()?;
+
+                core.set_hw_breakpoint(addr).into_target_result()?;
+            }
+
+            self.sw_breakpoints
+                .insert(addr, SoftwareBreakpoint::Hardware);
+
+            return Ok(true);
+        }
+
+        // Memory is shared between the cores, so it is sufficient to patch it once.
+        let mut core = session.core(self.cores[0]).into_target_result()?;
+
+        let instruction_set = core.instruction_set().into_target_result()?;
+        let Some(instruction) = breakpoint_instruction(core.core_type(), instruction_set, kind)
+        else {
+            tracing::warn!(
+                "No software breakpoint instruction of kind {} for {:?}",
+                kind,
+                core.core_type()
+            );
+            return Ok(false);
+        };
+
+        let mut original = vec![0; instruction.len()];
+        core.read(addr, &mut original).into_target_result()?;
+        core.write_8(addr, instruction).into_target_result()?;
+
+        // The breakpoint instruction has to halt the core instead of raising an exception.
+        match core.debug_on_sw_breakpoint(true) {
+            Ok(()) | Err(Error::NotImplemented(_)) => {}
+            Err(e) => return Err(e).into_target_result(),
+        }
+
+        self.sw_breakpoints
+            .insert(addr, SoftwareBreakpoint::Patched { original });
+
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        let Some(breakpoint) = self.sw_breakpoints.remove(&addr) else {
+            return Ok(false);
+        };
+
+        let mut session = self.session.lock().unwrap();
+
+        match breakpoint {
+            SoftwareBreakpoint::Patched { original } => {
+                let mut core = session.core(self.cores[0]).into_target_result()?;
+
+                core.write_8(addr, &original).into_target_result()?;
+            }
+            SoftwareBreakpoint::Hardware => {
+                for core_id in &self.cores {
+                    let mut core = session.core(*core_id).into_target_result()?;
+
+                    core.clear_hw_breakpoint(addr).into_target_result()?;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl HwBreakpoint for RuntimeTarget<'_> {
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.set_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+}
+
+impl HwWatchpoint for RuntimeTarget<'_> {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        len: u64,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        let access = match kind {
+            WatchKind::Write => WatchpointAccess::Write,
+            WatchKind::Read => WatchpointAccess::Read,
+            WatchKind::ReadWrite => WatchpointAccess::ReadWrite,
+        };
+
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            match core.set_watchpoint(addr, len, access) {
+                Ok(()) => {}
+                // The range is not supported by the hardware, or no unit is free.
+                // GDB will fall back to software watchpoints in this case.
+                Err(e @ Error::NotImplemented(_)) | Err(e @ Error::Other(_)) => {
+                    tracing::warn!("Unable to set watchpoint: {}", e);
+                    return Ok(false);
+                }
+                Err(e) => return Err(e).into_target_result(),
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        _len: u64,
+        _kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_watchpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Get the breakpoint instruction which halts a core, for a GDB breakpoint `kind`.
+///
+/// The kind is the size of the replaced instruction, with 3 denoting a 32-bit Thumb-2
+/// or microMIPS instruction. These are replaced by a 16-bit breakpoint instruction.
+fn breakpoint_instruction(
+    core_type: CoreType,
+    instruction_set: InstructionSet,
+    kind: usize,
+) -> Option<&'static [u8]> {
+    match (core_type, kind) {
+        // BKPT
+        (CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m, 2 | 3) => {
+            Some(&[0x00, 0xbe])
+        }
+        (CoreType::Armv7a, 2 | 3) => Some(&[0x00, 0xbe]),
+        (CoreType::Armv7a, 4) => Some(&[0x70, 0x00, 0x20, 0xe1]),
+        // HLT, BKPT would raise an exception instead of halting
+        (CoreType::Armv8a, 4) if instruction_set == InstructionSet::A64 => {
+            Some(&[0x00, 0x00, 0x40, 0xd4])
+        }
+        (CoreType::Armv8a, 2 | 3) => Some(&[0x80, 0xba]),
+        (CoreType::Armv8a, 4) => Some(&[0x70, 0x00, 0x00, 0xe1]),
+        // c.ebreak and ebreak
+        (CoreType::Riscv, 2) => Some(&[0x02, 0x90]),
+        (CoreType::Riscv, 4) => Some(&[0x73, 0x00, 0x10, 0x00]),
+        // SDBBP16 and SDBBP
+        (CoreType::Mips32, 2 | 3) => Some(&[0xc0, 0x46]),
+        (CoreType::Mips32, 4) => Some(&[0x3f, 0x00, 0x00, 0x70]),
+        _ => None,
+    }
+}
@@ -87,16 +87,20 @@ mod probe;
 #[cfg(feature = "rtt")]
 pub mod rtt;
 #[warn(missing_docs)]
+pub mod semihosting;
+#[warn(missing_docs)]
 mod session;
 
 pub use crate::config::{CoreType, InstructionSet, Target};
 pub use crate::core::{
-    Architecture, BreakpointCause, Core, CoreInformation, CoreInterface, CoreRegister,
-    CoreRegisters, CoreState, CoreStatus, HaltReason, MemoryMappedRegister, RegisterId,
-    RegisterRole, RegisterValue, SemihostingCommand, SpecificCoreState, VectorCatchCondition,
+    Architecture, BreakpointCause, Core, CoreDump, CoreDumpError, CoreInformation, CoreInterface,
+    CoreRegister, CoreRegisters, CoreState, CoreStatus, HaltReason, MemoryMappedRegister,
+    RegisterId, RegisterRole, RegisterValue, SemihostingCommand, SpecificCoreState,
+    VectorCatchCondition, Watchpoint, WatchpointAccess, WatchpointCause,
 };
 pub use crate::error::Error;
 pub use crate::memory::MemoryInterface;
+pub use crate::probe::remote::{ProbeServer, RemoteProbeError};
 pub use crate::probe::{
     AttachMethod, DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeSelector, DebugProbeType,
     Probe, ProbeCreationError, WireProtocol,
@@ -104,4 +108,4 @@ pub use crate::probe::{
 pub use crate::session::{Permissions, Session};
 
 // TODO: Hide behind feature
-pub use crate::probe::fake_probe::FakeProbe;
+pub use crate::probe::fake_probe::{FakeProbe, SimulatedTarget};
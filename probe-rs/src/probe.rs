@@ -7,10 +7,12 @@ pub(crate) mod fake_probe;
 #[cfg(feature = "ftdi")]
 pub(crate) mod ftdi;
 pub(crate) mod jlink;
+pub(crate) mod remote;
 pub(crate) mod stlink;
 
 use self::espusbjtag::list_espjtag_devices;
 use crate::architecture::arm::ArmError;
+use crate::architecture::mips::communication_interface::MipsError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::error::Error;
 use crate::Session;
@@ -25,6 +27,7 @@ use crate::{
             sequences::{ArmDebugSequence, DefaultArmSequence},
             PortType, SwoAccess,
         },
+        mips::communication_interface::MipsCommunicationInterface,
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     Permissions,
@@ -263,6 +266,13 @@ impl Probe {
     /// about all probes available.
     #[tracing::instrument(skip_all)]
     pub fn open(selector: impl Into<DebugProbeSelector> + Clone) -> Result<Self, DebugProbeError> {
+        let selector = selector.into();
+        if selector.remote_address.is_some() {
+            // Remote probes are never found locally, so don't try the USB drivers.
+            let probe = remote::RemoteProbe::new_from_selector(selector)?;
+            return Ok(Probe::from_specific_probe(probe));
+        }
+
         match cmsisdap::CmsisDap::new_from_selector(selector.clone()) {
             Ok(link) => return Ok(Probe::from_specific_probe(link)),
             Err(DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::NotFound)) => {}
@@ -353,7 +363,11 @@ impl Probe {
         self.attached = true;
         // The session will de-assert reset after connecting to the debug interface.
         Session::new(self, target.into(), AttachMethod::UnderReset, permissions).map_err(|e| {
-            if matches!(e, Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout)) {
+            if matches!(
+                e,
+                Error::Arm(ArmError::Timeout)
+                    | Error::Riscv(RiscvError::Timeout)
+            ) {
                 Error::Other(
                 anyhow::anyhow!("Timeout while attaching to target under reset. This can happen if the target is not responding to the reset sequence. Ensure the chip's reset pin is connected, or try attaching without reset (`connectUnderReset = false` for DAP Clients, or remove `connect-under-reset` option from CLI options.)."))
             } else {
@@ -489,6 +503,27 @@ impl Probe {
         }
     }
 
+    /// Check if the probe has an interface to
+    /// debug MIPS chips.
+    pub fn has_mips_interface(&self) -> bool {
+        self.inner.has_mips_interface()
+    }
+
+    /// Try to get a [`MipsCommunicationInterface`], which can
+    /// can be used to communicate with chips using the MIPS
+    /// architecture.
+    ///
+    /// If an error occurs while trying to connect, the probe is returned.
+    pub fn try_into_mips_interface(self) -> Result<MipsCommunicationInterface, (Self, MipsError)> {
+        if !self.attached {
+            Err((self, DebugProbeError::NotAttached.into()))
+        } else {
+            self.inner
+                .try_get_mips_interface()
+                .map_err(|(probe, err)| (Probe::from_attached_probe(probe), err))
+        }
+    }
+
     /// Gets a SWO interface from the debug probe.
     ///
     /// This does not work on all probes.
@@ -633,6 +668,22 @@ pub trait DebugProbe: Send + fmt::Debug {
         false
     }
 
+    /// Get the dedicated interface to debug MIPS chips. Ensure that the
+    /// probe actually supports this by calling [DebugProbe::has_mips_interface] first.
+    fn try_get_mips_interface(
+        self: Box<Self>,
+    ) -> Result<MipsCommunicationInterface, (Box<dyn DebugProbe>, MipsError)> {
+        Err((
+            self.into_probe(),
+            DebugProbeError::InterfaceNotAvailable("MIPS").into(),
+        ))
+    }
+
+    /// Check if the probe offers an interface to debug MIPS chips.
+    fn has_mips_interface(&self) -> bool {
+        false
+    }
+
     /// Get a SWO interface from the debug probe.
     ///
     /// This is not available on all debug probes.
@@ -657,6 +708,13 @@ pub trait DebugProbe: Send + fmt::Debug {
         None
     }
 
+    /// Try getting low-level access to the JTAG protocol.
+    ///
+    /// This is not available on all probes.
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        None
+    }
+
     /// Reads the target voltage in Volts, if possible. Returns `Ok(None)`
     /// if the probe doesn’t support reading the target voltage.
     fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
@@ -677,6 +735,8 @@ pub enum DebugProbeType {
     JLink,
     /// Built in RISC-V ESP JTAG debug probe
     EspJtag,
+    /// A probe attached to another machine, accessed through `probe-rs serve`
+    Remote,
 }
 
 /// Gathers some information about a debug probe which was found during a scan.
@@ -744,7 +804,7 @@ impl DebugProbeInfo {
 pub enum DebugProbeSelectorParseError {
     #[error("The VID or PID could not be parsed: {0}")]
     ParseInt(#[from] std::num::ParseIntError),
-    #[error("Please use a string in the form `VID:PID:<Serial>` where Serial is optional.")]
+    #[error("Please use a string in the form `VID:PID:<Serial>` or `tcp://HOST:PORT/<Selector>` where Serial and Selector are optional.")]
     Format,
 }
 
@@ -755,6 +815,11 @@ pub enum DebugProbeSelectorParseError {
 /// where the serialnumber is optional, and VID and PID are
 /// parsed as hexadecimal numbers.
 ///
+/// Probes served by `probe-rs serve` on another machine are selected
+/// with "tcp://HOST:PORT/SELECTOR", where the selector is either a
+/// "VID:PID:SERIALNUMBER" string as above or just the serial number.
+/// If the selector is omitted, the first probe on the server is used.
+///
 /// ## Example:
 ///
 /// ```
@@ -763,6 +828,11 @@ pub enum DebugProbeSelectorParseError {
 ///
 /// assert_eq!(selector.vendor_id, 0x1942);
 /// assert_eq!(selector.product_id, 0x1337);
+///
+/// let selector: probe_rs::DebugProbeSelector = "tcp://rack-3:4242/SERIAL".try_into().unwrap();
+///
+/// assert_eq!(selector.remote_address.as_deref(), Some("rack-3:4242"));
+/// assert_eq!(selector.serial_number.as_deref(), Some("SERIAL"));
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 // We need this so that serde will first convert from the string `VID:PID:<Serial>` to a struct before deserializing.
@@ -774,17 +844,42 @@ pub struct DebugProbeSelector {
     pub product_id: u16,
     /// The the serial number of the debug probe to be used.
     pub serial_number: Option<String>,
+    /// The `HOST:PORT` address of the `probe-rs serve` instance the probe is attached to,
+    /// or `None` for a probe attached to this machine.
+    pub remote_address: Option<String>,
 }
 
 impl TryFrom<&str> for DebugProbeSelector {
     type Error = DebugProbeSelectorParseError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if let Some(remote) = value.strip_prefix("tcp://") {
+            let (address, probe) = remote.split_once('/').unwrap_or((remote, ""));
+            if address.is_empty() {
+                return Err(DebugProbeSelectorParseError::Format);
+            }
+
+            let mut selector = if probe.contains(':') {
+                DebugProbeSelector::try_from(probe)?
+            } else {
+                DebugProbeSelector {
+                    vendor_id: 0,
+                    product_id: 0,
+                    serial_number: (!probe.is_empty()).then(|| probe.to_string()),
+                    remote_address: None,
+                }
+            };
+            selector.remote_address = Some(address.to_string());
+
+            return Ok(selector);
+        }
+
         let split = value.split(':').collect::<Vec<_>>();
         let mut selector = if split.len() > 1 {
             DebugProbeSelector {
                 vendor_id: u16::from_str_radix(split[0], 16)?,
                 product_id: u16::from_str_radix(split[1], 16)?,
                 serial_number: None,
+                remote_address: None,
             }
         } else {
             return Err(DebugProbeSelectorParseError::Format);
@@ -818,6 +913,7 @@ impl From<DebugProbeInfo> for DebugProbeSelector {
             vendor_id: selector.vendor_id,
             product_id: selector.product_id,
             serial_number: selector.serial_number,
+            remote_address: None,
         }
     }
 }
@@ -828,12 +924,22 @@ impl From<&DebugProbeInfo> for DebugProbeSelector {
             vendor_id: selector.vendor_id,
             product_id: selector.product_id,
             serial_number: selector.serial_number.clone(),
+            remote_address: None,
         }
     }
 }
 
 impl fmt::Display for DebugProbeSelector {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref address) = self.remote_address {
+            write!(f, "tcp://{address}/")?;
+            if self.vendor_id == 0 && self.product_id == 0 {
+                if let Some(ref sn) = self.serial_number {
+                    write!(f, "{sn}")?;
+                }
+                return Ok(());
+            }
+        }
         write!(f, "{:04x}:{:04x}", self.vendor_id, self.product_id)?;
         if let Some(ref sn) = self.serial_number {
             write!(f, ":{sn}")?;
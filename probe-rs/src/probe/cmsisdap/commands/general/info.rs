@@ -127,7 +127,7 @@ impl ParseFromResponse for u32 {
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Capabilities {
     pub(crate) _swd_implemented: bool,
-    pub(crate) _jtag_implemented: bool,
+    pub(crate) jtag_implemented: bool,
     pub(crate) swo_uart_implemented: bool,
     pub(crate) swo_manchester_implemented: bool,
     pub(crate) _atomic_commands_implemented: bool,
@@ -144,7 +144,7 @@ impl ParseFromResponse for Capabilities {
         if buffer[0] > 0 {
             let mut capabilites = Capabilities {
                 _swd_implemented: buffer[1] & 0x01 > 0,
-                _jtag_implemented: buffer[1] & 0x02 > 0,
+                jtag_implemented: buffer[1] & 0x02 > 0,
                 swo_uart_implemented: buffer[1] & 0x04 > 0,
                 swo_manchester_implemented: buffer[1] & 0x08 > 0,
                 _atomic_commands_implemented: buffer[1] & 0x10 > 0,
@@ -100,6 +100,33 @@ impl Sequence {
             data,
         })
     }
+
+    /// The number of TCK cycles this sequence clocks out.
+    pub(crate) fn tck_cycles(&self) -> usize {
+        match self.tck_cycles & 0x3F {
+            0 => 64,
+            cycles => cycles as usize,
+        }
+    }
+
+    /// Whether the probe captures TDO during this sequence.
+    pub(crate) fn captures_tdo(&self) -> bool {
+        self.tdo_capture
+    }
+
+    /// The number of bytes this sequence occupies in a DAP_JTAG_Sequence request.
+    pub(crate) fn request_len(&self) -> usize {
+        1 + (self.tck_cycles() + 7) / 8
+    }
+
+    /// The number of TDO bytes this sequence adds to a DAP_JTAG_Sequence response.
+    pub(crate) fn response_len(&self) -> usize {
+        if self.tdo_capture {
+            (self.tck_cycles() + 7) / 8
+        } else {
+            0
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +35,14 @@ pub enum CmsisDapError {
     InvalidIdCode,
     #[error("Error scanning IR lengths")]
     InvalidIR,
+    #[error("No suitable TAP found in the JTAG scan chain")]
+    TapNotFound,
+    #[error("{0} TAPs in the JTAG scan chain are suitable, the TAP to use is ambiguous")]
+    AmbiguousTap(usize),
+    #[error("Cannot shift an empty JTAG register")]
+    EmptyJtagRegister,
+    #[error("JTAG register address {0:#x} does not fit in the IR of the selected TAP")]
+    InvalidJtagRegister(u32),
 }
 
 #[derive(Debug, thiserror::Error)]
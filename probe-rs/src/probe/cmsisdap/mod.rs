@@ -2,20 +2,23 @@ pub mod commands;
 pub mod tools;
 
 use crate::{
-    architecture::arm::{
-        communication_interface::DapProbe,
-        communication_interface::UninitializedArmProbe,
-        dp::{Abort, Ctrl},
-        swo::poll_interval_from_buf_size,
-        ArmCommunicationInterface, ArmError, DapError, DpAddress, Pins, PortType, RawDapAccess,
-        Register, SwoAccess, SwoConfig, SwoMode,
+    architecture::{
+        arm::{
+            communication_interface::DapProbe,
+            communication_interface::UninitializedArmProbe,
+            dp::{Abort, Ctrl},
+            swo::poll_interval_from_buf_size,
+            ArmCommunicationInterface, ArmError, DapError, DpAddress, Pins, PortType, RawDapAccess,
+            Register, SwoAccess, SwoConfig, SwoMode,
+        },
+        riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
     },
     probe::{
         cmsisdap::commands::{
             general::info::{CapabilitiesCommand, PacketCountCommand, SWOTraceBufferSizeCommand},
             CmsisDapError,
         },
-        BatchCommand,
+        BatchCommand, BatchExecutionError, CommandResult, JTAGAccess, JtagWriteCommand,
     },
     CoreStatus, DebugProbe, DebugProbeError, DebugProbeSelector, WireProtocol,
 };
@@ -76,6 +79,13 @@ pub struct CmsisDap {
     scan_chain: Option<Vec<ScanChainElement>>,
 
     batch: Vec<BatchCommand>,
+
+    /// IR length of the TAP used for [`JTAGAccess`], as set with [`JTAGAccess::set_ir_len`].
+    jtag_ir_len: Option<u32>,
+    /// Position of the TAP used for [`JTAGAccess`] in the scan chain, once it has been selected.
+    jtag_chain_params: Option<jtag_util::ChainParams>,
+    /// Number of Run-Test/Idle cycles after each [`JTAGAccess`] register access.
+    jtag_idle_cycles: u8,
 }
 
 /// Stores information about a JTAG scan chain,
@@ -135,6 +145,9 @@ impl CmsisDap {
             speed_khz: 1_000,
             scan_chain: None,
             batch: Vec::new(),
+            jtag_ir_len: None,
+            jtag_chain_params: None,
+            jtag_idle_cycles: 0,
         })
     }
 
@@ -370,6 +383,119 @@ impl CmsisDap {
             })
     }
 
+    /// Send `sequences`, split into as few DAP_JTAG_Sequence commands as the packet size allows.
+    ///
+    /// Returns the TDO bits captured by all sequences.
+    fn send_jtag_sequences_split(
+        &mut self,
+        sequences: &[JtagSequence],
+    ) -> Result<BitVec<u8>, CmsisDapError> {
+        // Both the request and the response start with the command ID, followed by
+        // the sequence count respectively the status, which leaves this much for sequence data.
+        let max_len = self.packet_size as usize - 2;
+
+        let mut tdo = BitVec::new();
+        let mut start = 0;
+        while start < sequences.len() {
+            let mut end = start;
+            let mut request_len = 0;
+            let mut response_len = 0;
+            while end < sequences.len() && end - start < u8::MAX as usize {
+                let sequence = &sequences[end];
+                if request_len + sequence.request_len() > max_len
+                    || response_len + sequence.response_len() > max_len
+                {
+                    break;
+                }
+                request_len += sequence.request_len();
+                response_len += sequence.response_len();
+                end += 1;
+            }
+
+            let chunk = &sequences[start..end];
+            let data = self.send_jtag_sequences(JtagSequenceRequest::new(chunk.to_vec())?)?;
+            tdo.extend_from_bitslice(&jtag_util::collect_tdo(chunk, &data));
+
+            start = end;
+        }
+
+        Ok(tdo)
+    }
+
+    /// Select the TAP used for [`JTAGAccess`], which is the TAP in the scan chain with
+    /// the IR length set by [`JTAGAccess::set_ir_len`].
+    ///
+    /// If no scan chain was provided, the scan chain is detected first.
+    fn jtag_chain_params(&mut self) -> Result<jtag_util::ChainParams, DebugProbeError> {
+        if let Some(params) = self.jtag_chain_params {
+            return Ok(params);
+        }
+
+        self.connect_if_needed()?;
+
+        let irlens: Vec<usize> = if let Some(scan_chain) = &self.scan_chain {
+            let irlens = get_ir_lengths(scan_chain)
+                .into_iter()
+                .map(usize::from)
+                .collect();
+            self.jtag_ensure_run_test_idle()?;
+            irlens
+        } else {
+            tracing::info!("No scan chain provided, doing runtime detection");
+            self.jtag_scan(None)?.irlens
+        };
+
+        let index = jtag_util::select_tap(&irlens, self.jtag_ir_len)?;
+
+        let params = jtag_util::ChainParams::new(&irlens, index);
+        tracing::debug!("Selected TAP {index} of scan chain {irlens:?}: {params:?}");
+        self.jtag_chain_params = Some(params);
+
+        Ok(params)
+    }
+
+    /// Perform consecutive register accesses on the TAP used for [`JTAGAccess`].
+    ///
+    /// Each transfer is given as `(address, data, len)`, and the captured
+    /// register contents are returned for each of them.
+    fn jtag_register_transfers(
+        &mut self,
+        transfers: &[(u32, &[u8], u32)],
+    ) -> Result<Vec<Vec<u8>>, DebugProbeError> {
+        let params = self.jtag_chain_params()?;
+
+        let mut sequences = Vec::new();
+        let mut current_ir = None;
+        for &(address, data, len) in transfers {
+            params.register_access(
+                &mut sequences,
+                current_ir,
+                address,
+                data,
+                len as usize,
+                self.jtag_idle_cycles as usize,
+            )?;
+            current_ir = Some(address);
+        }
+
+        let tdo = self.send_jtag_sequences_split(&sequences)?;
+
+        let mut offset = 0;
+        let replies = transfers
+            .iter()
+            .map(|&(_, _, len)| {
+                let start = offset + params.drpre;
+                offset = start + len as usize + params.drpost;
+
+                let mut reply = bitvec![u8, Lsb0; 0; len as usize];
+                reply.copy_from_bitslice(&tdo[start..start + len as usize]);
+                reply.into_vec()
+            })
+            .collect();
+
+        Ok(replies)
+    }
+
     fn send_swj_sequences(&mut self, request: SequenceRequest) -> Result<(), CmsisDapError> {
         commands::send_command::<SequenceRequest>(&mut self.device, request)
             .map_err(CmsisDapError::from)
@@ -853,6 +979,27 @@ impl DebugProbe for CmsisDap {
         true
     }
 
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        // RISC-V debug is only available over JTAG.
+        if self.capabilities.jtag_implemented && self.protocol == Some(WireProtocol::Jtag) {
+            match RiscvCommunicationInterface::new(self) {
+                Ok(interface) => Ok(interface),
+                Err((probe, err)) => Err((probe.into_probe(), err)),
+            }
+        } else {
+            Err((
+                RawDapAccess::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("JTAG").into(),
+            ))
+        }
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.capabilities.jtag_implemented && self.protocol == Some(WireProtocol::Jtag)
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
@@ -860,6 +1007,65 @@ impl DebugProbe for CmsisDap {
     fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
         Some(self)
     }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+}
+
+impl JTAGAccess for CmsisDap {
+    fn set_ir_len(&mut self, len: u32) {
+        if self.jtag_ir_len != Some(len) {
+            self.jtag_ir_len = Some(len);
+            self.jtag_chain_params = None;
+        }
+    }
+
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        self.write_register(address, &[], len)
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.jtag_idle_cycles = idle_cycles;
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.jtag_idle_cycles
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let mut replies = self.jtag_register_transfers(&[(address, data, len)])?;
+        Ok(replies.remove(0))
+    }
+
+    fn write_register_batch(
+        &mut self,
+        writes: &[JtagWriteCommand],
+    ) -> Result<Vec<CommandResult>, BatchExecutionError> {
+        let transfers: Vec<_> = writes
+            .iter()
+            .map(|write| (write.address, write.data.as_slice(), write.len))
+            .collect();
+
+        let replies = self
+            .jtag_register_transfers(&transfers)
+            .map_err(|e| BatchExecutionError::new(crate::Error::Probe(e), Vec::new()))?;
+
+        let mut results = Vec::with_capacity(writes.len());
+        for (write, reply) in writes.iter().zip(replies) {
+            results.push(
+                (write.transform)(reply)
+                    .map_err(|e| BatchExecutionError::new(e, results.clone()))?,
+            );
+        }
+
+        Ok(results)
+    }
 }
 
 impl RawDapAccess for CmsisDap {
@@ -1207,7 +1413,8 @@ impl Drop for CmsisDap {
 }
 
 mod jtag_util {
-    use crate::probe::cmsisdap::commands::CmsisDapError;
+    use crate::probe::cmsisdap::commands::{jtag::sequence::Sequence, CmsisDapError};
+    use crate::DebugProbeError;
 
     use bitfield::bitfield;
     use bitvec::prelude::*;
@@ -1428,13 +1635,173 @@ mod jtag_util {
             Err(ScanChainError::InvalidIR)
         }
     }
+
+    /// Find the index of the TAP with an IR length of `ir_len` in a scan chain with the given IR lengths.
+    ///
+    /// Without an IR length, the scan chain has to consist of a single TAP. The TAP can't be
+    /// selected if several TAPs have the same IR length.
+    pub(crate) fn select_tap(
+        irlens: &[usize],
+        ir_len: Option<u32>,
+    ) -> Result<usize, CmsisDapError> {
+        let candidates: Vec<usize> = match ir_len {
+            Some(ir_len) => (0..irlens.len())
+                .filter(|&index| irlens[index] == ir_len as usize)
+                .collect(),
+            None => (0..irlens.len()).collect(),
+        };
+
+        match candidates[..] {
+            [index] => Ok(index),
+            [] => Err(CmsisDapError::TapNotFound),
+            _ => Err(CmsisDapError::AmbiguousTap(candidates.len())),
+        }
+    }
+
+    /// The position of a single TAP in the JTAG scan chain.
+    ///
+    /// All other TAPs are kept in BYPASS while this TAP is accessed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) struct ChainParams {
+        /// Total IR length of the TAPs between the selected TAP and TDO.
+        pub irpre: usize,
+        /// Total IR length of the TAPs between TDI and the selected TAP.
+        pub irpost: usize,
+        /// Number of TAPs between the selected TAP and TDO.
+        pub drpre: usize,
+        /// Number of TAPs between TDI and the selected TAP.
+        pub drpost: usize,
+        /// IR length of the selected TAP.
+        pub irlen: usize,
+    }
+
+    impl ChainParams {
+        /// Compute the parameters for the TAP at `index` in a scan chain with the given IR lengths.
+        pub(crate) fn new(irlens: &[usize], index: usize) -> Self {
+            Self {
+                irpre: irlens[..index].iter().sum(),
+                irpost: irlens[index + 1..].iter().sum(),
+                drpre: index,
+                drpost: irlens.len() - index - 1,
+                irlen: irlens[index],
+            }
+        }
+
+        /// Append the sequences for a single register access to `sequences`.
+        ///
+        /// Selects the register at `address`, unless it is already selected according to
+        /// `current_ir`, and shifts `len` bits of `data` through it, padded with zeros.
+        /// The captured DR bits, including those of the bypassed TAPs, are
+        /// `drpre + len + drpost` bits long.
+        pub(crate) fn register_access(
+            &self,
+            sequences: &mut Vec<Sequence>,
+            current_ir: Option<u32>,
+            address: u32,
+            data: &[u8],
+            len: usize,
+            idle_cycles: usize,
+        ) -> Result<(), DebugProbeError> {
+            if self.irlen > 32 || (self.irlen < 32 && address >> self.irlen != 0) {
+                return Err(CmsisDapError::InvalidJtagRegister(address).into());
+            }
+
+            if current_ir != Some(address) {
+                let mut ir = bitvec![u8, Lsb0; 1; self.irpre];
+                ir.extend_from_bitslice(&address.view_bits::<Lsb0>()[..self.irlen]);
+                ir.resize(ir.len() + self.irpost, true);
+
+                shift_register(sequences, true, &ir, false, 0)?;
+            }
+
+            let data = data.view_bits::<Lsb0>();
+            let data = &data[..len.min(data.len())];
+
+            let mut dr = bitvec![u8, Lsb0; 0; self.drpre];
+            dr.extend_from_bitslice(data);
+            dr.resize(self.drpre + len + self.drpost, false);
+
+            shift_register(sequences, false, &dr, true, idle_cycles)
+        }
+    }
+
+    /// Append sequences to `sequences` which shift `bits` through the IR or DR,
+    /// starting and ending in Run-Test/Idle.
+    ///
+    /// `idle_cycles` additional cycles are spent in Run-Test/Idle after the shift.
+    pub(crate) fn shift_register(
+        sequences: &mut Vec<Sequence>,
+        ir: bool,
+        bits: &BitSlice<u8>,
+        capture: bool,
+        idle_cycles: usize,
+    ) -> Result<(), DebugProbeError> {
+        if bits.is_empty() {
+            return Err(CmsisDapError::EmptyJtagRegister.into());
+        }
+
+        let sequence = |tms: bool, bits: &BitSlice<u8>| {
+            let mut tdi = bitvec![u8, Lsb0; 0; bits.len()];
+            tdi.copy_from_bitslice(bits);
+            if capture {
+                Sequence::capture(tms, &tdi)
+            } else {
+                Sequence::no_capture(tms, &tdi)
+            }
+        };
+
+        // Run-Test/Idle -> Select-DR-Scan (-> Select-IR-Scan)
+        let select_cycles = if ir { 2 } else { 1 };
+        sequences.push(Sequence::no_capture(
+            true,
+            &bitvec![u8, Lsb0; 0; select_cycles],
+        )?);
+        // -> Capture-xR -> Shift-xR
+        sequences.push(Sequence::no_capture(false, &bitvec![u8, Lsb0; 0; 2])?);
+
+        // Shift all bits, leaving Shift-xR for Exit1-xR with the last one.
+        let (body, last) = bits.split_at(bits.len() - 1);
+        for chunk in body.chunks(64) {
+            sequences.push(sequence(false, chunk)?);
+        }
+        sequences.push(sequence(true, last)?);
+
+        // Exit1-xR -> Update-xR -> Run-Test/Idle
+        sequences.push(Sequence::no_capture(true, &bitvec![u8, Lsb0; 0; 1])?);
+        let mut remaining = 1 + idle_cycles;
+        while remaining > 0 {
+            let cycles = remaining.min(64);
+            sequences.push(Sequence::no_capture(false, &bitvec![u8, Lsb0; 0; cycles])?);
+            remaining -= cycles;
+        }
+
+        Ok(())
+    }
+
+    /// Collect the TDO bits captured by `sequences` from the data of a DAP_JTAG_Sequence response.
+    pub(crate) fn collect_tdo(sequences: &[Sequence], tdo: &[u8]) -> BitVec<u8> {
+        let mut bits = BitVec::new();
+        let mut offset = 0;
+
+        for sequence in sequences.iter().filter(|sequence| sequence.captures_tdo()) {
+            let len = sequence.response_len();
+            bits.extend_from_bitslice(
+                &tdo[offset..offset + len].view_bits::<Lsb0>()[..sequence.tck_cycles()],
+            );
+            offset += len;
+        }
+
+        bits
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use bitvec::prelude::*;
 
+    use super::commands::{CmsisDapError, Request};
     use super::jtag_util;
+    use super::JtagSequenceRequest;
     use jtag_util::IdCode;
 
     const ARM_TAP: IdCode = IdCode(0x4BA00477);
@@ -1506,4 +1873,98 @@ mod tests {
 
         assert_eq!(idcodes, vec![Some(ARM_TAP), None, Some(STM_BS_TAP)]);
     }
+
+    #[test]
+    fn chain_params_tap_in_middle() {
+        let params = jtag_util::ChainParams::new(&[4, 5, 7], 1);
+
+        assert_eq!(
+            params,
+            jtag_util::ChainParams {
+                irpre: 4,
+                irpost: 7,
+                drpre: 1,
+                drpost: 1,
+                irlen: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn select_tap_by_ir_length() {
+        assert_eq!(jtag_util::select_tap(&[5], None).unwrap(), 0);
+        assert_eq!(jtag_util::select_tap(&[4, 5, 7], Some(7)).unwrap(), 2);
+        assert!(matches!(
+            jtag_util::select_tap(&[4, 5], None),
+            Err(CmsisDapError::AmbiguousTap(2))
+        ));
+        assert!(matches!(
+            jtag_util::select_tap(&[4, 5, 4], Some(4)),
+            Err(CmsisDapError::AmbiguousTap(2))
+        ));
+        assert!(matches!(
+            jtag_util::select_tap(&[4, 5], Some(6)),
+            Err(CmsisDapError::TapNotFound)
+        ));
+    }
+
+    #[test]
+    fn shift_empty_register() {
+        let mut sequences = Vec::new();
+
+        assert!(
+            jtag_util::shift_register(&mut sequences, false, BitSlice::empty(), true, 0).is_err()
+        );
+        assert!(sequences.is_empty());
+    }
+
+    #[test]
+    fn register_access_sequences_one_tap() {
+        let params = jtag_util::ChainParams::new(&[5], 0);
+        let mut sequences = Vec::new();
+        params
+            .register_access(&mut sequences, None, 0x11, &[0b101], 3, 0)
+            .unwrap();
+
+        let request = JtagSequenceRequest::new(sequences).unwrap();
+        let mut buffer = [0; 64];
+        let len = request.to_bytes(&mut buffer).unwrap();
+
+        #[rustfmt::skip]
+        let expected = [
+            12,
+            // IR: Run-Test/Idle -> Shift-IR, address, Exit1-IR -> Run-Test/Idle
+            0x42, 0x00, 0x02, 0x00, 0x04, 0x01, 0x41, 0x01, 0x41, 0x00, 0x01, 0x00,
+            // DR: Run-Test/Idle -> Shift-DR, captured data, Exit1-DR -> Run-Test/Idle
+            0x41, 0x00, 0x02, 0x00, 0x82, 0x01, 0xc1, 0x01, 0x41, 0x00, 0x01, 0x00,
+        ];
+        assert_eq!(&buffer[..len], &expected);
+    }
+
+    #[test]
+    fn register_access_skips_selected_ir() {
+        let params = jtag_util::ChainParams::new(&[5], 0);
+        let mut sequences = Vec::new();
+        params
+            .register_access(&mut sequences, Some(0x11), 0x11, &[], 41, 3)
+            .unwrap();
+
+        // Only the DR scan, with 40 + 1 captured bits and 1 + 3 idle cycles.
+        let tck_cycles: Vec<_> = sequences.iter().map(|s| s.tck_cycles()).collect();
+        assert_eq!(tck_cycles, vec![1, 2, 40, 1, 1, 4]);
+
+        let tdo = [0xff; 5 + 1];
+        let captured = jtag_util::collect_tdo(&sequences, &tdo);
+        assert_eq!(captured.len(), 41);
+    }
+
+    #[test]
+    fn register_access_rejects_large_address() {
+        let params = jtag_util::ChainParams::new(&[5], 0);
+        let mut sequences = Vec::new();
+
+        assert!(params
+            .register_access(&mut sequences, None, 0x20, &[], 32, 0)
+            .is_err());
+    }
 }
@@ -553,6 +553,10 @@ impl DebugProbe for EspUsbJtag {
         }
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn get_swo_interface(&self) -> Option<&dyn SwoAccess> {
         // This probe cannot debug ARM targets.
         None
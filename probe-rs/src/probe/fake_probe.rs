@@ -1,3 +1,7 @@
+mod simulated;
+
+pub use simulated::SimulatedTarget;
+
 use std::{fmt::Debug, sync::Arc};
 
 use probe_rs_target::ScanChainElement;
@@ -6,7 +10,8 @@ use crate::{
     architecture::arm::{
         ap::{memory_ap::mock::MockMemoryAp, AccessPort, MemoryAp},
         communication_interface::{
-            ArmDebugState, Initialized, SwdSequence, Uninitialized, UninitializedArmProbe,
+            ArmCommunicationInterface, ArmDebugState, DapProbe, Initialized, SwdSequence,
+            Uninitialized, UninitializedArmProbe,
         },
         dp::DebugPortError,
         memory::adi_v5_memory_interface::{ADIMemoryInterface, ArmProbe},
@@ -28,6 +33,8 @@ pub struct FakeProbe {
 
     dap_register_write_handler:
         Option<Box<dyn Fn(PortType, u8, u32) -> Result<(), ArmError> + Send>>,
+
+    simulated_target: Option<SimulatedTarget>,
 }
 
 impl Debug for FakeProbe {
@@ -49,6 +56,19 @@ impl FakeProbe {
 
             dap_register_read_handler: None,
             dap_register_write_handler: None,
+
+            simulated_target: None,
+        }
+    }
+
+    /// Creates a new [`FakeProbe`] which is connected to a [`SimulatedTarget`].
+    ///
+    /// All DAP register accesses are handled by the simulated target, and the probe
+    /// provides the regular ARM communication interface on top of them.
+    pub fn with_simulated_target(target: SimulatedTarget) -> Self {
+        FakeProbe {
+            simulated_target: Some(target),
+            ..FakeProbe::new()
         }
     }
 
@@ -152,16 +172,28 @@ impl DebugProbe for FakeProbe {
         self: Box<Self>,
     ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
     {
-        Ok(Box::new(FakeArmInterface::new(self)))
+        if self.simulated_target.is_some() {
+            Ok(Box::new(ArmCommunicationInterface::new(self, false)))
+        } else {
+            Ok(Box::new(FakeArmInterface::new(self)))
+        }
     }
 
     fn has_arm_interface(&self) -> bool {
         true
     }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        Some(self)
+    }
 }
 
 impl RawDapAccess for FakeProbe {
-    fn select_dp(&mut self, _dp: DpAddress) -> Result<(), ArmError> {
+    fn select_dp(&mut self, dp: DpAddress) -> Result<(), ArmError> {
+        if self.simulated_target.is_some() && dp == DpAddress::Default {
+            return Ok(());
+        }
+
         Err(DebugPortError::Unsupported(
             "Fake debug probe does not support DP selection.".to_string(),
         )
@@ -170,6 +202,10 @@ impl RawDapAccess for FakeProbe {
 
     /// Reads the DAP register on the specified port and address
     fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
+        if let Some(target) = &mut self.simulated_target {
+            return target.read_register(port, addr);
+        }
+
         let handler = self.dap_register_read_handler.as_ref().unwrap();
 
         handler(port, addr)
@@ -177,6 +213,10 @@ impl RawDapAccess for FakeProbe {
 
     /// Writes a value to the DAP register on the specified port and address
     fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
+        if let Some(target) = &mut self.simulated_target {
+            return target.write_register(port, addr, value);
+        }
+
         let handler = self.dap_register_write_handler.as_ref().unwrap();
 
         handler(port, addr, value)
@@ -187,15 +227,24 @@ impl RawDapAccess for FakeProbe {
     }
 
     fn swj_sequence(&mut self, _bit_len: u8, _bits: u64) -> Result<(), DebugProbeError> {
+        // The simulated target is always in SWD mode.
+        if self.simulated_target.is_some() {
+            return Ok(());
+        }
+
         todo!()
     }
 
     fn swj_pins(
         &mut self,
-        _pin_out: u32,
-        _pin_select: u32,
+        pin_out: u32,
+        pin_select: u32,
         _pin_wait: u32,
     ) -> Result<u32, DebugProbeError> {
+        if let Some(target) = &mut self.simulated_target {
+            return Ok(target.swj_pins(pin_out, pin_select));
+        }
+
         todo!()
     }
 
@@ -208,6 +257,8 @@ impl RawDapAccess for FakeProbe {
     }
 }
 
+impl DapProbe for FakeProbe {}
+
 #[derive(Debug)]
 struct FakeArmInterface<S: ArmDebugState> {
     probe: Box<FakeProbe>,
@@ -403,8 +454,11 @@ impl DapAccess for FakeArmInterface<Initialized> {
 
 #[cfg(test)]
 mod test {
-    use super::FakeProbe;
-    use crate::Permissions;
+    use super::{FakeProbe, SimulatedTarget};
+    use crate::{
+        architecture::arm::{sequences::DefaultArmSequence, DpAddress},
+        Permissions,
+    };
 
     #[test]
     fn create_session_with_fake_probe() {
@@ -416,4 +470,24 @@ mod test {
             .attach("nrf51822_xxAC", Permissions::default())
             .unwrap();
     }
+
+    #[test]
+    fn read_rom_table_of_simulated_target() {
+        let mut probe = FakeProbe::with_simulated_target(SimulatedTarget::new()).into_probe();
+        probe.attach_to_unspecified().unwrap();
+
+        let mut interface = probe
+            .try_into_arm_interface()
+            .unwrap()
+            .initialize(DefaultArmSequence::create())
+            .unwrap();
+
+        let chip_info = interface
+            .read_chip_info_from_rom_table(DpAddress::Default)
+            .unwrap()
+            .expect("The ROM table should contain the chip information.");
+
+        assert_eq!(chip_info.manufacturer.get(), Some("ARM Ltd"));
+        assert_eq!(chip_info.part, 0x4C3);
+    }
 }
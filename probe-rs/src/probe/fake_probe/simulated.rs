@@ -0,0 +1,1065 @@
+//! A simulated ARM Cortex-M target, which can be connected to the [`FakeProbe`](super::FakeProbe).
+//!
+//! The simulation works on the level of DAP register accesses, so the complete ARM debug stack
+//! of probe-rs is used when talking to it. The target consists of an SW-DP with a single AHB-AP,
+//! a ROM table, the halting debug registers in the SCS, a Flash Patch and Breakpoint unit, and
+//! RAM and flash memory regions.
+//!
+//! The simulated core only executes a small subset of the Thumb instruction set, which is enough
+//! to run flash algorithms and simple test programs. Any other instruction locks up the core.
+
+use std::collections::HashMap;
+
+use crate::architecture::arm::{ArmError, DapError, PortType};
+
+/// DPIDR of an ARM SW-DP, version 1.
+const DPIDR: u32 = 0x2BA0_1477;
+
+// CTRL/STAT register bits
+const CSYSPWRUPACK: u32 = 1 << 31;
+const CSYSPWRUPREQ: u32 = 1 << 30;
+const CDBGPWRUPACK: u32 = 1 << 29;
+const CDBGPWRUPREQ: u32 = 1 << 28;
+const STICKYERR: u32 = 1 << 5;
+
+/// ABORT register bit to clear the STICKYERR flag.
+const STKERRCLR: u32 = 1 << 2;
+
+// MEM-AP registers
+const AP_CSW: u8 = 0x00;
+const AP_TAR: u8 = 0x04;
+const AP_DRW: u8 = 0x0C;
+const AP_BD0: u8 = 0x10;
+const AP_BD3: u8 = 0x1C;
+const AP_BASE: u8 = 0xF8;
+const AP_IDR: u8 = 0xFC;
+
+/// IDR of an ARM AHB-AP, as found in Cortex-M3 and Cortex-M4 devices.
+const AHB_AP_IDR: u32 = 0x2477_0011;
+
+/// CSW bits which can be written: DbgSwEnable, PROT, CACHE, AddrInc and SIZE.
+/// HNONSEC is not supported.
+const CSW_WRITABLE: u32 = 0xBF00_0037;
+const CSW_DEVICE_EN: u32 = 1 << 6;
+const CSW_ADDR_INC_SINGLE: u32 = 0b01 << 4;
+
+// Memory map of the private peripheral bus
+const SYSTEM_BASE: u32 = 0xE000_0000;
+const DWT: u32 = 0xE000_1000;
+const FPB: u32 = 0xE000_2000;
+const SCS: u32 = 0xE000_E000;
+const ROM_TABLE: u32 = 0xE00F_F000;
+
+const DWT_CTRL: u32 = DWT;
+const FP_CTRL: u32 = FPB;
+const FP_COMP0: u32 = FPB + 0x8;
+const CPUID: u32 = 0xE000_ED00;
+const AIRCR: u32 = 0xE000_ED0C;
+const DFSR: u32 = 0xE000_ED30;
+const DHCSR: u32 = 0xE000_EDF0;
+const DCRSR: u32 = 0xE000_EDF4;
+const DCRDR: u32 = 0xE000_EDF8;
+const DEMCR: u32 = 0xE000_EDFC;
+
+/// CPUID of a Cortex-M3 r2p0.
+const CPUID_VALUE: u32 = 0x412F_C230;
+
+const NUM_FP_COMPARATORS: u32 = 6;
+const NUM_DWT_COMPARATORS: u32 = 4;
+
+/// Entries of the ROM table, pointing to the SCS, the DWT and the FPB.
+const ROM_TABLE_ENTRIES: [u32; 3] = [
+    SCS.wrapping_sub(ROM_TABLE) | 0b11,
+    DWT.wrapping_sub(ROM_TABLE) | 0b11,
+    FPB.wrapping_sub(ROM_TABLE) | 0b11,
+];
+
+/// CoreSight components with their base address, component class and part number.
+const COMPONENTS: [(u32, u32, u32); 4] = [
+    (ROM_TABLE, 0x1, 0x4C3),
+    (SCS, 0xE, 0x000),
+    (DWT, 0xE, 0x002),
+    (FPB, 0xE, 0x003),
+];
+
+// DHCSR bits
+const DBGKEY: u32 = 0xA05F;
+const C_DEBUGEN: u32 = 1 << 0;
+const C_HALT: u32 = 1 << 1;
+const C_STEP: u32 = 1 << 2;
+const C_MASKINTS: u32 = 1 << 3;
+const S_REGRDY: u32 = 1 << 16;
+const S_HALT: u32 = 1 << 17;
+const S_LOCKUP: u32 = 1 << 19;
+const S_RETIRE_ST: u32 = 1 << 24;
+const S_RESET_ST: u32 = 1 << 25;
+
+// DFSR bits
+const DFSR_HALTED: u32 = 1 << 0;
+const DFSR_BKPT: u32 = 1 << 1;
+const DFSR_VCATCH: u32 = 1 << 3;
+
+const DCRSR_REGWNR: u32 = 1 << 16;
+const DEMCR_VC_CORERESET: u32 = 1 << 0;
+
+const AIRCR_VECTKEY: u32 = 0x05FA;
+const AIRCR_VECTKEYSTAT: u32 = 0xFA05_0000;
+const AIRCR_SYSRESETREQ: u32 = 1 << 2;
+const AIRCR_VECTRESET: u32 = 1 << 0;
+
+// Core register numbers, as used in DCRSR.REGSEL
+const SP: usize = 13;
+const LR: usize = 14;
+const PC: usize = 15;
+const XPSR: usize = 16;
+const MSP: usize = 17;
+
+const XPSR_THUMB: u32 = 1 << 24;
+
+// Condition flags in the xPSR
+const APSR_N: u32 = 1 << 31;
+const APSR_Z: u32 = 1 << 30;
+const APSR_C: u32 = 1 << 29;
+const APSR_V: u32 = 1 << 28;
+
+/// nRESET in the pin mask of `swj_pins`.
+const NRESET: u32 = 1 << 7;
+
+/// Number of instructions the core executes each time the debugger polls DHCSR.
+const INSTRUCTIONS_PER_POLL: usize = 10_000;
+
+// Operations of the simulated flash controller, selected with the immediate of `UDF`.
+const FLASH_INIT: u32 = 1;
+const FLASH_UNINIT: u32 = 2;
+const FLASH_ERASE_SECTOR: u32 = 3;
+const FLASH_PROGRAM_PAGE: u32 = 4;
+const FLASH_ERASE_ALL: u32 = 5;
+const FLASH_BLANK_CHECK: u32 = 6;
+const FLASH_READ_OPTION_BYTES: u32 = 7;
+const FLASH_WRITE_OPTION_BYTES: u32 = 8;
+const FLASH_PROTECT: u32 = 9;
+const FLASH_UNPROTECT: u32 = 10;
+const FLASH_VERIFY: u32 = 11;
+
+/// Size of the option bytes of the simulated flash controller.
+const OPTION_BYTES_SIZE: usize = 16;
+
+#[derive(Debug)]
+struct MemoryRegion {
+    start: u32,
+    data: Vec<u8>,
+    /// Sector size of a flash region, `None` for RAM.
+    sector_size: Option<u32>,
+}
+
+impl MemoryRegion {
+    fn contains(&self, address: u32, len: u32) -> bool {
+        address >= self.start
+            && u64::from(address - self.start) + u64::from(len) <= self.data.len() as u64
+    }
+
+    fn offset(&self, address: u32) -> usize {
+        (address - self.start) as usize
+    }
+}
+
+/// A simulated ARM Cortex-M target.
+///
+/// The target is connected to probe-rs by creating a [`FakeProbe`](super::FakeProbe)
+/// with [`FakeProbe::with_simulated_target`](super::FakeProbe::with_simulated_target).
+///
+/// The core supports halting, single stepping, core register access, hardware breakpoints,
+/// reset and vector catch on reset. It executes the following Thumb instructions:
+/// `BKPT`, `NOP`, `MOVS Rd, #imm8`, `ADDS Rd, #imm8`, `SUBS Rd, #imm8`, `CMP Rn, #imm8`,
+/// `MVNS Rd, Rm`, `EORS Rd, Rm`, `LSRS Rd, Rm, #imm5`, `LDR Rt, [Rn, #imm]`,
+/// `STR Rt, [Rn, #imm]`, `LDRB Rt, [Rn, #imm]`, `BX Rm`, `B <label>` and `B<cond> <label>`.
+///
+/// Flash memory can not be written through the memory AP. Instead, the `UDF #imm8` instruction
+/// calls into the simulated flash controller, with the arguments in R0-R2 and the result in R0:
+///
+/// | `imm8` | Operation                                    |
+/// |--------|----------------------------------------------|
+/// | 1      | Init                                         |
+/// | 2      | UnInit                                       |
+/// | 3      | EraseSector(address)                         |
+/// | 4      | ProgramPage(address, size, buffer)           |
+/// | 5      | EraseChip                                    |
+/// | 6      | BlankCheck(address, size, value)             |
+/// | 7      | ReadOptionBytes(buffer, size)                |
+/// | 8      | WriteOptionBytes(buffer, size)               |
+/// | 9      | Protect(level)                               |
+/// | 10     | Unprotect                                    |
+/// | 11     | Verify(address, size, buffer)                |
+///
+/// There are 16 option bytes, the first one holds the readout protection level. Unprotect
+/// erases the flash and resets the level to 0. Verify returns the end address of the range
+/// if the flash contents match the buffer, and the address of the first mismatch otherwise.
+///
+/// A flash algorithm for the simulated target therefore consists of a `UDF`
+/// followed by a `BX LR` for each function.
+#[derive(Debug)]
+pub struct SimulatedTarget {
+    ctrl_stat: u32,
+    select: u32,
+    rdbuff: u32,
+
+    csw: u32,
+    tar: u32,
+
+    memory: Vec<MemoryRegion>,
+    option_bytes: [u8; OPTION_BYTES_SIZE],
+    /// Registers on the private peripheral bus without any special behaviour.
+    system_registers: HashMap<u32, u32>,
+
+    registers: [u32; 128],
+    /// Control bits of DHCSR.
+    dhcsr: u32,
+    dcrdr: u32,
+    dfsr: u32,
+    halted: bool,
+    locked_up: bool,
+    reset_status: bool,
+    retired: bool,
+    reset_asserted: bool,
+}
+
+impl SimulatedTarget {
+    /// Creates a new simulated target without any memory.
+    pub fn new() -> Self {
+        let mut target = Self {
+            ctrl_stat: 0,
+            select: 0,
+            rdbuff: 0,
+            csw: 0,
+            tar: 0,
+            memory: Vec::new(),
+            option_bytes: [0; OPTION_BYTES_SIZE],
+            system_registers: HashMap::new(),
+            registers: [0; 128],
+            dhcsr: 0,
+            dcrdr: 0,
+            dfsr: 0,
+            halted: false,
+            locked_up: false,
+            reset_status: false,
+            retired: false,
+            reset_asserted: false,
+        };
+
+        target.reset();
+        target
+    }
+
+    /// Adds a RAM region of `size` bytes at `start`.
+    ///
+    /// The core is reset afterwards.
+    #[must_use]
+    pub fn with_ram(mut self, start: u32, size: u32) -> Self {
+        self.memory.push(MemoryRegion {
+            start,
+            data: vec![0; size as usize],
+            sector_size: None,
+        });
+
+        self.reset();
+        self
+    }
+
+    /// Adds an erased flash region of `size` bytes at `start`, which is erased in sectors of `sector_size` bytes.
+    ///
+    /// The core is reset afterwards.
+    #[must_use]
+    pub fn with_flash(mut self, start: u32, size: u32, sector_size: u32) -> Self {
+        self.memory.push(MemoryRegion {
+            start,
+            data: vec![0xFF; size as usize],
+            sector_size: Some(sector_size),
+        });
+
+        self.reset();
+        self
+    }
+
+    /// Read a DAP register.
+    pub(super) fn read_register(&mut self, port: PortType, address: u8) -> Result<u32, ArmError> {
+        let address = address & 0xC;
+
+        match port {
+            PortType::DebugPort => Ok(self.read_dp_register(address)),
+            PortType::AccessPort => {
+                let value = self.read_ap_register(address)?;
+                self.rdbuff = value;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Write a DAP register.
+    pub(super) fn write_register(
+        &mut self,
+        port: PortType,
+        address: u8,
+        value: u32,
+    ) -> Result<(), ArmError> {
+        let address = address & 0xC;
+
+        match port {
+            PortType::DebugPort => {
+                self.write_dp_register(address, value);
+                Ok(())
+            }
+            PortType::AccessPort => self.write_ap_register(address, value),
+        }
+    }
+
+    /// Drive the pins of the debug connector, see [`RawDapAccess::swj_pins`](crate::architecture::arm::RawDapAccess::swj_pins).
+    ///
+    /// Only nRESET is connected. The core is reset when nRESET is released.
+    pub(super) fn swj_pins(&mut self, pin_out: u32, pin_select: u32) -> u32 {
+        if pin_select & NRESET != 0 {
+            let asserted = pin_out & NRESET == 0;
+
+            if self.reset_asserted && !asserted {
+                self.reset();
+            }
+
+            self.reset_asserted = asserted;
+        }
+
+        if self.reset_asserted {
+            0
+        } else {
+            NRESET
+        }
+    }
+
+    fn read_dp_register(&mut self, address: u8) -> u32 {
+        match address {
+            0x0 => DPIDR,
+            0x4 if self.select & 0xF == 0 => {
+                let mut value = self.ctrl_stat;
+                if value & CSYSPWRUPREQ != 0 {
+                    value |= CSYSPWRUPACK;
+                }
+                if value & CDBGPWRUPREQ != 0 {
+                    value |= CDBGPWRUPACK;
+                }
+                value
+            }
+            0xC => self.rdbuff,
+            _ => 0,
+        }
+    }
+
+    fn write_dp_register(&mut self, address: u8, value: u32) {
+        match address {
+            0x0 if value & STKERRCLR != 0 => self.ctrl_stat &= !STICKYERR,
+            0x4 if self.select & 0xF == 0 => {
+                let read_only = CSYSPWRUPACK | CDBGPWRUPACK | STICKYERR;
+                self.ctrl_stat = (value & !read_only) | (self.ctrl_stat & STICKYERR);
+            }
+            0x8 => self.select = value,
+            _ => (),
+        }
+    }
+
+    /// The AP register selected by SELECT and `address`, or `None` if an AP other than AP 0 is selected.
+    fn selected_ap_register(&self, address: u8) -> Option<u8> {
+        let ap = self.select >> 24;
+        let bank = ((self.select >> 4) & 0xF) as u8;
+
+        (ap == 0).then_some((bank << 4) | address)
+    }
+
+    fn read_ap_register(&mut self, address: u8) -> Result<u32, ArmError> {
+        let Some(register) = self.selected_ap_register(address) else {
+            return Ok(0);
+        };
+
+        match register {
+            AP_CSW => Ok(self.csw | CSW_DEVICE_EN),
+            AP_TAR => Ok(self.tar),
+            AP_DRW => {
+                let value = self.bus_read(self.tar, self.transfer_size())?;
+                self.increment_tar();
+                Ok(value)
+            }
+            AP_BD0..=AP_BD3 => self.bus_read(self.banked_address(register), 4),
+            AP_BASE => Ok(ROM_TABLE | 0b11),
+            AP_IDR => Ok(AHB_AP_IDR),
+            _ => Ok(0),
+        }
+    }
+
+    fn write_ap_register(&mut self, address: u8, value: u32) -> Result<(), ArmError> {
+        let Some(register) = self.selected_ap_register(address) else {
+            return Ok(());
+        };
+
+        match register {
+            AP_CSW => {
+                // Only 8, 16 and 32 bit transfers are supported.
+                let size = match value & 0x7 {
+                    size @ 0..=2 => size,
+                    _ => self.csw & 0x7,
+                };
+                self.csw = (value & CSW_WRITABLE & !0x7) | size;
+            }
+            AP_TAR => self.tar = value,
+            AP_DRW => {
+                self.bus_write(self.tar, self.transfer_size(), value)?;
+                self.increment_tar();
+            }
+            AP_BD0..=AP_BD3 => self.bus_write(self.banked_address(register), 4, value)?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn transfer_size(&self) -> u32 {
+        match self.csw & 0x7 {
+            0 => 1,
+            1 => 2,
+            _ => 4,
+        }
+    }
+
+    fn increment_tar(&mut self) {
+        // Only the lower 10 bits of TAR are incremented.
+        if self.csw & (0b11 << 4) == CSW_ADDR_INC_SINGLE {
+            let incremented = self.tar.wrapping_add(self.transfer_size());
+            self.tar = (self.tar & !0x3FF) | (incremented & 0x3FF);
+        }
+    }
+
+    fn banked_address(&self, register: u8) -> u32 {
+        (self.tar & !0xF) | u32::from(register & 0xC)
+    }
+
+    /// Memory read through the AP, with the data on the byte lanes selected by the address.
+    fn bus_read(&mut self, address: u32, size: u32) -> Result<u32, ArmError> {
+        match self.read_memory(address, size) {
+            Some(value) => Ok(value << ((address & 0x3) * 8)),
+            None => Err(self.fault()),
+        }
+    }
+
+    /// Memory write through the AP, with the data on the byte lanes selected by the address.
+    fn bus_write(&mut self, address: u32, size: u32, value: u32) -> Result<(), ArmError> {
+        if self.write_memory(address, size, value >> ((address & 0x3) * 8)) {
+            Ok(())
+        } else {
+            Err(self.fault())
+        }
+    }
+
+    fn fault(&mut self) -> ArmError {
+        self.ctrl_stat |= STICKYERR;
+        DapError::FaultResponse.into()
+    }
+
+    fn read_memory(&mut self, address: u32, size: u32) -> Option<u32> {
+        if address % size != 0 {
+            return None;
+        }
+
+        if address >= SYSTEM_BASE {
+            let word = self.read_system_register(address & !0x3);
+            let mask = u32::MAX >> (32 - size * 8);
+            return Some((word >> ((address & 0x3) * 8)) & mask);
+        }
+
+        let region = self
+            .memory
+            .iter()
+            .find(|region| region.contains(address, size))?;
+        let offset = region.offset(address);
+
+        let mut bytes = [0u8; 4];
+        bytes[..size as usize].copy_from_slice(&region.data[offset..offset + size as usize]);
+
+        Some(u32::from_le_bytes(bytes))
+    }
+
+    fn write_memory(&mut self, address: u32, size: u32, value: u32) -> bool {
+        if address % size != 0 {
+            return false;
+        }
+
+        if address >= SYSTEM_BASE {
+            if size != 4 {
+                return false;
+            }
+
+            self.write_system_register(address, value);
+            return true;
+        }
+
+        // Flash can only be written by the flash controller.
+        let Some(region) = self
+            .memory
+            .iter_mut()
+            .find(|region| region.sector_size.is_none() && region.contains(address, size))
+        else {
+            return false;
+        };
+        let offset = region.offset(address);
+
+        region.data[offset..offset + size as usize]
+            .copy_from_slice(&value.to_le_bytes()[..size as usize]);
+
+        true
+    }
+
+    fn system_register(&self, address: u32) -> u32 {
+        self.system_registers.get(&address).copied().unwrap_or(0)
+    }
+
+    fn read_system_register(&mut self, address: u32) -> u32 {
+        match address {
+            DHCSR => self.read_dhcsr(),
+            DCRDR => self.dcrdr,
+            DFSR => self.dfsr,
+            AIRCR => AIRCR_VECTKEYSTAT,
+            CPUID => CPUID_VALUE,
+            FP_CTRL => self.system_register(FP_CTRL) | (NUM_FP_COMPARATORS << 4),
+            DWT_CTRL => {
+                (self.system_register(DWT_CTRL) & 0x0FFF_FFFF) | (NUM_DWT_COMPARATORS << 28)
+            }
+            _ => {
+                if let Some(value) = component_id_register(address) {
+                    value
+                } else if (ROM_TABLE..ROM_TABLE + 0x1000).contains(&address) {
+                    ROM_TABLE_ENTRIES
+                        .get(((address - ROM_TABLE) / 4) as usize)
+                        .copied()
+                        .unwrap_or(0)
+                } else {
+                    self.system_register(address)
+                }
+            }
+        }
+    }
+
+    fn write_system_register(&mut self, address: u32, value: u32) {
+        match address {
+            DHCSR => self.write_dhcsr(value),
+            DCRSR => self.transfer_core_register(value),
+            DCRDR => self.dcrdr = value,
+            // The bits of DFSR are cleared by writing a one.
+            DFSR => self.dfsr &= !value,
+            AIRCR => {
+                if value >> 16 == AIRCR_VECTKEY
+                    && value & (AIRCR_SYSRESETREQ | AIRCR_VECTRESET) != 0
+                {
+                    self.reset();
+                }
+            }
+            CPUID => (),
+            FP_CTRL => {
+                // Writes are ignored unless the KEY bit is set.
+                if value & 0b10 != 0 {
+                    self.system_registers.insert(FP_CTRL, value & 0b1);
+                }
+            }
+            _ if (ROM_TABLE..ROM_TABLE + 0x1000).contains(&address) => (),
+            _ => {
+                self.system_registers.insert(address, value);
+            }
+        }
+    }
+
+    fn read_dhcsr(&mut self) -> u32 {
+        // The core keeps running while the debugger polls it.
+        self.run(INSTRUCTIONS_PER_POLL);
+
+        let mut value = self.dhcsr;
+
+        if self.halted {
+            value |= S_HALT | S_REGRDY;
+        }
+        if self.locked_up {
+            value |= S_LOCKUP;
+        }
+        if self.retired {
+            value |= S_RETIRE_ST;
+        }
+        if self.reset_status {
+            value |= S_RESET_ST;
+        }
+
+        // The sticky status bits are cleared on read.
+        self.retired = false;
+        self.reset_status = false;
+
+        value
+    }
+
+    fn write_dhcsr(&mut self, value: u32) {
+        if value >> 16 != DBGKEY {
+            return;
+        }
+
+        self.dhcsr = value & (C_DEBUGEN | C_HALT | C_STEP | C_MASKINTS);
+
+        if self.dhcsr & C_DEBUGEN == 0 {
+            self.halted = false;
+        } else if self.dhcsr & C_HALT != 0 {
+            if !self.halted {
+                self.enter_debug_state(DFSR_HALTED);
+            }
+        } else if self.halted {
+            self.halted = false;
+
+            if self.dhcsr & C_STEP != 0 {
+                self.run(1);
+
+                if !self.halted && !self.locked_up {
+                    self.enter_debug_state(DFSR_HALTED);
+                }
+            } else {
+                self.run(INSTRUCTIONS_PER_POLL);
+            }
+        }
+    }
+
+    fn transfer_core_register(&mut self, dcrsr: u32) {
+        if !self.halted {
+            return;
+        }
+
+        // SP is an alias of MSP, the process stack is not used.
+        let register = match (dcrsr & 0x7F) as usize {
+            MSP => SP,
+            register => register,
+        };
+
+        if dcrsr & DCRSR_REGWNR != 0 {
+            self.registers[register] = if register == PC {
+                self.dcrdr & !0x1
+            } else {
+                self.dcrdr
+            };
+        } else {
+            self.dcrdr = self.registers[register];
+        }
+    }
+
+    fn reset(&mut self) {
+        self.registers = [0; 128];
+        self.registers[SP] = self.read_memory(0x0, 4).unwrap_or(0);
+        self.registers[PC] = self.read_memory(0x4, 4).unwrap_or(0) & !0x1;
+        self.registers[LR] = 0xFFFF_FFFF;
+        self.registers[XPSR] = XPSR_THUMB;
+
+        self.dhcsr &= C_DEBUGEN;
+        self.halted = false;
+        self.locked_up = false;
+        self.reset_status = true;
+
+        if self.dhcsr & C_DEBUGEN != 0 && self.system_register(DEMCR) & DEMCR_VC_CORERESET != 0 {
+            self.enter_debug_state(DFSR_VCATCH);
+        }
+    }
+
+    fn enter_debug_state(&mut self, reason: u32) {
+        self.halted = true;
+        self.locked_up = false;
+        self.dhcsr |= C_HALT;
+        self.dfsr |= reason;
+    }
+
+    fn lock_up(&mut self, reason: &str) {
+        tracing::warn!(
+            "Simulated core locked up at {:#010x}: {}",
+            self.registers[PC],
+            reason
+        );
+        self.locked_up = true;
+    }
+
+    fn run(&mut self, instructions: usize) {
+        for _ in 0..instructions {
+            if self.halted || self.locked_up || self.reset_asserted {
+                break;
+            }
+
+            self.execute();
+        }
+    }
+
+    fn breakpoint_at(&self, address: u32) -> bool {
+        if self.system_register(FP_CTRL) & 0b1 == 0 {
+            return false;
+        }
+
+        (0..NUM_FP_COMPARATORS).any(|comparator| {
+            let comp = self.system_register(FP_COMP0 + 4 * comparator);
+            let comp_address = comp & 0x1FFF_FFFC;
+
+            comp & 0b1 != 0
+                && match comp >> 30 {
+                    0b01 => address == comp_address,
+                    0b10 => address == comp_address | 0x2,
+                    0b11 => address & !0x3 == comp_address,
+                    _ => false,
+                }
+        })
+    }
+
+    /// Execute the instruction at the current PC.
+    fn execute(&mut self) {
+        let pc = self.registers[PC];
+        let debug_enabled = self.dhcsr & C_DEBUGEN != 0;
+
+        if debug_enabled && self.breakpoint_at(pc) {
+            return self.enter_debug_state(DFSR_BKPT);
+        }
+
+        let Some(instruction) = self.read_memory(pc, 2) else {
+            return self.lock_up("instruction fetch failed");
+        };
+        let instruction = instruction as u16;
+
+        let low_register = usize::from((instruction >> 8) & 0x7);
+        let imm8 = u32::from(instruction & 0xFF);
+        let mut next_pc = pc.wrapping_add(2);
+
+        match instruction {
+            // BKPT #imm8
+            0xBE00..=0xBEFF => {
+                if !debug_enabled {
+                    return self.lock_up("breakpoint with halting debug disabled");
+                }
+
+                return self.enter_debug_state(DFSR_BKPT);
+            }
+            // NOP
+            0xBF00 => (),
+            // MOVS Rd, #imm8
+            0x2000..=0x27FF => {
+                self.registers[low_register] = imm8;
+                self.set_nz_flags(imm8);
+            }
+            // CMP Rn, #imm8
+            0x2800..=0x2FFF => {
+                self.add_with_carry(self.registers[low_register], !imm8, true);
+            }
+            // ADDS Rd, #imm8
+            0x3000..=0x37FF => {
+                self.registers[low_register] =
+                    self.add_with_carry(self.registers[low_register], imm8, false)
+            }
+            // SUBS Rd, #imm8
+            0x3800..=0x3FFF => {
+                self.registers[low_register] =
+                    self.add_with_carry(self.registers[low_register], !imm8, true)
+            }
+            // LSRS Rd, Rm, #imm5
+            0x0800..=0x0FFF => {
+                let rd = usize::from(instruction & 0x7);
+                let value = self.registers[usize::from((instruction >> 3) & 0x7)];
+                // A shift of 0 encodes a shift by 32.
+                let shift = match (instruction >> 6) & 0x1F {
+                    0 => 32,
+                    shift => u32::from(shift),
+                };
+
+                let result = value.checked_shr(shift).unwrap_or(0);
+                self.registers[rd] = result;
+                self.set_nz_flags(result);
+                self.set_flag(APSR_C, (value >> (shift - 1)) & 0x1 != 0);
+            }
+            // EORS Rd, Rm
+            0x4040..=0x407F => {
+                let rd = usize::from(instruction & 0x7);
+                let result =
+                    self.registers[rd] ^ self.registers[usize::from((instruction >> 3) & 0x7)];
+                self.registers[rd] = result;
+                self.set_nz_flags(result);
+            }
+            // MVNS Rd, Rm
+            0x43C0..=0x43FF => {
+                let result = !self.registers[usize::from((instruction >> 3) & 0x7)];
+                self.registers[usize::from(instruction & 0x7)] = result;
+                self.set_nz_flags(result);
+            }
+            // STR Rt, [Rn, #imm5 * 4] and LDR Rt, [Rn, #imm5 * 4]
+            0x6000..=0x6FFF => {
+                let rt = usize::from(instruction & 0x7);
+                let rn = usize::from((instruction >> 3) & 0x7);
+                let address =
+                    self.registers[rn].wrapping_add(u32::from((instruction >> 6) & 0x1F) * 4);
+
+                if instruction & 0x0800 == 0 {
+                    if !self.write_memory(address, 4, self.registers[rt]) {
+                        return self.lock_up("store to invalid address");
+                    }
+                } else {
+                    match self.read_memory(address, 4) {
+                        Some(value) => self.registers[rt] = value,
+                        None => return self.lock_up("load from invalid address"),
+                    }
+                }
+            }
+            // LDRB Rt, [Rn, #imm5]
+            0x7800..=0x7FFF => {
+                let rt = usize::from(instruction & 0x7);
+                let rn = usize::from((instruction >> 3) & 0x7);
+                let address = self.registers[rn].wrapping_add(u32::from((instruction >> 6) & 0x1F));
+
+                match self.read_memory(address, 1) {
+                    Some(value) => self.registers[rt] = value,
+                    None => return self.lock_up("load from invalid address"),
+                }
+            }
+            // BX Rm
+            0x4700..=0x477F if instruction & 0x7 == 0 => {
+                next_pc = self.registers[usize::from((instruction >> 3) & 0xF)] & !0x1;
+            }
+            // B<cond> <label>
+            0xD000..=0xDDFF => {
+                if self.condition_passed((instruction >> 8) & 0xF) {
+                    let offset = ((u32::from(instruction) << 24) as i32) >> 23;
+                    next_pc = pc.wrapping_add(4).wrapping_add(offset as u32);
+                }
+            }
+            // UDF #imm8, used to call the simulated flash controller.
+            0xDE00..=0xDEFF => {
+                if !self.flash_operation(imm8) {
+                    return self.lock_up("undefined instruction");
+                }
+            }
+            // B <label>
+            0xE000..=0xE7FF => {
+                let offset = ((u32::from(instruction) << 21) as i32) >> 20;
+                next_pc = pc.wrapping_add(4).wrapping_add(offset as u32);
+            }
+            _ => return self.lock_up(&format!("unsupported instruction {instruction:#06x}")),
+        }
+
+        self.registers[PC] = next_pc;
+        self.retired = true;
+    }
+
+    fn set_flag(&mut self, flag: u32, value: bool) {
+        if value {
+            self.registers[XPSR] |= flag;
+        } else {
+            self.registers[XPSR] &= !flag;
+        }
+    }
+
+    fn set_nz_flags(&mut self, result: u32) {
+        self.set_flag(APSR_N, result & (1 << 31) != 0);
+        self.set_flag(APSR_Z, result == 0);
+    }
+
+    /// Calculate `x + y + carry_in`, and set all condition flags.
+    ///
+    /// A subtraction `x - y` is calculated as `x + !y + 1`.
+    fn add_with_carry(&mut self, x: u32, y: u32, carry_in: bool) -> u32 {
+        let unsigned_sum = u64::from(x) + u64::from(y) + u64::from(carry_in);
+        let signed_sum = i64::from(x as i32) + i64::from(y as i32) + i64::from(carry_in);
+        let result = unsigned_sum as u32;
+
+        self.set_nz_flags(result);
+        self.set_flag(APSR_C, unsigned_sum != u64::from(result));
+        self.set_flag(APSR_V, signed_sum != i64::from(result as i32));
+
+        result
+    }
+
+    /// Check the condition of a conditional branch against the condition flags.
+    fn condition_passed(&self, condition: u16) -> bool {
+        let flag = |flag: u32| self.registers[XPSR] & flag != 0;
+        let (n, z, c, v) = (flag(APSR_N), flag(APSR_Z), flag(APSR_C), flag(APSR_V));
+
+        match condition {
+            0b0000 => z,
+            0b0001 => !z,
+            0b0010 => c,
+            0b0011 => !c,
+            0b0100 => n,
+            0b0101 => !n,
+            0b0110 => v,
+            0b0111 => !v,
+            0b1000 => c && !z,
+            0b1001 => !c || z,
+            0b1010 => n == v,
+            0b1011 => n != v,
+            0b1100 => !z && n == v,
+            0b1101 => z || n != v,
+            _ => true,
+        }
+    }
+
+    /// Execute an operation of the simulated flash controller. R0 is set to 0 on success, and to 1 on failure,
+    /// except for Verify, which returns an address.
+    ///
+    /// Returns `false` if the operation does not exist.
+    fn flash_operation(&mut self, operation: u32) -> bool {
+        let [address, size, buffer] = [self.registers[0], self.registers[1], self.registers[2]];
+
+        let success = match operation {
+            FLASH_INIT | FLASH_UNINIT => true,
+            FLASH_ERASE_SECTOR => self.erase_sector(address),
+            FLASH_PROGRAM_PAGE => self.program_page(address, size, buffer),
+            FLASH_ERASE_ALL => {
+                self.erase_all();
+                true
+            }
+            FLASH_BLANK_CHECK => self.is_blank(address, size, buffer as u8),
+            FLASH_READ_OPTION_BYTES => self.read_option_bytes(address, size),
+            FLASH_WRITE_OPTION_BYTES => self.write_option_bytes(address, size),
+            FLASH_PROTECT => {
+                self.option_bytes[0] = address as u8;
+                true
+            }
+            FLASH_UNPROTECT => {
+                self.erase_all();
+                self.option_bytes[0] = 0;
+                true
+            }
+            FLASH_VERIFY => {
+                self.registers[0] = self.verify(address, size, buffer);
+                return true;
+            }
+            _ => return false,
+        };
+
+        self.registers[0] = u32::from(!success);
+
+        true
+    }
+
+    fn flash_region(&mut self, address: u32, len: u32) -> Option<&mut MemoryRegion> {
+        self.memory
+            .iter_mut()
+            .find(|region| region.sector_size.is_some() && region.contains(address, len))
+    }
+
+    fn erase_all(&mut self) {
+        for region in self.memory.iter_mut() {
+            if region.sector_size.is_some() {
+                region.data.fill(0xFF);
+            }
+        }
+    }
+
+    fn is_blank(&mut self, address: u32, size: u32, erased_byte_value: u8) -> bool {
+        let Some(region) = self.flash_region(address, size) else {
+            return false;
+        };
+        let offset = region.offset(address);
+
+        region.data[offset..offset + size as usize]
+            .iter()
+            .all(|byte| *byte == erased_byte_value)
+    }
+
+    /// Compare `size` bytes of flash at `address` with the buffer, and return the end address
+    /// if they match, or the address of the first mismatch.
+    fn verify(&mut self, address: u32, size: u32, buffer: u32) -> u32 {
+        let mismatch = (0..size).find(|offset| {
+            let flash = self.read_memory(address.wrapping_add(*offset), 1);
+            let expected = self.read_memory(buffer.wrapping_add(*offset), 1);
+
+            flash.is_none() || flash != expected
+        });
+
+        address.wrapping_add(mismatch.unwrap_or(size))
+    }
+
+    fn read_option_bytes(&mut self, buffer: u32, size: u32) -> bool {
+        if size as usize > OPTION_BYTES_SIZE {
+            return false;
+        }
+
+        (0..size).all(|offset| {
+            let byte = self.option_bytes[offset as usize];
+            self.write_memory(buffer.wrapping_add(offset), 1, u32::from(byte))
+        })
+    }
+
+    fn write_option_bytes(&mut self, buffer: u32, size: u32) -> bool {
+        if size as usize > OPTION_BYTES_SIZE {
+            return false;
+        }
+
+        for offset in 0..size {
+            let Some(byte) = self.read_memory(buffer.wrapping_add(offset), 1) else {
+                return false;
+            };
+            self.option_bytes[offset as usize] = byte as u8;
+        }
+
+        true
+    }
+
+    fn erase_sector(&mut self, address: u32) -> bool {
+        let Some(region) = self.flash_region(address, 1) else {
+            return false;
+        };
+        // note(unwrap): only flash regions are returned
+        let sector_size = region.sector_size.unwrap() as usize;
+        let start = region.offset(address) / sector_size * sector_size;
+        let end = (start + sector_size).min(region.data.len());
+
+        region.data[start..end].fill(0xFF);
+
+        true
+    }
+
+    fn program_page(&mut self, address: u32, size: u32, buffer: u32) -> bool {
+        let data: Option<Vec<u8>> = (0..size)
+            .map(|offset| {
+                self.read_memory(buffer.wrapping_add(offset), 1)
+                    .map(|byte| byte as u8)
+            })
+            .collect();
+
+        let (Some(data), Some(region)) = (data, self.flash_region(address, size)) else {
+            return false;
+        };
+        let offset = region.offset(address);
+
+        // Programming can only clear bits.
+        for (target, byte) in region.data[offset..].iter_mut().zip(data) {
+            *target &= byte;
+        }
+
+        true
+    }
+}
+
+impl Default for SimulatedTarget {
+    fn default() -> Self {
+        SimulatedTarget::new()
+    }
+}
+
+/// The peripheral and component ID registers of the CoreSight components.
+fn component_id_register(address: u32) -> Option<u32> {
+    let (_, class, part) = COMPONENTS
+        .iter()
+        .find(|(base, _, _)| address & !0xFFF == *base)?;
+
+    let value = match address & 0xFFF {
+        // JEP106 continuation code of ARM
+        0xFD0 => 0x04,
+        0xFE0 => part & 0xFF,
+        // Lower bits of the JEP106 ID of ARM
+        0xFE4 => 0xB0 | (part >> 8),
+        // Upper bits of the JEP106 ID of ARM, JEDEC flag
+        0xFE8 => 0x0B,
+        0xFF0 => 0x0D,
+        0xFF4 => class << 4,
+        0xFF8 => 0x05,
+        0xFFC => 0xB1,
+        _ => return None,
+    };
+
+    Some(value)
+}
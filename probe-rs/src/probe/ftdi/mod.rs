@@ -1,8 +1,12 @@
+use crate::architecture::mips::communication_interface::MipsError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::architecture::{
-    arm::communication_interface::UninitializedArmProbe,
+    arm::communication_interface::{DapProbe, UninitializedArmProbe},
+    arm::ArmCommunicationInterface,
+    mips::communication_interface::MipsCommunicationInterface,
     riscv::communication_interface::RiscvCommunicationInterface,
 };
+use crate::probe::arm_jtag::{ProbeStatistics, RawProtocolIo, SwdSettings};
 use crate::probe::{JTAGAccess, ProbeCreationError, ScanChainElement};
 use crate::{
     DebugProbe, DebugProbeError, DebugProbeInfo, DebugProbeSelector, DebugProbeType, WireProtocol,
@@ -20,7 +24,7 @@ mod commands;
 
 use self::commands::{JtagCommand, WriteRegisterCommand};
 
-use super::{BatchExecutionError, CommandResult};
+use super::{common::bits_to_byte, BatchExecutionError, CommandResult};
 
 #[derive(Debug)]
 struct JtagChainItem {
@@ -37,6 +41,42 @@ pub(super) struct ChainParams {
     irlen: usize,
 }
 
+/// The state of the GPIO pins of an adapter: the lower byte is ADBUS, the upper byte ACBUS.
+#[derive(Clone, Copy, Debug)]
+struct PinLayout {
+    /// Output values of the pins
+    output: u16,
+    /// Direction of the pins, set bits are outputs.
+    direction: u16,
+}
+
+impl PinLayout {
+    /// Minimal values, may not work with all probes
+    ///
+    /// TCK, TDI and TMS are outputs, and TMS is high.
+    const DEFAULT: PinLayout = PinLayout {
+        output: 0x0008,
+        direction: 0x000b,
+    };
+}
+
+/// The TDI pin (ADBUS1), which drives SWDIO in SWD mode.
+const TDI_PIN: u16 = 0x0002;
+
+/// An FTDI based adapter, and how its pins are connected.
+#[derive(Debug)]
+struct FtdiDevice {
+    /// (VendorId, ProductId)
+    id: (u16, u16),
+    /// Pin setup in JTAG mode
+    jtag: PinLayout,
+    /// Pin setup in SWD mode, or `None` if the adapter can not be used for SWD.
+    ///
+    /// SWCLK is connected to TCK, and SWDIO to TDO. TDI drives SWDIO through a resistor,
+    /// and is switched to an input while the target drives SWDIO.
+    swd: Option<PinLayout>,
+}
+
 #[derive(Debug)]
 pub struct JtagAdapter {
     device: ftdi::Device,
@@ -55,18 +95,17 @@ impl JtagAdapter {
         })
     }
 
-    pub fn attach(&mut self) -> Result<(), ftdi::Error> {
+    fn attach(&mut self, layout: PinLayout) -> Result<(), ftdi::Error> {
         self.device.usb_reset()?;
         self.device.set_latency_timer(1)?;
-        self.device.set_bitmode(0x0b, ftdi::BitMode::Mpsse)?;
+        self.device
+            .set_bitmode(layout.direction as u8, ftdi::BitMode::Mpsse)?;
         self.device.usb_purge_buffers()?;
 
         let mut junk = vec![];
         let _ = self.device.read_to_end(&mut junk);
 
-        // Minimal values, may not work with all probes
-        let output: u16 = 0x0008;
-        let direction: u16 = 0x000b;
+        let PinLayout { output, direction } = layout;
         self.device
             .write_all(&[0x80, output as u8, direction as u8])?;
         self.device
@@ -205,6 +244,96 @@ impl JtagAdapter {
         Ok(reply)
     }
 
+    /// Clock out the `tms` bits, while TDI is held at `tdi`.
+    fn shift_tms_bits(&mut self, tms: &[bool], tdi: bool) -> io::Result<()> {
+        let mut command = vec![];
+
+        // A single command clocks out at most 7 bits, bit 7 is the value of TDI.
+        for chunk in tms.chunks(7) {
+            let byte = bits_to_byte(chunk.iter().copied()) as u8 | (tdi as u8) << 7;
+            command.extend_from_slice(&[0x4b, (chunk.len() - 1) as u8, byte]);
+        }
+
+        self.device.write_all(&command)
+    }
+
+    /// Clock out the `tdi` bits, while TMS is held at `tms`.
+    fn shift_tdi_bits(&mut self, tms: bool, tdi: &[bool]) -> io::Result<()> {
+        let mut command = vec![];
+
+        // The TMS command is the only one which sets TMS, so each bit needs its own command.
+        for bit in tdi {
+            command.extend_from_slice(&[0x4b, 0x00, tms as u8 | (*bit as u8) << 7]);
+        }
+
+        self.device.write_all(&command)
+    }
+
+    /// Perform SWD IO: The bits in `swdio` are clocked out on TDI, and TDO is sampled for every bit.
+    ///
+    /// Where `dir` is `false`, the target drives SWDIO, so TDI is switched to an input.
+    fn swd_io(&mut self, layout: PinLayout, dir: &[bool], swdio: &[bool]) -> io::Result<Vec<bool>> {
+        assert_eq!(dir.len(), swdio.len());
+
+        let mut command = vec![];
+        let mut read_lengths = vec![];
+        let mut driving = None;
+
+        let mut index = 0;
+        while index < dir.len() {
+            let output = dir[index];
+            let run = dir[index..].iter().take_while(|&&d| d == output).count();
+
+            if driving != Some(output) {
+                let direction = if output {
+                    layout.direction | TDI_PIN
+                } else {
+                    layout.direction & !TDI_PIN
+                };
+                command.extend_from_slice(&[0x80, layout.output as u8, direction as u8]);
+                driving = Some(output);
+            }
+
+            // Clock data bits out on the falling edge and in on the rising edge, LSB first.
+            for chunk in swdio[index..index + run].chunks(8) {
+                command.extend_from_slice(&[
+                    0x3b,
+                    (chunk.len() - 1) as u8,
+                    bits_to_byte(chunk.iter().copied()) as u8,
+                ]);
+                read_lengths.push(chunk.len());
+            }
+
+            index += run;
+        }
+
+        // The probe drives SWDIO while the line is idle.
+        if driving == Some(false) {
+            command.extend_from_slice(&[
+                0x80,
+                layout.output as u8,
+                (layout.direction | TDI_PIN) as u8,
+            ]);
+        }
+
+        // Send Immediate
+        command.push(0x87);
+
+        self.device.write_all(&command)?;
+
+        let reply = self.read_response(read_lengths.len())?;
+
+        // The bits are shifted in from the top, so the first bit
+        // of a command with n bits ends up in bit 8 - n.
+        let bits = reply
+            .iter()
+            .zip(read_lengths)
+            .flat_map(|(byte, length)| (8 - length..8).map(move |bit| byte & (1 << bit) != 0))
+            .collect();
+
+        Ok(bits)
+    }
+
     /// Reset and go to RUN-TEST/IDLE
     pub fn reset(&mut self) -> io::Result<()> {
         self.shift_tms(&[0xff, 0xff, 0xff, 0xff, 0x7f], 40)
@@ -419,9 +548,22 @@ impl JtagAdapter {
 #[derive(Debug)]
 pub struct FtdiProbe {
     adapter: JtagAdapter,
+    device: &'static FtdiDevice,
+    protocol: WireProtocol,
     speed_khz: u32,
     idle_cycles: u8,
     scan_chain: Option<Vec<ScanChainElement>>,
+    swd_settings: SwdSettings,
+    probe_statistics: ProbeStatistics,
+}
+
+impl FtdiProbe {
+    /// The pin layout of the adapter in SWD mode.
+    fn swd_layout(&self) -> Result<PinLayout, DebugProbeError> {
+        self.device
+            .swd
+            .ok_or(DebugProbeError::UnsupportedProtocol(WireProtocol::Swd))
+    }
 }
 
 impl DebugProbe for FtdiProbe {
@@ -438,20 +580,27 @@ impl DebugProbe for FtdiProbe {
         } = selector.into();
 
         // Only open FTDI-compatible probes
-        if !FTDI_COMPAT_DEVICE_IDS.contains(&(vendor_id, product_id)) {
+        let Some(device) = FTDI_COMPAT_DEVICES
+            .iter()
+            .find(|device| device.id == (vendor_id, product_id))
+        else {
             return Err(DebugProbeError::ProbeCouldNotBeCreated(
                 ProbeCreationError::NotFound,
             ));
-        }
+        };
 
         let adapter = JtagAdapter::open(vendor_id, product_id)
             .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
 
         let probe = FtdiProbe {
             adapter,
+            device,
+            protocol: WireProtocol::Jtag,
             speed_khz: 0,
             idle_cycles: 0,
             scan_chain: None,
+            swd_settings: SwdSettings::default(),
+            probe_statistics: ProbeStatistics::default(),
         };
         tracing::debug!("opened probe: {:?}", probe);
         Ok(Box::new(probe))
@@ -486,8 +635,17 @@ impl DebugProbe for FtdiProbe {
     fn attach(&mut self) -> Result<(), DebugProbeError> {
         tracing::debug!("attaching...");
 
+        if self.protocol == WireProtocol::Swd {
+            let layout = self.swd_layout()?;
+
+            return self
+                .adapter
+                .attach(layout)
+                .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)));
+        }
+
         self.adapter
-            .attach()
+            .attach(self.device.jtag)
             .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
 
         let taps = self
@@ -541,16 +699,18 @@ impl DebugProbe for FtdiProbe {
     }
 
     fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
-        if protocol != WireProtocol::Jtag {
-            Err(DebugProbeError::UnsupportedProtocol(protocol))
-        } else {
-            Ok(())
+        if protocol == WireProtocol::Swd {
+            // SWD needs an adapter which is wired for it
+            self.swd_layout()?;
         }
+
+        self.protocol = protocol;
+
+        Ok(())
     }
 
     fn active_protocol(&self) -> Option<WireProtocol> {
-        // Only supports JTAG
-        Some(WireProtocol::Jtag)
+        Some(self.protocol)
     }
 
     fn try_get_riscv_interface(
@@ -566,18 +726,109 @@ impl DebugProbe for FtdiProbe {
         true
     }
 
+    fn try_get_mips_interface(
+        self: Box<Self>,
+    ) -> Result<MipsCommunicationInterface, (Box<dyn DebugProbe>, MipsError)> {
+        match MipsCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_mips_interface(&self) -> bool {
+        true
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.protocol == WireProtocol::Swd
+    }
+
     fn try_get_arm_interface<'probe>(
         self: Box<Self>,
     ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
     {
-        todo!()
+        // ARM targets are only supported using SWD
+        if self.protocol != WireProtocol::Swd {
+            return Err((self, DebugProbeError::InterfaceNotAvailable("ARM")));
+        }
+
+        let uninitialized_interface = ArmCommunicationInterface::new(self, true);
+
+        Ok(Box::new(uninitialized_interface))
+    }
+}
+
+impl RawProtocolIo for FtdiProbe {
+    fn jtag_shift_tms<M>(&mut self, tms: M, tdi: bool) -> Result<(), DebugProbeError>
+    where
+        M: IntoIterator<Item = bool>,
+    {
+        if self.protocol == WireProtocol::Swd {
+            panic!("Logic error, requested jtag_io when in SWD mode");
+        }
+
+        self.probe_statistics.report_io();
+        let tms: Vec<_> = tms.into_iter().collect();
+
+        self.adapter
+            .shift_tms_bits(&tms, tdi)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
+    }
+
+    fn jtag_shift_tdi<I>(&mut self, tms: bool, tdi: I) -> Result<(), DebugProbeError>
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        if self.protocol == WireProtocol::Swd {
+            panic!("Logic error, requested jtag_io when in SWD mode");
+        }
+
+        self.probe_statistics.report_io();
+        let tdi: Vec<_> = tdi.into_iter().collect();
+
+        self.adapter
+            .shift_tdi_bits(tms, &tdi)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
+    }
+
+    fn swd_io<D, S>(&mut self, dir: D, swdio: S) -> Result<Vec<bool>, DebugProbeError>
+    where
+        D: IntoIterator<Item = bool>,
+        S: IntoIterator<Item = bool>,
+    {
+        if self.protocol == WireProtocol::Jtag {
+            panic!("Logic error, requested swd_io when in JTAG mode");
+        }
+
+        self.probe_statistics.report_io();
+        let layout = self.swd_layout()?;
+        let dir: Vec<_> = dir.into_iter().collect();
+        let swdio: Vec<_> = swdio.into_iter().collect();
+
+        self.adapter
+            .swd_io(layout, &dir, &swdio)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
+    }
+
+    fn swd_settings(&self) -> &SwdSettings {
+        &self.swd_settings
+    }
+
+    fn probe_statistics(&mut self) -> &mut ProbeStatistics {
+        &mut self.probe_statistics
     }
 }
 
+impl DapProbe for FtdiProbe {}
+
 impl JTAGAccess for FtdiProbe {
     fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
         tracing::debug!("read_register({:#x}, {})", address, len);
@@ -767,20 +1018,40 @@ impl JTAGAccess for FtdiProbe {
     }
 }
 
-/// (VendorId, ProductId)
-static FTDI_COMPAT_DEVICE_IDS: &[(u16, u16)] = &[
-    (0x0403, 0x6010), // FTDI Ltd. FT2232C/D/H Dual UART/FIFO IC
-    (0x0403, 0x6011), // FTDI Ltd. FT4232H Quad HS USB-UART/FIFO IC
-    (0x0403, 0x6014), // FTDI Ltd. FT232H Single HS USB-UART/FIFO IC
-    (0x15ba, 0x002a), // Olimex Ltd. ARM-USB-TINY-H JTAG interface
+/// Known FTDI based adapters
+static FTDI_COMPAT_DEVICES: &[FtdiDevice] = &[
+    // FTDI Ltd. FT2232C/D/H Dual UART/FIFO IC
+    FtdiDevice {
+        id: (0x0403, 0x6010),
+        jtag: PinLayout::DEFAULT,
+        swd: Some(PinLayout::DEFAULT),
+    },
+    // FTDI Ltd. FT4232H Quad HS USB-UART/FIFO IC
+    FtdiDevice {
+        id: (0x0403, 0x6011),
+        jtag: PinLayout::DEFAULT,
+        swd: Some(PinLayout::DEFAULT),
+    },
+    // FTDI Ltd. FT232H Single HS USB-UART/FIFO IC
+    FtdiDevice {
+        id: (0x0403, 0x6014),
+        jtag: PinLayout::DEFAULT,
+        swd: Some(PinLayout::DEFAULT),
+    },
+    // Olimex Ltd. ARM-USB-TINY-H JTAG interface, which needs an external adapter for SWD.
+    FtdiDevice {
+        id: (0x15ba, 0x002a),
+        jtag: PinLayout::DEFAULT,
+        swd: None,
+    },
 ];
 
 fn get_device_info(device: &rusb::Device<rusb::Context>) -> Option<DebugProbeInfo> {
     let d_desc = device.device_descriptor().ok()?;
 
-    if !FTDI_COMPAT_DEVICE_IDS
+    if !FTDI_COMPAT_DEVICES
         .iter()
-        .any(|(vid, pid)| d_desc.vendor_id() == *vid && d_desc.product_id() == *pid)
+        .any(|device| device.id == (d_desc.vendor_id(), d_desc.product_id()))
     {
         return None;
     }
@@ -8,6 +8,7 @@ use std::iter;
 use std::time::{Duration, Instant};
 
 use crate::architecture::arm::{ArmError, RawDapAccess};
+use crate::architecture::mips::communication_interface::MipsError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::probe::common::bits_to_byte;
 use crate::{
@@ -16,6 +17,7 @@ use crate::{
             communication_interface::DapProbe, communication_interface::UninitializedArmProbe,
             swo::SwoConfig, ArmCommunicationInterface, SwoAccess,
         },
+        mips::communication_interface::MipsCommunicationInterface,
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     probe::{
@@ -588,6 +590,22 @@ impl DebugProbe for JLink {
         }
     }
 
+    fn try_get_mips_interface(
+        self: Box<Self>,
+    ) -> Result<MipsCommunicationInterface, (Box<dyn DebugProbe>, MipsError)> {
+        if self.supported_protocols.contains(&WireProtocol::Jtag) {
+            match MipsCommunicationInterface::new(self) {
+                Ok(interface) => Ok(interface),
+                Err((probe, err)) => Err((probe.into_probe(), err)),
+            }
+        } else {
+            Err((
+                RawDapAccess::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("JTAG").into(),
+            ))
+        }
+    }
+
     fn get_swo_interface(&self) -> Option<&dyn SwoAccess> {
         Some(self as _)
     }
@@ -604,6 +622,10 @@ impl DebugProbe for JLink {
         self.supported_protocols.contains(&WireProtocol::Jtag)
     }
 
+    fn has_mips_interface(&self) -> bool {
+        self.supported_protocols.contains(&WireProtocol::Jtag)
+    }
+
     fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
         self
     }
@@ -612,6 +634,10 @@ impl DebugProbe for JLink {
         Some(self)
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_get_arm_interface<'probe>(
         self: Box<Self>,
     ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
@@ -0,0 +1,883 @@
+//! Debug probes attached to another machine, served by `probe-rs serve`.
+//!
+//! The [`RemoteProbe`] forwards the low-level probe interfaces ([`RawDapAccess`] and
+//! [`JTAGAccess`]) to a [`ProbeServer`], everything built on top of them runs locally.
+//! Commands which don't return anything, like DAP register writes, are queued and sent
+//! together with the next command that does, to save round trips.
+
+mod protocol;
+mod server;
+
+pub use server::ProbeServer;
+
+use std::net::TcpStream;
+
+use probe_rs_target::ScanChainElement;
+
+use crate::{
+    architecture::{
+        arm::{
+            communication_interface::{DapProbe, UninitializedArmProbe},
+            ArmCommunicationInterface, ArmError, DpAddress, PortType, RawDapAccess,
+        },
+        mips::communication_interface::{MipsCommunicationInterface, MipsError},
+        riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+    },
+    probe::{BatchExecutionError, CommandResult, JTAGAccess, JtagWriteCommand},
+    CoreStatus, DebugProbe, DebugProbeError, DebugProbeSelector, ProbeCreationError, WireProtocol,
+};
+
+use self::protocol::{
+    ClientMessage, Command, JtagWrite, ProbeDescription, RemoteError, Reply, ServerMessage,
+    PROTOCOL_VERSION,
+};
+
+/// Queued commands are sent at the latest when there are this many of them.
+const MAX_QUEUED_COMMANDS: usize = 1024;
+
+/// An error in the communication with a [`ProbeServer`].
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteProbeError {
+    /// The connection to the server failed.
+    #[error("Communication with the probe server failed")]
+    Io(#[from] std::io::Error),
+    /// A message could not be encoded or decoded.
+    #[error("Invalid message")]
+    Encoding(#[from] bincode::Error),
+    /// A message exceeded the maximum message size.
+    #[error("A message of {0} bytes is larger than the maximum message size")]
+    MessageTooLarge(usize),
+    /// The other side sent a message which is not valid at this point.
+    #[error("Unexpected message")]
+    UnexpectedMessage,
+    /// The probe on the server reported an error.
+    #[error("The probe server reported an error: {0}")]
+    Server(String),
+}
+
+impl From<RemoteProbeError> for DebugProbeError {
+    fn from(error: RemoteProbeError) -> Self {
+        DebugProbeError::ProbeSpecific(Box::new(error))
+    }
+}
+
+/// A command sent to the server did not succeed.
+#[derive(Debug)]
+enum ExecuteError {
+    /// The connection to the server failed.
+    Connection(RemoteProbeError),
+    /// The probe on the server reported an error.
+    Remote(RemoteError),
+}
+
+impl From<RemoteProbeError> for ExecuteError {
+    fn from(error: RemoteProbeError) -> Self {
+        ExecuteError::Connection(error)
+    }
+}
+
+impl From<ExecuteError> for DebugProbeError {
+    fn from(error: ExecuteError) -> Self {
+        match error {
+            ExecuteError::Connection(error) => error.into(),
+            ExecuteError::Remote(RemoteError::Timeout) => DebugProbeError::Timeout,
+            ExecuteError::Remote(error) => RemoteProbeError::Server(error.to_string()).into(),
+        }
+    }
+}
+
+impl From<ExecuteError> for ArmError {
+    fn from(error: ExecuteError) -> Self {
+        match error {
+            ExecuteError::Remote(RemoteError::Dap(error)) => ArmError::Dap(error),
+            ExecuteError::Remote(RemoteError::Timeout) => ArmError::Timeout,
+            other => ArmError::Probe(other.into()),
+        }
+    }
+}
+
+impl From<ExecuteError> for crate::Error {
+    fn from(error: ExecuteError) -> Self {
+        crate::Error::Probe(error.into())
+    }
+}
+
+/// A debug probe attached to another machine, which is running `probe-rs serve`.
+///
+/// This is opened through [`Probe::open`](crate::Probe::open) with a
+/// [`DebugProbeSelector`] of the form `tcp://HOST:PORT/SELECTOR`.
+#[derive(Debug)]
+pub struct RemoteProbe {
+    stream: TcpStream,
+    name: String,
+    speed_khz: u32,
+    protocol: Option<WireProtocol>,
+    has_arm_interface: bool,
+    has_riscv_interface: bool,
+    has_mips_interface: bool,
+    has_jtag_access: bool,
+    idle_cycles: u8,
+    /// Commands without a result, which have not been sent yet.
+    queue: Vec<Command>,
+}
+
+impl RemoteProbe {
+    fn connect(address: &str, selector: DebugProbeSelector) -> Result<Self, RemoteProbeError> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+
+        protocol::send(
+            &mut stream,
+            &ClientMessage::Open {
+                version: PROTOCOL_VERSION,
+                vendor_id: selector.vendor_id,
+                product_id: selector.product_id,
+                serial_number: selector.serial_number,
+            },
+        )?;
+
+        let description: ProbeDescription = match protocol::receive(&mut stream)? {
+            ServerMessage::Opened(Ok(description)) => description,
+            ServerMessage::Opened(Err(error)) => {
+                return Err(RemoteProbeError::Server(error.to_string()))
+            }
+            ServerMessage::Executed(_) => return Err(RemoteProbeError::UnexpectedMessage),
+        };
+
+        Ok(Self {
+            stream,
+            name: description.name,
+            speed_khz: description.speed_khz,
+            protocol: description.protocol,
+            has_arm_interface: description.has_arm_interface,
+            has_riscv_interface: description.has_riscv_interface,
+            has_mips_interface: description.has_mips_interface,
+            has_jtag_access: description.has_jtag_access,
+            idle_cycles: 0,
+            queue: Vec::new(),
+        })
+    }
+
+    /// Send all queued commands to the server, followed by `command`, and return its reply.
+    fn execute(&mut self, command: Command) -> Result<Reply, ExecuteError> {
+        self.queue.push(command);
+
+        let mut replies = self.send_queue()?;
+
+        replies.pop().ok_or_else(unexpected_reply)
+    }
+
+    /// Like [`RemoteProbe::execute`], for commands which don't have a result.
+    fn execute_unit(&mut self, command: Command) -> Result<(), ExecuteError> {
+        match self.execute(command)? {
+            Reply::None => Ok(()),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Queue a command without a result, it is sent with the next command that has one.
+    fn enqueue(&mut self, command: Command) -> Result<(), ExecuteError> {
+        debug_assert!(command.is_deferrable());
+        self.queue.push(command);
+
+        if self.queue.len() >= MAX_QUEUED_COMMANDS {
+            self.send_queue()?;
+        }
+
+        Ok(())
+    }
+
+    fn send_queue(&mut self) -> Result<Vec<Reply>, ExecuteError> {
+        let commands = std::mem::take(&mut self.queue);
+        let count = commands.len();
+
+        tracing::trace!("Sending batch of {} commands", count);
+        protocol::send(&mut self.stream, &ClientMessage::Execute(commands))?;
+
+        match protocol::receive(&mut self.stream)? {
+            ServerMessage::Executed(Ok(replies)) if replies.len() == count => Ok(replies),
+            ServerMessage::Executed(Err(failure)) => {
+                tracing::debug!(
+                    "Command {} of batch of {} commands failed: {}",
+                    failure.index,
+                    count,
+                    failure.error
+                );
+                Err(ExecuteError::Remote(failure.error))
+            }
+            _ => Err(RemoteProbeError::UnexpectedMessage.into()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), ExecuteError> {
+        if !self.queue.is_empty() {
+            self.send_queue()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn unexpected_reply() -> ExecuteError {
+    ExecuteError::Connection(RemoteProbeError::UnexpectedMessage)
+}
+
+impl DebugProbe for RemoteProbe {
+    fn new_from_selector(
+        selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        let selector = selector.into();
+
+        let Some(address) = selector.remote_address.clone() else {
+            return Err(DebugProbeError::ProbeCouldNotBeCreated(
+                ProbeCreationError::NotFound,
+            ));
+        };
+
+        let probe = Self::connect(&address, selector).map_err(|e| {
+            DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::ProbeSpecific(Box::new(e)))
+        })?;
+
+        Ok(Box::new(probe))
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.speed_khz
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        match self.execute(Command::SetSpeed(speed_khz))? {
+            Reply::Speed(actual_speed_khz) => {
+                self.speed_khz = actual_speed_khz;
+                Ok(actual_speed_khz)
+            }
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn set_scan_chain(&mut self, scan_chain: Vec<ScanChainElement>) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::SetScanChain(scan_chain))?)
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        match self.execute(Command::Attach)? {
+            Reply::Protocol(protocol) => {
+                self.protocol = protocol;
+                Ok(())
+            }
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        Ok(self.execute_unit(Command::Detach)?)
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::TargetReset)?)
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::TargetResetAssert)?)
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::TargetResetDeassert)?)
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        match self.execute(Command::SelectProtocol(protocol))? {
+            Reply::Protocol(protocol) => {
+                self.protocol = protocol;
+                Ok(())
+            }
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.protocol
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.has_arm_interface
+    }
+
+    fn try_get_arm_interface<'probe>(
+        self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        if !self.has_arm_interface {
+            return Err((
+                DebugProbe::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("ARM"),
+            ));
+        }
+
+        Ok(Box::new(ArmCommunicationInterface::new(self, false)))
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.has_riscv_interface
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        if !self.has_riscv_interface {
+            return Err((
+                DebugProbe::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("RISCV").into(),
+            ));
+        }
+
+        match RiscvCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_mips_interface(&self) -> bool {
+        self.has_mips_interface
+    }
+
+    fn try_get_mips_interface(
+        self: Box<Self>,
+    ) -> Result<MipsCommunicationInterface, (Box<dyn DebugProbe>, MipsError)> {
+        if !self.has_mips_interface {
+            return Err((
+                DebugProbe::into_probe(self),
+                DebugProbeError::InterfaceNotAvailable("MIPS").into(),
+            ));
+        }
+
+        match MipsCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        if self.has_arm_interface {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        if self.has_jtag_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
+        match self.execute(Command::GetTargetVoltage)? {
+            Reply::Voltage(voltage) => Ok(voltage),
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+}
+
+impl RawDapAccess for RemoteProbe {
+    fn raw_flush(&mut self) -> Result<(), ArmError> {
+        Ok(self.execute_unit(Command::RawFlush)?)
+    }
+
+    fn configure_jtag(&mut self) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::ConfigureJtag)?)
+    }
+
+    fn select_dp(&mut self, dp: DpAddress) -> Result<(), ArmError> {
+        Ok(self.execute_unit(Command::SelectDp(dp))?)
+    }
+
+    fn raw_read_register(&mut self, port: PortType, addr: u8) -> Result<u32, ArmError> {
+        match self.execute(Command::RawReadRegister { port, addr })? {
+            Reply::Value(value) => Ok(value),
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn raw_read_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &mut [u32],
+    ) -> Result<(), ArmError> {
+        let command = Command::RawReadBlock {
+            port,
+            addr,
+            len: values.len(),
+        };
+
+        match self.execute(command)? {
+            Reply::Values(read) if read.len() == values.len() => {
+                values.copy_from_slice(&read);
+                Ok(())
+            }
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn raw_write_register(&mut self, port: PortType, addr: u8, value: u32) -> Result<(), ArmError> {
+        Ok(self.enqueue(Command::RawWriteRegister { port, addr, value })?)
+    }
+
+    fn raw_write_block(
+        &mut self,
+        port: PortType,
+        addr: u8,
+        values: &[u32],
+    ) -> Result<(), ArmError> {
+        Ok(self.enqueue(Command::RawWriteBlock {
+            port,
+            addr,
+            values: values.to_vec(),
+        })?)
+    }
+
+    fn jtag_sequence(&mut self, cycles: u8, tms: bool, tdi: u64) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::JtagSequence { cycles, tms, tdi })?)
+    }
+
+    fn swj_sequence(&mut self, bit_len: u8, bits: u64) -> Result<(), DebugProbeError> {
+        Ok(self.execute_unit(Command::SwjSequence { bit_len, bits })?)
+    }
+
+    fn swj_pins(
+        &mut self,
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    ) -> Result<u32, DebugProbeError> {
+        let command = Command::SwjPins {
+            pin_out,
+            pin_select,
+            pin_wait,
+        };
+
+        match self.execute(command)? {
+            Reply::Value(pins) => Ok(pins),
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn core_status_notification(&mut self, state: CoreStatus) -> Result<(), DebugProbeError> {
+        Ok(self.enqueue(Command::CoreStatusNotification(state))?)
+    }
+}
+
+impl DapProbe for RemoteProbe {}
+
+impl JTAGAccess for RemoteProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        match self.execute(Command::JtagReadRegister { address, len })? {
+            Reply::Bits(data) => Ok(data),
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.idle_cycles = idle_cycles;
+
+        // This can't report errors, so leave it to the next command to send it.
+        self.queue.push(Command::SetIdleCycles(idle_cycles));
+    }
+
+    fn get_idle_cycles(&self) -> u8 {
+        self.idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        self.queue.push(Command::SetIrLen(len));
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let command = Command::JtagWriteRegister {
+            address,
+            data: data.to_vec(),
+            len,
+        };
+
+        match self.execute(command)? {
+            Reply::Bits(data) => Ok(data),
+            _ => Err(unexpected_reply().into()),
+        }
+    }
+
+    fn write_register_batch(
+        &mut self,
+        writes: &[JtagWriteCommand],
+    ) -> Result<Vec<CommandResult>, BatchExecutionError> {
+        let command = Command::JtagWriteRegisterBatch(
+            writes
+                .iter()
+                .map(|write| JtagWrite {
+                    address: write.address,
+                    data: write.data.clone(),
+                    len: write.len,
+                })
+                .collect(),
+        );
+
+        let (shifted_out, error) = match self.execute(command) {
+            Ok(Reply::JtagBatch { results, error }) => (results, error),
+            Ok(_) => return Err(BatchExecutionError::new(unexpected_reply().into(), vec![])),
+            Err(e) => return Err(BatchExecutionError::new(e.into(), vec![])),
+        };
+
+        let mut results = Vec::with_capacity(shifted_out.len());
+        for (write, data) in writes.iter().zip(shifted_out) {
+            match (write.transform)(data) {
+                Ok(result) => results.push(result),
+                Err(e) => return Err(BatchExecutionError::new(e, results)),
+            }
+        }
+
+        if let Some(error) = error {
+            return Err(BatchExecutionError::new(
+                ExecuteError::Remote(error).into(),
+                results,
+            ));
+        }
+
+        Ok(results)
+    }
+}
+
+impl Drop for RemoteProbe {
+    fn drop(&mut self) {
+        // Don't lose writes which are still queued.
+        if let Err(e) = self.flush() {
+            tracing::warn!(
+                "Failed to send queued commands to the probe server: {:?}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use probe_rs_target::ScanChainElement;
+
+    use super::server::serve_connection;
+    use crate::{
+        architecture::arm::{ArmError, DapError, PortType},
+        probe::{CommandResult, JTAGAccess, JtagWriteCommand},
+        DebugProbe, DebugProbeError, DebugProbeSelector, FakeProbe, Probe, WireProtocol,
+    };
+
+    /// Serve a single client from `probe`, and open it through TCP.
+    fn open_remote_probe(probe: Probe) -> Probe {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_connection(stream, |_| Ok(probe)).unwrap();
+        });
+
+        let selector: DebugProbeSelector = format!("tcp://{address}/").parse().unwrap();
+        Probe::open(selector).unwrap()
+    }
+
+    /// Serve a single client from a [`FakeProbe`], and open it through TCP.
+    fn open_fake_probe(fake_probe: FakeProbe) -> Probe {
+        open_remote_probe(fake_probe.into_probe())
+    }
+
+    /// A JTAG call received by a [`JtagRecorder`].
+    #[derive(Debug, PartialEq, Eq)]
+    enum JtagCall {
+        SetIrLen(u32),
+        SetIdleCycles(u8),
+        Read {
+            address: u32,
+            len: u32,
+        },
+        Write {
+            address: u32,
+            data: Vec<u8>,
+            len: u32,
+        },
+    }
+
+    /// A JTAG probe which records all calls. Reads return the instruction,
+    /// writes shift out the inverted data.
+    #[derive(Debug)]
+    struct JtagRecorder {
+        calls: Arc<Mutex<Vec<JtagCall>>>,
+        idle_cycles: u8,
+    }
+
+    impl JTAGAccess for JtagRecorder {
+        fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(JtagCall::Read { address, len });
+            Ok(address.to_le_bytes().to_vec())
+        }
+
+        fn set_idle_cycles(&mut self, idle_cycles: u8) {
+            self.idle_cycles = idle_cycles;
+            self.calls
+                .lock()
+                .unwrap()
+                .push(JtagCall::SetIdleCycles(idle_cycles));
+        }
+
+        fn get_idle_cycles(&self) -> u8 {
+            self.idle_cycles
+        }
+
+        fn set_ir_len(&mut self, len: u32) {
+            self.calls.lock().unwrap().push(JtagCall::SetIrLen(len));
+        }
+
+        fn write_register(
+            &mut self,
+            address: u32,
+            data: &[u8],
+            len: u32,
+        ) -> Result<Vec<u8>, DebugProbeError> {
+            self.calls.lock().unwrap().push(JtagCall::Write {
+                address,
+                data: data.to_vec(),
+                len,
+            });
+            Ok(data.iter().map(|byte| !byte).collect())
+        }
+    }
+
+    impl DebugProbe for JtagRecorder {
+        fn new_from_selector(
+            _selector: impl Into<DebugProbeSelector>,
+        ) -> Result<Box<Self>, DebugProbeError>
+        where
+            Self: Sized,
+        {
+            unimplemented!()
+        }
+
+        fn get_name(&self) -> &str {
+            "JTAG recorder"
+        }
+
+        fn speed_khz(&self) -> u32 {
+            1000
+        }
+
+        fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+            Ok(speed_khz)
+        }
+
+        fn set_scan_chain(
+            &mut self,
+            _scan_chain: Vec<ScanChainElement>,
+        ) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn attach(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn detach(&mut self) -> Result<(), crate::Error> {
+            Ok(())
+        }
+
+        fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn select_protocol(&mut self, _protocol: WireProtocol) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn active_protocol(&self) -> Option<WireProtocol> {
+            Some(WireProtocol::Jtag)
+        }
+
+        fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+            self
+        }
+
+        fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn forward_dap_accesses() {
+        let writes = Arc::new(Mutex::new(Vec::new()));
+
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.set_dap_register_read_handler(Box::new(|_port, addr| Ok(0x1000 + addr as u32)));
+        let recorded_writes = writes.clone();
+        fake_probe.set_dap_register_write_handler(Box::new(move |port, addr, value| {
+            recorded_writes.lock().unwrap().push((port, addr, value));
+            Ok(())
+        }));
+
+        let mut probe = open_fake_probe(fake_probe);
+        assert_eq!(probe.get_name(), "Mock probe for testing");
+        assert_eq!(probe.set_speed(4000).unwrap(), 4000);
+        assert_eq!(probe.speed_khz(), 4000);
+
+        let dap = probe.try_as_dap_probe().unwrap();
+        dap.raw_write_register(PortType::DebugPort, 0x8, 0x0)
+            .unwrap();
+        dap.raw_write_block(PortType::AccessPort, 0xC, &[1, 2])
+            .unwrap();
+
+        // The writes are queued until something has to be read.
+        assert!(writes.lock().unwrap().is_empty());
+
+        assert_eq!(
+            dap.raw_read_register(PortType::AccessPort, 0x4).unwrap(),
+            0x1004
+        );
+        assert_eq!(
+            *writes.lock().unwrap(),
+            vec![
+                (PortType::DebugPort, 0x8, 0x0),
+                (PortType::AccessPort, 0xC, 1),
+                (PortType::AccessPort, 0xC, 2),
+            ]
+        );
+
+        let mut values = [0; 3];
+        dap.raw_read_block(PortType::AccessPort, 0xC, &mut values)
+            .unwrap();
+        assert_eq!(values, [0x100C; 3]);
+    }
+
+    #[test]
+    fn forward_dap_errors() {
+        let mut fake_probe = FakeProbe::new();
+        fake_probe.set_dap_register_read_handler(Box::new(|_port, _addr| Ok(0)));
+        fake_probe.set_dap_register_write_handler(Box::new(|_port, addr, _value| {
+            if addr == 0x4 {
+                Err(DapError::FaultResponse.into())
+            } else {
+                Ok(())
+            }
+        }));
+
+        let mut probe = open_fake_probe(fake_probe);
+        let dap = probe.try_as_dap_probe().unwrap();
+
+        // The failing write is only reported with the next command which is sent.
+        dap.raw_write_register(PortType::AccessPort, 0x4, 0x0)
+            .unwrap();
+        let error = dap
+            .raw_read_register(PortType::AccessPort, 0x0)
+            .unwrap_err();
+        assert!(matches!(error, ArmError::Dap(DapError::FaultResponse)));
+
+        // The batch is discarded after an error.
+        dap.raw_read_register(PortType::AccessPort, 0x0).unwrap();
+    }
+
+    #[test]
+    fn forward_jtag_accesses() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorder = JtagRecorder {
+            calls: calls.clone(),
+            idle_cycles: 0,
+        };
+
+        let mut probe = open_remote_probe(Probe::from_specific_probe(Box::new(recorder)));
+        let jtag = probe.inner.try_as_jtag_probe().unwrap();
+
+        // The settings are queued until the next command is sent.
+        jtag.set_ir_len(5);
+        jtag.set_idle_cycles(7);
+        assert_eq!(jtag.get_idle_cycles(), 7);
+        assert!(calls.lock().unwrap().is_empty());
+
+        assert_eq!(jtag.read_register(0x1, 32).unwrap(), vec![1, 0, 0, 0]);
+        assert_eq!(
+            jtag.write_register(0x11, &[0x0f, 0xf0], 12).unwrap(),
+            vec![0xf0, 0x0f]
+        );
+
+        let writes = [0x12, 0x34].map(|value| JtagWriteCommand {
+            address: 0x11,
+            data: vec![value],
+            len: 8,
+            transform: |data| Ok(CommandResult::U8(data[0])),
+        });
+        let results = jtag.write_register_batch(&writes).unwrap();
+        assert!(matches!(
+            results[..],
+            [CommandResult::U8(0xed), CommandResult::U8(0xcb)]
+        ));
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                JtagCall::SetIrLen(5),
+                JtagCall::SetIdleCycles(7),
+                JtagCall::Read {
+                    address: 0x1,
+                    len: 32
+                },
+                JtagCall::Write {
+                    address: 0x11,
+                    data: vec![0x0f, 0xf0],
+                    len: 12
+                },
+                JtagCall::Write {
+                    address: 0x11,
+                    data: vec![0x12],
+                    len: 8
+                },
+                JtagCall::Write {
+                    address: 0x11,
+                    data: vec![0x34],
+                    len: 8
+                },
+            ]
+        );
+    }
+}
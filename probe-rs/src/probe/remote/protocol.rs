@@ -0,0 +1,309 @@
+//! The wire format spoken between a [`RemoteProbe`](super::RemoteProbe) and a
+//! [`ProbeServer`](super::ProbeServer).
+//!
+//! Every message is a little endian `u32` length followed by the `bincode` encoded message.
+//! The client opens a probe once, then sends batches of [`Command`]s. The server executes
+//! a batch in order, stops at the first failing command and answers with one [`Reply`]
+//! per successfully executed command.
+
+use std::io::{Read, Write};
+
+use probe_rs_target::ScanChainElement;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    architecture::arm::{ArmError, DapError, DpAddress, PortType},
+    CoreStatus, DebugProbeError, WireProtocol,
+};
+
+use super::RemoteProbeError;
+
+/// Incremented whenever the messages below change in an incompatible way.
+pub(crate) const PROTOCOL_VERSION: u32 = 1;
+
+/// Messages larger than this are rejected, to avoid allocating arbitrary amounts of memory.
+pub(crate) const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum ClientMessage {
+    /// Open a probe on the server. This has to be the first message of a connection.
+    Open {
+        version: u32,
+        vendor_id: u16,
+        product_id: u16,
+        serial_number: Option<String>,
+    },
+    /// Execute a batch of commands on the opened probe.
+    Execute(Vec<Command>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum ServerMessage {
+    Opened(Result<ProbeDescription, RemoteError>),
+    Executed(Result<Vec<Reply>, BatchFailure>),
+}
+
+/// What the client needs to know about the probe opened on the server.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ProbeDescription {
+    pub name: String,
+    pub speed_khz: u32,
+    pub protocol: Option<WireProtocol>,
+    pub has_arm_interface: bool,
+    pub has_riscv_interface: bool,
+    pub has_mips_interface: bool,
+    pub has_jtag_access: bool,
+}
+
+/// A single operation on the remote probe.
+///
+/// The variants map one-to-one to the methods of [`DebugProbe`](crate::DebugProbe),
+/// [`RawDapAccess`](crate::architecture::arm::RawDapAccess) and
+/// [`JTAGAccess`](crate::probe::JTAGAccess).
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum Command {
+    SetSpeed(u32),
+    SetScanChain(Vec<ScanChainElement>),
+    Attach,
+    Detach,
+    TargetReset,
+    TargetResetAssert,
+    TargetResetDeassert,
+    SelectProtocol(WireProtocol),
+    GetTargetVoltage,
+
+    SelectDp(DpAddress),
+    RawReadRegister {
+        port: PortType,
+        addr: u8,
+    },
+    RawReadBlock {
+        port: PortType,
+        addr: u8,
+        len: usize,
+    },
+    RawWriteRegister {
+        port: PortType,
+        addr: u8,
+        value: u32,
+    },
+    RawWriteBlock {
+        port: PortType,
+        addr: u8,
+        values: Vec<u32>,
+    },
+    RawFlush,
+    ConfigureJtag,
+    JtagSequence {
+        cycles: u8,
+        tms: bool,
+        tdi: u64,
+    },
+    SwjSequence {
+        bit_len: u8,
+        bits: u64,
+    },
+    SwjPins {
+        pin_out: u32,
+        pin_select: u32,
+        pin_wait: u32,
+    },
+    CoreStatusNotification(CoreStatus),
+
+    JtagReadRegister {
+        address: u32,
+        len: u32,
+    },
+    JtagWriteRegister {
+        address: u32,
+        data: Vec<u8>,
+        len: u32,
+    },
+    JtagWriteRegisterBatch(Vec<JtagWrite>),
+    SetIdleCycles(u8),
+    SetIrLen(u32),
+}
+
+impl Command {
+    /// Commands which don't return anything can be queued on the client
+    /// and sent together with the next command that does.
+    pub(crate) fn is_deferrable(&self) -> bool {
+        matches!(
+            self,
+            Command::RawWriteRegister { .. }
+                | Command::RawWriteBlock { .. }
+                | Command::CoreStatusNotification(_)
+                | Command::SetIdleCycles(_)
+                | Command::SetIrLen(_)
+        )
+    }
+}
+
+/// A JTAG register write, without the client side transform of the result.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct JtagWrite {
+    pub address: u32,
+    pub data: Vec<u8>,
+    pub len: u32,
+}
+
+/// The result of a successfully executed [`Command`].
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum Reply {
+    None,
+    Speed(u32),
+    Protocol(Option<WireProtocol>),
+    Value(u32),
+    Values(Vec<u32>),
+    Bits(Vec<u8>),
+    Voltage(Option<f32>),
+    /// The data shifted out by the writes of a [`Command::JtagWriteRegisterBatch`]
+    /// which succeeded, and the error which stopped the batch, if any.
+    JtagBatch {
+        results: Vec<Vec<u8>>,
+        error: Option<RemoteError>,
+    },
+}
+
+/// The command at `index` in a batch failed, none of the commands after it were executed.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct BatchFailure {
+    pub index: usize,
+    pub error: RemoteError,
+}
+
+/// An error reported by the probe on the server.
+///
+/// Errors can't be sent over the wire as they are, so everything except the
+/// errors callers react to is flattened into its message.
+#[derive(Debug, thiserror::Error, Serialize, Deserialize)]
+pub(crate) enum RemoteError {
+    #[error(transparent)]
+    Dap(DapError),
+    #[error("Timeout occurred during probe operation.")]
+    Timeout,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl RemoteError {
+    fn from_message(error: &dyn std::error::Error) -> Self {
+        let mut message = error.to_string();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+
+        RemoteError::Other(message)
+    }
+}
+
+impl From<DebugProbeError> for RemoteError {
+    fn from(error: DebugProbeError) -> Self {
+        match error {
+            DebugProbeError::Timeout => RemoteError::Timeout,
+            other => RemoteError::from_message(&other),
+        }
+    }
+}
+
+impl From<ArmError> for RemoteError {
+    fn from(error: ArmError) -> Self {
+        match error {
+            ArmError::Dap(error) => RemoteError::Dap(error),
+            ArmError::Timeout => RemoteError::Timeout,
+            ArmError::Probe(error) => error.into(),
+            other => RemoteError::from_message(&other),
+        }
+    }
+}
+
+impl From<crate::Error> for RemoteError {
+    fn from(error: crate::Error) -> Self {
+        match error {
+            crate::Error::Probe(error) => error.into(),
+            crate::Error::Arm(error) => error.into(),
+            crate::Error::Timeout => RemoteError::Timeout,
+            other => RemoteError::from_message(&other),
+        }
+    }
+}
+
+/// Send a single length prefixed message.
+pub(crate) fn send<T: Serialize>(
+    stream: &mut impl Write,
+    message: &T,
+) -> Result<(), RemoteProbeError> {
+    let len = bincode::serialized_size(message)? as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(RemoteProbeError::MessageTooLarge(len));
+    }
+
+    // Send length and payload in one go, so they end up in the same TCP segment.
+    let mut buffer = Vec::with_capacity(4 + len);
+    buffer.extend_from_slice(&(len as u32).to_le_bytes());
+    bincode::serialize_into(&mut buffer, message)?;
+
+    stream.write_all(&buffer)?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+/// Receive a single length prefixed message.
+pub(crate) fn receive<T: DeserializeOwned>(stream: &mut impl Read) -> Result<T, RemoteProbeError> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(RemoteProbeError::MessageTooLarge(len));
+    }
+
+    let mut buffer = vec![0u8; len];
+    stream.read_exact(&mut buffer)?;
+
+    Ok(bincode::deserialize(&buffer)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip_batch() {
+        let batch = ClientMessage::Execute(vec![
+            Command::RawWriteRegister {
+                port: PortType::AccessPort,
+                addr: 0x4,
+                value: 0x2000_0000,
+            },
+            Command::RawReadRegister {
+                port: PortType::AccessPort,
+                addr: 0xC,
+            },
+        ]);
+
+        let mut buffer = Vec::new();
+        send(&mut buffer, &batch).unwrap();
+
+        let ClientMessage::Execute(commands) = receive(&mut buffer.as_slice()).unwrap() else {
+            panic!("Decoded the wrong message");
+        };
+
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0].is_deferrable());
+        assert!(!commands[1].is_deferrable());
+    }
+
+    #[test]
+    fn reject_oversized_messages() {
+        let buffer = (MAX_MESSAGE_SIZE as u32 + 1).to_le_bytes();
+
+        let result: Result<ClientMessage, _> = receive(&mut buffer.as_slice());
+
+        assert!(matches!(result, Err(RemoteProbeError::MessageTooLarge(_))));
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+};
+
+use crate::{
+    architecture::arm::communication_interface::DapProbe,
+    probe::{CommandResult, JTAGAccess, JtagWriteCommand},
+    DebugProbe, DebugProbeError, DebugProbeSelector, Probe, ProbeCreationError,
+};
+
+use super::{
+    protocol::{
+        self, BatchFailure, ClientMessage, Command, ProbeDescription, RemoteError, Reply,
+        ServerMessage, MAX_MESSAGE_SIZE, PROTOCOL_VERSION,
+    },
+    RemoteProbeError,
+};
+
+/// Serves the debug probes attached to this machine over TCP.
+///
+/// Clients open a probe with a [`DebugProbeSelector`] of the form
+/// `tcp://HOST:PORT/SELECTOR`. Every connection gets its own probe,
+/// which stays open until the client disconnects.
+#[derive(Debug)]
+pub struct ProbeServer {
+    listener: TcpListener,
+}
+
+impl ProbeServer {
+    /// Listen for clients on the given address.
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept clients until an error occurs.
+    ///
+    /// Each client is served from a separate thread.
+    pub fn run(&self) -> io::Result<()> {
+        loop {
+            let (stream, client) = self.listener.accept()?;
+
+            thread::spawn(move || {
+                tracing::info!("Client {} connected", client);
+
+                match serve_connection(stream, open_local_probe) {
+                    Ok(()) => tracing::info!("Client {} disconnected", client),
+                    Err(e) => tracing::warn!("Connection to client {} failed: {}", client, e),
+                }
+            });
+        }
+    }
+}
+
+/// Open the probe a client asked for. Clients which only know the serial number
+/// of the probe leave the VID and PID at zero.
+fn open_local_probe(selector: DebugProbeSelector) -> Result<Probe, DebugProbeError> {
+    if selector.vendor_id != 0 || selector.product_id != 0 {
+        return Probe::open(selector);
+    }
+
+    Probe::list_all()
+        .into_iter()
+        .find(|info| {
+            selector.serial_number.is_none() || info.serial_number == selector.serial_number
+        })
+        .ok_or(DebugProbeError::ProbeCouldNotBeCreated(
+            ProbeCreationError::NotFound,
+        ))?
+        .open()
+}
+
+/// Open a probe for the client on the other end of `stream`,
+/// and execute its commands until it disconnects.
+pub(crate) fn serve_connection(
+    mut stream: TcpStream,
+    open: impl FnOnce(DebugProbeSelector) -> Result<Probe, DebugProbeError>,
+) -> Result<(), RemoteProbeError> {
+    stream.set_nodelay(true)?;
+
+    let ClientMessage::Open {
+        version,
+        vendor_id,
+        product_id,
+        serial_number,
+    } = protocol::receive(&mut stream)?
+    else {
+        return Err(RemoteProbeError::UnexpectedMessage);
+    };
+
+    if version != PROTOCOL_VERSION {
+        let error = RemoteError::Other(format!(
+            "The probe server uses protocol version {PROTOCOL_VERSION}, but the client uses version {version}."
+        ));
+        return protocol::send(&mut stream, &ServerMessage::Opened(Err(error)));
+    }
+
+    let selector = DebugProbeSelector {
+        vendor_id,
+        product_id,
+        serial_number,
+        remote_address: None,
+    };
+    tracing::info!("Opening probe {}", selector);
+
+    let mut probe = match open(selector) {
+        Ok(probe) => probe.inner,
+        Err(e) => return protocol::send(&mut stream, &ServerMessage::Opened(Err(e.into()))),
+    };
+
+    let description = describe(probe.as_mut());
+    tracing::info!("Opened probe {}", description.name);
+    protocol::send(&mut stream, &ServerMessage::Opened(Ok(description)))?;
+
+    loop {
+        let commands = match protocol::receive(&mut stream) {
+            Ok(ClientMessage::Execute(commands)) => commands,
+            Ok(ClientMessage::Open { .. }) => return Err(RemoteProbeError::UnexpectedMessage),
+            Err(RemoteProbeError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let result = execute(probe.as_mut(), commands);
+        protocol::send(&mut stream, &ServerMessage::Executed(result))?;
+    }
+}
+
+fn describe(probe: &mut dyn DebugProbe) -> ProbeDescription {
+    // Only the low-level interfaces can be forwarded, the interfaces
+    // built on top of them are created by the client.
+    let has_dap_access = probe.try_as_dap_probe().is_some();
+    let has_jtag_access = probe.try_as_jtag_probe().is_some();
+
+    ProbeDescription {
+        name: probe.get_name().to_string(),
+        speed_khz: probe.speed_khz(),
+        protocol: probe.active_protocol(),
+        has_arm_interface: probe.has_arm_interface() && has_dap_access,
+        has_riscv_interface: probe.has_riscv_interface() && has_jtag_access,
+        has_mips_interface: probe.has_mips_interface() && has_jtag_access,
+        has_jtag_access,
+    }
+}
+
+fn execute(probe: &mut dyn DebugProbe, commands: Vec<Command>) -> Result<Vec<Reply>, BatchFailure> {
+    let mut replies = Vec::with_capacity(commands.len());
+
+    for (index, command) in commands.into_iter().enumerate() {
+        match execute_command(probe, command) {
+            Ok(reply) => replies.push(reply),
+            Err(error) => return Err(BatchFailure { index, error }),
+        }
+    }
+
+    Ok(replies)
+}
+
+fn execute_command(probe: &mut dyn DebugProbe, command: Command) -> Result<Reply, RemoteError> {
+    match command {
+        Command::SetSpeed(speed_khz) => return Ok(Reply::Speed(probe.set_speed(speed_khz)?)),
+        Command::SetScanChain(scan_chain) => probe.set_scan_chain(scan_chain)?,
+        Command::Attach => {
+            probe.attach()?;
+            return Ok(Reply::Protocol(probe.active_protocol()));
+        }
+        Command::Detach => probe.detach()?,
+        Command::TargetReset => probe.target_reset()?,
+        Command::TargetResetAssert => probe.target_reset_assert()?,
+        Command::TargetResetDeassert => probe.target_reset_deassert()?,
+        Command::SelectProtocol(protocol) => {
+            probe.select_protocol(protocol)?;
+            return Ok(Reply::Protocol(probe.active_protocol()));
+        }
+        Command::GetTargetVoltage => return Ok(Reply::Voltage(probe.get_target_voltage()?)),
+
+        Command::SelectDp(dp) => dap(probe)?.select_dp(dp)?,
+        Command::RawReadRegister { port, addr } => {
+            return Ok(Reply::Value(dap(probe)?.raw_read_register(port, addr)?))
+        }
+        Command::RawReadBlock { port, addr, len } => {
+            if len > MAX_MESSAGE_SIZE / 4 {
+                return Err(RemoteError::Other(format!(
+                    "A block read of {len} words does not fit into a single message."
+                )));
+            }
+
+            let mut values = vec![0; len];
+            dap(probe)?.raw_read_block(port, addr, &mut values)?;
+            return Ok(Reply::Values(values));
+        }
+        Command::RawWriteRegister { port, addr, value } => {
+            dap(probe)?.raw_write_register(port, addr, value)?
+        }
+        Command::RawWriteBlock { port, addr, values } => {
+            dap(probe)?.raw_write_block(port, addr, &values)?
+        }
+        Command::RawFlush => dap(probe)?.raw_flush()?,
+        Command::ConfigureJtag => dap(probe)?.configure_jtag()?,
+        Command::JtagSequence { cycles, tms, tdi } => {
+            dap(probe)?.jtag_sequence(cycles, tms, tdi)?
+        }
+        Command::SwjSequence { bit_len, bits } => dap(probe)?.swj_sequence(bit_len, bits)?,
+        Command::SwjPins {
+            pin_out,
+            pin_select,
+            pin_wait,
+        } => {
+            return Ok(Reply::Value(
+                dap(probe)?.swj_pins(pin_out, pin_select, pin_wait)?,
+            ))
+        }
+        Command::CoreStatusNotification(status) => dap(probe)?.core_status_notification(status)?,
+
+        Command::JtagReadRegister { address, len } => {
+            return Ok(Reply::Bits(jtag(probe)?.read_register(address, len)?))
+        }
+        Command::JtagWriteRegister { address, data, len } => {
+            return Ok(Reply::Bits(
+                jtag(probe)?.write_register(address, &data, len)?,
+            ))
+        }
+        Command::JtagWriteRegisterBatch(writes) => {
+            // The transforms are applied by the client, so just pass on the shifted out data.
+            // Unlike with a local probe, writes after one whose transform fails are still
+            // executed, which is harmless as long as the target ignores them after an error,
+            // like RISC-V DTMs do.
+            let writes = writes
+                .into_iter()
+                .map(|write| JtagWriteCommand {
+                    address: write.address,
+                    data: write.data,
+                    len: write.len,
+                    transform: |data| Ok(CommandResult::VecU8(data)),
+                })
+                .collect::<Vec<_>>();
+
+            let (results, error) = match jtag(probe)?.write_register_batch(&writes) {
+                Ok(results) => (results, None),
+                Err(e) => (e.results, Some(e.error.into())),
+            };
+
+            let results = results
+                .into_iter()
+                .map(|result| match result {
+                    CommandResult::VecU8(data) => data,
+                    _ => unreachable!("The transform above always returns the shifted out data"),
+                })
+                .collect();
+
+            return Ok(Reply::JtagBatch { results, error });
+        }
+        Command::SetIdleCycles(idle_cycles) => jtag(probe)?.set_idle_cycles(idle_cycles),
+        Command::SetIrLen(len) => jtag(probe)?.set_ir_len(len),
+    }
+
+    Ok(Reply::None)
+}
+
+fn dap(probe: &mut dyn DebugProbe) -> Result<&mut dyn DapProbe, RemoteError> {
+    probe
+        .try_as_dap_probe()
+        .ok_or_else(|| DebugProbeError::InterfaceNotAvailable("DAP").into())
+}
+
+fn jtag(probe: &mut dyn DebugProbe) -> Result<&mut dyn JTAGAccess, RemoteError> {
+    probe
+        .try_as_jtag_probe()
+        .ok_or_else(|| DebugProbeError::InterfaceNotAvailable("JTAG").into())
+}
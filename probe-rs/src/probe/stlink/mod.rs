@@ -908,6 +908,60 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self, data, apsel), fields(ap=apsel, length= data.len()))]
+    fn read_mem_16bit(
+        &mut self,
+        address: u32,
+        data: &mut [u8],
+        apsel: u8,
+    ) -> Result<(), DebugProbeError> {
+        self.select_ap(apsel)?;
+
+        // Ensure maximum read length is not exceeded.
+        assert!(
+            data.len() <= STLINK_MAX_READ_LEN,
+            "Maximum read length for STLink is {STLINK_MAX_READ_LEN} bytes"
+        );
+
+        assert!(
+            data.len() % 2 == 0,
+            "Data length has to be a multiple of 2 for 16 bit reads"
+        );
+
+        if address % 2 != 0 {
+            return Err(StlinkError::UnalignedAddress.into());
+        }
+
+        let data_length = data.len().to_le_bytes();
+
+        let addbytes = address.to_le_bytes();
+
+        retry_on_wait(|| {
+            self.device.write(
+                &[
+                    commands::JTAG_COMMAND,
+                    commands::JTAG_READMEM_16BIT,
+                    addbytes[0],
+                    addbytes[1],
+                    addbytes[2],
+                    addbytes[3],
+                    data_length[0],
+                    data_length[1],
+                    apsel,
+                ],
+                &[],
+                data,
+                TIMEOUT,
+            )?;
+
+            self.get_last_rw_status()
+        })?;
+
+        tracing::debug!("Read ok");
+
+        Ok(())
+    }
+
     fn read_mem_8bit(
         &mut self,
         address: u32,
@@ -1025,6 +1079,57 @@ impl<D: StLinkUsb> StLink<D> {
         Ok(())
     }
 
+    fn write_mem_16bit(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        apsel: u8,
+    ) -> Result<(), DebugProbeError> {
+        self.select_ap(apsel)?;
+
+        tracing::trace!("write_mem_16bit");
+        let length = data.len();
+
+        assert!(
+            length <= STLINK_MAX_WRITE_LEN,
+            "Maximum write length for STLink is {STLINK_MAX_WRITE_LEN} bytes"
+        );
+
+        assert!(
+            data.len() % 2 == 0,
+            "Data length has to be a multiple of 2 for 16 bit writes"
+        );
+
+        if address % 2 != 0 {
+            return Err(StlinkError::UnalignedAddress.into());
+        }
+
+        let addbytes = address.to_le_bytes();
+        let lenbytes = length.to_le_bytes();
+        retry_on_wait(|| {
+            self.device.write(
+                &[
+                    commands::JTAG_COMMAND,
+                    commands::JTAG_WRITEMEM_16BIT,
+                    addbytes[0],
+                    addbytes[1],
+                    addbytes[2],
+                    addbytes[3],
+                    lenbytes[0],
+                    lenbytes[1],
+                    apsel,
+                ],
+                data,
+                &mut [],
+                TIMEOUT,
+            )?;
+
+            self.get_last_rw_status()
+        })?;
+
+        Ok(())
+    }
+
     fn write_mem_8bit(
         &mut self,
         address: u32,
@@ -1487,6 +1592,27 @@ impl ArmProbe for StLinkMemoryInterface<'_> {
         Ok(())
     }
 
+    fn read_16(&mut self, address: u64, data: &mut [u16]) -> Result<(), ArmError> {
+        let address = valid_32bit_arm_address(address)?;
+
+        // Read needs to be chunked into chunks with appropiate max length (see STLINK_MAX_READ_LEN).
+        for (index, chunk) in data.chunks_mut(STLINK_MAX_READ_LEN / 2).enumerate() {
+            let mut buff = vec![0u8; 2 * chunk.len()];
+
+            self.probe.probe.read_mem_16bit(
+                address + (index * STLINK_MAX_READ_LEN) as u32,
+                &mut buff,
+                self.current_ap.ap_address().ap,
+            )?;
+
+            for (index, word) in buff.chunks_exact(2).enumerate() {
+                chunk[index] = u16::from_le_bytes(word.try_into().unwrap());
+            }
+        }
+
+        Ok(())
+    }
+
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), ArmError> {
         let address = valid_32bit_arm_address(address)?;
 
@@ -1560,6 +1686,23 @@ impl ArmProbe for StLinkMemoryInterface<'_> {
         Ok(())
     }
 
+    fn write_16(&mut self, address: u64, data: &[u16]) -> Result<(), ArmError> {
+        let address = valid_32bit_arm_address(address)?;
+
+        let tx_buffer: Vec<u8> = data.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        // STLINK_MAX_WRITE_LEN is a multiple of 2, so every chunk stays halfword aligned.
+        for (index, chunk) in tx_buffer.chunks(STLINK_MAX_WRITE_LEN).enumerate() {
+            self.probe.probe.write_mem_16bit(
+                address + (index * STLINK_MAX_WRITE_LEN) as u32,
+                chunk,
+                self.current_ap.ap_address().ap,
+            )?;
+        }
+
+        Ok(())
+    }
+
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), ArmError> {
         let address = valid_32bit_arm_address(address)?;
 
@@ -0,0 +1,636 @@
+//! Host side implementation of the semihosting interface.
+//!
+//! With semihosting, a program running on the target requests the debugger to perform an
+//! operation on its behalf, such as printing to the console or reading a file. The target
+//! places the operation number and a parameter in registers, and halts on a special
+//! breakpoint instruction. The core then reports a
+//! [`SemihostingCommand::Request`](crate::SemihostingCommand::Request), which is serviced by a
+//! [`SemihostingHost`] before the core is resumed.
+//!
+//! The operations are defined by the [ARM semihosting specification](https://github.com/ARM-software/abi-aa/blob/main/semihosting/semihosting.rst).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use probe_rs::{BreakpointCause, CoreStatus, HaltReason, Permissions, Probe, SemihostingCommand};
+//! use probe_rs::semihosting::SemihostingHost;
+//!
+//! let probe = Probe::list_all()[0].open()?;
+//! let mut session = probe.attach("somechip", Permissions::default())?;
+//! let mut core = session.core(0)?;
+//!
+//! // Allow the target to access the files in `./data`.
+//! let mut host = SemihostingHost::new().with_root("data");
+//!
+//! loop {
+//!     match core.status()? {
+//!         CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+//!             SemihostingCommand::Request { operation, parameter },
+//!         ))) => host.service(&mut core, operation, parameter)?,
+//!         CoreStatus::Halted(_) => break,
+//!         _ => std::thread::sleep(std::time::Duration::from_millis(10)),
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{Core, Error, MemoryInterface, SemihostingCommand};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const SYS_OPEN: u32 = 0x01;
+const SYS_CLOSE: u32 = 0x02;
+const SYS_WRITEC: u32 = 0x03;
+const SYS_WRITE0: u32 = 0x04;
+const SYS_WRITE: u32 = 0x05;
+const SYS_READ: u32 = 0x06;
+const SYS_READC: u32 = 0x07;
+const SYS_ISTTY: u32 = 0x09;
+const SYS_SEEK: u32 = 0x0A;
+const SYS_FLEN: u32 = 0x0C;
+const SYS_CLOCK: u32 = 0x10;
+const SYS_TIME: u32 = 0x11;
+const SYS_ERRNO: u32 = 0x13;
+const SYS_GET_CMDLINE: u32 = 0x15;
+const SYS_HEAPINFO: u32 = 0x16;
+pub(crate) const SYS_EXIT: u32 = 0x18;
+pub(crate) const SYS_EXIT_EXTENDED: u32 = 0x20;
+const SYS_ELAPSED: u32 = 0x30;
+const SYS_TICKFREQ: u32 = 0x31;
+
+/// The reason code of `SYS_EXIT` for a normal exit of the application.
+const ADP_STOPPED_APPLICATION_EXIT: u64 = 0x20026;
+
+/// Special file name used to open the console.
+const CONSOLE_FILE_NAME: &str = ":tt";
+/// Special file name used to query the supported semihosting extensions.
+const FEATURES_FILE_NAME: &str = ":semihosting-features";
+/// The content of the features file: the magic number, followed by a byte with the supported
+/// extensions. Only `SYS_EXIT_EXTENDED` is supported.
+const FEATURES: &[u8] = b"SHFB\x01";
+
+/// Resolution of the tick counter returned by `SYS_ELAPSED`, in ticks per second.
+const TICK_FREQUENCY: u64 = 1_000_000;
+
+/// The longest file name accepted by `SYS_OPEN`, and the longest string written by
+/// `SYS_WRITE0`, including the terminating null.
+const PATH_MAX: u64 = 4096;
+/// `SYS_READ` and `SYS_WRITE` transfer the data between the target and the file in chunks
+/// of this size, so the length requested by the target is never allocated at once.
+const TRANSFER_CHUNK_SIZE: u64 = 4096;
+
+// Error numbers reported by `SYS_ERRNO`, as used by newlib and picolibc.
+const ENOENT: u32 = 2;
+const EIO: u32 = 5;
+const EBADF: u32 = 9;
+const EACCES: u32 = 13;
+const EINVAL: u32 = 22;
+const ENAMETOOLONG: u32 = 91;
+
+/// Decode the exit operations, which end the program on the target.
+///
+/// Returns `SemihostingCommand::Request` for all other operations, which are serviced by a
+/// [`SemihostingHost`].
+pub(crate) fn decode_command(
+    core: &mut (impl MemoryInterface + ?Sized),
+    operation: u32,
+    parameter: u64,
+) -> Result<SemihostingCommand, Error> {
+    let command = match operation {
+        SYS_EXIT if parameter == ADP_STOPPED_APPLICATION_EXIT => SemihostingCommand::ExitSuccess,
+        SYS_EXIT => SemihostingCommand::ExitError { code: parameter },
+        SYS_EXIT_EXTENDED => {
+            let reason = core.read_word_32(parameter)? as u64;
+            let subcode = core.read_word_32(parameter + 4)? as u64;
+
+            match (reason, subcode) {
+                (ADP_STOPPED_APPLICATION_EXIT, 0) => SemihostingCommand::ExitSuccess,
+                (ADP_STOPPED_APPLICATION_EXIT, code) => SemihostingCommand::ExitError { code },
+                (code, _) => SemihostingCommand::ExitError { code },
+            }
+        }
+        _ => SemihostingCommand::Request {
+            operation,
+            parameter,
+        },
+    };
+
+    Ok(command)
+}
+
+/// Information about the heap and stack of the program, returned by `SYS_HEAPINFO`.
+///
+/// A value of zero tells the C library on the target to use its default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapInfo {
+    /// The lowest address of the heap.
+    pub heap_base: u64,
+    /// The highest address of the heap.
+    pub heap_limit: u64,
+    /// The initial stack pointer.
+    pub stack_base: u64,
+    /// The lowest address of the stack.
+    pub stack_limit: u64,
+}
+
+/// A file opened by the target.
+enum HostFile {
+    Stdin,
+    Stdout,
+    Stderr,
+    Features(Cursor<&'static [u8]>),
+    File(File),
+}
+
+/// Performs the semihosting operations requested by a target.
+///
+/// Console output is written to the standard output of the host, and console input is read
+/// from the standard input, unless configured otherwise with
+/// [`with_stdout`](SemihostingHost::with_stdout) and [`with_stdin`](SemihostingHost::with_stdin).
+///
+/// The target can only open files inside the directory configured with
+/// [`with_root`](SemihostingHost::with_root). File names are resolved relative to this
+/// directory, and names which are absolute or contain `..` are rejected. Without a root
+/// directory, only the console can be opened.
+pub struct SemihostingHost {
+    root: Option<PathBuf>,
+    command_line: String,
+    heap_info: HeapInfo,
+    stdout: Box<dyn Write + Send>,
+    stdin: Box<dyn Read + Send>,
+    files: HashMap<u32, HostFile>,
+    next_handle: u32,
+    errno: u32,
+    start: Instant,
+}
+
+impl std::fmt::Debug for SemihostingHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemihostingHost")
+            .field("root", &self.root)
+            .field("command_line", &self.command_line)
+            .field("heap_info", &self.heap_info)
+            .field("open_files", &self.files.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SemihostingHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemihostingHost {
+    /// Create a host which uses the console of the host process, and doesn't give access to
+    /// any files.
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            command_line: String::new(),
+            heap_info: HeapInfo::default(),
+            stdout: Box::new(std::io::stdout()),
+            stdin: Box::new(std::io::stdin()),
+            files: HashMap::new(),
+            next_handle: 1,
+            errno: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Allow the target to access the files in `root`.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Set the command line returned by `SYS_GET_CMDLINE`, including the name of the program.
+    pub fn with_command_line(mut self, command_line: impl Into<String>) -> Self {
+        self.command_line = command_line.into();
+        self
+    }
+
+    /// Set the heap and stack information returned by `SYS_HEAPINFO`.
+    pub fn with_heap_info(mut self, heap_info: HeapInfo) -> Self {
+        self.heap_info = heap_info;
+        self
+    }
+
+    /// Write the console output of the target to `stdout`.
+    pub fn with_stdout(mut self, stdout: impl Write + Send + 'static) -> Self {
+        self.stdout = Box::new(stdout);
+        self
+    }
+
+    /// Read the console input of the target from `stdin`.
+    pub fn with_stdin(mut self, stdin: impl Read + Send + 'static) -> Self {
+        self.stdin = Box::new(stdin);
+        self
+    }
+
+    /// Perform the semihosting `operation` the core is halted on, and resume the core.
+    ///
+    /// The result of the operation is written to the return register of the core. Operations
+    /// which are not supported, or whose parameters can't be accessed, return `-1` to the target. When resumed, the core steps over the
+    /// instruction which triggered the request, like over any other breakpoint instruction.
+    pub fn service(
+        &mut self,
+        core: &mut Core<'_>,
+        operation: u32,
+        parameter: u64,
+    ) -> Result<(), Error> {
+        let mut call = Call {
+            word_size: core.program_counter().size_in_bytes() as u64,
+            core,
+            parameter,
+        };
+
+        tracing::debug!("Semihosting operation {operation:#x}, parameter {parameter:#x}");
+        let result = match self.perform(&mut call, operation) {
+            Ok(result) => result,
+            Err(error) => {
+                // The parameters passed by the target are invalid, e.g. point to unmapped memory.
+                tracing::warn!("Semihosting operation {operation:#x} failed: {error}");
+                self.fail(EIO)
+            }
+        };
+
+        let return_register = call.core.registers().result_register(0);
+        if call.word_size == 8 {
+            call.core.write_core_reg(return_register, result as u64)?;
+        } else {
+            call.core.write_core_reg(return_register, result as u32)?;
+        }
+
+        call.core.run()
+    }
+
+    /// Perform an operation, and return the value for the return register.
+    fn perform(&mut self, call: &mut Call<'_, '_>, operation: u32) -> Result<i64, Error> {
+        let result = match operation {
+            SYS_OPEN => {
+                let name = call.argument(0)?;
+                let mode = call.argument(1)?;
+                let length = call.argument(2)?;
+
+                if length >= PATH_MAX {
+                    return Ok(self.fail(ENAMETOOLONG));
+                }
+
+                let mut name_bytes = vec![0; length as usize];
+                call.core.read(name, &mut name_bytes)?;
+                let name = String::from_utf8_lossy(&name_bytes);
+
+                self.open(&name, mode)
+            }
+            SYS_CLOSE => {
+                let handle = call.argument(0)? as u32;
+                match self.files.remove(&handle) {
+                    Some(_) => 0,
+                    None => self.fail(EBADF),
+                }
+            }
+            SYS_WRITEC => {
+                let character = call.core.read_word_8(call.parameter)?;
+                self.write_console(&[character]);
+                0
+            }
+            SYS_WRITE0 => match call.read_string(call.parameter, PATH_MAX)? {
+                Some(string) => {
+                    self.write_console(&string);
+                    0
+                }
+                None => self.fail(EINVAL),
+            },
+            SYS_WRITE => {
+                let handle = call.argument(0)? as u32;
+                let buffer = call.argument(1)?;
+                let length = call.argument(2)?;
+
+                let mut data = vec![0; length.min(TRANSFER_CHUNK_SIZE) as usize];
+                let mut written = 0;
+                while written < length {
+                    let chunk = &mut data[..(length - written).min(TRANSFER_CHUNK_SIZE) as usize];
+                    call.core.read(buffer + written, chunk)?;
+
+                    if let Err(errno) = self.write(handle, chunk) {
+                        self.fail(errno);
+                        break;
+                    }
+
+                    written += chunk.len() as u64;
+                }
+
+                // The number of bytes which were not written.
+                (length - written) as i64
+            }
+            SYS_READ => {
+                let handle = call.argument(0)? as u32;
+                let buffer = call.argument(1)?;
+                let length = call.argument(2)?;
+
+                let mut data = vec![0; length.min(TRANSFER_CHUNK_SIZE) as usize];
+                let mut read = 0;
+                while read < length {
+                    let chunk = &mut data[..(length - read).min(TRANSFER_CHUNK_SIZE) as usize];
+
+                    let count = match self.read(handle, chunk) {
+                        Ok(count) => count,
+                        Err(errno) if read == 0 => return Ok(self.fail(errno)),
+                        Err(errno) => {
+                            self.fail(errno);
+                            break;
+                        }
+                    };
+
+                    call.core.write_8(buffer + read, &chunk[..count])?;
+                    read += count as u64;
+
+                    // The end of the file, or all the console input which is available.
+                    if count < chunk.len() {
+                        break;
+                    }
+                }
+
+                // The number of bytes which were not read.
+                (length - read) as i64
+            }
+            SYS_READC => {
+                let mut character = [0];
+                match self.stdin.read(&mut character) {
+                    Ok(1) => character[0] as i64,
+                    Ok(_) => -1,
+                    Err(error) => self.fail(errno_of(&error)),
+                }
+            }
+            SYS_ISTTY => {
+                let handle = call.argument(0)? as u32;
+                match self.files.get(&handle) {
+                    Some(HostFile::Stdin | HostFile::Stdout | HostFile::Stderr) => 1,
+                    Some(_) => 0,
+                    None => self.fail(EBADF),
+                }
+            }
+            SYS_SEEK => {
+                let handle = call.argument(0)? as u32;
+                let position = call.argument(1)?;
+
+                let result = match self.files.get_mut(&handle) {
+                    Some(HostFile::File(file)) => file.seek(SeekFrom::Start(position)),
+                    Some(HostFile::Features(features)) => features.seek(SeekFrom::Start(position)),
+                    Some(_) => Err(std::io::ErrorKind::Unsupported.into()),
+                    None => return Ok(self.fail(EBADF)),
+                };
+
+                match result {
+                    Ok(_) => 0,
+                    Err(error) => self.fail(errno_of(&error)),
+                }
+            }
+            SYS_FLEN => {
+                let handle = call.argument(0)? as u32;
+                match self.files.get(&handle) {
+                    Some(HostFile::File(file)) => match file.metadata() {
+                        Ok(metadata) => metadata.len() as i64,
+                        Err(error) => self.fail(errno_of(&error)),
+                    },
+                    Some(HostFile::Features(_)) => FEATURES.len() as i64,
+                    Some(_) => self.fail(EINVAL),
+                    None => self.fail(EBADF),
+                }
+            }
+            SYS_CLOCK => (self.start.elapsed().as_millis() / 10) as i64,
+            SYS_TIME => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs() as i64),
+            SYS_ERRNO => self.errno as i64,
+            SYS_GET_CMDLINE => {
+                let buffer = call.argument(0)?;
+                let length = call.argument(1)?;
+
+                let mut command_line = self.command_line.clone().into_bytes();
+                if command_line.len() as u64 >= length {
+                    return Ok(self.fail(EINVAL));
+                }
+
+                call.write_argument(1, command_line.len() as u64)?;
+                command_line.push(0);
+                call.core.write_8(buffer, &command_line)?;
+                0
+            }
+            SYS_HEAPINFO => {
+                let block = call.read_word(call.parameter)?;
+
+                let info = self.heap_info;
+                for (index, value) in [
+                    info.heap_base,
+                    info.heap_limit,
+                    info.stack_base,
+                    info.stack_limit,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    call.write_word(block + index as u64 * call.word_size, value)?;
+                }
+                0
+            }
+            SYS_ELAPSED => {
+                let ticks = self.start.elapsed().as_micros() as u64;
+                call.core.write_word_32(call.parameter, ticks as u32)?;
+                call.core
+                    .write_word_32(call.parameter + 4, (ticks >> 32) as u32)?;
+                0
+            }
+            SYS_TICKFREQ => TICK_FREQUENCY as i64,
+            _ => {
+                tracing::warn!("Unsupported semihosting operation {operation:#x}");
+                -1
+            }
+        };
+
+        Ok(result)
+    }
+
+    /// Open a file, and return its handle.
+    fn open(&mut self, name: &str, mode: u64) -> i64 {
+        let file = if name == CONSOLE_FILE_NAME {
+            match mode {
+                0..=3 => HostFile::Stdin,
+                4..=7 => HostFile::Stdout,
+                _ => HostFile::Stderr,
+            }
+        } else if name == FEATURES_FILE_NAME {
+            HostFile::Features(Cursor::new(FEATURES))
+        } else {
+            let Some(path) = self.resolve(name) else {
+                tracing::warn!("The target tried to open '{name}' outside the semihosting root");
+                return self.fail(EACCES);
+            };
+
+            // The modes correspond to the `fopen` modes "r", "r+", "w", "w+", "a" and "a+",
+            // each followed by the binary variant.
+            let mut options = OpenOptions::new();
+            match mode / 2 {
+                0 => options.read(true),
+                1 => options.read(true).write(true),
+                2 => options.write(true).create(true).truncate(true),
+                3 => options.read(true).write(true).create(true).truncate(true),
+                4 => options.append(true).create(true),
+                5 => options.read(true).append(true).create(true),
+                _ => return self.fail(EINVAL),
+            };
+
+            match options.open(&path) {
+                Ok(file) => HostFile::File(file),
+                Err(error) => return self.fail(errno_of(&error)),
+            }
+        };
+
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.files.insert(handle, file);
+
+        handle as i64
+    }
+
+    /// Resolve a file name inside the root directory.
+    fn resolve(&self, name: &str) -> Option<PathBuf> {
+        let root = self.root.as_ref()?;
+        let path = Path::new(name);
+
+        let inside_root = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+        inside_root.then(|| root.join(path))
+    }
+
+    fn write_console(&mut self, data: &[u8]) {
+        if let Err(error) = self
+            .stdout
+            .write_all(data)
+            .and_then(|_| self.stdout.flush())
+        {
+            tracing::warn!("Failed to write semihosting output: {error}");
+        }
+    }
+
+    fn write(&mut self, handle: u32, data: &[u8]) -> Result<(), u32> {
+        let result = match self.files.get_mut(&handle) {
+            Some(HostFile::Stdout) => self
+                .stdout
+                .write_all(data)
+                .and_then(|_| self.stdout.flush()),
+            Some(HostFile::Stderr) => std::io::stderr().write_all(data),
+            Some(HostFile::File(file)) => file.write_all(data),
+            Some(HostFile::Stdin | HostFile::Features(_)) => return Err(EBADF),
+            None => return Err(EBADF),
+        };
+
+        result.map_err(|error| errno_of(&error))
+    }
+
+    fn read(&mut self, handle: u32, data: &mut [u8]) -> Result<usize, u32> {
+        let result = match self.files.get_mut(&handle) {
+            Some(HostFile::Stdin) => self.stdin.read(data),
+            Some(HostFile::Features(features)) => features.read(data),
+            Some(HostFile::File(file)) => read_full(file, data),
+            Some(HostFile::Stdout | HostFile::Stderr) => return Err(EBADF),
+            None => return Err(EBADF),
+        };
+
+        result.map_err(|error| errno_of(&error))
+    }
+
+    /// Record the error number for `SYS_ERRNO`, and return `-1`.
+    fn fail(&mut self, errno: u32) -> i64 {
+        self.errno = errno;
+        -1
+    }
+}
+
+/// The core and the parameter of a semihosting call.
+struct Call<'a, 'probe> {
+    core: &'a mut Core<'probe>,
+    /// The size of a field in the parameter block.
+    word_size: u64,
+    parameter: u64,
+}
+
+impl Call<'_, '_> {
+    /// Read the field at `index` of the parameter block.
+    fn argument(&mut self, index: u64) -> Result<u64, Error> {
+        self.read_word(self.parameter + index * self.word_size)
+    }
+
+    /// Write the field at `index` of the parameter block.
+    fn write_argument(&mut self, index: u64, value: u64) -> Result<(), Error> {
+        self.write_word(self.parameter + index * self.word_size, value)
+    }
+
+    fn read_word(&mut self, address: u64) -> Result<u64, Error> {
+        if self.word_size == 8 {
+            self.core.read_word_64(address)
+        } else {
+            self.core.read_word_32(address).map(u64::from)
+        }
+    }
+
+    fn write_word(&mut self, address: u64, value: u64) -> Result<(), Error> {
+        if self.word_size == 8 {
+            self.core.write_word_64(address, value)
+        } else {
+            self.core.write_word_32(address, value as u32)
+        }
+    }
+
+    /// Read a null-terminated string of at most `max_length` bytes, including the null.
+    ///
+    /// Returns `None` if the string is longer.
+    fn read_string(&mut self, mut address: u64, max_length: u64) -> Result<Option<Vec<u8>>, Error> {
+        // Read up to the next 64 byte boundary at once, so the end of a memory region is
+        // never crossed.
+        const CHUNK_SIZE: u64 = 64;
+
+        let mut string = Vec::new();
+        while (string.len() as u64) < max_length {
+            let size = (CHUNK_SIZE - address % CHUNK_SIZE).min(max_length - string.len() as u64);
+            let mut chunk = vec![0; size as usize];
+            self.core.read(address, &mut chunk)?;
+
+            if let Some(end) = chunk.iter().position(|byte| *byte == 0) {
+                string.extend_from_slice(&chunk[..end]);
+                return Ok(Some(string));
+            }
+
+            string.extend_from_slice(&chunk);
+            address += chunk.len() as u64;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Read from a file until `data` is full or the end of the file is reached.
+fn read_full(file: &mut File, data: &mut [u8]) -> std::io::Result<usize> {
+    let mut count = 0;
+    while count < data.len() {
+        match file.read(&mut data[count..])? {
+            0 => break,
+            read => count += read,
+        }
+    }
+    Ok(count)
+}
+
+fn errno_of(error: &std::io::Error) -> u32 {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => ENOENT,
+        std::io::ErrorKind::PermissionDenied => EACCES,
+        _ => error.raw_os_error().map_or(EIO, |errno| errno as u32),
+    }
+}
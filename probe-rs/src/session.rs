@@ -1,20 +1,27 @@
 use crate::architecture::arm::component::get_arm_components;
 use crate::architecture::arm::sequences::{ArmDebugSequence, DefaultArmSequence};
-use crate::architecture::arm::{ArmError, DpAddress};
+use crate::architecture::arm::{
+    ap::{AccessPort, MemoryAp},
+    ArmError, DpAddress,
+};
+use crate::architecture::mips::communication_interface::MipsError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::config::{ChipInfo, CoreExt, RegistryError, Target, TargetSelector};
 use crate::core::{Architecture, CombinedCoreState};
 use crate::{
     architecture::{
         arm::{
-            communication_interface::ArmProbeInterface, component::TraceSink,
-            memory::CoresightComponent, SwoReader,
+            communication_interface::ArmProbeInterface,
+            component::{Deformatter, EtmArchitecture, TraceSink, TraceSource},
+            memory::CoresightComponent,
+            SwoReader,
         },
+        mips::communication_interface::MipsCommunicationInterface,
         riscv::communication_interface::RiscvCommunicationInterface,
     },
     config::DebugSequence,
 };
-use crate::{AttachMethod, Core, CoreType, Error, FakeProbe, Probe};
+use crate::{AttachMethod, Core, CoreDump, CoreType, Error, FakeProbe, Probe};
 use std::ops::DerefMut;
 use std::{fmt, sync::Arc, time::Duration};
 
@@ -42,11 +49,17 @@ pub struct Session {
     interface: ArchitectureInterface,
     cores: Vec<CombinedCoreState>,
     configured_trace_sink: Option<TraceSink>,
+    configured_trace_source: TraceSource,
+    /// Demultiplexes the formatted frames received over SWO, when tracing the ETM.
+    trace_deformatter: Deformatter,
+    permissions: Permissions,
 }
 
 pub(crate) enum ArchitectureInterface {
     Arm(Box<dyn ArmProbeInterface + 'static>),
     Riscv(Box<RiscvCommunicationInterface>),
+    Mips(Box<MipsCommunicationInterface>),
+    CoreDump(Box<CoreDump>),
 }
 
 impl fmt::Debug for ArchitectureInterface {
@@ -57,6 +70,13 @@ impl fmt::Debug for ArchitectureInterface {
                 .debug_tuple("ArchitectureInterface::Riscv")
                 .field(iface)
                 .finish(),
+            ArchitectureInterface::Mips(iface) => f
+                .debug_tuple("ArchitectureInterface::Mips")
+                .field(iface)
+                .finish(),
+            ArchitectureInterface::CoreDump(..) => {
+                f.write_str("ArchitectureInterface::CoreDump(..)")
+            }
         }
     }
 }
@@ -66,6 +86,8 @@ impl From<ArchitectureInterface> for Architecture {
         match value {
             ArchitectureInterface::Arm(_) => Architecture::Arm,
             ArchitectureInterface::Riscv(_) => Architecture::Riscv,
+            ArchitectureInterface::Mips(_) => Architecture::Mips,
+            ArchitectureInterface::CoreDump(core_dump) => core_dump.architecture(),
         }
     }
 }
@@ -80,6 +102,10 @@ impl ArchitectureInterface {
             ArchitectureInterface::Riscv(riscv_interface) => {
                 combined_state.attach_riscv(riscv_interface)
             }
+            ArchitectureInterface::Mips(mips_interface) => {
+                combined_state.attach_mips(mips_interface)
+            }
+            ArchitectureInterface::CoreDump(core_dump) => core_dump.core(combined_state.id()),
         }
     }
 }
@@ -115,6 +141,9 @@ impl Session {
             Architecture::Riscv => {
                 Self::attach_riscv(probe, target, attach_method, permissions, cores)?
             }
+            Architecture::Mips => {
+                Self::attach_mips(probe, target, attach_method, permissions, cores)?
+            }
         };
 
         session.clear_all_hw_breakpoints()?;
@@ -139,7 +168,7 @@ impl Session {
 
         let sequence_handle = match &target.debug_sequence {
             DebugSequence::Arm(sequence) => sequence.clone(),
-            DebugSequence::Riscv(_) => {
+            DebugSequence::Riscv(_) | DebugSequence::Mips(_) => {
                 panic!("Mismatch between architecture and sequence type!")
             }
         };
@@ -219,6 +248,9 @@ impl Session {
                 interface: ArchitectureInterface::Arm(interface),
                 cores,
                 configured_trace_sink: None,
+                configured_trace_source: TraceSource::Itm,
+                trace_deformatter: Deformatter::new(),
+                permissions,
             };
 
             {
@@ -242,6 +274,9 @@ impl Session {
                 interface: ArchitectureInterface::Arm(interface),
                 cores,
                 configured_trace_sink: None,
+                configured_trace_source: TraceSource::Itm,
+                trace_deformatter: Deformatter::new(),
+                permissions,
             })
         }
     }
@@ -250,14 +285,14 @@ impl Session {
         mut probe: Probe,
         target: Target,
         _attach_method: AttachMethod,
-        _permissions: Permissions,
+        permissions: Permissions,
         cores: Vec<CombinedCoreState>,
     ) -> Result<Self, Error> {
         // TODO: Handle attach under reset
 
         let sequence_handle = match &target.debug_sequence {
             DebugSequence::Riscv(sequence) => sequence.clone(),
-            DebugSequence::Arm(_) => {
+            DebugSequence::Arm(_) | DebugSequence::Mips(_) => {
                 panic!("Mismatch between architecture and sequence type!")
             }
         };
@@ -275,11 +310,14 @@ impl Session {
             interface: ArchitectureInterface::Riscv(Box::new(interface)),
             cores,
             configured_trace_sink: None,
+            configured_trace_source: TraceSource::Itm,
+            trace_deformatter: Deformatter::new(),
+            permissions,
         };
 
-        {
-            // Todo: Add multicore support. How to deal with any cores that are not active and won't respond?
-            let mut core = session.core(0)?;
+        // Halt all harts described by the target
+        for core_index in 0..session.cores.len() {
+            let mut core = session.core(core_index)?;
 
             core.halt(Duration::from_millis(100))?;
         }
@@ -289,6 +327,85 @@ impl Session {
         Ok(session)
     }
 
+    fn attach_mips(
+        mut probe: Probe,
+        target: Target,
+        _attach_method: AttachMethod,
+        permissions: Permissions,
+        cores: Vec<CombinedCoreState>,
+    ) -> Result<Self, Error> {
+        // TODO: Handle attach under reset
+
+        let sequence_handle = match &target.debug_sequence {
+            DebugSequence::Mips(sequence) => sequence.clone(),
+            DebugSequence::Arm(_) | DebugSequence::Riscv(_) => {
+                panic!("Mismatch between architecture and sequence type!")
+            }
+        };
+        probe.inner_attach()?;
+        if let Some(scan_chain) = target.scan_chain.clone() {
+            probe.set_scan_chain(scan_chain)?;
+        }
+
+        let mut interface = probe
+            .try_into_mips_interface()
+            .map_err(|(_probe, err)| err)?;
+
+        // The sequence might have to select the EJTAG TAP, before debug mode can be used.
+        sequence_handle.on_connect(&mut interface)?;
+        interface.enter_debug_mode()?;
+
+        let mut session = Session {
+            target,
+            interface: ArchitectureInterface::Mips(Box::new(interface)),
+            cores,
+            configured_trace_sink: None,
+            configured_trace_source: TraceSource::Itm,
+            trace_deformatter: Deformatter::new(),
+            permissions,
+        };
+
+        {
+            let mut core = session.core(0)?;
+
+            core.halt(Duration::from_millis(100))?;
+        }
+
+        Ok(session)
+    }
+
+    /// Open a session for post-mortem debugging of a [`CoreDump`].
+    ///
+    /// The target is looked up by the name stored in the core dump. All cores of the session
+    /// are halted, and read their registers and memory from the core dump.
+    pub fn from_core_dump(core_dump: CoreDump) -> Result<Self, Error> {
+        let target = crate::config::get_target_by_name(core_dump.target_name())?;
+
+        let cores = target
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(id, core)| {
+                Core::create_state(
+                    id,
+                    core.core_access_options.clone(),
+                    &target,
+                    core.core_type,
+                )
+            })
+            .collect();
+
+        Ok(Session {
+            target,
+            interface: ArchitectureInterface::CoreDump(Box::new(core_dump)),
+            cores,
+            configured_trace_sink: None,
+            configured_trace_source: TraceSource::Itm,
+            trace_deformatter: Deformatter::new(),
+            permissions: Permissions::default(),
+        })
+    }
+
     /// Automatically creates a session with the first connected probe found.
     #[tracing::instrument(skip(target))]
     pub fn auto_attach(
@@ -350,8 +467,14 @@ impl Session {
 
         match sink {
             TraceSink::Swo(_) => {
+                let source = self.configured_trace_source;
                 let interface = self.get_arm_interface()?;
-                interface.read_swo()
+                let data = interface.read_swo()?;
+
+                match source {
+                    TraceSource::Itm => Ok(data),
+                    TraceSource::Etm => Ok(self.trace_deformatter.push(&data, source.trace_id())),
+                }
             }
 
             TraceSink::Tpiu(_) => {
@@ -359,9 +482,14 @@ impl Session {
             }
 
             TraceSink::TraceMemory => {
+                let source = self.configured_trace_source;
                 let components = self.get_arm_components(DpAddress::Default)?;
                 let interface = self.get_arm_interface()?;
-                crate::architecture::arm::component::read_trace_memory(interface, &components)
+                crate::architecture::arm::component::read_trace_memory(
+                    interface,
+                    &components,
+                    source,
+                )
             }
         }
     }
@@ -398,6 +526,16 @@ impl Session {
         Ok(interface)
     }
 
+    /// Get the MIPS probe interface.
+    pub fn get_mips_interface(&mut self) -> Result<&mut MipsCommunicationInterface, MipsError> {
+        let interface = match &mut self.interface {
+            ArchitectureInterface::Mips(interface) => interface,
+            _ => return Err(MipsError::NoMipsTarget),
+        };
+
+        Ok(interface)
+    }
+
     #[tracing::instrument(skip_all)]
     fn reattach_arm_interface(
         interface: &mut Box<dyn ArmProbeInterface>,
@@ -437,7 +575,7 @@ impl Session {
     pub fn has_sequence_erase_all(&self) -> bool {
         match &self.target.debug_sequence {
             DebugSequence::Arm(seq) => seq.debug_erase_sequence().is_some(),
-            DebugSequence::Riscv(_) => false,
+            DebugSequence::Riscv(_) | DebugSequence::Mips(_) => false,
         }
     }
 
@@ -452,7 +590,9 @@ impl Session {
     pub fn sequence_erase_all(&mut self) -> Result<(), Error> {
         let interface = match &mut self.interface {
             ArchitectureInterface::Arm(interface) => interface,
-            ArchitectureInterface::Riscv(_) => {
+            ArchitectureInterface::Riscv(_)
+            | ArchitectureInterface::Mips(_)
+            | ArchitectureInterface::CoreDump(_) => {
                 return Err(Error::Probe(crate::DebugProbeError::NotImplemented(
                     "Debug Erase Sequence",
                 )))
@@ -461,7 +601,7 @@ impl Session {
 
         let debug_sequence = match &self.target.debug_sequence {
             DebugSequence::Arm(seq) => seq.clone(),
-            DebugSequence::Riscv(_) => {
+            DebugSequence::Riscv(_) | DebugSequence::Mips(_) => {
                 unreachable!("This should never happen. Please file a bug if it does.")
             }
         };
@@ -509,6 +649,11 @@ impl Session {
         &self.target
     }
 
+    /// Get the permissions the session was created with.
+    pub(crate) fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
     /// Configure the target and probe for serial wire view (SWV) tracing.
     pub fn setup_tracing(
         &mut self,
@@ -523,7 +668,7 @@ impl Session {
 
         let sequence_handle = match &self.target.debug_sequence {
             DebugSequence::Arm(sequence) => sequence.clone(),
-            DebugSequence::Riscv(_) => {
+            DebugSequence::Riscv(_) | DebugSequence::Mips(_) => {
                 panic!("Mismatch between architecture and sequence type!")
             }
         };
@@ -547,10 +692,90 @@ impl Session {
         crate::architecture::arm::component::setup_tracing(interface, &components, &destination)?;
 
         self.configured_trace_sink.replace(destination);
+        self.configured_trace_source = TraceSource::Itm;
 
         Ok(())
     }
 
+    /// Configure the target and probe for instruction tracing using the ETM.
+    ///
+    /// After this, [`Session::read_trace_data`] returns the ETM trace data instead of the
+    /// ITM data, which can be decoded using [`InstructionTracer`].
+    ///
+    /// When tracing to SWO or the TPIU, the TPIU formatter is enabled to separate the ETM
+    /// data from the ITM data.
+    ///
+    /// Returns the architecture of the ETM, as [`InstructionTracer`] only decodes ETMv3 trace.
+    ///
+    /// [`InstructionTracer`]: crate::architecture::arm::etm::InstructionTracer
+    pub fn setup_instruction_trace(
+        &mut self,
+        core_index: usize,
+        destination: TraceSink,
+    ) -> Result<EtmArchitecture, Error> {
+        let destination = match destination {
+            TraceSink::Swo(config) => TraceSink::Swo(config.set_continuous_formatting(true)),
+            TraceSink::Tpiu(config) => TraceSink::Tpiu(config.set_continuous_formatting(true)),
+            TraceSink::TraceMemory => TraceSink::TraceMemory,
+        };
+
+        self.setup_tracing(core_index, destination)?;
+
+        let (ap, core_position, cores_on_ap) = self.etm_location(core_index);
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        let architecture = crate::architecture::arm::component::setup_instruction_trace(
+            interface,
+            &components,
+            ap,
+            core_position,
+            cores_on_ap,
+        )?;
+
+        self.configured_trace_source = TraceSource::Etm;
+        self.trace_deformatter = Deformatter::new();
+
+        Ok(architecture)
+    }
+
+    /// Stop the instruction trace configured by [`Session::setup_instruction_trace`].
+    ///
+    /// This disables the ETM and the trace capture of the core.
+    pub fn disable_instruction_trace(&mut self, core_index: usize) -> Result<(), Error> {
+        let (ap, core_position, cores_on_ap) = self.etm_location(core_index);
+        let sink = self.configured_trace_sink.take();
+        let components = self.get_arm_components(DpAddress::Default)?;
+        let interface = self.get_arm_interface()?;
+        crate::architecture::arm::component::disable_instruction_trace(
+            interface,
+            &components,
+            ap,
+            core_position,
+            cores_on_ap,
+            sink.as_ref(),
+        )?;
+
+        self.configured_trace_source = TraceSource::Itm;
+
+        self.disable_swv(core_index)
+    }
+
+    /// The access port of the ETM of a core, the position of the core among the cores
+    /// on that access port, and the number of cores on it.
+    fn etm_location(&self, core_index: usize) -> (MemoryAp, usize, usize) {
+        // The ETM of a core is on the access port of the core.
+        let ap = self.cores[core_index].arm_memory_ap();
+        let cores_on_ap: Vec<usize> = (0..self.cores.len())
+            .filter(|&index| self.cores[index].arm_memory_ap().ap_address() == ap.ap_address())
+            .collect();
+        let core_position = cores_on_ap
+            .iter()
+            .position(|&index| index == core_index)
+            .unwrap_or_default();
+
+        (ap, core_position, cores_on_ap.len())
+    }
+
     /// Configure the target to stop emitting SWV trace data.
     #[tracing::instrument(skip(self))]
     pub fn disable_swv(&mut self, core_index: usize) -> Result<(), Error> {
@@ -581,6 +806,8 @@ impl Session {
         match self.interface {
             ArchitectureInterface::Arm(_) => Architecture::Arm,
             ArchitectureInterface::Riscv(_) => Architecture::Riscv,
+            ArchitectureInterface::Mips(_) => Architecture::Mips,
+            ArchitectureInterface::CoreDump(ref core_dump) => core_dump.architecture(),
         }
     }
 
@@ -591,6 +818,67 @@ impl Session {
                 .and_then(|mut core| core.clear_all_hw_breakpoints())
         })
     }
+
+    /// Puts the given cores into a common halt group, so that all of them are halted
+    /// as soon as one of them halts, e.g. because of a breakpoint.
+    ///
+    /// This is supported for RISC-V harts, if the debug module implements halt groups.
+    /// If the debug module also implements resume groups, the cores are resumed together as well.
+    /// Returns `false` if the cores can't be halted together, in which case they have to
+    /// be halted one after another.
+    pub fn enable_halt_group(&mut self, cores: &[usize]) -> Result<bool, Error> {
+        if !matches!(self.interface, ArchitectureInterface::Riscv(_)) || cores.len() < 2 {
+            return Ok(false);
+        }
+
+        let harts = self.riscv_hart_ids(cores)?;
+        let interface = self.get_riscv_interface()?;
+
+        // Halt group 0 means that a hart is not part of any halt group
+        for (index, &hart) in harts.iter().enumerate() {
+            if !interface.set_halt_group(hart, 1)? {
+                tracing::debug!("Hart {} does not support halt groups", hart);
+
+                // Don't leave the harts configured so far in a partial halt group
+                for &configured in &harts[..index] {
+                    interface.set_halt_group(configured, 0)?;
+                }
+
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Removes the given cores from the halt group set up by [`Session::enable_halt_group`],
+    /// so that they can be halted and resumed independently again.
+    pub fn disable_halt_group(&mut self, cores: &[usize]) -> Result<(), Error> {
+        if !matches!(self.interface, ArchitectureInterface::Riscv(_)) || cores.len() < 2 {
+            return Ok(());
+        }
+
+        let harts = self.riscv_hart_ids(cores)?;
+        let interface = self.get_riscv_interface()?;
+
+        for hart in harts {
+            interface.set_halt_group(hart, 0)?;
+        }
+
+        Ok(())
+    }
+
+    fn riscv_hart_ids(&self, cores: &[usize]) -> Result<Vec<u32>, Error> {
+        cores
+            .iter()
+            .map(|&core_index| {
+                self.cores
+                    .get(core_index)
+                    .map(|core| core.core_state.riscv_hart_id())
+                    .ok_or(Error::CoreNotFound(core_index))
+            })
+            .collect()
+    }
 }
 
 // This test ensures that [Session] is fully [Send] + [Sync].
@@ -607,6 +895,13 @@ impl Drop for Session {
             tracing::warn!("Could not clear all hardware breakpoints: {:?}", err);
         }
 
+        if let Err(err) = { 0..self.cores.len() }.try_for_each(|i| {
+            self.core(i)
+                .and_then(|mut core| core.clear_all_watchpoints())
+        }) {
+            tracing::warn!("Could not clear all data watchpoints: {:?}", err);
+        }
+
         // Call any necessary deconfiguration/shutdown hooks.
         if let Err(err) = { 0..self.cores.len() }
             .try_for_each(|i| self.core(i).and_then(|mut core| core.debug_core_stop()))
@@ -675,9 +970,15 @@ fn get_target_from_selector(
             if found_chip.is_none() && probe.has_riscv_interface() {
                 match probe.try_into_riscv_interface() {
                     Ok(mut interface) => {
-                        let idcode = interface.read_idcode();
+                        let found_riscv_chip =
+                            interface.read_chip_info().map(Some).unwrap_or_else(|e| {
+                                tracing::info!("Error during auto-detection of RISCV chips: {}", e);
+                                None
+                            });
 
-                        tracing::debug!("ID Code read over JTAG: {:x?}", idcode);
+                        tracing::debug!("RISCV chip information: {:x?}", found_riscv_chip);
+
+                        found_chip = found_riscv_chip.map(ChipInfo::from);
 
                         probe = interface.close();
                     }
@@ -719,6 +1020,8 @@ fn get_target_from_selector(
 pub struct Permissions {
     /// When set to true, all memory of the chip may be erased or reset to factory default
     erase_all: bool,
+    /// When set to true, the read protection and the option bytes of the chip may be changed
+    protect: bool,
 }
 
 impl Permissions {
@@ -747,6 +1050,26 @@ impl Permissions {
             Err(MissingPermissions("erase_all".into()))
         }
     }
+
+    /// Allow the session to change the read protection and the option bytes of the chip.
+    ///
+    /// # Warning
+    /// Depending on the device, this can permanently lock the debug access to the chip.
+    #[must_use]
+    pub fn allow_protect(self) -> Self {
+        Self {
+            protect: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn protect(&self) -> Result<(), MissingPermissions> {
+        if self.protect {
+            Ok(())
+        } else {
+            Err(MissingPermissions("protect".into()))
+        }
+    }
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
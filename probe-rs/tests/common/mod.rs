@@ -0,0 +1,40 @@
+//! Fixtures shared by the tests running on the simulated Cortex-M target.
+#![allow(dead_code)]
+
+use std::fs::File;
+
+use probe_rs::{
+    config::add_target_from_yaml, FakeProbe, Permissions, Probe, Session, SimulatedTarget,
+};
+
+/// Start of the RAM of the simulated target.
+pub const RAM: u32 = 0x2000_0000;
+
+/// A simulated target with 64 KiB of flash at address 0, and 16 KiB of RAM.
+pub fn simulated_target() -> SimulatedTarget {
+    SimulatedTarget::new()
+        .with_flash(0x0, 0x1_0000, 0x1000)
+        .with_ram(RAM, 0x4000)
+}
+
+/// Add the `simulated_cortex_m` target to the registry.
+pub fn add_simulated_target() {
+    add_target_from_yaml(File::open("tests/simulated_cortex_m.yaml").unwrap()).unwrap();
+}
+
+/// Create a probe which is connected to `target`.
+pub fn simulated_probe(target: SimulatedTarget) -> Probe {
+    Probe::from_specific_probe(Box::new(FakeProbe::with_simulated_target(target)))
+}
+
+pub fn attach(target: SimulatedTarget) -> Session {
+    attach_with_permissions(target, Permissions::default())
+}
+
+pub fn attach_with_permissions(target: SimulatedTarget, permissions: Permissions) -> Session {
+    add_simulated_target();
+
+    simulated_probe(target)
+        .attach("simulated_cortex_m", permissions)
+        .expect("Failed to attach to the simulated target.")
+}
@@ -0,0 +1,265 @@
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use common::{attach, simulated_target, RAM};
+use probe_rs::{
+    semihosting::SemihostingHost, BreakpointCause, Core, CoreStatus, HaltReason, MemoryInterface,
+    RegisterId, SemihostingCommand,
+};
+
+/// Parameter blocks and buffers used by the tests.
+const DATA: u32 = RAM + 0x100;
+
+/// `bkpt 0xAB`
+const SEMIHOSTING_CALL: [u8; 2] = [0xAB, 0xBE];
+
+/// Console output of the semihosting host.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run_to_halt(core: &mut Core<'_>) -> CoreStatus {
+    core.run().unwrap();
+    core.wait_for_core_halted(Duration::from_millis(100))
+        .unwrap();
+    core.status().unwrap()
+}
+
+/// Performs a single semihosting call, and returns the result in r0.
+fn call(core: &mut Core<'_>, host: &mut SemihostingHost, operation: u32, parameter: u32) -> u32 {
+    // The call is followed by `b .`, where the core is halted after the call was serviced.
+    core.write_8(u64::from(RAM), &[0xAB, 0xBE, 0xFE, 0xE7])
+        .unwrap();
+    core.write_core_reg(RegisterId(0), operation).unwrap();
+    core.write_core_reg(RegisterId(1), parameter).unwrap();
+    core.write_core_reg(core.program_counter().id(), RAM)
+        .unwrap();
+
+    let status = run_to_halt(core);
+    let CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+        SemihostingCommand::Request {
+            operation,
+            parameter,
+        },
+    ))) = status
+    else {
+        panic!("Expected a semihosting request, got {status:?}");
+    };
+
+    host.service(core, operation, parameter).unwrap();
+    assert_eq!(core.status().unwrap(), CoreStatus::Running);
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    let pc: u32 = core.read_core_reg(core.program_counter().id()).unwrap();
+    assert_eq!(pc, RAM + 2);
+
+    core.read_core_reg(RegisterId(0)).unwrap()
+}
+
+/// Runs a program which prints to the console, and exits.
+#[test]
+fn console_output_and_exit() {
+    let mut session = attach(simulated_target());
+    let mut core = session.core(0).unwrap();
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    let output = Output::default();
+    let mut host = SemihostingHost::new().with_stdout(output.clone());
+
+    let mut program = Vec::new();
+    // SYS_WRITE0, with r1 pointing to the string
+    program.extend_from_slice(&SEMIHOSTING_CALL);
+    // movs r0, #3 (SYS_WRITEC)
+    program.extend_from_slice(&[0x03, 0x20]);
+    program.extend_from_slice(&SEMIHOSTING_CALL);
+    // movs r0, #0x18 (SYS_EXIT); ldr r1, [r2]
+    program.extend_from_slice(&[0x18, 0x20, 0x11, 0x68]);
+    program.extend_from_slice(&SEMIHOSTING_CALL);
+
+    core.write_8(u64::from(RAM), &program).unwrap();
+    core.write_8(u64::from(DATA), b"Hello\n\0").unwrap();
+    core.write_word_32(u64::from(DATA + 0x10), 0x20026).unwrap();
+
+    core.write_core_reg(RegisterId(0), 0x04u32).unwrap();
+    core.write_core_reg(RegisterId(1), DATA).unwrap();
+    core.write_core_reg(RegisterId(2), DATA + 0x10).unwrap();
+    core.write_core_reg(core.program_counter().id(), RAM)
+        .unwrap();
+
+    let mut status = run_to_halt(&mut core);
+    while let CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+        SemihostingCommand::Request {
+            operation,
+            parameter,
+        },
+    ))) = status
+    {
+        host.service(&mut core, operation, parameter).unwrap();
+        core.wait_for_core_halted(Duration::from_millis(100))
+            .unwrap();
+        status = core.status().unwrap();
+    }
+
+    assert_eq!(
+        status,
+        CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(
+            SemihostingCommand::ExitSuccess
+        )))
+    );
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"Hello\nH");
+}
+
+/// Writes and reads back a file in the semihosting root, and checks that files outside of the
+/// root can't be opened.
+#[test]
+fn file_access_in_root() {
+    const SYS_OPEN: u32 = 0x01;
+    const SYS_CLOSE: u32 = 0x02;
+    const SYS_WRITE: u32 = 0x05;
+    const SYS_READ: u32 = 0x06;
+    const SYS_SEEK: u32 = 0x0A;
+    const SYS_FLEN: u32 = 0x0C;
+    const SYS_GET_CMDLINE: u32 = 0x15;
+
+    let root = std::env::temp_dir().join(format!("probe-rs-semihosting-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+
+    let mut session = attach(simulated_target());
+    let mut core = session.core(0).unwrap();
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    let mut host = SemihostingHost::new()
+        .with_root(&root)
+        .with_command_line("test --verbose");
+
+    let name = DATA + 0x100;
+    let buffer = DATA + 0x200;
+    let data = DATA + 0x300;
+    let block = |core: &mut Core<'_>, fields: &[u32]| {
+        core.write_32(u64::from(DATA), fields).unwrap();
+        DATA
+    };
+
+    // Open "out.bin" with mode "w+b".
+    core.write_8(u64::from(name), b"out.bin\0").unwrap();
+    let parameter = block(&mut core, &[name, 7, 7]);
+    let handle = call(&mut core, &mut host, SYS_OPEN, parameter);
+    assert_ne!(handle, u32::MAX);
+
+    core.write_8(u64::from(data), b"semihosting").unwrap();
+    let parameter = block(&mut core, &[handle, data, 11]);
+    assert_eq!(call(&mut core, &mut host, SYS_WRITE, parameter), 0);
+
+    let parameter = block(&mut core, &[handle]);
+    assert_eq!(call(&mut core, &mut host, SYS_FLEN, parameter), 11);
+
+    let parameter = block(&mut core, &[handle, 4]);
+    assert_eq!(call(&mut core, &mut host, SYS_SEEK, parameter), 0);
+
+    // Reading more than the rest of the file returns the number of bytes not read.
+    let parameter = block(&mut core, &[handle, buffer, 16]);
+    assert_eq!(call(&mut core, &mut host, SYS_READ, parameter), 9);
+    let mut read = [0; 7];
+    core.read_8(u64::from(buffer), &mut read).unwrap();
+    assert_eq!(&read, b"hosting");
+
+    let parameter = block(&mut core, &[handle]);
+    assert_eq!(call(&mut core, &mut host, SYS_CLOSE, parameter), 0);
+    assert_eq!(call(&mut core, &mut host, SYS_CLOSE, parameter), u32::MAX);
+
+    assert_eq!(std::fs::read(root.join("out.bin")).unwrap(), b"semihosting");
+
+    // Files outside of the root can't be opened.
+    core.write_8(u64::from(name), b"../out.bin\0").unwrap();
+    let parameter = block(&mut core, &[name, 0, 10]);
+    assert_eq!(call(&mut core, &mut host, SYS_OPEN, parameter), u32::MAX);
+
+    let parameter = block(&mut core, &[buffer, 64]);
+    assert_eq!(call(&mut core, &mut host, SYS_GET_CMDLINE, parameter), 0);
+    assert_eq!(core.read_word_32(u64::from(DATA + 4)).unwrap(), 14);
+    let mut command_line = [0; 15];
+    core.read_8(u64::from(buffer), &mut command_line).unwrap();
+    assert_eq!(&command_line, b"test --verbose\0");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// Checks that transfers larger than the host's buffer are split into chunks, and that
+/// invalid parameters are reported to the target instead of failing the call.
+#[test]
+fn large_and_invalid_transfers() {
+    const SYS_OPEN: u32 = 0x01;
+    const SYS_WRITE0: u32 = 0x04;
+    const SYS_WRITE: u32 = 0x05;
+    const SYS_READ: u32 = 0x06;
+    const SYS_ERRNO: u32 = 0x13;
+
+    let mut session = attach(simulated_target());
+    let mut core = session.core(0).unwrap();
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    let output = Output::default();
+    let mut host = SemihostingHost::new()
+        .with_stdout(output.clone())
+        .with_stdin(&b"input"[..]);
+
+    let name = DATA + 0x100;
+    let data = DATA + 0x200;
+    let block = |core: &mut Core<'_>, fields: &[u32]| {
+        core.write_32(u64::from(DATA), fields).unwrap();
+        DATA
+    };
+
+    // Open the console for writing, and for reading.
+    core.write_8(u64::from(name), b":tt\0").unwrap();
+    let parameter = block(&mut core, &[name, 4, 3]);
+    let stdout = call(&mut core, &mut host, SYS_OPEN, parameter);
+    let parameter = block(&mut core, &[name, 0, 3]);
+    let stdin = call(&mut core, &mut host, SYS_OPEN, parameter);
+
+    let text: Vec<u8> = (0..0x2800u32).map(|i| b'a' + (i % 26) as u8).collect();
+    core.write_8(u64::from(data), &text).unwrap();
+    let parameter = block(&mut core, &[stdout, data, text.len() as u32]);
+    assert_eq!(call(&mut core, &mut host, SYS_WRITE, parameter), 0);
+    assert_eq!(output.0.lock().unwrap().as_slice(), text.as_slice());
+
+    // Reading the console stops at the available input.
+    let parameter = block(&mut core, &[stdin, data, 0x2000]);
+    assert_eq!(call(&mut core, &mut host, SYS_READ, parameter), 0x2000 - 5);
+    let mut read = [0; 5];
+    core.read_8(u64::from(data), &mut read).unwrap();
+    assert_eq!(&read, b"input");
+
+    // A string without a null within the longest length the host supports.
+    assert_eq!(call(&mut core, &mut host, SYS_WRITE0, data), u32::MAX);
+    // EINVAL
+    assert_eq!(call(&mut core, &mut host, SYS_ERRNO, 0), 22);
+    assert_eq!(output.0.lock().unwrap().len(), text.len());
+
+    // A file name longer than the host supports.
+    let parameter = block(&mut core, &[name, 0, 0x1000_0000]);
+    assert_eq!(call(&mut core, &mut host, SYS_OPEN, parameter), u32::MAX);
+    // ENAMETOOLONG
+    assert_eq!(call(&mut core, &mut host, SYS_ERRNO, 0), 91);
+
+    // A buffer outside of the memory of the target.
+    let parameter = block(&mut core, &[stdout, 0x8000_0000, 0x1000_0000]);
+    assert_eq!(call(&mut core, &mut host, SYS_WRITE, parameter), u32::MAX);
+    // EIO
+    assert_eq!(call(&mut core, &mut host, SYS_ERRNO, 0), 5);
+}
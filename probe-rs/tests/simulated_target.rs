@@ -0,0 +1,385 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use common::{
+    add_simulated_target, attach, attach_with_permissions, simulated_probe, simulated_target, RAM,
+};
+use probe_rs::{
+    config::add_target_from_yaml,
+    flashing::{self, DownloadOptions, FlashError, FlashProgress, ProgressEvent},
+    CoreStatus, HaltReason, MemoryInterface, Permissions, RegisterId, Session, WatchpointAccess,
+};
+
+/// Steps and runs a loop incrementing a counter in RAM.
+#[test]
+fn step_and_run_program_in_ram() {
+    let mut session = attach(simulated_target());
+    let mut core = session.core(0).unwrap();
+
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    // loop: ldr r1, [r0]; adds r1, #1; str r1, [r0]; b loop
+    let program: [u8; 8] = [0x01, 0x68, 0x01, 0x31, 0x01, 0x60, 0xFB, 0xE7];
+    let counter = u64::from(RAM + 0x100);
+
+    core.write_8(u64::from(RAM), &program).unwrap();
+    core.write_word_32(counter, 0).unwrap();
+    core.write_core_reg(RegisterId(0), counter as u32).unwrap();
+    core.write_core_reg(core.program_counter().id(), RAM)
+        .unwrap();
+
+    for expected_pc in [RAM + 2, RAM + 4, RAM + 6, RAM] {
+        let info = core.step().unwrap();
+        assert_eq!(info.pc, u64::from(expected_pc));
+    }
+    assert_eq!(core.read_word_32(counter).unwrap(), 1);
+
+    core.run().unwrap();
+    assert_eq!(core.status().unwrap(), CoreStatus::Running);
+
+    core.halt(Duration::from_millis(100)).unwrap();
+    assert!(core.read_word_32(counter).unwrap() > 1);
+}
+
+/// Flashes a program using the flash algorithm of the simulated target, and runs it.
+#[test]
+fn flash_and_run_to_breakpoint() {
+    let mut session = attach(simulated_target());
+
+    // Vector table with the initial stack pointer and the reset vector,
+    // followed by `movs r0, #42; nop; bkpt; b .`
+    let mut image = Vec::new();
+    image.extend_from_slice(&(RAM + 0x4000).to_le_bytes());
+    image.extend_from_slice(&0x9u32.to_le_bytes());
+    image.extend_from_slice(&[0x2A, 0x20, 0x00, 0xBF, 0x00, 0xBE, 0xFE, 0xE7]);
+
+    let mut loader = session.target().flash_loader();
+    loader.add_data(0x0, &image).unwrap();
+    loader
+        .commit(&mut session, DownloadOptions::default())
+        .unwrap();
+
+    let mut core = session.core(0).unwrap();
+
+    let mut flash = vec![0; 0x1000];
+    core.read_8(0x0, &mut flash).unwrap();
+    assert_eq!(&flash[..image.len()], image.as_slice());
+    assert!(flash[image.len()..].iter().all(|byte| *byte == 0xFF));
+
+    let info = core.reset_and_halt(Duration::from_millis(100)).unwrap();
+    assert_eq!(info.pc, 0x8);
+
+    core.set_hw_breakpoint(0xA).unwrap();
+    core.run().unwrap();
+    core.wait_for_core_halted(Duration::from_millis(100))
+        .unwrap();
+
+    assert!(matches!(
+        core.status().unwrap(),
+        CoreStatus::Halted(HaltReason::Breakpoint(_))
+    ));
+    let pc: u32 = core.read_core_reg(core.program_counter().id()).unwrap();
+    assert_eq!(pc, 0xA);
+    let r0: u32 = core.read_core_reg(RegisterId(0)).unwrap();
+    assert_eq!(r0, 42);
+
+    // Continue to the `bkpt` instruction.
+    core.clear_hw_breakpoint(0xA).unwrap();
+    core.run().unwrap();
+    core.wait_for_core_halted(Duration::from_millis(100))
+        .unwrap();
+
+    let pc: u32 = core.read_core_reg(core.program_counter().id()).unwrap();
+    assert_eq!(pc, 0xC);
+}
+
+#[test]
+fn attach_under_reset() {
+    add_simulated_target();
+
+    let mut session = simulated_probe(simulated_target())
+        .attach_under_reset("simulated_cortex_m", Permissions::default())
+        .unwrap();
+
+    let mut core = session.core(0).unwrap();
+    assert!(matches!(
+        core.status().unwrap(),
+        CoreStatus::Halted(HaltReason::Exception)
+    ));
+}
+
+/// Watchpoints don't use DWT comparators which are already used for data tracing.
+#[test]
+fn watchpoints_skip_comparators_used_for_tracing() {
+    const DWT_FUNCTION0: u64 = 0xE000_1028;
+    const DWT_FUNCTION1: u64 = 0xE000_1038;
+
+    let mut session = attach(simulated_target());
+    let mut core = session.core(0).unwrap();
+    core.halt(Duration::from_millis(100)).unwrap();
+
+    // Comparator 0 emits data trace packets through the ITM.
+    core.write_word_32(DWT_FUNCTION0, 0b0011).unwrap();
+
+    let address = u64::from(RAM + 0x100);
+    core.set_watchpoint(address, 4, WatchpointAccess::Write)
+        .unwrap();
+
+    let watchpoints = core.watchpoints().unwrap();
+    assert_eq!(watchpoints[0], None);
+    assert!(matches!(watchpoints[1], Some(watchpoint) if watchpoint.address == address));
+    assert_eq!(core.read_word_32(DWT_FUNCTION0).unwrap(), 0b0011);
+    assert_eq!(core.read_word_32(DWT_FUNCTION1).unwrap(), 0b0110);
+
+    // The tracing comparator is kept when all watchpoints are cleared.
+    core.clear_all_watchpoints().unwrap();
+    assert_eq!(core.read_word_32(DWT_FUNCTION0).unwrap(), 0b0011);
+    assert_eq!(core.read_word_32(DWT_FUNCTION1).unwrap(), 0);
+}
+
+/// Reads and writes the option bytes, and enables and disables readout protection.
+#[test]
+fn option_bytes_and_protection() {
+    let mut session = attach_with_permissions(
+        simulated_target(),
+        Permissions::new().allow_protect().allow_erase_all(),
+    );
+
+    assert_eq!(
+        flashing::read_option_bytes(&mut session, None).unwrap(),
+        vec![0; 16]
+    );
+
+    let option_bytes: Vec<u8> = (0..16).collect();
+    flashing::write_option_bytes(&mut session, &option_bytes, None).unwrap();
+    assert_eq!(
+        flashing::read_option_bytes(&mut session, None).unwrap(),
+        option_bytes
+    );
+
+    assert!(matches!(
+        flashing::write_option_bytes(&mut session, &[0; 4], None),
+        Err(FlashError::InvalidOptionBytesSize {
+            size: 4,
+            expected: 16
+        })
+    ));
+
+    // The simulated flash controller stores the protection level in the first option byte,
+    // and erases the flash when the protection is removed.
+    let mut loader = session.target().flash_loader();
+    loader.add_data(0x0, &[0x12, 0x34, 0x56, 0x78]).unwrap();
+    loader
+        .commit(&mut session, DownloadOptions::default())
+        .unwrap();
+    assert!(!flashing::blank_check(&mut session, None).unwrap());
+
+    flashing::protect(&mut session, 2, None).unwrap();
+    assert_eq!(
+        flashing::read_option_bytes(&mut session, None).unwrap()[0],
+        2
+    );
+
+    flashing::unprotect(&mut session, None).unwrap();
+    assert_eq!(
+        flashing::read_option_bytes(&mut session, None).unwrap()[0],
+        0
+    );
+    assert!(flashing::blank_check(&mut session, None).unwrap());
+}
+
+/// A freshly erased flash is blank, and stops being blank once it is programmed.
+#[test]
+fn blank_check() {
+    let mut session = attach(simulated_target());
+
+    assert!(flashing::blank_check(&mut session, None).unwrap());
+
+    // Only the last byte of the last sector is programmed.
+    let mut loader = session.target().flash_loader();
+    loader.add_data(0xFFFF, &[0x00]).unwrap();
+    loader
+        .commit(&mut session, DownloadOptions::default())
+        .unwrap();
+
+    assert!(!flashing::blank_check(&mut session, None).unwrap());
+}
+
+/// Protection routines need the corresponding permissions.
+#[test]
+fn protection_requires_permissions() {
+    let mut session = attach(simulated_target());
+
+    assert!(matches!(
+        flashing::write_option_bytes(&mut session, &[0; 16], None),
+        Err(FlashError::MissingPermissions(_))
+    ));
+    assert!(matches!(
+        flashing::protect(&mut session, 1, None),
+        Err(FlashError::MissingPermissions(_))
+    ));
+    assert!(matches!(
+        flashing::unprotect(&mut session, None),
+        Err(FlashError::MissingPermissions(_))
+    ));
+}
+
+/// Option bytes which don't fit into the page buffer of the flash algorithm are rejected.
+#[test]
+fn option_bytes_larger_than_page_buffer() {
+    let yaml = std::fs::read_to_string("tests/simulated_cortex_m.yaml")
+        .unwrap()
+        .replace(
+            "name: Simulated Cortex-M",
+            "name: Simulated Cortex-M small pages",
+        )
+        .replace(
+            "name: simulated_cortex_m\n",
+            "name: simulated_cortex_m_small_pages\n",
+        )
+        .replace("page_size: 0x400", "page_size: 0x8");
+    add_target_from_yaml(yaml.as_bytes()).unwrap();
+
+    let mut session = simulated_probe(simulated_target())
+        .attach(
+            "simulated_cortex_m_small_pages",
+            Permissions::new().allow_protect(),
+        )
+        .unwrap();
+
+    assert!(matches!(
+        flashing::write_option_bytes(&mut session, &[0; 16], None),
+        Err(FlashError::OptionBytesTooLarge {
+            size: 16,
+            page_size: 8
+        })
+    ));
+    assert!(matches!(
+        flashing::read_option_bytes(&mut session, None),
+        Err(FlashError::OptionBytesTooLarge {
+            size: 16,
+            page_size: 8
+        })
+    ));
+}
+
+/// Flashes `data` at address 0 with `skip_unchanged_sectors`, and returns the number of skipped sectors.
+fn flash_skipping_unchanged_sectors(session: &mut Session, data: &[u8]) -> usize {
+    let skipped_sectors = Arc::new(AtomicUsize::new(0));
+    let skipped_sectors_clone = skipped_sectors.clone();
+
+    let mut options = DownloadOptions::default();
+    options.skip_unchanged_sectors = true;
+    options.progress = Some(FlashProgress::new(move |event| {
+        if let ProgressEvent::SectorSkipped { .. } = event {
+            skipped_sectors_clone.fetch_add(1, Ordering::Relaxed);
+        }
+    }));
+
+    let mut loader = session.target().flash_loader();
+    loader.add_data(0x0, data).unwrap();
+    loader.commit(session, options).unwrap();
+
+    skipped_sectors.load(Ordering::Relaxed)
+}
+
+/// Sectors are only skipped if the bytes which are not written are already erased.
+#[test]
+fn skip_unchanged_sectors() {
+    let mut session = attach(simulated_target());
+
+    // Fill the first two sectors.
+    assert_eq!(
+        flash_skipping_unchanged_sectors(&mut session, &[0x11; 0x2000]),
+        0
+    );
+
+    // The data at the start of the first sector is unchanged, but the rest of the sector has to be erased.
+    assert_eq!(
+        flash_skipping_unchanged_sectors(&mut session, &[0x11; 0x10]),
+        0
+    );
+
+    let mut flash = vec![0; 0x2000];
+    session.core(0).unwrap().read_8(0x0, &mut flash).unwrap();
+    assert!(flash[..0x10].iter().all(|byte| *byte == 0x11));
+    assert!(flash[0x10..0x1000].iter().all(|byte| *byte == 0xFF));
+    // The second sector is not part of the image, so it is left alone.
+    assert!(flash[0x1000..].iter().all(|byte| *byte == 0x11));
+
+    // Now the whole sector is unchanged.
+    assert_eq!(
+        flash_skipping_unchanged_sectors(&mut session, &[0x11; 0x10]),
+        1
+    );
+}
+
+/// Flashes `data` at address 0 and verifies it.
+///
+/// Without erasing, programming can only clear bits, so the flash contents may not match `data` afterwards.
+fn flash_and_verify(
+    session: &mut Session,
+    data: &[u8],
+    skip_erase: bool,
+) -> Result<(), FlashError> {
+    let mut options = DownloadOptions::default();
+    options.verify = true;
+    options.skip_erase = skip_erase;
+
+    let mut loader = session.target().flash_loader();
+    loader.add_data(0x0, data).unwrap();
+    loader.commit(session, options)
+}
+
+/// Flash contents are verified with the `Verify()` entry point of the flash algorithm.
+#[test]
+fn verify_with_algorithm() {
+    let mut session = attach(simulated_target());
+
+    flash_and_verify(&mut session, &[0x00; 0x800], false).unwrap();
+
+    // The flash still contains zeros, which don't match the image.
+    assert!(matches!(
+        flash_and_verify(&mut session, &[0x5A; 0x800], true),
+        Err(FlashError::Verify)
+    ));
+}
+
+/// Without a `Verify()` entry point, flash contents are verified with a CRC32 calculated on the target.
+#[test]
+fn verify_with_crc32() {
+    let yaml = std::fs::read_to_string("tests/simulated_cortex_m.yaml")
+        .unwrap()
+        .replace(
+            "name: Simulated Cortex-M",
+            "name: Simulated Cortex-M without Verify",
+        )
+        .replace(
+            "name: simulated_cortex_m\n",
+            "name: simulated_without_verify\n",
+        )
+        .replace("    pc_verify: 0x29\n", "");
+    add_target_from_yaml(yaml.as_bytes()).unwrap();
+
+    let mut session = simulated_probe(simulated_target())
+        .attach("simulated_without_verify", Permissions::default())
+        .unwrap();
+
+    let image: Vec<u8> = (0..0x800).map(|i| (i * 7) as u8).collect();
+    flash_and_verify(&mut session, &image, false).unwrap();
+
+    // Only a single byte differs, and it can't be programmed without erasing.
+    let mut corrupted = image.clone();
+    corrupted[0x123] = 0xFF;
+    assert!(matches!(
+        flash_and_verify(&mut session, &corrupted, true),
+        Err(FlashError::Verify)
+    ));
+}
@@ -105,3 +105,31 @@ fn find_non_existing_unit_by_path() {
         .get_breakpoint_location(unit_path, 14, None)
         .is_err());
 }
+
+#[test]
+fn function_breakpoint_location() {
+    let di = DebugInfo::from_file("tests/probe-rs-debugger-test").unwrap();
+
+    // The address after the prologue, which is the first statement of `main()`.
+    let expected_address = TEST_DATA[0].0;
+
+    for function_name in [
+        "__cortex_m_rt_main",
+        "probe_rs_debugger_test::__cortex_m_rt_main",
+    ] {
+        assert_eq!(
+            vec![expected_address],
+            di.get_function_addresses(function_name).unwrap(),
+            "Addresses do not match for function {function_name:?}"
+        );
+    }
+
+    assert_eq!(
+        vec![0x8005288],
+        di.get_function_addresses("rust_begin_unwind").unwrap()
+    );
+    assert!(di
+        .get_function_addresses("not_a_function")
+        .unwrap()
+        .is_empty());
+}
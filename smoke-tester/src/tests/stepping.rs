@@ -11,8 +11,11 @@ const TEST_CODE: &[u8] = include_bytes!("test_arm.bin");
 pub fn test_stepping(core: &mut Core, memory_regions: &[MemoryRegion]) -> Result<()> {
     println!("Testing stepping...");
 
-    if core.architecture() == Architecture::Riscv {
-        // Not implemented for RISCV yet
+    if matches!(
+        core.architecture(),
+        Architecture::Riscv | Architecture::Mips
+    ) {
+        // Not implemented for RISCV and MIPS yet
         return Ok(());
     }
 
@@ -105,6 +105,8 @@ pub fn cmd_elf(
                 flash_algorithms: vec![algorithm_name],
                 rtt_scan_ranges: None,
                 scan_chain: None,
+                debug_sequences: None,
+                riscv_identification: None,
             }],
             flash_algorithms: vec![algorithm],
             source: BuiltIn,
@@ -10,14 +10,20 @@ use probe_rs::{
     flashing::FlashAlgorithm,
     Architecture, CoreType,
 };
-use probe_rs_target::{ArmCoreAccessOptions, CoreAccessOptions, RiscvCoreAccessOptions};
+use probe_rs_target::{
+    ArmCoreAccessOptions, CoreAccessOptions, MipsCoreAccessOptions, PackSequences,
+    RiscvCoreAccessOptions,
+};
 use std::{
+    collections::HashMap,
     fs::{self},
     io::Read,
     path::Path,
 };
 use tokio::runtime::Builder;
 
+use crate::sequences::parse_sequences;
+
 pub(crate) enum Kind<'a, T>
 where
     T: std::io::Seek + std::io::Read,
@@ -28,6 +34,7 @@ where
 
 pub(crate) fn handle_package<T>(
     pdsc: Package,
+    mut sequences: HashMap<String, PackSequences>,
     mut kind: Kind<T>,
     families: &mut Vec<ChipFamily>,
     only_supported_familes: bool,
@@ -149,9 +156,12 @@ where
         let cores = device
             .processors
             .iter()
-            .map(create_core)
+            .enumerate()
+            .map(|(index, processor)| create_core(processor, index))
             .collect::<Result<Vec<_>>>()?;
 
+        let debug_sequences = sequences.remove(&device_name);
+
         family.variants.push(Chip {
             name: device_name,
             part: None,
@@ -160,13 +170,15 @@ where
             flash_algorithms: flash_algorithm_names,
             rtt_scan_ranges: None,
             scan_chain: None, // TODO, parse from sdf
+            debug_sequences,
+            riscv_identification: None,
         });
     }
 
     Ok(())
 }
 
-fn create_core(processor: &Processor) -> Result<ProbeCore> {
+fn create_core(processor: &Processor, index: usize) -> Result<ProbeCore> {
     let core_type = core_to_probe_core(&processor.core)?;
     Ok(ProbeCore {
         name: processor
@@ -182,7 +194,11 @@ fn create_core(processor: &Processor) -> Result<ProbeCore> {
                 debug_base: None,
                 cti_base: None,
             }),
-            Architecture::Riscv => CoreAccessOptions::Riscv(RiscvCoreAccessOptions {}),
+            // Assume that the harts are listed in order
+            Architecture::Riscv => CoreAccessOptions::Riscv(RiscvCoreAccessOptions {
+                hart_id: index as u32,
+            }),
+            Architecture::Mips => CoreAccessOptions::Mips(MipsCoreAccessOptions {}),
         },
     })
 }
@@ -216,8 +232,13 @@ pub(crate) fn visit_dirs(path: &Path, families: &mut Vec<ChipFamily>) -> Result<
             if extension == "pdsc" {
                 log::info!("Found .pdsc file: {}", path.display());
 
+                let pdsc = fs::read_to_string(&entry_path)?;
+
                 handle_package::<std::fs::File>(
-                    Package::from_path(&entry.path())?,
+                    Package::from_string(&pdsc).map_err(|e| {
+                        anyhow!("Failed to parse pdsc file {}: {}", entry_path.display(), e)
+                    })?,
+                    parse_sequences(&pdsc)?,
                     Kind::Directory(path),
                     families,
                     false,
@@ -254,9 +275,17 @@ pub(crate) fn visit_file(path: &Path, families: &mut Vec<ChipFamily>) -> Result<
         )
     })?;
 
+    let sequences = parse_sequences(&pdsc)?;
+
     drop(pdsc_file);
 
-    handle_package(package, Kind::Archive(&mut archive), families, false)
+    handle_package(
+        package,
+        sequences,
+        Kind::Archive(&mut archive),
+        families,
+        false,
+    )
 }
 
 pub(crate) fn visit_arm_files(
@@ -379,6 +408,19 @@ pub(crate) async fn visit_arm_file(
         }
     };
 
+    let sequences = match parse_sequences(&pdsc) {
+        Ok(sequences) => sequences,
+        Err(e) => {
+            log::error!(
+                "Failed to parse the debug sequences in pdsc file '{}' in CMSIS Pack {}: {:?}",
+                pdsc_file.name(),
+                &url,
+                e
+            );
+            return vec![];
+        }
+    };
+
     let pdsc_name = pdsc_file.name().to_owned();
 
     drop(pdsc_file);
@@ -387,6 +429,7 @@ pub(crate) async fn visit_arm_file(
 
     match handle_package(
         package,
+        sequences,
         Kind::Archive(&mut archive),
         &mut families,
         only_supported_familes,
@@ -4,6 +4,7 @@ pub mod fetch;
 pub mod flash_device;
 pub mod generate;
 pub mod parser;
+pub mod sequences;
 
 use anyhow::{ensure, Context, Result};
 use clap::Parser;
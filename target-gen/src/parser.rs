@@ -90,6 +90,16 @@ pub fn extract_flash_algo(
             "Init" => algo.pc_init = Some(sym.st_value - code_section_offset as u64),
             "UnInit" => algo.pc_uninit = Some(sym.st_value - code_section_offset as u64),
             "EraseChip" => algo.pc_erase_all = Some(sym.st_value - code_section_offset as u64),
+            "Verify" => algo.pc_verify = Some(sym.st_value - code_section_offset as u64),
+            "BlankCheck" => algo.pc_blank_check = Some(sym.st_value - code_section_offset as u64),
+            "ReadOptionBytes" => {
+                algo.pc_read_option_bytes = Some(sym.st_value - code_section_offset as u64)
+            }
+            "WriteOptionBytes" => {
+                algo.pc_write_option_bytes = Some(sym.st_value - code_section_offset as u64)
+            }
+            "Protect" => algo.pc_protect = Some(sym.st_value - code_section_offset as u64),
+            "Unprotect" => algo.pc_unprotect = Some(sym.st_value - code_section_offset as u64),
             "EraseSector" => algo.pc_erase_sector = sym.st_value - code_section_offset as u64,
             "ProgramPage" => algo.pc_program_page = sym.st_value - code_section_offset as u64,
             "_SEGGER_RTT" => {
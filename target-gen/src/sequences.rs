@@ -0,0 +1,239 @@
+//! Extraction of the debug sequences from a `.pdsc` file.
+//!
+//! The `cmsis-pack` crate doesn't parse the `<sequences>` and `<debugvars>` elements,
+//! so they are read from the XML directly.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use probe_rs_target::{
+    PackSequence, PackSequenceBlock, PackSequenceControl, PackSequenceElement, PackSequences,
+};
+use roxmltree::{Document, Node};
+
+/// Extract the debug sequences of every device in the `.pdsc` file, keyed by the device name.
+///
+/// Sequences defined on a family or sub-family apply to all of its devices, unless a device
+/// defines a sequence with the same name itself. Devices without any sequences are omitted.
+pub fn parse_sequences(pdsc: &str) -> Result<HashMap<String, PackSequences>> {
+    let document = Document::parse(pdsc).context("Failed to parse the .pdsc file as XML")?;
+
+    let mut devices = HashMap::new();
+
+    for node in document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("devices"))
+    {
+        for family in node.children().filter(|node| node.has_tag_name("family")) {
+            visit_device_node(family, &PackSequences::default(), &mut devices)?;
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Collect the sequences of a family, sub-family, device or variant element,
+/// and pass them on to the nested elements.
+fn visit_device_node(
+    node: Node,
+    inherited: &PackSequences,
+    devices: &mut HashMap<String, PackSequences>,
+) -> Result<()> {
+    let mut sequences = inherited.clone();
+
+    for child in node.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "sequences" => {
+                for element in child
+                    .children()
+                    .filter(|node| node.has_tag_name("sequence"))
+                {
+                    let sequence = parse_sequence(element)?;
+
+                    // More specific sequences replace the inherited ones.
+                    sequences
+                        .sequences
+                        .retain(|s| s.name != sequence.name || s.pname != sequence.pname);
+                    sequences.sequences.push(sequence);
+                }
+            }
+            "debugvars" => sequences.debug_vars = Some(code(child)),
+            _ => {}
+        }
+    }
+
+    // The nested elements may appear before the sequences of this element.
+    for child in node.children().filter(|node| {
+        node.has_tag_name("subFamily")
+            || node.has_tag_name("device")
+            || node.has_tag_name("variant")
+    }) {
+        visit_device_node(child, &sequences, devices)?;
+    }
+
+    let name = match node.tag_name().name() {
+        "device" => node.attribute("Dname"),
+        "variant" => node.attribute("Dvariant"),
+        _ => None,
+    };
+
+    if let Some(name) = name {
+        if sequences != PackSequences::default() {
+            devices.insert(name.to_string(), sequences);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_sequence(node: Node) -> Result<PackSequence> {
+    let name = node
+        .attribute("name")
+        .ok_or_else(|| anyhow!("Sequence without a name in line {}", line(node)))?;
+
+    Ok(PackSequence {
+        name: name.to_string(),
+        pname: node.attribute("Pname").map(String::from),
+        disable: flag(node, "disable"),
+        body: parse_elements(node)
+            .with_context(|| format!("Failed to parse the debug sequence '{name}'"))?,
+    })
+}
+
+fn parse_elements(node: Node) -> Result<Vec<PackSequenceElement>> {
+    let mut elements = Vec::new();
+
+    for child in node.children().filter(Node::is_element) {
+        let element = match child.tag_name().name() {
+            "block" => PackSequenceElement::Block(PackSequenceBlock {
+                atomic: flag(child, "atomic"),
+                code: code(child),
+            }),
+            "control" => PackSequenceElement::Control(PackSequenceControl {
+                if_condition: child.attribute("if").map(String::from),
+                while_condition: child.attribute("while").map(String::from),
+                timeout: child
+                    .attribute("timeout")
+                    .map(|timeout| {
+                        parse_int::parse(timeout).map_err(|e| {
+                            anyhow!("Invalid timeout '{timeout}' in line {}: {e}", line(child))
+                        })
+                    })
+                    .transpose()?,
+                body: parse_elements(child)?,
+            }),
+            other => {
+                log::warn!(
+                    "Ignoring unknown element <{}> in debug sequence in line {}",
+                    other,
+                    line(child)
+                );
+                continue;
+            }
+        };
+
+        elements.push(element);
+    }
+
+    Ok(elements)
+}
+
+/// The code inside an element, without the indentation of the XML file.
+fn code(node: Node) -> String {
+    let text = node
+        .children()
+        .filter_map(|node| node.text())
+        .collect::<String>();
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn flag(node: Node, attribute: &str) -> bool {
+    matches!(node.attribute(attribute), Some("1" | "true"))
+}
+
+fn line(node: Node) -> u32 {
+    node.document().text_pos_at(node.range().start).row
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PDSC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package schemaVersion="1.7.7">
+  <devices>
+    <family Dfamily="Example Series" Dvendor="Example:0">
+      <debugvars configfile="Debug.dbgconf">
+        __var Unlock = 0;
+      </debugvars>
+      <sequences>
+        <sequence name="ResetSystem">
+          <block>
+            Write32(0xE000ED0C, 0x05FA0004);
+          </block>
+        </sequence>
+        <sequence name="DebugCoreStop" disable="1"/>
+      </sequences>
+      <device Dname="EX100">
+        <sequences>
+          <sequence name="ResetSystem">
+            <block atomic="1">Write32(0x40000000, 1);</block>
+            <control while="(Read32(0x40000004) &amp; 1) == 0" timeout="0x1000">
+              <block>DAP_Delay(100);</block>
+            </control>
+          </sequence>
+        </sequences>
+      </device>
+      <subFamily DsubFamily="EX2">
+        <device Dname="EX200">
+          <variant Dvariant="EX200A"/>
+        </device>
+      </subFamily>
+    </family>
+  </devices>
+</package>
+"#;
+
+    #[test]
+    fn inherit_sequences() {
+        let devices = parse_sequences(PDSC).unwrap();
+
+        let ex100 = &devices["EX100"];
+        assert_eq!(ex100.debug_vars.as_deref(), Some("__var Unlock = 0;"));
+        assert_eq!(ex100.sequences.len(), 2);
+        assert!(ex100.get("DebugCoreStop").unwrap().disable);
+        assert_eq!(
+            ex100.get("ResetSystem").unwrap().body,
+            vec![
+                PackSequenceElement::Block(PackSequenceBlock {
+                    atomic: true,
+                    code: "Write32(0x40000000, 1);".to_string(),
+                }),
+                PackSequenceElement::Control(PackSequenceControl {
+                    if_condition: None,
+                    while_condition: Some("(Read32(0x40000004) & 1) == 0".to_string()),
+                    timeout: Some(0x1000),
+                    body: vec![PackSequenceElement::Block(PackSequenceBlock {
+                        atomic: false,
+                        code: "DAP_Delay(100);".to_string(),
+                    })],
+                }),
+            ]
+        );
+
+        let ex200a = &devices["EX200A"];
+        assert_eq!(
+            ex200a.get("ResetSystem").unwrap().body,
+            vec![PackSequenceElement::Block(PackSequenceBlock {
+                atomic: false,
+                code: "Write32(0xE000ED0C, 0x05FA0004);".to_string(),
+            })]
+        );
+    }
+}